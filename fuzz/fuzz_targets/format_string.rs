@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use starship::formatter::StringFormatter;
+
+fuzz_target!(|format: &str| {
+    if let Ok(formatter) = StringFormatter::new(format) {
+        let _ = formatter
+            .map(|variable| Some(Ok(variable.to_string())))
+            .parse(None);
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use starship::fuzzing::parse_porcelain_output;
+
+fuzz_target!(|input: (bool, bool, &str)| {
+    let (porcelain_v2, nul_separated, output) = input;
+    parse_porcelain_output(output, porcelain_v2, nul_separated);
+});
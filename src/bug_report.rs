@@ -1,3 +1,5 @@
+use crate::config::StarshipConfig;
+use crate::network;
 use crate::shadow;
 use crate::utils::exec_cmd;
 
@@ -5,7 +7,6 @@ use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
 
-#[cfg(feature = "http")]
 const GIT_IO_BASE_URL: &str = "https://git.io/";
 
 pub fn create() {
@@ -21,7 +22,14 @@ pub fn create() {
     };
 
     let link = make_github_issue_link(environment);
-    let short_link = shorten_link(&link);
+    let config = StarshipConfig::initialize();
+    let short_link = network::post_form(
+        &config.get_root_config(),
+        "shortening the issue link",
+        &format!("{}{}", GIT_IO_BASE_URL, "create"),
+        &[("url", &link)],
+    )
+    .map(|slug| format!("{}{}", GIT_IO_BASE_URL, slug));
 
     if open::that(&link)
         .map(|status| status.success())
@@ -36,21 +44,6 @@ pub fn create() {
     println!(" {}", short_link.unwrap_or(link));
 }
 
-#[cfg(feature = "http")]
-fn shorten_link(link: &str) -> Option<String> {
-    attohttpc::post(&format!("{}{}", GIT_IO_BASE_URL, "create"))
-        .form(&[("url", link)])
-        .ok()
-        .and_then(|r| r.send().ok())
-        .and_then(|r| r.text().ok())
-        .map(|slug| format!("{}{}", GIT_IO_BASE_URL, slug))
-}
-
-#[cfg(not(feature = "http"))]
-fn shorten_link(_url: &str) -> Option<String> {
-    None
-}
-
 const UNKNOWN_SHELL: &str = "<unknown shell>";
 const UNKNOWN_TERMINAL: &str = "<unknown terminal>";
 const UNKNOWN_VERSION: &str = "<unknown version>";
@@ -153,9 +146,16 @@ fn get_shell_info() -> ShellInfo {
 
     let shell = shell.unwrap();
 
-    let version = exec_cmd(&shell, &["--version"], Duration::from_millis(500))
-        .map(|output| output.stdout.trim().to_string())
-        .unwrap_or_else(|| UNKNOWN_VERSION.to_string());
+    let version = exec_cmd(
+        &shell,
+        &["--version"],
+        &[],
+        None,
+        Duration::from_millis(500),
+        false,
+    )
+    .map(|output| output.stdout.trim().to_string())
+    .unwrap_or_else(|| UNKNOWN_VERSION.to_string());
 
     let config = get_config_path(&shell)
         .and_then(|config_path| fs::read_to_string(config_path).ok())
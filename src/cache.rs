@@ -0,0 +1,867 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::RootModuleConfig;
+use crate::configs::cmd_duration::CmdDurationConfig;
+use crate::context::{Context, PROPERTY_ENV_FALLBACKS};
+use crate::utils;
+
+/// Returns the directory starship uses for its session files (logs, caches).
+/// Defaults to `~/.cache/starship` and can be overridden with `$STARSHIP_CACHE`.
+pub fn cache_dir(context: &Context) -> Option<PathBuf> {
+    context
+        .get_env_os("STARSHIP_CACHE")
+        .map(PathBuf::from)
+        .or_else(|| Some(context.get_home()?.join(".cache/starship")))
+}
+
+/// The file of the cache directory whose modification time is when it was last collected
+const GC_MARKER: &str = "gc.marker";
+
+/// How often the cache directory is collected when a prompt starts
+const GC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Collects the cache directory with `gc` if it wasn't in the last hour and `cache_ttl_days`
+/// isn't 0. Meant to run when a prompt starts.
+pub fn gc_if_due(context: &Context) {
+    let dir = match cache_dir(context) {
+        Some(dir) => dir,
+        None => return,
+    };
+    let marker = dir.join(GC_MARKER);
+    let now = SystemTime::now();
+    let due = match fs::metadata(&marker).and_then(|metadata| metadata.modified()) {
+        // A marker from the future means the clock changed, so it can't be trusted either
+        Ok(collected) => match now.duration_since(collected) {
+            Ok(age) => age >= GC_INTERVAL,
+            Err(_) => true,
+        },
+        Err(_) => true,
+    };
+    if !due {
+        return;
+    }
+
+    // Prompts starting at the same time should leave the collection to the first one
+    if let Err(error) = utils::write_file_atomic(&marker, "") {
+        log::debug!("Unable to write {:?}: {}", marker, error);
+        return;
+    }
+    run_gc(context);
+}
+
+/// Removes the stale session files of the cache directory, see `gc`, unless `cache_ttl_days`
+/// is 0 or less. Returns the paths removed.
+pub fn run_gc(context: &Context) -> Vec<PathBuf> {
+    let ttl_days = context.config.get_root_config().cache_ttl_days;
+    let dir = match cache_dir(context) {
+        Some(dir) if ttl_days > 0 => dir,
+        _ => return Vec::new(),
+    };
+    let ttl = Duration::from_secs(ttl_days as u64 * 24 * 60 * 60);
+    let session_key = context
+        .get_env("STARSHIP_SESSION_KEY")
+        .filter(|key| !key.is_empty());
+
+    let removed = gc(&dir, SystemTime::now(), ttl, session_key.as_deref());
+    log::debug!("Removed {} stale files from {:?}", removed.len(), dir);
+    removed
+}
+
+/// Removes the session files of `dir` that weren't modified in the `ttl` before `now`. Whether
+/// the shell of a session still runs can't be told, so files are only removed by age, and the
+/// files of the `current_session` are kept.
+///
+/// Only the files starship writes for sessions are removed: the caches of prompts and
+/// samples (`*_<session>.json`), logs (`session_<session>.log`) and the temporary files of
+/// interrupted writes. Other files, like a config file, are never touched. Files that other
+/// prompts remove at the same time are skipped.
+pub fn gc(
+    dir: &Path,
+    now: SystemTime,
+    ttl: Duration,
+    current_session: Option<&str>,
+) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            log::debug!("Unable to read the cache directory {:?}: {}", dir, error);
+            return Vec::new();
+        }
+    };
+    let current_session = current_session.map(|key| format!("_{}.", key));
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            is_session_file(&name)
+                && !matches!(&current_session, Some(current) if name.contains(current.as_str()))
+        })
+        .filter(|entry| {
+            // `DirEntry::metadata` doesn't follow symlinks, which are never removed
+            let modified = entry
+                .metadata()
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .and_then(|metadata| metadata.modified().ok());
+            matches!(modified.map(|modified| now.duration_since(modified)), Some(Ok(age)) if age > ttl)
+        })
+        .map(|entry| entry.path())
+        .filter(|path| match fs::remove_file(path) {
+            Ok(()) => true,
+            Err(error) if error.kind() == ErrorKind::NotFound => false,
+            Err(error) => {
+                log::debug!("Unable to remove {:?}: {}", path, error);
+                false
+            }
+        })
+        .collect()
+}
+
+/// Whether `name` is the name of a file starship writes for a session
+fn is_session_file(name: &str) -> bool {
+    let temporary = name.starts_with('.') && name.ends_with(".tmp");
+    let cache = name.ends_with(".json") && name.contains('_');
+    let log = matches!(
+        name.strip_prefix("session_").and_then(|name| name.strip_suffix(".log")),
+        Some(key) if is_session_key(key)
+    );
+    temporary || cache || log
+}
+
+/// Whether `key` looks like a `STARSHIP_SESSION_KEY`. The log of prompts without one is
+/// `session_.log`.
+fn is_session_key(key: &str) -> bool {
+    key.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Everything the previous render depended on. If any of these changed, the prompt has to be
+/// computed again.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PromptInputs {
+    current_dir: PathBuf,
+    logical_dir: PathBuf,
+    shell: String,
+    properties: BTreeMap<String, String>,
+    config_hash: u64,
+    /// Whether the prompt was rendered with `compact_format`, which depends on the terminal width
+    #[serde(default)]
+    compact: bool,
+    git: Option<GitFreshness>,
+}
+
+/// Cheap to compute snapshot of the state of a git repository, based on the files git
+/// rewrites whenever HEAD, the index or an ongoing merge change.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct GitFreshness {
+    head: Option<String>,
+    head_ref: Option<String>,
+    index: Option<FileStamp>,
+    merge_head: Option<FileStamp>,
+    packed_refs: Option<FileStamp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileStamp {
+    modified: u128,
+    len: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedPrompt {
+    inputs: PromptInputs,
+    rendered_at: u128,
+    prompt: String,
+}
+
+/// Cache of the last rendered prompt of a session, used to skip evaluating modules when
+/// nothing that could change the prompt has changed since the last render.
+pub struct PromptCache {
+    path: PathBuf,
+    max_age: u128,
+    inputs: PromptInputs,
+    /// Whether changes to the git index should be ignored, because git is rewriting it
+    ignore_index: bool,
+}
+
+impl PromptCache {
+    /// Returns `None` if caching is disabled (`cache_max_age_ms = 0`) or if there is no
+    /// session to key the cache by.
+    pub fn new(context: &Context) -> Option<Self> {
+        let config = context.config.get_root_config();
+        let max_age = config.cache_max_age_ms;
+        if max_age == 0 {
+            return None;
+        }
+
+        let session_key = context
+            .get_env("STARSHIP_SESSION_KEY")
+            .filter(|key| !key.is_empty())?;
+        let path = cache_dir(context)?.join(format!("prompt_{}.json", session_key));
+
+        let git_dir = find_git_dir(&context.current_dir);
+        let index_locked = match &git_dir {
+            Some(git_dir) => git_dir.join("index.lock").exists(),
+            None => false,
+        };
+        let ignore_index = config.cache_while_index_locked && index_locked;
+
+        Some(Self {
+            path,
+            max_age: u128::from(max_age),
+            inputs: PromptInputs::from_context(context, git_dir),
+            ignore_index,
+        })
+    }
+
+    /// Returns the cached prompt if it was rendered for the same inputs and isn't too old.
+    pub fn load(&self) -> Option<String> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        let mut cached: CachedPrompt = match serde_json::from_str(&content) {
+            Ok(cached) => cached,
+            Err(error) => {
+                log::debug!("Removing unreadable prompt cache: {}", error);
+                let _ = fs::remove_file(&self.path);
+                return None;
+            }
+        };
+
+        let age = now_millis().checked_sub(cached.rendered_at)?;
+        if age >= self.max_age {
+            log::trace!("Prompt cache is outdated ({}ms old)", age);
+            return None;
+        }
+        if self.ignore_index {
+            log::trace!("Git index is locked, ignoring changes to it");
+            if let (Some(cached_git), Some(git)) = (&mut cached.inputs.git, &self.inputs.git) {
+                cached_git.index = git.index.clone();
+            }
+        }
+        if cached.inputs != self.inputs {
+            log::trace!("Prompt cache inputs changed");
+            return None;
+        }
+
+        log::trace!("Using cached prompt ({}ms old)", age);
+        Some(cached.prompt)
+    }
+
+    /// Stores the prompt rendered for the inputs of this render.
+    pub fn store(self, prompt: &str) {
+        let Self { path, inputs, .. } = self;
+        let cached = CachedPrompt {
+            inputs,
+            rendered_at: now_millis(),
+            prompt: prompt.to_owned(),
+        };
+
+        let result = serde_json::to_string(&cached)
+            .map_err(std::io::Error::from)
+            .and_then(|content| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                utils::write_file_atomic(&path, content)
+            });
+        if let Err(error) = result {
+            log::debug!("Unable to write prompt cache {:?}: {}", path, error);
+        }
+    }
+}
+
+/// A value probed by a module, e.g. the battery level, with when and during which boot it
+/// was probed
+#[derive(Serialize, Deserialize)]
+struct Sample<T> {
+    sampled_at: u128,
+    boot_id: Option<String>,
+    /// What the value was probed for, like a directory, if it depends on more than the session
+    #[serde(default)]
+    key: Option<String>,
+    value: T,
+}
+
+/// Returns the value measured by `probe`, reusing the value cached for this module and
+/// session while it's younger than `refresh_interval` milliseconds.
+///
+/// `probe` runs on every render if `refresh_interval` is 0 or if there is no session to key
+/// the cache by.
+pub fn sampled<T, F>(context: &Context, module: &str, refresh_interval: u64, probe: F) -> Option<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Option<T>,
+{
+    sampled_for(context, module, None, refresh_interval, probe)
+}
+
+/// Like `sampled`, for values that also depend on `key`, e.g. the current directory. The cached
+/// value is only reused while it was probed for the same key.
+pub fn sampled_for<T, F>(
+    context: &Context,
+    module: &str,
+    key: Option<&str>,
+    refresh_interval: u64,
+    probe: F,
+) -> Option<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Option<T>,
+{
+    let session_key = context
+        .get_env("STARSHIP_SESSION_KEY")
+        .filter(|key| !key.is_empty() && refresh_interval > 0);
+    let path = match (session_key, cache_dir(context)) {
+        (Some(session_key), Some(dir)) => dir.join(format!("{}_{}.json", module, session_key)),
+        _ => return probe(),
+    };
+
+    SampleCache {
+        path,
+        refresh_interval: u128::from(refresh_interval),
+        boot_id: boot_id(),
+        key: key.map(str::to_owned),
+    }
+    .get(now_millis(), probe)
+}
+
+struct SampleCache {
+    path: PathBuf,
+    refresh_interval: u128,
+    boot_id: Option<String>,
+    key: Option<String>,
+}
+
+impl SampleCache {
+    fn get<T, F>(&self, now: u128, probe: F) -> Option<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Option<T>,
+    {
+        if let Some(value) = self.load(now) {
+            return Some(value);
+        }
+
+        let value = probe()?;
+        self.store(now, &value);
+        Some(value)
+    }
+
+    fn load<T: DeserializeOwned>(&self, now: u128) -> Option<T> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        let sample: Sample<T> = match serde_json::from_str(&content) {
+            Ok(sample) => sample,
+            Err(error) => {
+                log::debug!("Removing unreadable sample {:?}: {}", self.path, error);
+                let _ = fs::remove_file(&self.path);
+                return None;
+            }
+        };
+
+        if sample.boot_id != self.boot_id {
+            log::trace!("Sample {:?} is from another boot", self.path);
+            return None;
+        }
+        if sample.key != self.key {
+            log::trace!("Sample {:?} is for {:?}", self.path, sample.key);
+            return None;
+        }
+        let age = now.checked_sub(sample.sampled_at)?;
+        if age >= self.refresh_interval {
+            log::trace!("Sample {:?} is outdated ({}ms old)", self.path, age);
+            return None;
+        }
+
+        Some(sample.value)
+    }
+
+    fn store<T: Serialize>(&self, now: u128, value: &T) {
+        let sample = Sample {
+            sampled_at: now,
+            boot_id: self.boot_id.clone(),
+            key: self.key.clone(),
+            value,
+        };
+
+        let result = serde_json::to_string(&sample)
+            .map_err(std::io::Error::from)
+            .and_then(|content| {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                utils::write_file_atomic(&self.path, content)
+            });
+        if let Err(error) = result {
+            log::debug!("Unable to write sample {:?}: {}", self.path, error);
+        }
+    }
+}
+
+/// Identifies the current boot, so that samples don't survive a reboot
+#[cfg(target_os = "linux")]
+fn boot_id() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/random/boot_id")
+        .ok()
+        .map(|id| id.trim().to_owned())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn boot_id() -> Option<String> {
+    None
+}
+
+impl PromptInputs {
+    fn from_context(context: &Context, git_dir: Option<PathBuf>) -> Self {
+        let mut properties: BTreeMap<String, String> = context
+            .properties
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        // The arguments that weren't passed may come from the environment instead
+        for (name, _) in PROPERTY_ENV_FALLBACKS {
+            if let Some(value) = context.get_property(name) {
+                properties.insert(name.to_string(), value);
+            }
+        }
+
+        // Durations below `min_time` render the same prompt, so they share a bucket
+        if let Some(duration) = context.get_cmd_duration() {
+            let config =
+                CmdDurationConfig::try_load(context.config.get_module_config("cmd_duration"));
+            let bucket = if duration < config.min_time as u128 {
+                String::from("short")
+            } else {
+                duration.to_string()
+            };
+            properties.insert(String::from("cmd_duration"), bucket);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        if let Some(config) = &context.config.config {
+            config.to_string().hash(&mut hasher);
+        }
+
+        Self {
+            current_dir: context.current_dir.clone(),
+            logical_dir: context.logical_dir.clone(),
+            shell: format!("{:?}", context.shell),
+            properties,
+            config_hash: hasher.finish(),
+            compact: context.use_compact_format(),
+            git: git_dir.map(|git_dir| GitFreshness::new(&git_dir)),
+        }
+    }
+}
+
+impl GitFreshness {
+    fn new(git_dir: &Path) -> Self {
+        let head = utils::read_file_with_retry(git_dir.join("HEAD")).ok();
+        let head_ref = head
+            .as_deref()
+            .and_then(|head| head.trim().strip_prefix("ref: "))
+            .and_then(|reference| fs::read_to_string(git_dir.join(reference)).ok());
+
+        Self {
+            head,
+            head_ref,
+            index: FileStamp::new(&git_dir.join("index")),
+            merge_head: FileStamp::new(&git_dir.join("MERGE_HEAD")),
+            packed_refs: FileStamp::new(&git_dir.join("packed-refs")),
+        }
+    }
+}
+
+impl FileStamp {
+    fn new(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+
+        Some(Self {
+            modified,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// Finds the git directory of the repository containing `path` by looking for a `.git`
+/// directory (or a `.git` file pointing to one) in `path` and its ancestors.
+pub(crate) fn find_git_dir(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+
+        let content = fs::read_to_string(&dot_git).ok()?;
+        let git_dir = PathBuf::from(content.trim().strip_prefix("gitdir: ")?);
+        Some(if git_dir.is_relative() {
+            dir.join(git_dir)
+        } else {
+            git_dir
+        })
+    })
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use crate::context::Shell;
+    use crate::test::{fixture_repo, FixtureProvider};
+    use std::io;
+
+    fn context_for<'a>(path: &Path, cache: &Path, status: &str) -> Context<'a> {
+        let mut context = Context::new_with_shell_and_path(
+            clap::ArgMatches::default(),
+            Shell::Unknown,
+            path.to_path_buf(),
+            path.to_path_buf(),
+        );
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                cache_max_age_ms = 60_000
+            }),
+        };
+        context
+            .env
+            .insert("STARSHIP_CACHE", cache.to_string_lossy().to_string());
+        context.env.insert("STARSHIP_SESSION_KEY", "test".into());
+        context.properties.insert("status_code", status.into());
+        context
+    }
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = context_for(dir.path(), dir.path(), "0");
+        context.config = StarshipConfig { config: None };
+
+        assert!(PromptCache::new(&context).is_none());
+        dir.close()
+    }
+
+    #[test]
+    fn hit_with_same_inputs() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let cache_dir = tempfile::tempdir()?;
+
+        let context = context_for(repo_dir.path(), cache_dir.path(), "0");
+        let cache = PromptCache::new(&context).unwrap();
+        assert_eq!(cache.load(), None);
+        cache.store("cached prompt");
+
+        let context = context_for(repo_dir.path(), cache_dir.path(), "0");
+        let cache = PromptCache::new(&context).unwrap();
+        assert_eq!(cache.load(), Some(String::from("cached prompt")));
+
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn miss_on_status_change() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let cache_dir = tempfile::tempdir()?;
+
+        let context = context_for(repo_dir.path(), cache_dir.path(), "0");
+        PromptCache::new(&context).unwrap().store("cached prompt");
+
+        let context = context_for(repo_dir.path(), cache_dir.path(), "1");
+        assert_eq!(PromptCache::new(&context).unwrap().load(), None);
+
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn corrupt_cache_removed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_for(dir.path(), dir.path(), "0");
+        let cache = PromptCache::new(&context).unwrap();
+        fs::write(&cache.path, "{\"inputs\": {\"current_dir")?;
+
+        assert_eq!(cache.load(), None);
+        assert!(!cache.path.exists());
+        cache.store("cached prompt");
+
+        let cache = PromptCache::new(&context).unwrap();
+        assert_eq!(cache.load(), Some(String::from("cached prompt")));
+        dir.close()
+    }
+
+    #[test]
+    fn miss_on_index_change() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let cache_dir = tempfile::tempdir()?;
+
+        let context = context_for(repo_dir.path(), cache_dir.path(), "0");
+        PromptCache::new(&context).unwrap().store("cached prompt");
+
+        fs::write(repo_dir.path().join("new_file"), "")?;
+        std::process::Command::new("git")
+            .args(["add", "new_file"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let context = context_for(repo_dir.path(), cache_dir.path(), "0");
+        assert_eq!(PromptCache::new(&context).unwrap().load(), None);
+
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn short_durations_share_a_bucket() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let mut context = context_for(dir.path(), dir.path(), "0");
+        context.properties.insert("cmd_duration", "12".into());
+        PromptCache::new(&context).unwrap().store("cached prompt");
+
+        let mut context = context_for(dir.path(), dir.path(), "0");
+        context.properties.insert("cmd_duration", "345".into());
+        let cache = PromptCache::new(&context).unwrap();
+        assert_eq!(cache.load(), Some(String::from("cached prompt")));
+
+        let mut context = context_for(dir.path(), dir.path(), "0");
+        context.properties.insert("cmd_duration", "5000".into());
+        assert_eq!(PromptCache::new(&context).unwrap().load(), None);
+
+        dir.close()
+    }
+
+    #[test]
+    fn hit_on_index_change_while_locked() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let cache_dir = tempfile::tempdir()?;
+
+        let context = context_for(repo_dir.path(), cache_dir.path(), "0");
+        PromptCache::new(&context).unwrap().store("cached prompt");
+
+        fs::write(repo_dir.path().join("new_file"), "")?;
+        std::process::Command::new("git")
+            .args(["add", "new_file"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        fs::write(repo_dir.path().join(".git/index.lock"), "")?;
+
+        let context = context_for(repo_dir.path(), cache_dir.path(), "0");
+        let cache = PromptCache::new(&context).unwrap();
+        assert_eq!(cache.load(), Some(String::from("cached prompt")));
+
+        let mut context = context_for(repo_dir.path(), cache_dir.path(), "0");
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                cache_max_age_ms = 60_000
+                cache_while_index_locked = false
+            }),
+        };
+        assert_eq!(PromptCache::new(&context).unwrap().load(), None);
+
+        cache_dir.close()?;
+        repo_dir.close()
+    }
+
+    fn sample_cache(path: &Path, boot_id: &str) -> SampleCache {
+        SampleCache {
+            path: path.join("battery_test.json"),
+            refresh_interval: 30_000,
+            boot_id: Some(boot_id.to_owned()),
+            key: None,
+        }
+    }
+
+    #[test]
+    fn sample_probed_for_another_key() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let keyed = |key: &str| SampleCache {
+            key: Some(key.to_owned()),
+            ..sample_cache(dir.path(), "boot")
+        };
+
+        assert_eq!(keyed("/a").get(1_000, || Some(1)), Some(1));
+        assert_eq!(keyed("/a").get(2_000, || Some(2)), Some(1));
+        assert_eq!(keyed("/b").get(3_000, || Some(3)), Some(3));
+        assert_eq!(keyed("/a").get(4_000, || Some(4)), Some(4));
+        dir.close()
+    }
+
+    #[test]
+    fn sample_reused_within_refresh_interval() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = sample_cache(dir.path(), "boot");
+        let probes = std::cell::Cell::new(0);
+        let probe = || {
+            probes.set(probes.get() + 1);
+            Some(probes.get() * 10)
+        };
+
+        assert_eq!(cache.get(1_000, probe), Some(10));
+        assert_eq!(cache.get(30_999, probe), Some(10));
+        assert_eq!(probes.get(), 1);
+
+        assert_eq!(cache.get(31_000, probe), Some(20));
+        assert_eq!(cache.get(31_001, probe), Some(20));
+        assert_eq!(probes.get(), 2);
+        dir.close()
+    }
+
+    #[test]
+    fn sample_probed_when_corrupt() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache = sample_cache(dir.path(), "boot");
+        fs::write(&cache.path, "{\"sampled_at\": 0, \"val")?;
+
+        assert_eq!(cache.load::<u64>(1_000), None);
+        assert!(!cache.path.exists());
+        assert_eq!(cache.get(1_000, || Some(42)), Some(42));
+        assert_eq!(cache.get(2_000, || Some(0)), Some(42));
+        dir.close()
+    }
+
+    #[test]
+    fn sample_probed_after_reboot() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert_eq!(
+            sample_cache(dir.path(), "a").get(1_000, || Some(1)),
+            Some(1)
+        );
+        assert_eq!(
+            sample_cache(dir.path(), "b").get(2_000, || Some(2)),
+            Some(2)
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn sample_not_cached_without_session() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = context_for(dir.path(), dir.path(), "0");
+        context.env.remove("STARSHIP_SESSION_KEY");
+
+        assert_eq!(sampled(&context, "battery", 30_000, || Some(1)), Some(1));
+        assert_eq!(sampled(&context, "battery", 30_000, || Some(2)), Some(2));
+        assert_eq!(fs::read_dir(dir.path())?.count(), 0);
+        dir.close()
+    }
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Creates the file `name` in `dir`, last modified `age` ago
+    fn cache_file(dir: &Path, name: &str, age: Duration) -> io::Result<PathBuf> {
+        let path = dir.join(name);
+        let file = fs::File::create(&path)?;
+        file.set_modified(SystemTime::now() - age)?;
+        Ok(path)
+    }
+
+    #[test]
+    fn gc_removes_stale_session_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let stale = [
+            cache_file(dir.path(), "prompt_old.json", 8 * DAY)?,
+            cache_file(dir.path(), "git_status_old.json", 8 * DAY)?,
+            cache_file(dir.path(), "session_old.log", 30 * DAY)?,
+            cache_file(dir.path(), ".prompt_old.json.1234.tmp", 8 * DAY)?,
+        ];
+        let kept = [
+            cache_file(dir.path(), "prompt_new.json", DAY)?,
+            cache_file(dir.path(), "session_new.log", Duration::default())?,
+            // The files of the current session are kept however old they are
+            cache_file(dir.path(), "prompt_current.json", 8 * DAY)?,
+            cache_file(dir.path(), "session_current.log", 8 * DAY)?,
+            // Files starship doesn't write for sessions are never removed
+            cache_file(dir.path(), "starship.toml", 30 * DAY)?,
+            cache_file(dir.path(), "notes.txt", 30 * DAY)?,
+            cache_file(dir.path(), "config.json", 30 * DAY)?,
+            cache_file(dir.path(), "session_notes.backup.log", 30 * DAY)?,
+            cache_file(dir.path(), "my_session_old.log", 30 * DAY)?,
+        ];
+        fs::create_dir(dir.path().join("dir_old.json"))?;
+
+        let mut removed = gc(dir.path(), SystemTime::now(), 7 * DAY, Some("current"));
+        removed.sort();
+        let mut expected = stale.to_vec();
+        expected.sort();
+        assert_eq!(removed, expected);
+
+        assert!(stale.iter().all(|path| !path.exists()));
+        assert!(kept.iter().all(|path| path.exists()));
+        assert!(dir.path().join("dir_old.json").is_dir());
+        dir.close()
+    }
+
+    #[test]
+    fn gc_races_other_prompts() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        for i in 0..200 {
+            cache_file(dir.path(), &format!("prompt_{}.json", i), 8 * DAY)?;
+        }
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let dir = dir.path().to_owned();
+                std::thread::spawn(move || gc(&dir, SystemTime::now(), 7 * DAY, None).len())
+            })
+            .collect();
+        let removed: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+
+        assert_eq!(removed, 200);
+        assert_eq!(fs::read_dir(dir.path())?.count(), 0);
+        dir.close()
+    }
+
+    #[test]
+    fn gc_at_most_once_per_interval() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_for(dir.path(), dir.path(), "0");
+
+        let first = cache_file(dir.path(), "prompt_old.json", 8 * DAY)?;
+        gc_if_due(&context);
+        assert!(!first.exists());
+        assert!(dir.path().join(GC_MARKER).exists());
+
+        let second = cache_file(dir.path(), "prompt_older.json", 8 * DAY)?;
+        gc_if_due(&context);
+        assert!(second.exists());
+
+        cache_file(dir.path(), GC_MARKER, GC_INTERVAL)?;
+        gc_if_due(&context);
+        assert!(!second.exists());
+        dir.close()
+    }
+
+    #[test]
+    fn gc_disabled_without_ttl() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = context_for(dir.path(), dir.path(), "0");
+        context.config = StarshipConfig {
+            config: Some(toml::toml! { cache_ttl_days = 0 }),
+        };
+
+        let file = cache_file(dir.path(), "prompt_old.json", 8 * DAY)?;
+        assert!(run_gc(&context).is_empty());
+        assert!(file.exists());
+        dir.close()
+    }
+}
@@ -1,17 +1,99 @@
-use crate::configs::StarshipRootConfig;
+use crate::configs::{FullConfig, StarshipRootConfig};
+use crate::error::StarshipError;
+use crate::logger;
+use crate::module::ALL_MODULES;
+use crate::print::UnicodeWidthGraphemes;
 use crate::utils;
 use ansi_term::{Color, Style};
 use indexmap::IndexMap;
+use once_cell::sync::Lazy;
 use serde::Serialize;
 
+use std::cell::RefCell;
 use std::clone::Clone;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::marker::Sized;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
 
 use std::env;
+use toml::value::Table;
 use toml::Value;
 
+/// The defaults of the prompt and every module, as the table `FullConfig` serializes to
+pub static DEFAULT_CONFIG: Lazy<Table> =
+    Lazy::new(|| match Value::try_from(FullConfig::default()) {
+        Ok(Value::Table(defaults)) => defaults,
+        _ => Table::new(),
+    });
+
+/// The prefix of a reference to a named style of the `[styles]` table, e.g. `$styles.vcs`
+const NAMED_STYLE_PREFIX: &str = "$styles.";
+
+thread_local! {
+    /// The unknown keys reported on this thread, while `collect_unknown_keys` collects them
+    static UNKNOWN_KEYS: RefCell<Option<Vec<StarshipError>>> = const { RefCell::new(None) };
+}
+
+/// Warns about a config key that isn't one of `fields`, suggesting the closest field if any.
+pub fn warn_unknown_key(unknown: &str, fields: &[&str]) {
+    let error = StarshipError::UnknownKey {
+        key: unknown.to_owned(),
+        suggestion: did_you_mean(unknown, fields).map(str::to_owned),
+    };
+    let collected = UNKNOWN_KEYS.with(|keys| match keys.borrow_mut().as_mut() {
+        Some(keys) => {
+            keys.push(error.clone());
+            true
+        }
+        None => false,
+    });
+    if !collected {
+        error.log();
+    }
+}
+
+/// Calls `load`, returning the unknown keys it reports with `warn_unknown_key` on this thread
+/// instead of logging them
+pub fn collect_unknown_keys(load: impl FnOnce()) -> Vec<StarshipError> {
+    let previous = UNKNOWN_KEYS.with(|keys| keys.replace(Some(Vec::new())));
+    let _restore = CollectingUnknownKeys(previous);
+    load();
+    UNKNOWN_KEYS
+        .with(|keys| keys.borrow_mut().take())
+        .unwrap_or_default()
+}
+
+/// Restores the unknown keys collected on the thread when dropped, see `collect_unknown_keys`
+struct CollectingUnknownKeys(Option<Vec<StarshipError>>);
+
+impl Drop for CollectingUnknownKeys {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        UNKNOWN_KEYS.with(|keys| keys.replace(previous));
+    }
+}
+
+/// The field closest to an unknown key, if any is close enough to be a likely typo
+pub fn did_you_mean<'a>(unknown: &str, fields: &[&'a str]) -> Option<&'a str> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            let score = strsim::jaro_winkler(unknown, field);
+            if score > 0.8 {
+                Some((score, *field))
+            } else {
+                None
+            }
+        })
+        .max_by(|(score_a, _field_a), (score_b, _field_b)| {
+            score_a.partial_cmp(score_b).unwrap_or(Ordering::Equal)
+        })
+        .map(|(_score, field)| field)
+}
+
 /// Root config of a module.
 pub trait RootModuleConfig<'a>
 where
@@ -54,6 +136,36 @@ where
             let _ = std::mem::replace(self, value);
         }
     }
+
+    /// The keys accepted in the config table, in the order they are declared.
+    fn config_keys() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// The keys accepted in the config table of the field `key`.
+    fn field_config_keys(_key: &str) -> Option<&'static [&'static str]> {
+        None
+    }
+
+    /// The type and description of each of `config_keys`, in the same order.
+    fn config_docs() -> &'static [ConfigKeyDoc] {
+        &[]
+    }
+
+    /// The type and description of the keys accepted in the config table of the field `key`.
+    fn field_config_docs(_key: &str) -> Option<&'static [ConfigKeyDoc]> {
+        None
+    }
+}
+
+/// A config key as declared by a field of a config struct, for `explain-config`
+#[derive(Clone, Copy, Debug)]
+pub struct ConfigKeyDoc {
+    pub key: &'static str,
+    /// The Rust type of the field, as written in the struct
+    pub rust_type: &'static str,
+    /// The first paragraph of the doc comment of the field
+    pub description: &'static str,
 }
 
 // TODO: Add logging to default implementations
@@ -203,60 +315,325 @@ pub struct StarshipConfig {
     pub config: Option<Value>,
 }
 
+/// Why the configuration file couldn't be used
+#[derive(Debug, PartialEq)]
+pub enum ConfigFileError {
+    /// There is no configuration file
+    Missing,
+    /// The configuration file exists, but can't be read or parsed
+    Invalid,
+}
+
+/// The format of a configuration file, from its extension: `.json`, `.yaml` or `.yml`, and
+/// TOML otherwise
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub fn of_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        match extension.as_deref() {
+            Some("json") => ConfigFormat::Json,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Toml,
+        }
+    }
+
+    /// Parses a configuration in this format into the same TOML value a TOML file with the same
+    /// content would give. `null`s are left out, as TOML has no equivalent.
+    fn parse(self, content: &str) -> Result<Value, String> {
+        let config = match self {
+            ConfigFormat::Toml => {
+                return toml::from_str(content).map_err(|error| error.to_string())
+            }
+            ConfigFormat::Json => {
+                let json: serde_json::Value =
+                    serde_json::from_str(content).map_err(|error| error.to_string())?;
+                json_to_toml(json)
+            }
+            ConfigFormat::Yaml => {
+                let mut docs = yaml_rust::YamlLoader::load_from_str(content)
+                    .map_err(|error| error.to_string())?;
+                match docs.len() {
+                    0 => Some(Value::Table(Table::new())),
+                    1 => yaml_to_toml(docs.remove(0)),
+                    _ => return Err("expected a single YAML document".to_owned()),
+                }
+            }
+        };
+
+        match config {
+            Some(Value::Table(table)) => Ok(Value::Table(table)),
+            _ => Err("the configuration should be a table of keys".to_owned()),
+        }
+    }
+}
+
+fn json_to_toml(json: serde_json::Value) -> Option<Value> {
+    use serde_json::Value as Json;
+
+    Some(match json {
+        Json::Null => return None,
+        Json::Bool(value) => Value::Boolean(value),
+        Json::Number(number) => match number.as_i64() {
+            Some(integer) => Value::Integer(integer),
+            None => Value::Float(number.as_f64()?),
+        },
+        Json::String(value) => Value::String(value),
+        Json::Array(values) => Value::Array(values.into_iter().filter_map(json_to_toml).collect()),
+        Json::Object(entries) => Value::Table(
+            entries
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, json_to_toml(value)?)))
+                .collect(),
+        ),
+    })
+}
+
+fn yaml_to_toml(yaml: yaml_rust::Yaml) -> Option<Value> {
+    use yaml_rust::Yaml;
+
+    Some(match yaml {
+        Yaml::Null | Yaml::BadValue | Yaml::Alias(_) => return None,
+        Yaml::Boolean(value) => Value::Boolean(value),
+        Yaml::Integer(value) => Value::Integer(value),
+        Yaml::Real(value) => Value::Float(value.parse().ok()?),
+        Yaml::String(value) => Value::String(value),
+        Yaml::Array(values) => Value::Array(values.into_iter().filter_map(yaml_to_toml).collect()),
+        Yaml::Hash(entries) => Value::Table(
+            entries
+                .into_iter()
+                .filter_map(|(key, value)| {
+                    let key = match key {
+                        Yaml::String(key) => key,
+                        Yaml::Integer(key) => key.to_string(),
+                        Yaml::Boolean(key) => key.to_string(),
+                        _ => return None,
+                    };
+                    Some((key, yaml_to_toml(value)?))
+                })
+                .collect(),
+        ),
+    })
+}
+
+/// Merges the files matched by the `include` globs of `config` over it, in order, with the
+/// files matched by each glob sorted by path. Relative globs are relative to `base_dir`.
+///
+/// The `include` of the included files is ignored, so they can't include each other.
+fn merge_includes(config: &mut Value, base_dir: &Path) {
+    let patterns: Vec<String> = match config.get("include") {
+        Some(Value::Array(patterns)) => patterns
+            .iter()
+            .filter_map(|pattern| match pattern.as_str() {
+                Some(pattern) => Some(pattern.to_owned()),
+                None => {
+                    log::warn!("Ignoring `include` entry {}, it isn't a string", pattern);
+                    None
+                }
+            })
+            .collect(),
+        Some(Value::String(pattern)) => vec![pattern.to_owned()],
+        Some(value) => {
+            log::warn!("`include` should be a list of globs, got {}", value);
+            return;
+        }
+        None => return,
+    };
+
+    for pattern in patterns {
+        for path in include_paths(&pattern, base_dir) {
+            let file_path = path.to_string_lossy();
+            let mut included = match StarshipConfig::parse_file(&file_path) {
+                Ok(included) => included,
+                Err(_) => {
+                    log::warn!("Ignoring included config file {}", file_path);
+                    continue;
+                }
+            };
+            if let Some(table) = included.as_table_mut() {
+                if table.remove("include").is_some() {
+                    log::warn!(
+                        "Ignoring `include` in {}, included files can't include others",
+                        file_path
+                    );
+                }
+            }
+            log::debug!("Including config file {}", file_path);
+            merge_values(config, included);
+        }
+    }
+}
+
+/// The files matched by an `include` glob, sorted by path. A glob that matches nothing is fine,
+/// the files it's meant for may not exist on every machine.
+fn include_paths(pattern: &str, base_dir: &Path) -> Vec<PathBuf> {
+    // The directory the glob is relative to is escaped, as it may contain glob characters
+    let expanded = expand_config_path(pattern);
+    let expanded = if expanded.is_absolute() {
+        expanded
+    } else {
+        PathBuf::from(glob::Pattern::escape(&base_dir.to_string_lossy())).join(expanded)
+    };
+
+    let paths = match glob::glob(&expanded.to_string_lossy()) {
+        Ok(paths) => paths,
+        Err(error) => {
+            log::warn!("Invalid `include` glob {}: {}", pattern, error);
+            return Vec::new();
+        }
+    };
+    let mut paths: Vec<PathBuf> = paths
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .collect();
+    if paths.is_empty() {
+        log::debug!("The `include` glob {} matches no files", pattern);
+    }
+    paths.sort();
+    paths
+}
+
+/// Expands a path of the config while it's loaded, before there's a context
+fn expand_config_path(path: &str) -> PathBuf {
+    utils::expand_path_with(path, dirs_next::home_dir(), |name| env::var(name).ok())
+}
+
+/// Merges `overlay` into `base`: tables are merged key by key, and any other value replaces
+/// the one in `base`, including arrays.
+fn merge_values(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Table(base), Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_values(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// A name of `[module_aliases]` that renders a module with another config
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleAlias {
+    /// The module the alias renders
+    pub module: String,
+    /// The config of `module`, with the `config` of the alias merged over it
+    pub config: Value,
+}
+
 impl StarshipConfig {
     /// Initialize the Config struct
     pub fn initialize() -> Self {
-        if let Some(file_data) = Self::config_from_file() {
-            StarshipConfig {
-                config: Some(file_data),
+        Self::initialize_checked().0
+    }
+
+    /// Initialize the Config struct, also returning whether the configuration file is invalid
+    /// and the defaults are used instead
+    pub fn initialize_checked() -> (Self, bool) {
+        Self::from_file_result(Self::config_from_file())
+    }
+
+    /// Initialize the Config struct from the file at `file_path`, also returning whether it is
+    /// invalid and the defaults are used instead
+    pub fn initialize_from_path(file_path: &str) -> (Self, bool) {
+        Self::from_file_result(Self::config_from_path(file_path))
+    }
+
+    fn from_file_result(result: Result<Value, ConfigFileError>) -> (Self, bool) {
+        let (config, degraded) = match result {
+            Ok(mut file_data) => {
+                resolve_named_styles(&mut file_data);
+                crate::modules::warn_module_references(&file_data);
+                (file_data, false)
             }
-        } else {
+            Err(error) => (
+                Value::Table(toml::value::Table::new()),
+                error == ConfigFileError::Invalid,
+            ),
+        };
+
+        (
             StarshipConfig {
-                config: Some(Value::Table(toml::value::Table::new())),
-            }
-        }
+                config: Some(config),
+            },
+            degraded,
+        )
     }
 
     /// Create a config from a starship configuration file
-    fn config_from_file() -> Option<Value> {
+    fn config_from_file() -> Result<Value, ConfigFileError> {
         let file_path = if let Ok(path) = env::var("STARSHIP_CONFIG") {
             // Use $STARSHIP_CONFIG as the config path if available
             log::debug!("STARSHIP_CONFIG is set: {}", &path);
-            path
+            expand_config_path(&path).to_string_lossy().into_owned()
         } else {
             // Default to using ~/.config/starship.toml
             log::debug!("STARSHIP_CONFIG is not set");
-            let config_path = dirs_next::home_dir()?.join(".config/starship.toml");
-            let config_path_str = config_path.to_str()?.to_owned();
+            let config_path = dirs_next::home_dir()
+                .ok_or(ConfigFileError::Missing)?
+                .join(".config/starship.toml");
+            let config_path_str = config_path
+                .to_str()
+                .ok_or(ConfigFileError::Missing)?
+                .to_owned();
             log::debug!("Using default config path: {}", config_path_str);
             config_path_str
         };
 
-        let toml_content = match utils::read_file(&file_path) {
+        Self::config_from_path(&file_path)
+    }
+
+    /// Read and parse the configuration file at `file_path`, merging the files it includes
+    fn config_from_path(file_path: &str) -> Result<Value, ConfigFileError> {
+        let mut config = Self::parse_file(file_path)?;
+        let base_dir = Path::new(file_path)
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+        merge_includes(&mut config, base_dir);
+        Ok(config)
+    }
+
+    /// Read and parse a single configuration file, without following its `include`. JSON and
+    /// YAML files are read according to their extension, see `ConfigFormat`.
+    fn parse_file(file_path: &str) -> Result<Value, ConfigFileError> {
+        let content = match utils::read_file(file_path) {
             Ok(content) => {
                 log::trace!("Config file content: \"\n{}\"", &content);
-                Some(content)
+                content
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                log::debug!("Unable to read config file content: {}", &e);
+                return Err(ConfigFileError::Missing);
             }
             Err(e) => {
-                let level = if e.kind() == ErrorKind::NotFound {
-                    log::Level::Debug
-                } else {
-                    log::Level::Error
-                };
-
-                log::log!(level, "Unable to read config file content: {}", &e);
-                None
+                StarshipError::ConfigRead {
+                    message: e.to_string(),
+                }
+                .log();
+                return Err(ConfigFileError::Invalid);
             }
-        }?;
+        };
 
-        match toml::from_str(&toml_content) {
+        match ConfigFormat::of_path(Path::new(file_path)).parse(&content) {
             Ok(parsed) => {
                 log::debug!("Config parsed: {:?}", &parsed);
-                Some(parsed)
+                Ok(parsed)
             }
-            Err(error) => {
-                log::error!("Unable to parse the config file: {}", error);
-                None
+            Err(message) => {
+                StarshipError::ConfigParse { message }.log();
+                Err(ConfigFileError::Invalid)
             }
         }
     }
@@ -325,6 +702,48 @@ impl StarshipConfig {
         value
     }
 
+    /// The aliases of `[module_aliases]` by name, each rendering its module with the alias'
+    /// `config` merged over the module's own. Aliases of unknown modules are left out.
+    pub fn get_module_aliases(&self) -> HashMap<String, ModuleAlias> {
+        let aliases = match self
+            .get_config(&["module_aliases"])
+            .and_then(Value::as_table)
+        {
+            Some(aliases) => aliases,
+            None => return HashMap::new(),
+        };
+
+        aliases
+            .iter()
+            .filter_map(|(name, alias)| {
+                let module = alias.get("module").and_then(Value::as_str);
+                let module = match module {
+                    Some(module) if ALL_MODULES.contains(&module) => module,
+                    _ => {
+                        crate::log_once!(
+                            "`module_aliases.{}` needs the name of a module in `module`",
+                            name
+                        );
+                        return None;
+                    }
+                };
+
+                let mut config = self
+                    .get_module_config(module)
+                    .cloned()
+                    .unwrap_or_else(|| Value::Table(Table::new()));
+                if let Some(overlay) = alias.get("config") {
+                    merge_values(&mut config, overlay.clone());
+                }
+                let alias = ModuleAlias {
+                    module: module.to_string(),
+                    config,
+                };
+                Some((name.clone(), alias))
+            })
+            .collect()
+    }
+
     /// Get the subset of the table for a custom module by its name
     pub fn get_custom_module_config(&self, module_name: &str) -> Option<&Value> {
         let module_config = self.get_config(&["custom", module_name]);
@@ -343,13 +762,359 @@ impl StarshipConfig {
         self.get_config(&["custom"])?.as_table()
     }
 
+    /// Create a copy of this config where every module `symbol` that isn't plain ASCII is
+    /// replaced by the module's `symbol_fallback`, either user-provided or the default one.
+    pub fn with_symbol_fallbacks(&self) -> StarshipConfig {
+        let defaults = &*DEFAULT_CONFIG;
+        let mut config = match &self.config {
+            Some(Value::Table(config)) => config.clone(),
+            _ => toml::value::Table::new(),
+        };
+
+        for (module, default_config) in defaults.iter() {
+            let default_fallback = match default_config.get("symbol_fallback") {
+                Some(Value::String(fallback)) => fallback,
+                _ => continue,
+            };
+
+            let mut module_config = match config.get(module) {
+                Some(Value::Table(module_config)) => module_config.clone(),
+                Some(_) => continue,
+                None => toml::value::Table::new(),
+            };
+
+            let symbol = module_config
+                .get("symbol")
+                .or_else(|| default_config.get("symbol"))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            if symbol.is_ascii() {
+                continue;
+            }
+
+            let fallback = module_config
+                .get("symbol_fallback")
+                .and_then(Value::as_str)
+                .unwrap_or(default_fallback)
+                .to_owned();
+            log::trace!("Using symbol fallback {:?} for module {}", fallback, module);
+            module_config.insert("symbol".to_owned(), Value::String(fallback));
+            config.insert(module.clone(), Value::Table(module_config));
+        }
+
+        StarshipConfig {
+            config: Some(Value::Table(config)),
+        }
+    }
+
+    /// Create a copy of this config where the symbols every module substitutes for `$symbol`,
+    /// and the `symbol` of the custom modules, are padded according to `padding`. Returns
+    /// `None` if no symbol needs padding.
+    pub fn with_symbol_padding(&self, padding: SymbolPadding) -> Option<StarshipConfig> {
+        if padding == SymbolPadding::None {
+            return None;
+        }
+
+        let defaults = &*DEFAULT_CONFIG;
+        let config = match &self.config {
+            Some(Value::Table(config)) => Some(config),
+            _ => None,
+        };
+        fn symbol_of<'a>(table: Option<&'a Value>, key: &str) -> Option<&'a str> {
+            table?.get(key)?.as_str()
+        }
+
+        let mut padded: Vec<(Vec<&str>, &str, String)> = Vec::new();
+        for module in crate::module::ALL_MODULES {
+            let module_config = config.and_then(|config| config.get(*module));
+            for key in symbol_keys(module) {
+                let symbol = symbol_of(module_config, key)
+                    .or_else(|| symbol_of(defaults.get(*module), key))
+                    .and_then(|symbol| padding.pad(symbol));
+                if let Some(symbol) = symbol {
+                    padded.push((vec![module], key, symbol));
+                }
+            }
+        }
+        let custom_modules = config
+            .and_then(|config| config.get("custom"))
+            .and_then(Value::as_table);
+        for (name, module_config) in custom_modules.into_iter().flatten() {
+            let symbol = symbol_of(Some(module_config), "symbol").and_then(|s| padding.pad(s));
+            if let Some(symbol) = symbol {
+                padded.push((vec!["custom", name], "symbol", symbol));
+            }
+        }
+
+        if padded.is_empty() {
+            return None;
+        }
+
+        let mut config = config.cloned().unwrap_or_default();
+        for (path, key, symbol) in padded {
+            log::trace!("Padding {}.{} to {:?}", path.join("."), key, symbol);
+            let mut table = &mut config;
+            for name in path {
+                let entry = table
+                    .entry(name)
+                    .or_insert_with(|| Value::Table(Table::new()));
+                table = match entry {
+                    Value::Table(table) => table,
+                    _ => unreachable!("only tables have symbols to pad"),
+                };
+            }
+            table.insert(key.to_owned(), Value::String(symbol));
+        }
+
+        Some(StarshipConfig {
+            config: Some(Value::Table(config)),
+        })
+    }
+
+    /// The root config. Its errors aren't counted for the prompt, as most modules load it, see
+    /// the `config_error` module.
     pub fn get_root_config(&self) -> StarshipRootConfig {
         if let Some(root_config) = &self.config {
-            StarshipRootConfig::load(root_config)
+            logger::uncounted(|| StarshipRootConfig::load(root_config))
         } else {
             StarshipRootConfig::default()
         }
     }
+
+    /// Loads the config of the prompt and every module, logging the unknown keys of the modules
+    /// that aren't in the prompt as well, for `--strict`
+    pub fn validate(&self) {
+        let mut config = match &self.config {
+            Some(Value::Table(config)) => config.clone(),
+            _ => return,
+        };
+        // `line_break` has no config of its own besides `disabled`
+        config.remove("line_break");
+        let config = Value::Table(config);
+
+        FullConfig::default().load_config(&config);
+    }
+}
+
+/// The options of a module that it substitutes for `$symbol`. The symbols of `character` are
+/// format strings, so they are never padded.
+fn symbol_keys(module: &str) -> &'static [&'static str] {
+    match module {
+        "character" => &[],
+        "battery" => &[
+            "full_symbol",
+            "charging_symbol",
+            "discharging_symbol",
+            "unknown_symbol",
+            "empty_symbol",
+        ],
+        "status" => &[
+            "symbol",
+            "success_symbol",
+            "not_executable_symbol",
+            "not_found_symbol",
+            "sigint_symbol",
+            "signal_symbol",
+        ],
+        _ => &["symbol"],
+    }
+}
+
+/// How a `symbol` is separated from the text that follows it, see the root `symbol_padding`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SymbolPadding {
+    /// Append a space to symbols ending with a double-width character, like most emoji
+    Auto,
+    /// Leave symbols as configured
+    None,
+    /// Append a space to every symbol
+    Space,
+}
+
+impl SymbolPadding {
+    pub fn parse(padding: &str) -> Option<Self> {
+        match padding {
+            "auto" => Some(SymbolPadding::Auto),
+            "none" => Some(SymbolPadding::None),
+            "space" => Some(SymbolPadding::Space),
+            _ => None,
+        }
+    }
+
+    /// The padded `symbol`, or `None` if it is left as is. Symbols that are empty or already
+    /// end with whitespace are never padded.
+    pub fn pad(self, symbol: &str) -> Option<String> {
+        let last = symbol.graphemes(true).next_back()?;
+        if last.chars().all(char::is_whitespace) {
+            return None;
+        }
+
+        let pad = match self {
+            SymbolPadding::Auto => last.width_graphemes() > 1,
+            SymbolPadding::None => false,
+            SymbolPadding::Space => true,
+        };
+        if pad {
+            Some(format!("{} ", symbol))
+        } else {
+            None
+        }
+    }
+}
+
+/// Replaces the `$styles.<name>` references in `text` with the named styles of `styles`.
+/// References to names that aren't in `styles` are replaced with `fallback` and returned.
+pub fn substitute_named_styles(
+    text: &str,
+    styles: &Table,
+    fallback: &str,
+) -> (String, Vec<String>) {
+    let mut resolved = String::new();
+    let mut unknown = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(NAMED_STYLE_PREFIX) {
+        resolved.push_str(&rest[..start]);
+        rest = &rest[start + NAMED_STYLE_PREFIX.len()..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(rest.len());
+        let name = &rest[..end];
+        rest = &rest[end..];
+
+        match styles.get(name).and_then(Value::as_str) {
+            Some(style) => resolved.push_str(style),
+            None => {
+                resolved.push_str(fallback);
+                unknown.push(name.to_owned());
+            }
+        }
+    }
+    resolved.push_str(rest);
+    (resolved, unknown)
+}
+
+/// The named styles of the `[styles]` table, with their references to other named styles
+/// resolved, along with the problems found in the table by the name of the style.
+///
+/// A named style can only reference named styles that don't reference any themselves.
+pub fn named_styles(config: &Table) -> (Table, Vec<(String, String)>) {
+    let raw_styles = match config.get("styles") {
+        Some(Value::Table(styles)) => styles,
+        _ => return Default::default(),
+    };
+    let plain_styles: Table = raw_styles
+        .iter()
+        .filter(|(_, style)| matches!(style.as_str(), Some(style) if !style.contains(NAMED_STYLE_PREFIX)))
+        .map(|(name, style)| (name.clone(), style.clone()))
+        .collect();
+
+    let mut styles = Table::new();
+    let mut problems = Vec::new();
+    for (name, style) in raw_styles {
+        let style = match style.as_str() {
+            Some(style) => style,
+            None => {
+                problems.push((
+                    name.clone(),
+                    format!("`styles.{}` should be a string", name),
+                ));
+                continue;
+            }
+        };
+
+        let (resolved, unknown) = substitute_named_styles(style, &plain_styles, "");
+        for reference in unknown {
+            let message = if raw_styles.contains_key(&reference) {
+                format!(
+                    "`$styles.{}` in `styles.{}` references other named styles itself, which \
+                    named styles can't reference",
+                    reference, name
+                )
+            } else {
+                format!(
+                    "unknown named style `$styles.{}` in `styles.{}`",
+                    reference, name
+                )
+            };
+            problems.push((name.clone(), message));
+        }
+        styles.insert(name.clone(), Value::String(resolved));
+    }
+    (styles, problems)
+}
+
+/// Replaces the references to named styles in the style strings and format strings of the
+/// config with the styles of the `[styles]` table, before any of them are parsed.
+///
+/// Style strings with unknown references are removed, so the module uses its default style.
+/// Unknown references in a format string are replaced with the style of the module.
+fn resolve_named_styles(config: &mut Value) {
+    let config = match config {
+        Value::Table(config) => config,
+        _ => return,
+    };
+    let (styles, problems) = named_styles(config);
+    for (_, message) in problems {
+        log::warn!("{}", message);
+    }
+
+    for (key, value) in config.iter_mut() {
+        match value {
+            Value::String(format) if key == "format" || key == "compact_format" => {
+                // The root formats have no style of their own to fall back to
+                *format = resolve_references(format, &styles, "", key);
+            }
+            Value::Table(table) if key != "styles" => resolve_table(table, key, &styles),
+            _ => (),
+        }
+    }
+}
+
+/// Resolves the references to named styles of the config of a module, at `path`
+fn resolve_table(table: &mut Table, path: &str, styles: &Table) {
+    let mut unresolved = Vec::new();
+    for (key, value) in table.iter_mut() {
+        let key_path = format!("{}.{}", path, key);
+        match value {
+            Value::String(style) if key == "style" || key.ends_with("_style") => {
+                let (resolved, unknown) = substitute_named_styles(style, styles, "");
+                warn_unknown_named_styles(&unknown, &key_path);
+                if unknown.is_empty() {
+                    *style = resolved;
+                } else {
+                    unresolved.push(key.clone());
+                }
+            }
+            Value::String(format) if key == "format" || key.ends_with("_format") => {
+                *format = resolve_references(format, styles, "$style", &key_path);
+            }
+            Value::Table(table) => resolve_table(table, &key_path, styles),
+            Value::Array(values) => {
+                for (index, value) in values.iter_mut().enumerate() {
+                    if let Value::Table(table) = value {
+                        resolve_table(table, &format!("{}[{}]", key_path, index), styles);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    for key in unresolved {
+        table.remove(&key);
+    }
+}
+
+fn resolve_references(text: &str, styles: &Table, fallback: &str, path: &str) -> String {
+    let (resolved, unknown) = substitute_named_styles(text, styles, fallback);
+    warn_unknown_named_styles(&unknown, path);
+    resolved
+}
+
+fn warn_unknown_named_styles(unknown: &[String], path: &str) {
+    for name in unknown {
+        log::warn!("Unknown named style `$styles.{}` in `{}`", name, path);
+    }
 }
 
 /** Parse a style string which represents an ansi style. Valid tokens in the style
@@ -415,6 +1180,49 @@ pub fn parse_style_string(style_string: &str) -> Option<ansi_term::Style> {
         })
 }
 
+/// The style string of a style, such that `parse_style_string` returns the same style
+///
+/// Attributes that style strings can't express, like `blink`, are left out.
+pub fn style_to_string(style: &Style) -> String {
+    let mut words: Vec<String> = [
+        (style.is_bold, "bold"),
+        (style.is_italic, "italic"),
+        (style.is_underline, "underline"),
+        (style.is_dimmed, "dimmed"),
+        (style.is_reverse, "inverted"),
+    ]
+    .iter()
+    .filter(|(is_set, _)| *is_set)
+    .map(|(_, word)| (*word).to_owned())
+    .collect();
+    words.extend(
+        style
+            .foreground
+            .map(|color| format!("fg:{}", color_to_string(color))),
+    );
+    words.extend(
+        style
+            .background
+            .map(|color| format!("bg:{}", color_to_string(color))),
+    );
+    words.join(" ")
+}
+
+fn color_to_string(color: Color) -> String {
+    match color {
+        Color::Black => "black".to_owned(),
+        Color::Red => "red".to_owned(),
+        Color::Green => "green".to_owned(),
+        Color::Yellow => "yellow".to_owned(),
+        Color::Blue => "blue".to_owned(),
+        Color::Purple => "purple".to_owned(),
+        Color::Cyan => "cyan".to_owned(),
+        Color::White => "white".to_owned(),
+        Color::Fixed(number) => number.to_string(),
+        Color::RGB(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
 /** Parse a string that represents a color setting, returning None if this fails
  There are three valid color formats:
   - #RRGGBB      (a hash followed by an RGB hex)
@@ -479,6 +1287,8 @@ fn parse_color_string(color_string: &str) -> Option<ansi_term::Color> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::configs::git_branch::GitBranchConfig;
+    use crate::test::ModuleRenderer;
     use starship_module_config_derive::ModuleConfig;
 
     #[test]
@@ -507,6 +1317,29 @@ mod tests {
         assert_eq!(rust_config.some_array, vec!["A"]);
     }
 
+    #[test]
+    fn common_options_only_in_module_configs() {
+        let config = toml::toml! {
+            max_length = 10
+            [env]
+            FOO = "bar"
+            [[style_rules]]
+            pattern = "main"
+            max_length = 10
+        };
+        let unknown = collect_unknown_keys(|| {
+            GitBranchConfig::load(&config);
+        });
+
+        assert_eq!(
+            unknown,
+            vec![StarshipError::UnknownKey {
+                key: "max_length".to_owned(),
+                suggestion: None,
+            }]
+        );
+    }
+
     #[test]
     fn test_load_nested_config() {
         #[derive(Clone, Default, ModuleConfig)]
@@ -788,4 +1621,482 @@ mod tests {
             Style::new().fg(Color::Fixed(125)).on(Color::Fixed(127))
         );
     }
+
+    #[test]
+    fn style_strings_of_styles() {
+        assert_eq!(style_to_string(&Style::new()), "");
+        assert_eq!(style_to_string(&Color::Purple.bold()), "bold fg:purple");
+        assert_eq!(
+            style_to_string(
+                &Style::new()
+                    .dimmed()
+                    .fg(Color::Fixed(9))
+                    .on(Color::RGB(5, 16, 255))
+            ),
+            "dimmed fg:9 bg:#0510ff"
+        );
+
+        for style in &[
+            "bold italic underline dimmed inverted fg:green bg:black",
+            "fg:#00ff7f bg:242",
+            "bright-red",
+        ] {
+            let parsed = parse_style_string(style).unwrap();
+            assert_eq!(parse_style_string(&style_to_string(&parsed)), Some(parsed));
+        }
+    }
+
+    #[test]
+    fn config_file_errors() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let valid = dir.path().join("valid.toml");
+        std::fs::write(&valid, "add_newline = false\n")?;
+        let broken = dir.path().join("broken.toml");
+        std::fs::write(&broken, "[character\nsymbol = \">\"\n")?;
+
+        let path = |path: &std::path::Path| path.to_string_lossy().into_owned();
+        assert!(StarshipConfig::config_from_path(&path(&valid)).is_ok());
+        assert_eq!(
+            StarshipConfig::config_from_path(&path(&broken)).err(),
+            Some(ConfigFileError::Invalid)
+        );
+        assert_eq!(
+            StarshipConfig::config_from_path(&path(&dir.path().join("missing.toml"))).err(),
+            Some(ConfigFileError::Missing)
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn config_file_with_bom_and_crlf() -> std::io::Result<()> {
+        let contents = "add_newline = false\n\n[character]\nsuccess_symbol = \">\"\n";
+        let dir = tempfile::tempdir()?;
+        let clean = dir.path().join("clean.toml");
+        std::fs::write(&clean, contents)?;
+        let windows = dir.path().join("windows.toml");
+        std::fs::write(
+            &windows,
+            format!("\u{feff}{}", contents.replace('\n', "\r\n")),
+        )?;
+
+        let path = |path: &std::path::Path| path.to_string_lossy().into_owned();
+        let expected = StarshipConfig::config_from_path(&path(&clean)).unwrap();
+        assert_eq!(
+            StarshipConfig::config_from_path(&path(&windows)).unwrap(),
+            expected
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn json_and_yaml_configs_match_toml() -> std::io::Result<()> {
+        let toml = r#"
+add_newline = false
+format = "$directory$character"
+scan_timeout = 10
+
+[directory]
+style = "bold blue"
+truncation_length = 1
+read_only = " ro"
+
+[character]
+success_symbol = "[>](bold green)"
+
+[custom.unused]
+detect_files = ["unused.txt", "other.txt"]
+disabled = true
+"#;
+        let json = r#"{
+  "add_newline": false,
+  "format": "$directory$character",
+  "scan_timeout": 10,
+  "directory": {
+    "style": "bold blue",
+    "truncation_length": 1,
+    "read_only": " ro",
+    "home_symbol": null
+  },
+  "character": { "success_symbol": "[>](bold green)" },
+  "custom": {
+    "unused": { "detect_files": ["unused.txt", "other.txt"], "disabled": true }
+  }
+}"#;
+        let yaml = r#"
+add_newline: false
+format: "$directory$character"
+scan_timeout: 10
+directory:
+  style: bold blue
+  truncation_length: 1
+  read_only: " ro"
+  home_symbol: ~
+character:
+  success_symbol: "[>](bold green)"
+custom:
+  unused:
+    detect_files:
+      - unused.txt
+      - other.txt
+    disabled: true
+"#;
+        let dir = tempfile::tempdir()?;
+        let render = |name: &str, contents: &str| -> std::io::Result<(Value, String)> {
+            let path = dir.path().join(name);
+            std::fs::write(&path, contents)?;
+            let (config, degraded) = StarshipConfig::initialize_from_path(&path.to_string_lossy());
+            assert!(!degraded, "{} is invalid", name);
+
+            let mut context = crate::context::Context::new_with_shell_and_path(
+                clap::ArgMatches::default(),
+                crate::context::Shell::Unknown,
+                dir.path().into(),
+                dir.path().into(),
+            );
+            let value = config.config.clone().unwrap();
+            context.config = config;
+            Ok((value, crate::print::get_prompt(context)))
+        };
+
+        let expected = render("starship.toml", toml)?;
+        // The prompt is only the same if the character and the directory are configured
+        assert!(expected
+            .1
+            .contains(&Color::Green.bold().paint(">").to_string()));
+        assert!(expected
+            .1
+            .starts_with(&Color::Blue.bold().prefix().to_string()));
+        assert_eq!(render("starship.json", json)?, expected);
+        assert_eq!(render("starship.yaml", yaml)?, expected);
+        assert_eq!(render("starship.YML", yaml)?, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn config_file_errors_are_categorized() -> std::io::Result<()> {
+        use crate::error::ErrorCategory;
+        use crate::logger::error_count;
+
+        let dir = tempfile::tempdir()?;
+        let broken = dir.path().join("broken.toml");
+        std::fs::write(&broken, "[character\n")?;
+
+        let config_errors = error_count(ErrorCategory::Config);
+        assert!(StarshipConfig::config_from_path(&broken.to_string_lossy()).is_err());
+        assert!(error_count(ErrorCategory::Config) > config_errors);
+
+        // A directory exists but can't be read as a file
+        let io_errors = error_count(ErrorCategory::Io);
+        assert!(StarshipConfig::config_from_path(&dir.path().to_string_lossy()).is_err());
+        assert!(error_count(ErrorCategory::Io) > io_errors);
+
+        let config_errors = error_count(ErrorCategory::Config);
+        warn_unknown_key("formt", &["format"]);
+        assert!(error_count(ErrorCategory::Config) > config_errors);
+        dir.close()
+    }
+
+    #[test]
+    fn json_config_with_yaml_content() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("starship.json");
+        std::fs::write(&path, "add_newline: false\n")?;
+
+        assert_eq!(
+            StarshipConfig::config_from_path(&path.to_string_lossy()).err(),
+            Some(ConfigFileError::Invalid)
+        );
+        let (config, degraded) = StarshipConfig::initialize_from_path(&path.to_string_lossy());
+        assert!(degraded);
+        assert!(config.get_root_config().add_newline);
+        dir.close()
+    }
+
+    /// Writes `files` into a temporary directory, creating their parent directories
+    fn config_dir(files: &[(&str, &str)]) -> std::io::Result<tempfile::TempDir> {
+        let dir = tempfile::tempdir()?;
+        for (path, contents) in files {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, contents)?;
+        }
+        Ok(dir)
+    }
+
+    fn load_config_dir(dir: &tempfile::TempDir) -> Value {
+        let path = dir.path().join("starship.toml");
+        StarshipConfig::config_from_path(&path.to_string_lossy()).unwrap()
+    }
+
+    #[test]
+    fn include_files_for_different_modules() -> std::io::Result<()> {
+        let dir = config_dir(&[
+            (
+                "starship.toml",
+                "include = [\"conf.d/*.toml\"]\nadd_newline = false\n",
+            ),
+            ("conf.d/10-git.toml", "[git_branch]\nsymbol = \"git \"\n"),
+            ("conf.d/20-dir.toml", "[directory]\ntruncation_length = 1\n"),
+            ("conf.d/notes.txt", "not = \"included\"\n"),
+        ])?;
+
+        let config = load_config_dir(&dir);
+        assert_eq!(config["add_newline"], Value::Boolean(false));
+        assert_eq!(config["git_branch"]["symbol"], Value::from("git "));
+        assert_eq!(config["directory"]["truncation_length"], Value::Integer(1));
+        assert!(config.get("not").is_none());
+        dir.close()
+    }
+
+    #[test]
+    fn include_globs_expanded() -> std::io::Result<()> {
+        let dir = config_dir(&[("conf.d/git.toml", "[git_branch]\nsymbol = \"git \"\n")])?;
+        env::set_var("STARSHIP_TEST_INCLUDE_DIR", dir.path().join("conf.d"));
+        std::fs::write(
+            dir.path().join("starship.toml"),
+            "include = [\"${STARSHIP_TEST_INCLUDE_DIR}/*.toml\"]\n",
+        )?;
+
+        let config = load_config_dir(&dir);
+        assert_eq!(config["git_branch"]["symbol"], Value::from("git "));
+        dir.close()
+    }
+
+    #[test]
+    fn later_include_files_win() -> std::io::Result<()> {
+        let dir = config_dir(&[
+            (
+                "starship.toml",
+                "include = [\"b.toml\", \"conf.d/*.toml\"]\n\
+                 default_order = [\"directory\", \"character\"]\n\
+                 [character]\nsuccess_symbol = \">\"\n",
+            ),
+            ("b.toml", "[character]\nsuccess_symbol = \"b\"\n"),
+            (
+                "conf.d/a.toml",
+                "default_order = [\"git_branch\"]\n\
+                 [character]\nsuccess_symbol = \"a\"\nerror_symbol = \"!\"\n",
+            ),
+            ("conf.d/c.toml", "[character]\nsuccess_symbol = \"c\"\n"),
+        ])?;
+
+        let config = load_config_dir(&dir);
+        // Tables are merged, the last file setting a key wins
+        assert_eq!(config["character"]["success_symbol"], Value::from("c"));
+        assert_eq!(config["character"]["error_symbol"], Value::from("!"));
+        // Arrays are replaced
+        assert_eq!(
+            config["default_order"],
+            Value::Array(vec![Value::from("git_branch")])
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn bad_include_paths_ignored() -> std::io::Result<()> {
+        let dir = config_dir(&[
+            (
+                "starship.toml",
+                "include = [\"missing/*.toml\", \"[invalid\", \"broken.toml\", \"nested.toml\", 1]\n\
+                 add_newline = false\n",
+            ),
+            ("broken.toml", "[character\n"),
+            (
+                "nested.toml",
+                "include = [\"other.toml\"]\n[character]\nsuccess_symbol = \"n\"\n",
+            ),
+            ("other.toml", "[character]\nerror_symbol = \"o\"\n"),
+        ])?;
+
+        let config = load_config_dir(&dir);
+        assert_eq!(config["add_newline"], Value::Boolean(false));
+        assert_eq!(config["character"]["success_symbol"], Value::from("n"));
+        // Included files can't include others
+        assert!(config["character"].get("error_symbol").is_none());
+        assert_eq!(config["include"].as_array().map(Vec::len), Some(5));
+        dir.close()
+    }
+
+    fn resolved(mut config: Value) -> Value {
+        resolve_named_styles(&mut config);
+        config
+    }
+
+    #[test]
+    fn named_styles_resolved() {
+        let config = resolved(toml::toml! {
+            [styles]
+            vcs = "bold purple"
+
+            [git_branch]
+            style = "$styles.vcs"
+            format = "[$branch]($styles.vcs underline) "
+
+            [custom.foo]
+            style = "italic $styles.vcs"
+
+            [[battery.display]]
+            threshold = 10
+            style = "$styles.vcs"
+        });
+
+        assert_eq!(config["git_branch"]["style"].as_str(), Some("bold purple"));
+        assert_eq!(
+            config["git_branch"]["format"].as_str(),
+            Some("[$branch](bold purple underline) ")
+        );
+        assert_eq!(
+            config["custom"]["foo"]["style"].as_str(),
+            Some("italic bold purple")
+        );
+        assert_eq!(
+            config["battery"]["display"][0]["style"].as_str(),
+            Some("bold purple")
+        );
+    }
+
+    #[test]
+    fn named_styles_reference_one_level() {
+        let config = resolved(toml::toml! {
+            [styles]
+            base = "bold"
+            vcs = "$styles.base purple"
+            nested = "$styles.vcs"
+
+            [git_branch]
+            style = "$styles.vcs"
+        });
+
+        assert_eq!(
+            config["styles"]["vcs"].as_str(),
+            Some("$styles.base purple")
+        );
+        assert_eq!(config["git_branch"]["style"].as_str(), Some("bold purple"));
+
+        let (styles, problems) = named_styles(config.as_table().unwrap());
+        assert_eq!(styles["nested"].as_str(), Some(""));
+        assert_eq!(
+            problems,
+            vec![(
+                String::from("nested"),
+                String::from(
+                    "`$styles.vcs` in `styles.nested` references other named styles itself, \
+                    which named styles can't reference"
+                )
+            )]
+        );
+    }
+
+    #[test]
+    fn unknown_named_style_uses_module_default() {
+        let config = resolved(toml::toml! {
+            [styles]
+            vcs = "bold purple"
+
+            [git_branch]
+            style = "$styles.vsc"
+            format = "[$branch]($styles.vsc) "
+        });
+
+        assert!(config["git_branch"].get("style").is_none());
+        assert_eq!(
+            config["git_branch"]["format"].as_str(),
+            Some("[$branch]($style) ")
+        );
+        let git_branch = GitBranchConfig::load(&config["git_branch"]);
+        assert_eq!(git_branch.style, GitBranchConfig::default().style);
+    }
+
+    #[test]
+    fn named_style_in_textgroup() {
+        let config = resolved(toml::toml! {
+            [styles]
+            prompt = "bold green"
+
+            [character]
+            format = "[>]($styles.prompt) "
+        });
+
+        let actual = ModuleRenderer::new("character").config(config).collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint(">")));
+        assert_eq!(expected, actual);
+    }
+
+    fn render_symbol(padding: &str, symbol: &str) -> Option<String> {
+        ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                symbol_padding = padding
+
+                [env_var]
+                variable = "TEST_VAR"
+                symbol = symbol
+                format = "$symbol$env_value"
+            })
+            .env("TEST_VAR", "1.0")
+            .collect()
+    }
+
+    #[test]
+    fn symbol_padding_modes() {
+        let nerd_font = "\u{e7a8}";
+        let cases = [
+            ("auto", "🦀", "🦀 1.0"),
+            ("auto", nerd_font, "\u{e7a8}1.0"),
+            ("auto", "v", "v1.0"),
+            ("none", "🦀", "🦀1.0"),
+            ("none", nerd_font, "\u{e7a8}1.0"),
+            ("none", "v", "v1.0"),
+            ("space", "🦀", "🦀 1.0"),
+            ("space", nerd_font, "\u{e7a8} 1.0"),
+            ("space", "v", "v 1.0"),
+        ];
+
+        for (padding, symbol, expected) in cases {
+            assert_eq!(
+                Some(expected.to_owned()),
+                render_symbol(padding, symbol),
+                "{:?} with symbol_padding = {:?}",
+                symbol,
+                padding
+            );
+        }
+    }
+
+    #[test]
+    fn symbol_padding_keeps_trailing_whitespace() {
+        assert_eq!(Some("🦀 1.0".to_owned()), render_symbol("auto", "🦀 "));
+        assert_eq!(Some("v 1.0".to_owned()), render_symbol("space", "v "));
+        assert_eq!(None, SymbolPadding::Space.pad(""));
+    }
+
+    #[test]
+    fn symbol_padding_in_custom_modules() {
+        let config = StarshipConfig {
+            config: Some(toml::toml! {
+                [custom.pkg]
+                symbol = "📦"
+
+                [custom.plain]
+                symbol = "pkg"
+            }),
+        };
+
+        let padded = config.with_symbol_padding(SymbolPadding::Auto).unwrap();
+        let symbol = |name| padded.get_config(&["custom", name, "symbol"]).cloned();
+        assert_eq!(Some(Value::from("📦 ")), symbol("pkg"));
+        assert_eq!(Some(Value::from("pkg")), symbol("plain"));
+    }
+
+    #[test]
+    fn default_status_symbols_padded() {
+        let config = StarshipConfig { config: None };
+        let padded = config.with_symbol_padding(SymbolPadding::Auto).unwrap();
+        let symbol = |key| padded.get_config(&["status", key]).cloned();
+
+        assert_eq!(Some(Value::from("🚫 ")), symbol("not_executable_symbol"));
+        assert_eq!(Some(Value::from("⚡ ")), symbol("signal_symbol"));
+        // `✖` is single-width
+        assert_eq!(None, symbol("symbol"));
+        assert_eq!(None, padded.get_config(&["git_branch", "symbol"]));
+    }
 }
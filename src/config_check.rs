@@ -0,0 +1,712 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read};
+
+use toml::value::Table;
+use toml::Value;
+
+use crate::config::{
+    collect_unknown_keys, did_you_mean, named_styles, parse_style_string, substitute_named_styles,
+    warn_unknown_key, ModuleConfig,
+};
+use crate::configs::custom::CustomConfig;
+use crate::configs::{FullConfig, StarshipRootConfig};
+use crate::error::StarshipError;
+use crate::formatter::string_formatter::StringFormatterError;
+use crate::formatter::{FormatScope, StringFormatter, VariableHolder};
+use crate::module::ALL_MODULES;
+
+/// A problem found in a config file by `starship test-config`
+#[derive(Debug, PartialEq)]
+pub struct ConfigProblem {
+    /// The line and column of the problem, both starting at 1, if it could be found
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location {
+            Some((line, column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Checks the config file at `path`, or read from stdin if it's `-`, without rendering a
+/// prompt. Prints every problem found and returns the exit code of `starship test-config`.
+pub fn test_config(path: &str) -> i32 {
+    let source = if path == "-" {
+        let mut source = String::new();
+        io::stdin().read_to_string(&mut source).map(|_| source)
+    } else {
+        std::fs::read_to_string(path)
+    };
+    let source = match source {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("Unable to read {}: {}", path, error);
+            return 1;
+        }
+    };
+
+    let problems = check_config(&source);
+    for problem in &problems {
+        println!("{}:{}", path, problem);
+    }
+
+    if problems.is_empty() {
+        println!("{}: no problems found", path);
+        0
+    } else {
+        println!("{}: {} problem(s) found", path, problems.len());
+        1
+    }
+}
+
+/// Finds all the problems of a config: TOML syntax errors, unknown keys, format strings that
+/// don't parse, unknown modules in the prompt format and invalid style strings
+pub fn check_config(source: &str) -> Vec<ConfigProblem> {
+    let config = match source.parse::<Value>() {
+        Ok(Value::Table(config)) => config,
+        Ok(_) => return Vec::new(),
+        Err(error) => {
+            return vec![ConfigProblem {
+                location: error
+                    .line_col()
+                    .map(|(line, column)| (line + 1, column + 1)),
+                message: format!("invalid TOML: {}", error),
+            }]
+        }
+    };
+
+    let (styles, style_problems) = named_styles(&config);
+    let mut checker = Checker {
+        source,
+        problems: Vec::new(),
+        styles,
+        alias_modules: HashMap::new(),
+    };
+    for (name, message) in style_problems {
+        checker.report(&["styles"], &name, message);
+    }
+    checker.check_root(&config);
+    checker.problems
+}
+
+struct Checker<'a> {
+    source: &'a str,
+    problems: Vec<ConfigProblem>,
+    /// The named styles of the `[styles]` table, with their references resolved
+    styles: Table,
+    /// The modules of the aliases of `[module_aliases]`, by alias
+    alias_modules: HashMap<String, String>,
+}
+
+impl<'a> Checker<'a> {
+    fn report(&mut self, table: &[&str], key: &str, message: String) {
+        self.problems.push(ConfigProblem {
+            location: locate(self.source, table, key),
+            message,
+        });
+    }
+
+    fn check_root(&mut self, config: &Table) {
+        for (key, value) in config {
+            let is_module = FullConfig::field_config_keys(key)
+                .map(|keys| !keys.is_empty())
+                .unwrap_or(false);
+            match key.as_str() {
+                "custom" => self.check_custom_modules(value),
+                "module_aliases" => self.check_module_aliases(value),
+                // `line_break` has no config of its own besides `disabled`
+                "line_break" => match value {
+                    Value::Table(table) => {
+                        let unknown = collect_unknown_keys(|| {
+                            for key in table.keys().filter(|key| *key != "disabled") {
+                                warn_unknown_key(key, &["disabled"]);
+                            }
+                        });
+                        self.check_table(&[key], table, &unknown);
+                    }
+                    _ => self.report(&[], key, format!("`{}` should be a table", key)),
+                },
+                key if is_module => match value {
+                    Value::Table(table) => {
+                        let unknown = unknown_module_keys(key, value);
+                        self.check_table(&[key], table, &unknown);
+                    }
+                    _ => self.report(&[], key, format!("`{}` should be a table", key)),
+                },
+                key => {
+                    let option =
+                        Value::Table(std::iter::once((key.to_owned(), value.clone())).collect());
+                    let unknown = collect_unknown_keys(|| {
+                        StarshipRootConfig::default().load_config(&option);
+                    });
+                    self.report_unknown_keys(&[], &unknown);
+                }
+            }
+        }
+
+        for key in &["format", "compact_format"] {
+            if let Some(format) = config.get(*key).and_then(|format| format.as_str()) {
+                self.check_format(&[], key, format);
+                let (format, _) = substitute_named_styles(format, &self.styles, "");
+                self.check_prompt_modules(config, key, &format);
+            }
+        }
+        for (name, style) in self.styles.clone() {
+            if let Some(style) = style.as_str() {
+                self.check_style(&["styles"], &name, style);
+            }
+        }
+        if let Some(Value::Array(order)) = config.get("default_order") {
+            for module in order.iter().filter_map(|module| module.as_str()) {
+                if !is_module(config, module) {
+                    let message = format!("unknown module `{}` in `default_order`", module);
+                    self.report(&[], "default_order", message);
+                }
+            }
+        }
+    }
+
+    fn check_custom_modules(&mut self, value: &Value) {
+        let modules = match value {
+            Value::Table(modules) => modules,
+            _ => return self.report(&[], "custom", String::from("`custom` should be a table")),
+        };
+
+        for (name, module) in modules {
+            match module {
+                Value::Table(table) => {
+                    let unknown = collect_unknown_keys(|| {
+                        CustomConfig::default().load_config(module);
+                    });
+                    self.check_table(&["custom", name], table, &unknown)
+                }
+                _ => {
+                    let message = format!("`custom.{}` should be a table", name);
+                    self.report(&["custom"], name, message)
+                }
+            }
+        }
+    }
+
+    fn check_module_aliases(&mut self, value: &Value) {
+        let aliases = match value {
+            Value::Table(aliases) => aliases,
+            _ => {
+                let message = String::from("`module_aliases` should be a table");
+                return self.report(&[], "module_aliases", message);
+            }
+        };
+
+        for (name, alias) in aliases {
+            let path = ["module_aliases", name.as_str()];
+            let alias = match alias {
+                Value::Table(alias) => alias,
+                _ => {
+                    let message = format!("`module_aliases.{}` should be a table", name);
+                    self.report(&path[..1], name, message);
+                    continue;
+                }
+            };
+            if ALL_MODULES.contains(&name.as_str()) {
+                let message = format!(
+                    "`module_aliases.{}` has the name of a module, which is rendered instead",
+                    name
+                );
+                self.report(&path[..1], name, message);
+            }
+            let unknown = collect_unknown_keys(|| {
+                for key in alias
+                    .keys()
+                    .filter(|key| *key != "module" && *key != "config")
+                {
+                    warn_unknown_key(key, &["module", "config"]);
+                }
+            });
+            self.report_unknown_keys(&path, &unknown);
+
+            let module = match alias.get("module").and_then(Value::as_str) {
+                Some(module) if ALL_MODULES.contains(&module) => module,
+                Some(module) => {
+                    let mut message = format!(
+                        "unknown module `{}` in `{}`",
+                        module,
+                        key_path(&path, "module")
+                    );
+                    if let Some(name) = did_you_mean(module, ALL_MODULES) {
+                        message.push_str(&format!(", did you mean `{}`?", name));
+                    }
+                    self.report(&path, "module", message);
+                    continue;
+                }
+                None => {
+                    let message = format!(
+                        "`{}` should be the name of a module",
+                        key_path(&path, "module")
+                    );
+                    self.report(&path[..1], name, message);
+                    continue;
+                }
+            };
+            self.alias_modules.insert(name.clone(), module.to_string());
+
+            match alias.get("config") {
+                Some(value @ Value::Table(config)) => {
+                    let unknown = unknown_module_keys(module, value);
+                    let path = ["module_aliases", name.as_str(), "config"];
+                    self.check_table(&path, config, &unknown);
+                }
+                Some(_) => {
+                    let message = format!("`{}` should be a table", key_path(&path, "config"));
+                    self.report(&path, "config", message);
+                }
+                None => (),
+            }
+        }
+    }
+
+    /// Reports the `unknown` keys of the config of a module and checks the format strings and
+    /// style strings of the others
+    fn check_table(&mut self, path: &[&str], table: &Table, unknown: &[StarshipError]) {
+        self.report_unknown_keys(path, unknown);
+
+        for (key, value) in table {
+            let is_unknown = unknown.iter().any(|error| match error {
+                StarshipError::UnknownKey { key: unknown, .. } => unknown == key,
+                _ => false,
+            });
+            let value = match value.as_str() {
+                Some(value) if !is_unknown => value,
+                _ => continue,
+            };
+            if key == "format" || key.ends_with("_format") {
+                self.check_format(path, key, value);
+            } else if key == "style" || key.ends_with("_style") {
+                self.check_style(path, key, value);
+            }
+        }
+    }
+
+    fn check_format(&mut self, path: &[&str], key: &str, format: &str) {
+        let format = match self.resolve_named_styles(path, key, format) {
+            Some(format) => format,
+            None => return,
+        };
+        let formatter = match StringFormatter::new(&format) {
+            Ok(formatter) => formatter,
+            Err(error) => {
+                let message = format!(
+                    "invalid format string in `{}`: {}",
+                    key_path(path, key),
+                    describe_format_error(&error)
+                );
+                return self.report(path, key, message);
+            }
+        };
+
+        for style in formatter.get_literal_styles() {
+            self.check_style(path, key, &style);
+        }
+
+        let alias_module;
+        let scope = match path {
+            [] => FormatScope::Root,
+            ["custom", ..] => FormatScope::Module("custom"),
+            ["module_aliases", alias, ..] => {
+                alias_module = self.alias_modules.get(*alias).cloned().unwrap_or_default();
+                FormatScope::Module(&alias_module)
+            }
+            [module, ..] => FormatScope::Module(module),
+        };
+        for module in formatter.get_module_references(scope) {
+            let message = format!(
+                "`${}` in `{}` is another module, but module formats can only reference that \
+                module's own variables; to combine modules use the root `format`",
+                module,
+                key_path(path, key)
+            );
+            self.report(path, key, message);
+        }
+    }
+
+    fn check_style(&mut self, path: &[&str], key: &str, style: &str) {
+        let style = match self.resolve_named_styles(path, key, style) {
+            Some(style) => style,
+            None => return,
+        };
+        let style = style.as_str();
+        // `none` is a valid way to not style a text, even though it doesn't give a style
+        let is_none = style.split_whitespace().any(|token| {
+            token.eq_ignore_ascii_case("none") || token.eq_ignore_ascii_case("fg:none")
+        });
+        if parse_style_string(style).is_none() && !is_none {
+            let error = StarshipError::StyleParse {
+                key: key_path(path, key),
+                style: style.to_owned(),
+            };
+            self.report(path, key, error.to_string());
+        }
+    }
+
+    /// Checks that the variables of the prompt format in `key` are modules
+    fn check_prompt_modules(&mut self, config: &Table, key: &str, format: &str) {
+        let formatter = match StringFormatter::new(format) {
+            Ok(formatter) => formatter,
+            Err(_) => return,
+        };
+
+        for module in formatter.get_variables() {
+            if module == "all" || is_module(config, &module) {
+                continue;
+            }
+
+            let mut message = format!("unknown module `${}` in `{}`", module, key);
+            if let Some(name) = did_you_mean(&module, ALL_MODULES) {
+                message.push_str(&format!(", did you mean `${}`?", name));
+            }
+            self.report(&[], key, message);
+        }
+    }
+
+    /// Replaces the references to named styles in `text`, reporting the unknown ones
+    fn resolve_named_styles(&mut self, path: &[&str], key: &str, text: &str) -> Option<String> {
+        let (resolved, unknown) = substitute_named_styles(text, &self.styles, "");
+        for name in &unknown {
+            let message = format!(
+                "unknown named style `$styles.{}` in `{}`",
+                name,
+                key_path(path, key)
+            );
+            self.report(path, key, message);
+        }
+        if unknown.is_empty() {
+            Some(resolved)
+        } else {
+            None
+        }
+    }
+
+    /// Reports the unknown keys found by loading the table at `path`
+    fn report_unknown_keys(&mut self, path: &[&str], unknown: &[StarshipError]) {
+        for error in unknown {
+            if let StarshipError::UnknownKey { key, suggestion } = error {
+                let mut message = format!("unknown config key `{}`", key_path(path, key));
+                if let Some(field) = suggestion {
+                    message.push_str(&format!(", did you mean `{}`?", field));
+                }
+                self.report(path, key, message);
+            }
+        }
+    }
+}
+
+/// The unknown keys of `config` as the config of `module`, as loading it reports them
+fn unknown_module_keys(module: &str, config: &Value) -> Vec<StarshipError> {
+    let config = Value::Table(std::iter::once((module.to_owned(), config.clone())).collect());
+    collect_unknown_keys(|| {
+        FullConfig::default().load_config(&config);
+    })
+}
+
+/// Whether `module` is a built-in module, or a custom module configured in `config`
+fn is_module(config: &Table, module: &str) -> bool {
+    if ALL_MODULES.contains(&module) || module == "custom" {
+        return true;
+    }
+    let is_alias = config
+        .get("module_aliases")
+        .and_then(|aliases| aliases.get(module))
+        .is_some();
+    if is_alias {
+        return true;
+    }
+
+    match module.strip_prefix("custom.") {
+        Some(name) => config
+            .get("custom")
+            .and_then(|custom| custom.get(name))
+            .is_some(),
+        None => false,
+    }
+}
+
+fn key_path(path: &[&str], key: &str) -> String {
+    path.iter()
+        .chain(std::iter::once(&key))
+        .copied()
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The message of a format string error on a single line, with where it is in the format
+fn describe_format_error(error: &StringFormatterError) -> String {
+    let error = match error {
+        StringFormatterError::Parse(error) => error,
+        error => return error.to_string(),
+    };
+
+    let column = match error.line_col {
+        pest::error::LineColLocation::Pos((_, column)) => column,
+        pest::error::LineColLocation::Span((_, column), _) => column,
+    };
+    let rendered = error.to_string();
+    let message = rendered
+        .lines()
+        .find_map(|line| line.trim_start().strip_prefix("= "))
+        .unwrap_or("parse error");
+    format!("{} at character {}", message, column)
+}
+
+/// Finds the line and column of `key` in the table at `path` of a TOML file, by looking for
+/// the table's header and the key after it. Keys that are tables are found by their own
+/// header, and otherwise it falls back to the header of the table at `path`.
+fn locate(source: &str, path: &[&str], key: &str) -> Option<(usize, usize)> {
+    let mut in_table = path.is_empty();
+    let mut header = None;
+    let mut key_header = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if trimmed.starts_with('[') {
+            let name = trimmed.trim_start_matches('[');
+            let name = name.split(']').next().unwrap_or_default();
+            let name = name
+                .split('.')
+                .map(|part| part.trim().trim_matches('"').trim_matches('\''))
+                .collect::<Vec<_>>();
+            in_table = name == path;
+            if in_table {
+                header = Some((index + 1, indent + 1));
+            } else if name.len() == path.len() + 1
+                && name.starts_with(path)
+                && name[path.len()] == key
+            {
+                key_header = key_header.or(Some((index + 1, indent + 1)));
+            }
+            continue;
+        }
+
+        if in_table {
+            let rest = trimmed
+                .strip_prefix(key)
+                .or_else(|| trimmed.strip_prefix(&format!("\"{}\"", key)))
+                .or_else(|| trimmed.strip_prefix(&format!("'{}'", key)));
+            if rest.map(|rest| rest.trim_start().starts_with('=')) == Some(true) {
+                return Some((index + 1, indent + 1));
+            }
+        }
+    }
+
+    key_header.or(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<String> {
+        check_config(source)
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn valid_config() {
+        let source = r#"
+format = "$directory$git_branch$custom.foo$character"
+default_order = ["character", "custom.foo"]
+
+[git_branch]
+style = "bold fg:#ff0000 bg:none"
+format = "on [$branch](none) "
+
+[custom.foo]
+command = "echo foo"
+when = "true"
+
+[directory.substitutions]
+"~/code" = "C"
+"#;
+        assert_eq!(check_config(source), Vec::new());
+    }
+
+    #[test]
+    fn reports_all_problems() {
+        let source = r#"
+format = "$directory$git_brnch$character"
+
+[directory]
+style = "bold notacolor"
+
+[git_branch]
+format = "on [$branch(bold purple) "
+"#;
+        assert_eq!(
+            messages(source),
+            vec![
+                "5:1: invalid style `bold notacolor` in `directory.style`",
+                "8:1: invalid format string in `git_branch.format`: expected variable, string, textgroup, or conditional at character 26",
+                "2:1: unknown module `$git_brnch` in `format`, did you mean `$git_branch`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_module_aliases() {
+        let source = r#"
+format = "$git_branch$gb_short$hostname_short"
+
+[module_aliases]
+gb_short = { module = "git_branch", config = { formt = "$branch", truncation_length = 8 } }
+hostname_short = { module = "hostname", config = { format = "[$hostname]($style)" } }
+broken = { module = "git_brnch" }
+"#;
+        assert_eq!(
+            messages(source),
+            vec![
+                "unknown config key `module_aliases.gb_short.config.formt`, did you mean `format`?",
+                "unknown module `git_brnch` in `module_aliases.broken.module`, did you mean `git_branch`?",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_modules_in_compact_format() {
+        let source = r#"
+compact_format = "$directroy$character"
+"#;
+        assert_eq!(
+            messages(source),
+            vec![
+                "2:1: unknown module `$directroy` in `compact_format`, did you mean `$directory`?"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_named_styles() {
+        let source = r#"
+[styles]
+vcs = "bold purple"
+nested = "$styles.vcs"
+deep = "$styles.nested"
+broken = "bold purpel"
+
+[git_branch]
+style = "$styles.vcs"
+format = "[$branch]($styles.vsc) "
+
+[git_commit]
+style = "$styles.broken"
+"#;
+        assert_eq!(
+            messages(source),
+            vec![
+                "5:1: `$styles.nested` in `styles.deep` references other named styles itself, \
+                which named styles can't reference",
+                "10:1: unknown named style `$styles.vsc` in `git_branch.format`",
+                "13:1: invalid style `bold purpel` in `git_commit.style`",
+                "6:1: invalid style `bold purpel` in `styles.broken`",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_keys() {
+        let source = r#"
+add_newlines = false
+
+[aws]
+symbl = "a "
+
+[custom.foo]
+command = "echo foo"
+shell_args = ["-c"]
+
+[nosuchmodule]
+disabled = true
+"#;
+        assert_eq!(
+            messages(source),
+            vec![
+                "2:1: unknown config key `add_newlines`, did you mean `add_newline`?",
+                "5:1: unknown config key `aws.symbl`, did you mean `symbol`?",
+                "9:1: unknown config key `custom.foo.shell_args`, did you mean `shell`?",
+                "11:1: unknown config key `nosuchmodule`",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_keys_like_loading_the_config() {
+        let source = r#"
+[battery]
+max_length = 10
+display = [{ threshold = 10, styl = "bold red" }]
+"#;
+        assert_eq!(
+            messages(source),
+            vec!["2:1: unknown config key `battery.styl`, did you mean `style`?"]
+        );
+    }
+
+    #[test]
+    fn reports_styles_in_formats() {
+        let source = r#"
+[character]
+format = "[>](bold grean) "
+"#;
+        assert_eq!(
+            messages(source),
+            vec!["3:1: invalid style `bold grean` in `character.format`"]
+        );
+    }
+
+    #[test]
+    fn reports_modules_in_module_formats() {
+        let source = r#"
+format = "$directory$status$character"
+
+[directory]
+format = "[$path$git_branch]($style) "
+
+[status]
+format = "[$status]($style) "
+"#;
+        assert_eq!(
+            messages(source),
+            vec![
+                "5:1: `$git_branch` in `directory.format` is another module, but module formats \
+                can only reference that module's own variables; to combine modules use the root \
+                `format`"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_modules_in_default_order() {
+        let source = r#"
+default_order = ["character", "custom.missing"]
+"#;
+        assert_eq!(
+            messages(source),
+            vec!["2:1: unknown module `custom.missing` in `default_order`"]
+        );
+    }
+
+    #[test]
+    fn reports_toml_errors() {
+        let source = "[character\nsymbol = \">\"\n";
+        let problems = check_config(source);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].location, Some((1, 11)));
+        assert!(problems[0].message.starts_with("invalid TOML: "));
+    }
+}
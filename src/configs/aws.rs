@@ -5,11 +5,19 @@ use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct AwsConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The symbol used before displaying the current AWS profile.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `AWS` module.
     pub disabled: bool,
+    /// Table of region aliases to display in addition to the AWS name.
     pub region_aliases: HashMap<String, &'a str>,
+    /// The symbol displayed when the temporary credentials have expired.
     pub expiration_symbol: &'a str,
 }
 
@@ -18,6 +26,7 @@ impl<'a> Default for AwsConfig<'a> {
         AwsConfig {
             format: "on [$symbol($profile )(\\($region\\) )(\\[$duration\\])]($style)",
             symbol: "☁️  ",
+            symbol_fallback: "aws ",
             style: "bold yellow",
             disabled: false,
             region_aliases: HashMap::new(),
@@ -5,14 +5,24 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct BatteryConfig<'a> {
+    /// The symbol shown when the battery is full.
     pub full_symbol: &'a str,
+    /// The symbol shown when the battery is charging.
     pub charging_symbol: &'a str,
+    /// The symbol shown when the battery is discharging.
     pub discharging_symbol: &'a str,
+    /// The symbol shown when the battery state is unknown.
     pub unknown_symbol: &'a str,
+    /// The symbol shown when the battery state is empty.
     pub empty_symbol: &'a str,
+    /// Display threshold and style for the module.
     pub display: Vec<BatteryDisplayConfig<'a>>,
+    /// Disables the `battery` module.
     pub disabled: bool,
+    /// The format for the module.
     pub format: &'a str,
+    /// How long, in milliseconds, the battery status is reused before it is read again.
+    pub refresh_interval_ms: u64,
 }
 
 impl<'a> Default for BatteryConfig<'a> {
@@ -26,15 +36,20 @@ impl<'a> Default for BatteryConfig<'a> {
             format: "[$symbol$percentage]($style) ",
             display: vec![BatteryDisplayConfig::default()],
             disabled: false,
+            refresh_interval_ms: 30_000,
         }
     }
 }
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct BatteryDisplayConfig<'a> {
+    /// The upper bound for the display option.
     pub threshold: i64,
+    /// The style used if the display option is in use.
     pub style: &'a str,
+    /// The symbol shown when the battery is charging.
     pub charging_symbol: Option<&'a str>,
+    /// The symbol shown when the battery is discharging.
     pub discharging_symbol: Option<&'a str>,
 }
 
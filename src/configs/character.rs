@@ -5,10 +5,19 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct CharacterConfig<'a> {
+    /// The format string used before the text input.
     pub format: &'a str,
+    /// The format string used before the text input if the previous command succeeded.
     pub success_symbol: &'a str,
+    /// The format string used before the text input if the previous command failed.
     pub error_symbol: &'a str,
+    /// The format string used before the text input if the shell is in vim normal mode.
     pub vicmd_symbol: &'a str,
+    /// The format string used before the text input if the effective user is root.
+    pub root_symbol: &'a str,
+    /// The format string used before the text input if the current directory is read only.
+    pub readonly_symbol: &'a str,
+    /// Disables the `character` module.
     pub disabled: bool,
 }
 
@@ -19,6 +28,8 @@ impl<'a> Default for CharacterConfig<'a> {
             success_symbol: "[❯](bold green)",
             error_symbol: "[❯](bold red)",
             vicmd_symbol: "[❮](bold green)",
+            root_symbol: "",
+            readonly_symbol: "",
             disabled: false,
         }
     }
@@ -5,12 +5,19 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct CmdDurationConfig<'a> {
+    /// Shortest duration to show time for (in milliseconds).
     pub min_time: i64,
+    /// The format for the module.
     pub format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Show milliseconds in addition to seconds for the duration.
     pub show_milliseconds: bool,
+    /// Disables the `cmd_duration` module.
     pub disabled: bool,
+    /// Show desktop notifications when command completes.
     pub show_notifications: bool,
+    /// Shortest duration for notification (in milliseconds).
     pub min_time_to_notify: i64,
 }
 
@@ -0,0 +1,42 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct CobolConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
+    pub version_format: &'a str,
+    /// The symbol used before displaying the version of COBOL.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `cobol` module.
+    pub disabled: bool,
+    /// Which extensions should trigger this module.
+    pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
+    pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
+    pub detect_folders: Vec<&'a str>,
+}
+
+impl<'a> Default for CobolConfig<'a> {
+    fn default() -> Self {
+        CobolConfig {
+            format: "via [$symbol($version )]($style)",
+            version_format: "v${raw}",
+            symbol: "⚙️ ",
+            symbol_fallback: "cobol ",
+            style: "bold blue",
+            disabled: false,
+            detect_extensions: vec!["cbl", "cob", "CBL", "COB"],
+            detect_files: vec![],
+            detect_folders: vec![],
+        }
+    }
+}
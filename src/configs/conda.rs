@@ -5,11 +5,21 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct CondaConfig<'a> {
+    /// The number of directories the environment path should be truncated to, if the environment
+    /// was created via `conda create -p [path]`. `0` means no truncation. Also see the `directory`
+    /// module.
     pub truncation_length: usize,
+    /// The format for the module.
     pub format: &'a str,
+    /// The symbol used before the environment name.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Ignores `base` environment when activated.
     pub ignore_base: bool,
+    /// Disables the `conda` module.
     pub disabled: bool,
 }
 
@@ -19,6 +29,7 @@ impl<'a> Default for CondaConfig<'a> {
             truncation_length: 1,
             format: "via [$symbol$environment]($style) ",
             symbol: "🅒 ",
+            symbol_fallback: "conda ",
             style: "green bold",
             ignore_base: true,
             disabled: false,
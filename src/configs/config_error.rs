@@ -0,0 +1,30 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct ConfigErrorConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The symbol shown when the configuration file has problems.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `config_error` module.
+    pub disabled: bool,
+}
+
+impl<'a> Default for ConfigErrorConfig<'a> {
+    fn default() -> Self {
+        ConfigErrorConfig {
+            format: "[$symbol$count]($style) ",
+            symbol: "⚠ ",
+            symbol_fallback: "! ",
+            style: "bold red",
+            disabled: false,
+        }
+    }
+}
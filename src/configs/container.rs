@@ -0,0 +1,44 @@
+use crate::config::ModuleConfig;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct ContainerConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The symbol used for containers that aren't in `symbols`.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The symbol used for each runtime, `toolbox` or `distrobox`.
+    pub symbols: IndexMap<String, &'a str>,
+    /// The symbols used in place of `symbols` when `use_symbol_fallback` is enabled.
+    pub symbols_fallback: IndexMap<String, &'a str>,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `container` module.
+    pub disabled: bool,
+}
+
+impl<'a> Default for ContainerConfig<'a> {
+    fn default() -> Self {
+        let mut symbols = IndexMap::new();
+        symbols.insert("toolbox".to_string(), "⬢ ");
+        symbols.insert("distrobox".to_string(), "📦 ");
+        let mut symbols_fallback = IndexMap::new();
+        symbols_fallback.insert("toolbox".to_string(), "toolbox ");
+        symbols_fallback.insert("distrobox".to_string(), "distrobox ");
+
+        ContainerConfig {
+            format: "[$symbol(\\[$name\\] )]($style)",
+            symbol: "⬢ ",
+            symbol_fallback: "container ",
+            symbols,
+            symbols_fallback,
+            style: "red bold dimmed",
+            disabled: false,
+        }
+    }
+}
@@ -5,18 +5,35 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct CustomConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The symbol used before displaying the command output.
     pub symbol: &'a str,
+    /// The command whose output should be printed. The command will be passed on stdin to the
+    /// shell.
     pub command: &'a str,
+    /// A shell command used as a condition to show the module. The module will be shown if the
+    /// command returns a `0` status code.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub when: Option<&'a str>,
+    /// The command used to run `command`, followed by its arguments. The default depends on the
+    /// OS.
     pub shell: VecOr<&'a str>,
+    /// The description of the module that is shown when running `starship explain`.
     pub description: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables this `custom` module.
     pub disabled: bool,
+    /// The files that will be searched in the working directory for a match.
     pub files: Vec<&'a str>,
+    /// The extensions that will be searched in the working directory for a match.
     pub extensions: Vec<&'a str>,
+    /// The directories that will be searched in the working directory for a match.
     pub directories: Vec<&'a str>,
+    /// The position of the module in `${custom}`, lower first. Modules with the same `order`
+    /// keep the order of the config file.
+    pub order: i64,
 }
 
 impl<'a> Default for CustomConfig<'a> {
@@ -33,6 +50,7 @@ impl<'a> Default for CustomConfig<'a> {
             files: Vec::default(),
             extensions: Vec::default(),
             directories: Vec::default(),
+            order: 0,
         }
     }
 }
@@ -0,0 +1,42 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct DamlConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
+    pub version_format: &'a str,
+    /// A format string representing the symbol of Daml.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `daml` module.
+    pub disabled: bool,
+    /// Which extensions should trigger this module.
+    pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
+    pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
+    pub detect_folders: Vec<&'a str>,
+}
+
+impl<'a> Default for DamlConfig<'a> {
+    fn default() -> Self {
+        DamlConfig {
+            format: "via [$symbol($version )]($style)",
+            version_format: "v${raw}",
+            symbol: "Λ ",
+            symbol_fallback: "daml ",
+            style: "bold cyan",
+            disabled: false,
+            detect_extensions: vec![],
+            detect_files: vec!["daml.yaml"],
+            detect_folders: vec![],
+        }
+    }
+}
@@ -5,13 +5,23 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct DenoConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// A format string representing the symbol of Deno.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `deno` module.
     pub disabled: bool,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -21,6 +31,7 @@ impl<'a> Default for DenoConfig<'a> {
             format: "via [$symbol($version )]($style)",
             version_format: "v${raw}",
             symbol: "🦕 ",
+            symbol_fallback: "deno ",
             style: "green bold",
             disabled: false,
             detect_extensions: vec![],
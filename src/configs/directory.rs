@@ -6,18 +6,47 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct DirectoryConfig<'a> {
+    /// The number of parent folders that the current directory should be truncated to.
     pub truncation_length: i64,
+    /// Whether or not to truncate to the root of the git repo that you're currently in.
     pub truncate_to_repo: bool,
+    /// A table of substitutions to be made to the path.
     pub substitutions: IndexMap<String, &'a str>,
+    /// The number of characters to use when applying fish shell pwd path logic.
     pub fish_style_pwd_dir_length: i64,
+    /// Render the logical path from `PWD` or `--logical-path` rather than the physical path.
     pub use_logical_path: bool,
+    /// The format for the module.
     pub format: &'a str,
+    /// The format for the module when `repo_root_style` is set and the repo root is visible in the
+    /// path.
+    pub repo_root_format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// The style for the root of the git repo. Setting it enables `repo_root_format`.
+    pub repo_root_style: Option<&'a str>,
+    /// The style for the part of the path before the root of the git repo. Defaults to `style`.
+    pub before_repo_root_style: Option<&'a str>,
+    /// Disables the `directory` module.
     pub disabled: bool,
+    /// The symbol indicating current directory is read only.
     pub read_only: &'a str,
+    /// The style for the read only symbol.
     pub read_only_style: &'a str,
+    /// The symbol to prefix to truncated paths. eg: "…/".
     pub truncation_symbol: &'a str,
+    /// The symbol indicating home directory.
     pub home_symbol: &'a str,
+    /// Show the previous directory (`OLDPWD`) when it differs from the current one.
+    pub show_previous: bool,
+    /// Under WSL, show paths on mounted Windows drives by their Windows name, e.g. `C:/Users`.
+    pub translate_wsl_paths: bool,
+    /// A command printing a short name for the directory given as its last argument, shown as
+    /// `$alias`. `""` disables it.
+    pub alias_command: &'a str,
+    /// Reuse the `$alias` of a directory for this many milliseconds within a session. `0` runs
+    /// `alias_command` on every prompt.
+    pub alias_refresh_interval_ms: u64,
 }
 
 impl<'a> Default for DirectoryConfig<'a> {
@@ -28,13 +57,20 @@ impl<'a> Default for DirectoryConfig<'a> {
             fish_style_pwd_dir_length: 0,
             use_logical_path: true,
             substitutions: IndexMap::new(),
-            format: "[$path]($style)[$read_only]($read_only_style) ",
+            format: "[$path]($style)[$read_only]($read_only_style)[( from $previous)]($style) ",
+            repo_root_format: "[$before_root_path]($before_repo_root_style)[$repo_root]($repo_root_style)[$path]($style)[$read_only]($read_only_style)[( from $previous)]($style) ",
             style: "cyan bold",
+            repo_root_style: None,
+            before_repo_root_style: None,
             disabled: false,
             read_only: "🔒",
             read_only_style: "red",
             truncation_symbol: "",
             home_symbol: "~",
+            show_previous: false,
+            translate_wsl_paths: false,
+            alias_command: "",
+            alias_refresh_interval_ms: 0,
         }
     }
 }
@@ -5,13 +5,23 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct DockerContextConfig<'a> {
+    /// The symbol used before displaying the Docker context.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// The format for the module.
     pub format: &'a str,
+    /// Only show when there's a match.
     pub only_with_files: bool,
+    /// Disables the `docker_context` module.
     pub disabled: bool,
+    /// Which extensions should trigger this module (needs `only_with_files` to be true).
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module (needs `only_with_files` to be true).
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module (needs `only_with_files` to be true).
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -19,6 +29,7 @@ impl<'a> Default for DockerContextConfig<'a> {
     fn default() -> Self {
         DockerContextConfig {
             symbol: "🐳 ",
+            symbol_fallback: "docker ",
             style: "blue bold",
             format: "via [$symbol$context]($style) ",
             only_with_files: true,
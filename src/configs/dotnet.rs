@@ -5,14 +5,25 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct DotnetConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// The symbol used before displaying the version of dotnet.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Use faster version detection to keep starship snappy.
     pub heuristic: bool,
+    /// Disables the `dotnet` module.
     pub disabled: bool,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this modules.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -22,6 +33,7 @@ impl<'a> Default for DotnetConfig<'a> {
             format: "[$symbol($version )(🎯 $tfm )]($style)",
             version_format: "v${raw}",
             symbol: ".NET ",
+            symbol_fallback: ".NET ",
             style: "blue bold",
             heuristic: true,
             disabled: false,
@@ -5,13 +5,19 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct EnvVarConfig<'a> {
+    /// The symbol used before displaying the variable value.
     pub symbol: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// The environment variable to be displayed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub variable: Option<&'a str>,
+    /// The default value to be displayed when the selected variable is not defined.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<&'a str>,
+    /// The format for the module.
     pub format: &'a str,
+    /// Disables the `env_var` module.
     pub disabled: bool,
 }
 
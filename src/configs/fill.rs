@@ -0,0 +1,24 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct FillConfig<'a> {
+    /// The symbol repeated to fill the rest of the line.
+    pub symbol: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `fill` module.
+    pub disabled: bool,
+}
+
+impl<'a> Default for FillConfig<'a> {
+    fn default() -> Self {
+        FillConfig {
+            symbol: ".",
+            style: "bold black",
+            disabled: false,
+        }
+    }
+}
@@ -5,11 +5,20 @@ use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct GcloudConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The symbol used before displaying the current GCP profile.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `gcloud` module.
     pub disabled: bool,
+    /// Table of region aliases to display in addition to the GCP name.
     pub region_aliases: HashMap<String, &'a str>,
+    /// The symbol shown in `$credentials` when a service account key is in use.
+    pub service_account_symbol: &'a str,
 }
 
 impl<'a> Default for GcloudConfig<'a> {
@@ -17,9 +26,11 @@ impl<'a> Default for GcloudConfig<'a> {
         GcloudConfig {
             format: "on [$symbol$account(@$domain)(\\($region\\))]($style) ",
             symbol: "☁️  ",
+            symbol_fallback: "gcp ",
             style: "bold blue",
             disabled: false,
             region_aliases: HashMap::new(),
+            service_account_symbol: "🔑 ",
         }
     }
 }
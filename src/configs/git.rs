@@ -0,0 +1,22 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, Default, ModuleConfig, Serialize)]
+pub struct GitConfig<'a> {
+    /// Repositories to use where git finds none, e.g. a bare dotfiles repo.
+    pub extra_repositories: Vec<ExtraRepositoryConfig<'a>>,
+}
+
+/// A repository git can't discover from its work tree, like a bare repository of dotfiles
+/// checked out in the home directory
+#[derive(Clone, Default, ModuleConfig, Serialize)]
+pub struct ExtraRepositoryConfig<'a> {
+    /// The git directory of the repository.
+    pub git_dir: &'a str,
+    /// The directory the repository is checked out in.
+    pub work_tree: &'a str,
+    /// The name shown for the repository as `$repo_alias`.
+    pub alias: Option<&'a str>,
+}
@@ -1,31 +1,73 @@
 use crate::config::ModuleConfig;
 
+use indexmap::IndexMap;
 use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct GitBranchConfig<'a> {
+    /// The format for the module. Use `"$branch"` to refer to the current branch name.
     pub format: &'a str,
+    /// A format string representing the symbol of git branch.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Truncates a git branch to `N` graphemes.
     pub truncation_length: i64,
+    /// The symbol used to indicate a branch name was truncated. You can use `""` for no symbol.
     pub truncation_symbol: &'a str,
+    /// Only show the branch name when not in a detached `HEAD` state.
     pub only_attached: bool,
+    /// Shows the remote tracking branch name, even if it is equal to the local branch name.
     pub always_show_remote: bool,
+    /// A regex matched against the branch name to find `pr_number`, using its first capture group
+    /// if it has one.
+    pub pr_number_pattern: &'a str,
+    /// Rules overriding `style` and `symbol` when their regex matches the whole `branch`.
+    pub style_rules: Vec<GitBranchStyleRule<'a>>,
+    /// The `remote_symbol` of remotes whose host isn't in `remote_symbols`.
+    pub remote_symbol: &'a str,
+    /// The `remote_symbol` of each hosting provider, matched against the labels of the host of
+    /// the remote URL.
+    pub remote_symbols: IndexMap<String, &'a str>,
+    /// Disables the `git_branch` module.
     pub disabled: bool,
 }
 
 impl<'a> Default for GitBranchConfig<'a> {
     fn default() -> Self {
+        let mut remote_symbols = IndexMap::new();
+        remote_symbols.insert("github".to_string(), " ");
+        remote_symbols.insert("gitlab".to_string(), " ");
+        remote_symbols.insert("bitbucket".to_string(), " ");
+
         GitBranchConfig {
-            format: "on [$symbol$branch]($style)(:[$remote]($style)) ",
+            format: "on [$symbol$branch]($style) ",
             symbol: " ",
+            symbol_fallback: "git ",
             style: "bold purple",
             truncation_length: std::i64::MAX,
             truncation_symbol: "…",
             only_attached: false,
             always_show_remote: false,
+            pr_number_pattern: "",
+            style_rules: Vec::new(),
+            remote_symbol: " ",
+            remote_symbols,
             disabled: false,
         }
     }
 }
+
+/// Overrides `style` and `symbol` when `pattern` matches the whole branch name
+#[derive(Clone, Default, ModuleConfig, Serialize)]
+pub struct GitBranchStyleRule<'a> {
+    /// A regex that must match the whole branch name.
+    pub pattern: &'a str,
+    /// The style used instead of `style` for matching branches.
+    pub style: Option<&'a str>,
+    /// The symbol used instead of `symbol` for matching branches.
+    pub symbol: Option<&'a str>,
+}
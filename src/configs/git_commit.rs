@@ -5,13 +5,22 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct GitCommitConfig<'a> {
+    /// The length of the displayed git commit hash.
     pub commit_hash_length: usize,
+    /// The format for the module.
     pub format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Only show git commit hash when in detached `HEAD` state.
     pub only_detached: bool,
+    /// Disables the `git_commit` module.
     pub disabled: bool,
+    /// Tag symbol prefixing the info shown.
     pub tag_symbol: &'a str,
+    /// Disables showing tag info in `git_commit` module.
     pub tag_disabled: bool,
+    /// Only show annotated tags, ignoring lightweight ones.
+    pub tag_annotated_only: bool,
 }
 
 impl<'a> Default for GitCommitConfig<'a> {
@@ -25,6 +34,7 @@ impl<'a> Default for GitCommitConfig<'a> {
             disabled: false,
             tag_symbol: " 🏷  ",
             tag_disabled: true,
+            tag_annotated_only: false,
         }
     }
 }
@@ -5,15 +5,25 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct GitStateConfig<'a> {
+    /// A format string displayed when a `rebase` is in progress.
     pub rebase: &'a str,
+    /// A format string displayed when a `merge` is in progress.
     pub merge: &'a str,
+    /// A format string displayed when a `revert` is in progress.
     pub revert: &'a str,
+    /// A format string displayed when a `cherry-pick` is in progress.
     pub cherry_pick: &'a str,
+    /// A format string displayed when a `bisect` is in progress.
     pub bisect: &'a str,
+    /// A format string displayed when an `apply-mailbox` (`git am`) is in progress.
     pub am: &'a str,
+    /// A format string displayed when an ambiguous `apply-mailbox` or `rebase` is in progress.
     pub am_or_rebase: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// The format for the module.
     pub format: &'a str,
+    /// Disables the `git_state` module.
     pub disabled: bool,
 }
 
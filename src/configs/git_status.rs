@@ -5,18 +5,53 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct GitStatusConfig<'a> {
+    /// The default format for `git_status`
     pub format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// The format of `stashed`
     pub stashed: &'a str,
+    /// The format of `ahead`
     pub ahead: &'a str,
+    /// The format of `behind`
     pub behind: &'a str,
+    /// The format of `diverged`
     pub diverged: &'a str,
+    /// The format of `gone`, shown instead of the others of `ahead_behind` when the upstream
+    /// branch was deleted.
+    pub gone: &'a str,
+    /// This branch has merge conflicts.
     pub conflicted: &'a str,
+    /// The format of `deleted`
     pub deleted: &'a str,
+    /// The format of `renamed`
     pub renamed: &'a str,
+    /// The format of `modified`
     pub modified: &'a str,
+    /// The format of `staged`
     pub staged: &'a str,
+    /// The format of `untracked`
     pub untracked: &'a str,
+    /// The format of `clean`, shown when there are no changes. Ahead and behind commits are
+    /// ignored.
+    pub clean: &'a str,
+    /// A ref to compare with for `base_ahead` and `base_behind`, like `"origin/main"`.
+    pub base_branch: &'a str,
+    /// The format of `base_ahead`
+    pub base_ahead: &'a str,
+    /// The format of `base_behind`
+    pub base_behind: &'a str,
+    /// Only count files matching these git pathspecs (relative to the repo root).
+    pub pathspec: Vec<&'a str>,
+    /// How `git status` detects renamed files: `"off"`, `"renames"` or `"copies"` (which also
+    /// counts copies as renamed).
+    pub rename_detection: &'a str,
+    /// Which untracked files `git status` looks for: `"normal"`, `"no"` or `"all"`. Empty uses
+    /// the `status.showUntrackedFiles` git config.
+    pub untracked_files: &'a str,
+    /// Let `git status` take optional locks to refresh the index, unlike the default.
+    pub take_optional_locks: bool,
+    /// Disables the `git_status` module.
     pub disabled: bool,
 }
 
@@ -29,12 +64,21 @@ impl<'a> Default for GitStatusConfig<'a> {
             ahead: "⇡",
             behind: "⇣",
             diverged: "⇕",
+            gone: "×",
             conflicted: "=",
             deleted: "✘",
             renamed: "»",
             modified: "!",
             staged: "+",
             untracked: "?",
+            clean: "",
+            base_branch: "",
+            base_ahead: "⇡$count",
+            base_behind: "⇣$count",
+            pathspec: vec![],
+            rename_detection: "renames",
+            untracked_files: "",
+            take_optional_locks: false,
             disabled: false,
         }
     }
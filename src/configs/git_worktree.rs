@@ -0,0 +1,30 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct GitWorktreeConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The symbol shown before the name of the worktree.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `git_worktree` module.
+    pub disabled: bool,
+}
+
+impl<'a> Default for GitWorktreeConfig<'a> {
+    fn default() -> Self {
+        GitWorktreeConfig {
+            format: "in [$symbol$worktree]($style) ",
+            symbol: "🌲 ",
+            symbol_fallback: "worktree ",
+            style: "bold green",
+            disabled: false,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct GleamConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
+    pub version_format: &'a str,
+    /// A format string representing the symbol of Gleam.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `gleam` module.
+    pub disabled: bool,
+    /// Which extensions should trigger this module.
+    pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
+    pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
+    pub detect_folders: Vec<&'a str>,
+}
+
+impl<'a> Default for GleamConfig<'a> {
+    fn default() -> Self {
+        GleamConfig {
+            format: "via [$symbol($version )]($style)",
+            version_format: "v${raw}",
+            symbol: "⭐ ",
+            symbol_fallback: "gleam ",
+            style: "bold #FFAFF3",
+            disabled: false,
+            detect_extensions: vec!["gleam"],
+            detect_files: vec!["gleam.toml"],
+            detect_folders: vec![],
+        }
+    }
+}
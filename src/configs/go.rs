@@ -5,13 +5,26 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct GoConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// A format string representing the symbol of Go.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Show the version pinned in `.mise.toml`, `.mise/config.toml` or `.tool-versions` instead of
+    /// asking `go`.
+    pub prefer_pinned: bool,
+    /// Disables the `golang` module.
     pub disabled: bool,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -21,7 +34,9 @@ impl<'a> Default for GoConfig<'a> {
             format: "via [$symbol($version )]($style)",
             version_format: "v${raw}",
             symbol: "🐹 ",
+            symbol_fallback: "go ",
             style: "bold cyan",
+            prefer_pinned: true,
             disabled: false,
             detect_extensions: vec!["go"],
             detect_files: vec![
@@ -0,0 +1,48 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct HaxeConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
+    pub version_format: &'a str,
+    /// A format string representing the symbol of Haxe.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `haxe` module.
+    pub disabled: bool,
+    /// Which extensions should trigger this module.
+    pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
+    pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
+    pub detect_folders: Vec<&'a str>,
+}
+
+impl<'a> Default for HaxeConfig<'a> {
+    fn default() -> Self {
+        HaxeConfig {
+            format: "via [$symbol($version )]($style)",
+            version_format: "v${raw}",
+            symbol: "⌘ ",
+            symbol_fallback: "hx ",
+            style: "bold fg:202",
+            disabled: false,
+            detect_extensions: vec!["hx", "hxml"],
+            detect_files: vec![
+                "project.xml",
+                "Project.xml",
+                "application.xml",
+                "haxelib.json",
+                ".haxerc",
+            ],
+            detect_folders: vec![],
+        }
+    }
+}
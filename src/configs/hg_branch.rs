@@ -5,11 +5,19 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct HgBranchConfig<'a> {
+    /// The symbol used before the hg bookmark or branch name of the repo in your current directory.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// The format for the module. Use `"$branch"` to refer to the current branch name.
     pub format: &'a str,
+    /// Truncates the hg branch name to `N` graphemes.
     pub truncation_length: i64,
+    /// The symbol used to indicate a branch name was truncated. You can use `""` for no symbol.
     pub truncation_symbol: &'a str,
+    /// Disables the `hg_branch` module.
     pub disabled: bool,
 }
 
@@ -17,6 +25,7 @@ impl<'a> Default for HgBranchConfig<'a> {
     fn default() -> Self {
         HgBranchConfig {
             symbol: " ",
+            symbol_fallback: "hg ",
             style: "bold purple",
             format: "on [$symbol$branch]($style) ",
             truncation_length: std::i64::MAX,
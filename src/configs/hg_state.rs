@@ -0,0 +1,42 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct HgStateConfig<'a> {
+    /// A format string displayed when a `rebase` is in progress.
+    pub rebase: &'a str,
+    /// A format string displayed when a `merge` is in progress.
+    pub merge: &'a str,
+    /// A format string displayed when a `histedit` is in progress.
+    pub histedit: &'a str,
+    /// A format string displayed when a `graft` is in progress.
+    pub graft: &'a str,
+    /// A format string displayed when an `unshelve` is in progress.
+    pub unshelve: &'a str,
+    /// A format string displayed when a `bisect` is in progress.
+    pub bisect: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// The format for the module.
+    pub format: &'a str,
+    /// Disables the `hg_state` module.
+    pub disabled: bool,
+}
+
+impl<'a> Default for HgStateConfig<'a> {
+    fn default() -> Self {
+        HgStateConfig {
+            rebase: "REBASING",
+            merge: "MERGING",
+            histedit: "HISTEDITING",
+            graft: "GRAFTING",
+            unshelve: "UNSHELVING",
+            bisect: "BISECTING",
+            style: "bold yellow",
+            format: "\\([$state( $progress_current/$progress_total)]($style)\\) ",
+            disabled: true,
+        }
+    }
+}
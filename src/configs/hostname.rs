@@ -5,10 +5,16 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct HostnameConfig<'a> {
+    /// Only show hostname when connected to an SSH session.
     pub ssh_only: bool,
+    /// String that the hostname is cut off at, after the first match. `"."` will stop after the
+    /// first dot. `""` will disable any truncation.
     pub trim_at: &'a str,
+    /// The format for the module.
     pub format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `hostname` module.
     pub disabled: bool,
 }
 
@@ -5,13 +5,26 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct JavaConfig<'a> {
+    /// Show the version pinned in `.mise.toml`, `.mise/config.toml` or `.tool-versions` instead of
+    /// asking `java`.
+    pub prefer_pinned: bool,
+    /// Disables the `java` module.
     pub disabled: bool,
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// A format string representing the symbol of Java.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -20,9 +33,11 @@ impl<'a> Default for JavaConfig<'a> {
         JavaConfig {
             format: "via [$symbol($version )]($style)",
             version_format: "v${raw}",
+            prefer_pinned: true,
             disabled: false,
             style: "red dimmed",
             symbol: "☕ ",
+            symbol_fallback: "java ",
             detect_extensions: vec!["java", "class", "jar", "gradle", "clj", "cljc"],
             detect_files: vec![
                 "pom.xml",
@@ -5,10 +5,17 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct JobsConfig<'a> {
+    /// Show number of jobs if exceeded.
     pub threshold: i64,
+    /// The format for the module.
     pub format: &'a str,
+    /// A format string representing the number of jobs.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `jobs` module.
     pub disabled: bool,
 }
 
@@ -18,6 +25,7 @@ impl<'a> Default for JobsConfig<'a> {
             threshold: 1,
             format: "[$symbol$number]($style) ",
             symbol: "✦",
+            symbol_fallback: "*",
             style: "bold blue",
             disabled: false,
         }
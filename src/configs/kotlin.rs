@@ -5,14 +5,25 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct KotlinConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// A format string representing the symbol of Kotlin.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Configures the kotlin binary that Starship executes when getting the version.
     pub kotlin_binary: &'a str,
+    /// Disables the `kotlin` module.
     pub disabled: bool,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this modules.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -22,6 +33,7 @@ impl<'a> Default for KotlinConfig<'a> {
             format: "via [$symbol($version )]($style)",
             version_format: "v${raw}",
             symbol: "🅺 ",
+            symbol_fallback: "kt ",
             style: "bold blue",
             kotlin_binary: "kotlin",
             disabled: false,
@@ -6,10 +6,17 @@ use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct KubernetesConfig<'a> {
+    /// A format string representing the symbol displayed before the Cluster.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The format for the module.
     pub format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `kubernetes` module.
     pub disabled: bool,
+    /// Table of context aliases to display.
     pub context_aliases: HashMap<String, &'a str>,
 }
 
@@ -17,6 +24,7 @@ impl<'a> Default for KubernetesConfig<'a> {
     fn default() -> Self {
         KubernetesConfig {
             symbol: "☸ ",
+            symbol_fallback: "k8s ",
             format: "[$symbol$context( \\($namespace\\))]($style) in ",
             style: "cyan bold",
             disabled: true,
@@ -5,21 +5,32 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct MemoryConfig<'a> {
+    /// Hide the memory usage unless it exceeds this percentage.
     pub threshold: i64,
+    /// The format for the module.
     pub format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// The symbol used before displaying the memory usage.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// Disables the `memory_usage` module.
     pub disabled: bool,
+    /// How long, in milliseconds, the memory usage is reused before it is read again.
+    pub refresh_interval_ms: u64,
 }
 
 impl<'a> Default for MemoryConfig<'a> {
     fn default() -> Self {
         MemoryConfig {
             threshold: 75,
-            format: "via $symbol[$ram( | $swap)]($style) ",
+            format: "via [$symbol$ram( | $swap)]($style) ",
             style: "white bold dimmed",
             symbol: "🐏 ",
+            symbol_fallback: "mem ",
             disabled: true,
+            refresh_interval_ms: 5_000,
         }
     }
 }
@@ -8,9 +8,13 @@ pub mod battery;
 pub mod character;
 pub mod cmake;
 pub mod cmd_duration;
+pub mod cobol;
 pub mod conda;
+pub mod config_error;
+pub mod container;
 pub mod crystal;
 pub mod custom;
+pub mod daml;
 pub mod dart;
 pub mod deno;
 pub mod directory;
@@ -20,14 +24,21 @@ pub mod elixir;
 pub mod elm;
 pub mod env_var;
 pub mod erlang;
+pub mod fennel;
+pub mod fill;
 pub mod gcloud;
+pub mod git;
 pub mod git_branch;
 pub mod git_commit;
 pub mod git_state;
 pub mod git_status;
+pub mod git_worktree;
+pub mod gleam;
 pub mod go;
+pub mod haxe;
 pub mod helm;
 pub mod hg_branch;
+pub mod hg_state;
 pub mod hostname;
 pub mod java;
 pub mod jobs;
@@ -36,6 +47,7 @@ pub mod kotlin;
 pub mod kubernetes;
 pub mod lua;
 pub mod memory_usage;
+pub mod new_version_available;
 pub mod nim;
 pub mod nix_shell;
 pub mod nodejs;
@@ -44,6 +56,7 @@ pub mod openstack;
 pub mod package;
 pub mod perl;
 pub mod php;
+pub mod project_context;
 pub mod purescript;
 pub mod python;
 pub mod red;
@@ -51,9 +64,11 @@ pub mod rlang;
 pub mod ruby;
 pub mod rust;
 pub mod scala;
+pub mod separators;
 pub mod shell;
 pub mod shlvl;
 pub mod singularity;
+pub mod slow_fs;
 mod starship_root;
 pub mod status;
 pub mod swift;
@@ -65,6 +80,7 @@ pub mod vagrant;
 pub mod vcsh;
 pub mod zig;
 
+pub use crate::modules::PROMPT_ORDER;
 pub use starship_root::*;
 
 #[derive(Serialize, ModuleConfig, Clone)]
@@ -72,17 +88,44 @@ pub use starship_root::*;
 pub struct FullConfig<'a> {
     // Root config
     pub format: &'a str,
+    pub compact_format: &'a str,
+    pub compact_width_threshold: u64,
+    pub right_format: &'a str,
+    pub right_format_line: &'a str,
     pub scan_timeout: u64,
+    pub detect_case_sensitive: bool,
     pub command_timeout: u64,
+    pub fs_timeout_ms: u64,
+    pub prompt_timeout_ms: u64,
     pub add_newline: bool,
+    pub overflow_symbol: &'a str,
+    pub cache_max_age_ms: u64,
+    pub cache_ttl_days: i64,
+    pub cache_while_index_locked: bool,
+    pub default_order: Vec<&'a str>,
+    pub offline: bool,
+    pub update_check: bool,
+    pub update_check_interval: u64,
+    pub update_check_url: &'a str,
+    pub separators: separators::SeparatorsConfig<'a>,
+    pub styles: IndexMap<String, &'a str>,
+    pub trusted_directories: Vec<&'a str>,
+    pub include: Vec<&'a str>,
+    pub git: git::GitConfig<'a>,
+    pub repo_discovery_boundaries: Vec<&'a str>,
+    pub symbol_padding: &'a str,
     // modules
     aws: aws::AwsConfig<'a>,
-    battery: battery::BatteryDisplayConfig<'a>,
+    battery: battery::BatteryConfig<'a>,
     character: character::CharacterConfig<'a>,
     cmake: cmake::CMakeConfig<'a>,
     cmd_duration: cmd_duration::CmdDurationConfig<'a>,
+    cobol: cobol::CobolConfig<'a>,
     conda: conda::CondaConfig<'a>,
+    config_error: config_error::ConfigErrorConfig<'a>,
+    container: container::ContainerConfig<'a>,
     crystal: crystal::CrystalConfig<'a>,
+    daml: daml::DamlConfig<'a>,
     dart: dart::DartConfig<'a>,
     deno: deno::DenoConfig<'a>,
     directory: directory::DirectoryConfig<'a>,
@@ -92,14 +135,20 @@ pub struct FullConfig<'a> {
     elm: elm::ElmConfig<'a>,
     env_var: env_var::EnvVarConfig<'a>,
     erlang: erlang::ErlangConfig<'a>,
+    fennel: fennel::FennelConfig<'a>,
+    fill: fill::FillConfig<'a>,
     gcloud: gcloud::GcloudConfig<'a>,
     git_branch: git_branch::GitBranchConfig<'a>,
     git_commit: git_commit::GitCommitConfig<'a>,
     git_state: git_state::GitStateConfig<'a>,
     git_status: git_status::GitStatusConfig<'a>,
+    git_worktree: git_worktree::GitWorktreeConfig<'a>,
+    gleam: gleam::GleamConfig<'a>,
     golang: go::GoConfig<'a>,
+    haxe: haxe::HaxeConfig<'a>,
     helm: helm::HelmConfig<'a>,
     hg_branch: hg_branch::HgBranchConfig<'a>,
+    hg_state: hg_state::HgStateConfig<'a>,
     hostname: hostname::HostnameConfig<'a>,
     java: java::JavaConfig<'a>,
     jobs: jobs::JobsConfig<'a>,
@@ -108,6 +157,7 @@ pub struct FullConfig<'a> {
     kubernetes: kubernetes::KubernetesConfig<'a>,
     lua: lua::LuaConfig<'a>,
     memory_usage: memory_usage::MemoryConfig<'a>,
+    new_version_available: new_version_available::NewVersionAvailableConfig<'a>,
     nim: nim::NimConfig<'a>,
     nix_shell: nix_shell::NixShellConfig<'a>,
     nodejs: nodejs::NodejsConfig<'a>,
@@ -116,6 +166,7 @@ pub struct FullConfig<'a> {
     package: package::PackageConfig<'a>,
     perl: perl::PerlConfig<'a>,
     php: php::PhpConfig<'a>,
+    project_context: project_context::ProjectContextConfig<'a>,
     purescript: purescript::PureScriptConfig<'a>,
     python: python::PythonConfig<'a>,
     rlang: rlang::RLangConfig<'a>,
@@ -126,6 +177,7 @@ pub struct FullConfig<'a> {
     shell: shell::ShellConfig<'a>,
     shlvl: shlvl::ShLvlConfig<'a>,
     singularity: singularity::SingularityConfig<'a>,
+    slow_fs: slow_fs::SlowFsConfig<'a>,
     status: status::StatusConfig<'a>,
     swift: swift::SwiftConfig<'a>,
     terraform: terraform::TerraformConfig<'a>,
@@ -133,6 +185,7 @@ pub struct FullConfig<'a> {
     username: username::UsernameConfig<'a>,
     vlang: v::VConfig<'a>,
     vagrant: vagrant::VagrantConfig<'a>,
+    vcsh: vcsh::VcshConfig<'a>,
     zig: zig::ZigConfig<'a>,
     custom: IndexMap<String, custom::CustomConfig<'a>>,
 }
@@ -141,17 +194,44 @@ impl<'a> Default for FullConfig<'a> {
     fn default() -> Self {
         Self {
             format: "$all",
+            compact_format: "",
+            compact_width_threshold: 0,
+            right_format: "",
+            right_format_line: "last",
             scan_timeout: 30,
+            detect_case_sensitive: false,
             command_timeout: 500,
+            fs_timeout_ms: 0,
+            prompt_timeout_ms: 0,
             add_newline: true,
+            overflow_symbol: "",
+            cache_max_age_ms: 0,
+            cache_ttl_days: 7,
+            cache_while_index_locked: true,
+            default_order: PROMPT_ORDER.to_vec(),
+            offline: false,
+            update_check: false,
+            update_check_interval: 7,
+            update_check_url: UPDATE_CHECK_URL,
+            separators: Default::default(),
+            styles: IndexMap::new(),
+            trusted_directories: Vec::new(),
+            include: Vec::new(),
+            git: Default::default(),
+            repo_discovery_boundaries: Vec::new(),
+            symbol_padding: "auto",
 
             aws: Default::default(),
             battery: Default::default(),
             character: Default::default(),
             cmake: Default::default(),
             cmd_duration: Default::default(),
+            cobol: Default::default(),
             conda: Default::default(),
+            config_error: Default::default(),
+            container: Default::default(),
             crystal: Default::default(),
+            daml: Default::default(),
             dart: Default::default(),
             deno: Default::default(),
             directory: Default::default(),
@@ -161,14 +241,20 @@ impl<'a> Default for FullConfig<'a> {
             elm: Default::default(),
             env_var: Default::default(),
             erlang: Default::default(),
+            fennel: Default::default(),
+            fill: Default::default(),
             gcloud: Default::default(),
             git_branch: Default::default(),
             git_commit: Default::default(),
             git_state: Default::default(),
             git_status: Default::default(),
+            git_worktree: Default::default(),
+            gleam: Default::default(),
             golang: Default::default(),
+            haxe: Default::default(),
             helm: Default::default(),
             hg_branch: Default::default(),
+            hg_state: Default::default(),
             hostname: Default::default(),
             java: Default::default(),
             jobs: Default::default(),
@@ -177,6 +263,7 @@ impl<'a> Default for FullConfig<'a> {
             kubernetes: Default::default(),
             lua: Default::default(),
             memory_usage: Default::default(),
+            new_version_available: Default::default(),
             nim: Default::default(),
             nix_shell: Default::default(),
             nodejs: Default::default(),
@@ -185,6 +272,7 @@ impl<'a> Default for FullConfig<'a> {
             package: Default::default(),
             perl: Default::default(),
             php: Default::default(),
+            project_context: Default::default(),
             purescript: Default::default(),
             python: Default::default(),
             red: Default::default(),
@@ -195,15 +283,211 @@ impl<'a> Default for FullConfig<'a> {
             shell: Default::default(),
             shlvl: Default::default(),
             singularity: Default::default(),
+            slow_fs: Default::default(),
             status: Default::default(),
             swift: Default::default(),
             terraform: Default::default(),
             time: Default::default(),
             username: Default::default(),
             vagrant: Default::default(),
+            vcsh: Default::default(),
             vlang: Default::default(),
             zig: Default::default(),
             custom: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RootModuleConfig;
+    use crate::config_check::check_config;
+    use crate::formatter::{
+        StringFormatter, StyleVariableHolder, VariableHolder, VersionFormatter,
+    };
+    use crate::module::ALL_MODULES;
+    use crate::test::ModuleRenderer;
+
+    /// A directory with every file, extension and folder the modules detect
+    fn project_dir(config: &toml::Value) -> std::io::Result<tempfile::TempDir> {
+        let dir = tempfile::tempdir()?;
+        let entries = |module: &toml::Value, key: &str| -> Vec<String> {
+            module
+                .get(key)
+                .and_then(toml::Value::as_array)
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter_map(|entry| entry.as_str().map(str::to_owned))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        for module in config.as_table().unwrap().values() {
+            for file in entries(module, "detect_files") {
+                std::fs::File::create(dir.path().join(file))?;
+            }
+            for extension in entries(module, "detect_extensions") {
+                std::fs::File::create(dir.path().join(format!("detected.{}", extension)))?;
+            }
+            for folder in entries(module, "detect_folders") {
+                std::fs::create_dir_all(dir.path().join(folder))?;
+            }
+        }
+        Ok(dir)
+    }
+
+    /// `text` without the escape codes of its styles
+    fn strip_styles(text: &str) -> String {
+        let mut stripped = String::new();
+        let mut in_escape = false;
+        for c in text.chars() {
+            match c {
+                '\x1b' => in_escape = true,
+                'm' if in_escape => in_escape = false,
+                _ if in_escape => (),
+                c => stripped.push(c),
+            }
+        }
+        stripped
+    }
+
+    #[test]
+    fn empty_symbol_leaves_no_stray_spaces() -> std::io::Result<()> {
+        let config = toml::Value::try_from(FullConfig::default()).unwrap();
+        let dir = project_dir(&config)?;
+        let home = tempfile::tempdir()?;
+        let mut rendered = 0;
+
+        for module in ALL_MODULES {
+            // Modules without a config, like `line_break`, have nothing to check
+            let defaults = match config.get(module) {
+                Some(defaults) if defaults.get("symbol").is_some() => defaults,
+                _ => continue,
+            };
+            let mut module_config = toml::value::Table::new();
+            module_config.insert("symbol".to_owned(), toml::Value::from(""));
+            module_config.insert("disabled".to_owned(), toml::Value::from(false));
+            // Show the modules hidden below a threshold, like `memory_usage`
+            if let Some(toml::Value::Integer(_)) = defaults.get("threshold") {
+                module_config.insert("threshold".to_owned(), toml::Value::from(0));
+            }
+            let mut module_configs = toml::value::Table::new();
+            module_configs.insert(module.to_string(), toml::Value::Table(module_config));
+
+            let output = match ModuleRenderer::new(module)
+                .path(dir.path())
+                .env("HOME", home.path().to_string_lossy())
+                .config(toml::Value::Table(module_configs))
+                .collect()
+            {
+                Some(output) => strip_styles(&output),
+                None => continue,
+            };
+            rendered += 1;
+
+            assert!(
+                !output.starts_with(' ') && !output.contains("  ") && !output.ends_with("  "),
+                "`{}` renders {:?} with an empty symbol",
+                module,
+                output
+            );
+        }
+
+        assert!(rendered >= 20, "only {} modules were rendered", rendered);
+        home.close()?;
+        dir.close()
+    }
+
+    /// The default config as `print-config --default` writes it
+    fn printed_default_config() -> String {
+        let config = toml::Value::try_from(FullConfig::default()).unwrap();
+        toml::to_string_pretty(&config).unwrap()
+    }
+
+    #[test]
+    fn every_module_has_a_default_config() {
+        let config = toml::Value::try_from(FullConfig::default()).unwrap();
+
+        for module in ALL_MODULES.iter().filter(|module| **module != "line_break") {
+            assert!(
+                matches!(config.get(module), Some(toml::Value::Table(_))),
+                "`{}` has no default config",
+                module
+            );
+        }
+    }
+
+    #[test]
+    fn printed_default_config_loads_back_unchanged() {
+        let printed = printed_default_config();
+        let parsed: toml::Value = toml::from_str(&printed).unwrap();
+        let loaded = toml::Value::try_from(FullConfig::try_load(Some(&parsed))).unwrap();
+        let defaults = toml::Value::try_from(FullConfig::default()).unwrap();
+
+        for (key, default) in defaults.as_table().unwrap() {
+            assert_eq!(
+                loaded.get(key),
+                Some(default),
+                "`{}` changes when its default config is printed and loaded back",
+                key
+            );
+        }
+        assert_eq!(loaded, defaults);
+    }
+
+    #[test]
+    fn printed_default_config_has_no_problems() {
+        let printed = printed_default_config();
+
+        let problems: Vec<String> = check_config(&printed)
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect();
+        assert!(problems.is_empty(), "{:#?}", problems);
+    }
+
+    #[test]
+    fn default_formats_use_declared_variables() {
+        let config = toml::Value::try_from(FullConfig::default()).unwrap();
+
+        for module in ALL_MODULES {
+            let module_config = match config.get(module).and_then(toml::Value::as_table) {
+                Some(module_config) => module_config,
+                None => continue,
+            };
+            let module_variables = crate::modules::variables(module);
+            let formats = module_config.iter().filter_map(|(key, value)| match value {
+                toml::Value::String(format) if key == "format" || key.ends_with("_format") => {
+                    Some((key, format))
+                }
+                _ => None,
+            });
+
+            for (key, format) in formats {
+                let declared = match key.as_str() {
+                    "version_format" => VersionFormatter::VARIABLES,
+                    _ => module_variables,
+                };
+                let formatter = StringFormatter::new(format).unwrap_or_else(|error| {
+                    panic!("`{}.{}` doesn't parse: {:?}", module, key, error)
+                });
+                let used = formatter
+                    .get_variables()
+                    .into_iter()
+                    .chain(formatter.get_style_variables());
+                for variable in used {
+                    assert!(
+                        declared.contains(&variable.as_str()),
+                        "`{}.{}` uses `${}`, which the module doesn't declare",
+                        module,
+                        key,
+                        variable
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct NewVersionAvailableConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The symbol shown before the new version.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `new_version_available` module.
+    pub disabled: bool,
+}
+
+impl<'a> Default for NewVersionAvailableConfig<'a> {
+    fn default() -> Self {
+        NewVersionAvailableConfig {
+            format: "[$symbol$version]($style) ",
+            symbol: "⬆ ",
+            symbol_fallback: "update ",
+            style: "bold green",
+            disabled: false,
+        }
+    }
+}
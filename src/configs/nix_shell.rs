@@ -5,11 +5,19 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct NixShellConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// A format string representing the symbol of nix-shell.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// A format string shown when the shell is impure.
     pub impure_msg: &'a str,
+    /// A format string shown when the shell is pure.
     pub pure_msg: &'a str,
+    /// Disables the `nix_shell` module.
     pub disabled: bool,
 }
 
@@ -21,6 +29,7 @@ impl<'a> Default for NixShellConfig<'a> {
         NixShellConfig {
             format: "via [$symbol$state( \\($name\\))]($style) ",
             symbol: "❄️  ",
+            symbol_fallback: "nix ",
             style: "bold blue",
             impure_msg: "impure",
             pure_msg: "pure",
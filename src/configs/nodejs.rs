@@ -5,14 +5,29 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct NodejsConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// A format string representing the symbol of Node.js.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Show the version pinned in `.mise.toml`, `.mise/config.toml` or `.tool-versions` instead of
+    /// asking `node`.
+    pub prefer_pinned: bool,
+    /// Disables the `nodejs` module.
     pub disabled: bool,
+    /// The style for the module when an engines property in package.json does not match the Node.js
+    /// version.
     pub not_capable_style: &'a str,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -22,7 +37,9 @@ impl<'a> Default for NodejsConfig<'a> {
             format: "via [$symbol($version )]($style)",
             version_format: "v${raw}",
             symbol: " ",
+            symbol_fallback: "node ",
             style: "bold green",
+            prefer_pinned: true,
             disabled: false,
             not_capable_style: "bold red",
             detect_extensions: vec!["js", "mjs", "cjs", "ts"],
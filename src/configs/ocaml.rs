@@ -5,15 +5,27 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct OCamlConfig<'a> {
+    /// The format string for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// The format string used to represent global OPAM switch.
     pub global_switch_indicator: &'a str,
+    /// The format string used to represent local OPAM switch.
     pub local_switch_indicator: &'a str,
+    /// The symbol used before displaying the version of OCaml.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `ocaml` module.
     pub disabled: bool,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -25,6 +37,7 @@ impl<'a> Default for OCamlConfig<'a> {
             global_switch_indicator: "",
             local_switch_indicator: "*",
             symbol: "🐫 ",
+            symbol_fallback: "ml ",
             style: "bold yellow",
             disabled: false,
             detect_extensions: vec!["opam", "ml", "mli", "re", "rei"],
@@ -4,9 +4,15 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct OspConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The symbol used before displaying the current OpenStack cloud.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `openstack` module.
     pub disabled: bool,
 }
 
@@ -15,6 +21,7 @@ impl<'a> Default for OspConfig<'a> {
         OspConfig {
             format: "on [$symbol$cloud(\\($project\\))]($style) ",
             symbol: "☁️  ",
+            symbol_fallback: "os ",
             style: "bold yellow",
             disabled: false,
         }
@@ -5,10 +5,17 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct PackageConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The symbol used before displaying the version the package.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Enable displaying version for packages marked as private.
     pub display_private: bool,
+    /// Disables the `package` module.
     pub disabled: bool,
 }
 
@@ -17,6 +24,7 @@ impl<'a> Default for PackageConfig<'a> {
         PackageConfig {
             format: "is [$symbol$version]($style) ",
             symbol: "📦 ",
+            symbol_fallback: "pkg ",
             style: "208 bold",
             display_private: false,
             disabled: false,
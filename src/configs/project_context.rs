@@ -0,0 +1,30 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct ProjectContextConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The symbol used when the `.starship-context` file doesn't set one.
+    pub symbol: &'a str,
+    /// The style used when the `.starship-context` file doesn't set one.
+    pub style: &'a str,
+    /// The width the text of the file is shortened to, in columns.
+    pub max_length: usize,
+    /// Disables the `project_context` module.
+    pub disabled: bool,
+}
+
+impl<'a> Default for ProjectContextConfig<'a> {
+    fn default() -> Self {
+        ProjectContextConfig {
+            format: "[$symbol$text]($style) ",
+            symbol: "",
+            style: "bold yellow",
+            max_length: 32,
+            disabled: false,
+        }
+    }
+}
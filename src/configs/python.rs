@@ -5,16 +5,32 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct PythonConfig<'a> {
+    /// Use pyenv to get Python version.
     pub pyenv_version_name: bool,
+    /// Prefix before pyenv version display, only used if pyenv is used.
     pub pyenv_prefix: &'a str,
+    /// Configures the python binaries that Starship should executes when getting the version.
     pub python_binary: VecOr<&'a str>,
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// A format string representing the symbol of Python.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// Show the version pinned in `.mise.toml`, `.mise/config.toml` or `.tool-versions` instead of
+    /// asking `python`.
+    pub prefer_pinned: bool,
+    /// Disables the `python` module.
     pub disabled: bool,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -28,6 +44,8 @@ impl<'a> Default for PythonConfig<'a> {
             version_format: "v${raw}",
             style: "yellow bold",
             symbol: "🐍 ",
+            symbol_fallback: "py ",
+            prefer_pinned: true,
             disabled: false,
             detect_extensions: vec!["py"],
             detect_files: vec![
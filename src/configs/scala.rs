@@ -5,13 +5,23 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct ScalaConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
     pub version_format: &'a str,
+    /// Disables the `scala` module.
     pub disabled: bool,
+    /// The style for the module.
     pub style: &'a str,
+    /// A format string representing the symbol of Scala.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -23,6 +33,7 @@ impl<'a> Default for ScalaConfig<'a> {
             disabled: false,
             style: "red bold",
             symbol: "🆂 ",
+            symbol_fallback: "scala ",
             detect_extensions: vec!["sbt", "scala"],
             detect_files: vec![".scalaenv", ".sbtenv", "build.sbt"],
             detect_folders: vec![".metals"],
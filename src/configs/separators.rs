@@ -0,0 +1,41 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct SeparatorsConfig<'a> {
+    /// The separator between two modules. It is left unstyled between modules without a background.
+    pub left_separator: &'a str,
+    /// The separator leading from a module without a background into one with a background.
+    pub right_separator: &'a str,
+    /// `"powerline"` to insert separators, `"plain"` to leave modules as they are.
+    pub mode: &'a str,
+}
+
+impl<'a> SeparatorsConfig<'a> {
+    /// Whether separators should be inserted between modules
+    pub fn is_powerline(&self) -> bool {
+        match self.mode {
+            "powerline" => true,
+            "plain" => false,
+            unknown => {
+                log::warn!(
+                    "Unknown separators mode '{}', expected \"powerline\" or \"plain\"",
+                    unknown
+                );
+                false
+            }
+        }
+    }
+}
+
+impl<'a> Default for SeparatorsConfig<'a> {
+    fn default() -> Self {
+        SeparatorsConfig {
+            left_separator: "",
+            right_separator: "",
+            mode: "plain",
+        }
+    }
+}
@@ -5,15 +5,25 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct ShellConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// A format string used to represent bash.
     pub bash_indicator: &'a str,
+    /// A format string used to represent fish.
     pub fish_indicator: &'a str,
+    /// A format string used to represent zsh.
     pub zsh_indicator: &'a str,
+    /// A format string used to represent powershell.
     pub powershell_indicator: &'a str,
+    /// A format string used to represent ion.
     pub ion_indicator: &'a str,
+    /// A format string used to represent elvish.
     pub elvish_indicator: &'a str,
+    /// A format string used to represent tcsh.
     pub tcsh_indicator: &'a str,
+    /// The default value to be displayed when the shell is unknown.
     pub unknown_indicator: &'a str,
+    /// Disables the `shell` module.
     pub disabled: bool,
 }
 
@@ -5,11 +5,19 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct ShLvlConfig<'a> {
+    /// Display threshold.
     pub threshold: i64,
+    /// The format for the module.
     pub format: &'a str,
+    /// The symbol used to represent the `SHLVL`.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// Causes `symbol` to be repeated by the current `SHLVL` amount.
     pub repeat: bool,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `shlvl` module.
     pub disabled: bool,
 }
 
@@ -19,6 +27,7 @@ impl<'a> Default for ShLvlConfig<'a> {
             threshold: 2,
             format: "[$symbol$shlvl]($style) ",
             symbol: "↕️  ", // extra space for emoji
+            symbol_fallback: "shlvl ",
             repeat: false,
             style: "bold yellow",
             disabled: true,
@@ -5,9 +5,13 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct SingularityConfig<'a> {
+    /// A format string displayed before the image name.
     pub symbol: &'a str,
+    /// The format for the module.
     pub format: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `singularity` module.
     pub disabled: bool,
 }
 
@@ -0,0 +1,30 @@
+use crate::config::ModuleConfig;
+
+use serde::Serialize;
+use starship_module_config_derive::ModuleConfig;
+
+#[derive(Clone, ModuleConfig, Serialize)]
+pub struct SlowFsConfig<'a> {
+    /// The format for the module.
+    pub format: &'a str,
+    /// The symbol shown when filesystem checks were skipped.
+    pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
+    pub style: &'a str,
+    /// Disables the `slow_fs` module.
+    pub disabled: bool,
+}
+
+impl<'a> Default for SlowFsConfig<'a> {
+    fn default() -> Self {
+        SlowFsConfig {
+            format: "[$symbol]($style)",
+            symbol: "🐌 ",
+            symbol_fallback: "slow ",
+            style: "bold yellow",
+            disabled: false,
+        }
+    }
+}
@@ -1,97 +1,145 @@
-use crate::{config::ModuleConfig, module::ALL_MODULES};
+use crate::{
+    config::{warn_unknown_key, ModuleConfig},
+    configs::{git::GitConfig, separators::SeparatorsConfig},
+    module::{is_module_name, ALL_MODULES},
+    modules::PROMPT_ORDER,
+};
 
+use indexmap::IndexMap;
 use serde::Serialize;
-use std::cmp::Ordering;
+
+/// The latest starship release, as returned by the GitHub API
+pub const UPDATE_CHECK_URL: &str = "https://api.github.com/repos/starship/starship/releases/latest";
 
 // On changes please also update the `FullConfig` struct in `mod.rs`
 #[derive(Clone, Serialize)]
 pub struct StarshipRootConfig<'a> {
+    /// Configure the format of the prompt.
     pub format: &'a str,
+    /// An alternative `format` for narrow terminals, see `compact_width_threshold`. `""` disables
+    /// it.
+    pub compact_format: &'a str,
+    /// Use `compact_format` when the terminal is at most this many columns wide. `0` disables it.
+    pub compact_width_threshold: u64,
+    /// The format of the right prompt, printed by `prompt --right` for the shells that have
+    /// one. `""` disables it.
+    pub right_format: &'a str,
+    /// The line of a multi-line prompt the shell shows the right prompt on, `"first"` or
+    /// `"last"`. The fills of that line leave room for it.
+    pub right_format_line: &'a str,
+    /// Timeout for starship to scan files (in milliseconds).
     pub scan_timeout: u64,
+    /// Match the `detect_extensions` of modules case-sensitively. Entries like `"d.ts"` match
+    /// compound extensions.
+    pub detect_case_sensitive: bool,
+    /// Timeout for commands executed by starship (in milliseconds).
     pub command_timeout: u64,
+    /// Skip the remaining filesystem checks, like looking for a repository, once they took this
+    /// many milliseconds in total. `0` disables it.
+    pub fs_timeout_ms: u64,
+    /// Leave out the modules that are not done after this many milliseconds (`character` falls back
+    /// to `> `). `0` disables it.
+    pub prompt_timeout_ms: u64,
+    /// Inserts blank line between shell prompts.
     pub add_newline: bool,
+    /// Appended to a line of the prompt where a module was shortened to its `max_length`. `""`
+    /// disables it.
+    pub overflow_symbol: &'a str,
+    /// Reuse the previous prompt of the session for this long while nothing it depends on changed.
+    /// `0` disables it.
+    pub cache_max_age_ms: u64,
+    /// Remove the session files older than this many days from the cache directory, at most hourly.
+    /// `0` disables it. See `starship cache gc`.
+    pub cache_ttl_days: i64,
+    /// Keep reusing the cached prompt while `.git/index.lock` exists, even if the git index
+    /// changed.
+    pub cache_while_index_locked: bool,
+    /// The order of the modules `$all` expands to. Modules left out are appended in the default
+    /// order.
+    pub default_order: Vec<&'a str>,
+    /// Never access the network, even from modules or commands that would otherwise do so.
+    pub offline: bool,
+    /// Look for a newer release in the background for the `new_version_available` module.
+    pub update_check: bool,
+    /// Look for a newer release at most once in this many days.
+    pub update_check_interval: u64,
+    /// Where the latest release is looked up, either a GitHub release as JSON or a plain tag.
+    pub update_check_url: &'a str,
+    /// Separators inserted between modules.
+    pub separators: SeparatorsConfig<'a>,
+    /// Named styles that style strings can reference.
+    pub styles: IndexMap<String, &'a str>,
+    /// Glob patterns of directories where custom modules and `env` tables are used even if another
+    /// user owns them.
+    pub trusted_directories: Vec<&'a str>,
+    /// Globs of other config files merged over this one.
+    pub include: Vec<&'a str>,
+    /// Git repositories that are found by their work tree, like a bare dotfiles repo.
+    pub git: GitConfig<'a>,
+    /// Directories the search for a repository doesn't go up into, like
+    /// `GIT_CEILING_DIRECTORIES`.
+    pub repo_discovery_boundaries: Vec<&'a str>,
+    /// Append a space to `symbol`s: `"auto"` when they end with a double-width character like
+    /// an emoji, `"space"` always, or `"none"`.
+    pub symbol_padding: &'a str,
 }
 
-// List of default prompt order
-// NOTE: If this const value is changed then Default prompt order subheading inside
-// prompt heading of config docs needs to be updated according to changes made here.
-pub const PROMPT_ORDER: &[&str] = &[
-    "username",
-    "hostname",
-    "shlvl",
-    "singularity",
-    "kubernetes",
-    "directory",
-    "vcsh",
-    "git_branch",
-    "git_commit",
-    "git_state",
-    "git_status",
-    "hg_branch",
-    "docker_context",
-    "package",
-    // ↓ Toolchain version modules ↓
-    // (Let's keep these sorted alphabetically)
-    "cmake",
-    "dart",
-    "deno",
-    "dotnet",
-    "elixir",
-    "elm",
-    "erlang",
-    "golang",
-    "helm",
-    "java",
-    "julia",
-    "kotlin",
-    "lua",
-    "nim",
-    "nodejs",
-    "ocaml",
-    "perl",
-    "php",
-    "purescript",
-    "python",
-    "rlang",
-    "red",
-    "ruby",
-    "rust",
-    "scala",
-    "swift",
-    "terraform",
-    "vlang",
-    "vagrant",
-    "zig",
-    // ↑ Toolchain version modules ↑
-    "nix_shell",
-    "conda",
-    "memory_usage",
-    "aws",
-    "gcloud",
-    "openstack",
-    "env_var",
-    "crystal",
-    "custom",
-    "cmd_duration",
-    "line_break",
-    "jobs",
-    #[cfg(feature = "battery")]
-    "battery",
-    "time",
-    "status",
-    "shell",
-    "character",
-];
-
 // On changes please also update `Default` for the `FullConfig` struct in `mod.rs`
 impl<'a> Default for StarshipRootConfig<'a> {
     fn default() -> Self {
         StarshipRootConfig {
             format: "$all",
+            compact_format: "",
+            compact_width_threshold: 0,
+            right_format: "",
+            right_format_line: "last",
             scan_timeout: 30,
+            detect_case_sensitive: false,
             command_timeout: 500,
+            fs_timeout_ms: 0,
+            prompt_timeout_ms: 0,
             add_newline: true,
+            overflow_symbol: "",
+            cache_max_age_ms: 0,
+            cache_ttl_days: 7,
+            cache_while_index_locked: true,
+            default_order: PROMPT_ORDER.to_vec(),
+            offline: false,
+            update_check: false,
+            update_check_interval: 7,
+            update_check_url: UPDATE_CHECK_URL,
+            separators: SeparatorsConfig::default(),
+            styles: IndexMap::new(),
+            trusted_directories: Vec::new(),
+            include: Vec::new(),
+            git: GitConfig::default(),
+            repo_discovery_boundaries: Vec::new(),
+            symbol_padding: "auto",
+        }
+    }
+}
+
+impl<'a> StarshipRootConfig<'a> {
+    /// The order of the modules `$all` expands to: the modules of `default_order`, followed by
+    /// the ones it doesn't mention in the built-in order.
+    pub fn prompt_order(&self) -> Vec<&'a str> {
+        let mut order: Vec<&'a str> = Vec::new();
+
+        for module in &self.default_order {
+            if !is_module_name(module) {
+                log::warn!("Unknown module '{}' in `default_order`", module);
+            } else if !order.contains(module) {
+                order.push(module);
+            }
+        }
+
+        for module in PROMPT_ORDER {
+            if !order.contains(module) {
+                order.push(module);
+            }
         }
+
+        order
     }
 }
 
@@ -100,37 +148,75 @@ impl<'a> ModuleConfig<'a> for StarshipRootConfig<'a> {
         if let toml::Value::Table(config) = config {
             config.iter().for_each(|(k, v)| match k.as_str() {
                 "format" => self.format.load_config(v),
+                "compact_format" => self.compact_format.load_config(v),
+                "compact_width_threshold" => self.compact_width_threshold.load_config(v),
+                "right_format" => self.right_format.load_config(v),
+                "right_format_line" => self.right_format_line.load_config(v),
                 "scan_timeout" => self.scan_timeout.load_config(v),
+                "detect_case_sensitive" => self.detect_case_sensitive.load_config(v),
                 "command_timeout" => self.command_timeout.load_config(v),
+                "fs_timeout_ms" => self.fs_timeout_ms.load_config(v),
+                "prompt_timeout_ms" => self.prompt_timeout_ms.load_config(v),
                 "add_newline" => self.add_newline.load_config(v),
+                "overflow_symbol" => self.overflow_symbol.load_config(v),
+                "cache_max_age_ms" => self.cache_max_age_ms.load_config(v),
+                "cache_ttl_days" => self.cache_ttl_days.load_config(v),
+                "cache_while_index_locked" => self.cache_while_index_locked.load_config(v),
+                "default_order" => self.default_order.load_config(v),
+                "offline" => self.offline.load_config(v),
+                "update_check" => self.update_check.load_config(v),
+                "update_check_interval" => self.update_check_interval.load_config(v),
+                "update_check_url" => self.update_check_url.load_config(v),
+                "separators" => self.separators.load_config(v),
+                "styles" => self.styles.load_config(v),
+                "trusted_directories" => self.trusted_directories.load_config(v),
+                "include" => self.include.load_config(v),
+                "git" => self.git.load_config(v),
+                "repo_discovery_boundaries" => self.repo_discovery_boundaries.load_config(v),
+                "symbol_padding" => self.symbol_padding.load_config(v),
                 unknown => {
-                    if !ALL_MODULES.contains(&unknown) && unknown != "custom" {
-                        log::warn!("Unknown config key '{}'", unknown);
-
-                        let did_you_mean = &[
+                    if !ALL_MODULES.contains(&unknown)
+                        && unknown != "custom"
+                        && unknown != "module_aliases"
+                    {
+                        let fields: Vec<&str> = [
                             // Root options
                             "format",
+                            "compact_format",
+                            "compact_width_threshold",
+                            "right_format",
+                            "right_format_line",
                             "scan_timeout",
+                            "detect_case_sensitive",
                             "command_timeout",
+                            "fs_timeout_ms",
+                            "prompt_timeout_ms",
                             "add_newline",
+                            "overflow_symbol",
+                            "cache_max_age_ms",
+                            "cache_ttl_days",
+                            "cache_while_index_locked",
+                            "default_order",
+                            "offline",
+                            "update_check",
+                            "update_check_interval",
+                            "update_check_url",
+                            "separators",
+                            "styles",
+                            "trusted_directories",
+                            "include",
+                            "git",
+                            "repo_discovery_boundaries",
+                            "symbol_padding",
                             // Modules
                             "custom",
+                            "module_aliases",
                         ]
                         .iter()
                         .chain(ALL_MODULES.iter())
-                        .filter_map(|field| {
-                            let score = strsim::jaro_winkler(unknown, field);
-                            (score > 0.8).then(|| (score, field))
-                        })
-                        .max_by(
-                            |(score_a, _field_a), (score_b, _field_b)| {
-                                score_a.partial_cmp(score_b).unwrap_or(Ordering::Equal)
-                            },
-                        );
-
-                        if let Some((_score, field)) = did_you_mean {
-                            log::warn!("Did you mean '{}'?", field);
-                        }
+                        .copied()
+                        .collect();
+                        warn_unknown_key(unknown, &fields);
                     }
                 }
             });
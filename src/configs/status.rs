@@ -2,18 +2,35 @@ use crate::config::ModuleConfig;
 
 use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
+use std::collections::HashMap;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct StatusConfig<'a> {
+    /// The format of the module.
     pub format: &'a str,
+    /// The symbol displayed on program error.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The symbol displayed when the last command succeeded.
+    pub success_symbol: &'a str,
+    /// The symbol displayed when file isn't executable.
     pub not_executable_symbol: &'a str,
+    /// The symbol displayed when the command can't be found.
     pub not_found_symbol: &'a str,
+    /// The symbol displayed on SIGINT (Ctrl + c)
     pub sigint_symbol: &'a str,
+    /// The symbol displayed on any signal.
     pub signal_symbol: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Styles to use for specific exit codes, code ranges or classes.
+    pub style_map: HashMap<String, &'a str>,
+    /// Enable symbols mapping from exit code.
     pub map_symbol: bool,
+    /// Enable signal mapping from exit code.
     pub recognize_signal_code: bool,
+    /// Disables the `status` module.
     pub disabled: bool,
 }
 
@@ -22,11 +39,14 @@ impl<'a> Default for StatusConfig<'a> {
         StatusConfig {
             format: "[$symbol$status]($style) ",
             symbol: "✖",
+            symbol_fallback: "x",
+            success_symbol: "",
             not_executable_symbol: "🚫",
             not_found_symbol: "🔍",
             sigint_symbol: "🧱",
             signal_symbol: "⚡",
             style: "bold red",
+            style_map: HashMap::new(),
             map_symbol: false,
             recognize_signal_code: true,
             disabled: true,
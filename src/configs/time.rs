@@ -5,14 +5,26 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct TimeConfig<'a> {
+    /// The format string for the module.
     pub format: &'a str,
+    /// The style for the module time.
     pub style: &'a str,
+    /// Enables 12 hour formatting.
     pub use_12hr: bool,
+    /// The chrono format string used to format the time.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_format: Option<&'a str>,
+    /// Disables the `time` module.
     pub disabled: bool,
+    /// Sets the UTC offset to use. Range from -24 &lt; x &lt; 24. Allows floats to accommodate
+    /// 30/45 minute timezone offsets.
     pub utc_time_offset: &'a str,
+    /// Sets the time range during which the module will be shown. Times must be specified in
+    /// 24-hours format.
     pub time_range: &'a str,
+    /// The locale of weekday and month names, like `"de-DE"`. Defaults to `LC_ALL`, `LC_TIME` or
+    /// `LANG`.
+    pub locale: &'a str,
 }
 
 impl<'a> Default for TimeConfig<'a> {
@@ -25,6 +37,7 @@ impl<'a> Default for TimeConfig<'a> {
             disabled: true,
             utc_time_offset: "local",
             time_range: "-",
+            locale: "",
         }
     }
 }
@@ -5,10 +5,15 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct UsernameConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The style used when the user is root.
     pub style_root: &'a str,
+    /// The style used for non-root users.
     pub style_user: &'a str,
+    /// Always shows the `username` module.
     pub show_always: bool,
+    /// Disables the `username` module.
     pub disabled: bool,
 }
 
@@ -5,12 +5,23 @@ use starship_module_config_derive::ModuleConfig;
 
 #[derive(Clone, ModuleConfig, Serialize)]
 pub struct VConfig<'a> {
+    /// The format for the module.
     pub format: &'a str,
+    /// The version format. Available vars are `raw`, `major`, `minor`, & `patch`
+    pub version_format: &'a str,
+    /// A format string representing the symbol of V.
     pub symbol: &'a str,
+    /// The symbol used in place of `symbol` when `use_symbol_fallback` is enabled.
+    pub symbol_fallback: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// Disables the `vlang` module.
     pub disabled: bool,
+    /// Which extensions should trigger this module.
     pub detect_extensions: Vec<&'a str>,
+    /// Which filenames should trigger this module.
     pub detect_files: Vec<&'a str>,
+    /// Which folders should trigger this module.
     pub detect_folders: Vec<&'a str>,
 }
 
@@ -18,7 +29,9 @@ impl<'a> Default for VConfig<'a> {
     fn default() -> Self {
         VConfig {
             format: "via [$symbol($version )]($style)",
+            version_format: "v${raw}",
             symbol: "V ",
+            symbol_fallback: "V ",
             style: "blue bold",
             disabled: false,
             detect_extensions: vec!["v"],
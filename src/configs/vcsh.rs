@@ -1,12 +1,17 @@
 use crate::config::ModuleConfig;
 
+use serde::Serialize;
 use starship_module_config_derive::ModuleConfig;
 
-#[derive(Clone, ModuleConfig)]
+#[derive(Clone, ModuleConfig, Serialize)]
 pub struct VcshConfig<'a> {
+    /// The symbol used before displaying the repository name.
     pub symbol: &'a str,
+    /// The style for the module.
     pub style: &'a str,
+    /// The format for the module.
     pub format: &'a str,
+    /// Disables the `vcsh` module.
     pub disabled: bool,
 }
 
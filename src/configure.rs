@@ -1,13 +1,13 @@
 use std::env;
 use std::ffi::OsString;
-use std::io::ErrorKind;
+use std::io::{self, ErrorKind};
+use std::path::Path;
 use std::process;
 use std::process::Command;
 
 use crate::config::RootModuleConfig;
-use crate::config::StarshipConfig;
-use std::fs::File;
-use std::io::Write;
+use crate::config::{ConfigFormat, StarshipConfig};
+use crate::utils;
 use toml::map::Map;
 use toml::value::Table;
 use toml::Value;
@@ -124,12 +124,29 @@ pub fn get_configuration() -> Value {
 pub fn write_configuration(table: &mut Table) {
     let config_path = get_config_path();
 
+    if let Err(error) = write_configuration_to(Path::new(&config_path), table) {
+        log::error!(
+            "Unable to write the config file {:?}: {}",
+            config_path,
+            error
+        );
+        process::exit(1);
+    }
+}
+
+/// Writes the config atomically, so that the file is left untouched if the write fails. Only
+/// TOML files are written, as the comments and layout of others couldn't be kept.
+fn write_configuration_to(path: &Path, table: &Table) -> io::Result<()> {
+    if ConfigFormat::of_path(path) != ConfigFormat::Toml {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "only TOML config files can be changed by starship, edit this one instead",
+        ));
+    }
     let config_str =
         toml::to_string_pretty(&table).expect("Failed to serialize the config to string");
 
-    File::create(&config_path)
-        .and_then(|mut file| file.write_all(config_str.as_ref()))
-        .expect("Error writing starship config");
+    utils::write_file_atomic(path, config_str)
 }
 
 pub fn edit_configuration() {
@@ -237,4 +254,39 @@ mod tests {
         let actual = get_editor_internal(None, None);
         assert_eq!(STD_EDITOR, actual);
     }
+
+    #[test]
+    fn write_configuration_keeps_original_on_failure() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("starship.toml");
+        std::fs::write(&config_path, "add_newline = false\n")?;
+        // Nothing can be written next to the config, like on a read-only filesystem
+        std::fs::create_dir(utils::atomic_tmp_path(&config_path))?;
+
+        let mut table = Table::new();
+        table.insert("add_newline".to_string(), Value::Boolean(true));
+        assert!(write_configuration_to(&config_path, &table).is_err());
+        assert_eq!(
+            std::fs::read_to_string(&config_path)?,
+            "add_newline = false\n"
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn write_configuration_refuses_other_formats() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut table = Table::new();
+        table.insert("add_newline".to_string(), Value::Boolean(true));
+
+        for name in &["starship.json", "starship.yaml", "starship.yml"] {
+            let config_path = dir.path().join(name);
+            std::fs::write(&config_path, "{}")?;
+
+            let error = write_configuration_to(&config_path, &table).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidInput);
+            assert_eq!(std::fs::read_to_string(&config_path)?, "{}");
+        }
+        dir.close()
+    }
 }
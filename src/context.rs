@@ -1,20 +1,61 @@
-use crate::config::StarshipConfig;
+use crate::config::{ModuleAlias, StarshipConfig, SymbolPadding};
+use crate::fs_budget::{Clock, FsBudget, SystemClock};
+use crate::logger::ErrorCounter;
 use crate::module::Module;
-use crate::utils::{exec_cmd, CommandOutput};
+use crate::network;
+use crate::startup::{Phase, StartupTimings};
+use crate::utils::{self, exec_cmd, CommandOutput, EnvOverlay};
 
 use crate::modules;
+#[cfg(not(target_os = "windows"))]
+use crate::modules::utils::directory_nix as directory_utils;
+#[cfg(target_os = "windows")]
+use crate::modules::utils::directory_win as directory_utils;
+use crate::modules::utils::pinned_version::PIN_FILES;
 use clap::ArgMatches;
 use dirs_next::home_dir;
-use git2::{ErrorCode::UnbornBranch, Repository, RepositoryState};
+use git2::{ErrorCode::UnbornBranch, Oid, Repository, RepositoryOpenFlags, RepositoryState};
 use once_cell::sync::OnceCell;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::env;
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::string::String;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+/// The environment variables read for the arguments of `prompt` that weren't passed, e.g.
+/// when `starship prompt` is called by hand
+pub const PROPERTY_ENV_FALLBACKS: &[(&str, &str)] = &[
+    ("status_code", "STARSHIP_STATUS"),
+    ("cmd_duration", "STARSHIP_DURATION"),
+    ("jobs", "STARSHIP_JOBS"),
+    ("keymap", "STARSHIP_KEYMAP"),
+];
+
+/// The variables fish sets itself, read after `PROPERTY_ENV_FALLBACKS` when
+/// `STARSHIP_SHELL=fish`
+const FISH_PROPERTY_ENV_FALLBACKS: &[(&str, &str)] =
+    &[("status_code", "status"), ("cmd_duration", "CMD_DURATION")];
+
+thread_local! {
+    /// The alias of `[module_aliases]` whose module is rendering on this thread, see
+    /// `Context::with_module_alias`
+    static RENDERING_ALIAS: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Restores the alias rendering on the thread when dropped, see `Context::with_module_alias`
+struct RenderingAlias(Option<String>);
+
+impl Drop for RenderingAlias {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        RENDERING_ALIAS.with(|alias| alias.replace(previous));
+    }
+}
+
 /// Context contains data or common methods that may be used by multiple modules.
 /// The data contained within Context will be relevant to this particular rendering
 /// of the prompt.
@@ -22,6 +63,13 @@ pub struct Context<'a> {
     /// The deserialized configuration map from the user's `starship.toml` file.
     pub config: StarshipConfig,
 
+    /// Whether `starship.toml` couldn't be read or parsed, so the default config is used
+    pub config_degraded: bool,
+
+    /// The errors logged while rendering the modules of this prompt, see
+    /// `logger::counting_errors`
+    pub errors: Arc<ErrorCounter>,
+
     /// The current working directory that starship is being called in.
     pub current_dir: PathBuf,
 
@@ -33,12 +81,51 @@ pub struct Context<'a> {
     /// A struct containing directory contents in a lookup-optimised format.
     dir_contents: OnceCell<DirContents>,
 
+    /// `detect_case_sensitive` of the root config, read by the first scan of the directory
+    detect_case_sensitive: OnceCell<bool>,
+
     /// Properties to provide to modules.
     pub properties: HashMap<&'a str, String>,
 
+    /// Whether the right prompt is rendered, with `prompt --right`
+    pub right: bool,
+
     /// Private field to store Git information for modules who need it
     repo: OnceCell<Repo>,
 
+    /// The version of the `git` binary, or `None` if it isn't installed
+    git_version: OnceCell<Option<GitVersion>>,
+
+    /// The root of the Mercurial repository containing the current directory, if any
+    hg_root: OnceCell<Option<PathBuf>>,
+
+    /// The `mise` and `asdf` config files of the current directory and its parents
+    pin_files: OnceCell<Vec<(PathBuf, String)>>,
+
+    /// The configuration with ASCII symbols, used when the terminal can't display unicode
+    symbol_fallback_config: OnceCell<StarshipConfig>,
+
+    /// The configuration with padded symbols, if `symbol_padding` changed any
+    symbol_padding_config: OnceCell<Option<StarshipConfig>>,
+
+    /// The aliases of `[module_aliases]` by name
+    module_aliases: OnceCell<HashMap<String, ModuleAlias>>,
+
+    /// Whether the effective user is root, shared by the modules that need it
+    root_user: OnceCell<bool>,
+
+    /// Whether starship runs under WSL
+    wsl: OnceCell<bool>,
+
+    /// Whether the current directory isn't writable, shared by the modules that need it
+    readonly_dir: OnceCell<bool>,
+
+    /// Whether the current directory is trusted to run the commands of the config
+    trusted_dir: OnceCell<bool>,
+
+    /// The time the filesystem checks of the prompt may take, see `fs_timeout_ms`
+    fs_budget: OnceCell<FsBudget>,
+
     /// The shell the user is assumed to be running
     pub shell: Shell,
 
@@ -50,8 +137,47 @@ pub struct Context<'a> {
     #[cfg(test)]
     pub cmd: HashMap<&'a str, Option<CommandOutput>>,
 
+    /// A log of the commands executed through the context, mocked or not
+    #[cfg(test)]
+    pub cmd_log: std::sync::Mutex<Vec<String>>,
+
+    /// A log of the network accesses allowed by the context
+    #[cfg(test)]
+    pub network_log: std::sync::Mutex<Vec<String>>,
+
+    /// A mock of the contents of `/proc/version`
+    #[cfg(test)]
+    pub proc_version: Option<String>,
+
+    /// A mock of the root directory of the filesystem, for the system files of `system_path`
+    #[cfg(test)]
+    pub mock_root_dir: Option<PathBuf>,
+
+    /// A mock of whether the effective user is root
+    #[cfg(test)]
+    pub mock_root_user: Option<bool>,
+
+    /// A mock of whether the current directory is read only
+    #[cfg(test)]
+    pub mock_readonly_dir: Option<bool>,
+
+    /// A mock of whether the current directory and its repository are owned by the current user
+    #[cfg(test)]
+    pub mock_owned_dir: Option<bool>,
+
+    /// A mock of the clock the filesystem checks are timed with
+    #[cfg(test)]
+    pub mock_fs_clock: Option<Arc<dyn crate::fs_budget::Clock>>,
+
+    /// A mock of the clock the `prompt_timeout_ms` deadline is measured with
+    #[cfg(test)]
+    pub mock_prompt_clock: Option<Arc<dyn crate::fs_budget::Clock>>,
+
     /// Timeout for the execution of commands
     cmd_timeout: Duration,
+
+    /// When the phases of the startup up to building the context ended
+    pub startup_timings: StartupTimings,
 }
 
 impl<'a> Context<'a> {
@@ -59,28 +185,55 @@ impl<'a> Context<'a> {
     /// for it. "logical-path" is used when a shell allows the "current working directory"
     /// to be something other than a file system path (like powershell provider specific paths).
     pub fn new(arguments: ArgMatches) -> Context {
+        Context::new_with_timings(arguments, StartupTimings::new())
+    }
+
+    /// Like `new`, recording when the config was parsed and the context built in `timings`
+    pub fn new_with_timings(arguments: ArgMatches, timings: StartupTimings) -> Context {
         let shell = Context::get_shell();
+        let (path, logical_path) = Context::prompt_paths(
+            &arguments,
+            env::current_dir().ok(),
+            env::var("PWD").map(PathBuf::from).ok(),
+        );
 
+        Context::new_with_shell_path_and_timings(arguments, shell, path, logical_path, timings)
+    }
+
+    /// The current and logical directory of the prompt, given the directory of the process
+    /// `process_dir` and the `PWD` of the shell
+    pub fn prompt_paths(
+        arguments: &ArgMatches,
+        process_dir: Option<PathBuf>,
+        pwd: Option<PathBuf>,
+    ) -> (PathBuf, PathBuf) {
         // Retrieve the "current directory".
         // If the path argument is not set fall back to the OS current directory.
         let path = arguments
             .value_of("path")
             .map(PathBuf::from)
-            .or_else(|| env::current_dir().ok())
-            .or_else(|| env::var("PWD").map(PathBuf::from).ok())
+            .or(process_dir)
+            .or_else(|| pwd.clone())
             .or_else(|| arguments.value_of("logical_path").map(PathBuf::from))
             .unwrap_or_default();
 
         // Retrive the "logical directory".
         // If the path argument is not set fall back to the PWD env variable set by many shells
-        // or to the other path.
+        // or to the other path. `PWD` describes the shell's directory, so it isn't used when
+        // rendering the prompt for another `--path`.
         let logical_path = arguments
             .value_of("logical_path")
             .map(PathBuf::from)
-            .or_else(|| env::var("PWD").map(PathBuf::from).ok())
+            .or_else(|| {
+                if arguments.is_present("path") {
+                    None
+                } else {
+                    pwd
+                }
+            })
             .unwrap_or_else(|| path.clone());
 
-        Context::new_with_shell_and_path(arguments, shell, path, logical_path)
+        (path, logical_path)
     }
 
     /// Create a new instance of Context for the provided directory
@@ -90,7 +243,24 @@ impl<'a> Context<'a> {
         path: PathBuf,
         logical_path: PathBuf,
     ) -> Context {
-        let config = StarshipConfig::initialize();
+        Context::new_with_shell_path_and_timings(
+            arguments,
+            shell,
+            path,
+            logical_path,
+            StartupTimings::new(),
+        )
+    }
+
+    fn new_with_shell_path_and_timings(
+        arguments: ArgMatches,
+        shell: Shell,
+        path: PathBuf,
+        logical_path: PathBuf,
+        mut timings: StartupTimings,
+    ) -> Context {
+        let (config, config_degraded) = StarshipConfig::initialize_checked();
+        timings.mark(Phase::Config);
 
         // Unwrap the clap arguments into a simple hashtable
         // we only care about single arguments at this point, there isn't a
@@ -108,21 +278,92 @@ impl<'a> Context<'a> {
         let current_dir = current_dir.canonicalize().unwrap_or(current_dir);
         let logical_dir = logical_path;
 
+        let right = arguments.is_present("right");
         let cmd_timeout = Duration::from_millis(config.get_root_config().command_timeout);
+        timings.mark(Phase::Context);
 
         Context {
             config,
+            config_degraded,
+            errors: Default::default(),
             properties,
+            right,
             current_dir,
             logical_dir,
             dir_contents: OnceCell::new(),
+            detect_case_sensitive: OnceCell::new(),
             repo: OnceCell::new(),
+            git_version: OnceCell::new(),
+            hg_root: OnceCell::new(),
+            pin_files: OnceCell::new(),
+            symbol_fallback_config: OnceCell::new(),
+            symbol_padding_config: OnceCell::new(),
+            module_aliases: OnceCell::new(),
+            root_user: OnceCell::new(),
+            wsl: OnceCell::new(),
+            readonly_dir: OnceCell::new(),
+            trusted_dir: OnceCell::new(),
+            fs_budget: OnceCell::new(),
             shell,
             #[cfg(test)]
             env: HashMap::new(),
             #[cfg(test)]
             cmd: HashMap::new(),
+            #[cfg(test)]
+            cmd_log: Default::default(),
+            #[cfg(test)]
+            network_log: Default::default(),
+            #[cfg(test)]
+            proc_version: None,
+            #[cfg(test)]
+            mock_root_dir: None,
+            #[cfg(test)]
+            mock_root_user: None,
+            #[cfg(test)]
+            mock_readonly_dir: None,
+            #[cfg(test)]
+            mock_owned_dir: None,
+            #[cfg(test)]
+            mock_fs_clock: None,
+            #[cfg(test)]
+            mock_prompt_clock: None,
             cmd_timeout,
+            startup_timings: timings,
+        }
+    }
+
+    /// The width of the terminal in columns, from `--terminal-width` or else the terminal itself
+    pub fn terminal_width(&self) -> Option<usize> {
+        self.properties
+            .get("terminal_width")
+            .and_then(|width| width.parse().ok())
+            .or_else(|| term_size::dimensions().map(|(width, _)| width))
+    }
+
+    /// Whether the prompt is rendered with `compact_format`, because the terminal is at most
+    /// `compact_width_threshold` columns wide
+    pub fn use_compact_format(&self) -> bool {
+        let config = self.config.get_root_config();
+        if config.compact_format.is_empty() || config.compact_width_threshold == 0 {
+            return false;
+        }
+
+        match self.terminal_width() {
+            Some(width) => width as u64 <= config.compact_width_threshold,
+            None => false,
+        }
+    }
+
+    /// The root format of the prompt, `right_format` for the right prompt, `compact_format` in
+    /// narrow terminals and `format` otherwise
+    pub fn prompt_format(&self) -> &str {
+        let config = self.config.get_root_config();
+        if self.right {
+            config.right_format
+        } else if self.use_compact_format() {
+            config.compact_format
+        } else {
+            config.format
         }
     }
 
@@ -159,6 +400,152 @@ impl<'a> Context<'a> {
         env::var_os(key.as_ref())
     }
 
+    /// Whether starship runs under the Windows Subsystem for Linux, either WSL1 or WSL2,
+    /// detected once per prompt by the first module that asks
+    pub fn is_wsl(&self) -> bool {
+        *self.wsl.get_or_init(|| {
+            self.get_env("WSL_DISTRO_NAME").is_some()
+                || self.get_env("WSL_INTEROP").is_some()
+                || self
+                    .get_proc_version()
+                    .map(|version| version.to_lowercase().contains("microsoft"))
+                    .unwrap_or(false)
+        })
+    }
+
+    // Retrieves the kernel version from `/proc/version` or from a mock if in testing mode
+    #[cfg(test)]
+    fn get_proc_version(&self) -> Option<String> {
+        self.proc_version.clone()
+    }
+
+    #[cfg(not(test))]
+    fn get_proc_version(&self) -> Option<String> {
+        crate::utils::read_file("/proc/version").ok()
+    }
+
+    /// The path of a system file given relative to the root directory, e.g. `etc/os-release`,
+    /// which tests can mock
+    pub fn system_path(&self, path: &str) -> PathBuf {
+        #[cfg(test)]
+        if let Some(root_dir) = &self.mock_root_dir {
+            return root_dir.join(path);
+        }
+
+        Path::new("/").join(path)
+    }
+
+    /// Whether the effective user is root (UID = 0)
+    pub fn is_root_user(&self) -> bool {
+        *self.root_user.get_or_init(|| {
+            #[cfg(test)]
+            if let Some(root_user) = self.mock_root_user {
+                return root_user;
+            }
+            detect_root_user()
+        })
+    }
+
+    /// Whether the current directory can't be written to by the current user
+    pub fn is_readonly_dir(&self) -> bool {
+        *self.readonly_dir.get_or_init(|| {
+            #[cfg(test)]
+            if let Some(readonly_dir) = self.mock_readonly_dir {
+                return readonly_dir;
+            }
+            match directory_utils::is_write_allowed(&self.current_dir) {
+                Ok(res) => !res,
+                Err(e) => {
+                    log::debug!(
+                        "Failed to determine read only status of directory '{:?}': {}",
+                        self.current_dir,
+                        e
+                    );
+                    false
+                }
+            }
+        })
+    }
+
+    /// Whether the current directory is trusted to run `custom` modules, apply the `env`
+    /// overlays of modules and show `.starship-context` files: either it and the work tree of
+    /// its git repository are owned by the current user, or it's in one of the
+    /// `trusted_directories`. This is similar to git's `safe.directory`, so that a repository
+    /// cloned by another user can't make starship run commands that are configured for the
+    /// files it contains.
+    ///
+    /// Logs once that those are skipped if it isn't trusted.
+    pub fn is_trusted_dir(&self) -> bool {
+        *self.trusted_dir.get_or_init(|| {
+            let config = self.config.get_root_config();
+            if self.is_in_trusted_directories(&self.current_dir, &config.trusted_directories) {
+                return true;
+            }
+
+            let owned = self.is_owned_dir();
+            if !owned {
+                log::info!(
+                    "Skipping custom modules, `env` overlays and `.starship-context` files in \
+                    {:?}, which isn't owned by the current user nor in `trusted_directories`",
+                    self.current_dir
+                );
+            }
+            owned
+        })
+    }
+
+    /// Whether a file found in `dir`, the current directory or one of its parents, may be shown:
+    /// both `dir` and the file are owned by the current user, or `dir` is in one of the
+    /// `trusted_directories`. Files placed higher up by other users, in `/tmp` for instance,
+    /// are ignored even when the current directory is trusted.
+    pub fn is_trusted_file(&self, dir: &Path, file: &Path) -> bool {
+        let config = self.config.get_root_config();
+        self.is_in_trusted_directories(dir, &config.trusted_directories)
+            || (self.is_owned_path(dir) && self.is_owned_path(file))
+    }
+
+    /// Whether the current directory and the work tree of its git repository, if any, are
+    /// owned by the current user
+    fn is_owned_dir(&self) -> bool {
+        let work_tree = self
+            .discovery_dirs()
+            .into_iter()
+            .find(|dir| self.path_exists(&dir.join(".git")));
+        std::iter::once(self.current_dir.as_path())
+            .chain(work_tree)
+            .all(|dir| self.is_owned_path(dir))
+    }
+
+    fn is_owned_path(&self, path: &Path) -> bool {
+        #[cfg(test)]
+        if let Some(owned) = self.mock_owned_dir {
+            return owned;
+        }
+
+        is_owned_by_current_user(path)
+    }
+
+    /// Whether `dir` or one of its parents matches one of the glob `patterns`, which are
+    /// expanded like other paths of the config
+    fn is_in_trusted_directories(&self, dir: &Path, patterns: &[&str]) -> bool {
+        patterns.iter().any(|pattern| {
+            let pattern = utils::expand_path(pattern, self)
+                .to_string_lossy()
+                .into_owned();
+            match glob::Pattern::new(&pattern) {
+                Ok(pattern) => dir.ancestors().any(|dir| pattern.matches_path(dir)),
+                Err(error) => {
+                    log::warn!(
+                        "Invalid pattern `{}` in `trusted_directories`: {}",
+                        pattern,
+                        error
+                    );
+                    false
+                }
+            }
+        })
+    }
+
     /// Convert a `~` in a path to the home directory
     pub fn expand_tilde(dir: PathBuf) -> PathBuf {
         if dir.starts_with("~") {
@@ -170,12 +557,87 @@ impl<'a> Context<'a> {
 
     /// Create a new module
     pub fn new_module(&self, name: &str) -> Module {
-        let config = self.config.get_module_config(name);
+        let alias_config = RENDERING_ALIAS.with(|alias| {
+            let alias = self.get_module_alias(alias.borrow().as_deref()?)?;
+            Some(&alias.config).filter(|_| alias.module == name)
+        });
+        let config = alias_config.or_else(|| self.module_configs().get_module_config(name));
         let desc = modules::description(name);
 
         Module::new(name, desc, config)
     }
 
+    /// The alias of `[module_aliases]` with the given name
+    pub fn get_module_alias(&self, name: &str) -> Option<&ModuleAlias> {
+        self.module_aliases
+            .get_or_init(|| self.module_configs().get_module_aliases())
+            .get(name)
+    }
+
+    /// Calls `render` with the modules created by `new_module` on this thread using the config
+    /// of the alias `name`. Everything else, like the repo, is shared with the other modules.
+    pub fn with_module_alias<T>(&self, name: &str, render: impl FnOnce() -> T) -> T {
+        let previous = RENDERING_ALIAS.with(|alias| alias.replace(Some(name.to_string())));
+        let _restore = RenderingAlias(previous);
+        render()
+    }
+
+    /// The configuration the modules render with, where the symbols are adjusted to the terminal
+    /// by `symbol_fallback` and `symbol_padding`
+    pub fn module_configs(&self) -> &StarshipConfig {
+        let config = if self.use_symbol_fallback() {
+            self.symbol_fallback_config
+                .get_or_init(|| self.config.with_symbol_fallbacks())
+        } else {
+            &self.config
+        };
+
+        self.symbol_padding_config
+            .get_or_init(|| {
+                let padding = self.config.get_root_config().symbol_padding;
+                let padding = SymbolPadding::parse(padding).unwrap_or_else(|| {
+                    log::warn!(
+                        "Unknown symbol_padding '{}', expected auto, none or space",
+                        padding
+                    );
+                    SymbolPadding::Auto
+                });
+                config.with_symbol_padding(padding)
+            })
+            .as_ref()
+            .unwrap_or(config)
+    }
+
+    /// Whether module symbols should be replaced by their ASCII `symbol_fallback`.
+    /// This is the case on the Linux console, on dumb terminals and when the locale's
+    /// codeset isn't UTF-8 (e.g. `LANG=C`).
+    pub fn use_symbol_fallback(&self) -> bool {
+        if let Some("linux") | Some("dumb") = self.get_env("TERM").as_deref() {
+            return true;
+        }
+
+        // The first non-empty variable determines the locale, see locale(7)
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| self.get_env(name).filter(|value| !value.is_empty()));
+
+        match locale {
+            // Locales look like `language[_territory][.codeset][@modifier]`, but may also be
+            // just a codeset, like the `LC_CTYPE=UTF-8` of macOS
+            Some(locale) => {
+                let locale = locale.split('@').next().unwrap_or_default();
+                let codeset = match locale.split_once('.') {
+                    Some((_, codeset)) => codeset,
+                    None => locale,
+                };
+                let codeset = codeset.to_lowercase().replace('-', "");
+                codeset != "utf8"
+            }
+            // Without any locale information assume the terminal handles unicode
+            None => false,
+        }
+    }
+
     /// Check if `disabled` option of the module is true in configuration file.
     pub fn is_module_disabled_in_config(&self, name: &str) -> bool {
         let config = self.config.get_module_config(name);
@@ -203,6 +665,9 @@ impl<'a> Context<'a> {
             files: &[],
             folders: &[],
             extensions: &[],
+            case_sensitive: *self
+                .detect_case_sensitive
+                .get_or_init(|| self.config.get_root_config().detect_case_sensitive),
         })
     }
 
@@ -210,73 +675,338 @@ impl<'a> Context<'a> {
     pub fn get_repo(&self) -> Result<&Repo, std::io::Error> {
         self.repo
             .get_or_try_init(|| -> Result<Repo, std::io::Error> {
-                let repository = if env::var("GIT_DIR").is_ok() {
-                    Repository::open_from_env().ok()
-                } else {
-                    Repository::discover(&self.current_dir).ok()
+                let mut is_extra = false;
+                let mut from_env = false;
+                let mut alias = None;
+                let repository = match self.get_env_os("GIT_DIR") {
+                    Some(git_dir) => {
+                        from_env = true;
+                        open_env_repository(
+                            &self.current_dir,
+                            git_dir,
+                            self.get_env_os("GIT_WORK_TREE"),
+                        )
+                    }
+                    // A repository git finds by itself wins over the configured ones
+                    None => self
+                        .spend_fs(|| {
+                            let repository = self.discover_repository(&self.current_dir)?;
+                            Some(fix_moved_worktree(repository, &self.current_dir))
+                        })
+                        .flatten()
+                        .or_else(|| {
+                            let (repository, extra_alias) =
+                                self.spend_fs(|| self.open_extra_repository()).flatten()?;
+                            is_extra = true;
+                            alias = extra_alias;
+                            Some(repository)
+                        }),
                 };
-                let branch = repository
-                    .as_ref()
-                    .and_then(|repo| get_current_branch(repo));
-                let root = repository
-                    .as_ref()
-                    .and_then(|repo| repo.workdir().map(Path::to_path_buf));
-                let state = repository.as_ref().map(|repo| repo.state());
-                let remote = repository
-                    .as_ref()
-                    .and_then(|repo| get_remote_repository_info(repo));
-                Ok(Repo {
-                    branch,
-                    root,
-                    state,
-                    remote,
-                })
+                let mut repo = Repo::new(repository.as_ref(), is_extra, alias);
+                repo.from_env = from_env;
+                Ok(repo)
+            })
+    }
+
+    /// Finds the repository containing `dir` like git does, without going up into the
+    /// directories of `git_ceiling_dirs`
+    pub fn discover_repository(&self, dir: &Path) -> Option<Repository> {
+        Repository::open_ext(dir, RepositoryOpenFlags::empty(), self.git_ceiling_dirs()).ok()
+    }
+
+    /// The current directory and its parents, up to the first of `git_ceiling_dirs`, which are
+    /// the directories searched for a repository or for a file of the project
+    pub fn discovery_dirs(&self) -> Vec<&Path> {
+        let ceiling_dirs = self.git_ceiling_dirs();
+        let mut dirs = vec![self.current_dir.as_path()];
+        dirs.extend(
+            self.current_dir
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| !ceiling_dirs.iter().any(|ceiling| ceiling == dir)),
+        );
+        dirs
+    }
+
+    /// The directories the search for a repository stops before going up into, so that slow
+    /// mounts above the current directory aren't checked for a `.git` on every prompt:
+    ///
+    /// - the parent of the home directory, when the current directory is in it
+    /// - the `repo_discovery_boundaries` of the config
+    /// - the `GIT_CEILING_DIRECTORIES` of git
+    fn git_ceiling_dirs(&self) -> Vec<PathBuf> {
+        let mut ceiling_dirs = Vec::new();
+        if let Some(home) = self.get_home() {
+            if self.current_dir.starts_with(&home) {
+                ceiling_dirs.extend(home.parent().map(Path::to_path_buf));
+            }
+        }
+
+        let config = self.config.get_root_config();
+        ceiling_dirs.extend(
+            config
+                .repo_discovery_boundaries
+                .iter()
+                .map(|boundary| utils::expand_path(boundary, self)),
+        );
+
+        if let Some(git_ceiling_dirs) = self.get_env_os("GIT_CEILING_DIRECTORIES") {
+            ceiling_dirs.extend(
+                env::split_paths(&git_ceiling_dirs).filter(|dir| !dir.as_os_str().is_empty()),
+            );
+        }
+        ceiling_dirs
+    }
+
+    /// Opens the first of the `git.extra_repositories` whose work tree contains the current
+    /// directory, returning it with its alias
+    fn open_extra_repository(&self) -> Option<(Repository, Option<String>)> {
+        let config = self.config.get_root_config();
+        config.git.extra_repositories.iter().find_map(|extra| {
+            if extra.git_dir.is_empty() || extra.work_tree.is_empty() {
+                log::warn!("`git.extra_repositories` need both a `git_dir` and a `work_tree`");
+                return None;
+            }
+            let work_tree = utils::expand_path(extra.work_tree, self);
+            if !self.current_dir.starts_with(&work_tree) {
+                return None;
+            }
+
+            let git_dir = utils::expand_path(extra.git_dir, self);
+            let repository = match Repository::open(&git_dir) {
+                Ok(repository) => repository,
+                Err(error) => {
+                    log::warn!("Unable to open the repository {:?}: {}", git_dir, error);
+                    return None;
+                }
+            };
+            repository.set_workdir(&work_tree, false).ok()?;
+            Some((repository, extra.alias.map(str::to_owned)))
+        })
+    }
+
+    /// The root of the Mercurial repository containing the current directory: the closest
+    /// directory with a `.hg` folder, found once per prompt
+    pub fn hg_root(&self) -> Option<&Path> {
+        self.hg_root
+            .get_or_init(|| {
+                self.current_dir
+                    .ancestors()
+                    .find(|dir| self.path_exists(&dir.join(".hg")))
+                    .map(Path::to_path_buf)
             })
+            .as_deref()
+    }
+
+    /// The `mise` and `asdf` config files in the current directory and its parents with their
+    /// contents, nearest first, found once per prompt by the first module that looks for a
+    /// pinned version. The search stops once `fs_timeout_ms` is exceeded.
+    pub fn pin_files(&self) -> &[(PathBuf, String)] {
+        self.pin_files.get_or_init(|| {
+            let mut files = Vec::new();
+            for dir in self.current_dir.ancestors() {
+                for file in PIN_FILES {
+                    let path = dir.join(file);
+                    let contents = match self.spend_fs(|| {
+                        path.is_file()
+                            .then(|| utils::read_file(&path).ok())
+                            .flatten()
+                    }) {
+                        Some(contents) => contents,
+                        None => return files,
+                    };
+                    if let Some(contents) = contents {
+                        files.push((path, contents));
+                    }
+                }
+            }
+            files
+        })
+    }
+
+    /// The version of the `git` binary, detected once per prompt by the first module that runs
+    /// `git`. The others read the repository with libgit2 and don't need it.
+    /// Returns `None` if `git` isn't installed or its version can't be parsed.
+    pub fn git_version(&self) -> Option<GitVersion> {
+        *self.git_version.get_or_init(|| {
+            let version = self
+                .exec_cmd("git", &["--version"], &[])
+                .and_then(|output| GitVersion::parse(&output.stdout));
+            if version.is_none() {
+                log::debug!("git is not installed, skipping the modules that run it");
+            }
+            version
+        })
     }
 
     pub fn dir_contents(&self) -> Result<&DirContents, std::io::Error> {
         self.dir_contents.get_or_try_init(|| {
             let timeout = Duration::from_millis(self.config.get_root_config().scan_timeout);
-            DirContents::from_path_with_timeout(&self.current_dir, timeout)
+            self.spend_fs(|| DirContents::from_path_with_timeout(&self.current_dir, timeout))
+                .unwrap_or_else(|| Ok(DirContents::default()))
         })
     }
 
+    fn fs_budget(&self) -> &FsBudget {
+        self.fs_budget.get_or_init(|| {
+            #[cfg(test)]
+            let clock = self
+                .mock_fs_clock
+                .clone()
+                .unwrap_or_else(|| Arc::new(SystemClock));
+            #[cfg(not(test))]
+            let clock = Arc::new(SystemClock);
+            FsBudget::new(self.config.get_root_config().fs_timeout_ms, clock)
+        })
+    }
+
+    /// The clock the `prompt_timeout_ms` deadline is measured with
+    pub fn prompt_clock(&self) -> Arc<dyn Clock> {
+        #[cfg(test)]
+        if let Some(clock) = &self.mock_prompt_clock {
+            return clock.clone();
+        }
+
+        Arc::new(SystemClock)
+    }
+
+    /// Runs the filesystem operation `op` within `fs_timeout_ms`, returning `None` without
+    /// running it once the filesystem checks of the prompt took longer than that
+    pub fn spend_fs<T>(&self, op: impl FnOnce() -> T) -> Option<T> {
+        self.fs_budget().spend(op)
+    }
+
+    /// Whether `path` exists, or `false` once the filesystem checks of the prompt took longer
+    /// than `fs_timeout_ms`
+    pub fn path_exists(&self, path: &Path) -> bool {
+        self.spend_fs(|| path.exists()).unwrap_or(false)
+    }
+
+    /// Whether filesystem checks were skipped because they took longer than `fs_timeout_ms`,
+    /// which leaves the prompt without the modules that depend on them
+    pub fn is_fs_slow(&self) -> bool {
+        self.fs_budget().is_exhausted()
+    }
+
     fn get_shell() -> Shell {
         let shell = env::var("STARSHIP_SHELL").unwrap_or_default();
-        match shell.as_str() {
-            "bash" => Shell::Bash,
-            "fish" => Shell::Fish,
-            "ion" => Shell::Ion,
-            "powershell" => Shell::PowerShell,
-            "zsh" => Shell::Zsh,
-            "elvish" => Shell::Elvish,
-            "tcsh" => Shell::Tcsh,
-            _ => Shell::Unknown,
-        }
+        Shell::ALL
+            .iter()
+            .copied()
+            .find(|known| known.as_str() == shell)
+            .unwrap_or(Shell::Unknown)
     }
 
     pub fn get_cmd_duration(&self) -> Option<u128> {
-        self.properties.get("cmd_duration")?.parse::<u128>().ok()
+        self.get_property("cmd_duration")?.parse::<u128>().ok()
     }
 
-    /// Execute a command and return the output on stdout and stderr if successful
+    /// The value of the `prompt` argument `name`, e.g. `status_code`. When it wasn't passed,
+    /// the environment variable a minimal shell integration may set instead is used, see
+    /// `PROPERTY_ENV_FALLBACKS`.
+    pub fn get_property(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.properties.get(name) {
+            return Some(value.clone());
+        }
+
+        let fish_fallbacks = match self.shell {
+            Shell::Fish => FISH_PROPERTY_ENV_FALLBACKS,
+            _ => &[],
+        };
+        PROPERTY_ENV_FALLBACKS
+            .iter()
+            .chain(fish_fallbacks)
+            .filter(|(property, _)| *property == name)
+            .find_map(|(_, var)| self.get_env(var))
+    }
+
+    /// Returns whether `purpose` may access the network, which is never the case with
+    /// `offline = true`, see `network`. Commands known to access the network ask before they
+    /// are run, see `network::command_purpose`.
+    pub fn allow_network(&self, purpose: &str) -> bool {
+        if !network::is_allowed(&self.config.get_root_config(), purpose) {
+            return false;
+        }
+
+        #[cfg(test)]
+        self.network_log.lock().unwrap().push(purpose.to_owned());
+        true
+    }
+
+    /// Execute a command in `current_dir` and return the output on stdout and stderr if successful.
+    /// `env` is the overlay of the module running the command, see `Module::env`.
     #[inline]
-    pub fn exec_cmd(&self, cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+    pub fn exec_cmd(&self, cmd: &str, args: &[&str], env: &EnvOverlay) -> Option<CommandOutput> {
+        self.run_cmd(cmd, args, env, false)
+    }
+
+    /// Execute a command in `current_dir` and return its output whatever its exit code, for
+    /// commands printing useful output while exiting unsuccessfully. Check
+    /// `CommandOutput::status` when the exit code matters.
+    pub fn exec_cmd_with_status(
+        &self,
+        cmd: &str,
+        args: &[&str],
+        env: &EnvOverlay,
+    ) -> Option<CommandOutput> {
+        self.run_cmd(cmd, args, env, true)
+    }
+
+    fn run_cmd(
+        &self,
+        cmd: &str,
+        args: &[&str],
+        env: &EnvOverlay,
+        allow_nonzero_exit: bool,
+    ) -> Option<CommandOutput> {
+        let env = if env.is_empty() || self.is_trusted_dir() {
+            env
+        } else {
+            &[]
+        };
+        if let Some(purpose) = network::command_purpose(cmd, args) {
+            if !self.allow_network(purpose) {
+                return None;
+            }
+        }
         #[cfg(test)]
         {
-            let command = match args.len() {
-                0 => cmd.to_owned(),
-                _ => format!("{} {}", cmd, args.join(" ")),
-            };
+            let command = self.log_cmd(cmd, args, env);
             if let Some(output) = self.cmd.get(command.as_str()) {
-                return output.clone();
+                return output
+                    .clone()
+                    .filter(|output| allow_nonzero_exit || output.success());
             }
         }
-        exec_cmd(cmd, args, self.cmd_timeout)
+        exec_cmd(
+            cmd,
+            args,
+            env,
+            Some(&self.current_dir),
+            self.cmd_timeout,
+            allow_nonzero_exit,
+        )
+    }
+
+    /// Records a command in `cmd_log`, prefixed by its env overlay as in `KEY=value cmd args`,
+    /// and returns the command without the overlay to look up its mock
+    #[cfg(test)]
+    fn log_cmd(&self, cmd: &str, args: &[&str], env: &EnvOverlay) -> String {
+        let command = match args.len() {
+            0 => cmd.to_owned(),
+            _ => format!("{} {}", cmd, args.join(" ")),
+        };
+        let logged = env
+            .iter()
+            .map(|(key, value)| format!("{}={} ", key, value))
+            .chain(std::iter::once(command.clone()))
+            .collect();
+        self.cmd_log.lock().unwrap().push(logged);
+        command
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct DirContents {
     // HashSet of all files, no folders, relative to the base directory given at construction.
     files: HashSet<PathBuf>,
@@ -284,8 +1014,11 @@ pub struct DirContents {
     file_names: HashSet<String>,
     // HashSet of all folders, relative to the base directory given at construction.
     folders: HashSet<PathBuf>,
-    // HashSet of all extensions found, without dots, e.g. "js" instead of ".js".
+    // HashSet of all extensions found, without dots, e.g. "js" instead of ".js". Compound
+    // extensions are included along with their parts, e.g. "d.ts" and "ts".
     extensions: HashSet<String>,
+    // The extensions in lowercase, to match them regardless of case.
+    lowercase_extensions: HashSet<String>,
 }
 
 impl DirContents {
@@ -314,16 +1047,15 @@ impl DirContents {
                 if entry.path().is_dir() {
                     folders.insert(path);
                 } else {
-                    if !path.to_string_lossy().starts_with('.') {
-                        path.extension()
-                            .map(|ext| extensions.insert(ext.to_string_lossy().to_string()));
-                    }
                     if let Some(file_name) = path.file_name() {
-                        file_names.insert(file_name.to_string_lossy().to_string());
+                        let file_name = file_name.to_string_lossy();
+                        extensions.extend(file_extensions(&file_name).map(str::to_owned));
+                        file_names.insert(file_name.into_owned());
                     }
                     files.insert(path);
                 }
             });
+        let lowercase_extensions = extensions.iter().map(|ext| ext.to_lowercase()).collect();
 
         log::trace!(
             "Building HashSets of directory files, folders and extensions took {:?}",
@@ -335,6 +1067,7 @@ impl DirContents {
             file_names,
             folders,
             extensions,
+            lowercase_extensions,
         })
     }
 
@@ -366,25 +1099,300 @@ impl DirContents {
         self.extensions.contains(ext)
     }
 
-    pub fn has_any_extension(&self, exts: &[&str]) -> bool {
-        exts.iter().any(|ext| self.has_extension(ext))
+    pub fn has_extension_ignoring_case(&self, ext: &str) -> bool {
+        self.lowercase_extensions.contains(&ext.to_lowercase())
     }
+
+    pub fn has_any_extension(&self, exts: &[&str], case_sensitive: bool) -> bool {
+        exts.iter().any(|ext| {
+            if case_sensitive {
+                self.has_extension(ext)
+            } else {
+                self.has_extension_ignoring_case(ext)
+            }
+        })
+    }
+}
+
+/// The extensions of a file name, without dots, from the longest compound one to the last
+/// one, e.g. "d.ts" and "ts" for "index.d.ts". The leading dot of hidden files doesn't start
+/// an extension, so ".gitignore" has none and ".eslintrc.js" has "js".
+fn file_extensions(file_name: &str) -> impl Iterator<Item = &str> {
+    let name = file_name.strip_prefix('.').unwrap_or(file_name);
+    name.match_indices('.')
+        .map(move |(index, _)| &name[index + 1..])
+        .filter(|ext| !ext.is_empty())
 }
 
 pub struct Repo {
     /// If `current_dir` is a git repository or is contained within one,
     /// this is the current branch name of that repo.
+    /// When HEAD is detached this is the tag pointing at HEAD, or its abbreviated hash.
     pub branch: Option<String>,
 
+    /// What kind of ref `branch` is
+    pub ref_kind: Option<RefKind>,
+
     /// If `current_dir` is a git repository or is contained within one,
     /// this is the path to the root of that repo.
     pub root: Option<PathBuf>,
 
+    /// The git directory of the repo, e.g. `.git` in `root`
+    pub path: Option<PathBuf>,
+
+    /// Whether the repo is one of `git.extra_repositories`, which git can't find from `root`
+    pub is_extra: bool,
+
+    /// The `alias` of the repo in `git.extra_repositories`
+    pub alias: Option<String>,
+
+    /// Whether the repo is bare, so it has a git directory but no work tree
+    pub is_bare: bool,
+
+    /// Whether the repo was opened from `GIT_DIR` and `GIT_WORK_TREE`
+    pub from_env: bool,
+
     /// State
     pub state: Option<RepositoryState>,
 
     /// Remote repository
     pub remote: Option<Remote>,
+
+    /// The commits of HEAD that aren't in its upstream and the other way around, see
+    /// `ahead_behind`
+    upstream_ahead_behind: OnceCell<Option<(usize, usize)>>,
+
+    /// The URL of the remote, see `remote_url`
+    remote_url: OnceCell<Option<String>>,
+
+    /// The name of the linked worktree, see `worktree_name`
+    worktree_name: OnceCell<Option<String>>,
+}
+
+impl Repo {
+    fn new(repository: Option<&Repository>, is_extra: bool, alias: Option<String>) -> Self {
+        let (branch, ref_kind) = repository
+            .and_then(get_current_branch)
+            .map_or((None, None), |(branch, kind)| (Some(branch), Some(kind)));
+        Repo {
+            branch,
+            ref_kind,
+            root: repository.and_then(|repo| repo.workdir().map(Path::to_path_buf)),
+            path: repository.map(|repo| repo.path().to_path_buf()),
+            is_extra,
+            alias,
+            is_bare: matches!(repository, Some(repo) if repo.is_bare()),
+            from_env: false,
+            state: repository.map(|repo| repo.state()),
+            remote: repository.and_then(get_remote_repository_info),
+            upstream_ahead_behind: OnceCell::new(),
+            remote_url: OnceCell::new(),
+            worktree_name: OnceCell::new(),
+        }
+    }
+
+    /// The name of the linked worktree the repo was opened in, from its git dir
+    /// `<common dir>/worktrees/<name>`, read the first time it's asked for. `None` in the main
+    /// worktree. When the worktree was moved without `git worktree move`, so that the `gitdir`
+    /// file git keeps for it points to where it was, the name of its directory is used instead.
+    pub fn worktree_name(&self) -> Option<&str> {
+        self.worktree_name
+            .get_or_init(|| {
+                let git_dir = self.path.as_ref()?;
+                let root = self.root.as_ref()?;
+                // Only the git dirs of linked worktrees point to the common one
+                if !git_dir.join("commondir").is_file() {
+                    return None;
+                }
+
+                let in_worktrees =
+                    git_dir.parent().and_then(Path::file_name) == Some(OsStr::new("worktrees"));
+                let back_pointer = utils::read_file(git_dir.join("gitdir")).ok();
+                let is_stale = match back_pointer {
+                    Some(back_pointer) => {
+                        !same_path(Path::new(back_pointer.trim_end()), &root.join(".git"))
+                    }
+                    None => true,
+                };
+                let name = if in_worktrees && !is_stale {
+                    git_dir.file_name()
+                } else {
+                    root.file_name()
+                };
+                name.map(|name| name.to_string_lossy().into_owned())
+            })
+            .as_deref()
+    }
+
+    /// The URL of the remote of the upstream branch, or of `origin` without one, read from the
+    /// git config the first time it's asked for. `None` when there is no such remote.
+    pub fn remote_url(&self) -> Option<&str> {
+        self.remote_url
+            .get_or_init(|| {
+                let name = self
+                    .remote
+                    .as_ref()
+                    .and_then(|remote| remote.name.as_deref())
+                    .unwrap_or("origin");
+                let config = self.open()?.config().ok()?;
+                config.get_string(&format!("remote.{}.url", name)).ok()
+            })
+            .as_deref()
+    }
+
+    /// Counts the commits of HEAD that aren't in its upstream and the other way around with
+    /// `git rev-list`, the first time they are asked for. Without an upstream there is nothing
+    /// to count, which isn't an error, so no command runs and `None` is returned.
+    pub fn ahead_behind(&self, context: &Context, env: &EnvOverlay) -> Option<(usize, usize)> {
+        *self.upstream_ahead_behind.get_or_init(|| {
+            self.remote.as_ref()?;
+            let root = self.root.as_ref()?.to_string_lossy();
+            let args = [
+                "-C",
+                &root,
+                "rev-list",
+                "--left-right",
+                "--count",
+                "HEAD...@{upstream}",
+                "--",
+            ];
+
+            let output = context.exec_cmd_with_status("git", &args, env)?;
+            if !output.success() {
+                log::debug!(
+                    "Unable to compare with the upstream: {}",
+                    output.stderr.trim()
+                );
+                return None;
+            }
+
+            let mut counts = output.stdout.split_whitespace().map(str::parse::<usize>);
+            match (counts.next(), counts.next()) {
+                (Some(Ok(ahead)), Some(Ok(behind))) => Some((ahead, behind)),
+                _ => None,
+            }
+        })
+    }
+
+    /// The repos whose work trees contain this one, nearest first, such as a superproject
+    /// holding it as a plain nested repo. At most `limit` repos are opened on the way up, which
+    /// stops at the boundaries of `Context::discover_repository`.
+    pub fn outer_repos(&self, context: &Context, limit: usize) -> Vec<Repo> {
+        let mut repos = Vec::new();
+        let mut root = self.root.clone();
+        while repos.len() < limit {
+            let outer = match root.as_deref().and_then(Path::parent) {
+                Some(parent) => context.discover_repository(parent),
+                None => None,
+            };
+            let outer = match outer {
+                Some(outer) if outer.workdir().is_some() => Repo::new(Some(&outer), false, None),
+                _ => break,
+            };
+            root = outer.root.clone();
+            repos.push(outer);
+        }
+        repos
+    }
+
+    /// Opens the repo with its work tree, even when git wouldn't find it there by itself
+    pub fn open(&self) -> Option<Repository> {
+        let repository = Repository::open(self.path.as_ref()?).ok()?;
+        if let Some(root) = &self.root {
+            if repository.workdir() != Some(root.as_path()) {
+                repository.set_workdir(root, false).ok()?;
+            }
+        }
+        Some(repository)
+    }
+
+    /// The environment `git` commands run in the repo need to find it
+    pub fn git_env(&self) -> Vec<(&'static str, &str)> {
+        if !self.is_extra && !self.from_env {
+            return Vec::new();
+        }
+
+        let git_dir = self.path.as_deref().and_then(Path::to_str);
+        let work_tree = self.root.as_deref().and_then(Path::to_str);
+        [("GIT_DIR", git_dir), ("GIT_WORK_TREE", work_tree)]
+            .iter()
+            .filter_map(|(key, value)| Some((*key, (*value)?)))
+            .collect()
+    }
+
+    /// Returns the name of the tag pointing at the given commit, looking at both loose and
+    /// packed tag refs. If several tags match, the most recently created one is returned.
+    /// Lightweight tags don't have a creation date of their own, so they are dated by the commit
+    /// they point to. It takes the opened repository, since it also names a detached HEAD while
+    /// the `Repo` is being created.
+    pub fn commit_tag(
+        git_repo: &Repository,
+        commit_oid: Oid,
+        annotated_only: bool,
+    ) -> Option<String> {
+        let references = git_repo.references_glob("refs/tags/*").ok()?;
+
+        references
+            .flatten()
+            .filter_map(|reference| {
+                let commit = reference.peel_to_commit().ok()?;
+                if commit.id() != commit_oid {
+                    return None;
+                }
+
+                // Annotated tags point to a tag object, lightweight ones directly to the commit
+                let created = match reference.peel_to_tag() {
+                    // fall back to oldest + 1s time if the tagger is unavailable
+                    Ok(tag) => tag.tagger().map_or(1, |tagger| tagger.when().seconds()),
+                    Err(_) if annotated_only => return None,
+                    Err(_) => commit.time().seconds(),
+                };
+
+                Some((created, reference.shorthand()?.to_owned()))
+            })
+            .max()
+            .map(|(_, name)| name)
+    }
+}
+
+/// The kind of ref HEAD points to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefKind {
+    /// A local branch
+    Branch,
+    /// A detached HEAD at a tagged commit
+    Tag,
+    /// A detached HEAD at an untagged commit
+    Detached,
+}
+
+impl RefKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RefKind::Branch => "branch",
+            RefKind::Tag => "tag",
+            RefKind::Detached => "detached",
+        }
+    }
+}
+
+/// The `major.minor.patch` version of the `git` binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GitVersion(pub u32, pub u32, pub u32);
+
+impl GitVersion {
+    /// Parses the output of `git --version`, e.g. `git version 2.30.1.windows.1`
+    pub fn parse(output: &str) -> Option<Self> {
+        let version = output.trim().strip_prefix("git version ")?;
+        let mut parts = version
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|part| part.parse::<u32>().ok());
+
+        let major = parts.next()??;
+        let minor = parts.next().flatten().unwrap_or(0);
+        let patch = parts.next().flatten().unwrap_or(0);
+        Some(GitVersion(major, minor, patch))
+    }
 }
 
 /// Remote repository
@@ -400,6 +1408,7 @@ pub struct ScanDir<'a> {
     files: &'a [&'a str],
     folders: &'a [&'a str],
     extensions: &'a [&'a str],
+    case_sensitive: bool,
 }
 
 impl<'a> ScanDir<'a> {
@@ -421,13 +1430,71 @@ impl<'a> ScanDir<'a> {
     /// based on the current PathBuf check to see
     /// if any of this criteria match or exist and returning a boolean
     pub fn is_match(&self) -> bool {
-        self.dir_contents.has_any_extension(self.extensions)
+        self.dir_contents
+            .has_any_extension(self.extensions, self.case_sensitive)
             || self.dir_contents.has_any_folder(self.folders)
             || self.dir_contents.has_any_file_name(self.files)
     }
 }
 
-fn get_current_branch(repository: &Repository) -> Option<String> {
+/// Opens the repository selected by `GIT_DIR` and `GIT_WORK_TREE`, as set by git for hooks
+/// and aliases. Relative paths are relative to the current directory, like in git.
+fn open_env_repository(
+    current_dir: &Path,
+    git_dir: OsString,
+    work_tree: Option<OsString>,
+) -> Option<Repository> {
+    let repository = Repository::open(current_dir.join(git_dir)).ok()?;
+    let work_tree = match work_tree {
+        Some(work_tree) => current_dir.join(work_tree),
+        // Like git, take the current directory as the top of the work tree, unless the
+        // repository is bare or sets `core.worktree` itself
+        None if repository.is_bare() || has_core_worktree(&repository) => return Some(repository),
+        None => current_dir.to_path_buf(),
+    };
+    repository.set_workdir(&work_tree, false).ok()?;
+    Some(repository)
+}
+
+fn has_core_worktree(repository: &Repository) -> bool {
+    repository
+        .config()
+        .and_then(|config| config.get_path("core.worktree"))
+        .is_ok()
+}
+
+/// libgit2 reads the work tree of a linked worktree from the `gitdir` file git keeps for it,
+/// which still points to where the worktree was if it was moved without `git worktree move`.
+/// Like git, use the directory the worktree's `.git` file was found in instead.
+fn fix_moved_worktree(repository: Repository, current_dir: &Path) -> Repository {
+    let is_moved = repository.is_worktree()
+        && !matches!(repository.workdir(), Some(workdir) if current_dir.starts_with(workdir));
+    if is_moved {
+        let work_tree = current_dir
+            .ancestors()
+            .find(|dir| dir.join(".git").is_file());
+        if let Some(work_tree) = work_tree {
+            if let Err(error) = repository.set_workdir(work_tree, false) {
+                log::debug!(
+                    "Unable to use the moved worktree {:?}: {}",
+                    work_tree,
+                    error
+                );
+            }
+        }
+    }
+    repository
+}
+
+/// Whether two paths are the same file, even if one of them goes through a symlink
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn get_current_branch(repository: &Repository) -> Option<(String, RefKind)> {
     let head = match repository.head() {
         Ok(reference) => reference,
         Err(e) => {
@@ -437,24 +1504,68 @@ fn get_current_branch(repository: &Repository) -> Option<String> {
                 let mut head_path = repository.path().to_path_buf();
                 head_path.push("HEAD");
 
-                // get first line, then last path segment
-                fs::read_to_string(&head_path)
-                    .ok()?
-                    .lines()
-                    .next()?
-                    .trim()
-                    .split('/')
-                    .last()
-                    .map(|r| r.to_owned())
+                let head = fs::read_to_string(&head_path).ok()?;
+                unborn_branch_name(head.lines().next()?)
+                    .map(|branch| (branch.to_owned(), RefKind::Branch))
             } else {
                 None
             };
         }
     };
 
+    if repository.head_detached().unwrap_or(false) {
+        let commit_oid = head.peel_to_commit().ok()?.id();
+        return Some(match Repo::commit_tag(repository, commit_oid, false) {
+            Some(tag) => (tag, RefKind::Tag),
+            None => {
+                let mut hash = commit_oid.to_string();
+                hash.truncate(7);
+                (hash, RefKind::Detached)
+            }
+        });
+    }
+
     let shorthand = head.shorthand();
 
-    shorthand.map(std::string::ToString::to_string)
+    shorthand.map(|branch| (branch.to_owned(), RefKind::Branch))
+}
+
+/// The name of the branch a `ref: refs/heads/<branch>` line of `.git/HEAD` points to. Branch
+/// names may contain slashes, so only the prefix is removed.
+fn unborn_branch_name(head: &str) -> Option<&str> {
+    head.trim()
+        .strip_prefix("ref:")?
+        .trim_start()
+        .strip_prefix("refs/heads/")
+        .filter(|branch| !branch.is_empty())
+}
+
+#[cfg(target_os = "windows")]
+fn detect_root_user() -> bool {
+    false
+}
+
+#[cfg(not(target_os = "windows"))]
+fn detect_root_user() -> bool {
+    nix::unistd::geteuid() == nix::unistd::ROOT
+}
+
+/// Whether `dir` is owned by the effective user. Directories that don't exist don't contain
+/// anything to distrust.
+#[cfg(not(target_os = "windows"))]
+fn is_owned_by_current_user(dir: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match fs::metadata(dir) {
+        Ok(metadata) => metadata.uid() == nix::unistd::geteuid().as_raw(),
+        Err(error) => error.kind() == std::io::ErrorKind::NotFound,
+    }
+}
+
+/// Ownership isn't checked on Windows yet, the directories in the user's profile are trusted
+#[cfg(target_os = "windows")]
+fn is_owned_by_current_user(dir: &Path) -> bool {
+    matches!(home_dir(), Some(home) if dir.starts_with(home))
 }
 
 fn get_remote_repository_info(repository: &Repository) -> Option<Remote> {
@@ -490,6 +1601,35 @@ pub enum Shell {
     Unknown,
 }
 
+impl Shell {
+    /// Every shell, each of which has a golden file of its escaped prompt in the tests of
+    /// `shell_escape`
+    pub const ALL: [Shell; 8] = [
+        Shell::Bash,
+        Shell::Fish,
+        Shell::Ion,
+        Shell::PowerShell,
+        Shell::Zsh,
+        Shell::Elvish,
+        Shell::Tcsh,
+        Shell::Unknown,
+    ];
+
+    /// The name of the shell in `STARSHIP_SHELL`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Fish => "fish",
+            Shell::Ion => "ion",
+            Shell::PowerShell => "powershell",
+            Shell::Zsh => "zsh",
+            Shell::Elvish => "elvish",
+            Shell::Tcsh => "tcsh",
+            Shell::Unknown => "unknown",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -518,6 +1658,7 @@ mod tests {
                 files: &["package.json"],
                 extensions: &["js"],
                 folders: &["node_modules"],
+                case_sensitive: false,
             }
             .is_match(),
             false
@@ -532,6 +1673,7 @@ mod tests {
                 files: &["package.json"],
                 extensions: &["js"],
                 folders: &["node_modules"],
+                case_sensitive: false,
             }
             .is_match(),
             false
@@ -546,6 +1688,7 @@ mod tests {
                 files: &["package.json"],
                 extensions: &["js"],
                 folders: &["node_modules"],
+                case_sensitive: false,
             }
             .is_match(),
             false
@@ -560,6 +1703,7 @@ mod tests {
                 files: &["package.json"],
                 extensions: &["js"],
                 folders: &["node_modules"],
+                case_sensitive: false,
             }
             .is_match(),
             true
@@ -569,6 +1713,88 @@ mod tests {
         Ok(())
     }
 
+    fn scan_extensions(
+        dir_contents: &DirContents,
+        extensions: &[&str],
+        case_sensitive: bool,
+    ) -> bool {
+        ScanDir {
+            dir_contents,
+            files: &[],
+            extensions,
+            folders: &[],
+            case_sensitive,
+        }
+        .is_match()
+    }
+
+    #[test]
+    fn test_file_extensions() {
+        let extensions = |name| file_extensions(name).collect::<Vec<_>>();
+        assert_eq!(extensions("main.rs"), vec!["rs"]);
+        assert_eq!(extensions("index.d.ts"), vec!["d.ts", "ts"]);
+        assert_eq!(extensions("archive.tar.gz"), vec!["tar.gz", "gz"]);
+        assert_eq!(extensions("Makefile"), Vec::<&str>::new());
+        assert_eq!(extensions(".gitignore"), Vec::<&str>::new());
+        assert_eq!(extensions(".eslintrc.js"), vec!["js"]);
+        assert_eq!(extensions("file."), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_scan_dir_compound_extensions() -> io::Result<()> {
+        let dir = testdir(&["index.d.ts", "build.gradle.kts"])?;
+        let dc = DirContents::from_path(dir.path())?;
+
+        assert!(scan_extensions(&dc, &["d.ts"], false));
+        assert!(scan_extensions(&dc, &["ts"], false));
+        assert!(scan_extensions(&dc, &["gradle.kts"], false));
+        assert!(!scan_extensions(&dc, &["spec.ts"], false));
+        assert!(!scan_extensions(&dc, &["index.d.ts"], false));
+        // An extension has to start after a dot
+        assert!(!scan_extensions(&dc, &["s"], false));
+        dir.close()
+    }
+
+    #[test]
+    fn test_scan_dir_extensions_case() -> io::Result<()> {
+        let dir = testdir(&["Main.JAVA", "App.Spec.JS"])?;
+        let dc = DirContents::from_path(dir.path())?;
+
+        assert!(scan_extensions(&dc, &["java"], false));
+        assert!(scan_extensions(&dc, &["spec.js"], false));
+        assert!(!scan_extensions(&dc, &["java"], true));
+        assert!(!scan_extensions(&dc, &["spec.js"], true));
+        assert!(scan_extensions(&dc, &["JAVA"], true));
+        assert!(scan_extensions(&dc, &["Spec.JS"], true));
+        dir.close()
+    }
+
+    #[test]
+    fn test_scan_dir_hidden_files() -> io::Result<()> {
+        let dir = testdir(&[".gitignore", ".eslintrc.js"])?;
+        let dc = DirContents::from_path(dir.path())?;
+
+        assert!(!scan_extensions(&dc, &["gitignore"], false));
+        assert!(!scan_extensions(&dc, &["eslintrc.js"], false));
+        assert!(scan_extensions(&dc, &["js"], false));
+        assert!(dc.has_file_name(".gitignore"));
+        dir.close()
+    }
+
+    #[test]
+    fn detect_case_sensitive_config() -> io::Result<()> {
+        let dir = testdir(&["main.GO"])?;
+
+        let context = config_context(dir.path(), toml::Value::Table(Default::default()));
+        let scan = context.try_begin_scan().unwrap().set_extensions(&["go"]);
+        assert!(scan.is_match());
+
+        let context = config_context(dir.path(), toml::toml! { detect_case_sensitive = true });
+        let scan = context.try_begin_scan().unwrap().set_extensions(&["go"]);
+        assert!(!scan.is_match());
+        dir.close()
+    }
+
     #[test]
     fn context_constructor_should_canonicalize_current_dir() -> io::Result<()> {
         #[cfg(not(windows))]
@@ -647,4 +1873,497 @@ mod tests {
         let expected_logical_dir = test_path;
         assert_eq!(expected_logical_dir, context.logical_dir);
     }
+
+    fn config_context(path: &Path, config: toml::Value) -> Context<'static> {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            path.to_owned(),
+            path.to_owned(),
+        );
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+        context
+    }
+
+    #[test]
+    fn module_alias_is_restored_after_rendering() {
+        let context = config_context(Path::new("/"), toml::Value::Table(Default::default()));
+        let rendering = || RENDERING_ALIAS.with(|alias| alias.borrow().clone());
+
+        context.with_module_alias("outer", || {
+            context.with_module_alias("inner", || {
+                assert_eq!(rendering().as_deref(), Some("inner"));
+            });
+            assert_eq!(rendering().as_deref(), Some("outer"));
+
+            let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                context.with_module_alias("inner", || panic!("module failed"))
+            }));
+            assert!(panicked.is_err());
+            assert_eq!(rendering().as_deref(), Some("outer"));
+        });
+        assert_eq!(rendering(), None);
+    }
+
+    #[test]
+    fn pin_files_are_found_once_from_subdirectories() -> io::Result<()> {
+        let dir = testdir(&["project/src/main.rs"])?;
+        fs::write(dir.path().join(".tool-versions"), "nodejs 18.16.0\n")?;
+        fs::write(
+            dir.path().join("project/.mise.toml"),
+            "[tools]\nnode = \"20\"\n",
+        )?;
+        let context = config_context(
+            &dir.path().join("project/src"),
+            toml::Value::Table(Default::default()),
+        );
+
+        let found = |context: &Context| -> Vec<PathBuf> {
+            context
+                .pin_files()
+                .iter()
+                .map(|(path, _)| path.clone())
+                .collect()
+        };
+        let before = found(&context);
+        assert_eq!(
+            before[..2],
+            [
+                dir.path().join("project/.mise.toml"),
+                dir.path().join(".tool-versions"),
+            ]
+        );
+
+        fs::write(dir.path().join("project/src/.tool-versions"), "nodejs 16\n")?;
+        assert_eq!(found(&context), before);
+        dir.close()
+    }
+
+    #[test]
+    fn owned_dir_trusted() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = config_context(dir.path(), toml::Value::Table(Default::default()));
+        assert!(context.is_trusted_dir());
+        dir.close()
+    }
+
+    #[test]
+    fn other_owned_dir_untrusted() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = config_context(dir.path(), toml::Value::Table(Default::default()));
+        context.mock_owned_dir = Some(false);
+        assert!(!context.is_trusted_dir());
+        dir.close()
+    }
+
+    #[test]
+    fn trusted_directories_override_ownership() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+        let project = home.path().join("work/project/src");
+        fs::create_dir_all(&project)?;
+
+        for (pattern, trusted) in [
+            ("~/work", true),
+            ("~/wo*", true),
+            ("~/work/*/src", true),
+            ("~/other", false),
+            ("/work", false),
+            ("${WORK}", true),
+            ("%WORK%/*/src", true),
+        ] {
+            let mut context = config_context(
+                &project,
+                toml::toml! {
+                    trusted_directories = [pattern]
+                },
+            );
+            context.mock_owned_dir = Some(false);
+            context
+                .env
+                .insert("HOME", home.path().to_string_lossy().into());
+            context
+                .env
+                .insert("WORK", home.path().join("work").to_string_lossy().into());
+            assert_eq!(context.is_trusted_dir(), trusted, "{}", pattern);
+        }
+        home.close()
+    }
+
+    #[test]
+    fn env_overlay_skipped_in_untrusted_dir() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        for (owned, expected) in [(true, "GREETING=hello echo"), (false, "echo")] {
+            let mut context = config_context(dir.path(), toml::Value::Table(Default::default()));
+            context.mock_owned_dir = Some(owned);
+            context.exec_cmd("echo", &[], &[("GREETING", "hello")]);
+            assert_eq!(context.cmd_log.into_inner().unwrap(), vec![expected]);
+        }
+        dir.close()
+    }
+
+    #[test]
+    fn git_dir_env_without_work_tree_uses_current_dir() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let other_dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            other_dir.path().to_path_buf(),
+            other_dir.path().to_path_buf(),
+        );
+        let git_dir = repo_dir.path().join(".git");
+        context
+            .env
+            .insert("GIT_DIR", git_dir.to_string_lossy().into_owned());
+
+        let repo = context.get_repo()?;
+        assert_eq!(repo.root.as_deref(), Some(other_dir.path()));
+        assert!(repo.from_env);
+        let (key, value) = repo.git_env()[0];
+        assert_eq!((key, Path::new(value)), ("GIT_DIR", git_dir.as_path()));
+        other_dir.close()?;
+        repo_dir.close()
+    }
+
+    /// The root of the repo found from `dir` with `config` and the `env` variables
+    fn discovered_root(
+        dir: &Path,
+        config: toml::Value,
+        env: &[(&'static str, &Path)],
+    ) -> Option<PathBuf> {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            dir.to_path_buf(),
+            dir.to_path_buf(),
+        );
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+        for (key, value) in env {
+            context
+                .env
+                .insert(key, value.to_string_lossy().into_owned());
+        }
+        let root = context.get_repo().unwrap().root.as_deref()?.canonicalize();
+        root.ok()
+    }
+
+    #[test]
+    fn repo_discovery_stops_at_boundaries() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let repo_root = repo_dir.path().canonicalize()?;
+        let boundary = repo_root.join("boundary");
+        let inner = boundary.join("inner");
+        fs::create_dir_all(&inner)?;
+        let no_config = || toml::Value::Table(Default::default());
+
+        assert_eq!(
+            discovered_root(&inner, no_config(), &[]),
+            Some(repo_root.clone())
+        );
+
+        let boundary_str = boundary.to_string_lossy().into_owned();
+        let config = toml::toml! {
+            repo_discovery_boundaries = [boundary_str]
+        };
+        assert_eq!(discovered_root(&inner, config, &[]), None);
+
+        let env = [("GIT_CEILING_DIRECTORIES", boundary.as_path())];
+        assert_eq!(discovered_root(&inner, no_config(), &env), None);
+
+        // The home directory itself is still searched, but not the directories above it
+        let env = [("HOME", boundary.as_path())];
+        assert_eq!(discovered_root(&inner, no_config(), &env), None);
+        let env = [("HOME", inner.as_path())];
+        assert_eq!(
+            discovered_root(&boundary, no_config(), &env),
+            Some(repo_root)
+        );
+        repo_dir.close()
+    }
+
+    #[test]
+    fn discovery_dirs_stop_at_boundaries() {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            PathBuf::from("/mnt/share/project"),
+            PathBuf::from("/mnt/share/project"),
+        );
+        context.config = StarshipConfig {
+            config: Some(toml::toml! { repo_discovery_boundaries = ["/mnt/share"] }),
+        };
+        assert_eq!(
+            context.discovery_dirs(),
+            vec![Path::new("/mnt/share/project")]
+        );
+
+        // Like for git, a boundary only stops the search above the current directory
+        context.current_dir = PathBuf::from("/mnt/share");
+        assert_eq!(
+            context.discovery_dirs(),
+            vec![Path::new("/mnt/share"), Path::new("/mnt"), Path::new("/")]
+        );
+    }
+
+    #[test]
+    fn every_shell_is_in_all() {
+        // Each shell names the one after it, so that a new shell doesn't compile until it's
+        // added here, and then fails below until it's added to `Shell::ALL`
+        fn next(shell: Shell) -> Option<Shell> {
+            match shell {
+                Shell::Bash => Some(Shell::Fish),
+                Shell::Fish => Some(Shell::Ion),
+                Shell::Ion => Some(Shell::PowerShell),
+                Shell::PowerShell => Some(Shell::Zsh),
+                Shell::Zsh => Some(Shell::Elvish),
+                Shell::Elvish => Some(Shell::Tcsh),
+                Shell::Tcsh => Some(Shell::Unknown),
+                Shell::Unknown => None,
+            }
+        }
+
+        let shells: Vec<Shell> =
+            std::iter::successors(Some(Shell::Bash), |&shell| next(shell)).collect();
+        for shell in &shells {
+            assert!(
+                Shell::ALL.contains(shell),
+                "{:?} is missing from Shell::ALL",
+                shell
+            );
+        }
+        assert_eq!(shells.len(), Shell::ALL.len());
+    }
+
+    fn uses_symbol_fallback(env: &[(&'static str, &str)]) -> bool {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            PathBuf::new(),
+            PathBuf::new(),
+        );
+        for (key, value) in env {
+            context.env.insert(key, value.to_string());
+        }
+        context.use_symbol_fallback()
+    }
+
+    #[test]
+    fn symbol_fallback_follows_the_codeset() {
+        assert!(!uses_symbol_fallback(&[]));
+        assert!(!uses_symbol_fallback(&[("LANG", "en_US.UTF-8")]));
+        assert!(!uses_symbol_fallback(&[("LANG", "de_DE.utf8@euro")]));
+        assert!(!uses_symbol_fallback(&[("LC_ALL", "C.UTF-8")]));
+        assert!(!uses_symbol_fallback(&[("LC_CTYPE", "UTF-8")]));
+        assert!(!uses_symbol_fallback(&[("LC_CTYPE", "utf8")]));
+
+        assert!(uses_symbol_fallback(&[("LANG", "C")]));
+        assert!(uses_symbol_fallback(&[("LANG", "POSIX")]));
+        assert!(uses_symbol_fallback(&[("LANG", "en_US.ISO-8859-1")]));
+        assert!(uses_symbol_fallback(&[
+            ("LANG", "en_US.UTF-8"),
+            ("TERM", "linux")
+        ]));
+    }
+
+    fn wsl_context(env: &[(&'static str, &str)], proc_version: &str) -> Context<'static> {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            PathBuf::new(),
+            PathBuf::new(),
+        );
+        for (key, value) in env {
+            context.env.insert(key, value.to_string());
+        }
+        context.proc_version = Some(proc_version.to_string());
+        context
+    }
+
+    #[test]
+    fn detect_wsl1() {
+        let context = wsl_context(
+            &[],
+            "Linux version 4.4.0-19041-Microsoft (Microsoft@Microsoft.com) (gcc version 5.4.0 (GCC) ) #488-Microsoft Mon Sep 01 13:43:00 PST 2020",
+        );
+        assert!(context.is_wsl());
+    }
+
+    #[test]
+    fn detect_wsl2() {
+        let context = wsl_context(
+            &[
+                ("WSL_DISTRO_NAME", "Ubuntu"),
+                ("WSL_INTEROP", "/run/WSL/8_interop"),
+            ],
+            "Linux version 5.10.16.3-microsoft-standard-WSL2 (oe-user@oe-host) (x86_64-msft-linux-gcc (GCC) 9.3.0) #1 SMP Fri Apr 2 22:23:49 UTC 2021",
+        );
+        assert!(context.is_wsl());
+
+        let context = wsl_context(&[("WSL_DISTRO_NAME", "Ubuntu")], "");
+        assert!(context.is_wsl());
+    }
+
+    #[test]
+    fn detect_plain_linux() {
+        let context = wsl_context(
+            &[],
+            "Linux version 5.15.0-91-generic (buildd@lcy02-amd64-045) (gcc (Ubuntu 11.4.0-1ubuntu1~22.04) 11.4.0) #101-Ubuntu SMP",
+        );
+        assert!(!context.is_wsl());
+    }
+
+    #[test]
+    fn wsl_is_detected_once() {
+        let mut context = wsl_context(&[], "Linux version 5.15.90.1-microsoft-standard-WSL2");
+        assert!(context.is_wsl());
+
+        context.proc_version = None;
+        assert!(context.is_wsl());
+    }
+
+    #[test]
+    fn parse_git_version() {
+        assert_eq!(
+            GitVersion::parse("git version 2.39.2\n"),
+            Some(GitVersion(2, 39, 2))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.30.1.windows.1\n"),
+            Some(GitVersion(2, 30, 1))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.24.3 (Apple Git-128)\n"),
+            Some(GitVersion(2, 24, 3))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.40"),
+            Some(GitVersion(2, 40, 0))
+        );
+        assert_eq!(GitVersion::parse("hub version 2.14.2"), None);
+        assert_eq!(GitVersion::parse(""), None);
+        assert!(GitVersion(1, 8, 3) < GitVersion(2, 11, 0));
+    }
+
+    fn properties_context(shell: Shell) -> Context<'static> {
+        let path = PathBuf::from("/");
+        Context::new_with_shell_and_path(ArgMatches::default(), shell, path.clone(), path)
+    }
+
+    #[test]
+    fn properties_from_flags() {
+        let mut context = properties_context(Shell::Bash);
+        context.properties.insert("status_code", "1".into());
+        context.properties.insert("cmd_duration", "2500".into());
+
+        assert_eq!(context.get_property("status_code"), Some("1".into()));
+        assert_eq!(context.get_cmd_duration(), Some(2500));
+    }
+
+    #[test]
+    fn properties_from_env_without_flags() {
+        let mut context = properties_context(Shell::Bash);
+        context.env.insert("STARSHIP_STATUS", "1".into());
+        context.env.insert("STARSHIP_DURATION", "2500".into());
+        context.env.insert("STARSHIP_JOBS", "3".into());
+        context.env.insert("STARSHIP_KEYMAP", "vicmd".into());
+
+        assert_eq!(context.get_property("status_code"), Some("1".into()));
+        assert_eq!(context.get_cmd_duration(), Some(2500));
+        assert_eq!(context.get_property("jobs"), Some("3".into()));
+        assert_eq!(context.get_property("keymap"), Some("vicmd".into()));
+    }
+
+    #[test]
+    fn property_flags_win_over_env() {
+        let mut context = properties_context(Shell::Fish);
+        context.properties.insert("status_code", "0".into());
+        context.env.insert("STARSHIP_STATUS", "1".into());
+        context.env.insert("status", "2".into());
+
+        assert_eq!(context.get_property("status_code"), Some("0".into()));
+    }
+
+    #[test]
+    fn properties_unset_without_flags_or_env() {
+        let context = properties_context(Shell::Bash);
+
+        for (property, _) in PROPERTY_ENV_FALLBACKS {
+            assert_eq!(context.get_property(property), None);
+        }
+        assert_eq!(context.get_cmd_duration(), None);
+    }
+
+    #[test]
+    fn fish_properties_from_its_own_variables() {
+        let mut context = properties_context(Shell::Fish);
+        context.env.insert("status", "127".into());
+        context.env.insert("CMD_DURATION", "2500".into());
+        assert_eq!(context.get_property("status_code"), Some("127".into()));
+        assert_eq!(context.get_cmd_duration(), Some(2500));
+
+        context.env.insert("STARSHIP_STATUS", "1".into());
+        assert_eq!(context.get_property("status_code"), Some("1".into()));
+
+        let mut context = properties_context(Shell::Zsh);
+        context.env.insert("status", "127".into());
+        assert_eq!(context.get_property("status_code"), None);
+    }
+
+    fn slow_fs_context(dir: &Path, fs_timeout_ms: i64) -> Context<'static> {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            dir.into(),
+            dir.into(),
+        );
+        context.config = StarshipConfig {
+            config: Some(toml::toml! { fs_timeout_ms = fs_timeout_ms }),
+        };
+        context.mock_fs_clock = Some(Arc::new(crate::fs_budget::SteppingClock::new(
+            Duration::from_millis(150),
+        )));
+        context
+    }
+
+    #[test]
+    fn slow_fs_skips_scan_and_repo_discovery() -> io::Result<()> {
+        let dir = testdir(&["package.json"])?;
+        git2::Repository::init(dir.path()).unwrap();
+        let context = slow_fs_context(dir.path(), 100);
+
+        // The first check takes longer than the budget, which skips the others
+        assert!(context.path_exists(&dir.path().join("package.json")));
+        assert!(context.is_fs_slow());
+        assert!(!context.path_exists(&dir.path().join("package.json")));
+        assert!(!context
+            .try_begin_scan()
+            .unwrap()
+            .set_files(&["package.json"])
+            .is_match());
+        assert_eq!(context.get_repo()?.root, None);
+        dir.close()
+    }
+
+    #[test]
+    fn fs_checks_without_fs_timeout() -> io::Result<()> {
+        let dir = testdir(&["package.json"])?;
+        git2::Repository::init(dir.path()).unwrap();
+        let context = slow_fs_context(dir.path(), 0);
+
+        assert!(context.path_exists(&dir.path().join("package.json")));
+        assert!(context
+            .try_begin_scan()
+            .unwrap()
+            .set_files(&["package.json"])
+            .is_match());
+        assert!(context.get_repo()?.root.is_some());
+        assert!(!context.is_fs_slow());
+        dir.close()
+    }
 }
@@ -0,0 +1,262 @@
+use pest::error::InputLocation;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::formatter::string_formatter::StringFormatterError;
+use crate::logger;
+
+/// What a `StarshipError` is about, so that `--strict` can fail on some kinds of problems only
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// A problem with the configuration, which the user can fix in their config file
+    Config,
+    /// A command that didn't run as expected
+    Command,
+    /// A file that couldn't be read
+    Io,
+}
+
+impl ErrorCategory {
+    pub const ALL: [ErrorCategory; 3] = [
+        ErrorCategory::Config,
+        ErrorCategory::Command,
+        ErrorCategory::Io,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCategory::Config => "config",
+            ErrorCategory::Command => "command",
+            ErrorCategory::Io => "io",
+        }
+    }
+}
+
+impl FromStr for ErrorCategory {
+    type Err = String;
+
+    fn from_str(category: &str) -> Result<Self, Self::Err> {
+        ErrorCategory::ALL
+            .iter()
+            .find(|known| known.as_str() == category)
+            .copied()
+            .ok_or_else(|| format!("Unknown error category `{}`", category))
+    }
+}
+
+/// A problem starship reports while computing the prompt. The `Display` output is the message
+/// shown to the user.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StarshipError {
+    /// The config file isn't valid TOML, JSON or YAML
+    ConfigParse { message: String },
+    /// The config file exists, but couldn't be read
+    ConfigRead { message: String },
+    /// A config key that isn't an option, with the closest option if it's a likely typo
+    UnknownKey {
+        key: String,
+        suggestion: Option<String>,
+    },
+    /// The format string of a module couldn't be rendered, either because it doesn't parse or
+    /// because one of its variables failed
+    Format {
+        module: String,
+        /// The byte range of the format string where it stopped parsing
+        span: Option<(usize, usize)>,
+        message: String,
+    },
+    /// A style string that doesn't parse
+    StyleParse { key: String, style: String },
+    /// A command that didn't finish within `command_timeout`
+    CommandTimeout { command: String },
+}
+
+impl StarshipError {
+    /// The error of rendering the format string of `module`
+    pub fn from_format(module: &str, error: StringFormatterError) -> Self {
+        let span = match &error {
+            StringFormatterError::Parse(error) => Some(match error.location {
+                InputLocation::Pos(position) => (position, position),
+                InputLocation::Span(span) => span,
+            }),
+            StringFormatterError::Custom(_) => None,
+        };
+        StarshipError::Format {
+            module: module.to_owned(),
+            span,
+            message: error.to_string(),
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            StarshipError::ConfigParse { .. }
+            | StarshipError::UnknownKey { .. }
+            | StarshipError::Format { .. }
+            | StarshipError::StyleParse { .. } => ErrorCategory::Config,
+            StarshipError::CommandTimeout { .. } => ErrorCategory::Command,
+            StarshipError::ConfigRead { .. } => ErrorCategory::Io,
+        }
+    }
+
+    /// The module the error is about, if any
+    pub fn module(&self) -> Option<&str> {
+        match self {
+            StarshipError::Format { module, .. } => Some(module),
+            StarshipError::StyleParse { key, .. } => key.split('.').next(),
+            _ => None,
+        }
+    }
+
+    /// The byte range of the format string where it stopped parsing, for format errors
+    pub fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            StarshipError::Format { span, .. } => *span,
+            _ => None,
+        }
+    }
+
+    /// Logs the error and counts it in its category. A config file that can't be parsed or read
+    /// is logged as an error on every prompt, since the whole config is ignored. All other errors
+    /// are warnings that are only shown once per session, see `log_once!`.
+    pub fn log(&self) {
+        logger::count_error(self.category());
+        match self {
+            StarshipError::ConfigParse { .. } | StarshipError::ConfigRead { .. } => {
+                log::error!("{}", self)
+            }
+            StarshipError::UnknownKey {
+                suggestion: Some(suggestion),
+                ..
+            } => crate::log_once!("{}\nDid you mean '{}'?", self, suggestion),
+            _ => crate::log_once!("{}", self),
+        }
+    }
+}
+
+impl fmt::Display for StarshipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StarshipError::ConfigParse { message } => {
+                write!(f, "Unable to parse the config file: {}", message)
+            }
+            StarshipError::ConfigRead { message } => {
+                write!(f, "Unable to read config file content: {}", message)
+            }
+            StarshipError::UnknownKey { key, .. } => write!(f, "Unknown config key '{}'", key),
+            StarshipError::Format {
+                module, message, ..
+            } => write!(f, "Error in module `{}`:\n{}", module, message),
+            StarshipError::StyleParse { key, style } => {
+                write!(f, "invalid style `{}` in `{}`", style, key)
+            }
+            StarshipError::CommandTimeout { command } => write!(
+                f,
+                "Executing command {:?} timed out.\nYou can set command_timeout in your config to a higher value to allow longer-running commands to keep executing.",
+                command
+            ),
+        }
+    }
+}
+
+impl Error for StarshipError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::StringFormatter;
+
+    #[test]
+    fn format_syntax_error_has_span() {
+        let error = StringFormatter::new("[$branch").err().unwrap();
+        let rendered = error.to_string();
+        let error = StarshipError::from_format("git_branch", error);
+
+        assert_eq!(error.module(), Some("git_branch"));
+        assert_eq!(error.span(), Some((8, 8)));
+        assert_eq!(error.category(), ErrorCategory::Config);
+        assert_eq!(
+            error.to_string(),
+            format!("Error in module `git_branch`:\n{}", rendered)
+        );
+    }
+
+    #[test]
+    fn variable_error_has_no_span() {
+        let error = StringFormatterError::Custom("Invalid variable".to_owned());
+        let error = StarshipError::from_format("custom.foo", error);
+
+        assert_eq!(error.module(), Some("custom.foo"));
+        assert_eq!(error.span(), None);
+        assert_eq!(
+            error.to_string(),
+            "Error in module `custom.foo`:\nInvalid variable"
+        );
+    }
+
+    #[test]
+    fn messages_and_categories() {
+        let cases = [
+            (
+                StarshipError::ConfigParse {
+                    message: "expected `=`".to_owned(),
+                },
+                "Unable to parse the config file: expected `=`",
+                ErrorCategory::Config,
+            ),
+            (
+                StarshipError::ConfigRead {
+                    message: "Permission denied".to_owned(),
+                },
+                "Unable to read config file content: Permission denied",
+                ErrorCategory::Io,
+            ),
+            (
+                StarshipError::UnknownKey {
+                    key: "formt".to_owned(),
+                    suggestion: Some("format".to_owned()),
+                },
+                "Unknown config key 'formt'",
+                ErrorCategory::Config,
+            ),
+            (
+                StarshipError::StyleParse {
+                    key: "git_branch.style".to_owned(),
+                    style: "bold purpel".to_owned(),
+                },
+                "invalid style `bold purpel` in `git_branch.style`",
+                ErrorCategory::Config,
+            ),
+            (
+                StarshipError::CommandTimeout {
+                    command: "git".to_owned(),
+                },
+                "Executing command \"git\" timed out.\nYou can set command_timeout in your config to a higher value to allow longer-running commands to keep executing.",
+                ErrorCategory::Command,
+            ),
+        ];
+
+        for (error, message, category) in cases {
+            assert_eq!(error.to_string(), message);
+            assert_eq!(error.category(), category);
+        }
+    }
+
+    #[test]
+    fn style_error_module() {
+        let error = StarshipError::StyleParse {
+            key: "git_branch.style".to_owned(),
+            style: "purpel".to_owned(),
+        };
+        assert_eq!(error.module(), Some("git_branch"));
+    }
+
+    #[test]
+    fn parse_categories() {
+        for category in ErrorCategory::ALL {
+            assert_eq!(category.as_str().parse(), Ok(category));
+        }
+        assert!("warnings".parse::<ErrorCategory>().is_err());
+    }
+}
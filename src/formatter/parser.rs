@@ -1,7 +1,8 @@
 // Can't rename internal Pest names
 #![allow(clippy::upper_case_acronyms)]
 
-use pest::{error::Error, iterators::Pair, Parser};
+use pest::error::{Error, ErrorVariant};
+use pest::{iterators::Pair, Parser, Position};
 use pest_derive::*;
 
 use super::model::*;
@@ -59,7 +60,45 @@ fn parse_style(style: Pair<Rule>) -> Vec<StyleElement> {
         .collect()
 }
 
+/// How deeply text groups and conditionals can be nested. The grammar is parsed recursively, so
+/// a format string like `[[[[...` would otherwise overflow the stack.
+const MAX_NESTING: usize = 32;
+
+/// Finds the first bracket nested deeper than `MAX_NESTING`
+fn find_excess_nesting(format: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut escaped = false;
+
+    for (index, c) in format.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' | '(' => {
+                depth += 1;
+                if depth > MAX_NESTING {
+                    return Some(index);
+                }
+            }
+            ']' | ')' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    None
+}
+
 pub fn parse(format: &str) -> Result<Vec<FormatElement>, Error<Rule>> {
+    if let Some(index) = find_excess_nesting(format) {
+        let message = format!(
+            "text groups and conditionals can't be nested more than {} levels deep",
+            MAX_NESTING
+        );
+        return Err(Error::new_from_pos(
+            ErrorVariant::CustomError { message },
+            Position::new(format, index).unwrap(),
+        ));
+    }
+
     IdentParser::parse(Rule::expression, format).map(|pairs| {
         pairs
             .take_while(|pair| pair.as_rule() != Rule::EOI)
@@ -1,10 +1,12 @@
 use ansi_term::Style;
+use once_cell::sync::OnceCell;
 use pest::error::Error as PestError;
 use rayon::prelude::*;
 use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
 use std::error::Error;
 use std::fmt;
+use std::sync::Arc;
 
 use crate::config::parse_style_string;
 use crate::segment::Segment;
@@ -17,6 +19,21 @@ enum VariableValue<'a> {
     Plain(Cow<'a, str>),
     Styled(Vec<Segment>),
     Meta(Vec<FormatElement<'a>>),
+    Lazy(Arc<LazyValue<'a>>),
+}
+
+type LazyResult = Option<Result<Vec<Segment>, StringFormatterError>>;
+
+/// The value of a variable of `StringFormatter::map_lazy`, computed the first time it's needed
+struct LazyValue<'a> {
+    compute: Box<dyn Fn() -> LazyResult + Send + Sync + 'a>,
+    value: OnceCell<LazyResult>,
+}
+
+impl LazyValue<'_> {
+    fn get(&self) -> Option<&Result<Vec<Segment>, StringFormatterError>> {
+        self.value.get_or_init(|| (self.compute)()).as_ref()
+    }
 }
 
 impl<'a> Default for VariableValue<'a> {
@@ -53,6 +70,15 @@ impl From<String> for StringFormatterError {
     }
 }
 
+/// Where a format string is used, which decides what its variables refer to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FormatScope<'a> {
+    /// The root `format`, whose variables are modules
+    Root,
+    /// The format of a module, whose variables are the values of that module
+    Module(&'a str),
+}
+
 pub struct StringFormatter<'a> {
     format: Vec<FormatElement<'a>>,
     variables: VariableMapType<'a>,
@@ -100,6 +126,11 @@ impl<'a> StringFormatter<'a> {
     ///
     /// - `Some(Ok(_))`: The value of this variable will be displayed in the format string.
     ///
+    /// The mapper is only called for variables found in the format string (including the format
+    /// strings of meta-variables), so expensive values are never computed unless they're used.
+    /// Use `VariableHolder::get_variables` to check for a variable before doing work up front, or
+    /// `StringFormatter::map_lazy` to only do it once the variable is rendered.
+    ///
     pub fn map<T, M>(mut self, mapper: M) -> Self
     where
         T: Into<Cow<'a, str>>,
@@ -114,6 +145,47 @@ impl<'a> StringFormatter<'a> {
         self
     }
 
+    /// Maps variable name to a closure computing its value, for values that are expensive to
+    /// compute
+    ///
+    /// Unlike the values of `StringFormatter::map`, the closure is only called once the variable
+    /// is rendered, or when a conditional group can't be pruned without it because none of its
+    /// other variables have a value. It's called at most once.
+    ///
+    /// See `StringFormatter::map` for description on the values returned by the closure.
+    pub fn map_lazy<T, M, F>(self, mapper: M) -> Self
+    where
+        T: Into<String>,
+        M: Fn(&str) -> Option<F> + Sync,
+        F: Fn() -> Option<Result<T, StringFormatterError>> + Send + Sync + 'a,
+    {
+        self.map_lazy_segments(|variable| {
+            let compute = mapper(variable)?;
+            Some(move || compute().map(|var| var.map(|text| vec![Segment::new(None, text)])))
+        })
+    }
+
+    /// Maps variable name to a closure computing an array of segments, see
+    /// `StringFormatter::map_lazy` and `StringFormatter::map_variables_to_segments`
+    pub fn map_lazy_segments<M, F>(mut self, mapper: M) -> Self
+    where
+        M: Fn(&str) -> Option<F> + Sync,
+        F: Fn() -> Option<Result<Vec<Segment>, StringFormatterError>> + Send + Sync + 'a,
+    {
+        self.variables
+            .par_iter_mut()
+            .filter(|(_, value)| value.is_none())
+            .for_each(|(key, value)| {
+                *value = mapper(key).map(|compute| {
+                    Ok(VariableValue::Lazy(Arc::new(LazyValue {
+                        compute: Box::new(compute),
+                        value: OnceCell::new(),
+                    })))
+                });
+            });
+        self
+    }
+
     /// Maps a meta-variable to a format string containing other variables.
     ///
     /// This function should be called **before** other map methods so that variables found in
@@ -160,6 +232,10 @@ impl<'a> StringFormatter<'a> {
 
     /// Maps variable name to an array of segments
     ///
+    /// Each segment keeps its own style. Segments without a style inherit the
+    /// style of the group surrounding the variable, the same way a plain string
+    /// would.
+    ///
     /// See `StringFormatter::map` for description on the parameters.
     pub fn map_variables_to_segments<M>(mut self, mapper: M) -> Self
     where
@@ -238,6 +314,19 @@ impl<'a> StringFormatter<'a> {
                 .transpose()
         }
 
+        fn inherit_style(segments: Vec<Segment>, style: Option<Style>) -> Vec<Segment> {
+            segments
+                .into_iter()
+                .map(|mut segment| {
+                    // Derive upper style if the style of segments are none.
+                    if segment.style.is_none() {
+                        segment.style = style;
+                    };
+                    segment
+                })
+                .collect()
+        }
+
         fn parse_format<'a>(
             format: Vec<FormatElement<'a>>,
             style: Option<Style>,
@@ -261,17 +350,14 @@ impl<'a> StringFormatter<'a> {
                             .expect("Uncached variable found")
                             .as_ref()
                             .map(|segments| match segments.clone()? {
-                                VariableValue::Styled(segments) => Ok(segments
-                                    .into_iter()
-                                    .map(|mut segment| {
-                                        // Derive upper style if the style of segments are none.
-                                        if segment.style.is_none() {
-                                            segment.style = style;
-                                        };
-                                        segment
-                                    })
-                                    .collect()),
+                                VariableValue::Styled(segments) => {
+                                    Ok(inherit_style(segments, style))
+                                }
                                 VariableValue::Plain(text) => Ok(vec![Segment::new(style, text)]),
+                                VariableValue::Lazy(value) => match value.get() {
+                                    Some(segments) => Ok(inherit_style(segments.clone()?, style)),
+                                    None => Ok(Vec::new()),
+                                },
                                 VariableValue::Meta(format) => {
                                     let formatter = StringFormatter {
                                         format,
@@ -289,33 +375,46 @@ impl<'a> StringFormatter<'a> {
                                 format_elements: &[FormatElement],
                                 variables: &'a VariableMapType<'a>,
                             ) -> bool {
-                                format_elements.get_variables().iter().any(|var| {
-                                    variables
-                                        .get(var.as_ref())
-                                        .map(|map_result| {
-                                            let map_result = map_result.as_ref();
-                                            map_result
-                                                .and_then(|result| result.as_ref().ok())
-                                                .map(|result| match result {
-                                                    // If the variable is a meta variable, also
-                                                    // check the format string inside it.
-                                                    VariableValue::Meta(meta_elements) => {
-                                                        let meta_variables =
-                                                            clone_without_meta(variables);
-                                                        should_show_elements(
-                                                            &meta_elements,
-                                                            &meta_variables,
-                                                        )
-                                                    }
-                                                    _ => true,
-                                                })
-                                                // The variable is None or Err, or a meta variable
-                                                // that shouldn't show
-                                                .unwrap_or(false)
-                                        })
-                                        // Can't find the variable in format string
-                                        .unwrap_or(false)
-                                })
+                                let format_variables = format_elements.get_variables();
+                                let any_shown = |compute_lazy: bool| {
+                                    format_variables.iter().any(|var| {
+                                        variables
+                                            .get(var.as_ref())
+                                            .map(|map_result| {
+                                                let map_result = map_result.as_ref();
+                                                map_result
+                                                    .and_then(|result| result.as_ref().ok())
+                                                    .map(|result| match result {
+                                                        // If the variable is a meta variable, also
+                                                        // check the format string inside it.
+                                                        VariableValue::Meta(meta_elements) => {
+                                                            let meta_variables =
+                                                                clone_without_meta(variables);
+                                                            should_show_elements(
+                                                                &meta_elements,
+                                                                &meta_variables,
+                                                            )
+                                                        }
+                                                        VariableValue::Lazy(value) => {
+                                                            compute_lazy
+                                                                && matches!(
+                                                                    value.get(),
+                                                                    Some(Ok(_))
+                                                                )
+                                                        }
+                                                        _ => true,
+                                                    })
+                                                    // The variable is None or Err, or a meta
+                                                    // variable that shouldn't show
+                                                    .unwrap_or(false)
+                                            })
+                                            // Can't find the variable in format string
+                                            .unwrap_or(false)
+                                    })
+                                };
+
+                                // Lazy variables are only computed if the others don't decide it
+                                any_shown(false) || any_shown(true)
                             }
 
                             let should_show: bool = should_show_elements(&format, variables);
@@ -341,6 +440,66 @@ impl<'a> StringFormatter<'a> {
     }
 }
 
+impl<'a> StringFormatter<'a> {
+    /// The style strings of the text groups that don't depend on any style variables
+    pub fn get_literal_styles(&self) -> Vec<String> {
+        fn collect(format: &[FormatElement], styles: &mut Vec<String>) {
+            for el in format {
+                match el {
+                    FormatElement::TextGroup(textgroup) => {
+                        let literal = textgroup.style.iter().all(|style| match style {
+                            StyleElement::Text(_) => true,
+                            StyleElement::Variable(_) => false,
+                        });
+                        if literal {
+                            styles.push(
+                                textgroup
+                                    .style
+                                    .iter()
+                                    .map(|style| match style {
+                                        StyleElement::Text(text) => text.as_ref(),
+                                        StyleElement::Variable(_) => "",
+                                    })
+                                    .collect(),
+                            );
+                        }
+                        collect(&textgroup.format, styles);
+                    }
+                    FormatElement::Conditional(format) => collect(format, styles),
+                    _ => (),
+                }
+            }
+        }
+
+        let mut styles = Vec::new();
+        collect(&self.format, &mut styles);
+        styles
+    }
+}
+
+impl<'a> StringFormatter<'a> {
+    /// The variables that name other modules, which a format in `scope` can't render
+    ///
+    /// Modules can only be combined in the root `format`, so in the format of a module these
+    /// variables are always empty, unless the module has a variable of the same name.
+    pub fn get_module_references(&self, scope: FormatScope) -> Vec<String> {
+        let module = match scope {
+            FormatScope::Root => return Vec::new(),
+            FormatScope::Module(module) => module,
+        };
+        let own_variables = crate::modules::variables(module);
+
+        self.variables
+            .keys()
+            .filter(|variable| {
+                crate::module::is_module_name(variable)
+                    && !own_variables.contains(&variable.as_str())
+            })
+            .cloned()
+            .collect()
+    }
+}
+
 impl<'a> VariableHolder<String> for StringFormatter<'a> {
     fn get_variables(&self) -> BTreeSet<String> {
         self.variables.keys().cloned().collect()
@@ -516,6 +675,59 @@ mod tests {
         match_next!(result_iter, "styled_no_modifier", styled_no_modifier_style);
     }
 
+    #[test]
+    fn test_segments_in_style_group() {
+        const FORMAT_STR: &str = "[<$var>](red bold)";
+        let outer_style = Some(Color::Red.bold());
+        let green_style = Some(Color::Green.normal());
+        let blue_style = Some(Color::Blue.italic());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_variables_to_segments(|variable| match variable {
+                "var" => Some(Ok(vec![
+                    Segment::new(green_style, "a"),
+                    Segment::new(None, "b"),
+                    Segment::new(blue_style, "c"),
+                ])),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "<", outer_style);
+        match_next!(result_iter, "a", green_style);
+        match_next!(result_iter, "b", outer_style);
+        match_next!(result_iter, "c", blue_style);
+        match_next!(result_iter, ">", outer_style);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_segments_in_conditional() {
+        const FORMAT_STR: &str = "($some )($none )end";
+        let green_style = Some(Color::Green.normal());
+        let blue_style = Some(Color::Blue.normal());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_variables_to_segments(|variable| match variable {
+                "some" => Some(Ok(vec![
+                    Segment::new(green_style, "a"),
+                    Segment::new(None, "b"),
+                    Segment::new(blue_style, "c"),
+                ])),
+                _ => None,
+            });
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "a", green_style);
+        match_next!(result_iter, "b", None);
+        match_next!(result_iter, "c", blue_style);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "end", None);
+        assert!(result_iter.next().is_none());
+    }
+
     #[test]
     fn test_meta_variable() {
         const FORMAT_STR: &str = "$all";
@@ -665,4 +877,145 @@ mod tests {
         });
         assert!(segments.is_err());
     }
+
+    #[test]
+    fn test_lazy_variables() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const FORMAT_STR: &str = "($some$lazy) $lazy";
+        let computed = AtomicUsize::new(0);
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "some" => Some(Ok("some")),
+                _ => None,
+            })
+            .map_lazy(|var| match var {
+                "lazy" => Some(|| {
+                    computed.fetch_add(1, Ordering::SeqCst);
+                    Some(Ok("lazy"))
+                }),
+                _ => None,
+            });
+        assert_eq!(computed.load(Ordering::SeqCst), 0);
+
+        let result = formatter.parse(None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "some", None);
+        match_next!(result_iter, "lazy", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "lazy", None);
+        assert!(result_iter.next().is_none());
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_lazy_variable_after_error() {
+        const FORMAT_STR: &str = "$never$lazy";
+
+        let segments = StringFormatter::new(FORMAT_STR).and_then(|formatter| {
+            formatter
+                .map(|var| -> Option<Result<String, _>> {
+                    match var {
+                        "never" => Some(Err(StringFormatterError::Custom("NEVER".to_owned()))),
+                        _ => None,
+                    }
+                })
+                .map_lazy(|var| match var {
+                    "lazy" => Some(|| -> Option<Result<String, StringFormatterError>> {
+                        panic!("`$lazy` is never rendered")
+                    }),
+                    _ => None,
+                })
+                .parse(None)
+        });
+        assert!(segments.is_err());
+    }
+
+    #[test]
+    fn test_lazy_variable_decides_group() {
+        const FORMAT_STR: &str = "($none$lazy)($none$missing)";
+
+        let result = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_lazy(|var| {
+                let value = match var {
+                    "lazy" => Some("lazy"),
+                    "missing" => None,
+                    _ => return None,
+                };
+                Some(move || value.map(Ok))
+            })
+            .parse(None)
+            .unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "lazy", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_module_references() {
+        const FORMAT_STR: &str = "$status$directory$git_branch$custom$path";
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap();
+
+        assert!(formatter
+            .get_module_references(FormatScope::Root)
+            .is_empty());
+        assert_eq!(
+            formatter.get_module_references(FormatScope::Module("status")),
+            vec!["custom", "directory", "git_branch"]
+        );
+        assert_eq!(
+            formatter.get_module_references(FormatScope::Module("directory")),
+            vec!["custom", "directory", "git_branch", "status"]
+        );
+    }
+
+    fn nested_groups(depth: usize) -> String {
+        format!("{}text{}", "[".repeat(depth), "](red)".repeat(depth))
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let segments = StringFormatter::new(&nested_groups(32))
+            .unwrap()
+            .parse(None)
+            .unwrap();
+        assert_eq!(segments[0].value, "text");
+
+        let error = StringFormatter::new(&nested_groups(33)).err().unwrap();
+        assert!(error.to_string().contains("more than 32 levels deep"));
+
+        // Only unescaped brackets nest
+        assert!(StringFormatter::new(&"\\[".repeat(1000)).is_ok());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_never_panics(format in r"[\[\]()$\\{}a-z ]{0,100}|\PC{0,100}") {
+            if let Ok(formatter) = StringFormatter::new(&format) {
+                let _ = formatter.map(|_| Some(Ok("value"))).parse(None);
+            }
+        }
+
+        #[test]
+        fn prop_deep_nesting_never_panics(depth in 0..10_000usize, open in r"\[|\(") {
+            let _ = StringFormatter::new(&open.repeat(depth));
+        }
+
+        #[test]
+        fn prop_roundtrips_literals(text in r"\PC{1,100}") {
+            let escaped: String = text
+                .chars()
+                .flat_map(|c| match c {
+                    '[' | ']' | '(' | ')' | '\\' | '$' => vec!['\\', c],
+                    _ => vec![c],
+                })
+                .collect();
+            let segments = StringFormatter::new(&escaped).unwrap().parse(None).unwrap();
+            let rendered: String = segments.iter().map(|segment| segment.value.as_str()).collect();
+            proptest::prop_assert_eq!(rendered, text);
+        }
+    }
 }
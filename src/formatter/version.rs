@@ -9,6 +9,9 @@ pub struct VersionFormatter<'a> {
 }
 
 impl<'a> VersionFormatter<'a> {
+    /// The variables a `version_format` can use
+    pub const VARIABLES: &'static [&'static str] = &["raw", "major", "minor", "patch"];
+
     /// Creates an instance of a VersionFormatter from a format string
     ///
     /// Like the StringFormatter, this will throw an error when the string isn't
@@ -69,7 +72,7 @@ impl<'a> VersionFormatter<'a> {
         match VersionFormatter::format_version(version, version_format) {
             Ok(formatted) => Some(formatted),
             Err(error) => {
-                log::warn!("Error formatting `{}` version:\n{}", module_name, error);
+                crate::log_once!("Error formatting `{}` version:\n{}", module_name, error);
                 Some(format!("v{}", version))
             }
         }
@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The source of the time filesystem operations are measured with
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The actual time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that moves forward by `step` each time it is read, so that every operation
+/// measured with it seems to take `step`
+#[cfg(test)]
+pub struct SteppingClock {
+    now: Mutex<Instant>,
+    step: Duration,
+}
+
+#[cfg(test)]
+impl SteppingClock {
+    pub fn new(step: Duration) -> Self {
+        Self {
+            now: Mutex::new(Instant::now()),
+            step,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Clock for SteppingClock {
+    fn now(&self) -> Instant {
+        let mut now = self.now.lock().unwrap();
+        let current = *now;
+        *now += self.step;
+        current
+    }
+}
+
+/// The time filesystem operations may take in total for a prompt, see `fs_timeout_ms`.
+///
+/// Once the operations took longer than the budget, the remaining ones are skipped, which
+/// keeps slow network filesystems from holding up the prompt.
+pub struct FsBudget {
+    /// The total time the operations may take, or `None` for no limit
+    budget: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    spent: Mutex<Duration>,
+    exhausted: AtomicBool,
+}
+
+impl FsBudget {
+    /// A budget of `budget_ms` milliseconds, where `0` means there is no limit
+    pub fn new(budget_ms: u64, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            budget: Some(Duration::from_millis(budget_ms)).filter(|budget| !budget.is_zero()),
+            clock,
+            spent: Mutex::new(Duration::default()),
+            exhausted: AtomicBool::new(false),
+        }
+    }
+
+    /// Runs the filesystem operation `op` and adds the time it took to the budget, or returns
+    /// `None` without running it if the budget is exhausted
+    pub fn spend<T>(&self, op: impl FnOnce() -> T) -> Option<T> {
+        let budget = match self.budget {
+            Some(budget) => budget,
+            None => return Some(op()),
+        };
+        if self.is_exhausted() {
+            return None;
+        }
+
+        let start = self.clock.now();
+        let result = op();
+        let elapsed = self.clock.now().saturating_duration_since(start);
+
+        let mut spent = self.spent.lock().unwrap();
+        *spent += elapsed;
+        if *spent > budget && !self.exhausted.swap(true, Ordering::Relaxed) {
+            log::warn!(
+                "Filesystem access took longer than `fs_timeout_ms` ({}ms), skipping the \
+                remaining checks",
+                budget.as_millis()
+            );
+        }
+        Some(result)
+    }
+
+    /// Whether the filesystem operations took longer than the budget, so that the next ones
+    /// are skipped
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_operations_once_exhausted() {
+        let clock = Arc::new(SteppingClock::new(Duration::from_millis(60)));
+        let budget = FsBudget::new(100, clock);
+
+        assert_eq!(budget.spend(|| 1), Some(1));
+        assert!(!budget.is_exhausted());
+        // The operation that exhausts the budget still returns its result
+        assert_eq!(budget.spend(|| 2), Some(2));
+        assert!(budget.is_exhausted());
+
+        let mut skipped = true;
+        assert_eq!(budget.spend(|| skipped = false), None);
+        assert!(skipped);
+    }
+
+    #[test]
+    fn no_limit_without_budget() {
+        let clock = Arc::new(SteppingClock::new(Duration::from_secs(60)));
+        let budget = FsBudget::new(0, clock);
+
+        for i in 0..10 {
+            assert_eq!(budget.spend(|| i), Some(i));
+        }
+        assert!(!budget.is_exhausted());
+    }
+}
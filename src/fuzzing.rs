@@ -0,0 +1,7 @@
+//! Entry points for the fuzz targets in `fuzz/`, for parsers that aren't public otherwise.
+//! This isn't a stable API.
+
+/// Parses the output of `git status` like the `git_status` module does
+pub fn parse_porcelain_output(output: &str, porcelain_v2: bool, nul_separated: bool) {
+    crate::modules::git_status::parse_porcelain_output(output, porcelain_v2, nul_separated);
+}
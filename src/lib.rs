@@ -5,18 +5,30 @@ shadow!(shadow);
 
 // Lib is present to allow for benchmarking
 pub mod bug_report;
+pub mod cache;
 pub mod config;
+pub mod config_check;
 pub mod configs;
 pub mod configure;
 pub mod context;
+pub mod error;
 pub mod formatter;
+pub mod fs_budget;
+#[doc(hidden)]
+pub mod fuzzing;
 pub mod init;
 pub mod logger;
 pub mod module;
 mod modules;
+pub mod network;
 pub mod print;
+mod repaint;
 mod segment;
+mod shell_escape;
+pub mod startup;
+pub mod update_check;
 mod utils;
+pub mod watch;
 
 #[cfg(test)]
 mod test;
@@ -1,20 +1,102 @@
+use crate::error::ErrorCategory;
 use ansi_term::Color;
-use log::{Level, LevelFilter, Metadata, Record};
+use clap::ArgMatches;
+use log::{Level, LevelFilter, Log, Metadata, Record};
 use once_cell::sync::OnceCell;
 use std::{
+    cell::RefCell,
     collections::HashSet,
     env,
     fs::{self, File, OpenOptions},
     io::Write,
-    path::PathBuf,
-    sync::Mutex,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
+/// The number of warnings and errors logged by this process
+static WARNING_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of `StarshipError`s logged by this process, indexed by `ErrorCategory`
+static ERROR_COUNTS: [AtomicUsize; ErrorCategory::ALL.len()] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+thread_local! {
+    /// The counter of the prompt rendering on this thread, see `counting_errors`
+    static PROMPT_ERRORS: RefCell<Option<Arc<ErrorCounter>>> = const { RefCell::new(None) };
+}
+
+/// The logger of the process, kept so that `configure` can change it once the command line
+/// arguments are parsed
+static LOGGER: OnceCell<StarshipLogger> = OnceCell::new();
+
+/// The log levels, from the least to the most verbose
+const LEVELS: [Level; 5] = [
+    Level::Error,
+    Level::Warn,
+    Level::Info,
+    Level::Debug,
+    Level::Trace,
+];
+
+/// Log target of warnings that are shown only once per session
+pub const ONCE_TARGET: &str = "starship::once";
+
+/// Log a warning about the configuration.
+///
+/// Every occurrence is written to the session log, but the warning is printed
+/// only the first time it is seen in a session, so a broken config doesn't
+/// repeat the same message on every prompt. Other log lines are only kept from
+/// being printed again by later invocations, not within the same one.
+#[macro_export]
+macro_rules! log_once {
+    ($($arg:tt)+) => (log::warn!(target: $crate::logger::ONCE_TARGET, $($arg)+))
+}
+
+/// How the logger is changed by the command line arguments, see `configure`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LogOptions {
+    /// How many times `--verbose` was given, each raising the log level by one
+    pub verbosity: u64,
+    /// The `--log-file` to write to instead of the session log
+    pub log_file: Option<PathBuf>,
+    /// Whether logs only go to the log file unless `--verbose` is given, for commands whose
+    /// output is read by the shell, where anything printed to stderr messes up the prompt
+    pub quiet: bool,
+}
+
+impl LogOptions {
+    /// Reads the global `--verbose` and `--log-file` arguments, which are in the matches of
+    /// the subcommand they were given with. Only the prompt is quiet.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let (subcommand, args) = matches.subcommand();
+        let args = args.unwrap_or(matches);
+        LogOptions {
+            verbosity: args.occurrences_of("verbose"),
+            log_file: args.value_of_os("log_file").map(PathBuf::from),
+            quiet: subcommand == "prompt",
+        }
+    }
+}
+
 pub struct StarshipLogger {
-    log_file: OnceCell<Mutex<File>>,
-    log_file_path: PathBuf,
-    log_file_content: HashSet<String>,
-    log_level: Level,
+    /// The opened log file, or `None` if it couldn't be opened, in which case the lines meant
+    /// for it are dropped
+    log_file: OnceCell<Option<Mutex<File>>>,
+    log_file_path: Mutex<PathBuf>,
+    /// Lines already shown in this session, seeded from the session log
+    log_file_content: Mutex<HashSet<String>>,
+    /// The minimum `Level` as a `usize`, so that it can be raised by `configure`
+    log_level: AtomicUsize,
+    /// Whether logs are printed to stderr, besides the log file
+    print_to_stderr: AtomicBool,
+    #[cfg(test)]
+    printed: Mutex<Vec<String>>,
 }
 
 impl Default for StarshipLogger {
@@ -35,23 +117,24 @@ impl Default for StarshipLogger {
         ));
 
         Self {
-            log_file_content: fs::read_to_string(&session_log_file)
-                .unwrap_or_default()
-                .lines()
-                .map(|line| line.to_string())
-                .collect(),
+            log_file_content: Mutex::new(read_log_lines(&session_log_file)),
             log_file: OnceCell::new(),
-            log_file_path: session_log_file,
-            log_level: env::var("STARSHIP_LOG")
-                .map(|level| match level.to_lowercase().as_str() {
-                    "trace" => Level::Trace,
-                    "debug" => Level::Debug,
-                    "info" => Level::Info,
-                    "warn" => Level::Warn,
-                    "error" => Level::Error,
-                    _ => Level::Warn,
-                })
-                .unwrap_or_else(|_| Level::Warn),
+            log_file_path: Mutex::new(session_log_file),
+            log_level: AtomicUsize::new(
+                env::var("STARSHIP_LOG")
+                    .map(|level| match level.to_lowercase().as_str() {
+                        "trace" => Level::Trace,
+                        "debug" => Level::Debug,
+                        "info" => Level::Info,
+                        "warn" => Level::Warn,
+                        "error" => Level::Error,
+                        _ => Level::Warn,
+                    })
+                    .unwrap_or_else(|_| Level::Warn) as usize,
+            ),
+            print_to_stderr: AtomicBool::new(true),
+            #[cfg(test)]
+            printed: Mutex::new(Vec::new()),
         }
     }
 }
@@ -59,19 +142,96 @@ impl Default for StarshipLogger {
 impl StarshipLogger {
     /// Override the minimum log level
     pub fn set_log_level(&mut self, level: log::Level) {
-        self.log_level = level;
+        *self.log_level.get_mut() = level as usize;
     }
 
     /// Override the log level path
     /// This won't change anything if a log file was already opened
     pub fn set_log_file_path(&mut self, path: PathBuf) {
-        self.log_file_path = path;
+        *self
+            .log_file_path
+            .get_mut()
+            .expect("Log file path mutex was poisoned!") = path;
+    }
+
+    fn log_level(&self) -> Level {
+        let level = self.log_level.load(Ordering::Relaxed);
+        LEVELS
+            .iter()
+            .copied()
+            .find(|known| *known as usize == level)
+            .unwrap_or(Level::Warn)
+    }
+
+    /// Applies the command line `options`. This won't change the log file if one was already
+    /// opened, so it has to happen before anything is logged.
+    pub fn configure(&self, options: &LogOptions) {
+        let level = LEVELS
+            .iter()
+            .skip_while(|level| **level != self.log_level())
+            .nth(options.verbosity as usize)
+            .copied()
+            .unwrap_or(Level::Trace);
+        self.log_level.store(level as usize, Ordering::Relaxed);
+        self.print_to_stderr
+            .store(!options.quiet || options.verbosity > 0, Ordering::Relaxed);
+
+        if let Some(path) = &options.log_file {
+            *self
+                .log_file_content
+                .lock()
+                .expect("Log content mutex was poisoned!") = read_log_lines(path);
+            *self
+                .log_file_path
+                .lock()
+                .expect("Log file path mutex was poisoned!") = path.clone();
+        }
+    }
+
+    /// Opens the log file the first time it's needed. A path that can't be opened, which may
+    /// come from `--log-file`, is reported once on stderr instead of failing the command.
+    fn log_file(&self) -> Option<&Mutex<File>> {
+        self.log_file
+            .get_or_init(|| {
+                let path = self
+                    .log_file_path
+                    .lock()
+                    .expect("Log file path mutex was poisoned!");
+                match OpenOptions::new().create(true).append(true).open(&*path) {
+                    Ok(file) => Some(Mutex::new(file)),
+                    Err(error) => {
+                        let message = format!("Unable to open the log file {:?}: {}", path, error);
+                        #[cfg(test)]
+                        self.printed.lock().unwrap().push(message.clone());
+                        eprintln!("[{}] - {}", Color::Red.paint("ERROR"), message);
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    /// Whether a line should be printed: lines already in the session log aren't, and lines
+    /// logged with `log_once!` are remembered so they aren't repeated
+    fn should_print(&self, record: &Record, line: &str) -> bool {
+        if !self.enabled(record.metadata()) || !self.print_to_stderr.load(Ordering::Relaxed) {
+            return false;
+        }
+        let mut shown = self
+            .log_file_content
+            .lock()
+            .expect("Log content mutex was poisoned!");
+        if record.target() == ONCE_TARGET {
+            shown.insert(line.to_string())
+        } else {
+            !shown.contains(line)
+        }
     }
 }
 
-impl log::Log for StarshipLogger {
+impl Log for StarshipLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.log_level
+        metadata.level() <= self.log_level()
     }
 
     fn log(&self, record: &Record) {
@@ -82,30 +242,26 @@ impl log::Log for StarshipLogger {
             record.args()
         );
 
-        if record.metadata().level() <= Level::Warn {
-            self.log_file
-                .get_or_try_init(|| {
-                    let m = Mutex::new(
-                        OpenOptions::new()
-                            .create(true)
-                            .append(true)
-                            .open(&self.log_file_path)?,
-                    );
-                    Ok(m)
-                })
-                .unwrap_or_else(|err: std::io::Error| {
-                    panic!(
-                        "Unable to open session log file {:?}: {:?}!",
-                        self.log_file_path, err
-                    )
-                })
-                .lock()
-                .map(|mut file| writeln!(file, "{}", to_print))
-                .expect("Log file writer mutex was poisoned!")
-                .expect("Unable to write to the log file!");
+        let is_warning = record.metadata().level() <= Level::Warn;
+        if is_warning {
+            WARNING_COUNT.fetch_add(1, Ordering::Relaxed);
+        }
+        // Warnings are always kept in the log file, and so is everything else that isn't
+        // printed to stderr
+        let is_quiet = !self.print_to_stderr.load(Ordering::Relaxed);
+        if is_warning || (is_quiet && self.enabled(record.metadata())) {
+            if let Some(file) = self.log_file() {
+                let _ = writeln!(
+                    file.lock().expect("Log file writer mutex was poisoned!"),
+                    "{}",
+                    to_print
+                );
+            }
         }
 
-        if self.enabled(record.metadata()) && !self.log_file_content.contains(to_print.as_str()) {
+        if self.should_print(record, &to_print) {
+            #[cfg(test)]
+            self.printed.lock().unwrap().push(to_print.clone());
             eprintln!(
                 "[{}] - ({}): {}",
                 match record.level() {
@@ -122,16 +278,399 @@ impl log::Log for StarshipLogger {
     }
 
     fn flush(&self) {
-        if let Some(m) = self.log_file.get() {
-            m.lock()
-                .map(|mut writer| writer.flush())
+        if let Some(Some(m)) = self.log_file.get() {
+            let _ = m
+                .lock()
                 .expect("Log file writer mutex was poisoned!")
-                .expect("Unable to flush the log file!");
+                .flush();
         }
     }
 }
 
+/// The number of warnings and errors logged so far
+pub fn warning_count() -> usize {
+    WARNING_COUNT.load(Ordering::Relaxed)
+}
+
+/// Counts a `StarshipError` of `category`, see `StarshipError::log`
+pub fn count_error(category: ErrorCategory) {
+    ERROR_COUNTS[category as usize].fetch_add(1, Ordering::Relaxed);
+    PROMPT_ERRORS.with(|counter| {
+        if let Some(counter) = &*counter.borrow() {
+            counter.0[category as usize].fetch_add(1, Ordering::Relaxed);
+        }
+    });
+}
+
+/// The number of `StarshipError`s of `category` logged so far
+pub fn error_count(category: ErrorCategory) -> usize {
+    ERROR_COUNTS[category as usize].load(Ordering::Relaxed)
+}
+
+/// The `StarshipError`s counted while rendering a single prompt, see `counting_errors`
+#[derive(Debug, Default)]
+pub struct ErrorCounter([AtomicUsize; ErrorCategory::ALL.len()]);
+
+impl ErrorCounter {
+    /// The number of `StarshipError`s of `category` counted so far
+    pub fn count(&self, category: ErrorCategory) -> usize {
+        self.0[category as usize].load(Ordering::Relaxed)
+    }
+}
+
+/// Restores the counter of the thread when dropped, see `counting_errors`
+struct CountingErrors(Option<Arc<ErrorCounter>>);
+
+impl Drop for CountingErrors {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        PROMPT_ERRORS.with(|counter| counter.replace(previous));
+    }
+}
+
+/// Calls `f`, also counting the errors it logs on this thread with `counter`
+pub fn counting_errors<T>(counter: &Arc<ErrorCounter>, f: impl FnOnce() -> T) -> T {
+    with_prompt_errors(Some(Arc::clone(counter)), f)
+}
+
+/// Calls `f` without counting the errors it logs for the prompt rendering on this thread
+pub fn uncounted<T>(f: impl FnOnce() -> T) -> T {
+    with_prompt_errors(None, f)
+}
+
+fn with_prompt_errors<T>(counter: Option<Arc<ErrorCounter>>, f: impl FnOnce() -> T) -> T {
+    let previous = PROMPT_ERRORS.with(|current| current.replace(counter));
+    let _restore = CountingErrors(previous);
+    f()
+}
+
+/// The lines of a log file, or none if it can't be read
+fn read_log_lines(path: &Path) -> HashSet<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| line.to_string())
+        .collect()
+}
+
 pub fn init() {
-    log::set_boxed_logger(Box::new(StarshipLogger::default())).unwrap();
+    let logger = LOGGER.get_or_init(StarshipLogger::default);
+    log::set_logger(logger).unwrap();
     log::set_max_level(LevelFilter::Trace);
 }
+
+/// Applies the `--verbose` and `--log-file` options to the logger set up by `init`
+pub fn configure(options: &LogOptions) {
+    if let Some(logger) = LOGGER.get() {
+        logger.configure(options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use crate::context::{Context, Shell};
+    use std::io;
+    use std::sync::Arc;
+
+    fn logger_in(dir: &std::path::Path) -> StarshipLogger {
+        let mut logger = StarshipLogger::default();
+        logger.set_log_file_path(dir.join("session_test.log"));
+        logger
+    }
+
+    fn warn(logger: &StarshipLogger, target: &str, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .target(target)
+                .module_path(Some("starship::modules::git_commit"))
+                .args(format_args!("{}", message))
+                .build(),
+        );
+        logger.flush();
+    }
+
+    #[test]
+    fn log_once_prints_a_warning_once_per_session() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let logger = logger_in(dir.path());
+
+        let message = "Error in module `git_commit`:\nUnclosed bracket";
+        warn(&logger, ONCE_TARGET, message);
+        warn(&logger, ONCE_TARGET, message);
+
+        assert_eq!(logger.printed.lock().unwrap().len(), 1);
+        let log = fs::read_to_string(dir.path().join("session_test.log"))?;
+        assert_eq!(log.matches("Unclosed bracket").count(), 2);
+        dir.close()
+    }
+
+    #[test]
+    fn log_once_skips_warnings_from_earlier_prompts() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let log_path = dir.path().join("session_test.log");
+        fs::write(
+            &log_path,
+            "[WARN] - (starship::modules::git_commit): Invalid format\n",
+        )?;
+        let mut logger = logger_in(dir.path());
+        logger.log_file_content = Mutex::new(
+            fs::read_to_string(&log_path)?
+                .lines()
+                .map(|line| line.to_string())
+                .collect(),
+        );
+
+        warn(&logger, ONCE_TARGET, "Invalid format");
+        warn(&logger, ONCE_TARGET, "Another problem");
+
+        assert_eq!(
+            *logger.printed.lock().unwrap(),
+            vec!["[WARN] - (starship::modules::git_commit): Another problem"]
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn errors_are_counted_by_category() {
+        let before = error_count(ErrorCategory::Io);
+
+        count_error(ErrorCategory::Io);
+        count_error(ErrorCategory::Io);
+
+        // Other tests may count errors meanwhile
+        assert!(error_count(ErrorCategory::Io) >= before + 2);
+    }
+
+    #[test]
+    fn errors_are_counted_for_the_prompt() {
+        let prompt = Arc::new(ErrorCounter::default());
+        let inner = Arc::new(ErrorCounter::default());
+
+        counting_errors(&prompt, || {
+            count_error(ErrorCategory::Config);
+            counting_errors(&inner, || count_error(ErrorCategory::Io));
+            uncounted(|| count_error(ErrorCategory::Config));
+            count_error(ErrorCategory::Config);
+        });
+        count_error(ErrorCategory::Config);
+
+        assert_eq!(prompt.count(ErrorCategory::Config), 2);
+        assert_eq!(prompt.count(ErrorCategory::Io), 0);
+        assert_eq!(inner.count(ErrorCategory::Io), 1);
+    }
+
+    /// Logs a warning about the config and a debug message
+    fn warn_and_debug(logger: &StarshipLogger) {
+        warn(logger, "starship::modules::git_commit", "Invalid format");
+        logger.log(
+            &Record::builder()
+                .level(Level::Debug)
+                .target("starship::modules::git_commit")
+                .module_path(Some("starship::modules::git_commit"))
+                .args(format_args!("Found the repo"))
+                .build(),
+        );
+        logger.flush();
+    }
+
+    #[test]
+    fn quiet_prompt_logs_only_to_the_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut logger = logger_in(dir.path());
+        logger.set_log_level(Level::Warn);
+        logger.configure(&LogOptions {
+            quiet: true,
+            ..Default::default()
+        });
+        let logger = Arc::new(logger);
+
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            dir.path().into(),
+            dir.path().into(),
+        );
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "$character"
+                [character]
+                format = "[quiet_prompt_logs_only_to_the_file"
+            }),
+        };
+        let capture = crate::test::capture_logs(logger.clone());
+        crate::print::get_prompt(context);
+        drop(capture);
+        logger.flush();
+
+        assert!(logger.printed.lock().unwrap().is_empty());
+        let log = fs::read_to_string(dir.path().join("session_test.log"))?;
+        assert!(log.contains("[WARN] - (starship::error): Error in module `character`"));
+        assert!(log.contains("quiet_prompt_logs_only_to_the_file"));
+        assert!(!log.contains("[DEBUG]"));
+        dir.close()
+    }
+
+    #[test]
+    fn verbose_prints_debug_logs_to_stderr() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut logger = logger_in(dir.path());
+        logger.set_log_level(Level::Warn);
+        logger.configure(&LogOptions {
+            verbosity: 2,
+            quiet: true,
+            ..Default::default()
+        });
+
+        warn_and_debug(&logger);
+
+        assert_eq!(
+            *logger.printed.lock().unwrap(),
+            vec![
+                "[WARN] - (starship::modules::git_commit): Invalid format",
+                "[DEBUG] - (starship::modules::git_commit): Found the repo",
+            ]
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn log_file_option_replaces_the_session_log() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let log_file = dir.path().join("debug.log");
+        let logger = logger_in(dir.path());
+        logger.configure(&LogOptions {
+            log_file: Some(log_file.clone()),
+            ..Default::default()
+        });
+
+        warn(&logger, "starship::modules::git_commit", "Invalid format");
+
+        assert!(fs::read_to_string(&log_file)?.contains("Invalid format"));
+        assert!(!dir.path().join("session_test.log").exists());
+        dir.close()
+    }
+
+    #[test]
+    fn unopenable_log_file_is_reported_once() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let log_file = dir.path().join("missing").join("debug.log");
+        let logger = logger_in(dir.path());
+        logger.configure(&LogOptions {
+            log_file: Some(log_file.clone()),
+            quiet: true,
+            ..Default::default()
+        });
+
+        warn(&logger, "starship::modules::git_commit", "Invalid format");
+        warn(&logger, "starship::modules::git_commit", "Invalid format");
+
+        let printed = logger.printed.lock().unwrap();
+        assert_eq!(printed.len(), 1);
+        assert!(printed[0].starts_with(&format!("Unable to open the log file {:?}", log_file)));
+        drop(printed);
+        dir.close()
+    }
+
+    #[test]
+    fn options_from_the_command_line() {
+        let app = clap::App::new("starship")
+            .arg(
+                clap::Arg::with_name("verbose")
+                    .short("v")
+                    .multiple(true)
+                    .global(true),
+            )
+            .arg(
+                clap::Arg::with_name("log_file")
+                    .long("log-file")
+                    .takes_value(true)
+                    .global(true),
+            )
+            .subcommand(clap::SubCommand::with_name("prompt"))
+            .subcommand(clap::SubCommand::with_name("module"));
+
+        let options = |args: &[&str]| {
+            LogOptions::from_matches(&app.clone().get_matches_from(args.iter().copied()))
+        };
+        assert_eq!(
+            options(&["starship", "prompt"]),
+            LogOptions {
+                quiet: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            options(&[
+                "starship",
+                "prompt",
+                "-vv",
+                "--log-file",
+                "/tmp/starship.log"
+            ]),
+            LogOptions {
+                verbosity: 2,
+                log_file: Some(PathBuf::from("/tmp/starship.log")),
+                quiet: true,
+            }
+        );
+        assert_eq!(
+            options(&["starship", "-v", "module"]),
+            LogOptions {
+                verbosity: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn plain_warnings_from_earlier_prompts_are_not_printed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut logger = logger_in(dir.path());
+        logger.log_file_content = Mutex::new(
+            std::iter::once(
+                "[WARN] - (starship::modules::git_commit): Unknown config key 'formt'".to_owned(),
+            )
+            .collect(),
+        );
+
+        warn(
+            &logger,
+            "starship::modules::git_commit",
+            "Unknown config key 'formt'",
+        );
+        warn(
+            &logger,
+            "starship::modules::git_commit",
+            "Command timed out",
+        );
+
+        assert_eq!(
+            *logger.printed.lock().unwrap(),
+            vec!["[WARN] - (starship::modules::git_commit): Command timed out"]
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn plain_warnings_repeat_within_an_invocation() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let logger = logger_in(dir.path());
+
+        warn(
+            &logger,
+            "starship::modules::git_commit",
+            "Command timed out",
+        );
+        warn(
+            &logger,
+            "starship::modules::git_commit",
+            "Command timed out",
+        );
+
+        assert_eq!(logger.printed.lock().unwrap().len(), 2);
+        dir.close()
+    }
+}
@@ -9,10 +9,12 @@ use starship::module::ALL_MODULES;
 use starship::*;
 
 fn main() {
+    let mut timings = startup::StartupTimings::new();
     // Configure the current terminal on windows to support ANSI escape sequences.
     #[cfg(windows)]
     let _ = ansi_term::enable_ansi_support();
     logger::init();
+    timings.mark(startup::Phase::Logger);
 
     let status_code_arg = Arg::with_name("status_code")
         .short("s")
@@ -67,126 +69,300 @@ fn main() {
         .help("The number of currently running jobs")
         .takes_value(true);
 
+    let terminal_width_arg = Arg::with_name("terminal_width")
+        .short("w")
+        .long("terminal-width")
+        .value_name("WIDTH")
+        .help("The width of the terminal in columns, detected from the terminal if not given")
+        .takes_value(true);
+
+    let output_format_arg = Arg::with_name("output_format")
+        .long("format")
+        .value_name("FORMAT")
+        .help("The output format, `json` being stable for use by other tools")
+        .possible_values(&["text", "json"])
+        .default_value("text")
+        .takes_value(true);
+
+    let strict_arg = Arg::with_name("strict")
+        .long("strict")
+        .value_name("CATEGORY")
+        .help("Exit with code 3 if any warnings were logged while computing the prompt, or only errors of CATEGORY with --strict=CATEGORY")
+        .possible_values(&["config", "command", "io"])
+        .min_values(0)
+        .require_equals(true)
+        .takes_value(true);
+
+    let verbose_arg = Arg::with_name("verbose")
+        .short("v")
+        .long("verbose")
+        .help("Print more logs to stderr, also for the prompt, which only writes them to the log file otherwise. Repeat it to print even more, e.g. -vv for debug logs")
+        .multiple(true)
+        .global(true);
+
+    let log_file_arg = Arg::with_name("log_file")
+        .long("log-file")
+        .value_name("PATH")
+        .help("Write the logs to PATH instead of the session log in the cache directory")
+        .takes_value(true)
+        .global(true);
+
+    let porcelain_arg = Arg::with_name("porcelain").long("porcelain").help(
+        "Print a stable, tab-separated format for scripts that won't change between releases",
+    );
+
     let init_scripts_arg = Arg::with_name("print_full_init")
         .long("print-full-init")
         .help("Print the main initialization script (as opposed to the init stub)");
 
     let long_version = crate::shadow::clap_version();
-    let mut app = App::new("starship")
-        .about("The cross-shell prompt for astronauts. ☄🌌️")
-        // pull the version number from Cargo.toml
-        .version(shadow::PKG_VERSION)
-        .long_version(long_version.as_str())
-        // pull the authors from Cargo.toml
-        .author(crate_authors!())
-        .after_help("https://github.com/starship/starship")
-        .setting(AppSettings::SubcommandRequiredElseHelp)
-        .subcommand(
-            SubCommand::with_name("init")
-                .about("Prints the shell function used to execute starship")
-                .arg(&shell_arg)
-                .arg(&init_scripts_arg),
-        )
-        .subcommand(
-            SubCommand::with_name("prompt")
-                .about("Prints the full starship prompt")
-                .arg(&status_code_arg)
-                .arg(&path_arg)
-                .arg(&logical_path_arg)
-                .arg(&cmd_duration_arg)
-                .arg(&keymap_arg)
-                .arg(&jobs_arg),
-        )
-        .subcommand(
-            SubCommand::with_name("module")
-                .about("Prints a specific prompt module")
-                .arg(
-                    Arg::with_name("name")
-                        .help("The name of the module to be printed")
-                        .required(true)
-                        .required_unless("list"),
-                )
-                .arg(
-                    Arg::with_name("list")
-                        .short("l")
-                        .long("list")
-                        .help("List out all supported modules"),
-                )
-                .arg(&status_code_arg)
-                .arg(&path_arg)
-                .arg(&logical_path_arg)
-                .arg(&cmd_duration_arg)
-                .arg(&keymap_arg)
-                .arg(&jobs_arg),
-        )
-        .subcommand(
-            SubCommand::with_name("config")
-                .alias("configure")
-                .about("Edit the starship configuration")
-                .arg(
-                    Arg::with_name("name")
-                        .help("Configuration key to edit")
-                        .required(false)
-                        .requires("value"),
-                )
-                .arg(Arg::with_name("value").help("Value to place into that key")),
-        )
-        .subcommand(
-            SubCommand::with_name("print-config")
-                .about("Prints the computed starship configuration")
-                .arg(
-                    Arg::with_name("default")
-                        .short("d")
-                        .long("default")
-                        .help("Print the default instead of the computed config")
-                        .takes_value(false),
-                ),
-        )
-        .subcommand(
-            SubCommand::with_name("toggle")
-                .about("Toggle a given starship module")
-                .arg(
-                    Arg::with_name("name")
-                        .help("The name of the module to be toggled")
-                        .required(true),
-                )
-                .arg(
-                    Arg::with_name("key")
-                        .help("The key of the config to be toggled")
-                        .required(false)
-                        .required_unless("name"),
-                ),
-        )
-        .subcommand(
-            SubCommand::with_name("bug-report").about(
+    let mut app =
+        App::new("starship")
+            .about("The cross-shell prompt for astronauts. ☄🌌️")
+            // pull the version number from Cargo.toml
+            .version(shadow::PKG_VERSION)
+            .long_version(long_version.as_str())
+            // pull the authors from Cargo.toml
+            .author(crate_authors!())
+            .after_help("https://github.com/starship/starship")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .arg(&verbose_arg)
+            .arg(&log_file_arg)
+            .subcommand(
+                SubCommand::with_name("init")
+                    .about("Prints the shell function used to execute starship")
+                    .arg(&shell_arg)
+                    .arg(&init_scripts_arg),
+            )
+            .subcommand(
+                SubCommand::with_name("prompt")
+                    .about("Prints the full starship prompt")
+                    .arg(&status_code_arg)
+                    .arg(&path_arg)
+                    .arg(&logical_path_arg)
+                    .arg(&cmd_duration_arg)
+                    .arg(&keymap_arg)
+                    .arg(&jobs_arg)
+                    .arg(&terminal_width_arg)
+                    .arg(
+                        Arg::with_name("right")
+                            .long("right")
+                            .help("Print the right prompt, of `right_format`, instead of the left one")
+                            .conflicts_with("watch"),
+                    )
+                    .arg(
+                        Arg::with_name("no_cache")
+                            .long("no-cache")
+                            .help("Ignore the cached prompt and render it again"),
+                    )
+                    .arg(
+                        Arg::with_name("watch")
+                            .long("watch")
+                            .help("Keep running and print the prompt again whenever the directory or its git repository changes"),
+                    )
+                    .arg(
+                        Arg::with_name("delimiter")
+                            .long("delimiter")
+                            .value_name("DELIMITER")
+                            .help("Printed after each prompt with --watch [default: a newline]")
+                            .takes_value(true)
+                            .requires("watch"),
+                    )
+                    .arg(
+                        Arg::with_name("clear")
+                            .long("clear")
+                            .help("Clear the screen and repaint the prompt in place with --watch")
+                            .requires("watch")
+                            .conflicts_with("delimiter"),
+                    )
+                    .arg(
+                        Arg::with_name("poll")
+                            .long("poll")
+                            .help("Check for changes periodically instead of using filesystem events with --watch")
+                            .requires("watch"),
+                    )
+                    .arg(
+                        Arg::with_name("output_format")
+                            .long("format")
+                            .value_name("FORMAT")
+                            .help("The output format, `segments` printing a `module<TAB>text<TAB>style` line for each styled part of the modules, e.g. for status bars")
+                            .possible_values(&["text", "segments"])
+                            .default_value("text")
+                            .takes_value(true),
+                    )
+                    .arg(
+                        Arg::with_name("modules")
+                            .long("modules")
+                            .value_name("MODULES")
+                            .help("Only print these comma-separated modules, in this order, with --format segments")
+                            .takes_value(true)
+                            .use_delimiter(true),
+                    )
+                    .arg(
+                        Arg::with_name("profile_startup")
+                            .long("profile-startup")
+                            .help("Print how long each phase of the startup took to stderr after the prompt")
+                            .conflicts_with("watch"),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("module")
+                    .about("Prints a specific prompt module")
+                    .arg(
+                        Arg::with_name("name")
+                            .help("The name of the module to be printed")
+                            .required(true)
+                            .required_unless("list"),
+                    )
+                    .arg(
+                        Arg::with_name("list")
+                            .short("l")
+                            .long("list")
+                            .help("List out all supported modules"),
+                    )
+                    .arg(
+                        Arg::with_name("ordered")
+                            .long("ordered")
+                            .requires("list")
+                            .help("List the modules in the order `$all` expands to"),
+                    )
+                    .arg(&porcelain_arg)
+                    .arg(
+                        Arg::with_name("describe")
+                            .long("describe")
+                            .requires("name")
+                            .help("Describe the variables and options of the module"),
+                    )
+                    .arg(&output_format_arg)
+                    .arg(&status_code_arg)
+                    .arg(&path_arg)
+                    .arg(&logical_path_arg)
+                    .arg(&cmd_duration_arg)
+                    .arg(&keymap_arg)
+                    .arg(&jobs_arg),
+            )
+            .subcommand(
+                SubCommand::with_name("config")
+                    .alias("configure")
+                    .about("Edit the starship configuration")
+                    .arg(
+                        Arg::with_name("name")
+                            .help("Configuration key to edit")
+                            .required(false)
+                            .requires("value"),
+                    )
+                    .arg(Arg::with_name("value").help("Value to place into that key")),
+            )
+            .subcommand(
+                SubCommand::with_name("print-config")
+                    .about("Prints the computed starship configuration")
+                    .arg(
+                        Arg::with_name("default")
+                            .short("d")
+                            .long("default")
+                            .help("Print the default instead of the computed config")
+                            .takes_value(false),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("test-config")
+                    .about("Checks a configuration file for problems without rendering a prompt")
+                    .arg(
+                        Arg::with_name("path")
+                            .help("The configuration file to check, `-` to read it from stdin")
+                            .required(true),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("toggle")
+                    .about("Toggle a given starship module")
+                    .arg(
+                        Arg::with_name("name")
+                            .help("The name of the module to be toggled")
+                            .required(true),
+                    )
+                    .arg(
+                        Arg::with_name("key")
+                            .help("The key of the config to be toggled")
+                            .required(false)
+                            .required_unless("name"),
+                    ),
+            )
+            .subcommand(SubCommand::with_name("bug-report").about(
                 "Create a pre-populated GitHub issue with information about your configuration",
-            ),
-        )
-        .subcommand(
-            SubCommand::with_name("time")
-                .about("Prints time in milliseconds")
-                .settings(&[AppSettings::Hidden]),
-        )
-        .subcommand(
-            SubCommand::with_name("explain").about("Explains the currently showing modules"),
-        )
-        .subcommand(SubCommand::with_name("timings").about("Prints timings of all active modules"))
-        .subcommand(
-            SubCommand::with_name("completions")
-                .about("Generate starship shell completions for your shell to stdout")
-                .arg(
-                    Arg::with_name("shell")
-                        .takes_value(true)
-                        .possible_values(&Shell::variants())
-                        .help("the shell to generate completions for")
-                        .value_name("SHELL")
-                        .required(true)
-                        .env("STARSHIP_SHELL"),
-                ),
-        )
-        .subcommand(SubCommand::with_name("session").about("Generate random session key"));
+            ))
+            .subcommand(
+                SubCommand::with_name("time")
+                    .about("Prints time in milliseconds")
+                    .settings(&[AppSettings::Hidden]),
+            )
+            .subcommand(
+                SubCommand::with_name("update-check")
+                    .about("Looks up the latest release in the background, see `update_check`")
+                    .settings(&[AppSettings::Hidden])
+                    .arg(
+                        Arg::with_name("url")
+                            .long("url")
+                            .takes_value(true)
+                            .required(true),
+                    )
+                    .arg(
+                        Arg::with_name("state_file")
+                            .long("state-file")
+                            .takes_value(true)
+                            .required(true),
+                    ),
+            )
+            .subcommand(
+                SubCommand::with_name("explain")
+                    .about("Explains the currently showing modules")
+                    .arg(&output_format_arg)
+                    .arg(&strict_arg),
+            )
+            .subcommand(
+                SubCommand::with_name("explain-config")
+                    .about("Explains the options and variables of a module, with their defaults")
+                    .arg(
+                        Arg::with_name("name")
+                            .help("The name of the module to explain")
+                            .required(true),
+                    )
+                    .arg(&output_format_arg),
+            )
+            .subcommand(
+                SubCommand::with_name("timings")
+                    .about("Prints timings of all active modules")
+                    .arg(&output_format_arg)
+                    .arg(&porcelain_arg)
+                    .arg(&strict_arg),
+            )
+            .subcommand(
+                SubCommand::with_name("completions")
+                    .about("Generate starship shell completions for your shell to stdout")
+                    .arg(
+                        Arg::with_name("shell")
+                            .takes_value(true)
+                            .possible_values(&Shell::variants())
+                            .help("the shell to generate completions for")
+                            .value_name("SHELL")
+                            .required(true)
+                            .env("STARSHIP_SHELL"),
+                    ),
+            )
+            .subcommand(SubCommand::with_name("session").about("Generate random session key"))
+            .subcommand(
+                SubCommand::with_name("cache")
+                    .about("Manage the cache directory of starship")
+                    .setting(AppSettings::SubcommandRequiredElseHelp)
+                    .subcommand(SubCommand::with_name("gc").about(
+                        "Remove the session files not modified in the last `cache_ttl_days` days",
+                    )),
+            );
 
     let matches = app.clone().get_matches();
+    // Before anything is logged, so that the log file isn't opened yet
+    logger::configure(&logger::LogOptions::from_matches(&matches));
+    timings.mark(startup::Phase::Arguments);
 
     match matches.subcommand() {
         ("init", Some(sub_m)) => {
@@ -197,17 +373,35 @@ fn main() {
                 init::init_stub(shell_name).expect("can't init_stub");
             }
         }
-        ("prompt", Some(sub_m)) => print::prompt(sub_m.clone()),
+        ("prompt", Some(sub_m)) => {
+            if sub_m.value_of("output_format") == Some("segments") {
+                print::segments(sub_m.clone())
+            } else if sub_m.is_present("watch") {
+                watch::watch(sub_m.clone())
+            } else {
+                print::prompt(sub_m.clone(), timings)
+            }
+        }
         ("module", Some(sub_m)) => {
             if sub_m.is_present("list") {
-                println!("Supported modules list");
-                println!("----------------------");
-                for modules in ALL_MODULES {
-                    println!("{}", modules);
-                }
+                let porcelain = sub_m.is_present("porcelain");
+                let list = if sub_m.is_present("ordered") {
+                    let config = config::StarshipConfig::initialize();
+                    let order = config.get_root_config().prompt_order();
+                    print::format_module_list(&order, porcelain, print::use_color())
+                } else {
+                    print::format_module_list(ALL_MODULES, porcelain, print::use_color())
+                };
+                print!("{}", list);
             }
             if let Some(module_name) = sub_m.value_of("name") {
-                print::module(module_name, sub_m.clone());
+                if sub_m.is_present("describe") {
+                    if !print::describe(module_name, sub_m.clone()) {
+                        std::process::exit(1);
+                    }
+                } else {
+                    print::module(module_name, sub_m.clone());
+                }
             }
         }
         ("config", Some(sub_m)) => {
@@ -223,6 +417,10 @@ fn main() {
             let print_default = sub_m.is_present("default");
             configure::print_configuration(print_default)
         }
+        ("test-config", Some(sub_m)) => {
+            let path = sub_m.value_of("path").expect("Config path missing.");
+            std::process::exit(config_check::test_config(path));
+        }
         ("toggle", Some(sub_m)) => {
             if let Some(name) = sub_m.value_of("name") {
                 if let Some(value) = sub_m.value_of("key") {
@@ -242,8 +440,30 @@ fn main() {
                 None => println!("{}", -1),
             }
         }
-        ("explain", Some(sub_m)) => print::explain(sub_m.clone()),
-        ("timings", Some(sub_m)) => print::timings(sub_m.clone()),
+        ("update-check", Some(sub_m)) => {
+            let url = sub_m.value_of("url").expect("URL missing.");
+            let state_file = sub_m.value_of("state_file").expect("State file missing.");
+            let config = config::StarshipConfig::initialize();
+            update_check::run(
+                &config.get_root_config(),
+                url,
+                std::path::Path::new(state_file),
+            );
+        }
+        ("explain", Some(sub_m)) => {
+            let code = print::explain(sub_m.clone());
+            std::process::exit(code);
+        }
+        ("explain-config", Some(sub_m)) => {
+            let module_name = sub_m.value_of("name").expect("Module name missing.");
+            if !print::describe(module_name, sub_m.clone()) {
+                std::process::exit(1);
+            }
+        }
+        ("timings", Some(sub_m)) => {
+            let code = print::timings(sub_m.clone(), timings);
+            std::process::exit(code);
+        }
         ("completions", Some(sub_m)) => {
             let shell: Shell = sub_m
                 .value_of("shell")
@@ -253,6 +473,16 @@ fn main() {
 
             app.gen_completions_to("starship", shell, &mut io::stdout().lock());
         }
+        ("cache", Some(sub_m)) => {
+            if let ("gc", Some(gc_m)) = sub_m.subcommand() {
+                let context = context::Context::new(gc_m.clone());
+                let removed = cache::run_gc(&context);
+                for path in &removed {
+                    println!("Removed {}", path.display());
+                }
+                println!("Removed {} stale cache file(s)", removed.len());
+            }
+        }
         ("session", _) => println!(
             "{}",
             rand::thread_rng()
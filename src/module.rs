@@ -1,10 +1,13 @@
-use crate::context::Shell;
-use crate::segment::Segment;
-use crate::utils::wrap_colorseq_for_shell;
-use ansi_term::{ANSIString, ANSIStrings};
+use crate::print::UnicodeWidthGraphemes;
+use crate::segment::{paint_segments, truncate_segments, Segment, Truncation};
+use ansi_term::ANSIString;
 use std::fmt;
 use std::time::Duration;
 
+/// The options every module accepts besides those of its config: the `env` of its commands,
+/// read by `Module::env`, and the options of `Module::apply_max_length`
+pub const COMMON_OPTIONS: &[&str] = &["env", "max_length", "truncate", "truncate_symbol"];
+
 // List of all modules
 // Keep these ordered alphabetically.
 // Default ordering is handled in configs/starship_root.rs
@@ -15,7 +18,11 @@ pub const ALL_MODULES: &[&str] = &[
     "character",
     "cmake",
     "cmd_duration",
+    "cobol",
     "conda",
+    "config_error",
+    "container",
+    "daml",
     "dart",
     "deno",
     "directory",
@@ -24,15 +31,21 @@ pub const ALL_MODULES: &[&str] = &[
     "elixir",
     "elm",
     "erlang",
+    "fennel",
+    "fill",
     "env_var",
     "gcloud",
     "git_branch",
     "git_commit",
     "git_state",
     "git_status",
+    "git_worktree",
+    "gleam",
     "golang",
+    "haxe",
     "helm",
     "hg_branch",
+    "hg_state",
     "hostname",
     "java",
     "scala",
@@ -43,6 +56,7 @@ pub const ALL_MODULES: &[&str] = &[
     "line_break",
     "lua",
     "memory_usage",
+    "new_version_available",
     "nim",
     "nix_shell",
     "nodejs",
@@ -50,6 +64,7 @@ pub const ALL_MODULES: &[&str] = &[
     "openstack",
     "package",
     "perl",
+    "project_context",
     "purescript",
     "python",
     "rlang",
@@ -63,6 +78,7 @@ pub const ALL_MODULES: &[&str] = &[
     "shell",
     "shlvl",
     "singularity",
+    "slow_fs",
     "status",
     "time",
     "username",
@@ -72,6 +88,12 @@ pub const ALL_MODULES: &[&str] = &[
     "zig",
 ];
 
+/// Whether `name` can be used as a module in the root `format`: a built-in module, every custom
+/// module (`custom`) or a single one (`custom.<name>`)
+pub fn is_module_name(name: &str) -> bool {
+    ALL_MODULES.contains(&name) || name == "custom" || name.starts_with("custom.")
+}
+
 /// A module is a collection of segments showing data for a single integration
 /// (e.g. The git module shows the current git branch and status)
 pub struct Module<'a> {
@@ -89,6 +111,9 @@ pub struct Module<'a> {
 
     /// the time it took to compute this module
     pub duration: Duration,
+
+    /// Whether the output was shortened to the `max_length` of the config
+    pub truncated: bool,
 }
 
 impl<'a> Module<'a> {
@@ -100,6 +125,7 @@ impl<'a> Module<'a> {
             description: desc.to_string(),
             segments: Vec::new(),
             duration: Duration::default(),
+            truncated: false,
         }
     }
 
@@ -118,6 +144,73 @@ impl<'a> Module<'a> {
         &self.description
     }
 
+    /// The environment variables to set for the commands of the module, from the `env` table
+    /// of its config. An empty value unsets the variable.
+    pub fn env(&self) -> Vec<(&'a str, &'a str)> {
+        let env = match self.config.and_then(|config| config.get("env")) {
+            Some(toml::Value::Table(env)) => env,
+            Some(_) => {
+                log::warn!("`env` of module `{}` should be a table", self.name);
+                return Vec::new();
+            }
+            None => return Vec::new(),
+        };
+
+        env.iter()
+            .filter_map(|(key, value)| match value.as_str() {
+                Some(value) => Some((key.as_str(), value)),
+                None => {
+                    log::warn!("`env.{}` of module `{}` should be a string", key, self.name);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Shortens the output of the module to the `max_length` columns of its config, cutting it
+    /// where `truncate` says and marking the cut with `truncate_symbol`
+    pub fn apply_max_length(&mut self) {
+        let config = match self.config {
+            Some(config) => config,
+            None => return,
+        };
+        let max_length = match config.get("max_length") {
+            Some(toml::Value::Integer(max_length)) if *max_length > 0 => *max_length as usize,
+            Some(_) => {
+                log::warn!(
+                    "`max_length` of module `{}` should be a positive integer",
+                    self.name
+                );
+                return;
+            }
+            None => return,
+        };
+        let truncation = match config.get("truncate").and_then(toml::Value::as_str) {
+            Some(strategy) => Truncation::parse(strategy).unwrap_or_else(|| {
+                log::warn!(
+                    "Unknown `truncate` value `{}` in module `{}`, expected one of: end, middle, start",
+                    strategy,
+                    self.name
+                );
+                Truncation::End
+            }),
+            None => Truncation::End,
+        };
+        let symbol = config
+            .get("truncate_symbol")
+            .and_then(toml::Value::as_str)
+            .unwrap_or("…");
+
+        let width: usize = self
+            .segments
+            .iter()
+            .map(|s| s.value.width_graphemes())
+            .sum();
+        self.truncated = width > max_length;
+        let segments = std::mem::take(&mut self.segments);
+        self.segments = truncate_segments(segments, max_length, truncation, symbol);
+    }
+
     /// Whether a module has non-empty segments
     pub fn is_empty(&self) -> bool {
         self.segments
@@ -134,45 +227,18 @@ impl<'a> Module<'a> {
             .collect()
     }
 
-    /// Returns a vector of colored ANSIString elements to be later used with
-    /// `ANSIStrings()` to optimize ANSI codes
+    /// Returns a vector of colored ANSIString elements, one per segment
     pub fn ansi_strings(&self) -> Vec<ANSIString> {
-        self.ansi_strings_for_shell(Shell::Unknown)
-    }
-
-    pub fn ansi_strings_for_shell(&self, shell: Shell) -> Vec<ANSIString> {
-        let ansi_strings = self
-            .segments
-            .iter()
-            .map(Segment::ansi_string)
-            .collect::<Vec<ANSIString>>();
-
-        match shell {
-            Shell::Bash => ansi_strings_modified(ansi_strings, shell),
-            Shell::Zsh => ansi_strings_modified(ansi_strings, shell),
-            Shell::Tcsh => ansi_strings_modified(ansi_strings, shell),
-            _ => ansi_strings,
-        }
+        self.segments.iter().map(Segment::ansi_string).collect()
     }
 }
 
 impl<'a> fmt::Display for Module<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let ansi_strings = self.ansi_strings();
-        write!(f, "{}", ANSIStrings(&ansi_strings))
+        write!(f, "{}", paint_segments(&self.segments))
     }
 }
 
-fn ansi_strings_modified(ansi_strings: Vec<ANSIString>, shell: Shell) -> Vec<ANSIString> {
-    ansi_strings
-        .into_iter()
-        .map(|ansi| {
-            let wrapped = wrap_colorseq_for_shell(ansi.to_string(), shell);
-            ANSIString::from(wrapped)
-        })
-        .collect::<Vec<ANSIString>>()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,6 +253,7 @@ mod tests {
             description: desc.to_string(),
             segments: Vec::new(),
             duration: Duration::default(),
+            truncated: false,
         };
 
         assert!(module.is_empty());
@@ -202,6 +269,7 @@ mod tests {
             description: desc.to_string(),
             segments: vec![Segment::new(None, "")],
             duration: Duration::default(),
+            truncated: false,
         };
 
         assert!(module.is_empty());
@@ -217,6 +285,7 @@ mod tests {
             description: desc.to_string(),
             segments: vec![Segment::new(None, "\n")],
             duration: Duration::default(),
+            truncated: false,
         };
 
         assert!(!module.is_empty());
@@ -232,8 +301,47 @@ mod tests {
             description: desc.to_string(),
             segments: vec![Segment::new(None, " ")],
             duration: Duration::default(),
+            truncated: false,
         };
 
         assert!(!module.is_empty());
     }
+
+    fn render_with_max_length(config: toml::Value) -> Option<String> {
+        crate::test::ModuleRenderer::new("env_var")
+            .config(config)
+            .env("KUBE_CONTEXT", "arn:aws:eks:us-east-1:cluster")
+            .collect()
+    }
+
+    #[test]
+    fn test_max_length_applies_to_whole_output() {
+        let actual = render_with_max_length(toml::toml! {
+            [env_var]
+            variable = "KUBE_CONTEXT"
+            format = "[$env_value]($style) [!](red)"
+            style = "bold blue"
+            max_length = 10
+            truncate = "middle"
+        });
+
+        let expected = Some(format!(
+            "{} {}",
+            ansi_term::Color::Blue.bold().paint("arn:a…er"),
+            ansi_term::Color::Red.paint("!")
+        ));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_invalid_max_length_is_ignored() {
+        let actual = render_with_max_length(toml::toml! {
+            [env_var]
+            variable = "KUBE_CONTEXT"
+            format = "$env_value"
+            max_length = 0
+        });
+
+        assert_eq!(Some(String::from("arn:aws:eks:us-east-1:cluster")), actual);
+    }
 }
@@ -1,19 +1,37 @@
 use std::collections::HashMap;
-use std::fs::File;
+use std::ffi::OsStr;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use chrono::DateTime;
+use serde::Deserialize;
 
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::aws::AwsConfig;
 use crate::formatter::StringFormatter;
-use crate::utils::render_time;
+use crate::utils::{self, render_time};
 
 type Profile = String;
 type Region = String;
+type ProfileConfig = HashMap<String, String>;
+
+/// The SSO session a profile signs in with
+struct SsoSession {
+    /// The name of the `[sso-session]` section, unset for legacy profiles
+    name: Option<String>,
+    start_url: Option<String>,
+}
+
+/// A token cached by `aws sso login` in `~/.aws/sso/cache`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SsoToken {
+    start_url: String,
+    expires_at: String,
+}
 
 fn get_credentials_file_path(context: &Context) -> Option<PathBuf> {
     context
@@ -37,50 +55,96 @@ fn get_config_file_path(context: &Context) -> Option<PathBuf> {
         })
 }
 
-fn get_aws_region_from_config(context: &Context, aws_profile: Option<&str>) -> Option<Region> {
-    let config_location = get_config_file_path(context)?;
-
-    let file = File::open(&config_location).ok()?;
+/// Reads the `key = value` pairs of the section with the given header, e.g. `[default]`
+fn get_config_section(path: &Path, header: &str) -> Option<HashMap<String, String>> {
+    let file = File::open(path).ok()?;
     let reader = BufReader::new(file);
-    let lines = reader.lines().filter_map(Result::ok);
+    let mut lines = reader
+        .lines()
+        .filter_map(Result::ok)
+        .skip_while(|line| line.trim() != header);
+    lines.next()?;
+
+    let section = lines
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
 
-    let region_line = if let Some(ref aws_profile) = aws_profile {
-        lines
-            .skip_while(|line| line != &format!("[profile {}]", aws_profile))
-            .skip(1)
-            .take_while(|line| !line.starts_with('['))
-            .find(|line| line.starts_with("region"))
-    } else {
-        lines
-            .skip_while(|line| line != "[default]")
-            .skip(1)
-            .take_while(|line| !line.starts_with('['))
-            .find(|line| line.starts_with("region"))
-    }?;
+    Some(section)
+}
 
-    let region = region_line.split('=').nth(1)?;
-    let region = region.trim();
+fn get_profile_config(context: &Context, aws_profile: Option<&str>) -> Option<ProfileConfig> {
+    let config_location = get_config_file_path(context)?;
+    let header = match aws_profile {
+        Some(aws_profile) => format!("[profile {}]", aws_profile),
+        None => "[default]".to_string(),
+    };
 
-    Some(region.to_string())
+    get_config_section(&config_location, &header)
 }
 
-fn get_aws_profile_and_region(context: &Context) -> (Option<Profile>, Option<Region>) {
+fn get_aws_profile(context: &Context) -> Option<Profile> {
     let profile_env_vars = vec!["AWSU_PROFILE", "AWS_VAULT", "AWSUME_PROFILE", "AWS_PROFILE"];
-    let profile = profile_env_vars
+    profile_env_vars
         .iter()
-        .find_map(|env_var| context.get_env(env_var));
-    let region = context
+        .find_map(|env_var| context.get_env(env_var))
+}
+
+fn get_aws_region(context: &Context, profile_config: Option<&ProfileConfig>) -> Option<Region> {
+    context
         .get_env("AWS_DEFAULT_REGION")
-        .or_else(|| context.get_env("AWS_REGION"));
-    match (profile, region) {
-        (Some(p), Some(r)) => (Some(p), Some(r)),
-        (None, Some(r)) => (None, Some(r)),
-        (Some(ref p), None) => (
-            Some(p.to_owned()),
-            get_aws_region_from_config(context, Some(p)),
-        ),
-        (None, None) => (None, get_aws_region_from_config(context, None)),
+        .or_else(|| context.get_env("AWS_REGION"))
+        .or_else(|| profile_config?.get("region").cloned())
+}
+
+/// Finds the SSO session of a profile, either through an `sso_session` referencing an
+/// `[sso-session]` section or through the legacy `sso_start_url` key.
+fn get_sso_session(context: &Context, profile_config: &ProfileConfig) -> Option<SsoSession> {
+    if let Some(name) = profile_config.get("sso_session") {
+        let start_url = get_config_file_path(context)
+            .and_then(|path| get_config_section(&path, &format!("[sso-session {}]", name)))
+            .and_then(|session| session.get("sso_start_url").cloned());
+
+        return Some(SsoSession {
+            name: Some(name.to_string()),
+            start_url,
+        });
     }
+
+    profile_config
+        .get("sso_start_url")
+        .map(|start_url| SsoSession {
+            name: None,
+            start_url: Some(start_url.to_string()),
+        })
+}
+
+/// Returns the time left on the latest token cached for the given SSO start URL
+fn get_sso_token_duration(context: &Context, start_url: &str) -> Option<i64> {
+    let cache_dir = context.get_home()?.join(".aws/sso/cache");
+
+    let expires_at = fs::read_dir(cache_dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension() == Some(OsStr::new("json")))
+        .filter_map(|path| {
+            let token: SsoToken = serde_json::from_str(&utils::read_file(path).ok()?).ok()?;
+            if token.start_url != start_url {
+                return None;
+            }
+
+            // Older versions of the AWS CLI write a `UTC` suffix instead of `Z`
+            let expires_at = token.expires_at.replace("UTC", "Z");
+            DateTime::parse_from_rfc3339(&expires_at).ok()
+        })
+        .map(|expires_at| expires_at.timestamp())
+        .max()?;
+
+    Some(expires_at - chrono::Local::now().timestamp())
 }
 
 fn get_credentials_duration(context: &Context, aws_profile: Option<&Profile>) -> Option<i64> {
@@ -127,8 +191,21 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("aws");
     let config: AwsConfig = AwsConfig::try_load(module.config);
 
-    let (aws_profile, aws_region) = get_aws_profile_and_region(context);
-    if aws_profile.is_none() && aws_region.is_none() {
+    let aws_profile = get_aws_profile(context);
+    let profile_config = get_profile_config(context, aws_profile.as_deref());
+    let aws_region = get_aws_region(context, profile_config.as_ref());
+    let sso_session = profile_config
+        .as_ref()
+        .and_then(|profile_config| get_sso_session(context, profile_config));
+    let has_credential_process = profile_config
+        .as_ref()
+        .and_then(|profile_config| profile_config.get("credential_process"))
+        .is_some();
+    if aws_profile.is_none()
+        && aws_region.is_none()
+        && sso_session.is_none()
+        && !has_credential_process
+    {
         return None;
     }
 
@@ -139,14 +216,25 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     };
 
     let duration = {
-        get_credentials_duration(context, aws_profile.as_ref()).map(|duration| {
-            if duration > 0 {
-                render_time((duration * 1000) as u128, false)
-            } else {
-                config.expiration_symbol.to_string()
-            }
-        })
+        get_credentials_duration(context, aws_profile.as_ref())
+            .or_else(|| {
+                // A missing token cache means the SSO session has to be signed in again
+                let sso_session = sso_session.as_ref()?;
+                let duration = sso_session
+                    .start_url
+                    .as_ref()
+                    .and_then(|start_url| get_sso_token_duration(context, start_url));
+                Some(duration.unwrap_or(0))
+            })
+            .map(|duration| {
+                if duration > 0 {
+                    render_time((duration * 1000) as u128, false)
+                } else {
+                    config.expiration_symbol.to_string()
+                }
+            })
     };
+    let sso_session_name = sso_session.and_then(|sso_session| sso_session.name);
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -162,6 +250,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 "profile" => aws_profile.as_ref().map(Ok),
                 "region" => mapped_region.as_ref().map(Ok),
                 "duration" => duration.as_ref().map(Ok),
+                "sso_session" => sso_session_name.as_ref().map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -170,7 +259,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `aws`: \n{}", error);
+            crate::error::StarshipError::from_format("aws", error).log();
             return None;
         }
     });
@@ -182,7 +271,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 mod tests {
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io::{self, Write};
 
     #[test]
@@ -607,4 +696,195 @@ expiration={}
 
         assert_eq!(expected, actual);
     }
+
+    /// Writes an `~/.aws/config` using SSO and, when given, a token cache expiring at `expires_at`
+    fn sso_fixture(expires_at: Option<&str>) -> io::Result<tempfile::TempDir> {
+        let dir = tempfile::tempdir()?;
+        let aws_dir = dir.path().join(".aws");
+        let cache_dir = aws_dir.join("sso/cache");
+        fs::create_dir_all(&cache_dir)?;
+
+        fs::write(
+            aws_dir.join("config"),
+            "[profile astronauts]
+sso_session = launchpad
+sso_account_id = 123456789012
+region = us-east-2
+
+[profile legacy]
+sso_start_url = https://legacy.awsapps.com/start
+
+[sso-session launchpad]
+sso_start_url = https://launchpad.awsapps.com/start
+sso_region = us-east-1
+",
+        )?;
+
+        // Client registrations share the cache directory and have to be skipped
+        fs::write(
+            cache_dir.join("botocore-client-id-us-east-1.json"),
+            r#"{"clientId": "dummy", "expiresAt": "2000-01-01T00:00:00Z"}"#,
+        )?;
+
+        if let Some(expires_at) = expires_at {
+            fs::write(
+                cache_dir.join("0123456789abcdef.json"),
+                format!(
+                    r#"{{"startUrl": "https://launchpad.awsapps.com/start", "region": "us-east-1", "accessToken": "dummy", "expiresAt": "{}"}}"#,
+                    expires_at
+                ),
+            )?;
+        }
+
+        Ok(dir)
+    }
+
+    fn expires_in(seconds: i64) -> String {
+        use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+
+        let expires_at: DateTime<Utc> = chrono::DateTime::from_utc(
+            NaiveDateTime::from_timestamp(chrono::Local::now().timestamp() + seconds, 0),
+            Utc,
+        );
+        expires_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+
+    #[test]
+    fn sso_token_valid() -> io::Result<()> {
+        let dir = sso_fixture(Some(&expires_in(1800)))?;
+
+        let actual = ModuleRenderer::new("aws")
+            .config(toml::toml! {
+                [aws]
+                format = "on [$symbol$profile \\($sso_session\\) \\[$duration\\]]($style)"
+            })
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "astronauts")
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Yellow
+                .bold()
+                .paint("☁️  astronauts (launchpad) [30m]")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn sso_token_expired() -> io::Result<()> {
+        let dir = sso_fixture(Some(&expires_in(-1800)))?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "astronauts")
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Yellow.bold().paint("☁️  astronauts (us-east-2) [X]")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn sso_token_missing() -> io::Result<()> {
+        let dir = sso_fixture(None)?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "astronauts")
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Yellow.bold().paint("☁️  astronauts (us-east-2) [X]")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn sso_token_for_other_start_url() -> io::Result<()> {
+        let dir = sso_fixture(Some(&expires_in(1800)))?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "legacy")
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Yellow.bold().paint("☁️  legacy [X]")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn sso_legacy_token_with_utc_suffix() -> io::Result<()> {
+        let dir = sso_fixture(None)?;
+        let expires_at = expires_in(1800).replace('Z', "UTC");
+        fs::write(
+            dir.path().join(".aws/sso/cache/legacy.json"),
+            format!(
+                r#"{{"startUrl": "https://legacy.awsapps.com/start", "accessToken": "dummy", "expiresAt": "{}"}}"#,
+                expires_at
+            ),
+        )?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "legacy")
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Yellow.bold().paint("☁️  legacy [30m]")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn sso_session_not_used_by_profile() -> io::Result<()> {
+        let dir = sso_fixture(None)?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("HOME", dir.path().to_string_lossy())
+            .env("AWS_PROFILE", "astronauts-static")
+            .env("AWS_REGION", "ap-northeast-2")
+            .collect();
+        let expected = Some(format!(
+            "on {}",
+            Color::Yellow
+                .bold()
+                .paint("☁️  astronauts-static (ap-northeast-2) ")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn default_profile_with_credential_process() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("config");
+        fs::write(
+            &config_path,
+            "[default]
+credential_process = /opt/bin/fetch-credentials
+",
+        )?;
+
+        let actual = ModuleRenderer::new("aws")
+            .env("AWS_CONFIG_FILE", config_path.to_string_lossy().as_ref())
+            .collect();
+        let expected = Some(format!("on {}", Color::Yellow.bold().paint("☁️  ")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 }
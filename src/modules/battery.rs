@@ -1,23 +1,24 @@
-use super::{Context, Module, RootModuleConfig, Shell};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Context, Module, RootModuleConfig};
+use crate::cache;
 use crate::configs::battery::BatteryConfig;
 
 use crate::formatter::StringFormatter;
 
 /// Creates a module for the battery percentage and charging state
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    // TODO: Update when v1.0 printing refactor is implemented to only
-    // print escapes in a prompt context.
-    let percentage_char = match context.shell {
-        Shell::Zsh => "%%", // % is an escape in zsh, see PROMPT in `man zshmisc`
-        _ => "%",
-    };
-
-    let battery_status = get_battery_status()?;
-    let BatteryStatus { state, percentage } = battery_status;
-
     let mut module = context.new_module("battery");
     let config: BatteryConfig = BatteryConfig::try_load(module.config);
 
+    let battery_status = cache::sampled(
+        context,
+        "battery",
+        config.refresh_interval_ms,
+        get_battery_status,
+    )?;
+    let BatteryStatus { state, percentage } = battery_status;
+
     // Parse config under `display`.
     // Select the first style that match the threshold,
     // if all thresholds are lower do not display battery module.
@@ -53,7 +54,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     _ => None,
                 })
                 .map(|variable| match variable {
-                    "percentage" => Some(Ok(format!("{}{}", percentage.round(), percentage_char))),
+                    "percentage" => Some(Ok(format!("{}%", percentage.round()))),
                     _ => None,
                 });
 
@@ -62,14 +63,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     module.set_segments(format_string);
                     Some(module)
                 }
-                Err(e) => {
-                    log::warn!("Cannot parse `battery.format`: {}", e);
+                Err(error) => {
+                    crate::error::StarshipError::from_format("battery", error).log();
                     None
                 }
             }
         }
-        Err(e) => {
-            log::warn!("Cannot load `battery.format`: {}", e);
+        Err(error) => {
+            crate::error::StarshipError::from_format("battery", error).log();
             None
         }
     }
@@ -151,8 +152,28 @@ struct BatteryInfo {
     state: battery::State,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct BatteryStatus {
     percentage: f32,
+    #[serde(with = "state_name")]
     state: battery::State,
 }
+
+/// Stores a battery state by its name when the status is cached
+mod state_name {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        state: &battery::State,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(state)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<battery::State, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -10,6 +10,9 @@ use crate::formatter::StringFormatter;
 ///   (green arrow by default)
 /// - If the exit-code was anything else, it will be formatted with
 ///   `error_symbol` (red arrow by default)
+///
+/// Otherwise `vicmd_symbol`, `root_symbol` and `readonly_symbol` take
+/// precedence over `success_symbol`, in that order, when they apply and are set.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     enum ShellEditMode {
         Normal,
@@ -21,10 +24,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("character");
     let config: CharacterConfig = CharacterConfig::try_load(module.config);
 
-    let props = &context.properties;
-    let exit_code = props.get("status_code").map(String::as_str).unwrap_or("0");
-    let keymap = props.get("keymap").map(String::as_str).unwrap_or("viins");
-    let exit_success = exit_code == "0";
+    let exit_code = context.get_property("status_code");
+    let keymap = context.get_property("keymap");
+    let keymap = keymap.as_deref().unwrap_or("viins");
+    let exit_success = exit_code.as_deref().unwrap_or("0") == "0";
 
     // Match shell "keymap" names to normalized vi modes
     // NOTE: in vi mode, fish reports normal mode as "default".
@@ -36,15 +39,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         _ => ASSUMED_MODE,
     };
 
-    let symbol = match mode {
-        ShellEditMode::Normal => config.vicmd_symbol,
-        ShellEditMode::Insert => {
-            if exit_success {
-                config.success_symbol
-            } else {
-                config.error_symbol
-            }
-        }
+    let symbol = if !exit_success {
+        config.error_symbol
+    } else if let ShellEditMode::Normal = mode {
+        config.vicmd_symbol
+    } else if !config.root_symbol.is_empty() && context.is_root_user() {
+        config.root_symbol
+    } else if !config.readonly_symbol.is_empty() && context.is_readonly_dir() {
+        config.readonly_symbol
+    } else {
+        config.success_symbol
     };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
@@ -59,7 +63,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `character`:\n{}", error);
+            crate::error::StarshipError::from_format("character", error).log();
             return None;
         }
     });
@@ -98,6 +102,22 @@ mod test {
         }
     }
 
+    #[test]
+    fn failure_status_from_env() {
+        let expected = Some(format!("{} ", Color::Red.bold().paint("❯")));
+
+        let actual = ModuleRenderer::new("character")
+            .env("STARSHIP_STATUS", "1")
+            .collect();
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("character")
+            .shell(Shell::Fish)
+            .env("status", "1")
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn custom_symbol() {
         let expected_fail = Some(format!("{} ", Color::Red.bold().paint("✖")));
@@ -193,4 +213,106 @@ mod test {
             .collect();
         assert_eq!(expected_other, actual);
     }
+
+    fn render_precedence(status: i32, keymap: &str, root_user: bool, readonly_dir: bool) -> String {
+        ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character]
+                format = "$symbol"
+                success_symbol = "success"
+                error_symbol = "error"
+                vicmd_symbol = "vicmd"
+                root_symbol = "root"
+                readonly_symbol = "readonly"
+            })
+            .shell(Shell::Zsh)
+            .status(status)
+            .keymap(keymap)
+            .root_user(root_user)
+            .readonly_dir(readonly_dir)
+            .collect()
+            .unwrap()
+    }
+
+    #[test]
+    fn symbol_precedence() {
+        for &status in &[0, 1] {
+            for &keymap in &["viins", "vicmd"] {
+                for &root_user in &[false, true] {
+                    for &readonly_dir in &[false, true] {
+                        let expected = if status != 0 {
+                            "error"
+                        } else if keymap == "vicmd" {
+                            "vicmd"
+                        } else if root_user {
+                            "root"
+                        } else if readonly_dir {
+                            "readonly"
+                        } else {
+                            "success"
+                        };
+
+                        let actual = render_precedence(status, keymap, root_user, readonly_dir);
+                        assert_eq!(
+                            expected, actual,
+                            "status={} keymap={} root={} readonly={}",
+                            status, keymap, root_user, readonly_dir
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn root_and_readonly_unset_by_default() {
+        let expected = Some(format!("{} ", Color::Green.bold().paint("❯")));
+
+        let actual = ModuleRenderer::new("character")
+            .root_user(true)
+            .readonly_dir(true)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn root_symbol() {
+        let expected = Some(format!("{} ", Color::Red.bold().paint("#")));
+
+        let actual = ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character]
+                root_symbol = "[#](bold red)"
+            })
+            .root_user(true)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn readonly_symbol() {
+        let expected = Some(format!("{} ", Color::Yellow.bold().paint("❯")));
+
+        let actual = ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character]
+                readonly_symbol = "[❯](bold yellow)"
+            })
+            .root_user(false)
+            .readonly_dir(true)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn error_overrides_vicmd() {
+        let expected = Some(format!("{} ", Color::Red.bold().paint("❯")));
+
+        let actual = ModuleRenderer::new("character")
+            .shell(Shell::Zsh)
+            .keymap("vicmd")
+            .status(1)
+            .collect();
+        assert_eq!(expected, actual);
+    }
 }
@@ -7,6 +7,7 @@ use crate::formatter::StringFormatter;
 /// Creates a module with the current CMake version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("cmake");
+    let env = module.env();
     let config = CMakeConfig::try_load(module.config);
 
     let is_cmake_project = context
@@ -32,8 +33,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let cmake_version =
-                        get_cmake_version(&context.exec_cmd("cmake", &["--version"])?.stdout)?;
+                    let cmake_version = get_cmake_version(
+                        &context.exec_cmd("cmake", &["--version"], &env)?.stdout,
+                    )?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &cmake_version,
@@ -49,7 +51,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `cmake`: \n{}", error);
+            crate::error::StarshipError::from_format("cmake", error).log();
             return None;
         }
     });
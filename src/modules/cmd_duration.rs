@@ -13,7 +13,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: CmdDurationConfig = CmdDurationConfig::try_load(module.config);
 
     if config.min_time < 0 {
-        log::warn!(
+        crate::log_once!(
             "min_time in [cmd_duration] ({}) was less than zero",
             config.min_time
         );
@@ -43,7 +43,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `cmd_duration`: \n{}", error);
+            crate::error::StarshipError::from_format("cmd_duration", error).log();
             return None;
         }
     });
@@ -0,0 +1,127 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::cobol::CobolConfig;
+use crate::formatter::StringFormatter;
+use crate::formatter::VersionFormatter;
+
+/// Creates a module with the current COBOL version
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("cobol");
+    let env = module.env();
+    let config = CobolConfig::try_load(module.config);
+
+    let is_cobol_project = context
+        .try_begin_scan()?
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
+        .is_match();
+
+    if !is_cobol_project {
+        return None;
+    }
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "version" => {
+                    let cobol_version =
+                        get_cobol_version(&context.exec_cmd("cobc", &["-version"], &env)?.stdout)?;
+                    VersionFormatter::format_module_version(
+                        module.get_name(),
+                        &cobol_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("cobol", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn get_cobol_version(cobc_stdout: &str) -> Option<String> {
+    // cobc output looks like this:
+    // cobc (GnuCOBOL) 3.1.2.0
+    // Copyright (C) 2020 Free Software Foundation, Inc.
+    // ...
+
+    let version = cobc_stdout
+        // get the first line
+        .lines()
+        .next()?
+        // return "3.1.2.0"
+        .split_whitespace()
+        .last()?;
+
+    Some(version.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io;
+
+    #[test]
+    fn folder_without_cobol_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("cobol").path(dir.path()).collect();
+
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_cob_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.cob"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("cobol").path(dir.path()).collect();
+
+        let expected = Some(format!("via {}", Color::Blue.bold().paint("⚙️ v3.1.2.0 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_cbl_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("MAIN.CBL"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("cobol").path(dir.path()).collect();
+
+        let expected = Some(format!("via {}", Color::Blue.bold().paint("⚙️ v3.1.2.0 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_cobol_version() {
+        let input = "cobc (GnuCOBOL) 2.2.0\nCopyright (C) 2017 Free Software Foundation, Inc.\n";
+        assert_eq!(get_cobol_version(input), Some("2.2.0".to_string()));
+        assert_eq!(get_cobol_version(""), None);
+    }
+}
@@ -43,7 +43,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `conda`:\n{}", error);
+            crate::error::StarshipError::from_format("conda", error).log();
             return None;
         }
     });
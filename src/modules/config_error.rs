@@ -0,0 +1,122 @@
+use super::{Context, Module};
+
+use crate::config::RootModuleConfig;
+use crate::configs::config_error::ConfigErrorConfig;
+use crate::configs::StarshipRootConfig;
+use crate::error::ErrorCategory;
+use crate::formatter::StringFormatter;
+
+/// Creates a module that warns about errors in the configuration file
+///
+/// Will display the number of problems found in the configuration file, counting an invalid
+/// file as a single problem and otherwise each config error logged while rendering the prompt,
+/// like an unknown key. It's rendered after the other modules of the prompt to count theirs.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let count = if context.config_degraded {
+        1
+    } else {
+        // The root config is loaded by most modules, so its errors are only counted here
+        if let Some(config) = &context.config.config {
+            StarshipRootConfig::load(config);
+        }
+        context.errors.count(ErrorCategory::Config)
+    };
+    if count == 0 {
+        return None;
+    }
+
+    let mut module = context.new_module("config_error");
+    let config: ConfigErrorConfig = ConfigErrorConfig::try_load(module.config);
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "count" => Some(Ok(count.to_string())),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("config_error", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+
+    #[test]
+    fn clean_config() {
+        let actual = ModuleRenderer::new("config_error")
+            .config(toml::toml! {
+                format = "$all"
+                [aws]
+                symbol = "aws "
+                [line_break]
+                disabled = true
+                [custom.foo]
+                command = "echo foo"
+            })
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn no_config() {
+        let actual = ModuleRenderer::new("config_error").collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn broken_toml() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("starship.toml");
+        fs::write(&config_path, "[aws\nsymbol = 1")?;
+
+        let actual = ModuleRenderer::new("config_error")
+            .config_file(&config_path)
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("⚠ 1")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn ascii_symbol_without_utf8_locale() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("starship.toml");
+        fs::write(&config_path, "[aws\nsymbol = 1")?;
+
+        let actual = ModuleRenderer::new("config_error")
+            .config_file(&config_path)
+            .env("LANG", "C")
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("! 1")));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+}
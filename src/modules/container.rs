@@ -0,0 +1,252 @@
+use super::{Context, Module};
+
+use crate::config::RootModuleConfig;
+use crate::configs::container::ContainerConfig;
+use crate::formatter::StringFormatter;
+use crate::utils;
+
+/// The container starship runs in
+struct Container {
+    /// `toolbox`, `distrobox`, or `container` for other containers
+    runtime: &'static str,
+    name: Option<String>,
+}
+
+/// Creates a module with the toolbox or distrobox container the shell is in
+///
+/// Will display the name of the container if it's known
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let container = detect_container(context)?;
+
+    let mut module = context.new_module("container");
+    let config: ContainerConfig = ContainerConfig::try_load(module.config);
+    let symbol = match config.symbols.get(container.runtime) {
+        // Only `symbol` itself is replaced with its fallback by the config
+        Some(symbol) if !symbol.is_ascii() && context.use_symbol_fallback() => config
+            .symbols_fallback
+            .get(container.runtime)
+            .copied()
+            .unwrap_or(config.symbol),
+        Some(symbol) => symbol,
+        None => config.symbol,
+    };
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "name" => container.name.as_deref().map(Ok),
+                "runtime" => Some(Ok(container.runtime)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("container", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn detect_container(context: &Context) -> Option<Container> {
+    let runtime = if is_set(context, "DISTROBOX_ENTER_PATH") {
+        "distrobox"
+    } else if context.system_path("run/.toolboxenv").exists() {
+        "toolbox"
+    } else if is_set(context, "CONTAINER_ID") || is_container_os(context) {
+        "container"
+    } else {
+        return None;
+    };
+
+    Some(Container {
+        runtime,
+        name: container_name(context),
+    })
+}
+
+fn is_set(context: &Context, name: &str) -> bool {
+    matches!(context.get_env(name), Some(value) if !value.is_empty())
+}
+
+/// The name of the container from `CONTAINER_ID`, or else the `name` of the toolbox or
+/// podman env file
+fn container_name(context: &Context) -> Option<String> {
+    context
+        .get_env("CONTAINER_ID")
+        .filter(|name| !name.is_empty())
+        .or_else(|| {
+            ["run/.toolboxenv", "run/.containerenv"]
+                .iter()
+                .filter_map(|path| utils::read_file(context.system_path(path)).ok())
+                .find_map(|contents| env_file_value(&contents, "name"))
+        })
+}
+
+/// Whether `/etc/os-release` describes a container image, like the images of toolbox
+fn is_container_os(context: &Context) -> bool {
+    let variant = utils::read_file(context.system_path("etc/os-release"))
+        .ok()
+        .and_then(|contents| env_file_value(&contents, "VARIANT_ID"));
+    variant.as_deref() == Some("container")
+}
+
+/// The value of `key` in a file of `key=value` lines, with the quotes around it removed
+fn env_file_value(contents: &str, key: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (line_key, value) = line.trim().split_once('=')?;
+        if line_key != key {
+            return None;
+        }
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        Some(value.to_owned()).filter(|value| !value.is_empty())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    fn root_with(files: &[(&str, &str)]) -> io::Result<tempfile::TempDir> {
+        let root = tempfile::tempdir()?;
+        for (path, contents) in files {
+            let path = root.path().join(path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, contents)?;
+        }
+        Ok(root)
+    }
+
+    fn render(root: &Path) -> ModuleRenderer<'static> {
+        ModuleRenderer::new("container").root_dir(root)
+    }
+
+    #[test]
+    fn host() -> io::Result<()> {
+        let root = root_with(&[(
+            "etc/os-release",
+            "NAME=Fedora Linux\nVARIANT_ID=workstation\n",
+        )])?;
+        let actual = render(root.path()).collect();
+        assert_eq!(actual, None);
+        root.close()
+    }
+
+    #[test]
+    fn toolbox() -> io::Result<()> {
+        let root = root_with(&[
+            ("run/.toolboxenv", ""),
+            (
+                "run/.containerenv",
+                "engine=\"podman-4.4.1\"\nname=\"fedora-toolbox-38\"\n",
+            ),
+            ("etc/os-release", "VARIANT_ID=container\n"),
+        ])?;
+        let actual = render(root.path()).collect();
+        let expected = Some(format!(
+            "{}",
+            Color::Red.bold().dimmed().paint("⬢ [fedora-toolbox-38] ")
+        ));
+        assert_eq!(actual, expected);
+        root.close()
+    }
+
+    #[test]
+    fn toolbox_name_from_env() -> io::Result<()> {
+        let root = root_with(&[("run/.toolboxenv", "")])?;
+        let actual = render(root.path())
+            .env("CONTAINER_ID", "dev")
+            .config(toml::toml! {
+                [container]
+                format = "$runtime $name"
+            })
+            .collect();
+        assert_eq!(actual, Some("toolbox dev".to_string()));
+        root.close()
+    }
+
+    #[test]
+    fn distrobox() -> io::Result<()> {
+        let root = root_with(&[("run/.containerenv", "name=\"ubuntu\"\n")])?;
+        let actual = render(root.path())
+            .env("DISTROBOX_ENTER_PATH", "/usr/bin/distrobox-enter")
+            .env("CONTAINER_ID", "arch")
+            .collect();
+        let expected = Some(format!(
+            "{}",
+            Color::Red.bold().dimmed().paint("📦 [arch] ")
+        ));
+        assert_eq!(actual, expected);
+        root.close()
+    }
+
+    #[test]
+    fn custom_runtime_symbols() -> io::Result<()> {
+        let root = root_with(&[("etc/os-release", "VARIANT_ID=\"container\"\n")])?;
+        let config = toml::toml! {
+            [container]
+            format = "$symbol"
+            symbol = "other "
+            [container.symbols]
+            distrobox = "db "
+        };
+
+        let actual = render(root.path()).config(config.clone()).collect();
+        assert_eq!(actual, Some("other ".to_string()));
+
+        let actual = render(root.path())
+            .env("DISTROBOX_ENTER_PATH", "/usr/bin/distrobox-enter")
+            .config(config)
+            .collect();
+        assert_eq!(actual, Some("db ".to_string()));
+        root.close()
+    }
+
+    #[test]
+    fn ascii_symbols_without_utf8_locale() -> io::Result<()> {
+        let root = root_with(&[("etc/os-release", "VARIANT_ID=container\n")])?;
+        let config = toml::toml! {
+            [container]
+            format = "$symbol"
+        };
+
+        let actual = render(root.path())
+            .config(config.clone())
+            .env("LANG", "C")
+            .collect();
+        assert_eq!(actual, Some("container ".to_string()));
+
+        let actual = render(root.path())
+            .config(config)
+            .env("DISTROBOX_ENTER_PATH", "/usr/bin/distrobox-enter")
+            .env("LANG", "C")
+            .collect();
+        assert_eq!(actual, Some("distrobox ".to_string()));
+        root.close()
+    }
+
+    #[test]
+    fn unnamed_container() -> io::Result<()> {
+        let root = root_with(&[("etc/os-release", "VARIANT_ID=container\n")])?;
+        let actual = render(root.path()).collect();
+        let expected = Some(format!("{}", Color::Red.bold().dimmed().paint("⬢ ")));
+        assert_eq!(actual, expected);
+        root.close()
+    }
+}
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Crystal version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("crystal");
+    let env = module.env();
     let config: CrystalConfig = CrystalConfig::try_load(module.config);
 
     let is_crystal_project = context
@@ -32,8 +33,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let crystal_version =
-                        get_crystal_version(&context.exec_cmd("crystal", &["--version"])?.stdout)?;
+                    let crystal_version = get_crystal_version(
+                        &context.exec_cmd("crystal", &["--version"], &env)?.stdout,
+                    )?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &crystal_version,
@@ -49,7 +51,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `crystal`:\n{}", error);
+            crate::error::StarshipError::from_format("crystal", error).log();
             return None;
         }
     });
@@ -1,9 +1,11 @@
 use std::io::Write;
+use std::path::Path;
 use std::process::{Command, Output, Stdio};
 use std::time::Instant;
 
 use super::{Context, Module, RootModuleConfig};
 
+use crate::utils::{self, EnvOverlay};
 use crate::{configs::custom::CustomConfig, formatter::StringFormatter};
 
 /// Creates a custom module with some configuration
@@ -14,11 +16,20 @@ use crate::{configs::custom::CustomConfig, formatter::StringFormatter};
 ///
 /// Finally, the content of the module itself is also set by a command.
 pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
+    if !context.is_trusted_dir() {
+        return None;
+    }
+
     let start: Instant = Instant::now();
-    let toml_config = context.config.get_custom_module_config(name).expect(
-        "modules::custom::module should only be called after ensuring that the module exists",
-    );
+    let toml_config = context
+        .module_configs()
+        .get_custom_module_config(name)
+        .expect(
+            "modules::custom::module should only be called after ensuring that the module exists",
+        );
     let config = CustomConfig::load(toml_config);
+    let mut module = Module::new(name, config.description, Some(toml_config));
+    let env = module.env();
 
     let mut is_match = context
         .try_begin_scan()?
@@ -29,7 +40,7 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
 
     if !is_match {
         if let Some(when) = config.when {
-            is_match = exec_when(when, &config.shell.0);
+            is_match = exec_when(when, &config.shell.0, &env, &context.current_dir);
         }
 
         if !is_match {
@@ -37,8 +48,6 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
         }
     }
 
-    let mut module = Module::new(name, config.description, Some(toml_config));
-
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -51,7 +60,8 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "output" => {
-                    let output = exec_command(config.command, &config.shell.0)?;
+                    let output =
+                        exec_command(config.command, &config.shell.0, &env, &context.current_dir)?;
                     let trimmed = output.trim();
 
                     if trimmed.is_empty() {
@@ -68,9 +78,10 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
     match parsed {
         Ok(segments) => module.set_segments(segments),
         Err(error) => {
-            log::warn!("Error in module `custom.{}`:\n{}", name, error);
+            crate::error::StarshipError::from_format(&format!("custom.{}", name), error).log();
         }
     };
+    module.apply_max_length();
     let elapsed = start.elapsed();
     log::trace!("Took {:?} to compute custom module {:?}", elapsed, name);
     module.duration = elapsed;
@@ -91,11 +102,18 @@ fn get_shell<'a, 'b>(shell_args: &'b [&'a str]) -> (std::borrow::Cow<'a, str>, &
 
 /// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`
 #[cfg(not(windows))]
-fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
+fn shell_command(
+    cmd: &str,
+    shell_args: &[&str],
+    env: &EnvOverlay,
+    current_dir: &Path,
+) -> Option<Output> {
     let (shell, shell_args) = get_shell(shell_args);
     let mut command = Command::new(shell.as_ref());
+    utils::apply_env(&mut command, env);
 
     command
+        .current_dir(current_dir)
         .args(shell_args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
@@ -111,7 +129,11 @@ fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
                 "Could not launch command with given shell or STARSHIP_SHELL env variable, retrying with /usr/bin/env sh"
             );
 
-            Command::new("/usr/bin/env")
+            let mut command = Command::new("/usr/bin/env");
+            utils::apply_env(&mut command, env);
+
+            command
+                .current_dir(current_dir)
                 .arg("sh")
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
@@ -128,7 +150,12 @@ fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
 /// Attempt to run the given command in a shell by passing it as `stdin` to `get_shell()`,
 /// or by invoking cmd.exe /C.
 #[cfg(windows)]
-fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
+fn shell_command(
+    cmd: &str,
+    shell_args: &[&str],
+    env: &EnvOverlay,
+    current_dir: &Path,
+) -> Option<Output> {
     let (shell, shell_args) = if !shell_args.is_empty() {
         (
             Some(std::borrow::Cow::Borrowed(shell_args[0])),
@@ -142,8 +169,10 @@ fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
 
     if let Some(forced_shell) = shell {
         let mut command = Command::new(forced_shell.as_ref());
+        utils::apply_env(&mut command, env);
 
         command
+            .current_dir(current_dir)
             .args(shell_args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -162,7 +191,11 @@ fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
         );
     }
 
-    let command = Command::new("cmd.exe")
+    let mut command = Command::new("cmd.exe");
+    utils::apply_env(&mut command, env);
+
+    let command = command
+        .current_dir(current_dir)
         .arg("/C")
         .arg(cmd)
         .stdin(Stdio::piped())
@@ -173,11 +206,11 @@ fn shell_command(cmd: &str, shell_args: &[&str]) -> Option<Output> {
     command.ok()?.wait_with_output().ok()
 }
 
-/// Execute the given command capturing all output, and return whether it return 0
-fn exec_when(cmd: &str, shell_args: &[&str]) -> bool {
+/// Execute the given command in `current_dir` capturing all output, and return whether it return 0
+fn exec_when(cmd: &str, shell_args: &[&str], env: &EnvOverlay, current_dir: &Path) -> bool {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell_args) {
+    if let Some(output) = shell_command(cmd, shell_args, env, current_dir) {
         if !output.status.success() {
             log::trace!("non-zero exit code '{:?}'", output.status.code());
             log::trace!(
@@ -198,11 +231,16 @@ fn exec_when(cmd: &str, shell_args: &[&str]) -> bool {
     }
 }
 
-/// Execute the given command, returning its output on success
-fn exec_command(cmd: &str, shell_args: &[&str]) -> Option<String> {
+/// Execute the given command in `current_dir`, returning its output on success
+fn exec_command(
+    cmd: &str,
+    shell_args: &[&str],
+    env: &EnvOverlay,
+    current_dir: &Path,
+) -> Option<String> {
     log::trace!("Running '{}'", cmd);
 
-    if let Some(output) = shell_command(cmd, shell_args) {
+    if let Some(output) = shell_command(cmd, shell_args, env, current_dir) {
         if !output.status.success() {
             log::trace!("Non-zero exit code '{:?}'", output.status.code());
             log::trace!(
@@ -253,21 +291,24 @@ mod tests {
 
     #[test]
     fn when_returns_right_value() {
-        assert!(exec_when("echo hello", SHELL));
-        assert!(!exec_when(FAILING_COMMAND, SHELL));
+        assert!(exec_when("echo hello", SHELL, &[], Path::new(".")));
+        assert!(!exec_when(FAILING_COMMAND, SHELL, &[], Path::new(".")));
     }
 
     #[test]
     fn when_returns_false_if_invalid_command() {
-        assert!(!exec_when(UNKNOWN_COMMAND, SHELL));
+        assert!(!exec_when(UNKNOWN_COMMAND, SHELL, &[], Path::new(".")));
     }
 
     #[test]
     #[cfg(not(windows))]
     fn command_returns_right_string() {
-        assert_eq!(exec_command("echo hello", SHELL), Some("hello\n".into()));
         assert_eq!(
-            exec_command("echo 강남스타일", SHELL),
+            exec_command("echo hello", SHELL, &[], Path::new(".")),
+            Some("hello\n".into())
+        );
+        assert_eq!(
+            exec_command("echo 강남스타일", SHELL, &[], Path::new(".")),
             Some("강남스타일\n".into())
         );
     }
@@ -275,9 +316,12 @@ mod tests {
     #[test]
     #[cfg(windows)]
     fn command_returns_right_string() {
-        assert_eq!(exec_command("echo hello", SHELL), Some("hello\r\n".into()));
         assert_eq!(
-            exec_command("echo 강남스타일", SHELL),
+            exec_command("echo hello", SHELL, &[], Path::new(".")),
+            Some("hello\r\n".into())
+        );
+        assert_eq!(
+            exec_command("echo 강남스타일", SHELL, &[], Path::new(".")),
             Some("강남스타일\r\n".into())
         );
     }
@@ -286,11 +330,11 @@ mod tests {
     #[cfg(not(windows))]
     fn command_ignores_stderr() {
         assert_eq!(
-            exec_command("echo foo 1>&2; echo bar", SHELL),
+            exec_command("echo foo 1>&2; echo bar", SHELL, &[], Path::new(".")),
             Some("bar\n".into())
         );
         assert_eq!(
-            exec_command("echo foo; echo bar 1>&2", SHELL),
+            exec_command("echo foo; echo bar 1>&2", SHELL, &[], Path::new(".")),
             Some("foo\n".into())
         );
     }
@@ -299,18 +343,81 @@ mod tests {
     #[cfg(windows)]
     fn command_ignores_stderr() {
         assert_eq!(
-            exec_command("echo foo 1>&2 & echo bar", SHELL),
+            exec_command("echo foo 1>&2 & echo bar", SHELL, &[], Path::new(".")),
             Some("bar\r\n".into())
         );
         assert_eq!(
-            exec_command("echo foo& echo bar 1>&2", SHELL),
+            exec_command("echo foo& echo bar 1>&2", SHELL, &[], Path::new(".")),
             Some("foo\r\n".into())
         );
     }
 
     #[test]
     fn command_can_fail() {
-        assert_eq!(exec_command(FAILING_COMMAND, SHELL), None);
-        assert_eq!(exec_command(UNKNOWN_COMMAND, SHELL), None);
+        assert_eq!(
+            exec_command(FAILING_COMMAND, SHELL, &[], Path::new(".")),
+            None
+        );
+        assert_eq!(
+            exec_command(UNKNOWN_COMMAND, SHELL, &[], Path::new(".")),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn command_uses_env_overlay() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_shell_and_path(
+            clap::ArgMatches::default(),
+            crate::context::Shell::Unknown,
+            dir.path().into(),
+            dir.path().into(),
+        );
+        context.config = crate::config::StarshipConfig {
+            config: Some(toml::toml! {
+                [custom.greeting]
+                command = "echo \"$GREETING ${HOME:-nobody}\""
+                when = "test -z \"$HOME\""
+                shell = ["/bin/sh"]
+                format = "$output"
+                [custom.greeting.env]
+                GREETING = "hello"
+                HOME = ""
+            }),
+        };
+
+        let actual = module("greeting", &context).map(|module| module.to_string());
+
+        assert_eq!(actual, Some("hello nobody".to_string()));
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn skipped_in_untrusted_dir() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        for (owned, expected) in [(true, Some("hello")), (false, None)] {
+            let mut context = Context::new_with_shell_and_path(
+                clap::ArgMatches::default(),
+                crate::context::Shell::Unknown,
+                dir.path().into(),
+                dir.path().into(),
+            );
+            context.config = crate::config::StarshipConfig {
+                config: Some(toml::toml! {
+                    [custom.greeting]
+                    command = "echo hello"
+                    when = "true"
+                    shell = ["/bin/sh"]
+                    format = "$output"
+                }),
+            };
+            context.mock_owned_dir = Some(owned);
+
+            let actual = module("greeting", &context).map(|module| module.to_string());
+            assert_eq!(actual.as_deref(), expected);
+        }
+        dir.close()
     }
 }
@@ -0,0 +1,165 @@
+use yaml_rust::{Yaml, YamlLoader};
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::daml::DamlConfig;
+use crate::formatter::StringFormatter;
+use crate::formatter::VersionFormatter;
+use crate::utils;
+
+const DAML_YAML: &str = "daml.yaml";
+
+/// Creates a module with the Daml SDK version of the current project
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("daml");
+    let config = DamlConfig::try_load(module.config);
+
+    let is_daml_project = context
+        .try_begin_scan()?
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
+        .is_match();
+
+    if !is_daml_project {
+        return None;
+    }
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "version" => {
+                    let daml_version = get_daml_sdk_version(context)?;
+                    VersionFormatter::format_module_version(
+                        module.get_name(),
+                        &daml_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("daml", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// Reads the `sdk-version` of the project from `daml.yaml`, without running `daml`
+fn get_daml_sdk_version(context: &Context) -> Option<String> {
+    let contents = utils::read_file(context.current_dir.join(DAML_YAML)).ok()?;
+    parse_daml_sdk_version(&contents)
+}
+
+fn parse_daml_sdk_version(daml_yaml: &str) -> Option<String> {
+    let yaml_docs = YamlLoader::load_from_str(daml_yaml).ok()?;
+    let sdk_version = &yaml_docs.first()?["sdk-version"];
+
+    // An unquoted version such as `2.0` is parsed as a number, which keeps it as written
+    match sdk_version {
+        Yaml::String(version) | Yaml::Real(version) => Some(version.to_owned()),
+        Yaml::Integer(version) => Some(version.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io::{self, Write};
+
+    #[test]
+    fn folder_without_daml_yaml() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("daml").path(dir.path()).collect();
+
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_daml_yaml() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join(DAML_YAML))?;
+        file.write_all(b"sdk-version: 2.2.0\nname: quickstart\nsource: daml\nversion: 0.0.1\n")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("daml").path(dir.path()).collect();
+
+        let expected = Some(format!("via {}", Color::Cyan.bold().paint("Λ v2.2.0 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_unquoted_sdk_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join(DAML_YAML))?;
+        file.write_all(b"sdk-version: 2.0\nname: quickstart\n")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("daml").path(dir.path()).collect();
+
+        let expected = Some(format!("via {}", Color::Cyan.bold().paint("Λ v2.0 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_daml_yaml_without_sdk_version() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(DAML_YAML))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("daml").path(dir.path()).collect();
+
+        let expected = Some(format!("via {}", Color::Cyan.bold().paint("Λ ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_daml_sdk_version() {
+        assert_eq!(
+            parse_daml_sdk_version("sdk-version: 1.18.1\nname: app\n"),
+            Some("1.18.1".to_string())
+        );
+        assert_eq!(
+            parse_daml_sdk_version("sdk-version: \"2.5.0-snapshot.20221201\"\n"),
+            Some("2.5.0-snapshot.20221201".to_string())
+        );
+        assert_eq!(
+            parse_daml_sdk_version("sdk-version: 2.0\n"),
+            Some("2.0".to_string())
+        );
+        assert_eq!(
+            parse_daml_sdk_version("sdk-version: 2.10\n"),
+            Some("2.10".to_string())
+        );
+        assert_eq!(
+            parse_daml_sdk_version("sdk-version: 2\n"),
+            Some("2".to_string())
+        );
+        assert_eq!(parse_daml_sdk_version("name: app\n"), None);
+    }
+}
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Dart version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("dart");
+    let env = module.env();
     let config: DartConfig = DartConfig::try_load(module.config);
 
     let is_dart_project = context
@@ -33,7 +34,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .map(|variable| match variable {
                 "version" => {
                     let dart_version =
-                        get_dart_version(&context.exec_cmd("dart", &["--version"])?.stderr)?;
+                        get_dart_version(&context.exec_cmd("dart", &["--version"], &env)?.stderr)?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &dart_version,
@@ -49,7 +50,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `dart`:\n{}", error);
+            crate::error::StarshipError::from_format("dart", error).log();
             return None;
         }
     });
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Deno version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("deno");
+    let env = module.env();
     let config = DenoConfig::try_load(module.config);
     let is_deno_project = context
         .try_begin_scan()?
@@ -32,7 +33,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .map(|variable| match variable {
                 "version" => {
                     let deno_version =
-                        get_deno_version(&context.exec_cmd("deno", &["-V"])?.stdout)?;
+                        get_deno_version(&context.exec_cmd("deno", &["-V"], &env)?.stdout)?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &deno_version,
@@ -48,7 +49,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `deno`:\n{}", error);
+            crate::error::StarshipError::from_format("deno", error).log();
             return None;
         }
     });
@@ -1,7 +1,3 @@
-#[cfg(not(target_os = "windows"))]
-use super::utils::directory_nix as directory_utils;
-#[cfg(target_os = "windows")]
-use super::utils::directory_win as directory_utils;
 use super::utils::path::PathExt as SPathExt;
 use indexmap::IndexMap;
 use path_slash::PathExt;
@@ -12,9 +8,11 @@ use unicode_segmentation::UnicodeSegmentation;
 use super::{Context, Module};
 
 use super::utils::directory::truncate;
+use crate::cache;
 use crate::config::RootModuleConfig;
 use crate::configs::directory::DirectoryConfig;
 use crate::formatter::StringFormatter;
+use crate::utils::{self, EnvOverlay};
 
 /// Creates a module with the current logical or physical directory
 ///
@@ -32,14 +30,25 @@ use crate::formatter::StringFormatter;
 /// Paths will be limited in length to `3` path components by default.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("directory");
-    let config: DirectoryConfig = DirectoryConfig::try_load(module.config);
+    let mut config: DirectoryConfig = DirectoryConfig::try_load(module.config);
 
-    let home_symbol = String::from(config.home_symbol);
     let home_dir = context
         .get_home()
         .expect("Unable to determine HOME_DIR for user");
+    config.substitutions = expand_substitutions(context, &config, &home_dir);
     let physical_dir = &context.current_dir;
-    let display_dir = if config.use_logical_path {
+    // Git runs aliases from the root of the work tree, with the directory they were
+    // invoked from in `GIT_PREFIX`
+    let git_prefix_dir = context
+        .get_env("GIT_PREFIX")
+        .filter(|prefix| !prefix.is_empty())
+        .and_then(|prefix| {
+            let root = context.get_repo().ok()?.root.as_ref()?;
+            Some(root.join(prefix.trim_end_matches('/')))
+        });
+    let display_dir = if let Some(git_prefix_dir) = &git_prefix_dir {
+        git_prefix_dir
+    } else if config.use_logical_path {
         &context.logical_dir
     } else {
         &context.current_dir
@@ -55,58 +64,72 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     } else {
         None
     };
-    let dir_string = repo
-        .and_then(|r| r.root.as_ref())
-        .filter(|root| *root != &home_dir)
-        .and_then(|root| contract_repo_path(&display_dir, root));
-
-    // Otherwise use the logical path, automatically contracting
-    // the home directory if required.
-    let dir_string =
-        dir_string.unwrap_or_else(|| contract_path(&display_dir, &home_dir, &home_symbol));
-
-    #[cfg(windows)]
-    let dir_string = remove_extended_path_prefix(dir_string);
-
-    // Apply path substitutions
-    let dir_string = substitute_path(dir_string, &config.substitutions);
+    let repo_root = repo
+        .and_then(|r| r.root.as_deref())
+        .filter(|root| *root != home_dir);
+
+    // Under WSL, paths on Windows drives can be shown by their Windows name
+    let translate_wsl = config.translate_wsl_paths && context.is_wsl();
+
+    let displayed_path = format_path(display_dir, repo_root, &home_dir, translate_wsl, &config);
+
+    // Split the path around the repo root component when it is styled separately
+    let split_path = if config.repo_root_style.is_some() {
+        context
+            .get_repo()
+            .ok()
+            .and_then(|r| r.root.as_deref())
+            .filter(|root| *root != home_dir)
+            .and_then(|root| split_repo_root(display_dir, root, &displayed_path))
+    } else {
+        None
+    };
+    let (display_format, [before_root_path, repo_root_name, path]) = match split_path {
+        Some(parts) => (config.repo_root_format, parts),
+        None => (config.format, ["", "", displayed_path.as_str()]),
+    };
 
-    // Truncate the dir string to the maximum number of path components
-    let dir_string = truncate(dir_string, config.truncation_length as usize);
+    // The directory the shell was in before the last `cd`, if it differs from the current one
+    let previous_path = if config.show_previous {
+        context
+            .get_env_os("OLDPWD")
+            .filter(|oldpwd| !oldpwd.is_empty())
+            .map(PathBuf::from)
+            .filter(|oldpwd| oldpwd != &context.logical_dir && oldpwd != physical_dir)
+            .map(|oldpwd| format_path(&oldpwd, repo_root, &home_dir, translate_wsl, &config))
+    } else {
+        None
+    };
 
-    let prefix = if is_truncated(&dir_string, &home_symbol) {
-        // Substitutions could have changed the prefix, so don't allow them and
-        // fish-style path contraction together
-        if config.fish_style_pwd_dir_length > 0 && config.substitutions.is_empty() {
-            // If user is using fish style path, we need to add the segment first
-            let contracted_home_dir = contract_path(&display_dir, &home_dir, &home_symbol);
-            to_fish_style(
-                config.fish_style_pwd_dir_length as usize,
-                contracted_home_dir,
-                &dir_string,
-            )
-        } else {
-            String::from(config.truncation_symbol)
-        }
+    // A short name for the directory, e.g. from zoxide
+    let alias = if config.alias_command.is_empty() {
+        None
     } else {
-        String::from("")
+        get_alias(context, &config, display_dir, &module.env())
     };
 
-    let displayed_path = prefix + &dir_string;
     let lock_symbol = String::from(config.read_only);
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let parsed = StringFormatter::new(display_format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
                 "style" => Some(Ok(config.style)),
                 "read_only_style" => Some(Ok(config.read_only_style)),
+                "repo_root_style" => Some(Ok(config.repo_root_style.unwrap_or(config.style))),
+                "before_repo_root_style" => {
+                    Some(Ok(config.before_repo_root_style.unwrap_or(config.style)))
+                }
                 _ => None,
             })
             .map(|variable| match variable {
-                "path" => Some(Ok(&displayed_path)),
+                "path" if !path.is_empty() => Some(Ok(path)),
+                "repo_root" => Some(Ok(repo_root_name)),
+                "before_root_path" if !before_root_path.is_empty() => Some(Ok(before_root_path)),
+                "previous" => previous_path.as_deref().map(Ok),
+                "alias" => alias.as_deref().map(Ok),
                 "read_only" => {
-                    if is_readonly_dir(&physical_dir) {
-                        Some(Ok(&lock_symbol))
+                    if context.is_readonly_dir() {
+                        Some(Ok(lock_symbol.as_str()))
                     } else {
                         None
                     }
@@ -119,7 +142,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `directory`:\n{}", error);
+            crate::error::StarshipError::from_format("directory", error).log();
             return None;
         }
     });
@@ -127,6 +150,117 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Runs `alias_command` with `dir` as its last argument and returns what it printed, unless
+/// that was nothing. A command that fails or times out has no alias either.
+fn get_alias(
+    context: &Context,
+    config: &DirectoryConfig,
+    dir: &Path,
+    env: &EnvOverlay,
+) -> Option<String> {
+    let command = match shell_words::split(config.alias_command) {
+        Ok(command) => command,
+        Err(error) => {
+            crate::log_once!("Unable to parse `directory.alias_command`: {}", error);
+            return None;
+        }
+    };
+    let (cmd, args) = command.split_first()?;
+    let dir = dir.to_string_lossy();
+
+    let alias = cache::sampled_for(
+        context,
+        "directory",
+        Some(&dir),
+        config.alias_refresh_interval_ms,
+        || {
+            let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+            args.push(&dir);
+            let output = context.exec_cmd(cmd, &args, env)?;
+            Some(output.stdout.trim().to_owned())
+        },
+    )?;
+    Some(alias).filter(|alias| !alias.is_empty())
+}
+
+/// Contract, substitute and truncate a path for display
+fn format_path(
+    display_dir: &Path,
+    repo_root: Option<&Path>,
+    home_dir: &Path,
+    translate_wsl: bool,
+    config: &DirectoryConfig,
+) -> String {
+    let home_symbol = config.home_symbol;
+
+    let dir_string = repo_root.and_then(|root| contract_repo_path(display_dir, root));
+
+    // Paths on Windows drives mounted in WSL are shown like `C:/Users`, unless they're in HOME
+    let wsl_dir_string = if translate_wsl && !display_dir.starts_with(home_dir) {
+        utils::wslpath(display_dir)
+    } else {
+        None
+    };
+
+    // Otherwise use the logical path, automatically contracting
+    // the home directory if required.
+    let dir_string = dir_string
+        .or_else(|| wsl_dir_string.clone())
+        .unwrap_or_else(|| contract_path(display_dir, home_dir, home_symbol));
+
+    #[cfg(windows)]
+    let dir_string = remove_extended_path_prefix(dir_string);
+
+    // Apply path substitutions
+    let dir_string = substitute_path(dir_string, &config.substitutions);
+
+    // Truncate the dir string to the maximum number of path components
+    let dir_string = truncate(dir_string, config.truncation_length as usize);
+
+    let prefix = if is_truncated(&dir_string, home_symbol) {
+        // Substitutions could have changed the prefix, so don't allow them and
+        // fish-style path contraction together
+        if config.fish_style_pwd_dir_length > 0 && config.substitutions.is_empty() {
+            // If user is using fish style path, we need to add the segment first
+            let contracted_home_dir =
+                wsl_dir_string.unwrap_or_else(|| contract_path(display_dir, home_dir, home_symbol));
+            to_fish_style(
+                config.fish_style_pwd_dir_length as usize,
+                contracted_home_dir,
+                &dir_string,
+            )
+        } else {
+            String::from(config.truncation_symbol)
+        }
+    } else {
+        String::from("")
+    };
+
+    prefix + &dir_string
+}
+
+/// Split a displayed path into the part before the repo root, the repo root
+/// component itself, and the part after it
+///
+/// Returns `None` when the repo root isn't visible in the displayed path, e.g.
+/// because it was truncated away or replaced by a substitution.
+fn split_repo_root<'a>(
+    display_dir: &Path,
+    repo_root: &Path,
+    displayed_path: &'a str,
+) -> Option<[&'a str; 3]> {
+    let contracted = contract_repo_path(display_dir, repo_root)?;
+    let root_name_len = contracted.split('/').next()?.len();
+
+    let before = displayed_path.strip_suffix(contracted.as_str())?;
+    if !(before.is_empty() || before.ends_with('/')) {
+        return None;
+    }
+
+    let (root, after) = displayed_path[before.len()..].split_at(root_name_len);
+    Some([before, root, after])
+}
+
 #[cfg(windows)]
 fn remove_extended_path_prefix(path: String) -> String {
     fn try_trim_prefix<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
@@ -148,21 +282,17 @@ fn remove_extended_path_prefix(path: String) -> String {
 fn is_truncated(path: &str, home_symbol: &str) -> bool {
     !(path.starts_with(&home_symbol)
         || PathBuf::from(path).has_root()
-        || (cfg!(target_os = "windows") && PathBuf::from(String::from(path) + r"\").has_root()))
+        || (cfg!(target_os = "windows") && PathBuf::from(String::from(path) + r"\").has_root())
+        || has_drive_prefix(path))
 }
 
-fn is_readonly_dir(path: &Path) -> bool {
-    match directory_utils::is_write_allowed(path) {
-        Ok(res) => !res,
-        Err(e) => {
-            log::debug!(
-                "Failed to determine read only status of directory '{:?}': {}",
-                path,
-                e
-            );
-            false
-        }
-    }
+/// Whether a path starts with a Windows drive, like the WSL paths translated by `utils::wslpath`
+fn has_drive_prefix(path: &str) -> bool {
+    let mut chars = path.chars();
+    matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some(drive), Some(':'), Some('/')) if drive.is_ascii_alphabetic()
+    )
 }
 
 /// Contract the root component of a path
@@ -247,6 +377,27 @@ fn real_path<P: AsRef<Path>>(path: P) -> PathBuf {
     buf.canonicalize().unwrap_or_else(|_| path.into())
 }
 
+/// Expands the keys of the substitutions like other paths of the config. The keys that change
+/// have the home directory contracted again, like the path they're substituted in.
+fn expand_substitutions<'a>(
+    context: &Context,
+    config: &DirectoryConfig<'a>,
+    home_dir: &Path,
+) -> IndexMap<String, &'a str> {
+    config
+        .substitutions
+        .iter()
+        .map(|(from, to)| {
+            let expanded = utils::expand_path(from, context);
+            if expanded == Path::new(from) {
+                (from.clone(), *to)
+            } else {
+                (contract_path(&expanded, home_dir, config.home_symbol), *to)
+            }
+        })
+        .collect()
+}
+
 /// Perform a list of string substitutions on the path
 ///
 /// Given a list of (from, to) pairs, this will perform the string
@@ -297,8 +448,9 @@ fn to_fish_style(pwd_dir_length: usize, dir_string: String, truncated_dir_string
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test::ModuleRenderer;
-    use ansi_term::Color;
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use crate::utils::CommandOutput;
+    use ansi_term::{Color, Style};
     use dirs_next::home_dir;
     #[cfg(not(target_os = "windows"))]
     use std::os::unix::fs::symlink;
@@ -585,6 +737,199 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn substitution_keys_expanded() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/some/long/network/path/workspace")
+            .env("NETWORK", "/some/long/network")
+            .config(toml::toml! {
+                [directory]
+                [directory.substitutions]
+                "${NETWORK}/path" = "net"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("net/workspace")));
+        assert_eq!(expected, actual);
+
+        let code = home_dir().unwrap().join("code");
+        for key in ["~/code", "${CODE}"] {
+            let actual = ModuleRenderer::new("directory")
+                .path(code.join("project"))
+                .env("CODE", code.to_string_lossy())
+                .config(
+                    format!("[directory.substitutions]\n{:?} = \"src\"", key)
+                        .parse()
+                        .unwrap(),
+                )
+                .collect();
+            let expected = Some(format!("{} ", Color::Cyan.bold().paint("src/project")));
+            assert_eq!(expected, actual, "{}", key);
+        }
+    }
+
+    #[test]
+    fn previous_directory_shown() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/path/to/sub")
+            .env("OLDPWD", "/some/long/previous/dir")
+            .config(toml::toml! {
+                [directory]
+                show_previous = true
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan
+                .bold()
+                .paint("/path/to/sub from long/previous/dir")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    fn alias_output(stdout: &str) -> Option<CommandOutput> {
+        Some(CommandOutput {
+            stdout: stdout.to_owned(),
+            stderr: String::default(),
+            status: Some(0),
+        })
+    }
+
+    #[test]
+    fn alias_shown_before_path() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/path/to/sub")
+            .cmd("dir-alias --short /path/to/sub", alias_output("sub\n"))
+            .config(toml::toml! {
+                [directory]
+                alias_command = "dir-alias --short"
+                format = "[(\\[$alias\\] )$path]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint("[sub] /path/to/sub")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn alias_collapses_when_empty() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/path/to/sub")
+            .cmd("dir-alias /path/to/sub", alias_output("  \n"))
+            .config(toml::toml! {
+                [directory]
+                alias_command = "dir-alias"
+                format = "[(\\[$alias\\] )$path]($style) "
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("/path/to/sub")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn alias_empty_on_timeout() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let render = |config: toml::Value| {
+            ModuleRenderer::new("directory")
+                .path(dir.path())
+                .config(config)
+                .collect()
+        };
+
+        let quick = render(toml::toml! {
+            [directory]
+            alias_command = "sh -c 'echo quick' sh"
+            format = "$alias"
+        });
+        assert_eq!(quick, Some("quick".to_owned()));
+
+        let slow = render(toml::toml! {
+            [directory]
+            alias_command = "sh -c 'sleep 2; echo slow' sh"
+            format = "$alias"
+        });
+        assert_eq!(slow, None);
+        dir.close()
+    }
+
+    #[test]
+    fn alias_cached_by_directory() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+        let render = |path: &str, command: Option<(&str, &str)>| {
+            let mut renderer = ModuleRenderer::new("directory")
+                .path(path)
+                .env("STARSHIP_CACHE", cache.path().to_string_lossy())
+                .env("STARSHIP_SESSION_KEY", "test")
+                .config(toml::toml! {
+                    [directory]
+                    alias_command = "dir-alias"
+                    alias_refresh_interval_ms = 60_000
+                    format = "$alias"
+                });
+            if let Some((command, stdout)) = command {
+                renderer = renderer.cmd(command, alias_output(stdout));
+            }
+            renderer.collect()
+        };
+
+        assert_eq!(
+            render("/a", Some(("dir-alias /a", "a"))),
+            Some("a".to_owned())
+        );
+        // Without the command, the alias of the same directory comes from the cache
+        assert_eq!(render("/a", None), Some("a".to_owned()));
+        assert_eq!(
+            render("/b", Some(("dir-alias /b", "b"))),
+            Some("b".to_owned())
+        );
+        cache.close()
+    }
+
+    #[test]
+    fn previous_directory_hidden_when_same_as_current() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/path/to/sub")
+            .env("OLDPWD", "/path/to/sub")
+            .config(toml::toml! {
+                [directory]
+                show_previous = true
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("/path/to/sub")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn previous_directory_hidden_when_unset() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/path/to/sub")
+            .config(toml::toml! {
+                [directory]
+                show_previous = true
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("/path/to/sub")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn previous_directory_hidden_by_default() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/path/to/sub")
+            .env("OLDPWD", "/some/other/dir")
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("/path/to/sub")));
+
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn substitution_order() {
         let actual = ModuleRenderer::new("directory")
@@ -682,6 +1027,69 @@ mod tests {
         tmp_dir.close()
     }
 
+    #[test]
+    fn wsl_windows_drive_translated() {
+        let render = |path: &str| {
+            ModuleRenderer::new("directory")
+                .config(toml::toml! {
+                    [directory]
+                    translate_wsl_paths = true
+                })
+                .env("WSL_DISTRO_NAME", "Ubuntu")
+                .path(path)
+                .collect()
+        };
+
+        assert_eq!(
+            render("/mnt/c/Users"),
+            Some(format!("{} ", Color::Cyan.bold().paint("C:/Users")))
+        );
+        assert_eq!(
+            render("/mnt/c/Users/astronaut/projects"),
+            Some(format!(
+                "{} ",
+                Color::Cyan.bold().paint("Users/astronaut/projects")
+            ))
+        );
+        assert_eq!(
+            render("/mnt/wsl/shared"),
+            Some(format!("{} ", Color::Cyan.bold().paint("/mnt/wsl/shared")))
+        );
+    }
+
+    #[test]
+    fn wsl_windows_drive_translated_with_fish_style() {
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                translate_wsl_paths = true
+                fish_style_pwd_dir_length = 1
+            })
+            .env("WSL_DISTRO_NAME", "Ubuntu")
+            .path("/mnt/c/Users/astronaut/projects/starship")
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint("C/U/astronaut/projects/starship")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn wsl_windows_drive_not_translated_outside_wsl() {
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                translate_wsl_paths = true
+            })
+            .path("/mnt/c/Users")
+            .collect();
+        let expected = Some(format!("{} ", Color::Cyan.bold().paint("/mnt/c/Users")));
+
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn root_directory() {
         // Note: We have disable the read_only settings here due to false positives when running
@@ -987,6 +1395,177 @@ mod tests {
         tmp_dir.close()
     }
 
+    #[test]
+    fn repo_root_style_outside_repo() -> io::Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let dir = tmp_dir.path().join("rocket-controls/src");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                repo_root_style = "bold red"
+                before_repo_root_style = "dimmed"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint(format!(
+                "{}/rocket-controls/src",
+                tmp_dir.path().file_name().unwrap().to_string_lossy()
+            ))
+        ));
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
+    #[test]
+    fn repo_root_style_in_git_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let dir = repo_dir.path().join("src/meters");
+        fs::create_dir_all(&dir)?;
+        let above_repo = repo_dir.path().parent().unwrap().to_slash_lossy();
+        let repo_name = repo_dir.path().file_name().unwrap().to_string_lossy();
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_length = 0
+                truncate_to_repo = false
+                repo_root_style = "bold red"
+                before_repo_root_style = "dimmed"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "{}{}{} ",
+            Style::new().dimmed().paint(format!("{}/", above_repo)),
+            Color::Red.bold().paint(repo_name),
+            Color::Cyan.bold().paint("/src/meters"),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_root_style_truncated_to_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let repo_name = repo_dir.path().file_name().unwrap().to_string_lossy();
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                repo_root_style = "bold red"
+                repo_root_format = "<[$before_root_path]($before_repo_root_style)[$repo_root]($repo_root_style)>[$path]($style) "
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!("<{}> ", Color::Red.bold().paint(repo_name)));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_root_style_in_git_repo_in_home() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let dir = repo_dir.path().join("meters");
+        fs::create_dir_all(&dir)?;
+        let home = repo_dir.path().parent().unwrap();
+        let repo_name = repo_dir.path().file_name().unwrap().to_string_lossy();
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_length = 0
+                truncate_to_repo = false
+                home_symbol = "⌂"
+                repo_root_style = "bold red"
+            })
+            .path(dir)
+            .env("HOME", home.to_str().unwrap())
+            .collect();
+        let expected = Some(format!(
+            "{}{}{} ",
+            Color::Cyan.bold().paint("⌂/"),
+            Color::Red.bold().paint(repo_name),
+            Color::Cyan.bold().paint("/meters"),
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn repo_root_style_when_root_is_truncated() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let dir = repo_dir.path().join("src/meters/fuel-gauge");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                repo_root_style = "bold red"
+            })
+            .path(dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint("src/meters/fuel-gauge")
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn git_prefix_in_alias() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        fs::create_dir_all(repo_dir.path().join("src/meters"))?;
+        let repo_name = repo_dir.path().file_name().unwrap().to_string_lossy();
+
+        let actual = ModuleRenderer::new("directory")
+            .path(repo_dir.path())
+            .env("GIT_DIR", ".git")
+            .env("GIT_PREFIX", "src/meters/")
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan
+                .bold()
+                .paint(format!("{}/src/meters", repo_name))
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn git_work_tree_in_hook() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let other_dir = tempfile::tempdir()?;
+        fs::create_dir_all(repo_dir.path().join("src"))?;
+        let repo_name = repo_dir.path().file_name().unwrap().to_string_lossy();
+
+        let actual = ModuleRenderer::new("directory")
+            .path(other_dir.path())
+            .env("GIT_DIR", repo_dir.path().join(".git").to_string_lossy())
+            .env("GIT_WORK_TREE", repo_dir.path().to_string_lossy())
+            .env("GIT_PREFIX", "src/")
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint(format!("{}/src", repo_name))
+        ));
+
+        assert_eq!(expected, actual);
+        other_dir.close()?;
+        repo_dir.close()
+    }
+
     #[test]
     #[ignore]
     fn symlinked_git_repo_root() -> io::Result<()> {
@@ -67,7 +67,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     module.set_segments(match parsed {
                         Ok(segments) => segments,
                         Err(error) => {
-                            log::warn!("Error in module `docker_context`:\n{}", error);
+                            crate::error::StarshipError::from_format("docker_context", error).log();
                             return None;
                         }
                     });
@@ -8,7 +8,7 @@ use std::str;
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::dotnet::DotnetConfig;
 use crate::formatter::StringFormatter;
-use crate::utils;
+use crate::utils::{self, EnvOverlay};
 
 type JValue = serde_json::Value;
 use crate::formatter::VersionFormatter;
@@ -20,6 +20,7 @@ const PROJECT_JSON_FILE: &str = "project.json";
 
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("dotnet");
+    let env = module.env();
     let config = DotnetConfig::try_load(module.config);
 
     // First check if this is a DotNet Project before doing the O(n)
@@ -60,9 +61,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                             &dotnet_files,
                             &context.current_dir,
                             repo_root,
+                            &env,
                         )
                     } else {
-                        get_version_from_cli(context)
+                        get_version_from_cli(context, &env)
                     };
                     VersionFormatter::format_module_version(
                         module.get_name(),
@@ -80,7 +82,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `dotnet`:\n{}", error);
+            crate::error::StarshipError::from_format("dotnet", error).log();
             return None;
         }
     });
@@ -139,6 +141,7 @@ fn estimate_dotnet_version(
     files: &[DotNetFile],
     current_dir: &Path,
     repo_root: Option<&Path>,
+    env: &EnvOverlay,
 ) -> Option<String> {
     let get_file_of_type = |t: FileType| files.iter().find(|f| f.file_type == t);
 
@@ -150,18 +153,18 @@ fn estimate_dotnet_version(
 
     match relevant_file.file_type {
         FileType::GlobalJson => get_pinned_sdk_version_from_file(relevant_file.path.as_path())
-            .or_else(|| get_latest_sdk_from_cli(context)),
+            .or_else(|| get_latest_sdk_from_cli(context, env)),
         FileType::SolutionFile => {
             // With this heuristic, we'll assume that a "global.json" won't
             // be found in any directory above the solution file.
-            get_latest_sdk_from_cli(context)
+            get_latest_sdk_from_cli(context, env)
         }
         _ => {
             // If we see a dotnet project, we'll check a small number of neighboring
             // directories to see if we can find a global.json. Otherwise, assume the
             // latest SDK is in use.
             try_find_nearby_global_json(current_dir, repo_root)
-                .or_else(|| get_latest_sdk_from_cli(context))
+                .or_else(|| get_latest_sdk_from_cli(context, env))
         }
     }
 }
@@ -286,13 +289,13 @@ fn map_str_to_lower(value: Option<&OsStr>) -> Option<String> {
     Some(value?.to_str()?.to_ascii_lowercase())
 }
 
-fn get_version_from_cli(context: &Context) -> Option<String> {
-    let version_output = context.exec_cmd("dotnet", &["--version"])?;
+fn get_version_from_cli(context: &Context, env: &EnvOverlay) -> Option<String> {
+    let version_output = context.exec_cmd("dotnet", &["--version"], env)?;
     Some(format!("v{}", version_output.stdout.trim()))
 }
 
-fn get_latest_sdk_from_cli(context: &Context) -> Option<String> {
-    match context.exec_cmd("dotnet", &["--list-sdks"]) {
+fn get_latest_sdk_from_cli(context: &Context, env: &EnvOverlay) -> Option<String> {
+    match context.exec_cmd("dotnet", &["--list-sdks"], env) {
         Some(sdks_output) => {
             fn parse_failed<T>() -> Option<T> {
                 log::warn!("Unable to parse the output from `dotnet --list-sdks`.");
@@ -322,7 +325,7 @@ fn get_latest_sdk_from_cli(context: &Context) -> Option<String> {
                 "Received a non-success exit code from `dotnet --list-sdks`. \
                  Falling back to `dotnet --version`.",
             );
-            get_version_from_cli(context)
+            get_version_from_cli(context, env)
         }
     }
 }
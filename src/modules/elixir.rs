@@ -2,6 +2,7 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::elixir::ElixirConfig;
 use crate::formatter::StringFormatter;
+use crate::utils::EnvOverlay;
 
 use crate::formatter::VersionFormatter;
 use once_cell::sync::Lazy;
@@ -10,6 +11,7 @@ use std::ops::Deref;
 /// Create a module with the current Elixir version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("elixir");
+    let env = module.env();
     let config = ElixirConfig::try_load(module.config);
 
     let is_elixir_project = context
@@ -23,7 +25,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
-    let versions = Lazy::new(|| get_elixir_version(context));
+    let versions = Lazy::new(|| get_elixir_version(context, &env));
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -61,7 +63,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `elixir`:\n{}", error);
+            crate::error::StarshipError::from_format("elixir", error).log();
             return None;
         }
     });
@@ -69,8 +71,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_elixir_version(context: &Context) -> Option<(String, String)> {
-    let output = context.exec_cmd("elixir", &["--version"])?.stdout;
+fn get_elixir_version(context: &Context, env: &EnvOverlay) -> Option<(String, String)> {
+    let output = context.exec_cmd("elixir", &["--version"], env)?.stdout;
 
     parse_elixir_version(&output)
 }
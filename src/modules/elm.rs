@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Elm version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("elm");
+    let env = module.env();
     let config: ElmConfig = ElmConfig::try_load(module.config);
 
     let is_elm_project = context
@@ -32,7 +33,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let elm_version = context.exec_cmd("elm", &["--version"])?.stdout;
+                    let elm_version = context.exec_cmd("elm", &["--version"], &env)?.stdout;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &elm_version.trim(),
@@ -48,7 +49,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `elm`:\n{}", error);
+            crate::error::StarshipError::from_format("elm", error).log();
             return None;
         }
     });
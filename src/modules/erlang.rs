@@ -3,10 +3,12 @@ use super::{Context, Module, RootModuleConfig};
 use crate::configs::erlang::ErlangConfig;
 use crate::formatter::StringFormatter;
 use crate::formatter::VersionFormatter;
+use crate::utils::EnvOverlay;
 
 /// Create a module with the current Erlang version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("erlang");
+    let env = module.env();
     let config = ErlangConfig::try_load(module.config);
 
     let is_erlang_project = context
@@ -32,7 +34,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let erlang_version = get_erlang_version(context)?;
+                    let erlang_version = get_erlang_version(context, &env)?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &erlang_version,
@@ -48,7 +50,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `erlang`:\n{}", error);
+            crate::error::StarshipError::from_format("erlang", error).log();
             return None;
         }
     });
@@ -56,7 +58,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_erlang_version(context: &Context) -> Option<String> {
+fn get_erlang_version(context: &Context, env: &EnvOverlay) -> Option<String> {
     Some(context.exec_cmd(
         "erl",
         &[
@@ -66,7 +68,8 @@ fn get_erlang_version(context: &Context) -> Option<String> {
              {ok,Content}=file:read_file(Fn),\
              io:format(\"~s\",[Content]),\
              halt(0)."
-        ]
+        ],
+        env,
     )?.stdout.trim().to_string())
 }
 
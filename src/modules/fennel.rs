@@ -0,0 +1,114 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::fennel::FennelConfig;
+use crate::formatter::StringFormatter;
+use crate::formatter::VersionFormatter;
+
+/// Creates a module with the current Fennel version
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("fennel");
+    let env = module.env();
+    let config = FennelConfig::try_load(module.config);
+
+    let is_fennel_project = context
+        .try_begin_scan()?
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
+        .is_match();
+
+    if !is_fennel_project {
+        return None;
+    }
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "version" => {
+                    let fennel_version = get_fennel_version(
+                        &context.exec_cmd("fennel", &["--version"], &env)?.stdout,
+                    )?;
+                    VersionFormatter::format_module_version(
+                        module.get_name(),
+                        &fennel_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("fennel", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn get_fennel_version(fennel_stdout: &str) -> Option<String> {
+    // fennel output looks like this:
+    // Fennel 1.2.1 on PUC Lua 5.4
+
+    let version = fennel_stdout
+        // split into ["Fennel", "1.2.1", "on", ...]
+        .split_whitespace()
+        // return "1.2.1"
+        .nth(1)?;
+
+    Some(version.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io;
+
+    #[test]
+    fn folder_without_fennel_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("fennel").path(dir.path()).collect();
+
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_fennel_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.fnl"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("fennel").path(dir.path()).collect();
+
+        let expected = Some(format!("via {}", Color::Green.bold().paint("🧅 v1.2.1 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_fennel_version() {
+        assert_eq!(
+            get_fennel_version("Fennel 0.9.2 on LuaJIT 2.1.0-beta3\n"),
+            Some("0.9.2".to_string())
+        );
+        assert_eq!(get_fennel_version(""), None);
+    }
+}
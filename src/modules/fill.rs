@@ -0,0 +1,39 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::config::parse_style_string;
+use crate::configs::fill::FillConfig;
+use crate::segment::Segment;
+
+/// Creates a module that fills the rest of its line of the prompt with `symbol`.
+///
+/// The width is only known once the whole prompt is assembled, so the module marks where the
+/// line is filled and the symbol is repeated then.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("fill");
+    let config = FillConfig::try_load(module.config);
+
+    let style = parse_style_string(config.style);
+    module.set_segments(vec![Segment::fill(style, config.symbol)]);
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+
+    #[test]
+    fn shows_symbol_once_by_itself() {
+        let actual = ModuleRenderer::new("fill")
+            .config(toml::toml! {
+                [fill]
+                symbol = "-"
+                style = "green"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Green.paint("-")));
+
+        assert_eq!(expected, actual);
+    }
+}
@@ -1,4 +1,6 @@
 use once_cell::sync::{Lazy, OnceCell};
+use std::fs::File;
+use std::io::Read;
 use std::ops::Deref;
 use std::path::Path;
 use std::path::PathBuf;
@@ -6,11 +8,14 @@ use std::path::PathBuf;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::gcloud::GcloudConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{StringFormatter, VariableHolder};
 use crate::utils;
 
 type Account = (String, Option<String>);
 
+/// The largest service account key file read for `$credentials`
+const CREDENTIALS_SIZE_LIMIT: u64 = 64 * 1024;
+
 struct GcloudContext {
     config_name: String,
     config_path: PathBuf,
@@ -106,13 +111,46 @@ fn get_active_config(context: &Context, config_dir: &Path) -> Option<String> {
     })
 }
 
+/// The local part of the `client_email` of a service account key file
+fn get_credentials_user(path: &Path) -> Option<String> {
+    let mut content = String::new();
+    File::open(path)
+        .ok()?
+        .take(CREDENTIALS_SIZE_LIMIT)
+        .read_to_string(&mut content)
+        .ok()?;
+    let key: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let email = key.get("client_email")?.as_str()?;
+    email.split('@').next().map(String::from)
+}
+
+/// Whether `format` has the `$credentials` variable
+fn shows_credentials(format: &str) -> bool {
+    StringFormatter::new(format)
+        .map(|formatter| formatter.get_variables().contains("credentials"))
+        .unwrap_or(false)
+}
+
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("gcloud");
     let config: GcloudConfig = GcloudConfig::try_load(module.config);
 
-    let (config_name, config_path) = get_current_config(context)?;
-    let gcloud_context = GcloudContext::new(&config_name, &config_path);
-    let account: Lazy<Option<Account>, _> = Lazy::new(|| gcloud_context.get_account());
+    let credentials_path = context
+        .get_env("GOOGLE_APPLICATION_CREDENTIALS")
+        .filter(|path| !path.is_empty());
+    let credentials_user: Lazy<Option<String>, _> = Lazy::new(|| {
+        credentials_path
+            .as_ref()
+            .and_then(|path| get_credentials_user(Path::new(path)))
+    });
+    let gcloud_context = match get_current_config(context) {
+        Some((config_name, config_path)) => Some(GcloudContext::new(&config_name, &config_path)),
+        // Without a configuration, only the service account of a key file can be shown
+        None if shows_credentials(config.format) && credentials_user.is_some() => None,
+        None => return None,
+    };
+    let account: Lazy<Option<Account>, _> =
+        Lazy::new(|| gcloud_context.as_ref().and_then(|ctx| ctx.get_account()));
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -136,7 +174,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     .and_then(|(_, domain)| (*domain).to_owned())
                     .map(Ok),
                 "region" => gcloud_context
-                    .get_region()
+                    .as_ref()
+                    .and_then(|ctx| ctx.get_region())
                     .map(|region| {
                         config
                             .region_aliases
@@ -147,9 +186,18 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     .map(Ok),
                 "project" => context
                     .get_env("CLOUDSDK_CORE_PROJECT")
-                    .or_else(|| gcloud_context.get_project())
+                    .or_else(|| gcloud_context.as_ref().and_then(|ctx| ctx.get_project()))
                     .map(Ok),
-                "active" => Some(Ok(gcloud_context.config_name.to_owned())),
+                "active" => gcloud_context
+                    .as_ref()
+                    .map(|ctx| Ok(ctx.config_name.to_owned())),
+                "credentials" => credentials_path.as_ref().map(|_| {
+                    Ok(format!(
+                        "{}{}",
+                        config.service_account_symbol,
+                        credentials_user.deref().as_deref().unwrap_or_default()
+                    ))
+                }),
                 _ => None,
             })
             .parse(None)
@@ -158,7 +206,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::error!("Error in module `gcloud`: \n{}", error);
+            crate::error::StarshipError::from_format("gcloud", error).log();
             return None;
         }
     });
@@ -441,4 +489,125 @@ project = overridden
         assert_eq!(actual, expected);
         dir.close()
     }
+
+    #[test]
+    fn credentials_from_key_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let key_path = dir.path().join("key.json");
+        let mut key_file = File::create(&key_path)?;
+        key_file.write_all(
+            br#"{
+  "type": "service_account",
+  "project_id": "abc",
+  "client_email": "deployer@abc.iam.gserviceaccount.com"
+}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .env("GOOGLE_APPLICATION_CREDENTIALS", key_path.to_string_lossy())
+            .config(toml::toml! {
+                [gcloud]
+                format = "on [$symbol$credentials]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Blue.bold().paint("☁️  🔑 deployer")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn credentials_with_missing_key_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let active_config_path = dir.path().join("active_config");
+        let mut active_config_file = File::create(&active_config_path)?;
+        active_config_file.write_all(b"default")?;
+
+        create_dir(dir.path().join("configurations"))?;
+        File::create(dir.path().join("configurations").join("config_default"))?;
+
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .env(
+                "GOOGLE_APPLICATION_CREDENTIALS",
+                dir.path().join("missing.json").to_string_lossy(),
+            )
+            .config(toml::toml! {
+                [gcloud]
+                format = "on [$symbol$credentials]($style) "
+                service_account_symbol = "SA"
+            })
+            .collect();
+        let expected = Some(format!("on {} ", Color::Blue.bold().paint("☁️  SA")));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn credentials_only_without_gcloud_config() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let key_path = dir.path().join("key.json");
+        let mut key_file = File::create(&key_path)?;
+        key_file.write_all(br#"{"client_email": "deployer@abc.iam.gserviceaccount.com"}"#)?;
+
+        // The default format has nothing to show from the key file
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .env("GOOGLE_APPLICATION_CREDENTIALS", key_path.to_string_lossy())
+            .collect();
+        assert_eq!(actual, None);
+
+        // Neither has a key file that can't be read
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .env(
+                "GOOGLE_APPLICATION_CREDENTIALS",
+                dir.path().join("missing.json").to_string_lossy(),
+            )
+            .config(toml::toml! {
+                [gcloud]
+                format = "on [$symbol$credentials]($style) "
+            })
+            .collect();
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn credentials_not_set() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let active_config_path = dir.path().join("active_config");
+        let mut active_config_file = File::create(&active_config_path)?;
+        active_config_file.write_all(b"default")?;
+
+        create_dir(dir.path().join("configurations"))?;
+        let config_default_path = dir.path().join("configurations").join("config_default");
+        let mut config_default_file = File::create(&config_default_path)?;
+        config_default_file.write_all(
+            b"\
+[core]
+account = foo@example.com
+",
+        )?;
+
+        let actual = ModuleRenderer::new("gcloud")
+            .env("CLOUDSDK_CONFIG", dir.path().to_string_lossy())
+            .config(toml::toml! {
+                [gcloud]
+                format = "on [$symbol$account(@$domain)( $credentials)]($style) "
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Blue.bold().paint("☁️  foo@example.com")
+        ));
+
+        assert_eq!(actual, expected);
+        dir.close()
+    }
 }
@@ -1,9 +1,8 @@
 use super::{Context, Module, RootModuleConfig};
-use git2::Repository;
-use git2::Time;
 
 use crate::configs::git_commit::GitCommitConfig;
-use crate::formatter::StringFormatter;
+use crate::context::Repo;
+use crate::formatter::{StringFormatter, VariableHolder};
 
 /// Creates a module with the Git commit in the current directory
 ///
@@ -13,8 +12,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: GitCommitConfig = GitCommitConfig::try_load(module.config);
 
     let repo = context.get_repo().ok()?;
-    let repo_root = repo.root.as_ref()?;
-    let git_repo = Repository::open(repo_root).ok()?;
+    // Bare repos have no work tree, but their HEAD still has a commit
+    repo.path.as_ref()?;
+    let git_repo = repo.open()?;
 
     let is_detached = git_repo.head_detached().ok()?;
     if config.only_detached && !is_detached {
@@ -25,9 +25,22 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let head_commit = git_head.peel_to_commit().ok()?;
     let commit_oid = head_commit.id();
 
-    let mut parsed;
+    let tag_name = if config.tag_disabled {
+        None
+    } else {
+        Repo::commit_tag(&git_repo, commit_oid, config.tag_annotated_only)
+    };
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        // `git describe` walks the history, so it's only run when its variables are used
+        let variables = formatter.get_variables();
+        let description =
+            if variables.contains("closest_tag") || variables.contains("describe_distance") {
+                describe(context, repo, module.env())
+            } else {
+                None
+            };
 
-    parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
                 "style" => Some(Ok(config.style)),
@@ -38,65 +51,22 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     commit_oid.as_bytes(),
                     config.commit_hash_length,
                 ))),
+                "tag" => tag_name
+                    .as_ref()
+                    .map(|name| Ok(format!("{}{}", config.tag_symbol, name))),
+                "closest_tag" => description.as_ref().map(|(tag, _)| Ok(tag.clone())),
+                "describe_distance" => description
+                    .as_ref()
+                    .map(|(_, distance)| Ok(distance.to_string())),
                 _ => None,
             })
             .parse(None)
     });
 
-    if !config.tag_disabled {
-        // Let's get repo tags names
-        let tag_names = git_repo.tag_names(None).ok()?;
-        let tag_and_refs = tag_names.iter().flatten().flat_map(|name| {
-            let full_tag = format!("refs/tags/{}", name);
-            let tag_obj = git_repo.find_reference(&full_tag)?.peel_to_tag()?;
-            let sig_obj = tag_obj.tagger();
-            git_repo.find_reference(&full_tag).map(|reference| {
-                (
-                    String::from(name),
-                    // fall back to oldest + 1s time if sig_obj is unavailable
-                    sig_obj.map_or(git2::Time::new(1, 0), |s| s.when()),
-                    reference,
-                )
-            })
-        });
-
-        let mut tag_name = String::new();
-        let mut oldest = Time::new(0, 0);
-        // Let's check if HEAD has some tag. If several, gets last created one...
-        for (name, timestamp, reference) in tag_and_refs.rev() {
-            if commit_oid == reference.peel_to_commit().ok()?.id() && timestamp > oldest {
-                tag_name = name;
-                oldest = timestamp;
-            }
-        }
-        // If we have tag...
-        if !tag_name.is_empty() {
-            parsed = StringFormatter::new(config.format).and_then(|formatter| {
-                formatter
-                    .map_style(|variable| match variable {
-                        "style" => Some(Ok(config.style)),
-                        _ => None,
-                    })
-                    .map(|variable| match variable {
-                        "hash" => Some(Ok(id_to_hex_abbrev(
-                            commit_oid.as_bytes(),
-                            config.commit_hash_length,
-                        ))),
-                        _ => None,
-                    })
-                    .map(|variable| match variable {
-                        "tag" => Some(Ok(format!("{}{}", &config.tag_symbol, &tag_name))),
-                        _ => None,
-                    })
-                    .parse(None)
-            });
-        }
-    };
-
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `git_commit`:\n{}", error);
+            crate::error::StarshipError::from_format("git_commit", error).log();
             return None;
         }
     });
@@ -104,6 +74,38 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// The closest tag reachable from HEAD and the number of commits since it, from
+/// `git describe`. `None` when HEAD is tagged, so the variables only show between releases,
+/// and in repos without tags, where `git describe` fails.
+fn describe<'a>(
+    context: &Context,
+    repo: &'a Repo,
+    mut env: Vec<(&'a str, &'a str)>,
+) -> Option<(String, usize)> {
+    let dir = repo.root.as_deref().or(repo.path.as_deref())?.to_str()?;
+    env.extend(repo.git_env());
+    let output = context.exec_cmd("git", &["-C", dir, "describe", "--tags", "--long"], &env)?;
+
+    match parse_describe(&output.stdout) {
+        Some((_, 0)) => None,
+        Some(description) => Some(description),
+        None => {
+            log::debug!("Unexpected output of `git describe`: {:?}", output.stdout);
+            None
+        }
+    }
+}
+
+/// Splits the `<tag>-<distance>-g<hash>` output of `git describe --long`, where the tag may
+/// itself contain dashes
+fn parse_describe(output: &str) -> Option<(String, usize)> {
+    let mut parts = output.trim().rsplitn(3, '-');
+    parts.next().filter(|hash| hash.starts_with('g'))?;
+    let distance = parts.next()?.parse().ok()?;
+    let tag = parts.next().filter(|tag| !tag.is_empty())?;
+    Some((tag.to_string(), distance))
+}
+
 /// len specifies length of hex encoded string
 pub fn id_to_hex_abbrev(bytes: &[u8], len: usize) -> String {
     bytes
@@ -122,6 +124,7 @@ mod tests {
     use std::process::Command;
     use std::{io, str};
 
+    use super::parse_describe;
     use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
 
     #[test]
@@ -138,6 +141,35 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn test_render_commit_hash_in_bare_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::BareGit)?;
+
+        let mut git_output = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(repo_dir.path())
+            .output()?
+            .stdout;
+        git_output.truncate(7);
+        let expected_hash = str::from_utf8(&git_output).unwrap();
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "{} ",
+            Color::Green.bold().paint(format!("({})", expected_hash))
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn test_render_commit_hash() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -416,4 +448,176 @@ mod tests {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    #[test]
+    fn test_lightweight_and_annotated_tag_on_same_commit() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let mut git_commit = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(repo_dir.path())
+            .output()?
+            .stdout;
+        git_commit.truncate(7);
+        let commit_output = str::from_utf8(&git_commit).unwrap().trim();
+
+        // Tagged before the commit was made, so the lightweight tag is the newest one
+        Command::new("git")
+            .args(["tag", "annotated", "-m", "Testing annotated tags"])
+            .env("GIT_COMMITTER_DATE", "2000-01-01T00:00:00+0000")
+            .current_dir(repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(["pack-refs", "--all"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(["tag", "lightweight"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let render = |annotated_only: bool| {
+            ModuleRenderer::new("git_commit")
+                .config(toml::toml! {
+                    [git_commit]
+                        only_detached = false
+                        tag_disabled = false
+                        tag_annotated_only = annotated_only
+                        tag_symbol = " "
+                })
+                .path(repo_dir.path())
+                .collect()
+        };
+        let expected = |tag: &str| {
+            Some(format!(
+                "{} ",
+                Color::Green
+                    .bold()
+                    .paint(format!("({} {})", commit_output, tag))
+            ))
+        };
+
+        assert_eq!(expected("lightweight"), render(false));
+        assert_eq!(expected("annotated"), render(true));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_loose_tag_overrides_packed_tag() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let mut git_commit = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(repo_dir.path())
+            .output()?
+            .stdout;
+        git_commit.truncate(7);
+        let commit_output = str::from_utf8(&git_commit).unwrap().trim();
+
+        // The packed `moved` points to the parent commit, the loose one written after to HEAD
+        for args in [
+            &["tag", "moved", "-m", "Packed", "HEAD~1"][..],
+            &["pack-refs", "--all"],
+            &["tag", "-f", "moved", "-m", "Loose"],
+        ] {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_dir.path())
+                .output()?;
+        }
+        assert!(repo_dir.path().join(".git/refs/tags/moved").exists());
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    tag_disabled = false
+                    tag_symbol = " "
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Green
+                .bold()
+                .paint(format!("({} moved)", commit_output))
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    fn render_description(repo_dir: &std::path::Path) -> Option<String> {
+        ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    format = "($closest_tag-$describe_distance)"
+            })
+            .path(repo_dir)
+            .collect()
+    }
+
+    #[test]
+    fn describe_distance_since_closest_tag() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        Command::new("git")
+            .args(["commit", "--allow-empty", "-m", "Third", "--no-gpg-sign"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(["tag", "v1", "@~2"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        assert_eq!(
+            Some("v1-2".to_string()),
+            render_description(repo_dir.path())
+        );
+        repo_dir.close()
+    }
+
+    #[test]
+    fn describe_distance_hidden_on_tagged_commit() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        Command::new("git")
+            .args(["commit", "--allow-empty", "-m", "Third", "--no-gpg-sign"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(["tag", "v1", "@~2"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(["tag", "v2"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        assert_eq!(None, render_description(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn describe_distance_hidden_without_tags() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        assert_eq!(None, render_description(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn parse_describe_output() {
+        assert_eq!(
+            Some(("v1".to_string(), 2)),
+            parse_describe("v1-2-g35dc38f\n")
+        );
+        assert_eq!(
+            Some(("release-1.0-rc1".to_string(), 14)),
+            parse_describe("release-1.0-rc1-14-gb703eb3")
+        );
+        assert_eq!(None, parse_describe("35dc38f"));
+        assert_eq!(None, parse_describe(""));
+    }
 }
@@ -1,8 +1,9 @@
-use git2::RepositoryState;
+use git2::{Oid, RepositoryState};
 use std::path::{Path, PathBuf};
 
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::git_state::GitStateConfig;
+use crate::context::Repo;
 use crate::formatter::StringFormatter;
 
 /// Creates a module with the state of the git repository at the current directory
@@ -14,10 +15,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let config: GitStateConfig = GitStateConfig::try_load(module.config);
 
     let repo = context.get_repo().ok()?;
-    let repo_root = repo.root.as_ref()?;
+    if repo.is_bare {
+        return None;
+    }
+    repo.root.as_ref()?;
+    let git_dir = repo.path.as_ref()?;
     let repo_state = repo.state?;
 
-    let state_description = get_state_description(repo_state, repo_root, &config)?;
+    let state_description = get_state_description(repo_state, repo, git_dir, &config)?;
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -40,7 +45,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `git_state`:\n{}", error);
+            crate::error::StarshipError::from_format("git_state", error).log();
             return None;
         }
     });
@@ -53,7 +58,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 /// During a git operation it will show: REBASING, BISECTING, MERGING, etc.
 fn get_state_description<'a>(
     state: RepositoryState,
-    root: &'a Path,
+    repo: &Repo,
+    git_dir: &'a Path,
     config: &GitStateConfig<'a>,
 ) -> Option<StateDescription<'a>> {
     match state {
@@ -63,48 +69,29 @@ fn get_state_description<'a>(
             current: None,
             total: None,
         }),
-        RepositoryState::Revert => Some(StateDescription {
-            label: config.revert,
-            current: None,
-            total: None,
-        }),
-        RepositoryState::RevertSequence => Some(StateDescription {
-            label: config.revert,
-            current: None,
-            total: None,
-        }),
-        RepositoryState::CherryPick => Some(StateDescription {
-            label: config.cherry_pick,
-            current: None,
-            total: None,
-        }),
-        RepositoryState::CherryPickSequence => Some(StateDescription {
-            label: config.cherry_pick,
-            current: None,
-            total: None,
-        }),
+        RepositoryState::Revert | RepositoryState::RevertSequence => {
+            Some(describe_sequencer(repo, git_dir, config.revert))
+        }
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+            Some(describe_sequencer(repo, git_dir, config.cherry_pick))
+        }
         RepositoryState::Bisect => Some(StateDescription {
             label: config.bisect,
             current: None,
             total: None,
         }),
-        RepositoryState::ApplyMailbox => Some(StateDescription {
-            label: config.am,
-            current: None,
-            total: None,
-        }),
-        RepositoryState::ApplyMailboxOrRebase => Some(StateDescription {
-            label: config.am_or_rebase,
-            current: None,
-            total: None,
-        }),
-        RepositoryState::Rebase => Some(describe_rebase(root, config.rebase)),
-        RepositoryState::RebaseInteractive => Some(describe_rebase(root, config.rebase)),
-        RepositoryState::RebaseMerge => Some(describe_rebase(root, config.rebase)),
+        RepositoryState::ApplyMailbox => Some(describe_rebase(git_dir, config.am)),
+        RepositoryState::ApplyMailboxOrRebase => {
+            Some(describe_rebase(git_dir, config.am_or_rebase))
+        }
+        RepositoryState::Rebase => Some(describe_rebase(git_dir, config.rebase)),
+        RepositoryState::RebaseInteractive => Some(describe_rebase(git_dir, config.rebase)),
+        RepositoryState::RebaseMerge => Some(describe_rebase(git_dir, config.rebase)),
     }
 }
 
-fn describe_rebase<'a>(root: &'a Path, rebase_config: &'a str) -> StateDescription<'a> {
+/// Describes a rebase or `git am` with `label`, along with its progress if git recorded it
+fn describe_rebase<'a>(git_dir: &'a Path, label: &'a str) -> StateDescription<'a> {
     /*
      *  Sadly, libgit2 seems to have some issues with reading the state of
      *  interactive rebases. So, instead, we'll poke a few of the .git files
@@ -113,26 +100,14 @@ fn describe_rebase<'a>(root: &'a Path, rebase_config: &'a str) -> StateDescripti
      *  The following is based heavily on: https://github.com/magicmonty/bash-git-prompt
      */
 
-    let dot_git = root.join(".git");
-    let dot_git = if let Ok(conf) = std::fs::read_to_string(&dot_git) {
-        let gitdir_re = regex::Regex::new(r"(?m)^gitdir: (.*)$").unwrap();
-        if let Some(caps) = gitdir_re.captures(&conf) {
-            root.join(caps.get(1).unwrap().as_str())
-        } else {
-            dot_git
-        }
-    } else {
-        dot_git
-    };
-
     let has_path = |relative_path: &str| {
-        let path = dot_git.join(PathBuf::from(relative_path));
+        let path = git_dir.join(PathBuf::from(relative_path));
         path.exists()
     };
 
     let file_to_usize = |relative_path: &str| {
-        let path = dot_git.join(PathBuf::from(relative_path));
-        let contents = crate::utils::read_file(path).ok()?;
+        let path = git_dir.join(PathBuf::from(relative_path));
+        let contents = crate::utils::read_file_with_retry(path).ok()?;
         let quantity = contents.trim().parse::<usize>().ok()?;
         Some(quantity)
     };
@@ -158,7 +133,47 @@ fn describe_rebase<'a>(root: &'a Path, rebase_config: &'a str) -> StateDescripti
     };
 
     StateDescription {
-        label: rebase_config,
+        label,
+        current,
+        total,
+    }
+}
+
+/// Describes a cherry-pick or revert with `label`, along with its progress when it goes over
+/// several commits
+///
+/// git keeps the commits left to pick (including the one it stopped at) in `sequencer/todo`
+/// and the HEAD the operation started from in `sequencer/head`, so the commits made since then
+/// are the ones already picked. Picking a single commit doesn't use the sequencer, and git
+/// replaces its files atomically, so they are read once without retrying.
+fn describe_sequencer<'a>(repo: &Repo, git_dir: &Path, label: &'a str) -> StateDescription<'a> {
+    let progress = || {
+        let todo = crate::utils::read_file(git_dir.join("sequencer/todo")).ok()?;
+        let remaining = todo
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .count();
+        if remaining == 0 {
+            return None;
+        }
+
+        let start = crate::utils::read_file(git_dir.join("sequencer/head")).ok()?;
+        let start = Oid::from_str(start.trim()).ok()?;
+        let repository = repo.open()?;
+        let head = repository.head().ok()?.target()?;
+        let (done, _) = repository.graph_ahead_behind(head, start).ok()?;
+
+        Some((done + 1, done + remaining))
+    };
+
+    let (current, total) = match progress() {
+        Some((current, total)) => (Some(current.to_string()), Some(total.to_string())),
+        None => (None, None),
+    };
+
+    StateDescription {
+        label,
         current,
         total,
     }
@@ -179,7 +194,7 @@ mod tests {
     use std::path::Path;
     use std::process::{Command, Stdio};
 
-    use crate::test::ModuleRenderer;
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
 
     #[test]
     fn show_nothing_on_empty_dir() -> io::Result<()> {
@@ -195,6 +210,21 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn show_nothing_in_bare_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::BareGit)?;
+        // A leftover merge marker, which git reports as a state even in a bare repo
+        let head = std::fs::read_to_string(repo_dir.path().join("refs/heads/master"))?;
+        std::fs::write(repo_dir.path().join("MERGE_HEAD"), head)?;
+
+        let actual = ModuleRenderer::new("git_state")
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_rebasing() -> io::Result<()> {
         let repo_dir = create_repo_with_conflict()?;
@@ -210,6 +240,57 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_rebase_progress() -> io::Result<()> {
+        let repo_dir = create_repo_with_conflict()?;
+        let path = repo_dir.path();
+
+        // Three commits to replay, of which only the second conflicts with master
+        run_git_cmd(&["checkout", "-b", "topic", "master~1"], Some(path), true)?;
+        commit_file(path, "first", "1")?;
+        commit_file(path, "the_file", "Version D")?;
+        commit_file(path, "third", "3")?;
+        run_git_cmd(&["rebase", "master"], Some(path), false)?;
+
+        let actual = ModuleRenderer::new("git_state").path(path).collect();
+
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("REBASING 2/3")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_am_progress() -> io::Result<()> {
+        let repo_dir = fake_git_dir(&["rebase-apply/applying"])?;
+        let apply_dir = repo_dir.path().join(".git/rebase-apply");
+        std::fs::write(apply_dir.join("next"), "2\n")?;
+        std::fs::write(apply_dir.join("last"), "5\n")?;
+
+        let actual = ModuleRenderer::new("git_state")
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("AM 2/5")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn omits_unknown_progress() -> io::Result<()> {
+        let repo_dir = fake_git_dir(&["rebase-merge/interactive"])?;
+
+        let actual = ModuleRenderer::new("git_state")
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("REBASING")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_merging() -> io::Result<()> {
         let repo_dir = create_repo_with_conflict()?;
@@ -243,6 +324,75 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_cherry_pick_progress() -> io::Result<()> {
+        let repo_dir = create_repo_with_conflict()?;
+        let path = repo_dir.path();
+
+        // Both commits of the range conflict with master
+        run_git_cmd(
+            &["checkout", "-b", "topic", "other-branch"],
+            Some(path),
+            true,
+        )?;
+        std::fs::write(path.join("the_file"), "Version D")?;
+        run_git_cmd(
+            &["commit", "--all", "--message", "Commit D", "--no-gpg-sign"],
+            Some(path),
+            true,
+        )?;
+        run_git_cmd(&["checkout", "master"], Some(path), true)?;
+        run_git_cmd(&["cherry-pick", "master~1..topic"], Some(path), false)?;
+
+        let actual = ModuleRenderer::new("git_state").path(path).collect();
+        let expected = Some(format!(
+            "({}) ",
+            Color::Yellow.bold().paint("CHERRY-PICKING 1/2")
+        ));
+        assert_eq!(expected, actual);
+
+        // Resolve the first pick so that the second one conflicts as well
+        std::fs::write(path.join("the_file"), "Version E")?;
+        run_git_cmd(&["add", "the_file"], Some(path), true)?;
+        run_git_cmd(
+            &["-c", "core.editor=true", "cherry-pick", "--continue"],
+            Some(path),
+            false,
+        )?;
+
+        let actual = ModuleRenderer::new("git_state").path(path).collect();
+        let expected = Some(format!(
+            "({}) ",
+            Color::Yellow.bold().paint("CHERRY-PICKING 2/2")
+        ));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_revert_progress() -> io::Result<()> {
+        let repo_dir = create_repo_with_conflict()?;
+        let path = repo_dir.path();
+
+        commit_file(path, "other_file", "X")?;
+        commit_file(path, "the_file", "Version E")?;
+
+        // Reverts the commit adding other_file first, then Commit C, which conflicts with E
+        run_git_cmd(
+            &["revert", "--no-edit", "HEAD~3..HEAD~1"],
+            Some(path),
+            false,
+        )?;
+
+        let actual = ModuleRenderer::new("git_state").path(path).collect();
+        let expected = Some(format!(
+            "({}) ",
+            Color::Yellow.bold().paint("REVERTING 2/2")
+        ));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_bisecting() -> io::Result<()> {
         let repo_dir = create_repo_with_conflict()?;
@@ -273,6 +423,88 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn cherry_pick_head_marker_shows_cherry_picking() -> io::Result<()> {
+        let repo_dir = fake_git_dir(&["CHERRY_PICK_HEAD"])?;
+
+        let actual = ModuleRenderer::new("git_state")
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!(
+            "({}) ",
+            Color::Yellow.bold().paint("CHERRY-PICKING")
+        ));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn revert_head_marker_shows_reverting() -> io::Result<()> {
+        let repo_dir = fake_git_dir(&["REVERT_HEAD"])?;
+
+        let actual = ModuleRenderer::new("git_state")
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("REVERTING")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn merge_head_marker_wins_over_cherry_pick_head() -> io::Result<()> {
+        let repo_dir = fake_git_dir(&["MERGE_HEAD", "CHERRY_PICK_HEAD"])?;
+
+        let actual = ModuleRenderer::new("git_state")
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("MERGING")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn rebase_dir_wins_over_cherry_pick_and_revert_markers() -> io::Result<()> {
+        // An interactive rebase stopped on a conflicting pick leaves CHERRY_PICK_HEAD behind
+        let repo_dir = fake_git_dir(&[
+            "rebase-merge/interactive",
+            "CHERRY_PICK_HEAD",
+            "REVERT_HEAD",
+        ])?;
+        let rebase_dir = repo_dir.path().join(".git/rebase-merge");
+        std::fs::write(rebase_dir.join("msgnum"), "2\n")?;
+        std::fs::write(rebase_dir.join("end"), "3\n")?;
+
+        let actual = ModuleRenderer::new("git_state")
+            .path(repo_dir.path())
+            .collect();
+
+        let expected = Some(format!("({}) ", Color::Yellow.bold().paint("REBASING 2/3")));
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    /// Creates an empty repository and touches the given files inside its git dir
+    fn fake_git_dir(markers: &[&str]) -> io::Result<tempfile::TempDir> {
+        let repo_dir = tempfile::tempdir()?;
+        let git_dir = repo_dir.path().join(".git");
+        run_git_cmd(&["init", "--quiet"], Some(repo_dir.path()), true)?;
+
+        for marker in markers {
+            let marker = git_dir.join(marker);
+            std::fs::create_dir_all(marker.parent().unwrap())?;
+            std::fs::File::create(marker)?;
+        }
+
+        Ok(repo_dir)
+    }
+
     fn run_git_cmd<A, S>(args: A, dir: Option<&Path>, should_succeed: bool) -> io::Result<()>
     where
         A: IntoIterator<Item = S>,
@@ -298,6 +530,17 @@ mod tests {
         }
     }
 
+    /// Commits `text` as the contents of `file`, with the name of the file as message
+    fn commit_file(path: &Path, file: &str, text: &str) -> io::Result<()> {
+        std::fs::write(path.join(file), text)?;
+        run_git_cmd(&["add", file], Some(path), true)?;
+        run_git_cmd(
+            &["commit", "--message", file, "--no-gpg-sign"],
+            Some(path),
+            true,
+        )
+    }
+
     fn create_repo_with_conflict() -> io::Result<tempfile::TempDir> {
         let repo_dir = tempfile::tempdir()?;
         let path = repo_dir.path();
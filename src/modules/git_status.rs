@@ -4,13 +4,22 @@ use regex::Regex;
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::git_status::GitStatusConfig;
-use crate::context::Repo;
-use crate::formatter::StringFormatter;
+use crate::context::{GitVersion, Repo};
+use crate::formatter::{StringFormatter, VariableHolder};
 use crate::segment::Segment;
-use std::path::Path;
+use crate::utils::{self, EnvOverlay};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-const ALL_STATUS_FORMAT: &str = "$conflicted$stashed$deleted$renamed$modified$staged$untracked";
+/// The first version of git with `git status --porcelain=2`
+const PORCELAIN_V2: GitVersion = GitVersion(2, 11, 0);
+/// The first version of git with `--no-optional-locks`
+const NO_OPTIONAL_LOCKS: GitVersion = GitVersion(2, 15, 0);
+/// The first version of git with `git status --no-renames` and `--find-renames`
+const STATUS_RENAME_FLAGS: GitVersion = GitVersion(2, 18, 0);
+
+const ALL_STATUS_FORMAT: &str =
+    "$conflicted$stashed$deleted$renamed$modified$staged$untracked$clean";
 
 /// Creates a module with the Git branch in the current directory
 ///
@@ -20,52 +29,61 @@ const ALL_STATUS_FORMAT: &str = "$conflicted$stashed$deleted$renamed$modified$st
 ///   - `⇡` – This branch is ahead of the branch being tracked
 ///   - `⇣` – This branch is behind of the branch being tracked
 ///   - `⇕` – This branch has diverged from the branch being tracked
+///   - `×` – The branch being tracked was deleted
 ///   - `?` — There are untracked files in the working directory
 ///   - `$` — A stash exists for the local repository
 ///   - `!` — There are file modifications in the working directory
 ///   - `+` — A new file has been added to the staging area
 ///   - `»` — A renamed file has been added to the staging area
 ///   - `✘` — A file's deletion has been added to the staging area
+///
+/// The `clean` symbol, which is empty by default, is shown when there are no changes at all.
+/// `$base_ahead` and `$base_behind` compare with `base_branch` instead of the upstream.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let repo = context.get_repo().ok()?;
-    let info = Arc::new(GitStatusInfo::load(context, repo));
-
+    // Bare repos have nothing to run `git status` on
+    if repo.is_bare {
+        return None;
+    }
+    repo.root.as_ref()?;
+    let git_version = context.git_version()?;
     let mut module = context.new_module("git_status");
     let config: GitStatusConfig = GitStatusConfig::try_load(module.config);
 
+    let mut env = module.env();
+    env.extend(repo.git_env());
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        let formatter = formatter.map_meta(|variable, _| match variable {
+            "all_status" => Some(ALL_STATUS_FORMAT),
+            _ => None,
+        });
+
+        // Comparing with the upstream is only needed for `$ahead_behind`
+        let info = Arc::new(GitStatusInfo::load(
+            context,
+            repo,
+            &config,
+            git_version,
+            env,
+            formatter.get_variables().contains("ahead_behind"),
+        ));
+
         formatter
-            .map_meta(|variable, _| match variable {
-                "all_status" => Some(ALL_STATUS_FORMAT),
-                _ => None,
-            })
             .map_style(|variable: &str| match variable {
                 "style" => Some(Ok(config.style)),
                 _ => None,
             })
+            .map(|variable| match variable {
+                "repo_alias" => repo.alias.clone().map(Ok),
+                _ => None,
+            })
             .map_variables_to_segments(|variable: &str| {
                 let info = Arc::clone(&info);
                 let segments = match variable {
                     "stashed" => info.get_stashed().and_then(|count| {
                         format_count(config.stashed, "git_status.stashed", count)
                     }),
-                    "ahead_behind" => info.get_ahead_behind().and_then(|(ahead, behind)| {
-                        if ahead > 0 && behind > 0 {
-                            format_text(config.diverged, "git_status.diverged", |variable| {
-                                match variable {
-                                    "ahead_count" => Some(ahead.to_string()),
-                                    "behind_count" => Some(behind.to_string()),
-                                    _ => None,
-                                }
-                            })
-                        } else if ahead > 0 && behind == 0 {
-                            format_count(config.ahead, "git_status.ahead", ahead)
-                        } else if behind > 0 && ahead == 0 {
-                            format_count(config.behind, "git_status.behind", behind)
-                        } else {
-                            None
-                        }
-                    }),
                     "conflicted" => info.get_conflicted().and_then(|count| {
                         format_count(config.conflicted, "git_status.conflicted", count)
                     }),
@@ -84,10 +102,30 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     "untracked" => info.get_untracked().and_then(|count| {
                         format_count(config.untracked, "git_status.untracked", count)
                     }),
+                    "clean" => info
+                        .is_clean()
+                        .filter(|clean| *clean && !config.clean.is_empty())
+                        .and_then(|_| format_text(config.clean, "git_status.clean", |_| None)),
+                    "base_ahead" => info.get_base_ahead_behind().and_then(|(ahead, _)| {
+                        format_count(config.base_ahead, "git_status.base_ahead", ahead)
+                    }),
+                    "base_behind" => info.get_base_ahead_behind().and_then(|(_, behind)| {
+                        format_count(config.base_behind, "git_status.base_behind", behind)
+                    }),
                     _ => None,
                 };
                 segments.map(Ok)
             })
+            // Comparing with the upstream may need another query, so it's only done if
+            // `$ahead_behind` is rendered
+            .map_lazy_segments(|variable| match variable {
+                "ahead_behind" => {
+                    let info = Arc::clone(&info);
+                    let config = &config;
+                    Some(move || format_ahead_behind(&info, config).map(Ok))
+                }
+                _ => None,
+            })
             .parse(None)
     });
 
@@ -100,7 +138,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             }
         }
         Err(error) => {
-            log::warn!("Error in module `git_status`:\n{}", error);
+            crate::error::StarshipError::from_format("git_status", error).log();
             return None;
         }
     });
@@ -108,32 +146,91 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// The `ahead`, `behind`, `diverged` or `gone` segments of `$ahead_behind`
+fn format_ahead_behind(info: &GitStatusInfo, config: &GitStatusConfig) -> Option<Vec<Segment>> {
+    let (ahead, behind) = info.get_ahead_behind()?;
+    if info.is_upstream_gone() == Some(true) {
+        format_text(config.gone, "git_status.gone", |_| None)
+    } else if ahead > 0 && behind > 0 {
+        format_text(
+            config.diverged,
+            "git_status.diverged",
+            |variable| match variable {
+                "ahead_count" => Some(ahead.to_string()),
+                "behind_count" => Some(behind.to_string()),
+                _ => None,
+            },
+        )
+    } else if ahead > 0 && behind == 0 {
+        format_count(config.ahead, "git_status.ahead", ahead)
+    } else if behind > 0 && ahead == 0 {
+        format_count(config.behind, "git_status.behind", behind)
+    } else {
+        None
+    }
+}
+
 struct GitStatusInfo<'a> {
     context: &'a Context<'a>,
     repo: &'a Repo,
+    config: &'a GitStatusConfig<'a>,
+    git_version: GitVersion,
+    env: Vec<(&'a str, &'a str)>,
+    ahead_behind: bool,
     repo_status: OnceCell<Option<RepoStatus>>,
     stashed_count: OnceCell<Option<usize>>,
+    base_ahead_behind: OnceCell<Option<(usize, usize)>>,
 }
 
 impl<'a> GitStatusInfo<'a> {
-    pub fn load(context: &'a Context, repo: &'a Repo) -> Self {
+    pub fn load(
+        context: &'a Context,
+        repo: &'a Repo,
+        config: &'a GitStatusConfig,
+        git_version: GitVersion,
+        env: Vec<(&'a str, &'a str)>,
+        ahead_behind: bool,
+    ) -> Self {
         Self {
             context,
             repo,
+            config,
+            git_version,
+            env,
+            ahead_behind,
             repo_status: OnceCell::new(),
             stashed_count: OnceCell::new(),
+            base_ahead_behind: OnceCell::new(),
         }
     }
 
+    /// The counts of the branch header of `git status`, or of `git rev-list` if the header
+    /// had none even though there is an upstream
     pub fn get_ahead_behind(&self) -> Option<(usize, usize)> {
-        self.get_repo_status().map(|data| (data.ahead, data.behind))
+        let status = self.get_repo_status().as_ref()?;
+        if status.has_ahead_behind || status.upstream_gone {
+            return Some((status.ahead, status.behind));
+        }
+
+        Some(
+            self.repo
+                .ahead_behind(self.context, &self.env)
+                .unwrap_or((0, 0)),
+        )
     }
 
     pub fn get_repo_status(&self) -> &Option<RepoStatus> {
         self.repo_status.get_or_init(|| {
             let repo_root = self.repo.root.as_ref()?;
 
-            match get_repo_status(self.context, repo_root) {
+            match get_repo_status(
+                self.context,
+                repo_root,
+                self.config,
+                self.git_version,
+                self.ahead_behind,
+                &self.env,
+            ) {
                 Some(repo_status) => Some(repo_status),
                 None => {
                     log::debug!("get_repo_status: git status execution failed");
@@ -144,16 +241,18 @@ impl<'a> GitStatusInfo<'a> {
     }
 
     pub fn get_stashed(&self) -> &Option<usize> {
-        self.stashed_count.get_or_init(|| {
-            let repo_root = self.repo.root.as_ref()?;
+        self.stashed_count
+            .get_or_init(|| Some(get_stashed_count(self.repo.path.as_ref()?)))
+    }
 
-            match get_stashed_count(self.context, repo_root) {
-                Some(stashed_count) => Some(stashed_count),
-                None => {
-                    log::debug!("get_stashed_count: git stash execution failed");
-                    None
-                }
+    pub fn get_base_ahead_behind(&self) -> Option<(usize, usize)> {
+        *self.base_ahead_behind.get_or_init(|| {
+            let repo_root = self.repo.root.as_ref()?;
+            if self.config.base_branch.is_empty() {
+                return None;
             }
+
+            get_base_ahead_behind(self.context, repo_root, self.config.base_branch, &self.env)
         })
     }
 
@@ -180,56 +279,217 @@ impl<'a> GitStatusInfo<'a> {
     pub fn get_untracked(&self) -> Option<usize> {
         self.get_repo_status().map(|data| data.untracked)
     }
+
+    pub fn is_clean(&self) -> Option<bool> {
+        self.get_repo_status().as_ref().map(RepoStatus::is_clean)
+    }
+
+    /// Whether the branch tracks an upstream branch that no longer exists, e.g. because it was
+    /// deleted after its pull request was merged. Branches without an upstream aren't.
+    pub fn is_upstream_gone(&self) -> Option<bool> {
+        self.get_repo_status()
+            .as_ref()
+            .map(|status| status.upstream_gone)
+    }
 }
 
-/// Gets the number of files in various git states (staged, modified, deleted, etc...)
-fn get_repo_status(context: &Context, repo_root: &Path) -> Option<RepoStatus> {
+/// Gets the number of files in various git states (staged, modified, deleted, etc...),
+/// only counting the files matching `pathspec` unless it is empty.
+/// Flags the installed `git_version` doesn't know about are left out, and the
+/// branch header is only requested when `ahead_behind` counts are needed.
+fn get_repo_status(
+    context: &Context,
+    repo_root: &Path,
+    config: &GitStatusConfig,
+    git_version: GitVersion,
+    ahead_behind: bool,
+    env: &EnvOverlay,
+) -> Option<RepoStatus> {
     log::debug!("New repo status created");
 
+    let repo_root = repo_root.to_string_lossy();
+    let pathspec = &config.pathspec;
+    let porcelain_v2 = git_version >= PORCELAIN_V2;
+    let mut args = vec!["-C", &repo_root];
+    if !config.take_optional_locks && git_version >= NO_OPTIONAL_LOCKS {
+        args.push("--no-optional-locks");
+    }
+    let rename_flag = match config.rename_detection {
+        "off" => Some("--no-renames"),
+        // `git status` has no flag for copy detection, only the config option
+        "copies" => {
+            args.extend(&["-c", "status.renames=copies"]);
+            Some("--find-renames")
+        }
+        "renames" => None,
+        unknown => {
+            crate::log_once!(
+                "Unknown `rename_detection` value `{}` in module `git_status`, expected one of: off, renames, copies",
+                unknown
+            );
+            None
+        }
+    };
+    args.push("status");
+    args.push(if porcelain_v2 {
+        "--porcelain=2"
+    } else {
+        "--porcelain"
+    });
+    args.push("-z");
+    if ahead_behind {
+        args.push("--branch");
+    }
+    if let Some(rename_flag) = rename_flag.filter(|_| git_version >= STATUS_RENAME_FLAGS) {
+        args.push(rename_flag);
+    }
+    match config.untracked_files {
+        "" => (),
+        "normal" => args.push("--untracked-files=normal"),
+        "no" => args.push("--untracked-files=no"),
+        "all" => args.push("--untracked-files=all"),
+        unknown => crate::log_once!(
+            "Unknown `untracked_files` value `{}` in module `git_status`, expected one of: normal, no, all",
+            unknown
+        ),
+    }
+    if !pathspec.is_empty() {
+        args.push("--");
+        args.extend(pathspec);
+    }
+
+    let status_output = context.exec_cmd_with_status("git", &args, env)?;
+    if !status_output.success() {
+        if !pathspec.is_empty() {
+            log::warn!(
+                "Error in module `git_status` with pathspec {:?}:\n{}",
+                pathspec,
+                status_output.stderr.trim()
+            );
+        }
+        return None;
+    }
+
+    Some(parse_porcelain_output(
+        &status_output.stdout,
+        porcelain_v2,
+        true,
+    ))
+}
+
+/// Counts the files in each state from the output of `git status --porcelain` (v1), or of
+/// `--porcelain=2` if `porcelain_v2` is set.
+///
+/// With `nul_separated`, the output is the one of `git status -z`: entries end with a NUL
+/// instead of a newline, paths aren't quoted, and renamed or copied entries are followed by an
+/// extra entry with the original path.
+pub(crate) fn parse_porcelain_output(
+    output: &str,
+    porcelain_v2: bool,
+    nul_separated: bool,
+) -> RepoStatus {
     let mut repo_status = RepoStatus::default();
-    let status_output = context.exec_cmd(
-        "git",
-        &[
-            "-C",
-            &repo_root.to_string_lossy(),
-            "--no-optional-locks",
-            "status",
-            "--porcelain=2",
-            "--branch",
-        ],
-    )?;
-    let statuses = status_output.stdout.lines();
-
-    statuses.for_each(|status| {
-        if status.starts_with("# branch.ab ") {
+    let mut has_upstream = false;
+    let entries: Vec<&str> = if nul_separated {
+        output.split('\0').collect()
+    } else {
+        output.lines().collect()
+    };
+    let mut entries = entries.into_iter();
+
+    while let Some(status) = entries.next() {
+        let is_rename = if !porcelain_v2 {
+            repo_status.add_v1(status);
+            matches!(status.get(..2), Some(xy) if xy.contains(['R', 'C']))
+        } else if status.starts_with("# branch.ab ") {
             repo_status.set_ahead_behind(status);
+            false
+        } else if status.starts_with("# branch.upstream ") {
+            has_upstream = true;
+            false
         } else if !status.starts_with('#') {
             repo_status.add(status);
+            status.starts_with("2 ")
+        } else {
+            false
+        };
+
+        if nul_separated && is_rename {
+            // Skip the original path, which could look like an entry itself
+            entries.next();
         }
-    });
+    }
+
+    // git leaves out `branch.ab` when the upstream branch can't be found
+    if has_upstream && !repo_status.has_ahead_behind {
+        repo_status.upstream_gone = true;
+    }
+    repo_status
+}
 
-    Some(repo_status)
+/// Counts the stashes from the reflog of `refs/stash`, which is shared by all the worktrees of
+/// the repo, without running `git`. There are no stashes if it doesn't exist.
+fn get_stashed_count(git_dir: &Path) -> usize {
+    let stash_log = common_dir(git_dir).join("logs/refs/stash");
+    match utils::read_file(stash_log) {
+        Ok(log) => log.lines().filter(|line| !line.is_empty()).count(),
+        Err(_) => 0,
+    }
+}
+
+/// The directory with the refs and logs shared by all worktrees, for the git dir of a worktree
+fn common_dir(git_dir: &Path) -> PathBuf {
+    match utils::read_file(git_dir.join("commondir")) {
+        Ok(common_dir) => git_dir.join(common_dir.trim_end()),
+        Err(_) => git_dir.to_path_buf(),
+    }
 }
 
-fn get_stashed_count(context: &Context, repo_root: &Path) -> Option<usize> {
-    let stash_output = context.exec_cmd(
-        "git",
-        &[
-            "-C",
-            &repo_root.to_string_lossy(),
-            "--no-optional-locks",
-            "stash",
-            "list",
-        ],
-    )?;
-
-    Some(stash_output.stdout.trim().lines().count())
+/// Counts the commits of HEAD that aren't in `base_branch` and the other way around.
+/// A base that doesn't exist isn't an error: it's common for it to be missing in some repos.
+fn get_base_ahead_behind(
+    context: &Context,
+    repo_root: &Path,
+    base_branch: &str,
+    env: &EnvOverlay,
+) -> Option<(usize, usize)> {
+    let repo_root = repo_root.to_string_lossy();
+    let range = format!("{}...HEAD", base_branch);
+    let args = [
+        "-C",
+        &repo_root,
+        "rev-list",
+        "--left-right",
+        "--count",
+        &range,
+        "--",
+    ];
+
+    let output = context.exec_cmd_with_status("git", &args, env)?;
+    if !output.success() {
+        log::debug!(
+            "Unable to compare with base branch `{}`: {}",
+            base_branch,
+            output.stderr.trim()
+        );
+        return None;
+    }
+
+    let mut counts = output.stdout.split_whitespace().map(str::parse::<usize>);
+    match (counts.next(), counts.next()) {
+        (Some(Ok(behind)), Some(Ok(ahead))) => Some((ahead, behind)),
+        _ => None,
+    }
 }
 
 #[derive(Default, Debug, Copy, Clone)]
-struct RepoStatus {
+pub(crate) struct RepoStatus {
     ahead: usize,
     behind: usize,
+    /// Whether the branch header had the commits ahead and behind the upstream
+    has_ahead_behind: bool,
+    /// Whether the branch header had an upstream that doesn't exist
+    upstream_gone: bool,
     conflicted: usize,
     deleted: usize,
     renamed: usize,
@@ -239,52 +499,86 @@ struct RepoStatus {
 }
 
 impl RepoStatus {
-    fn is_conflicted(status: &str) -> bool {
-        status.starts_with("u ")
-    }
-
-    fn is_deleted(status: &str) -> bool {
-        // is_wt_deleted || is_index_deleted
-        status.starts_with("1 .D") || status.starts_with("1 D")
-    }
-
-    fn is_renamed(status: &str) -> bool {
-        // is_wt_renamed || is_index_renamed
-        // Potentially a copy and not a rename
-        status.starts_with("2 ")
-    }
-
-    fn is_modified(status: &str) -> bool {
-        // is_wt_modified
-        status.starts_with("1 .M") || status.starts_with("1 .A")
-    }
-
-    fn is_staged(status: &str) -> bool {
-        // is_index_modified || is_index_new
-        status.starts_with("1 M") || status.starts_with("1 A")
-    }
-
-    fn is_untracked(status: &str) -> bool {
-        // is_wt_new
-        status.starts_with("? ")
+    /// Whether there are no changes, regardless of the commits ahead or behind the upstream
+    fn is_clean(&self) -> bool {
+        self.conflicted == 0
+            && self.deleted == 0
+            && self.renamed == 0
+            && self.modified == 0
+            && self.staged == 0
+            && self.untracked == 0
     }
 
+    /// Adds an entry of `git status --porcelain=2`.
+    ///
+    /// Changed entries (`1` and `2`) have the status of the index and of the work tree, e.g.
+    /// `M.` for a staged modification or `MD` for a staged modification of a file deleted since.
+    /// Changes in the index count as staged, and deletions as deleted wherever they are. Only
+    /// unmerged entries (`u`) count as conflicted.
     fn add(&mut self, s: &str) {
-        self.conflicted += RepoStatus::is_conflicted(s) as usize;
-        self.deleted += RepoStatus::is_deleted(s) as usize;
-        self.renamed += RepoStatus::is_renamed(s) as usize;
-        self.modified += RepoStatus::is_modified(s) as usize;
-        self.staged += RepoStatus::is_staged(s) as usize;
-        self.untracked += RepoStatus::is_untracked(s) as usize;
+        let mut fields = s.splitn(3, ' ');
+        let kind = fields.next().unwrap_or_default();
+        let mut xy = fields.next().unwrap_or_default().chars();
+        let index = xy.next().unwrap_or('.');
+        let worktree = xy.next().unwrap_or('.');
+
+        match kind {
+            "1" | "2" => {
+                self.staged += (index != '.') as usize;
+                self.modified += matches!(worktree, 'M' | 'T' | 'A') as usize;
+                self.deleted += (index == 'D' || worktree == 'D') as usize;
+                // Potentially a copy and not a rename
+                self.renamed += (kind == "2") as usize;
+            }
+            "u" => self.conflicted += 1,
+            "?" => self.untracked += 1,
+            _ => (),
+        }
     }
 
     fn set_ahead_behind(&mut self, s: &str) {
         let re = Regex::new(r"branch\.ab \+([0-9]+) \-([0-9]+)").unwrap();
 
         if let Some(caps) = re.captures(s) {
-            self.ahead = caps.get(1).unwrap().as_str().parse::<usize>().unwrap();
-            self.behind = caps.get(2).unwrap().as_str().parse::<usize>().unwrap();
+            // The counts can't overflow for a real repo, but don't trust the output blindly
+            if let (Ok(ahead), Ok(behind)) = (caps[1].parse(), caps[2].parse()) {
+                self.ahead = ahead;
+                self.behind = behind;
+                self.has_ahead_behind = true;
+            }
+        }
+    }
+
+    /// Adds a line of `git status --porcelain` (v1) output, for versions of git
+    /// without `--porcelain=2`, by translating it to its v2 equivalent
+    fn add_v1(&mut self, s: &str) {
+        if let Some(branch) = s.strip_prefix("## ") {
+            // A branch in sync with its upstream has no counts, which means zero
+            self.has_ahead_behind = branch.contains("...");
+            self.upstream_gone = branch.ends_with(" [gone]");
+            let re = Regex::new(r"\[(?:ahead ([0-9]+))?(?:, )?(?:behind ([0-9]+))?\]$").unwrap();
+            if let Some(caps) = re.captures(branch) {
+                let count = |i| caps.get(i).map_or(0, |m| m.as_str().parse().unwrap_or(0));
+                self.ahead = count(1);
+                self.behind = count(2);
+            }
+            return;
         }
+
+        let xy = match s.get(..2) {
+            Some(xy) => xy,
+            None => return,
+        };
+        let status = match xy {
+            "??" => "? ".to_string(),
+            "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU" => "u ".to_string(),
+            // Renames and copies in the index, or in the work tree for files added with
+            // `git add --intent-to-add`. The paths after the status are never looked at, so an
+            // `old -> new` pair can contain anything, even quoted arrows.
+            _ if xy.contains(['R', 'C']) => format!("2 {}", xy.replace(' ', ".")),
+            _ => format!("1 {}", xy.replace(' ', ".")),
+        };
+        self.add(&status);
     }
 }
 
@@ -298,7 +592,7 @@ where
             .parse(None)
             .ok()
     } else {
-        log::warn!("Error parsing format string `{}`", &config_path);
+        crate::log_once!("Error parsing format string `{}`", &config_path);
         None
     }
 }
@@ -316,13 +610,17 @@ fn format_count(format_str: &str, config_path: &str, count: usize) -> Option<Vec
 
 #[cfg(test)]
 mod tests {
-    use ansi_term::{ANSIStrings, Color};
+    use ansi_term::Color;
     use std::fs::{self, File};
     use std::io;
     use std::path::Path;
     use std::process::Command;
 
+    use super::GitStatusInfo;
+    use crate::configs::git_status::GitStatusConfig;
+    use crate::context::{Context, GitVersion, Shell};
     use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use crate::utils::CommandOutput;
 
     /// Right after the calls to git the filesystem state may not have finished
     /// updating yet causing some of the tests to fail. These barriers are placed
@@ -357,6 +655,91 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_nothing_in_bare_repo() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::BareGit)?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "[status]($style)"
+            })
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_nothing_when_clean_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_clean() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                clean = "✓"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("✓");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_clean_and_ahead() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        ahead(repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                clean = "✓"
+                ahead = "⇡$count"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("✓⇡1");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn hides_clean_with_changes() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_untracked(repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                clean = "✓"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("?");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_behind() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -391,6 +774,50 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_gone_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        // What `git fetch --prune` does once the branch was deleted on the remote
+        Command::new("git")
+            .args(["update-ref", "-d", "refs/remotes/origin/master"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("×");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn no_gone_symbol_without_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        Command::new("git")
+            .args(["checkout", "-b", "local"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                gone = "gone"
+                clean = "clean"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("clean");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_ahead() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -461,6 +888,60 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_ahead_behind_base() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        stacked_branch(repo_dir.path())?;
+
+        let (actual, _) = render_base(repo_dir.path(), "origin/master");
+        assert_eq!(Some(String::from("⇡2⇣1")), actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_nothing_when_even_with_base() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let (actual, _) = render_base(repo_dir.path(), "origin/master");
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_nothing_for_missing_base() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        stacked_branch(repo_dir.path())?;
+
+        let (actual, _) = render_base(repo_dir.path(), "origin/main");
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn base_not_compared_when_unset() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        stacked_branch(repo_dir.path())?;
+
+        let (actual, commands) = render_base(repo_dir.path(), "");
+        assert_eq!(None, actual);
+        assert!(commands.iter().all(|command| !command.contains("rev-list")));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn base_compared_once() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        stacked_branch(repo_dir.path())?;
+
+        let (_, commands) = render_base(repo_dir.path(), "origin/master");
+        let rev_lists = commands
+            .iter()
+            .filter(|command| command.contains("rev-list"))
+            .count();
+        assert_eq!(1, rev_lists);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_conflicted() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -529,6 +1010,58 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn untracked_files_setting() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_untracked(repo_dir.path())?;
+        fs::create_dir(repo_dir.path().join("new"))?;
+        File::create(repo_dir.path().join("new/a"))?.sync_all()?;
+        File::create(repo_dir.path().join("new/b"))?.sync_all()?;
+
+        let render = |untracked_files: &str| {
+            ModuleRenderer::new("git_status")
+                .config(toml::toml! {
+                    [git_status]
+                    untracked = "?$count"
+                    untracked_files = untracked_files
+                })
+                .path(repo_dir.path())
+                .collect()
+        };
+
+        assert_eq!(format_output("?2"), render(""));
+        assert_eq!(format_output("?2"), render("normal"));
+        assert_eq!(format_output("?3"), render("all"));
+        assert_eq!(None, render("no"));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn untracked_files_setting_overrides_git_config() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_untracked(repo_dir.path())?;
+
+        Command::new("git")
+            .args(["config", "status.showUntrackedFiles", "no"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                untracked_files = "normal"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("?");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn doesnt_show_untracked_file_if_disabled() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -600,12 +1133,60 @@ mod tests {
     }
 
     #[test]
-    fn shows_modified() -> io::Result<()> {
+    fn shows_stashed_count_in_worktree() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let worktree_dir = tempfile::tempdir()?;
+        let worktree = worktree_dir.path().join("worktree");
+        barrier();
 
-        create_modified(&repo_dir.path())?;
-
-        let actual = ModuleRenderer::new("git_status")
+        create_stash(repo_dir.path())?;
+        File::create(repo_dir.path().join("license"))?.sync_all()?;
+        Command::new("git")
+            .args(["stash", "--include-untracked"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        Command::new("git")
+            .args(["worktree", "add", "-q", "-b", "other"])
+            .arg(&worktree)
+            .current_dir(repo_dir.path())
+            .output()?;
+        barrier();
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                stashed = r"\$$count"
+            })
+            .path(&worktree)
+            .collect();
+        let expected = format_output("$2");
+
+        assert_eq!(expected, actual);
+        worktree_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn stashed_count_without_stash_log() -> io::Result<()> {
+        let git_dir = tempfile::tempdir()?;
+        assert_eq!(super::get_stashed_count(git_dir.path()), 0);
+
+        fs::create_dir_all(git_dir.path().join("logs/refs"))?;
+        fs::write(
+            git_dir.path().join("logs/refs/stash"),
+            "0000 1111 a <a> 0 +0000\tWIP on master\n",
+        )?;
+        assert_eq!(super::get_stashed_count(git_dir.path()), 1);
+        git_dir.close()
+    }
+
+    #[test]
+    fn shows_modified() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_modified(&repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
             .path(&repo_dir.path())
             .collect();
         let expected = format_output("!");
@@ -614,6 +1195,73 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_same_counts_from_subdirectory() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let subdir = repo_dir.path().join("src").join("deep").join("dir");
+        fs::create_dir_all(&subdir)?;
+
+        create_modified(repo_dir.path())?;
+        create_untracked(repo_dir.path())?;
+
+        let render = |path: &Path| {
+            ModuleRenderer::new("git_status")
+                .config(toml::toml! {
+                    [git_status]
+                    modified = "!$count"
+                    untracked = "?$count"
+                })
+                .path(path)
+                .collect()
+        };
+        let expected = format_output("!1?1");
+
+        assert_eq!(expected, render(repo_dir.path()));
+        assert_eq!(expected, render(&subdir));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_modified_in_extra_repository() -> io::Result<()> {
+        let home = fixture_repo(FixtureProvider::Dotfiles)?;
+
+        create_modified(home.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git]
+                    extra_repositories = [
+                        { git_dir = "~/.dotfiles", work_tree = "~", alias = "dotfiles" },
+                    ]
+            })
+            .env("HOME", home.path().to_string_lossy())
+            .path(home.path())
+            .collect();
+        let expected = format_output("!");
+
+        assert_eq!(expected, actual);
+        home.close()
+    }
+
+    #[test]
+    fn shows_modified_with_git_dir_env() -> io::Result<()> {
+        let home = fixture_repo(FixtureProvider::Dotfiles)?;
+        let other_dir = tempfile::tempdir()?;
+
+        create_modified(home.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .env("GIT_DIR", home.path().join(".dotfiles").to_string_lossy())
+            .env("GIT_WORK_TREE", home.path().to_string_lossy())
+            .path(other_dir.path())
+            .collect();
+        let expected = format_output("!");
+
+        assert_eq!(expected, actual);
+        other_dir.close()?;
+        home.close()
+    }
+
     #[test]
     fn shows_modified_with_count() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -677,12 +1325,10 @@ mod tests {
             .path(&repo_dir.path())
             .collect();
         let expected = Some(format!(
-            "{} ",
-            ANSIStrings(&[
-                Color::Red.bold().paint("[+"),
-                Color::Green.paint("1"),
-                Color::Red.bold().paint("]"),
-            ])
+            "{}{}{} ",
+            Color::Red.bold().paint("[+"),
+            Color::Green.paint("1"),
+            Color::Red.bold().paint("]"),
         ));
 
         assert_eq!(expected, actual);
@@ -698,7 +1344,8 @@ mod tests {
         let actual = ModuleRenderer::new("git_status")
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("»");
+        // A rename is staged
+        let expected = format_output("»+");
 
         assert_eq!(expected, actual);
         repo_dir.close()
@@ -717,7 +1364,7 @@ mod tests {
             })
             .path(&repo_dir.path())
             .collect();
-        let expected = format_output("»1");
+        let expected = format_output("»1+");
 
         assert_eq!(expected, actual);
         repo_dir.close()
@@ -757,6 +1404,593 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn renders_while_index_is_locked() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::DirtyGit(1))?;
+
+        // Held by another git process, e.g. an interactive rebase
+        let lock = repo_dir.path().join(".git").join("index.lock");
+        File::create(&lock)?.sync_all()?;
+
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .collect_with_commands();
+        let expected = format_output("!⇡");
+
+        assert_eq!(expected, actual);
+        assert!(lock.exists());
+        assert!(commands
+            .iter()
+            .filter(|command| command.starts_with("git -C "))
+            .all(|command| command.contains(" --no-optional-locks ")));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn take_optional_locks() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::DirtyGit(1))?;
+
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                take_optional_locks = true
+            })
+            .path(repo_dir.path())
+            .collect_with_commands();
+        let expected = format_output("!⇡");
+
+        assert_eq!(expected, actual);
+        assert!(commands
+            .iter()
+            .all(|command| !command.contains("--no-optional-locks")));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn skipped_without_git() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::DirtyGit(1))?;
+
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .path(repo_dir.path())
+            .cmd("git --version", None)
+            .collect_with_commands();
+
+        assert_eq!(None, actual);
+        assert_eq!(commands, vec!["git --version"]);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn skips_upstream_without_ahead_behind() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::DirtyGit(1))?;
+
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "[$modified]($style) "
+            })
+            .path(repo_dir.path())
+            .collect_with_commands();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("!")));
+        let repo_root = repo_dir.path().join("").to_string_lossy().into_owned();
+
+        assert_eq!(expected, actual);
+        assert_eq!(
+            commands,
+            vec![
+                String::from("git --version"),
+                format!(
+                    "git -C {} --no-optional-locks status --porcelain=2 -z",
+                    repo_root
+                ),
+            ]
+        );
+        repo_dir.close()
+    }
+
+    #[test]
+    fn falls_back_for_old_git() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::DirtyGit(1))?;
+
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                rename_detection = "off"
+            })
+            .path(repo_dir.path())
+            .cmd(
+                "git --version",
+                Some(CommandOutput {
+                    stdout: String::from("git version 1.8.3\n"),
+                    stderr: String::default(),
+                    status: Some(0),
+                }),
+            )
+            .collect_with_commands();
+        let expected = format_output("!⇡");
+        let repo_root = repo_dir.path().join("").to_string_lossy().into_owned();
+
+        assert_eq!(expected, actual);
+        assert_eq!(
+            commands,
+            vec![
+                String::from("git --version"),
+                format!("git -C {} status --porcelain -z --branch", repo_root),
+            ]
+        );
+        repo_dir.close()
+    }
+
+    #[test]
+    fn parse_porcelain_v1() {
+        let mut status = super::RepoStatus::default();
+        for line in &[
+            "## master...origin/master [ahead 2, behind 1]",
+            " M modified",
+            "M  staged",
+            "MM staged_and_modified",
+            "A  added",
+            "AM added_and_modified",
+            " D deleted",
+            "D  staged_deletion",
+            "R  old -> new",
+            "UU conflicted",
+            "DD both_deleted",
+            "AA both_added",
+            "?? untracked",
+        ] {
+            status.add_v1(line);
+        }
+
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert_eq!(status.modified, 3);
+        assert_eq!(status.staged, 6);
+        assert_eq!(status.deleted, 2);
+        assert_eq!(status.renamed, 1);
+        // Only unmerged entries are conflicts, even with the same status in both columns
+        assert_eq!(status.conflicted, 3);
+        assert_eq!(status.untracked, 1);
+    }
+
+    #[test]
+    fn parse_porcelain_v1_renames_and_copies() {
+        let output = concat!(
+            "## master\n",
+            "R  old -> new\n",
+            "C  original -> copy\n",
+            "RM \"a -> b.txt\" -> \"c \\\" -> d.txt\"\n",
+            " R intent_to_add -> renamed\n",
+            "?? \"x -> y\"\n",
+        );
+        let status = super::parse_porcelain_output(output, false, false);
+
+        assert_eq!(status.renamed, 4);
+        assert_eq!(status.staged, 3);
+        assert_eq!(status.modified, 1);
+        assert_eq!(status.untracked, 1);
+        assert_eq!(status.deleted, 0);
+    }
+
+    #[test]
+    fn parse_porcelain_v1_nul_separated() {
+        let output =
+            "## master...origin/master [ahead 2]\0 M modified\0R  new\0?? old\0?? untracked\0";
+        let status = super::parse_porcelain_output(output, false, true);
+
+        assert_eq!((status.ahead, status.behind), (2, 0));
+        assert_eq!(status.modified, 1);
+        assert_eq!(status.renamed, 1);
+        // `?? old` is the original path of the rename, not an untracked file
+        assert_eq!(status.untracked, 1);
+    }
+
+    #[test]
+    fn parse_porcelain_v2_nul_separated() {
+        let output = concat!(
+            "# branch.oid 0000000000000000000000000000000000000000\0",
+            "# branch.ab +1 -3\0",
+            "1 .M N... 100644 100644 100644 0000000 0000000 with\nnewline\0",
+            "2 R. N... 100644 100644 100644 0000000 0000000 R100 new\0",
+            "? old\0",
+            "? untracked\0",
+        );
+        let status = super::parse_porcelain_output(output, true, true);
+
+        assert_eq!((status.ahead, status.behind), (1, 3));
+        assert_eq!(status.modified, 1);
+        assert_eq!(status.renamed, 1);
+        assert_eq!(status.untracked, 1);
+    }
+
+    #[test]
+    fn parse_porcelain_v2_tracked_branch() {
+        let output = concat!(
+            "# branch.oid 7c2e5fbb3b4ea12f05e9fa93d8ea2c7a86ba0e4e\n",
+            "# branch.head master\n",
+            "# branch.upstream origin/master\n",
+            "# branch.ab +2 -5\n",
+            "1 .M N... 100644 100644 100644 3b18e51 3b18e51 src/main.rs\n",
+            "1 M. N... 100644 100644 100644 8c7e5a6 9f1b2c3 Cargo.toml\n",
+            "1 A. N... 000000 100644 100644 0000000 e69de29 new.rs\n",
+            "1 D. N... 100644 000000 000000 e69de29 0000000 gone.rs\n",
+            "1 .D N... 100644 100644 000000 e69de29 e69de29 removed.rs\n",
+            "2 R. N... 100644 100644 100644 e69de29 e69de29 R100 renamed.rs\told.rs\n",
+            "u UU N... 100644 100644 100644 100644 1f2e3d4 5a6b7c8 9d0e1f2 conflict.rs\n",
+            "? untracked.rs\n",
+        );
+        let status = super::parse_porcelain_output(output, true, false);
+
+        assert_eq!((status.ahead, status.behind), (2, 5));
+        assert!(status.has_ahead_behind);
+        assert_eq!(status.modified, 1);
+        assert_eq!(status.staged, 4);
+        assert_eq!(status.deleted, 2);
+        assert_eq!(status.renamed, 1);
+        assert_eq!(status.conflicted, 1);
+        assert_eq!(status.untracked, 1);
+    }
+
+    #[test]
+    fn parse_porcelain_v2_without_upstream() {
+        let output = concat!(
+            "# branch.oid 7c2e5fbb3b4ea12f05e9fa93d8ea2c7a86ba0e4e\n",
+            "# branch.head topic\n",
+            "? untracked.rs\n",
+        );
+        let status = super::parse_porcelain_output(output, true, false);
+
+        assert_eq!((status.ahead, status.behind), (0, 0));
+        assert!(!status.upstream_gone);
+        assert_eq!(status.untracked, 1);
+    }
+
+    #[test]
+    fn parse_porcelain_v2_with_gone_upstream() {
+        // git leaves out `branch.ab` when the upstream branch no longer exists
+        let output = concat!(
+            "# branch.oid 7c2e5fbb3b4ea12f05e9fa93d8ea2c7a86ba0e4e\n",
+            "# branch.head topic\n",
+            "# branch.upstream origin/topic\n",
+            "1 .M N... 100644 100644 100644 3b18e51 3b18e51 src/main.rs\n",
+        );
+        let status = super::parse_porcelain_output(output, true, false);
+
+        assert_eq!((status.ahead, status.behind), (0, 0));
+        assert!(!status.has_ahead_behind);
+        assert!(status.upstream_gone);
+        assert_eq!(status.modified, 1);
+    }
+
+    #[test]
+    fn parse_porcelain_v1_with_gone_upstream() {
+        let status = super::parse_porcelain_output("## topic...origin/topic [gone]", false, false);
+        assert!(status.upstream_gone);
+
+        let status = super::parse_porcelain_output("## topic...origin/topic", false, false);
+        assert!(!status.upstream_gone);
+        let status = super::parse_porcelain_output("## topic", false, false);
+        assert!(!status.upstream_gone);
+    }
+
+    fn upstream_ahead_behind(repo_dir: &Path) -> Option<(usize, usize)> {
+        let context = Context::new_with_shell_and_path(
+            clap::ArgMatches::default(),
+            Shell::Unknown,
+            repo_dir.to_path_buf(),
+            repo_dir.to_path_buf(),
+        );
+        let ahead_behind = context.get_repo().ok()?.ahead_behind(&context, &[]);
+        ahead_behind
+    }
+
+    #[test]
+    fn upstream_ahead_behind_counts() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        assert_eq!(upstream_ahead_behind(repo_dir.path()), Some((0, 0)));
+        ahead(repo_dir.path())?;
+        assert_eq!(upstream_ahead_behind(repo_dir.path()), Some((1, 0)));
+        repo_dir.close()?;
+
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        behind(repo_dir.path())?;
+        assert_eq!(upstream_ahead_behind(repo_dir.path()), Some((0, 1)));
+        repo_dir.close()?;
+
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        diverge(repo_dir.path())?;
+        assert_eq!(upstream_ahead_behind(repo_dir.path()), Some((1, 1)));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn upstream_ahead_behind_without_upstream() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        Command::new("git")
+            .args(["checkout", "-b", "local"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        let context = Context::new_with_shell_and_path(
+            clap::ArgMatches::default(),
+            Shell::Unknown,
+            repo_dir.path().to_path_buf(),
+            repo_dir.path().to_path_buf(),
+        );
+        let repo = context.get_repo().unwrap();
+
+        assert_eq!(repo.ahead_behind(&context, &[]), None);
+        assert!(context.cmd_log.lock().unwrap().is_empty());
+        repo_dir.close()
+    }
+
+    #[test]
+    fn counts_with_rev_list_when_status_has_none() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        ahead(repo_dir.path())?;
+        let repo_root = repo_dir.path().join("");
+        let repo_root = repo_root.to_string_lossy();
+        let status_cmd = format!(
+            "git -C {} --no-optional-locks status --porcelain=2 -z --branch",
+            repo_root
+        );
+
+        // The branch header doesn't have `branch.ab`, as with some git versions and wrappers
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                ahead = "⇡$count"
+            })
+            .path(repo_dir.path())
+            .cmd(
+                &status_cmd,
+                Some(CommandOutput {
+                    stdout: String::from("# branch.oid 0000000000000000000000000000000000000000\0"),
+                    stderr: String::default(),
+                    status: Some(0),
+                }),
+            )
+            .collect_with_commands();
+
+        assert_eq!(format_output("⇡1"), actual);
+        assert!(commands.contains(&format!(
+            "git -C {} rev-list --left-right --count HEAD...@{{upstream}} --",
+            repo_root
+        )));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn parse_porcelain_ignores_overflowing_counts() {
+        let status =
+            super::parse_porcelain_output("# branch.ab +99999999999999999999999 -1", true, false);
+
+        assert_eq!((status.ahead, status.behind), (0, 0));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_porcelain_never_panics(output in r"\PC{0,200}", porcelain_v2: bool, nul_separated: bool) {
+            super::parse_porcelain_output(&output, porcelain_v2, nul_separated);
+        }
+
+        #[test]
+        fn prop_porcelain_counts_bounded_by_entries(
+            entries in proptest::collection::vec(
+                r"(1 [.MADU]{2} |2 [.RC]{2} |u UU |\? |[ MADRCU?]{2} |# branch\.ab \+[0-9]{1,25} -[0-9]{1,3})[a-z \n]{0,8}",
+                0..20,
+            ),
+            porcelain_v2: bool,
+            nul_separated: bool,
+        ) {
+            let (output, count) = if nul_separated {
+                let output = entries.join("\0");
+                let count = output.split('\0').count();
+                (output, count)
+            } else {
+                let output = entries.join("\n");
+                let count = output.lines().count();
+                (output, count)
+            };
+            let status = super::parse_porcelain_output(&output, porcelain_v2, nul_separated);
+            // An entry can be in several states, e.g. staged and modified, but only once in each
+            for total in [
+                status.conflicted,
+                status.deleted,
+                status.renamed,
+                status.modified,
+                status.staged,
+                status.untracked,
+            ] {
+                proptest::prop_assert!(total <= count, "{} files in {} entries", total, count);
+            }
+        }
+    }
+
+    #[test]
+    fn rename_detection_off() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_renamed(repo_dir.path())?;
+
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                rename_detection = "off"
+            })
+            .path(repo_dir.path())
+            .collect_with_commands();
+        let expected = format_output("✘+");
+
+        assert_eq!(expected, actual);
+        assert!(commands
+            .iter()
+            .any(|command| command.ends_with("status --porcelain=2 -z --branch --no-renames")));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn rename_detection_renames() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_renamed(repo_dir.path())?;
+
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                rename_detection = "renames"
+                renamed = "»$count"
+            })
+            .path(repo_dir.path())
+            .collect_with_commands();
+        let expected = format_output("»1+");
+
+        assert_eq!(expected, actual);
+        assert!(commands
+            .iter()
+            .any(|command| command.ends_with("status --porcelain=2 -z --branch")));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn rename_detection_copies() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_copied(repo_dir.path())?;
+
+        let (actual, commands) = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                rename_detection = "copies"
+                renamed = "»$count"
+            })
+            .path(repo_dir.path())
+            .collect_with_commands();
+        let expected = format_output("»1+");
+
+        assert_eq!(expected, actual);
+        assert!(commands.iter().any(|command| command
+            .contains("-c status.renames=copies status --porcelain=2 -z --branch --find-renames")));
+
+        // Without copy detection the copy is a new file
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                renamed = "»$count"
+                staged = "+$count"
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("+2");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_modified_inside_pathspec() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_modified(repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                pathspec = ["readme.md"]
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = format_output("!");
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn ignores_modified_outside_pathspec() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_modified(repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                pathspec = ["services/api/**"]
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_nothing_with_invalid_pathspec() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_modified(repo_dir.path())?;
+
+        let actual = ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                pathspec = [":(bogus)readme.md"]
+            })
+            .path(repo_dir.path())
+            .collect();
+        let expected = None;
+
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn pathspecs_are_cached_separately() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_modified(repo_dir.path())?;
+        fs::create_dir_all(repo_dir.path().join("services/api"))?;
+        File::create(repo_dir.path().join("services/api/main.rs"))?.sync_all()?;
+
+        let context = Context::new_with_shell_and_path(
+            clap::ArgMatches::default(),
+            Shell::Unknown,
+            repo_dir.path().into(),
+            repo_dir.path().into(),
+        );
+        let repo = context.get_repo()?;
+        let api_config = GitStatusConfig {
+            pathspec: vec!["services/api/**"],
+            ..GitStatusConfig::default()
+        };
+        let readme_config = GitStatusConfig {
+            pathspec: vec!["readme.md"],
+            ..GitStatusConfig::default()
+        };
+        let api = GitStatusInfo::load(
+            &context,
+            repo,
+            &api_config,
+            GitVersion(2, 39, 0),
+            Vec::new(),
+            false,
+        );
+        let readme = GitStatusInfo::load(
+            &context,
+            repo,
+            &readme_config,
+            GitVersion(2, 39, 0),
+            Vec::new(),
+            false,
+        );
+
+        assert_eq!(api.get_untracked(), Some(1));
+        assert_eq!(readme.get_modified(), Some(1));
+        assert_eq!(api.get_modified(), Some(0));
+        assert_eq!(readme.get_untracked(), Some(0));
+
+        repo_dir.close()
+    }
+
     // Whenever a file is manually renamed, git itself ('git status') does not treat such file as renamed,
     // but as untracked instead. The following test checks if manually deleted and manually renamed
     // files are tracked by git_status module in the same way 'git status' does.
@@ -796,6 +2030,35 @@ mod tests {
         repo_dir.close()
     }
 
+    fn render_base(repo_dir: &Path, base_branch: &str) -> (Option<String>, Vec<String>) {
+        ModuleRenderer::new("git_status")
+            .config(toml::toml! {
+                [git_status]
+                format = "$base_ahead$base_behind"
+                base_branch = base_branch
+            })
+            .path(repo_dir)
+            .collect_with_commands()
+    }
+
+    fn stacked_branch(repo_dir: &Path) -> io::Result<()> {
+        Command::new("git")
+            .args(["checkout", "-b", "feature", "HEAD^"])
+            .current_dir(repo_dir)
+            .output()?;
+        barrier();
+
+        for message in ["First change", "Second change"] {
+            Command::new("git")
+                .args(["commit", "--allow-empty", "-m", message, "--no-gpg-sign"])
+                .current_dir(repo_dir)
+                .output()?;
+            barrier();
+        }
+
+        Ok(())
+    }
+
     fn ahead(repo_dir: &Path) -> io::Result<()> {
         File::create(repo_dir.join("readme.md"))?.sync_all()?;
 
@@ -931,6 +2194,19 @@ mod tests {
         Ok(())
     }
 
+    fn create_copied(repo_dir: &Path) -> io::Result<()> {
+        fs::copy(repo_dir.join("readme.md"), repo_dir.join("copy.md"))?;
+        fs::write(repo_dir.join("readme.md"), "# modified\n")?;
+
+        Command::new("git")
+            .args(["add", "-A"])
+            .current_dir(repo_dir)
+            .output()?;
+        barrier();
+
+        Ok(())
+    }
+
     fn create_deleted(repo_dir: &Path) -> io::Result<()> {
         fs::remove_file(repo_dir.join("readme.md"))?;
 
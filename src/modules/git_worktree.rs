@@ -0,0 +1,138 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::git_worktree::GitWorktreeConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the name of the linked git worktree the current directory is in
+///
+/// Nothing is shown in the main worktree of a repo.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("git_worktree");
+    let config = GitWorktreeConfig::try_load(module.config);
+
+    let repo = context.get_repo().ok()?;
+    let worktree = repo.worktree_name()?;
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "worktree" => Some(Ok(worktree)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("git_worktree", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+    use std::process::Command;
+
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+
+    fn add_worktree(repo_dir: &Path, worktree: &Path) -> io::Result<()> {
+        Command::new("git")
+            .args(["worktree", "add", "-q", "-b", "other"])
+            .arg(worktree)
+            .current_dir(repo_dir)
+            .output()?;
+        Ok(())
+    }
+
+    fn format_output(worktree: &str) -> Option<String> {
+        Some(format!(
+            "in {} ",
+            Color::Green.bold().paint(format!("🌲 {}", worktree))
+        ))
+    }
+
+    #[test]
+    fn shows_linked_worktree_name() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let worktree_dir = tempfile::tempdir()?;
+        let worktree = worktree_dir.path().join("feature");
+        add_worktree(repo_dir.path(), &worktree)?;
+
+        let actual = ModuleRenderer::new("git_worktree")
+            .path(&worktree)
+            .collect();
+
+        assert_eq!(actual, format_output("feature"));
+        worktree_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn ascii_symbol_without_utf8_locale() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let worktree_dir = tempfile::tempdir()?;
+        let worktree = worktree_dir.path().join("feature");
+        add_worktree(repo_dir.path(), &worktree)?;
+
+        let actual = ModuleRenderer::new("git_worktree")
+            .path(&worktree)
+            .env("LANG", "C")
+            .collect();
+        let expected = Some(format!(
+            "in {} ",
+            Color::Green.bold().paint("worktree feature")
+        ));
+
+        assert_eq!(actual, expected);
+        worktree_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn nothing_in_main_worktree() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let worktree_dir = tempfile::tempdir()?;
+        add_worktree(repo_dir.path(), &worktree_dir.path().join("feature"))?;
+
+        let actual = ModuleRenderer::new("git_worktree")
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(actual, None);
+        worktree_dir.close()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn moved_worktree_uses_directory_name() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let worktree_dir = tempfile::tempdir()?;
+        let worktree = worktree_dir.path().join("feature");
+        add_worktree(repo_dir.path(), &worktree)?;
+        // Moved without `git worktree move`, so git still points to the old directory
+        let moved = worktree_dir.path().join("review");
+        fs::rename(&worktree, &moved)?;
+
+        let actual = ModuleRenderer::new("git_worktree").path(&moved).collect();
+
+        assert_eq!(actual, format_output("review"));
+        worktree_dir.close()?;
+        repo_dir.close()
+    }
+}
@@ -0,0 +1,170 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::gleam::GleamConfig;
+use crate::formatter::StringFormatter;
+use crate::formatter::VersionFormatter;
+use crate::utils;
+
+/// Creates a module with the current Gleam version
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("gleam");
+    let env = module.env();
+    let config = GleamConfig::try_load(module.config);
+
+    let is_gleam_project = context
+        .try_begin_scan()?
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
+        .is_match();
+
+    if !is_gleam_project {
+        return None;
+    }
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "version" => {
+                    let gleam_version = get_gleam_version(
+                        &context.exec_cmd("gleam", &["--version"], &env)?.stdout,
+                    )?;
+                    VersionFormatter::format_module_version(
+                        module.get_name(),
+                        &gleam_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
+                "target" => get_gleam_target(context).map(Ok),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("gleam", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn get_gleam_version(gleam_stdout: &str) -> Option<String> {
+    // gleam output looks like this:
+    // gleam 0.22.0
+
+    let version = gleam_stdout
+        // split into ["gleam", "0.22.0"]
+        .split_whitespace()
+        // return "0.22.0"
+        .nth(1)?;
+
+    Some(version.to_string())
+}
+
+/// Reads the compilation target (e.g. `erlang` or `javascript`) from `gleam.toml`
+fn get_gleam_target(context: &Context) -> Option<String> {
+    let contents = utils::read_file(context.current_dir.join("gleam.toml")).ok()?;
+    let manifest = toml::from_str::<toml::Value>(&contents).ok()?;
+
+    manifest
+        .get("target")
+        .and_then(|target| target.as_str())
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs::File;
+    use std::io::{self, Write};
+
+    #[test]
+    fn folder_without_gleam_files() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("gleam").path(dir.path()).collect();
+
+        let expected = None;
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_gleam_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.gleam"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("gleam").path(dir.path()).collect();
+
+        let expected = Some(format!(
+            "via {}",
+            Color::RGB(255, 175, 243).bold().paint("⭐ v0.22.0 ")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_gleam_toml() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("gleam.toml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("gleam").path(dir.path()).collect();
+
+        let expected = Some(format!(
+            "via {}",
+            Color::RGB(255, 175, 243).bold().paint("⭐ v0.22.0 ")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_gleam_target() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut file = File::create(dir.path().join("gleam.toml"))?;
+        file.write_all(b"name = \"app\"\nversion = \"1.0.0\"\ntarget = \"javascript\"\n")?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("gleam")
+            .path(dir.path())
+            .config(toml::toml! {
+                [gleam]
+                format = "via [$symbol($version )($target )]($style)"
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "via {}",
+            Color::RGB(255, 175, 243)
+                .bold()
+                .paint("⭐ v0.22.0 javascript ")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_get_gleam_version() {
+        assert_eq!(
+            get_gleam_version("gleam 0.22.0\n"),
+            Some("0.22.0".to_string())
+        );
+        assert_eq!(get_gleam_version(""), None);
+    }
+}
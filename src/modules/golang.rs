@@ -1,3 +1,4 @@
+use super::utils::pinned_version::{pinned_version, VersionSource};
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::go::GoConfig;
@@ -7,6 +8,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Go version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("golang");
+    let env = module.env();
     let config = GoConfig::try_load(module.config);
     let is_go_project = context
         .try_begin_scan()?
@@ -19,10 +21,17 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
+    let pinned = if config.prefer_pinned {
+        pinned_version(context, &["go", "golang"])
+    } else {
+        None
+    };
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
+                "version_source" => Some(VersionSource::from_pin(&pinned).as_str()),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -31,8 +40,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let golang_version =
-                        get_go_version(&context.exec_cmd("go", &["version"])?.stdout)?;
+                    let golang_version = match &pinned {
+                        Some(version) => version.clone(),
+                        None => {
+                            get_go_version(&context.exec_cmd("go", &["version"], &env)?.stdout)?
+                        }
+                    };
 
                     VersionFormatter::format_module_version(
                         module.get_name(),
@@ -49,7 +62,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `golang`:\n{}", error);
+            crate::error::StarshipError::from_format("golang", error).log();
             return None;
         }
     });
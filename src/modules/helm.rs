@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Helm version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("helm");
+    let env = module.env();
     let config = HelmConfig::try_load(module.config);
 
     let is_helm_project = context
@@ -34,7 +35,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 "version" => {
                     let helm_version = get_helm_version(
                         &context
-                            .exec_cmd("helm", &["version", "--short", "--client"])?
+                            .exec_cmd("helm", &["version", "--short", "--client"], &env)?
                             .stdout,
                     )?;
                     VersionFormatter::format_module_version(
@@ -52,7 +53,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `helm`:\n{}", error);
+            crate::error::StarshipError::from_format("helm", error).log();
             return None;
         }
     });
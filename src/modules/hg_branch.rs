@@ -4,6 +4,7 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::hg_branch::HgBranchConfig;
 use crate::formatter::StringFormatter;
+use crate::utils;
 
 /// Creates a module with the Hg bookmark or branch in the current directory
 ///
@@ -25,7 +26,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     };
 
     let len = if config.truncation_length <= 0 {
-        log::warn!(
+        crate::log_once!(
             "\"truncation_length\" should be a positive value, found {}",
             config.truncation_length
         );
@@ -66,7 +67,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `hg_branch`:\n{}", error);
+            crate::error::StarshipError::from_format("hg_branch", error).log();
             return None;
         }
     });
@@ -75,13 +76,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 }
 
 fn get_hg_branch_name(ctx: &Context) -> String {
-    std::fs::read_to_string(ctx.current_dir.join(".hg").join("branch"))
+    utils::read_file(ctx.current_dir.join(".hg").join("branch"))
         .map(|s| s.trim().into())
         .unwrap_or_else(|_| "default".to_string())
 }
 
 fn get_hg_current_bookmark(ctx: &Context) -> Option<String> {
-    std::fs::read_to_string(ctx.current_dir.join(".hg").join("bookmarks.current"))
+    utils::read_file(ctx.current_dir.join(".hg").join("bookmarks.current"))
         .map(|s| s.trim().into())
         .ok()
 }
@@ -0,0 +1,343 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use super::{Context, Module, RootModuleConfig};
+use crate::configs::hg_state::HgStateConfig;
+use crate::formatter::StringFormatter;
+use crate::utils;
+
+/// Creates a module with the state of the Mercurial repository containing the current directory
+///
+/// During an hg operation it will show: REBASING, HISTEDITING, MERGING, etc.
+/// If the progress of a rebase is known (e.g. rebasing 3/10), it will show that too.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("hg_state");
+    let config: HgStateConfig = HgStateConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    };
+
+    let hg_dir = context.hg_root()?.join(".hg");
+
+    let state_description = get_state_description(&hg_dir, &config)?;
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "state" => Some(state_description.label),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "progress_current" => state_description.current.as_ref().map(Ok),
+                "progress_total" => state_description.total.as_ref().map(Ok),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("hg_state", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// Returns the operation in progress from the state files hg keeps in `.hg`
+///
+/// Rebases, histedits, grafts and unshelves leave a merge behind when they stop on a conflict,
+/// so they are checked first.
+fn get_state_description<'a>(
+    hg_dir: &Path,
+    config: &HgStateConfig<'a>,
+) -> Option<StateDescription<'a>> {
+    let label = |label| StateDescription {
+        label,
+        current: None,
+        total: None,
+    };
+
+    if hg_dir.join("rebasestate").exists() {
+        Some(describe_rebase(hg_dir, config.rebase))
+    } else if hg_dir.join("histedit-state").exists() {
+        Some(label(config.histedit))
+    } else if hg_dir.join("graftstate").exists() {
+        Some(label(config.graft))
+    } else if hg_dir.join("shelvedstate").exists() {
+        Some(label(config.unshelve))
+    } else if hg_dir.join("merge").is_dir() || has_second_parent(hg_dir) {
+        Some(label(config.merge))
+    } else if hg_dir.join("bisect.state").exists() {
+        Some(label(config.bisect))
+    } else {
+        None
+    }
+}
+
+fn describe_rebase<'a>(hg_dir: &Path, rebase_config: &'a str) -> StateDescription<'a> {
+    let (current, total) = match rebase_progress(hg_dir) {
+        Some((current, total)) => (Some(current.to_string()), Some(total.to_string())),
+        None => (None, None),
+    };
+
+    StateDescription {
+        label: rebase_config,
+        current,
+        total,
+    }
+}
+
+/// The revision being rebased and the number of revisions to rebase, from `.hg/rebasestate`
+///
+/// After a header, the file has a `<old>:<new>[:<destination>]` line for each revision to
+/// rebase, where `<new>` is a negative number until the revision has been rebased.
+fn rebase_progress(hg_dir: &Path) -> Option<(usize, usize)> {
+    let state = utils::read_file(hg_dir.join("rebasestate")).ok()?;
+    let revisions: Vec<&str> = state
+        .lines()
+        .filter_map(|line| line.split(':').nth(1))
+        .collect();
+    if revisions.is_empty() {
+        return None;
+    }
+
+    let rebased = revisions
+        .iter()
+        .filter(|new| !new.is_empty() && !new.starts_with('-'))
+        .count();
+    Some(((rebased + 1).min(revisions.len()), revisions.len()))
+}
+
+/// Whether the working directory has a second parent, as after an `hg merge` that had no
+/// conflicts to record in `.hg/merge`
+///
+/// The parents are the two 20 byte nodes at the start of `.hg/dirstate`, after a marker in
+/// the `dirstate-v2` format.
+fn has_second_parent(hg_dir: &Path) -> bool {
+    const V2_MARKER: &[u8] = b"dirstate-v2\n";
+
+    let mut header = [0; V2_MARKER.len() + 40];
+    let read = fs::File::open(hg_dir.join("dirstate"))
+        .and_then(|mut file| file.read(&mut header))
+        .unwrap_or(0);
+    let header = &header[..read];
+    let parents = header.strip_prefix(V2_MARKER).unwrap_or(header);
+
+    match parents.get(20..40) {
+        Some(second_parent) => second_parent.iter().any(|byte| *byte != 0),
+        None => false,
+    }
+}
+
+struct StateDescription<'a> {
+    label: &'a str,
+    current: Option<String>,
+    total: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+
+    const NODE_A: &str = "1f0dee641bb7258c56bd60e93edfa2405381c41e";
+    const NODE_B: &str = "2a1b2e9e0b5d3c1f5c71f5b0d2b0bb3e9e4f7a10";
+    const NODE_C: &str = "3c5f8a6d2e0b4f1a9d7c6b5a4e3f2d1c0b9a8e7d";
+
+    fn render(repo_dir: &Path) -> Option<String> {
+        ModuleRenderer::new("hg_state")
+            .path(repo_dir)
+            .config(toml::toml! {
+                [hg_state]
+                disabled = false
+            })
+            .collect()
+    }
+
+    fn expected(state: &str) -> Option<String> {
+        Some(format!("({}) ", Color::Yellow.bold().paint(state)))
+    }
+
+    /// Renders the module in the hg fixture with the state file `path` holding `contents`
+    fn render_with_state(path: &str, contents: &str) -> io::Result<Option<String>> {
+        let repo_dir = fixture_repo(FixtureProvider::HgMetadata)?;
+        let path = repo_dir.path().join(".hg").join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, contents)?;
+
+        let actual = render(repo_dir.path());
+        repo_dir.close()?;
+        Ok(actual)
+    }
+
+    fn rebasestate(revisions: &[&str]) -> String {
+        let mut state = format!("{}\n{}\n{}\n0\n0\n0\n\n", NODE_A, NODE_B, "0".repeat(40));
+        for revision in revisions {
+            state.push_str(revision);
+            state.push('\n');
+        }
+        state
+    }
+
+    #[test]
+    fn show_nothing_on_empty_dir() -> io::Result<()> {
+        let repo_dir = tempfile::tempdir()?;
+
+        assert_eq!(None, render(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn show_nothing_when_clean() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::HgMetadata)?;
+
+        assert_eq!(None, render(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::HgMetadata)?;
+        fs::write(repo_dir.path().join(".hg/graftstate"), NODE_A)?;
+
+        let actual = ModuleRenderer::new("hg_state")
+            .path(repo_dir.path())
+            .collect();
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_rebasing_with_progress() -> io::Result<()> {
+        let state = rebasestate(&[
+            &format!("{}:{}:{}", NODE_A, NODE_C, NODE_B),
+            &format!("{}:-1:{}", NODE_B, NODE_B),
+            &format!("{}:-1:{}", NODE_C, NODE_B),
+        ]);
+
+        assert_eq!(
+            expected("REBASING 2/3"),
+            render_with_state("rebasestate", &state)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shows_rebasing_without_progress() -> io::Result<()> {
+        assert_eq!(expected("REBASING"), render_with_state("rebasestate", "")?);
+        Ok(())
+    }
+
+    #[test]
+    fn shows_histediting() -> io::Result<()> {
+        let state = format!(
+            "v1\n{}\n{}\nFalse\n2\npick\n{}\nedit\n{}\n0\n\n",
+            NODE_A, NODE_C, NODE_B, NODE_C
+        );
+
+        assert_eq!(
+            expected("HISTEDITING"),
+            render_with_state("histedit-state", &state)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shows_grafting() -> io::Result<()> {
+        assert_eq!(
+            expected("GRAFTING"),
+            render_with_state("graftstate", &format!("{}\n", NODE_A))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shows_unshelving() -> io::Result<()> {
+        let state = format!("2\ndefault\n{}\n{}\n\n", NODE_A, NODE_B);
+
+        assert_eq!(
+            expected("UNSHELVING"),
+            render_with_state("shelvedstate", &state)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shows_merging_with_conflicts() -> io::Result<()> {
+        assert_eq!(
+            expected("MERGING"),
+            render_with_state("merge/state2", &format!("L{}\n", NODE_A))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shows_merging_from_second_parent() -> io::Result<()> {
+        let mut dirstate = vec![0x11; 20];
+        dirstate.extend([0x22; 20]);
+        let repo_dir = fixture_repo(FixtureProvider::HgMetadata)?;
+        fs::write(repo_dir.path().join(".hg/dirstate"), &dirstate)?;
+        assert_eq!(expected("MERGING"), render(repo_dir.path()));
+
+        let mut dirstate_v2 = b"dirstate-v2\n".to_vec();
+        dirstate_v2.extend(&dirstate);
+        fs::write(repo_dir.path().join(".hg/dirstate"), &dirstate_v2)?;
+        assert_eq!(expected("MERGING"), render(repo_dir.path()));
+
+        dirstate.truncate(20);
+        dirstate.extend([0; 20]);
+        fs::write(repo_dir.path().join(".hg/dirstate"), &dirstate)?;
+        assert_eq!(None, render(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_bisecting() -> io::Result<()> {
+        let state = format!("bad {}\ngood {}\n", NODE_A, NODE_B);
+
+        assert_eq!(
+            expected("BISECTING"),
+            render_with_state("bisect.state", &state)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rebase_wins_over_its_merge() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::HgMetadata)?;
+        fs::create_dir(repo_dir.path().join(".hg/merge"))?;
+        fs::write(
+            repo_dir.path().join(".hg/rebasestate"),
+            rebasestate(&[&format!("{}:-1:{}", NODE_A, NODE_B)]),
+        )?;
+
+        assert_eq!(expected("REBASING 1/1"), render(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_state_in_subdirectory() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::HgMetadata)?;
+        fs::write(repo_dir.path().join(".hg/graftstate"), NODE_A)?;
+        let sub_dir = repo_dir.path().join("src/module");
+        fs::create_dir_all(&sub_dir)?;
+
+        assert_eq!(expected("GRAFTING"), render(&sub_dir));
+        repo_dir.close()
+    }
+}
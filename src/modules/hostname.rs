@@ -48,7 +48,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "hostname" => Some(Ok(host)),
+                "hostname" => Some(Ok(host.to_string())),
+                "wsl_distro" => context
+                    .is_wsl()
+                    .then(|| context.get_env("WSL_DISTRO_NAME"))
+                    .flatten()
+                    .filter(|distro| !distro.is_empty())
+                    .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -57,7 +63,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `hostname`:\n{}", error);
+            crate::error::StarshipError::from_format("hostname", error).log();
             return None;
         }
     });
@@ -159,6 +165,42 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn wsl_distro() {
+        let hostname = get_hostname!();
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = false
+                trim_at = ""
+                format = "[$hostname( \\($wsl_distro\\))]($style) in "
+            })
+            .env("WSL_DISTRO_NAME", "Ubuntu")
+            .collect();
+        let expected = Some(format!(
+            "{} in ",
+            style().paint(format!("{} (Ubuntu)", hostname))
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn wsl_distro_outside_wsl() {
+        let hostname = get_hostname!();
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = false
+                trim_at = ""
+                format = "[$hostname( \\($wsl_distro\\))]($style) in "
+            })
+            .collect();
+        let expected = Some(format!("{} in ", style().paint(hostname)));
+
+        assert_eq!(expected, actual);
+    }
+
     fn style() -> Style {
         Color::Green.bold().dimmed()
     }
@@ -1,7 +1,9 @@
 use crate::configs::java::JavaConfig;
 use crate::formatter::{StringFormatter, VersionFormatter};
+use crate::utils::EnvOverlay;
 use std::path::PathBuf;
 
+use super::utils::pinned_version::{pinned_version, VersionSource};
 use super::{Context, Module, RootModuleConfig};
 
 use regex::Regex;
@@ -10,6 +12,7 @@ const JAVA_VERSION_PATTERN: &str = "(?P<version>[\\d\\.]+)[^\\s]*\\s(?:built|fro
 /// Creates a module with the current Java version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("java");
+    let env = module.env();
     let config: JavaConfig = JavaConfig::try_load(module.config);
 
     let is_java_project = context
@@ -23,10 +26,17 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
+    let pinned = if config.prefer_pinned {
+        pinned_version(context, &["java"])
+    } else {
+        None
+    };
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
+                "version_source" => Some(VersionSource::from_pin(&pinned).as_str()),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -34,7 +44,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => get_java_version(context, &config).map(Ok),
+                "version" => match &pinned {
+                    Some(version) => VersionFormatter::format_module_version(
+                        module.get_name(),
+                        version,
+                        config.version_format,
+                    ),
+                    None => get_java_version(context, &config, &env),
+                }
+                .map(Ok),
                 _ => None,
             })
             .parse(None)
@@ -43,7 +61,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `java`:\n{}", error);
+            crate::error::StarshipError::from_format("java", error).log();
             return None;
         }
     });
@@ -51,7 +69,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_java_version(context: &Context, config: &JavaConfig) -> Option<String> {
+fn get_java_version(context: &Context, config: &JavaConfig, env: &EnvOverlay) -> Option<String> {
     let java_command = context
         .get_env("JAVA_HOME")
         .map(PathBuf::from)
@@ -64,7 +82,7 @@ fn get_java_version(context: &Context, config: &JavaConfig) -> Option<String> {
         })
         .unwrap_or_else(|| String::from("java"));
 
-    let output = context.exec_cmd(&java_command, &["-Xinternalversion"])?;
+    let output = context.exec_cmd(&java_command, &["-Xinternalversion"], env)?;
     let java_version = if output.stdout.is_empty() {
         output.stderr
     } else {
@@ -82,7 +100,7 @@ fn format_java_version(java_version: &str, version_format: &str) -> Option<Strin
     match VersionFormatter::format_version(version, version_format) {
         Ok(formatted) => Some(formatted),
         Err(error) => {
-            log::warn!("Error formatting `java` version:\n{}", error);
+            crate::log_once!("Error formatting `java` version:\n{}", error);
             Some(format!("v{}", version))
         }
     }
@@ -224,7 +242,8 @@ mod tests {
         File::create(dir.path().join("Main.java"))?.sync_all()?;
         let actual = ModuleRenderer::new("java").cmd("java -Xinternalversion", Some(CommandOutput {
             stdout: "OpenJDK 64-Bit Server VM (16+14) for bsd-aarch64 JRE (16+14), built on Jan 17 2021 07:19:47 by \"brew\" with clang Apple LLVM 12.0.0 (clang-1200.0.32.28)\n".to_owned(),
-            stderr: "".to_owned()
+            stderr: "".to_owned(),
+            status: Some(0),
         })).path(dir.path()).collect();
         let expected = Some(format!("via {}", Color::Red.dimmed().paint("☕ v16 ")));
         assert_eq!(expected, actual);
@@ -327,6 +346,7 @@ mod tests {
             Some(CommandOutput {
                 stdout: "OpenJDK 64-Bit Server VM (11.0.4+11-LTS-sapmachine) for linux-amd64 JRE (11.0.4+11-LTS-sapmachine), built on Jul 17 2019 08:58:43 by \"\" with gcc 7.3.0".to_owned(),
                 stderr: String::new(),
+                status: Some(0),
             }))
             .path(dir.path())
             .collect();
@@ -8,17 +8,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("jobs");
     let config = JobsConfig::try_load(module.config);
 
-    let props = &context.properties;
-    let num_of_jobs = props
-        .get("jobs")
-        .map(String::as_str)
-        .unwrap_or("0")
+    let num_of_jobs = context
+        .get_property("jobs")
+        .unwrap_or_else(|| "0".to_owned())
         .trim()
         .parse::<i64>()
         .ok()?;
 
     if config.threshold < 0 {
-        log::warn!(
+        crate::log_once!(
             "threshold in [jobs] ({}) was less than zero",
             config.threshold
         );
@@ -55,7 +53,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `jobs`:\n{}", error);
+            crate::error::StarshipError::from_format("jobs", error).log();
             return None;
         }
     });
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Julia version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("julia");
+    let env = module.env();
     let config = JuliaConfig::try_load(module.config);
 
     let is_julia_project = context
@@ -32,8 +33,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let julia_version =
-                        get_julia_version(&context.exec_cmd("julia", &["--version"])?.stdout)?;
+                    let julia_version = get_julia_version(
+                        &context.exec_cmd("julia", &["--version"], &env)?.stdout,
+                    )?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &julia_version,
@@ -49,7 +51,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `julia`:\n{}", error);
+            crate::error::StarshipError::from_format("julia", error).log();
             return None;
         }
     });
@@ -3,6 +3,7 @@ use super::{Context, Module, RootModuleConfig};
 use crate::configs::kotlin::KotlinConfig;
 use crate::formatter::StringFormatter;
 use crate::formatter::VersionFormatter;
+use crate::utils::EnvOverlay;
 
 use regex::Regex;
 const KOTLIN_VERSION_PATTERN: &str = "(?P<version>[\\d\\.]+[\\d\\.]+[\\d\\.]+)";
@@ -10,6 +11,7 @@ const KOTLIN_VERSION_PATTERN: &str = "(?P<version>[\\d\\.]+[\\d\\.]+[\\d\\.]+)";
 /// Creates a module with the current Kotlin version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("kotlin");
+    let env = module.env();
     let config = KotlinConfig::try_load(module.config);
 
     let is_kotlin_project = context
@@ -35,7 +37,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let kotlin_version = get_kotlin_version(context, &config.kotlin_binary)?;
+                    let kotlin_version = get_kotlin_version(context, &config.kotlin_binary, &env)?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &kotlin_version,
@@ -51,7 +53,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `kotlin`:\n{}", error);
+            crate::error::StarshipError::from_format("kotlin", error).log();
             return None;
         }
     });
@@ -59,8 +61,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_kotlin_version(context: &Context, kotlin_binary: &str) -> Option<String> {
-    match context.exec_cmd(kotlin_binary, &["-version"]) {
+fn get_kotlin_version(context: &Context, kotlin_binary: &str, env: &EnvOverlay) -> Option<String> {
+    // `kotlinc -version` exits with a non-zero exit code on some versions
+    match context.exec_cmd_with_status(kotlin_binary, &["-version"], env) {
         Some(output) => {
             let kotlin_output = if output.stdout.is_empty() {
                 output.stderr
@@ -88,6 +91,7 @@ fn parse_kotlin_version(kotlin_stdout: &str) -> Option<String> {
 mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
     use ansi_term::Color;
     use std::fs::File;
     use std::io;
@@ -161,6 +165,32 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_kotlin_file_compiler_nonzero_exit() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.kt"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("kotlin")
+            .path(dir.path())
+            .config(toml::toml! {
+                 [kotlin]
+                 kotlin_binary = "kotlinc"
+            })
+            .cmd(
+                "kotlinc -version",
+                Some(CommandOutput {
+                    stdout: String::default(),
+                    stderr: String::from("info: kotlinc-jvm 1.5.10 (JRE 11.0.11+9)\n"),
+                    status: Some(1),
+                }),
+            )
+            .collect();
+
+        let expected = Some(format!("via {}", Color::Blue.bold().paint("🅺 v1.5.10 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
     #[test]
     fn test_parse_kotlin_version_from_runtime() {
         let kotlin_input = "Kotlin version 1.4.21-release-411 (JRE 14.0.1+7)";
@@ -94,7 +94,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `kubernetes`: \n{}", error);
+            crate::error::StarshipError::from_format("kubernetes", error).log();
             return None;
         }
     });
@@ -3,10 +3,12 @@ use super::{Context, Module, RootModuleConfig};
 use crate::configs::lua::LuaConfig;
 use crate::formatter::StringFormatter;
 use crate::formatter::VersionFormatter;
+use crate::utils::EnvOverlay;
 
 /// Creates a module with the current Lua version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("lua");
+    let env = module.env();
     let config = LuaConfig::try_load(module.config);
 
     let is_lua_project = context
@@ -32,7 +34,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let lua_version = get_lua_version(context, &config.lua_binary)?;
+                    let lua_version = get_lua_version(context, &config.lua_binary, &env)?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &lua_version,
@@ -48,7 +50,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `lua`:\n{}", error);
+            crate::error::StarshipError::from_format("lua", error).log();
             return None;
         }
     });
@@ -56,8 +58,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_lua_version(context: &Context, lua_binary: &str) -> Option<String> {
-    let output = context.exec_cmd(lua_binary, &["-v"])?;
+fn get_lua_version(context: &Context, lua_binary: &str, env: &EnvOverlay) -> Option<String> {
+    let output = context.exec_cmd(lua_binary, &["-v"], env)?;
     let lua_version = if output.stdout.is_empty() {
         output.stderr
     } else {
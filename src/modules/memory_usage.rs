@@ -1,7 +1,9 @@
 use byte_unit::{Byte, ByteUnit};
+use serde::{Deserialize, Serialize};
 
-use super::{Context, Module, RootModuleConfig, Shell};
+use super::{Context, Module, RootModuleConfig};
 
+use crate::cache;
 use crate::configs::memory_usage::MemoryConfig;
 use crate::formatter::StringFormatter;
 
@@ -12,39 +14,57 @@ fn format_kib(n_kib: u64) -> String {
     display_bytes
 }
 
-fn format_pct(pct_number: f64, pct_sign: &str) -> String {
-    format!("{:.0}{}", pct_number, pct_sign)
+fn format_pct(pct_number: f64) -> String {
+    format!("{:.0}%", pct_number)
 }
 
 fn format_usage_total(usage: u64, total: u64) -> String {
     format!("{}/{}", format_kib(usage), format_kib(total))
 }
 
+/// The parts of `sys_info::MemInfo` used by the module, in KiB
+#[derive(Serialize, Deserialize)]
+struct MemInfo {
+    total: u64,
+    free: u64,
+    avail: u64,
+    swap_total: u64,
+    swap_free: u64,
+}
+
+fn get_mem_info() -> Option<MemInfo> {
+    match sys_info::mem_info() {
+        Ok(info) => Some(MemInfo {
+            total: info.total,
+            free: info.free,
+            avail: info.avail,
+            swap_total: info.swap_total,
+            swap_free: info.swap_free,
+        }),
+        Err(err) => {
+            log::warn!("Unable to access memory usage information:\n{}", err);
+            None
+        }
+    }
+}
+
 /// Creates a module with system memory usage information
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("memory_usage");
     let config = MemoryConfig::try_load(module.config);
 
-    // TODO: Update when v1.0 printing refactor is implemented to only
-    // print escapes in a prompt context.
-    let pct_sign = match context.shell {
-        Shell::Zsh => "%%", // % is an escape in zsh, see PROMPT in `man zshmisc`
-        _ => "%",
-    };
-
     // As we default to disabled=true, we have to check here after loading our config module,
     // before it was only checking against whatever is in the config starship.toml
     if config.disabled {
         return None;
     }
 
-    let system = match sys_info::mem_info() {
-        Ok(info) => info,
-        Err(err) => {
-            log::warn!("Unable to access memory usage information:\n{}", err);
-            return None;
-        }
-    };
+    let system = cache::sampled(
+        context,
+        "memory_usage",
+        config.refresh_interval_ms,
+        get_mem_info,
+    )?;
 
     // avail includes reclaimable memory, but isn't supported on all platforms
     let avail_memory_kib = match system.avail {
@@ -54,7 +74,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let used_memory_kib = system.total.saturating_sub(avail_memory_kib);
     let total_memory_kib = system.total;
     let ram_used = (used_memory_kib as f64 / total_memory_kib as f64) * 100.;
-    let ram_pct = format_pct(ram_used, pct_sign);
+    let ram_pct = format_pct(ram_used);
 
     let threshold = config.threshold;
     if ram_used.round() < threshold as f64 {
@@ -65,7 +85,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let total_swap_kib = system.swap_total;
     let used_swap_kib = system.swap_total.saturating_sub(system.swap_free);
     let percent_swap_used = (used_swap_kib as f64 / total_swap_kib as f64) * 100.;
-    let swap_pct = format_pct(percent_swap_used, pct_sign);
+    let swap_pct = format_pct(percent_swap_used);
     let swap = format_usage_total(used_swap_kib, total_swap_kib);
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
@@ -92,7 +112,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `memory_usage`:\n{}", error);
+            crate::error::StarshipError::from_format("memory_usage", error).log();
             return None;
         }
     });
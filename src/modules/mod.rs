@@ -3,9 +3,13 @@ mod aws;
 mod character;
 mod cmake;
 mod cmd_duration;
+mod cobol;
 mod conda;
+mod config_error;
+mod container;
 mod crystal;
 pub(crate) mod custom;
+mod daml;
 mod dart;
 mod deno;
 mod directory;
@@ -15,14 +19,20 @@ mod elixir;
 mod elm;
 mod env_var;
 mod erlang;
+mod fennel;
+mod fill;
 mod gcloud;
 mod git_branch;
 mod git_commit;
 mod git_state;
-mod git_status;
+pub(crate) mod git_status;
+mod git_worktree;
+mod gleam;
 mod golang;
+mod haxe;
 mod helm;
 mod hg_branch;
+mod hg_state;
 mod hostname;
 mod java;
 mod jobs;
@@ -32,6 +42,7 @@ mod kubernetes;
 mod line_break;
 mod lua;
 mod memory_usage;
+mod new_version_available;
 mod nim;
 mod nix_shell;
 mod nodejs;
@@ -40,6 +51,7 @@ mod openstack;
 mod package;
 mod perl;
 mod php;
+mod project_context;
 mod purescript;
 mod python;
 mod red;
@@ -50,12 +62,13 @@ mod scala;
 mod shell;
 mod shlvl;
 mod singularity;
+mod slow_fs;
 mod status;
 mod swift;
 mod terraform;
 mod time;
 mod username;
-mod utils;
+pub(crate) mod utils;
 mod vagrant;
 mod vcsh;
 mod vlang;
@@ -66,9 +79,93 @@ mod battery;
 
 use crate::config::RootModuleConfig;
 use crate::context::{Context, Shell};
-use crate::module::Module;
+use crate::formatter::{FormatScope, StringFormatter};
+use crate::module::{Module, ALL_MODULES};
 use std::time::Instant;
 
+// List of default prompt order
+// NOTE: If this const value is changed then Default prompt order subheading inside
+// prompt heading of config docs needs to be updated according to changes made here.
+pub const PROMPT_ORDER: &[&str] = &[
+    "username",
+    "hostname",
+    "shlvl",
+    "singularity",
+    "container",
+    "kubernetes",
+    "slow_fs",
+    "directory",
+    "vcsh",
+    "git_branch",
+    "git_worktree",
+    "git_commit",
+    "git_state",
+    "git_status",
+    "hg_branch",
+    "hg_state",
+    "project_context",
+    "docker_context",
+    "package",
+    // ↓ Toolchain version modules ↓
+    // (Let's keep these sorted alphabetically)
+    "cmake",
+    "cobol",
+    "daml",
+    "dart",
+    "deno",
+    "dotnet",
+    "elixir",
+    "elm",
+    "erlang",
+    "fennel",
+    "gleam",
+    "golang",
+    "haxe",
+    "helm",
+    "java",
+    "julia",
+    "kotlin",
+    "lua",
+    "nim",
+    "nodejs",
+    "ocaml",
+    "perl",
+    "php",
+    "purescript",
+    "python",
+    "rlang",
+    "red",
+    "ruby",
+    "rust",
+    "scala",
+    "swift",
+    "terraform",
+    "vlang",
+    "vagrant",
+    "zig",
+    // ↑ Toolchain version modules ↑
+    "nix_shell",
+    "conda",
+    "memory_usage",
+    "aws",
+    "gcloud",
+    "openstack",
+    "env_var",
+    "crystal",
+    "custom",
+    "cmd_duration",
+    "config_error",
+    "new_version_available",
+    "line_break",
+    "jobs",
+    #[cfg(feature = "battery")]
+    "battery",
+    "time",
+    "status",
+    "shell",
+    "character",
+];
+
 pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
     let start: Instant = Instant::now();
 
@@ -82,7 +179,11 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "character" => character::module(context),
             "cmake" => cmake::module(context),
             "cmd_duration" => cmd_duration::module(context),
+            "cobol" => cobol::module(context),
             "conda" => conda::module(context),
+            "container" => container::module(context),
+            "config_error" => config_error::module(context),
+            "daml" => daml::module(context),
             "dart" => dart::module(context),
             "deno" => deno::module(context),
             "directory" => directory::module(context),
@@ -91,15 +192,21 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "elixir" => elixir::module(context),
             "elm" => elm::module(context),
             "erlang" => erlang::module(context),
+            "fennel" => fennel::module(context),
+            "fill" => fill::module(context),
             "env_var" => env_var::module(context),
             "gcloud" => gcloud::module(context),
             "git_branch" => git_branch::module(context),
             "git_commit" => git_commit::module(context),
             "git_state" => git_state::module(context),
             "git_status" => git_status::module(context),
+            "git_worktree" => git_worktree::module(context),
+            "gleam" => gleam::module(context),
             "golang" => golang::module(context),
+            "haxe" => haxe::module(context),
             "helm" => helm::module(context),
             "hg_branch" => hg_branch::module(context),
+            "hg_state" => hg_state::module(context),
             "hostname" => hostname::module(context),
             "java" => java::module(context),
             "jobs" => jobs::module(context),
@@ -109,6 +216,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "line_break" => line_break::module(context),
             "lua" => lua::module(context),
             "memory_usage" => memory_usage::module(context),
+            "new_version_available" => new_version_available::module(context),
             "nim" => nim::module(context),
             "nix_shell" => nix_shell::module(context),
             "nodejs" => nodejs::module(context),
@@ -117,6 +225,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "package" => package::module(context),
             "perl" => perl::module(context),
             "php" => php::module(context),
+            "project_context" => project_context::module(context),
             "purescript" => purescript::module(context),
             "python" => python::module(context),
             "rlang" => rlang::module(context),
@@ -127,6 +236,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "shell" => shell::module(context),
             "shlvl" => shlvl::module(context),
             "singularity" => singularity::module(context),
+            "slow_fs" => slow_fs::module(context),
             "swift" => swift::module(context),
             "status" => status::module(context),
             "terraform" => terraform::module(context),
@@ -144,6 +254,10 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
         }
     };
 
+    if let Some(m) = m.as_mut() {
+        m.apply_max_length();
+    }
+
     let elapsed = start.elapsed();
     log::trace!("Took {:?} to compute module {:?}", elapsed, module);
     if elapsed.as_millis() < 1 {
@@ -158,6 +272,71 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
+/// Warns once per session about the variables in the format strings of the modules of `config`
+/// that name other modules
+///
+/// These always render as empty, which is easily mistaken for the other module not showing.
+pub fn warn_module_references(config: &toml::Value) {
+    for warning in config_reference_warnings(config) {
+        crate::log_once!("{}", warning);
+    }
+}
+
+fn config_reference_warnings(config: &toml::Value) -> Vec<String> {
+    let table = match config.as_table() {
+        Some(table) => table,
+        None => return Vec::new(),
+    };
+    let modules = table
+        .iter()
+        .filter(|(module, _)| ALL_MODULES.contains(&module.as_str()))
+        .map(|(module, config)| (module.clone(), config));
+    let custom_modules = table
+        .get("custom")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flatten()
+        .map(|(name, config)| (format!("custom.{}", name), config));
+
+    modules
+        .chain(custom_modules)
+        .flat_map(|(module, config)| module_reference_warnings(&module, config))
+        .collect()
+}
+
+fn module_reference_warnings(module: &str, config: &toml::Value) -> Vec<String> {
+    let scope = if module.starts_with("custom.") {
+        "custom"
+    } else {
+        module
+    };
+    let table = match config.as_table() {
+        Some(table) => table,
+        None => return Vec::new(),
+    };
+
+    let mut warnings = Vec::new();
+    for (key, value) in table {
+        let format = match value.as_str() {
+            Some(format) if key == "format" || key.ends_with("_format") => format,
+            _ => continue,
+        };
+        // Format strings that don't parse are reported by the module itself
+        let formatter = match StringFormatter::new(format) {
+            Ok(formatter) => formatter,
+            Err(_) => continue,
+        };
+        for reference in formatter.get_module_references(FormatScope::Module(scope)) {
+            warnings.push(format!(
+                "`${}` in `{}.{}` is another module, but module formats can only reference that \
+                module's own variables; to combine modules use the root `format`",
+                reference, module, key
+            ));
+        }
+    }
+    warnings
+}
+
 pub fn description(module: &str) -> &'static str {
     match module {
         "aws" => "The current AWS region and profile",
@@ -167,8 +346,12 @@ pub fn description(module: &str) -> &'static str {
         }
         "cmake" => "The currently installed version of CMake",
         "cmd_duration" => "How long the last command took to execute",
+        "cobol" => "The currently installed version of COBOL",
         "conda" => "The current conda environment, if $CONDA_DEFAULT_ENV is set",
+        "config_error" => "A warning when the configuration file has errors or unknown keys",
+        "container" => "The toolbox or distrobox container the shell is in",
         "crystal" => "The currently installed version of Crystal",
+        "daml" => "The Daml SDK version of the current project",
         "dart" => "The currently installed version of Dart",
         "deno" => "The currently installed version of Deno",
         "directory" => "The current working directory",
@@ -176,14 +359,20 @@ pub fn description(module: &str) -> &'static str {
         "dotnet" => "The relevant version of the .NET Core SDK for the current directory",
         "env_var" => "Displays the current value of a selected environment variable",
         "erlang" => "Current OTP version",
+        "fennel" => "The currently installed version of Fennel",
+        "fill" => "Fills the rest of the line of the prompt with a symbol",
         "gcloud" => "The current GCP client configuration",
         "git_branch" => "The active branch of the repo in your current directory",
         "git_commit" => "The active commit (and tag if any) of the repo in your current directory",
         "git_state" => "The current git operation, and it's progress",
         "git_status" => "Symbol representing the state of the repo",
+        "git_worktree" => "The name of the linked git worktree of the current directory",
+        "gleam" => "The currently installed version of Gleam",
         "golang" => "The currently installed version of Golang",
+        "haxe" => "The currently installed version of Haxe",
         "helm" => "The currently installed version of Helm",
         "hg_branch" => "The active branch of the repo in your current directory",
+        "hg_state" => "The current hg operation, and it's progress",
         "hostname" => "The system hostname",
         "java" => "The currently installed version of Java",
         "jobs" => "The current number of jobs running",
@@ -193,6 +382,7 @@ pub fn description(module: &str) -> &'static str {
         "line_break" => "Separates the prompt into two lines",
         "lua" => "The currently installed version of Lua",
         "memory_usage" => "Current system memory and swap usage",
+        "new_version_available" => "A newer starship release, when `update_check` is enabled",
         "nim" => "The currently installed version of Nim",
         "nix_shell" => "The nix-shell environment",
         "nodejs" => "The currently installed version of NodeJS",
@@ -201,6 +391,7 @@ pub fn description(module: &str) -> &'static str {
         "package" => "The package version of the current directory's project",
         "perl" => "The currently installed version of Perl",
         "php" => "The currently installed version of PHP",
+        "project_context" => "A note about the project from a trusted `.starship-context` file",
         "purescript" => "The currently installed version of PureScript",
         "python" => "The currently installed version of Python",
         "rlang" => "The currently installed version of R",
@@ -211,6 +402,9 @@ pub fn description(module: &str) -> &'static str {
         "swift" => "The currently installed version of Swift",
         "shell" => "The currently used shell indicator",
         "shlvl" => "The current value of SHLVL",
+        "slow_fs" => {
+            "Whether filesystem checks were skipped for taking longer than `fs_timeout_ms`"
+        }
         "status" => "The status of the last command",
         "terraform" => "The currently selected terraform workspace and version",
         "time" => "The current local time",
@@ -222,3 +416,223 @@ pub fn description(module: &str) -> &'static str {
         _ => "<no description>",
     }
 }
+
+/// The variables a module provides to its format strings
+pub fn variables(module: &str) -> &'static [&'static str] {
+    match module {
+        "aws" => &[
+            "symbol",
+            "profile",
+            "region",
+            "duration",
+            "sso_session",
+            "style",
+        ],
+        "battery" => &["symbol", "percentage", "style"],
+        "character" => &["symbol"],
+        "cmd_duration" => &["duration", "style"],
+        "conda" => &["symbol", "environment", "style"],
+        "container" => &["symbol", "name", "runtime", "style"],
+        "config_error" => &["symbol", "count", "style"],
+        "directory" => &[
+            "path",
+            "repo_root",
+            "before_root_path",
+            "previous",
+            "alias",
+            "read_only",
+            "style",
+            "read_only_style",
+            "repo_root_style",
+            "before_repo_root_style",
+        ],
+        "docker_context" => &["symbol", "context", "style"],
+        "dotnet" => &["symbol", "version", "tfm", "style"],
+        "elixir" => &["symbol", "version", "otp_version", "style"],
+        "env_var" => &["symbol", "env_value", "style"],
+        "gcloud" => &[
+            "symbol", "account", "domain", "region", "project", "active", "style",
+        ],
+        "git_branch" => &[
+            "symbol",
+            "branch",
+            "ref_kind",
+            "remote_branch",
+            "remote_name",
+            "pr_number",
+            "repo_alias",
+            "remote_symbol",
+            "style",
+        ],
+        "git_commit" => &["hash", "tag", "closest_tag", "describe_distance", "style"],
+        "git_state" => &["state", "progress_current", "progress_total", "style"],
+        "git_status" => &[
+            "all_status",
+            "ahead_behind",
+            "conflicted",
+            "deleted",
+            "renamed",
+            "modified",
+            "staged",
+            "untracked",
+            "stashed",
+            "clean",
+            "base_ahead",
+            "base_behind",
+            "count",
+            "ahead_count",
+            "behind_count",
+            "repo_alias",
+            "style",
+        ],
+        "git_worktree" => &["symbol", "worktree", "style"],
+        "gleam" => &["symbol", "version", "target", "style"],
+        "golang" | "java" | "nodejs" | "ruby" => &["symbol", "version", "version_source", "style"],
+        "hg_branch" => &["symbol", "branch", "style"],
+        "hg_state" => &["state", "progress_current", "progress_total", "style"],
+        "hostname" => &["hostname", "wsl_distro", "style"],
+        "jobs" => &["symbol", "number", "style"],
+        "kubernetes" => &["symbol", "context", "namespace", "style"],
+        "fill" => &[],
+        "line_break" => &[],
+        "memory_usage" => &["symbol", "ram", "ram_pct", "swap", "swap_pct", "style"],
+        "new_version_available" => &["symbol", "version", "style"],
+        "nix_shell" => &["symbol", "state", "name", "style"],
+        "ocaml" => &[
+            "symbol",
+            "version",
+            "switch_indicator",
+            "switch_name",
+            "style",
+        ],
+        "openstack" => &["symbol", "cloud", "project", "style"],
+        "project_context" => &["symbol", "text", "style"],
+        "python" => &[
+            "symbol",
+            "version",
+            "version_source",
+            "virtualenv",
+            "pyenv_prefix",
+            "style",
+        ],
+        "shell" => &[
+            "indicator",
+            "bash_indicator",
+            "fish_indicator",
+            "zsh_indicator",
+            "powershell_indicator",
+            "ion_indicator",
+            "elvish_indicator",
+            "tcsh_indicator",
+            "unknown_indicator",
+        ],
+        "shlvl" => &["symbol", "shlvl", "style"],
+        "singularity" => &["symbol", "env", "style"],
+        "slow_fs" => &["symbol", "style"],
+        "status" => &[
+            "symbol",
+            "status",
+            "int",
+            "maybe_int",
+            "common_meaning",
+            "signal_number",
+            "signal_name",
+            "style",
+        ],
+        "terraform" => &["symbol", "version", "workspace", "style"],
+        "custom" => &["symbol", "output", "style"],
+        "time" => &["time", "style"],
+        "username" => &["user", "style"],
+        "vcsh" => &["symbol", "repo", "style"],
+        // The modules that show the version of a tool
+        "cmake" | "cobol" | "crystal" | "daml" | "dart" | "deno" | "elm" | "erlang" | "fennel"
+        | "haxe" | "helm" | "julia" | "kotlin" | "lua" | "nim" | "package" | "perl" | "php"
+        | "purescript" | "red" | "rlang" | "rust" | "scala" | "swift" | "vagrant" | "vlang"
+        | "zig" => &["symbol", "version", "style"],
+        // Not a module
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_module_declares_its_variables() {
+        for module in ALL_MODULES
+            .iter()
+            .filter(|module| !matches!(**module, "fill" | "line_break"))
+        {
+            assert!(
+                !variables(module).is_empty(),
+                "`{}` has no variables",
+                module
+            );
+        }
+    }
+
+    #[test]
+    fn warns_about_other_modules_in_module_format() {
+        let config = toml::toml! {
+            format = "[$path$git_branch]($style) "
+            repo_root_format = "$repo_root$path"
+        };
+
+        assert_eq!(
+            module_reference_warnings("directory", &config),
+            vec![
+                "`$git_branch` in `directory.format` is another module, but module formats can only \
+                reference that module's own variables; to combine modules use the root `format`"
+            ]
+        );
+    }
+
+    #[test]
+    fn allows_own_variable_named_like_a_module() {
+        let config = toml::toml! {
+            format = "[$symbol$status]($style) "
+        };
+
+        assert!(module_reference_warnings("status", &config).is_empty());
+    }
+
+    #[test]
+    fn warns_about_other_modules_in_custom_format() {
+        let config = toml::toml! {
+            command = "echo foo"
+            format = "$output$character"
+        };
+
+        assert_eq!(
+            module_reference_warnings("custom.foo", &config),
+            vec![
+                "`$character` in `custom.foo.format` is another module, but module formats can only \
+                reference that module's own variables; to combine modules use the root `format`"
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_about_every_module_of_the_config() {
+        let config = toml::toml! {
+            format = "$directory$git_branch"
+            [directory]
+            format = "$path$git_branch"
+            [styles]
+            format = "$directory"
+            [custom.foo]
+            format = "$output$character"
+        };
+
+        assert_eq!(
+            config_reference_warnings(&config),
+            vec![
+                "`$git_branch` in `directory.format` is another module, but module formats can only \
+                reference that module's own variables; to combine modules use the root `format`",
+                "`$character` in `custom.foo.format` is another module, but module formats can only \
+                reference that module's own variables; to combine modules use the root `format`"
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::new_version_available::NewVersionAvailableConfig;
+use crate::formatter::StringFormatter;
+use crate::update_check;
+
+/// Creates a module showing a newer starship release, when `update_check` is enabled
+///
+/// The release comes from the last update check, which runs in the background after a prompt
+/// was printed. Rendering never looks it up itself.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let version = update_check::newer_version(context)?;
+
+    let mut module = context.new_module("new_version_available");
+    let config = NewVersionAvailableConfig::try_load(module.config);
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "version" => Some(Ok(&version)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("new_version_available", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::StarshipConfig;
+    use crate::context::{Context, Shell};
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+
+    const STATE_FILE: &str = "latest-release.json";
+
+    #[test]
+    fn shows_nothing_by_default() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+        fs::write(
+            cache.path().join(STATE_FILE),
+            r#"{"checked_at": 0, "latest_version": "999.0.0"}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("new_version_available")
+            .env("STARSHIP_CACHE", cache.path().to_string_lossy())
+            .collect();
+
+        assert_eq!(None, actual);
+        cache.close()
+    }
+
+    #[test]
+    fn shows_nothing_before_the_first_check() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("new_version_available")
+            .config(toml::toml! { update_check = true })
+            .env("STARSHIP_CACHE", cache.path().to_string_lossy())
+            .collect();
+
+        assert_eq!(None, actual);
+        cache.close()
+    }
+
+    #[test]
+    fn shows_newer_version() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+        fs::write(
+            cache.path().join(STATE_FILE),
+            r#"{"checked_at": 0, "latest_version": "999.0.0"}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("new_version_available")
+            .config(toml::toml! { update_check = true })
+            .env("STARSHIP_CACHE", cache.path().to_string_lossy())
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint("⬆ 999.0.0")));
+
+        assert_eq!(expected, actual);
+        cache.close()
+    }
+
+    #[test]
+    fn ascii_symbol_without_utf8_locale() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+        fs::write(
+            cache.path().join(STATE_FILE),
+            r#"{"checked_at": 0, "latest_version": "999.0.0"}"#,
+        )?;
+
+        let actual = ModuleRenderer::new("new_version_available")
+            .config(toml::toml! { update_check = true })
+            .env("STARSHIP_CACHE", cache.path().to_string_lossy())
+            .env("LANG", "C")
+            .collect();
+        let expected = Some(format!("{} ", Color::Green.bold().paint("update 999.0.0")));
+
+        assert_eq!(expected, actual);
+        cache.close()
+    }
+
+    #[test]
+    fn rendering_never_checks_for_updates() -> io::Result<()> {
+        let cache = tempfile::tempdir()?;
+        let state = r#"{"checked_at": 0, "latest_version": "999.0.0"}"#;
+        fs::write(cache.path().join(STATE_FILE), state)?;
+        let mut context = Context::new_with_shell_and_path(
+            clap::ArgMatches::default(),
+            Shell::Unknown,
+            cache.path().into(),
+            cache.path().into(),
+        );
+        context.config = StarshipConfig {
+            config: Some(toml::toml! { update_check = true }),
+        };
+        context
+            .env
+            .insert("STARSHIP_CACHE", cache.path().to_string_lossy().to_string());
+
+        // The check is long overdue, but only the prompt starts it once it was printed
+        assert!(crate::modules::handle("new_version_available", &context).is_some());
+        assert!(context.network_log.lock().unwrap().is_empty());
+        assert_eq!(fs::read_to_string(cache.path().join(STATE_FILE))?, state);
+        cache.close()
+    }
+}
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Nim version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("nim");
+    let env = module.env();
     let config = NimConfig::try_load(module.config);
     let is_nim_project = context
         .try_begin_scan()?
@@ -31,7 +32,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => context
-                    .exec_cmd("nim", &["--version"])
+                    .exec_cmd("nim", &["--version"], &env)
                     .map(|command_output| command_output.stdout)
                     .and_then(|nim_version_output| {
                         let nim_version = parse_nim_version(&nim_version_output)?;
@@ -50,7 +51,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `nim`:\n{}", error);
+            crate::error::StarshipError::from_format("nim", error).log();
             return None;
         }
     });
@@ -52,7 +52,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `nix_shell`:\n{}", error);
+            crate::error::StarshipError::from_format("nix_shell", error).log();
             return None;
         }
     });
@@ -1,3 +1,4 @@
+use super::utils::pinned_version::{pinned_version, VersionSource};
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::nodejs::NodejsConfig;
@@ -15,6 +16,7 @@ use std::path::Path;
 /// Creates a module with the current Node.js version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("nodejs");
+    let env = module.env();
     let config = NodejsConfig::try_load(module.config);
     let is_js_project = context
         .try_begin_scan()?
@@ -32,15 +34,23 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
+    let pinned = if config.prefer_pinned {
+        pinned_version(context, &["node", "nodejs"])
+    } else {
+        None
+    };
     let nodejs_version = Lazy::new(|| {
-        context
-            .exec_cmd("node", &["--version"])
-            .map(|cmd| cmd.stdout)
+        pinned.clone().or_else(|| {
+            context
+                .exec_cmd("node", &["--version"], &env)
+                .map(|cmd| cmd.stdout)
+        })
     });
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
+                "version_source" => Some(VersionSource::from_pin(&pinned).as_str()),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -69,7 +79,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `nodejs`:\n{}", error);
+            crate::error::StarshipError::from_format("nodejs", error).log();
             return None;
         }
     });
@@ -93,12 +103,11 @@ fn check_engines_version(nodejs_version: &str, engines_version: Option<String>)
         Err(_e) => return true,
     };
     let re = Regex::new(r"\d+\.\d+\.\d+").unwrap();
-    let version = re
-        .captures(nodejs_version)
-        .unwrap()
-        .get(0)
-        .unwrap()
-        .as_str();
+    // Pinned versions may be partial, e.g. `18`
+    let version = match re.captures(nodejs_version) {
+        Some(captures) => captures.get(0).unwrap().as_str(),
+        None => return true,
+    };
     let v = match Version::parse(version) {
         Ok(v) => v,
         Err(_e) => return true,
@@ -112,7 +121,7 @@ fn format_node_version(node_version: &str, version_format: &str) -> String {
     match VersionFormatter::format_version(version, version_format) {
         Ok(formatted) => formatted,
         Err(error) => {
-            log::warn!("Error formatting `node` version:\n{}", error);
+            crate::log_once!("Error formatting `node` version:\n{}", error);
             format!("v{}", version)
         }
     }
@@ -225,6 +234,17 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    fn folder_with_uppercase_declaration_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("Types.D.TS"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("nodejs").path(dir.path()).collect();
+        let expected = Some(format!("via {}", Color::Green.bold().paint(" v12.0.0 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
     #[test]
     fn folder_with_node_modules() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -274,4 +294,82 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn pinned_in_tool_versions() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("index.js"))?.sync_all()?;
+        fs::write(
+            dir.path().join(".tool-versions"),
+            "# runtimes\nnodejs 18.16.0 # LTS\n",
+        )?;
+
+        let (actual, commands) = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "via [$symbol($version )]($style)\\($version_source\\)"
+            })
+            .collect_with_commands();
+        let expected = Some(format!(
+            "via {}(pin)",
+            Color::Green.bold().paint(" v18.16.0 ")
+        ));
+        assert_eq!(expected, actual);
+        assert!(commands.iter().all(|cmd| !cmd.starts_with("node")));
+        dir.close()
+    }
+
+    #[test]
+    fn pinned_in_parent_mise_toml() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let project = dir.path().join("project");
+        fs::create_dir(&project)?;
+        File::create(project.join("index.js"))?.sync_all()?;
+        fs::write(
+            dir.path().join(".mise.toml"),
+            "[tools]\nnode = \"20.1.0\"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("nodejs").path(&project).collect();
+        let expected = Some(format!("via {}", Color::Green.bold().paint(" v20.1.0 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn ref_pin_falls_back_to_binary() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("index.js"))?.sync_all()?;
+        fs::write(dir.path().join(".tool-versions"), "nodejs ref:v18.16.0\n")?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                format = "$version $version_source"
+            })
+            .collect();
+        let expected = Some(String::from("v12.0.0 binary"));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn pin_ignored_without_prefer_pinned() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("index.js"))?.sync_all()?;
+        fs::write(dir.path().join(".tool-versions"), "nodejs 18.16.0\n")?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .config(toml::toml! {
+                [nodejs]
+                prefer_pinned = false
+            })
+            .collect();
+        let expected = Some(format!("via {}", Color::Green.bold().paint(" v12.0.0 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
 }
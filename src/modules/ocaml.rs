@@ -6,6 +6,7 @@ use std::path::Path;
 use crate::configs::ocaml::OCamlConfig;
 use crate::formatter::StringFormatter;
 use crate::formatter::VersionFormatter;
+use crate::utils::EnvOverlay;
 
 #[derive(Debug, PartialEq)]
 enum SwitchType {
@@ -17,6 +18,7 @@ type OpamSwitch = (SwitchType, String);
 /// Creates a module with the current OCaml version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("ocaml");
+    let env = module.env();
     let config: OCamlConfig = OCamlConfig::try_load(module.config);
     let is_ocaml_project = context
         .try_begin_scan()?
@@ -29,7 +31,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
-    let opam_switch: Lazy<Option<OpamSwitch>, _> = Lazy::new(|| get_opam_switch(context));
+    let opam_switch: Lazy<Option<OpamSwitch>, _> = Lazy::new(|| get_opam_switch(context, &env));
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -60,9 +62,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                         .is_match();
 
                     let ocaml_version = if is_esy_project {
-                        context.exec_cmd("esy", &["ocaml", "-vnum"])?.stdout
+                        context.exec_cmd("esy", &["ocaml", "-vnum"], &env)?.stdout
                     } else {
-                        context.exec_cmd("ocaml", &["-vnum"])?.stdout
+                        context.exec_cmd("ocaml", &["-vnum"], &env)?.stdout
                     };
                     VersionFormatter::format_module_version(
                         module.get_name(),
@@ -79,7 +81,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `ocaml`: \n{}", error);
+            crate::error::StarshipError::from_format("ocaml", error).log();
             return None;
         }
     });
@@ -87,9 +89,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_opam_switch(context: &Context) -> Option<OpamSwitch> {
+fn get_opam_switch(context: &Context, env: &EnvOverlay) -> Option<OpamSwitch> {
     let opam_switch = context
-        .exec_cmd("opam", &["switch", "show", "--safe"])?
+        .exec_cmd("opam", &["switch", "show", "--safe"], env)?
         .stdout;
 
     parse_opam_switch(&opam_switch.trim())
@@ -322,6 +324,7 @@ mod tests {
                 Some(CommandOutput {
                     stdout: String::default(),
                     stderr: String::default(),
+                    status: Some(0),
                 }),
             )
             .path(dir.path())
@@ -342,6 +345,7 @@ mod tests {
                 Some(CommandOutput {
                     stdout: String::from("ocaml-base-compiler.4.10.0\n"),
                     stderr: String::default(),
+                    status: Some(0),
                 }),
             )
             .path(dir.path())
@@ -371,6 +375,7 @@ mod tests {
                 Some(CommandOutput {
                     stdout: String::from("ocaml-base-compiler.4.10.0\n"),
                     stderr: String::default(),
+                    status: Some(0),
                 }),
             )
             .path(dir.path())
@@ -396,6 +401,7 @@ mod tests {
                 Some(CommandOutput {
                     stdout: String::from("/path/to/my-project\n"),
                     stderr: String::default(),
+                    status: Some(0),
                 }),
             )
             .path(dir.path())
@@ -423,6 +429,7 @@ mod tests {
                 Some(CommandOutput {
                     stdout: String::from("/path/to/my-project\n"),
                     stderr: String::default(),
+                    status: Some(0),
                 }),
             )
             .path(dir.path())
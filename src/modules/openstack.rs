@@ -11,9 +11,15 @@ type Project = String;
 
 fn get_osp_project_from_config(context: &Context, osp_cloud: &str) -> Option<Project> {
     // Attempt to follow OpenStack standards for clouds.yaml location:
-    // 1st = $PWD/clouds.yaml, 2nd = $HOME/.config/openstack/clouds.yaml, 3rd = /etc/openstack/clouds.yaml
+    // 1st = ./clouds.yaml, 2nd = $HOME/.config/openstack/clouds.yaml, 3rd = /etc/openstack/clouds.yaml
     let config = [
-        context.get_env("PWD").map(|pwd| pwd + "/clouds.yaml"),
+        Some(
+            context
+                .current_dir
+                .join("clouds.yaml")
+                .display()
+                .to_string(),
+        ),
         context.get_home().map(|home| {
             home.join(".config/openstack/clouds.yaml")
                 .display()
@@ -75,7 +81,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::error!("Error in module `openstack`: \n{}", error);
+            crate::error::StarshipError::from_format("openstack", error).log();
             return None;
         }
     });
@@ -107,7 +113,7 @@ clouds:
 ",
         )?;
         let actual = ModuleRenderer::new("openstack")
-            .env("PWD", dir.path().to_str().unwrap())
+            .path(dir.path())
             .env("OS_CLOUD", "corp")
             .config(toml::toml! {
                 [openstack]
@@ -133,7 +139,7 @@ dummy_yaml
 ",
         )?;
         let actual = ModuleRenderer::new("openstack")
-            .env("PWD", dir.path().to_str().unwrap())
+            .path(dir.path())
             .env("OS_CLOUD", "test")
             .config(toml::toml! {
                 [openstack]
@@ -153,7 +159,7 @@ dummy_yaml
         file.write_all(b"")?;
         drop(file);
         let actual = ModuleRenderer::new("openstack")
-            .env("PWD", dir.path().to_str().unwrap())
+            .path(dir.path())
             .env("OS_CLOUD", "test")
             .config(toml::toml! {
                 [openstack]
@@ -1,7 +1,7 @@
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::package::PackageConfig;
 use crate::formatter::StringFormatter;
-use crate::utils;
+use crate::utils::{self, EnvOverlay};
 
 use quick_xml::events::Event as QXEvent;
 use quick_xml::Reader as QXReader;
@@ -13,8 +13,9 @@ use serde_json as json;
 /// Will display if a version is defined for your Node.js or Rust project (if one exists)
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("package");
+    let env = module.env();
     let config: PackageConfig = PackageConfig::try_load(module.config);
-    let module_version = get_package_version(context, &config)?;
+    let module_version = get_package_version(context, &config, &env)?;
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -36,7 +37,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `package`:\n{}", error);
+            crate::error::StarshipError::from_format("package", error).log();
             return None;
         }
     });
@@ -52,8 +53,8 @@ fn extract_cargo_version(file_contents: &str) -> Option<String> {
     Some(formatted_version)
 }
 
-fn extract_nimble_version(context: &Context) -> Option<String> {
-    let cmd_output = context.exec_cmd("nimble", &["dump", "--json"])?;
+fn extract_nimble_version(context: &Context, env: &EnvOverlay) -> Option<String> {
+    let cmd_output = context.exec_cmd("nimble", &["dump", "--json"], env)?;
 
     let nimble_json: json::Value = json::from_str(&cmd_output.stdout).ok()?;
     let raw_version = nimble_json.get("version")?.as_str()?;
@@ -202,7 +203,11 @@ fn extract_vpkg_version(file_contents: &str) -> Option<String> {
     Some(formatted_version)
 }
 
-fn get_package_version(context: &Context, config: &PackageConfig) -> Option<String> {
+fn get_package_version(
+    context: &Context,
+    config: &PackageConfig,
+    env: &EnvOverlay,
+) -> Option<String> {
     let base_dir = &context.current_dir;
 
     if let Ok(cargo_toml) = utils::read_file(base_dir.join("Cargo.toml")) {
@@ -212,7 +217,7 @@ fn get_package_version(context: &Context, config: &PackageConfig) -> Option<Stri
         .set_extensions(&["nimble"])
         .is_match()
     {
-        extract_nimble_version(context)
+        extract_nimble_version(context, env)
     } else if let Ok(package_json) = utils::read_file(base_dir.join("package.json")) {
         extract_package_version(&package_json, config.display_private)
     } else if let Ok(poetry_toml) = utils::read_file(base_dir.join("pyproject.toml")) {
@@ -334,6 +339,7 @@ license = "MIT"
 "##
                     .to_owned(),
                     stderr: "".to_owned(),
+                    status: Some(0),
                 }),
             )
             .path(project_dir.path())
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current perl version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("perl");
+    let env = module.env();
     let config: PerlConfig = PerlConfig::try_load(module.config);
     let is_perl_project = context
         .try_begin_scan()?
@@ -32,7 +33,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .map(|variable| match variable {
                 "version" => {
                     let perl_version = context
-                        .exec_cmd("perl", &["-e", "printf q#%vd#,$^V;"])?
+                        .exec_cmd("perl", &["-e", "printf q#%vd#,$^V;"], &env)?
                         .stdout;
                     VersionFormatter::format_module_version(
                         module.get_name(),
@@ -49,7 +50,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `perl`:\n{}", error);
+            crate::error::StarshipError::from_format("perl", error).log();
             return None;
         }
     });
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current PHP version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("php");
+    let env = module.env();
     let config: PhpConfig = PhpConfig::try_load(module.config);
     let is_php_project = context
         .try_begin_scan()?
@@ -37,6 +38,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                             "-nr",
                             "echo PHP_MAJOR_VERSION.\".\".PHP_MINOR_VERSION.\".\".PHP_RELEASE_VERSION;",
                         ],
+                        &env,
                     )?.stdout;
                     VersionFormatter::format_module_version(module.get_name(), &php_version, config.version_format).map(Ok)
                 }
@@ -48,7 +50,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `php`:\n{}", error);
+            crate::error::StarshipError::from_format("php", error).log();
             return None;
         }
     });
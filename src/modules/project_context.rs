@@ -0,0 +1,304 @@
+use std::path::Path;
+
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::project_context::ProjectContextConfig;
+use crate::formatter::StringFormatter;
+use crate::segment::{truncate_segments, Segment, Truncation};
+use crate::utils;
+
+/// The file with the note about the project, looked up in the current directory and its parents
+const CONTEXT_FILE: &str = ".starship-context";
+
+/// The note of a `.starship-context` file
+#[derive(Debug, Default, PartialEq)]
+struct ProjectContext {
+    text: String,
+    style: Option<String>,
+    symbol: Option<String>,
+}
+
+/// Creates a module with the note of the nearest `.starship-context` file
+///
+/// The file is either a single line of text, or a small TOML table with `text`, and optionally
+/// `style` and `symbol`. It's only read in trusted directories, see `Context::is_trusted_dir`,
+/// since a repository cloned from someone else could otherwise put anything in the prompt.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("project_context");
+    let config = ProjectContextConfig::try_load(module.config);
+
+    if !context.is_trusted_dir() {
+        return None;
+    }
+
+    let project_context = find_context_file(context)?;
+    let text = shorten(&project_context.text, config.max_length);
+    let symbol = project_context.symbol.as_deref().unwrap_or(config.symbol);
+    let style = project_context.style.as_deref().unwrap_or(config.style);
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "text" => Some(Ok(text.as_str())),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("project_context", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// Reads the nearest `.starship-context` file, walking up from the current directory. Files in
+/// parents that aren't trusted themselves are skipped, see `Context::is_trusted_file`. Like
+/// repositories, the file isn't looked for beyond the repository discovery boundaries.
+fn find_context_file(context: &Context) -> Option<ProjectContext> {
+    for dir in context.discovery_dirs() {
+        let path = dir.join(CONTEXT_FILE);
+        let found = context.spend_fs(|| {
+            read_context_file(&path).filter(|_| {
+                let trusted = context.is_trusted_file(dir, &path);
+                if !trusted {
+                    log::debug!("Ignoring {:?}, which isn't trusted", path);
+                }
+                trusted
+            })
+        })?;
+        if let Some(found) = found {
+            return found;
+        }
+    }
+
+    None
+}
+
+/// `None` if there is no file at `path`, and `Some(None)` if it's there but has no text
+fn read_context_file(path: &Path) -> Option<Option<ProjectContext>> {
+    if !path.is_file() {
+        return None;
+    }
+
+    Some(
+        utils::read_file(path)
+            .ok()
+            .and_then(|contents| parse(&contents)),
+    )
+}
+
+fn parse(contents: &str) -> Option<ProjectContext> {
+    let project_context = match toml::from_str::<toml::value::Table>(contents) {
+        Ok(table) if table.contains_key("text") => {
+            let get = |key: &str| table.get(key).and_then(toml::Value::as_str).map(sanitize);
+            ProjectContext {
+                text: get("text").unwrap_or_default(),
+                style: get("style"),
+                symbol: get("symbol"),
+            }
+        }
+        _ => ProjectContext {
+            text: sanitize(contents.lines().next().unwrap_or_default()),
+            ..Default::default()
+        },
+    };
+
+    let text = project_context.text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(ProjectContext {
+        text: text.to_owned(),
+        ..project_context
+    })
+}
+
+/// Removes the control characters, so that the file can't move the cursor or change the
+/// colors of the prompt with escape sequences
+fn sanitize(value: &str) -> String {
+    value.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Shortens `text` to `max_length` columns, ending it with `…` when it's cut
+fn shorten(text: &str, max_length: usize) -> String {
+    truncate_segments(
+        vec![Segment::new(None, text)],
+        max_length,
+        Truncation::End,
+        "…",
+    )
+    .into_iter()
+    .map(|segment| segment.value)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use ansi_term::Color;
+    use std::fs;
+    use std::io;
+
+    #[test]
+    fn no_context_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let actual = ModuleRenderer::new("project_context")
+            .path(dir.path())
+            .owned_dir(true)
+            .collect();
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn plain_text_in_parent_dir() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sub_dir = dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+        fs::write(
+            dir.path().join(CONTEXT_FILE),
+            "staging cluster\nsecond line\n",
+        )?;
+
+        let actual = ModuleRenderer::new("project_context")
+            .path(&sub_dir)
+            .owned_dir(true)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Yellow.bold().paint("staging cluster")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn not_found_beyond_discovery_boundary() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sub_dir = dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+        fs::write(dir.path().join(CONTEXT_FILE), "staging cluster")?;
+        let boundary = dir.path().to_string_lossy().into_owned();
+
+        let actual = ModuleRenderer::new("project_context")
+            .config(toml::toml! {
+                repo_discovery_boundaries = [boundary]
+            })
+            .path(&sub_dir)
+            .owned_dir(true)
+            .collect();
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn toml_with_style_and_symbol() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join(CONTEXT_FILE),
+            "text = \"prod\"\nstyle = \"bold red\"\nsymbol = \"⚠ \"\n",
+        )?;
+
+        let actual = ModuleRenderer::new("project_context")
+            .path(dir.path())
+            .owned_dir(true)
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("⚠ prod")));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn ignored_in_untrusted_dir() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(CONTEXT_FILE), "staging cluster")?;
+
+        let actual = ModuleRenderer::new("project_context")
+            .path(dir.path())
+            .owned_dir(false)
+            .collect();
+        assert_eq!(actual, None);
+        dir.close()
+    }
+
+    #[test]
+    fn ignored_in_untrusted_parent_dir() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let project_dir = dir.path().join("project");
+        let sub_dir = project_dir.join("src");
+        fs::create_dir_all(&sub_dir)?;
+        fs::write(dir.path().join(CONTEXT_FILE), "planted by someone else")?;
+
+        let config = toml::toml! {
+            trusted_directories = [(project_dir.to_string_lossy())]
+        };
+        let actual = ModuleRenderer::new("project_context")
+            .path(&sub_dir)
+            .config(config.clone())
+            .owned_dir(false)
+            .collect();
+        assert_eq!(actual, None);
+
+        fs::write(project_dir.join(CONTEXT_FILE), "staging cluster")?;
+        let actual = ModuleRenderer::new("project_context")
+            .path(&sub_dir)
+            .config(config)
+            .owned_dir(false)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Yellow.bold().paint("staging cluster")
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn oversized_text_is_truncated() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(CONTEXT_FILE), "a".repeat(4096))?;
+
+        let actual = ModuleRenderer::new("project_context")
+            .path(dir.path())
+            .owned_dir(true)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Yellow.bold().paint(format!("{}…", "a".repeat(31)))
+        ));
+        assert_eq!(actual, expected);
+        dir.close()
+    }
+
+    #[test]
+    fn strips_control_characters() {
+        // Without the escape character, what's left of an escape sequence is plain text
+        let parsed = parse("\u{1b}[31mred\u{7}\tzone\r\n");
+        assert_eq!(
+            parsed.map(|context| context.text),
+            Some("[31mredzone".to_owned())
+        );
+        assert_eq!(parse("  \n"), None);
+        assert_eq!(
+            parse("text = \"\\u001b]0;title\\u0007\""),
+            Some(ProjectContext {
+                text: "]0;title".to_owned(),
+                ..Default::default()
+            })
+        );
+    }
+}
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current PureScript version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("purescript");
+    let env = module.env();
     let config: PureScriptConfig = PureScriptConfig::try_load(module.config);
     let is_purs_project = context
         .try_begin_scan()?
@@ -31,10 +32,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let purs_version = context.exec_cmd("purs", &["--version"])?.stdout;
+                    let purs_version = parse_purs_version(
+                        &context.exec_cmd("purs", &["--version"], &env)?.stdout,
+                    )?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
-                        &purs_version.trim(),
+                        &purs_version,
                         config.version_format,
                     )
                     .map(Ok)
@@ -47,7 +50,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `purescript`:\n{}", error);
+            crate::error::StarshipError::from_format("purescript", error).log();
             return None;
         }
     });
@@ -55,8 +58,17 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+fn parse_purs_version(purs_stdout: &str) -> Option<String> {
+    // purs output is the version, which development builds follow with
+    // a description, e.g. `0.15.0 [development build; commit: abc123]`
+    let version = purs_stdout.split_whitespace().next()?;
+
+    Some(version.to_string())
+}
+
 #[cfg(test)]
 mod tests {
+    use super::parse_purs_version;
     use crate::test::ModuleRenderer;
     use ansi_term::Color;
     use std::fs::File;
@@ -92,4 +104,25 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    #[test]
+    fn folder_with_spago_yaml() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("spago.yaml"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("purescript").path(dir.path()).collect();
+        let expected = Some(format!("via {}", Color::White.bold().paint("<=> v0.13.5 ")));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_parse_purs_version() {
+        assert_eq!(parse_purs_version("0.13.5\n"), Some("0.13.5".to_string()));
+        assert_eq!(
+            parse_purs_version("0.15.0 [development build; commit: abc123]\n"),
+            Some("0.15.0".to_string())
+        );
+        assert_eq!(parse_purs_version(""), None);
+    }
 }
@@ -1,14 +1,17 @@
 use ini::Ini;
 use std::path::Path;
 
+use super::utils::pinned_version::{pinned_version, VersionSource};
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::python::PythonConfig;
 use crate::formatter::StringFormatter;
 use crate::formatter::VersionFormatter;
+use crate::utils::EnvOverlay;
 
 /// Creates a module with the current Python version and, if active, virtual environment.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("python");
+    let env = module.env();
     let config: PythonConfig = PythonConfig::try_load(module.config);
 
     let is_py_project = context
@@ -30,10 +33,18 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         ""
     };
 
+    // `pyenv version-name` takes precedence, as it is what the user asked for
+    let pinned = if config.prefer_pinned && !config.pyenv_version_name {
+        pinned_version(context, &["python"])
+    } else {
+        None
+    };
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
+                "version_source" => Some(VersionSource::from_pin(&pinned).as_str()),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -41,7 +52,15 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => get_python_version(context, &config).map(Ok),
+                "version" => match &pinned {
+                    Some(version) => VersionFormatter::format_module_version(
+                        module.get_name(),
+                        version,
+                        config.version_format,
+                    ),
+                    None => get_python_version(context, &config, &env),
+                }
+                .map(Ok),
                 "virtualenv" => {
                     let virtual_env = get_python_virtual_env(context);
                     virtual_env.as_ref().map(|e| Ok(e.trim().to_string()))
@@ -55,7 +74,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `python`:\n{}", error);
+            crate::error::StarshipError::from_format("python", error).log();
             return None;
         }
     });
@@ -63,16 +82,20 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_python_version(context: &Context, config: &PythonConfig) -> Option<String> {
+fn get_python_version(
+    context: &Context,
+    config: &PythonConfig,
+    env: &EnvOverlay,
+) -> Option<String> {
     if config.pyenv_version_name {
-        let version_name = context.exec_cmd("pyenv", &["version-name"])?.stdout;
+        let version_name = context.exec_cmd("pyenv", &["version-name"], env)?.stdout;
         return Some(version_name.trim().to_string());
     };
     let version = config
         .python_binary
         .0
         .iter()
-        .find_map(|binary| context.exec_cmd(binary, &["--version"]))
+        .find_map(|binary| context.exec_cmd(binary, &["--version"], env))
         .map(|output| {
             if output.stdout.is_empty() {
                 output.stderr
@@ -94,7 +117,7 @@ fn format_python_version(python_version: &str, version_format: &str) -> Option<S
     match VersionFormatter::format_version(version, version_format) {
         Ok(formatted) => Some(formatted),
         Err(error) => {
-            log::warn!("Error formatting `python` version:\n{}", error);
+            crate::log_once!("Error formatting `python` version:\n{}", error);
             Some(format!("v{}", version))
         }
     }
@@ -427,6 +450,30 @@ prompt = '(foo)'
         dir.close()
     }
 
+    #[test]
+    fn pyenv_with_env_overlay() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".python-version"))?.sync_all()?;
+
+        let (actual, commands) = ModuleRenderer::new("python")
+            .path(dir.path())
+            .config(toml::toml! {
+                [python]
+                pyenv_version_name = true
+                [python.env]
+                PYENV_VERSION = "3.9"
+            })
+            .collect_with_commands();
+
+        let expected = Some(format!(
+            "via {}",
+            Color::Yellow.bold().paint("🐍 pyenv system ")
+        ));
+        assert_eq!(expected, actual);
+        assert_eq!(commands, vec!["PYENV_VERSION=3.9 pyenv version-name"]);
+        dir.close()
+    }
+
     fn check_python2_renders(dir: &tempfile::TempDir, starship_config: Option<toml::Value>) {
         let config = starship_config.unwrap_or(toml::toml! {
             [python]
@@ -2,10 +2,12 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::red::RedConfig;
 use crate::formatter::StringFormatter;
+use crate::formatter::VersionFormatter;
 
 /// Creates a module with the current  Red version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("red");
+    let env = module.env();
     let config = RedConfig::try_load(module.config);
     let is_red_project = context
         .try_begin_scan()?
@@ -29,10 +31,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => context
-                    .exec_cmd("red", &["--version"])
-                    .map(|output| parse_red_version(output.stdout.trim()))
-                    .map(Ok),
+                "version" => {
+                    let red_version =
+                        parse_red_version(&context.exec_cmd("red", &["--version"], &env)?.stdout)?;
+                    VersionFormatter::format_module_version(
+                        module.get_name(),
+                        &red_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 _ => None,
             })
             .parse(None)
@@ -41,7 +49,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `red`:\n{}", error);
+            crate::error::StarshipError::from_format("red", error).log();
             return None;
         }
     });
@@ -49,8 +57,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn parse_red_version(red_version: &str) -> String {
-    format!("v{}", red_version)
+fn parse_red_version(red_version: &str) -> Option<String> {
+    // red output is either only the version, e.g. `0.6.4`, or
+    // `Red 0.6.4 for Linux built 19-Mar-2022/8:43:22+01:00`
+    let version = red_version
+        .split_whitespace()
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))?;
+
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -64,7 +78,12 @@ mod tests {
     #[test]
     fn test_parse_red_version() {
         const OUTPUT: &str = "0.6.4\n";
-        assert_eq!(parse_red_version(OUTPUT.trim()), "v0.6.4".to_string())
+        assert_eq!(parse_red_version(OUTPUT), Some("0.6.4".to_string()));
+        assert_eq!(
+            parse_red_version("Red 0.6.4 for Linux built 19-Mar-2022/8:43:22+01:00\n"),
+            Some("0.6.4".to_string())
+        );
+        assert_eq!(parse_red_version(""), None);
     }
 
     #[test]
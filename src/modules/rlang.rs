@@ -3,9 +3,11 @@ use crate::formatter::VersionFormatter;
 
 use crate::configs::rlang::RLangConfig;
 use crate::formatter::StringFormatter;
+use crate::utils::EnvOverlay;
 
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("rlang");
+    let env = module.env();
     let config: RLangConfig = RLangConfig::try_load(module.config);
 
     let is_r_project = context
@@ -30,7 +32,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let r_version = get_r_version(context)?;
+                    let r_version = get_r_version(context, &env)?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &r_version,
@@ -46,7 +48,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `rlang`:\n{}", error);
+            crate::error::StarshipError::from_format("rlang", error).log();
             return None;
         }
     });
@@ -54,8 +56,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_r_version(context: &Context) -> Option<String> {
-    let r_version = context.exec_cmd("R", &["--version"])?.stderr;
+fn get_r_version(context: &Context, env: &EnvOverlay) -> Option<String> {
+    let r_version = context.exec_cmd("R", &["--version"], env)?.stderr;
     parse_version(&r_version)
 }
 
@@ -1,3 +1,4 @@
+use super::utils::pinned_version::{pinned_version, VersionSource};
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::ruby::RubyConfig;
@@ -10,6 +11,7 @@ use crate::formatter::{StringFormatter, VersionFormatter};
 ///     - Current directory contains a `Gemfile` or `.ruby-version` file
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("ruby");
+    let env = module.env();
     let config = RubyConfig::try_load(module.config);
 
     let is_rb_project = context
@@ -23,10 +25,17 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
+    let pinned = if config.prefer_pinned {
+        pinned_version(context, &["ruby"])
+    } else {
+        None
+    };
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
+                "version_source" => Some(VersionSource::from_pin(&pinned).as_str()),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -34,10 +43,17 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => format_ruby_version(
-                    &context.exec_cmd("ruby", &["-v"])?.stdout,
-                    config.version_format,
-                )
+                "version" => match &pinned {
+                    Some(version) => VersionFormatter::format_module_version(
+                        module.get_name(),
+                        version,
+                        config.version_format,
+                    ),
+                    None => format_ruby_version(
+                        &context.exec_cmd("ruby", &["-v"], &env)?.stdout,
+                        config.version_format,
+                    ),
+                }
                 .map(Ok),
                 _ => None,
             })
@@ -47,7 +63,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `ruby`:\n{}", error);
+            crate::error::StarshipError::from_format("ruby", error).log();
             return None;
         }
     });
@@ -69,7 +85,7 @@ fn format_ruby_version(ruby_version: &str, version_format: &str) -> Option<Strin
     match VersionFormatter::format_version(version, version_format) {
         Ok(formatted) => Some(formatted),
         Err(error) => {
-            log::warn!("Error formatting `ruby` version:\n{}", error);
+            crate::log_once!("Error formatting `ruby` version:\n{}", error);
             Some(format!("v{}", version))
         }
     }
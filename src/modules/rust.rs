@@ -1,4 +1,3 @@
-use std::fs;
 use std::path::Path;
 use std::process::{Command, Output};
 
@@ -8,6 +7,7 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::rust::RustConfig;
 use crate::formatter::{StringFormatter, VersionFormatter};
+use crate::utils;
 
 /// Creates a module with the current Rust version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
@@ -47,7 +47,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `rust`:\n{}", error);
+            crate::error::StarshipError::from_format("rust", error).log();
             return None;
         }
     });
@@ -138,7 +138,7 @@ fn find_rust_toolchain_file(context: &Context) -> Option<String> {
     }
 
     fn read_channel(path: &Path) -> Option<String> {
-        let contents = fs::read_to_string(path).ok()?;
+        let contents = utils::read_file(path).ok()?;
 
         match contents.lines().count() {
             0 => None,
@@ -213,7 +213,7 @@ fn format_rustc_version(rustc_version: &str, version_format: &str) -> Option<Str
     match VersionFormatter::format_version(version, version_format) {
         Ok(formatted) => Some(formatted),
         Err(error) => {
-            log::warn!("Error formatting `rust` version:\n{}", error);
+            crate::log_once!("Error formatting `rust` version:\n{}", error);
             Some(format!("v{}", version))
         }
     }
@@ -231,6 +231,7 @@ enum RustupRunRustcVersionOutcome {
 mod tests {
     use crate::context::Shell;
     use once_cell::sync::Lazy;
+    use std::fs;
     use std::io;
     use std::process::{ExitStatus, Output};
 
@@ -351,6 +352,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_rust_toolchain_file_with_bom_and_crlf() -> io::Result<()> {
+        for contents in [
+            "\u{feff}1.34.0\r\n",
+            "\u{feff}[toolchain]\r\nchannel = \"1.34.0\"\r\n",
+        ] {
+            let dir = tempfile::tempdir()?;
+            fs::write(dir.path().join("rust-toolchain"), contents)?;
+
+            let context = Context::new_with_shell_and_path(
+                Default::default(),
+                Shell::Unknown,
+                dir.path().into(),
+                dir.path().into(),
+            );
+
+            assert_eq!(
+                find_rust_toolchain_file(&context),
+                Some("1.34.0".to_owned())
+            );
+            dir.close()?;
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_find_rust_toolchain_file() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
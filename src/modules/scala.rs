@@ -3,9 +3,11 @@ use crate::formatter::StringFormatter;
 
 use super::{Context, Module, RootModuleConfig};
 use crate::formatter::VersionFormatter;
+use crate::utils::EnvOverlay;
 
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("scala");
+    let env = module.env();
     let config: ScalaConfig = ScalaConfig::try_load(module.config);
 
     let is_scala_project = context
@@ -31,7 +33,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let scala_version = get_scala_version(context)?;
+                    let scala_version = get_scala_version(context, &env)?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &scala_version,
@@ -47,7 +49,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `scala`:\n{}", error);
+            crate::error::StarshipError::from_format("scala", error).log();
             return None;
         }
     });
@@ -55,8 +57,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn get_scala_version(context: &Context) -> Option<String> {
-    let output = context.exec_cmd("scalac", &["-version"])?;
+fn get_scala_version(context: &Context, env: &EnvOverlay) -> Option<String> {
+    let output = context.exec_cmd("scalac", &["-version"], env)?;
     let scala_version = if output.stdout.is_empty() {
         output.stderr
     } else {
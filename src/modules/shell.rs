@@ -45,7 +45,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `shell`: \n{}", error);
+            crate::error::StarshipError::from_format("shell", error).log();
             return None;
         }
     });
@@ -53,7 +53,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `shlvl`:\n{}", error);
+            crate::error::StarshipError::from_format("shlvl", error).log();
             return None;
         }
     });
@@ -32,7 +32,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `singularity`: \n{}", error);
+            crate::error::StarshipError::from_format("singularity", error).log();
             return None;
         }
     });
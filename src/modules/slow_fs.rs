@@ -0,0 +1,119 @@
+use super::{Context, Module, RootModuleConfig};
+
+use crate::configs::slow_fs::SlowFsConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module that shows when filesystem checks were skipped because they took longer
+/// than `fs_timeout_ms`, which explains why other modules are missing
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    if context.config.get_root_config().fs_timeout_ms == 0 {
+        return None;
+    }
+
+    // Most modules depend on these, so they are shared with them rather than done twice. The
+    // budget can only be exhausted afterwards by checks this module doesn't know about.
+    let _ = context.dir_contents();
+    let _ = context.get_repo();
+    if !context.is_fs_slow() {
+        return None;
+    }
+
+    let mut module = context.new_module("slow_fs");
+    let config: SlowFsConfig = SlowFsConfig::try_load(module.config);
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .parse(None)
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            crate::error::StarshipError::from_format("slow_fs", error).log();
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use ansi_term::Color;
+    use std::io;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::fs_budget::SteppingClock;
+    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+
+    #[test]
+    fn hidden_without_fs_timeout() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("slow_fs")
+            .path(repo_dir.path())
+            .fs_clock(Arc::new(SteppingClock::new(Duration::from_secs(1))))
+            .collect();
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn hidden_on_fast_fs() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("slow_fs")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                fs_timeout_ms = 100
+            })
+            .fs_clock(Arc::new(SteppingClock::new(Duration::from_millis(1))))
+            .collect();
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shown_on_slow_fs() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("slow_fs")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                fs_timeout_ms = 100
+                [slow_fs]
+                symbol = "slow fs"
+            })
+            .fs_clock(Arc::new(SteppingClock::new(Duration::from_millis(150))))
+            .collect();
+        let expected = Some(format!("{}", Color::Yellow.bold().paint("slow fs")));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn ascii_symbol_without_utf8_locale() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        let actual = ModuleRenderer::new("slow_fs")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                fs_timeout_ms = 100
+            })
+            .env("LANG", "C")
+            .fs_clock(Arc::new(SteppingClock::new(Duration::from_millis(150))))
+            .collect();
+        let expected = Some(format!("{}", Color::Yellow.bold().paint("slow ")));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+}
@@ -8,25 +8,23 @@ type SignalNumber = u32;
 
 /// Creates a module with the status of the last command
 ///
-/// Will display the status only if it is not 0
+/// Will display the status only if it is not 0, or if `success_symbol` is set
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let exit_code = context
-        .properties
-        .get("status_code")
-        .map_or("0", String::as_str);
+    let exit_code = context.get_property("status_code");
+    let exit_code = exit_code.as_deref().unwrap_or("0");
 
-    if exit_code == "0" {
-        None
-    } else {
-        let mut module = context.new_module("status");
-        let config = StatusConfig::try_load(module.config);
+    let mut module = context.new_module("status");
+    let config = StatusConfig::try_load(module.config);
 
-        // As we default to disabled=true, we have to check here after loading our config module,
-        // before it was only checking against whatever is in the config starship.toml
-        if config.disabled {
-            return None;
-        };
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    };
 
+    if exit_code == "0" && config.success_symbol.is_empty() {
+        None
+    } else {
         let exit_code_int: ExitCode = match exit_code.parse() {
             Ok(i) => i,
             Err(_) => return None,
@@ -52,6 +50,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             formatter
                 .map_meta(|var, _| match var {
                     "symbol" => match exit_code_int {
+                        0 => Some(config.success_symbol),
                         126 if config.map_symbol => Some(config.not_executable_symbol),
                         127 if config.map_symbol => Some(config.not_found_symbol),
                         130 if config.recognize_signal_code && config.map_symbol => {
@@ -68,7 +67,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     _ => None,
                 })
                 .map_style(|variable| match variable {
-                    "style" => Some(Ok(config.style)),
+                    "style" => Some(Ok(status_style(&config, exit_code_int))),
                     _ => None,
                 })
                 .map(|variable| match variable {
@@ -85,8 +84,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
         module.set_segments(match parsed {
             Ok(segments) => segments,
-            Err(_error) => {
-                log::warn!("Error parsing format string in `status.format`");
+            Err(error) => {
+                crate::error::StarshipError::from_format("status", error).log();
                 return None;
             }
         });
@@ -94,6 +93,52 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
+/// The classes of an exit code that `style_map` accepts, most specific first
+fn status_classes(ex: ExitCode, recognize_signal_code: bool) -> Vec<&'static str> {
+    match ex {
+        0 => vec!["success"],
+        126 => vec!["not_executable", "error"],
+        127 => vec!["not_found", "error"],
+        x if recognize_signal_code && status_to_signal(x).is_some() => vec!["signal", "error"],
+        _ => vec!["error"],
+    }
+}
+
+/// Parses a `style_map` range key such as `"1-2"` into its inclusive bounds
+fn parse_status_range(key: &str) -> Option<(ExitCode, ExitCode)> {
+    let (start, end) = key.split_once('-')?;
+    let (start, end) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+    if start <= end {
+        Some((start, end))
+    } else {
+        None
+    }
+}
+
+/// Resolves the style of an exit code from `style_map`, most specific key first:
+/// the exact code (e.g. `"130"`), then the narrowest range containing it (e.g. `"1-2"`),
+/// then its class (e.g. `"signal"`), falling back to `style`
+fn status_style<'a>(config: &StatusConfig<'a>, ex: ExitCode) -> &'a str {
+    if let Some(style) = config.style_map.get(&ex.to_string()) {
+        return style;
+    }
+
+    let narrowest_range = config
+        .style_map
+        .iter()
+        .filter_map(|(key, style)| Some((parse_status_range(key)?, *style)))
+        .filter(|((start, end), _)| (*start..=*end).contains(&ex))
+        .min_by_key(|((start, end), _)| (end - start, *start));
+    if let Some((_, style)) = narrowest_range {
+        return style;
+    }
+
+    status_classes(ex, config.recognize_signal_code)
+        .iter()
+        .find_map(|class| config.style_map.get(*class).copied())
+        .unwrap_or(config.style)
+}
+
 fn status_common_meaning(ex: ExitCode) -> Option<&'static str> {
     // Over 128 are Signal exit code
     if ex > 128 {
@@ -147,7 +192,10 @@ fn status_signal_name(signal: SignalNumber) -> Option<&'static str> {
 #[cfg(test)]
 mod tests {
     use ansi_term::Color;
+    use std::sync::Arc;
 
+    use crate::error::ErrorCategory;
+    use crate::logger::{self, ErrorCounter};
     use crate::test::ModuleRenderer;
 
     #[test]
@@ -291,7 +339,8 @@ mod tests {
         let exit_values_name = ["🔴", "🚫", "🔍", "🧱", "⚡"];
 
         for (status, name) in exit_values.iter().zip(exit_values_name.iter()) {
-            let expected = Some(name.to_string());
+            // The emoji are double-width, so `symbol_padding = "auto"` appends a space
+            let expected = Some(format!("{} ", name));
             let actual = ModuleRenderer::new("status")
                 .config(toml::toml! {
                     [status]
@@ -317,7 +366,8 @@ mod tests {
         let exit_values_name = ["🔴", "🚫", "🔍", "🔴", "🔴"];
 
         for (status, name) in exit_values.iter().zip(exit_values_name.iter()) {
-            let expected = Some(name.to_string());
+            // The emoji are double-width, so `symbol_padding = "auto"` appends a space
+            let expected = Some(format!("{} ", name));
             let actual = ModuleRenderer::new("status")
                 .config(toml::toml! {
                     [status]
@@ -336,4 +386,122 @@ mod tests {
             assert_eq!(expected, actual);
         }
     }
+
+    #[test]
+    fn style_map_precedence() {
+        let cases = [
+            (130, Color::Purple.bold()),
+            (131, Color::Blue.bold()),
+            (2, Color::Yellow.bold()),
+            (3, Color::Cyan.bold()),
+            (127, Color::White.bold()),
+            (101, Color::Red.bold()),
+        ];
+
+        for (status, style) in cases.iter() {
+            let expected = Some(style.paint(status.to_string()).to_string());
+            let actual = ModuleRenderer::new("status")
+                .config(toml::toml! {
+                    [status]
+                    format = "[$status]($style)"
+                    disabled = false
+                    [status.style_map]
+                    "130" = "bold purple"
+                    signal = "bold blue"
+                    "1-2" = "bold yellow"
+                    "1-9" = "bold cyan"
+                    not_found = "bold white"
+                    error = "bold red"
+                })
+                .status(*status)
+                .collect();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn style_map_falls_back_to_style() {
+        let expected = Some(Color::Green.paint("1").to_string());
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                format = "[$status]($style)"
+                style = "green"
+                disabled = false
+                [status.style_map]
+                success = "bold green"
+            })
+            .status(1)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn success_symbol() {
+        let config = toml::toml! {
+            [status]
+            format = "[$symbol( $common_meaning)]($style) "
+            success_symbol = "✔"
+            disabled = false
+            [status.style_map]
+            success = "bold green"
+        };
+
+        let expected = Some(format!("{} ", Color::Green.bold().paint("✔")));
+        let actual = ModuleRenderer::new("status")
+            .config(config.clone())
+            .status(0)
+            .collect();
+        assert_eq!(expected, actual);
+
+        let expected = Some(format!("{} ", Color::Red.bold().paint("✖ ERROR")));
+        let actual = ModuleRenderer::new("status")
+            .config(config)
+            .status(1)
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn empty_success_symbol_collapses() {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                format = "[$symbol( $common_meaning)]($style) "
+                success_symbol = ""
+                disabled = false
+                [status.style_map]
+                success = "bold green"
+            })
+            .status(0)
+            .collect();
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn invalid_format_is_a_config_error() {
+        let errors = Arc::new(ErrorCounter::default());
+        let actual = logger::counting_errors(&errors, || {
+            ModuleRenderer::new("status")
+                .config(toml::toml! {
+                    [status]
+                    format = "[$symbol"
+                    disabled = false
+                })
+                .status(1)
+                .collect()
+        });
+
+        assert_eq!(None, actual);
+        assert_eq!(errors.count(ErrorCategory::Config), 1);
+    }
+
+    #[test]
+    fn parse_ranges() {
+        assert_eq!(super::parse_status_range("1-2"), Some((1, 2)));
+        assert_eq!(super::parse_status_range("126 - 255"), Some((126, 255)));
+        assert_eq!(super::parse_status_range("5-1"), None);
+        assert_eq!(super::parse_status_range("signal"), None);
+        assert_eq!(super::parse_status_range("130"), None);
+    }
 }
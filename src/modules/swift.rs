@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Swift version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("swift");
+    let env = module.env();
     let config: SwiftConfig = SwiftConfig::try_load(module.config);
 
     let is_swift_project = context
@@ -32,8 +33,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let swift_version =
-                        parse_swift_version(&context.exec_cmd("swift", &["--version"])?.stdout)?;
+                    let swift_version = parse_swift_version(
+                        &context.exec_cmd("swift", &["--version"], &env)?.stdout,
+                    )?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &swift_version,
@@ -49,7 +51,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `swift`:\n{}", error);
+            crate::error::StarshipError::from_format("swift", error).log();
             return None;
         }
     });
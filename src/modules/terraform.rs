@@ -11,6 +11,7 @@ use std::path::PathBuf;
 /// Creates a module with the current Terraform version and workspace
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("terraform");
+    let env = module.env();
     let config: TerraformConfig = TerraformConfig::try_load(module.config);
 
     let is_terraform_project = context
@@ -35,18 +36,25 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => {
+                "workspace" => get_terraform_workspace(context).map(Ok),
+                _ => None,
+            })
+            // `terraform version` is slow, so it only runs if `$version` is rendered
+            .map_lazy(|variable| match variable {
+                "version" => Some(|| {
                     let terraform_version = get_terraform_version(
-                        &context.exec_cmd("terraform", &["version"])?.stdout.as_str(),
+                        &context
+                            .exec_cmd("terraform", &["version"], &env)?
+                            .stdout
+                            .as_str(),
                     )?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &terraform_version,
                         config.version_format,
                     )
-                }
-                .map(Ok),
-                "workspace" => get_terraform_workspace(context).map(Ok),
+                    .map(Ok)
+                }),
                 _ => None,
             })
             .parse(None)
@@ -55,7 +63,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `terraform`:\n{}", error);
+            crate::error::StarshipError::from_format("terraform", error).log();
             return None;
         }
     });
@@ -78,7 +86,7 @@ fn get_terraform_workspace(context: &Context) -> Option<String> {
     };
     match utils::read_file(datadir.join("environment")) {
         Err(ref e) if e.kind() == io::ErrorKind::NotFound => Some("default".to_string()),
-        Ok(s) => Some(s),
+        Ok(s) => Some(s.trim().to_string()),
         _ => None,
     }
 }
@@ -163,6 +171,57 @@ is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
         dir.close()
     }
 
+    #[test]
+    fn version_asks_for_network_access() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join(".terraform"))?;
+
+        let (actual, network_access) = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version]($style) "
+            })
+            .collect_with_network_access();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠 v0.12.14")
+        ));
+        assert_eq!(expected, actual);
+        assert_eq!(network_access, ["terraform version check"]);
+
+        // The default format only shows the workspace, which is read from the files
+        let (_, network_access) = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .collect_with_network_access();
+        assert!(network_access.is_empty());
+        dir.close()
+    }
+
+    #[test]
+    fn folder_with_dotterraform_with_version_offline() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tf_dir = dir.path().join(".terraform");
+        fs::create_dir(&tf_dir)?;
+
+        let (actual, commands) = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                offline = true
+                [terraform]
+                format = "via [$symbol$version $workspace]($style) "
+            })
+            .collect_with_commands();
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Fixed(105).bold().paint("💠  default")
+        ));
+        assert_eq!(expected, actual);
+        assert!(commands.is_empty());
+        dir.close()
+    }
+
     #[test]
     fn folder_with_dotterraform_with_version_with_environment() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
@@ -214,6 +273,28 @@ is 0.12.14. You can update by downloading from www.terraform.io/downloads.html
         dir.close()
     }
 
+    #[test]
+    fn version_is_only_queried_when_used() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.tf"))?;
+
+        let (_, commands) = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .collect_with_commands();
+        assert!(commands.is_empty());
+
+        let (_, commands) = ModuleRenderer::new("terraform")
+            .path(dir.path())
+            .config(toml::toml! {
+                [terraform]
+                format = "via [$symbol$version]($style) "
+            })
+            .collect_with_commands();
+        assert_eq!(commands, vec![String::from("terraform version")]);
+
+        dir.close()
+    }
+
     #[test]
     fn folder_with_workspace_override() -> io::Result<()> {
         let dir = tempfile::tempdir()?;
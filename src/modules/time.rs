@@ -1,4 +1,4 @@
-use chrono::{DateTime, FixedOffset, Local, NaiveTime, Utc};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveTime, TimeZone, Utc};
 
 use super::{Context, Module, RootModuleConfig};
 use crate::configs::time::TimeConfig;
@@ -30,18 +30,22 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         time_format
     );
 
+    let names = get_locale(context, config.locale)
+        .as_deref()
+        .and_then(get_time_names);
+
     let formatted_time_string = if config.utc_time_offset != "local" {
-        match create_offset_time_string(Utc::now(), &config.utc_time_offset, &time_format) {
+        match create_offset_time_string(Utc::now(), &config.utc_time_offset, &time_format, names) {
             Ok(formatted_string) => formatted_string,
             Err(_) => {
-                log::warn!(
+                crate::log_once!(
                     "Invalid utc_time_offset configuration provided! Falling back to \"local\"."
                 );
-                format_time(&time_format, Local::now())
+                format_time(&time_format, Local::now(), names)
             }
         }
     } else {
-        format_time(&time_format, Local::now())
+        format_time(&time_format, Local::now(), names)
     };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
@@ -60,7 +64,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `time`: \n{}", error);
+            crate::error::StarshipError::from_format("time", error).log();
             return None;
         }
     });
@@ -72,6 +76,7 @@ fn create_offset_time_string(
     utc_time: DateTime<Utc>,
     utc_time_offset_str: &str,
     time_format: &str,
+    names: Option<&TimeNames>,
 ) -> Result<String, &'static str> {
     // Using floats to allow 30/45 minute offsets: https://www.timeanddate.com/time/time-zones-interesting.html
     let utc_time_offset_in_hours = utc_time_offset_str.parse::<f32>().unwrap_or(
@@ -86,7 +91,7 @@ fn create_offset_time_string(
         let target_time = utc_time.with_timezone(&timezone_offset);
         log::trace!("Time in target timezone now is {}", target_time);
 
-        Ok(format_time_fixed_offset(&time_format, target_time))
+        Ok(format_time_fixed_offset(&time_format, target_time, names))
     } else {
         Err("Invalid timezone offset.")
     }
@@ -94,14 +99,362 @@ fn create_offset_time_string(
 
 /// Format a given time into the given string. This function should be referentially
 /// transparent, which makes it easy to test (unlike anything involving the actual time)
-fn format_time(time_format: &str, local_time: DateTime<Local>) -> String {
-    local_time.format(time_format).to_string()
+fn format_time(
+    time_format: &str,
+    local_time: DateTime<Local>,
+    names: Option<&TimeNames>,
+) -> String {
+    let time_format = localize_format(time_format, &local_time, names);
+    local_time.format(&time_format).to_string()
+}
+
+fn format_time_fixed_offset(
+    time_format: &str,
+    utc_time: DateTime<FixedOffset>,
+    names: Option<&TimeNames>,
+) -> String {
+    let time_format = localize_format(time_format, &utc_time, names);
+    utc_time.format(&time_format).to_string()
+}
+
+/// The locale for the names of weekdays and months: the `locale` option if it's set,
+/// otherwise the locale of the environment
+fn get_locale(context: &Context, locale: &str) -> Option<String> {
+    if !locale.is_empty() {
+        return Some(locale.to_owned());
+    }
+
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .find_map(|var| context.get_env(var).filter(|value| !value.is_empty()))
 }
 
-fn format_time_fixed_offset(time_format: &str, utc_time: DateTime<FixedOffset>) -> String {
-    utc_time.format(time_format).to_string()
+/// Names of weekdays, starting on Monday, and of months in a language
+struct TimeNames {
+    weekdays: [&'static str; 7],
+    weekdays_abbr: [&'static str; 7],
+    months: [&'static str; 12],
+    months_abbr: [&'static str; 12],
+}
+
+/// Finds the names for a locale like `de-DE` or `de_DE.UTF-8`. English, which chrono
+/// uses already, and unknown locales give `None`.
+fn get_time_names(locale: &str) -> Option<&'static TimeNames> {
+    let language = locale
+        .split(['-', '_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let names = match language.as_str() {
+        "de" => &GERMAN,
+        "es" => &SPANISH,
+        "fr" => &FRENCH,
+        "it" => &ITALIAN,
+        "ja" => &JAPANESE,
+        "nl" => &DUTCH,
+        "pt" => &PORTUGUESE,
+        "sv" => &SWEDISH,
+        "zh" => &CHINESE,
+        "en" | "c" | "posix" => return None,
+        _ => {
+            log::debug!("No names for locale {:?}, using English", locale);
+            return None;
+        }
+    };
+    Some(names)
+}
+
+/// Replaces the weekday and month names in a chrono format string (`%A`, `%a`, `%B`, `%b`
+/// and `%h`) with their translations for the date of `time`
+fn localize_format<Tz: TimeZone>(
+    time_format: &str,
+    time: &DateTime<Tz>,
+    names: Option<&TimeNames>,
+) -> String {
+    let names = match names {
+        Some(names) => names,
+        None => return time_format.to_owned(),
+    };
+    let weekday = time.weekday().num_days_from_monday() as usize;
+    let month = time.month0() as usize;
+
+    let mut localized = String::with_capacity(time_format.len());
+    let mut chars = time_format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            localized.push(c);
+            continue;
+        }
+
+        let name = match chars.next() {
+            Some('A') => names.weekdays[weekday],
+            Some('a') => names.weekdays_abbr[weekday],
+            Some('B') => names.months[month],
+            Some('b') | Some('h') => names.months_abbr[month],
+            Some(specifier) => {
+                localized.push('%');
+                localized.push(specifier);
+                continue;
+            }
+            None => {
+                localized.push('%');
+                break;
+            }
+        };
+        localized.push_str(&name.replace('%', "%%"));
+    }
+    localized
 }
 
+const GERMAN: TimeNames = TimeNames {
+    weekdays: [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+    weekdays_abbr: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    months_abbr: [
+        "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+    ],
+};
+
+const SPANISH: TimeNames = TimeNames {
+    weekdays: [
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "domingo",
+    ],
+    weekdays_abbr: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    months: [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    months_abbr: [
+        "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+    ],
+};
+
+const FRENCH: TimeNames = TimeNames {
+    weekdays: [
+        "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+    ],
+    weekdays_abbr: ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    months_abbr: [
+        "janv.", "févr.", "mars", "avril", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+};
+
+const ITALIAN: TimeNames = TimeNames {
+    weekdays: [
+        "lunedì",
+        "martedì",
+        "mercoledì",
+        "giovedì",
+        "venerdì",
+        "sabato",
+        "domenica",
+    ],
+    weekdays_abbr: ["lun", "mar", "mer", "gio", "ven", "sab", "dom"],
+    months: [
+        "gennaio",
+        "febbraio",
+        "marzo",
+        "aprile",
+        "maggio",
+        "giugno",
+        "luglio",
+        "agosto",
+        "settembre",
+        "ottobre",
+        "novembre",
+        "dicembre",
+    ],
+    months_abbr: [
+        "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+    ],
+};
+
+const JAPANESE: TimeNames = TimeNames {
+    weekdays: [
+        "月曜日",
+        "火曜日",
+        "水曜日",
+        "木曜日",
+        "金曜日",
+        "土曜日",
+        "日曜日",
+    ],
+    weekdays_abbr: ["月", "火", "水", "木", "金", "土", "日"],
+    months: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+    months_abbr: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+};
+
+const DUTCH: TimeNames = TimeNames {
+    weekdays: [
+        "maandag",
+        "dinsdag",
+        "woensdag",
+        "donderdag",
+        "vrijdag",
+        "zaterdag",
+        "zondag",
+    ],
+    weekdays_abbr: ["ma", "di", "wo", "do", "vr", "za", "zo"],
+    months: [
+        "januari",
+        "februari",
+        "maart",
+        "april",
+        "mei",
+        "juni",
+        "juli",
+        "augustus",
+        "september",
+        "oktober",
+        "november",
+        "december",
+    ],
+    months_abbr: [
+        "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+    ],
+};
+
+const PORTUGUESE: TimeNames = TimeNames {
+    weekdays: [
+        "segunda-feira",
+        "terça-feira",
+        "quarta-feira",
+        "quinta-feira",
+        "sexta-feira",
+        "sábado",
+        "domingo",
+    ],
+    weekdays_abbr: ["seg", "ter", "qua", "qui", "sex", "sáb", "dom"],
+    months: [
+        "janeiro",
+        "fevereiro",
+        "março",
+        "abril",
+        "maio",
+        "junho",
+        "julho",
+        "agosto",
+        "setembro",
+        "outubro",
+        "novembro",
+        "dezembro",
+    ],
+    months_abbr: [
+        "jan", "fev", "mar", "abr", "mai", "jun", "jul", "ago", "set", "out", "nov", "dez",
+    ],
+};
+
+const SWEDISH: TimeNames = TimeNames {
+    weekdays: [
+        "måndag", "tisdag", "onsdag", "torsdag", "fredag", "lördag", "söndag",
+    ],
+    weekdays_abbr: ["mån", "tis", "ons", "tor", "fre", "lör", "sön"],
+    months: [
+        "januari",
+        "februari",
+        "mars",
+        "april",
+        "maj",
+        "juni",
+        "juli",
+        "augusti",
+        "september",
+        "oktober",
+        "november",
+        "december",
+    ],
+    months_abbr: [
+        "jan", "feb", "mar", "apr", "maj", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+    ],
+};
+
+const CHINESE: TimeNames = TimeNames {
+    weekdays: [
+        "星期一",
+        "星期二",
+        "星期三",
+        "星期四",
+        "星期五",
+        "星期六",
+        "星期日",
+    ],
+    weekdays_abbr: ["一", "二", "三", "四", "五", "六", "日"],
+    months: [
+        "一月",
+        "二月",
+        "三月",
+        "四月",
+        "五月",
+        "六月",
+        "七月",
+        "八月",
+        "九月",
+        "十月",
+        "十一月",
+        "十二月",
+    ],
+    months_abbr: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+};
+
 /// Returns true if time_now is between time_start and time_end.
 /// If one of these values is not given, then it is ignored.
 /// It also handles cases where time_start and time_end have a midnight in between
@@ -162,49 +515,49 @@ mod tests {
     #[test]
     fn test_midnight_12hr() {
         let time = Local.ymd(2014, 7, 8).and_hms(0, 0, 0);
-        let formatted = format_time(FMT_12, time);
+        let formatted = format_time(FMT_12, time, None);
         assert_eq!(formatted, "12:00:00 AM");
     }
 
     #[test]
     fn test_midnight_24hr() {
         let time = Local.ymd(2014, 7, 8).and_hms(0, 0, 0);
-        let formatted = format_time(FMT_24, time);
+        let formatted = format_time(FMT_24, time, None);
         assert_eq!(formatted, "00:00:00");
     }
 
     #[test]
     fn test_noon_12hr() {
         let time = Local.ymd(2014, 7, 8).and_hms(12, 0, 0);
-        let formatted = format_time(FMT_12, time);
+        let formatted = format_time(FMT_12, time, None);
         assert_eq!(formatted, "12:00:00 PM");
     }
 
     #[test]
     fn test_noon_24hr() {
         let time = Local.ymd(2014, 7, 8).and_hms(12, 0, 0);
-        let formatted = format_time(FMT_24, time);
+        let formatted = format_time(FMT_24, time, None);
         assert_eq!(formatted, "12:00:00");
     }
 
     #[test]
     fn test_arbtime_12hr() {
         let time = Local.ymd(2014, 7, 8).and_hms(15, 36, 47);
-        let formatted = format_time(FMT_12, time);
+        let formatted = format_time(FMT_12, time, None);
         assert_eq!(formatted, "03:36:47 PM");
     }
 
     #[test]
     fn test_arbtime_24hr() {
         let time = Local.ymd(2014, 7, 8).and_hms(15, 36, 47);
-        let formatted = format_time(FMT_24, time);
+        let formatted = format_time(FMT_24, time, None);
         assert_eq!(formatted, "15:36:47");
     }
 
     #[test]
     fn test_format_with_paren() {
         let time = Local.ymd(2014, 7, 8).and_hms(15, 36, 47);
-        let formatted = format_time("[%T]", time);
+        let formatted = format_time("[%T]", time, None);
         assert_eq!(formatted, "[15:36:47]");
     }
 
@@ -215,7 +568,7 @@ mod tests {
             .ymd(2014, 7, 8)
             .and_hms(0, 0, 0)
             .with_timezone(&timezone_offset);
-        let formatted = format_time_fixed_offset(FMT_12, time);
+        let formatted = format_time_fixed_offset(FMT_12, time, None);
         assert_eq!(formatted, "12:00:00 AM");
     }
 
@@ -226,7 +579,7 @@ mod tests {
             .ymd(2014, 7, 8)
             .and_hms(0, 0, 0)
             .with_timezone(&timezone_offset);
-        let formatted = format_time_fixed_offset(FMT_24, time);
+        let formatted = format_time_fixed_offset(FMT_24, time, None);
         assert_eq!(formatted, "00:00:00");
     }
 
@@ -237,7 +590,7 @@ mod tests {
             .ymd(2014, 7, 8)
             .and_hms(12, 0, 0)
             .with_timezone(&timezone_offset);
-        let formatted = format_time_fixed_offset(FMT_12, time);
+        let formatted = format_time_fixed_offset(FMT_12, time, None);
         assert_eq!(formatted, "12:00:00 PM");
     }
 
@@ -248,7 +601,7 @@ mod tests {
             .ymd(2014, 7, 8)
             .and_hms(12, 0, 0)
             .with_timezone(&timezone_offset);
-        let formatted = format_time_fixed_offset(FMT_24, time);
+        let formatted = format_time_fixed_offset(FMT_24, time, None);
         assert_eq!(formatted, "12:00:00");
     }
 
@@ -259,7 +612,7 @@ mod tests {
             .ymd(2014, 7, 8)
             .and_hms(15, 36, 47)
             .with_timezone(&timezone_offset);
-        let formatted = format_time_fixed_offset(FMT_12, time);
+        let formatted = format_time_fixed_offset(FMT_12, time, None);
         assert_eq!(formatted, "03:36:47 PM");
     }
 
@@ -270,7 +623,7 @@ mod tests {
             .ymd(2014, 7, 8)
             .and_hms(15, 36, 47)
             .with_timezone(&timezone_offset);
-        let formatted = format_time_fixed_offset(FMT_24, time);
+        let formatted = format_time_fixed_offset(FMT_24, time, None);
         assert_eq!(formatted, "15:36:47");
     }
 
@@ -281,7 +634,7 @@ mod tests {
             .ymd(2014, 7, 8)
             .and_hms(15, 36, 47)
             .with_timezone(&timezone_offset);
-        let formatted = format_time_fixed_offset("[%T]", time);
+        let formatted = format_time_fixed_offset("[%T]", time, None);
         assert_eq!(formatted, "[15:36:47]");
     }
 
@@ -290,7 +643,8 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "-3";
 
-        let actual = create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12).unwrap();
+        let actual =
+            create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None).unwrap();
         assert_eq!(actual, "12:36:47 PM");
     }
 
@@ -299,7 +653,8 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+5";
 
-        let actual = create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12).unwrap();
+        let actual =
+            create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None).unwrap();
         assert_eq!(actual, "08:36:47 PM");
     }
 
@@ -308,7 +663,8 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+9.5";
 
-        let actual = create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12).unwrap();
+        let actual =
+            create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None).unwrap();
         assert_eq!(actual, "01:06:47 AM");
     }
 
@@ -317,7 +673,8 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+5.75";
 
-        let actual = create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12).unwrap();
+        let actual =
+            create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None).unwrap();
         assert_eq!(actual, "09:21:47 PM");
     }
 
@@ -326,7 +683,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+24";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -336,7 +693,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "-24";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -346,7 +703,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "+9001";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -356,7 +713,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "-4242";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -366,7 +723,7 @@ mod tests {
         let utc_time: DateTime<Utc> = Utc.ymd(2014, 7, 8).and_hms(15, 36, 47);
         let utc_time_offset_str = "completely wrong config";
 
-        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12)
+        create_offset_time_string(utc_time, &utc_time_offset_str, FMT_12, None)
             .err()
             .expect("Invalid timezone offset.");
     }
@@ -510,4 +867,100 @@ mod tests {
         assert!(actual.starts_with(&col_prefix));
         assert!(actual.ends_with(&col_suffix));
     }
+
+    #[test]
+    fn test_german_names() {
+        let time = Local.ymd(2024, 3, 4).and_hms(9, 30, 0);
+        let names = get_time_names("de_DE.UTF-8");
+
+        assert_eq!(format_time("%A %d %B", time, names), "Montag 04 März");
+        assert_eq!(
+            format_time("%a %d %b %H:%M", time, names),
+            "Mo 04 Mär 09:30"
+        );
+    }
+
+    #[test]
+    fn test_japanese_names() {
+        let time = FixedOffset::east(9 * 3600)
+            .ymd(2024, 3, 4)
+            .and_hms(9, 30, 0);
+        let names = get_time_names("ja_JP.UTF-8");
+
+        assert_eq!(
+            format_time_fixed_offset("%B%d日 (%a)", time, names),
+            "3月04日 (月)"
+        );
+    }
+
+    #[test]
+    fn test_names_follow_offset_date() {
+        // Still Sunday in UTC, already Monday in Tokyo
+        let utc_time = Utc.ymd(2024, 3, 3).and_hms(20, 0, 0);
+        let names = get_time_names("de-DE");
+
+        let actual = create_offset_time_string(utc_time, "+9", "%A", names).unwrap();
+        assert_eq!(actual, "Montag");
+    }
+
+    #[test]
+    fn test_escaped_specifiers_not_localized() {
+        let time = Local.ymd(2024, 3, 4).and_hms(9, 30, 0);
+        let names = get_time_names("de");
+
+        assert_eq!(format_time("%%A %A", time, names), "%A Montag");
+    }
+
+    #[test]
+    fn test_unknown_locale_falls_back_to_english() {
+        let time = Local.ymd(2024, 3, 4).and_hms(9, 30, 0);
+
+        assert!(get_time_names("xx_XX.UTF-8").is_none());
+        assert!(get_time_names("en_US.UTF-8").is_none());
+        assert!(get_time_names("C").is_none());
+        assert_eq!(
+            format_time("%A %B", time, get_time_names("xx")),
+            "Monday March"
+        );
+    }
+
+    #[test]
+    fn test_locale_precedence() {
+        let mut context = Context::new_with_shell_and_path(
+            clap::ArgMatches::default(),
+            crate::context::Shell::Unknown,
+            std::path::PathBuf::new(),
+            std::path::PathBuf::new(),
+        );
+        assert_eq!(get_locale(&context, ""), None);
+
+        context.env.insert("LANG", "ja_JP.UTF-8".into());
+        assert_eq!(get_locale(&context, "").as_deref(), Some("ja_JP.UTF-8"));
+
+        context.env.insert("LC_TIME", "fr_FR.UTF-8".into());
+        assert_eq!(get_locale(&context, "").as_deref(), Some("fr_FR.UTF-8"));
+
+        context.env.insert("LC_ALL", "sv_SE.UTF-8".into());
+        assert_eq!(get_locale(&context, "").as_deref(), Some("sv_SE.UTF-8"));
+
+        // The option overrides the environment
+        assert_eq!(get_locale(&context, "de-DE").as_deref(), Some("de-DE"));
+    }
+
+    #[test]
+    fn config_locale_overrides_env() {
+        let actual = ModuleRenderer::new("time")
+            .config(toml::toml! {
+                [time]
+                disabled = false
+                format = "$time"
+                time_format = "%B"
+                locale = "de-DE"
+            })
+            .env("LANG", "ja_JP.UTF-8")
+            .collect();
+
+        let expected = GERMAN.months[Local::now().month0() as usize];
+        assert_eq!(actual.as_deref(), Some(expected));
+    }
 }
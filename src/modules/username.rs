@@ -21,7 +21,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("username");
     let config: UsernameConfig = UsernameConfig::try_load(module.config);
 
-    let is_root = is_root_user();
+    let is_root = context.is_root_user();
     let show_username = config.show_always
         || is_root // [1]
         || !is_login_user(&context, &username) // [2]
@@ -53,7 +53,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `username`:\n{}", error);
+            crate::error::StarshipError::from_format("username", error).log();
             return None;
         }
     });
@@ -68,16 +68,6 @@ fn is_login_user(context: &Context, username: &str) -> bool {
         .unwrap_or(true)
 }
 
-#[cfg(target_os = "windows")]
-fn is_root_user() -> bool {
-    false
-}
-
-#[cfg(not(target_os = "windows"))]
-fn is_root_user() -> bool {
-    nix::unistd::geteuid() == nix::unistd::ROOT
-}
-
 fn is_ssh_session(context: &Context) -> bool {
     let ssh_env = ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"];
     ssh_env.iter().any(|env| context.get_env_os(env).is_some())
@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use crate::context::Context;
+
+/// Files checked for version pins in each directory, in order of precedence
+pub const PIN_FILES: &[&str] = &[".mise.toml", ".mise/config.toml", ".tool-versions"];
+
+/// Where the `$version` of a language module came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VersionSource {
+    /// A pin in a `mise` or `asdf` config file
+    Pin,
+    /// The output of the tool's binary
+    Binary,
+}
+
+impl VersionSource {
+    pub fn from_pin<T>(pin: &Option<T>) -> Self {
+        if pin.is_some() {
+            VersionSource::Pin
+        } else {
+            VersionSource::Binary
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VersionSource::Pin => "pin",
+            VersionSource::Binary => "binary",
+        }
+    }
+}
+
+/// A pin found for a tool
+#[derive(Debug, PartialEq)]
+enum Pin {
+    /// A concrete version, e.g. `18.16.0`
+    Version(String),
+    /// A pin that can't be turned into a version without the binary, e.g. `ref:main`
+    Unresolvable,
+}
+
+/// Finds the version of a tool pinned by `mise` or `asdf`.
+///
+/// Returns the version from the nearest pin of any of `tools` (e.g. `["node", "nodejs"]`) in
+/// the `Context::pin_files` of the current directory and its parents. Returns `None` when
+/// there is no pin, or when the nearest pin isn't a concrete version and the tool's binary has
+/// to be asked instead.
+pub fn pinned_version(context: &Context, tools: &[&str]) -> Option<String> {
+    for (path, contents) in context.pin_files() {
+        match find_pin(path, contents, tools) {
+            Some(Pin::Version(version)) => return Some(version),
+            Some(Pin::Unresolvable) => return None,
+            None => continue,
+        }
+    }
+
+    None
+}
+
+fn find_pin(path: &Path, contents: &str, tools: &[&str]) -> Option<Pin> {
+    if path.extension().and_then(|ext| ext.to_str()) == Some("toml") {
+        parse_mise_toml(contents, tools)
+    } else {
+        parse_tool_versions(contents, tools)
+    }
+}
+
+/// Parses an asdf `.tool-versions` file, e.g. `nodejs 18.16.0 16.20.0 # comment`
+fn parse_tool_versions(contents: &str, tools: &[&str]) -> Option<Pin> {
+    contents.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let tool = fields.next()?;
+        if !tools.contains(&tool) {
+            return None;
+        }
+
+        // When several versions are listed the first one is used
+        fields.next().map(to_pin)
+    })
+}
+
+/// Parses the `[tools]` table of a mise `.mise.toml` file
+fn parse_mise_toml(contents: &str, tools: &[&str]) -> Option<Pin> {
+    let config = toml::from_str::<toml::Value>(contents).ok()?;
+    let table = config.get("tools")?.as_table()?;
+
+    tools.iter().find_map(|tool| {
+        let version = match table.get(*tool)? {
+            toml::Value::String(version) => Some(version.as_str()),
+            toml::Value::Array(versions) => versions.first().and_then(|v| v.as_str()),
+            toml::Value::Table(options) => options.get("version").and_then(|v| v.as_str()),
+            _ => None,
+        };
+
+        Some(version.map_or(Pin::Unresolvable, to_pin))
+    })
+}
+
+fn to_pin(version: &str) -> Pin {
+    if version.starts_with("ref:") || version.starts_with("path:") {
+        return Pin::Unresolvable;
+    }
+
+    // Drop prefixes such as `prefix:` or a vendor name (`temurin-17.0.2`),
+    // and treat aliases without any digits (`lts`, `system`) as unresolvable.
+    match version.find(|c: char| c.is_ascii_digit()) {
+        Some(start) => Pin::Version(version[start..].to_string()),
+        None => Pin::Unresolvable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE: &[&str] = &["node", "nodejs"];
+
+    fn version(version: &str) -> Option<Pin> {
+        Some(Pin::Version(version.to_string()))
+    }
+
+    #[test]
+    fn tool_versions_basic() {
+        let contents = "ruby 3.2.2\nnodejs 18.16.0\n";
+        assert_eq!(parse_tool_versions(contents, NODE), version("18.16.0"));
+        assert_eq!(parse_tool_versions(contents, &["ruby"]), version("3.2.2"));
+        assert_eq!(parse_tool_versions(contents, &["python"]), None);
+    }
+
+    #[test]
+    fn tool_versions_comments_and_fallbacks() {
+        let contents = "# nodejs 12.0.0\n\n  nodejs   18.16.0 16.20.0 # the LTS\n";
+        assert_eq!(parse_tool_versions(contents, NODE), version("18.16.0"));
+    }
+
+    #[test]
+    fn tool_versions_ref_and_path() {
+        assert_eq!(
+            parse_tool_versions("nodejs ref:v18.16.0\n", NODE),
+            Some(Pin::Unresolvable)
+        );
+        assert_eq!(
+            parse_tool_versions("golang path:/opt/go\n", &["golang"]),
+            Some(Pin::Unresolvable)
+        );
+        assert_eq!(
+            parse_tool_versions("python system\n", &["python"]),
+            Some(Pin::Unresolvable)
+        );
+    }
+
+    #[test]
+    fn tool_versions_vendor_prefix() {
+        assert_eq!(
+            parse_tool_versions("java temurin-17.0.2+8\n", &["java"]),
+            version("17.0.2+8")
+        );
+    }
+
+    #[test]
+    fn mise_toml_forms() {
+        let contents = r#"
+            # project tools
+            [env]
+            NODE_ENV = "development"
+
+            [tools]
+            node = "20.1.0" # pinned for CI
+            python = ["3.11.4", "3.10"]
+            ruby = { version = "3.2.2", virtualenv = ".venv" }
+        "#;
+        assert_eq!(parse_mise_toml(contents, NODE), version("20.1.0"));
+        assert_eq!(parse_mise_toml(contents, &["python"]), version("3.11.4"));
+        assert_eq!(parse_mise_toml(contents, &["ruby"]), version("3.2.2"));
+        assert_eq!(parse_mise_toml(contents, &["java"]), None);
+    }
+
+    #[test]
+    fn mise_toml_ref_and_path() {
+        let contents = r#"
+            [tools]
+            node = "ref:main"
+            go = "path:~/.local/go"
+            python = "prefix:3.11"
+        "#;
+        assert_eq!(parse_mise_toml(contents, NODE), Some(Pin::Unresolvable));
+        assert_eq!(
+            parse_mise_toml(contents, &["go", "golang"]),
+            Some(Pin::Unresolvable)
+        );
+        assert_eq!(parse_mise_toml(contents, &["python"]), version("3.11"));
+    }
+
+    #[test]
+    fn mise_toml_invalid() {
+        assert_eq!(parse_mise_toml("[tools\nnode = 1", NODE), None);
+        assert_eq!(parse_mise_toml("[env]\nnode = \"1.0\"", NODE), None);
+    }
+}
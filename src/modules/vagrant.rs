@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Vagrant version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("vagrant");
+    let env = module.env();
     let config = VagrantConfig::try_load(module.config);
 
     let is_vagrant_project = context
@@ -32,8 +33,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let vagrant_version =
-                        get_vagrant_version(&context.exec_cmd("vagrant", &["--version"])?.stdout)?;
+                    let vagrant_version = get_vagrant_version(
+                        &context.exec_cmd("vagrant", &["--version"], &env)?.stdout,
+                    )?;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &vagrant_version,
@@ -49,7 +51,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `vagrant`:\n{}", error);
+            crate::error::StarshipError::from_format("vagrant", error).log();
             return None;
         }
     });
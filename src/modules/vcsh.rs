@@ -36,7 +36,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `vcsh`:\n{}", error);
+            crate::error::StarshipError::from_format("vcsh", error).log();
             return None;
         }
     });
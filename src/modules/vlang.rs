@@ -2,10 +2,12 @@ use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::v::VConfig;
 use crate::formatter::StringFormatter;
+use crate::formatter::VersionFormatter;
 
 /// Creates a module with the current V version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("vlang");
+    let env = module.env();
     let config = VConfig::try_load(module.config);
     let is_v_project = context
         .try_begin_scan()?
@@ -29,10 +31,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => context
-                    .exec_cmd("v", &["version"])
-                    .and_then(|output| parse_v_version(output.stdout.trim()))
-                    .map(Ok),
+                "version" => {
+                    let v_version =
+                        parse_v_version(&context.exec_cmd("v", &["version"], &env)?.stdout)?;
+                    VersionFormatter::format_module_version(
+                        module.get_name(),
+                        &v_version,
+                        config.version_format,
+                    )
+                    .map(Ok)
+                }
                 _ => None,
             })
             .parse(None)
@@ -41,7 +49,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `vlang`:\n{}", error);
+            crate::error::StarshipError::from_format("vlang", error).log();
             return None;
         }
     });
@@ -51,12 +59,12 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
 fn parse_v_version(v_version: &str) -> Option<String> {
     let version = v_version
-        // split into ["V", "0.2", "30c0659"]
+        // split into ["V", "0.4.4", "abc123"]
         .split_whitespace()
-        // return "0.2"
+        // return "0.4.4"
         .nth(1)?;
 
-    Some(format!("v{}", version))
+    Some(version.to_string())
 }
 
 #[cfg(test)]
@@ -70,7 +78,9 @@ mod tests {
     #[test]
     fn test_parse_v_version() {
         const OUTPUT: &str = "V 0.2 30c0659\n";
-        assert_eq!(parse_v_version(OUTPUT.trim()), Some("v0.2".to_string()))
+        assert_eq!(parse_v_version(OUTPUT), Some("0.2".to_string()));
+        assert_eq!(parse_v_version("V 0.4.4 abc123"), Some("0.4.4".to_string()));
+        assert_eq!(parse_v_version(""), None);
     }
 
     #[test]
@@ -7,6 +7,7 @@ use crate::formatter::VersionFormatter;
 /// Creates a module with the current Zig version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("zig");
+    let env = module.env();
     let config = ZigConfig::try_load(module.config);
 
     let is_zig_project = context
@@ -32,7 +33,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let zig_version = context.exec_cmd("zig", &["version"])?.stdout;
+                    let zig_version = context.exec_cmd("zig", &["version"], &env)?.stdout;
                     VersionFormatter::format_module_version(
                         module.get_name(),
                         &zig_version.trim(),
@@ -48,7 +49,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     module.set_segments(match parsed {
         Ok(segments) => segments,
         Err(error) => {
-            log::warn!("Error in module `zig`:\n{}", error);
+            crate::error::StarshipError::from_format("zig", error).log();
             return None;
         }
     });
@@ -0,0 +1,151 @@
+//! Network access, which is never allowed with `offline = true`.
+//!
+//! Everything starship does that opens network connections goes through here: HTTP requests
+//! are sent with `get` and `post_form`, and `Context::exec_cmd` asks `Context::allow_network`
+//! before running one of the `NETWORK_COMMANDS`.
+
+use crate::configs::StarshipRootConfig;
+
+#[cfg(feature = "http")]
+use std::time::Duration;
+
+/// Commands that access the network, by their arguments, with the purpose they are allowed for
+const NETWORK_COMMANDS: &[(&str, &[&str], &str)] = &[
+    // `terraform version` checks for updates online
+    ("terraform", &["version"], "terraform version check"),
+];
+
+/// How long an HTTP request may take
+#[cfg(feature = "http")]
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns whether `purpose` may access the network, which is never the case with
+/// `offline = true`
+pub fn is_allowed(config: &StarshipRootConfig, purpose: &str) -> bool {
+    if config.offline {
+        log::debug!("Offline mode, not allowing network access for {}", purpose);
+        return false;
+    }
+    true
+}
+
+/// The purpose of running `cmd` with `args` if it's one of the `NETWORK_COMMANDS`
+pub fn command_purpose(cmd: &str, args: &[&str]) -> Option<&'static str> {
+    NETWORK_COMMANDS
+        .iter()
+        .find(|(command, command_args, _)| *command == cmd && *command_args == args)
+        .map(|(_, _, purpose)| *purpose)
+}
+
+/// Sends a GET request to `url` for `purpose`, returning the body of a successful response
+pub fn get(config: &StarshipRootConfig, purpose: &str, url: &str) -> Option<String> {
+    if !is_allowed(config, purpose) {
+        return None;
+    }
+    http_get(url)
+}
+
+/// Posts the url-encoded `form` to `url` for `purpose`, returning the body of the response
+pub fn post_form(
+    config: &StarshipRootConfig,
+    purpose: &str,
+    url: &str,
+    form: &[(&str, &str)],
+) -> Option<String> {
+    if !is_allowed(config, purpose) {
+        return None;
+    }
+    http_post_form(url, form)
+}
+
+#[cfg(feature = "http")]
+fn http_get(url: &str) -> Option<String> {
+    let response = attohttpc::get(url)
+        .header("User-Agent", "starship")
+        .timeout(HTTP_TIMEOUT)
+        .send()
+        .ok()?;
+    if !response.is_success() {
+        return None;
+    }
+    response.text().ok()
+}
+
+#[cfg(not(feature = "http"))]
+fn http_get(_url: &str) -> Option<String> {
+    None
+}
+
+#[cfg(feature = "http")]
+fn http_post_form(url: &str, form: &[(&str, &str)]) -> Option<String> {
+    attohttpc::post(url)
+        .timeout(HTTP_TIMEOUT)
+        .form(&form)
+        .ok()?
+        .send()
+        .ok()?
+        .text()
+        .ok()
+}
+
+#[cfg(not(feature = "http"))]
+fn http_post_form(_url: &str, _form: &[(&str, &str)]) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use std::fs;
+    use std::path::Path;
+
+    fn offline_config() -> StarshipConfig {
+        StarshipConfig {
+            config: Some(toml::toml! { offline = true }),
+        }
+    }
+
+    #[test]
+    fn offline_sends_no_requests() {
+        let config = offline_config();
+        let config = config.get_root_config();
+
+        assert!(!is_allowed(&config, "test"));
+        assert_eq!(get(&config, "test", "http://127.0.0.1:9/"), None);
+        assert_eq!(post_form(&config, "test", "http://127.0.0.1:9/", &[]), None);
+    }
+
+    #[test]
+    fn network_commands_have_a_purpose() {
+        assert_eq!(
+            command_purpose("terraform", &["version"]),
+            Some("terraform version check")
+        );
+        assert_eq!(command_purpose("terraform", &["workspace", "show"]), None);
+        assert_eq!(command_purpose("git", &["version"]), None);
+    }
+
+    /// HTTP requests are only sent from here, where the offline check can't be skipped
+    #[test]
+    fn http_requests_go_through_the_gate() {
+        fn check(dir: &Path) {
+            for entry in fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    check(&path);
+                } else if path.extension().and_then(|ext| ext.to_str()) == Some("rs")
+                    && !path.ends_with("network.rs")
+                {
+                    let source = fs::read_to_string(&path).unwrap();
+                    assert!(
+                        !source.contains(concat!("attohttpc", "::")),
+                        "{:?} sends HTTP requests without going through `network`",
+                        path
+                    );
+                }
+            }
+        }
+        check(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"));
+    }
+}
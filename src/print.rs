@@ -1,20 +1,236 @@
-use ansi_term::ANSIStrings;
+use ansi_term::{Color, Style};
 use clap::ArgMatches;
 use rayon::prelude::*;
-use std::collections::BTreeSet;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::{self, Debug, Write as FmtWrite};
 use std::io::{self, Write};
-use std::time::Duration;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthChar;
 
-use crate::configs::PROMPT_ORDER;
+use crate::cache::PromptCache;
+use crate::config::{self as config, ModuleConfig};
+use crate::configs::separators::SeparatorsConfig;
+use crate::configs::FullConfig;
 use crate::context::{Context, Shell};
+use crate::error::ErrorCategory;
 use crate::formatter::{StringFormatter, VariableHolder};
+use crate::fs_budget::Clock;
+use crate::logger;
 use crate::module::Module;
 use crate::module::ALL_MODULES;
 use crate::modules;
-use crate::segment::Segment;
+use crate::segment::{Segment, SegmentKind};
+use crate::shadow;
+use crate::shell_escape;
+use crate::startup::{Phase, StartupReport, StartupTimings};
+
+/// Exit code of `explain` and `timings` when the prompt was rendered as configured
+pub const EXIT_SUCCESS: i32 = 0;
+/// Exit code of `explain` and `timings` when the config file is invalid and the defaults were used
+pub const EXIT_CONFIG_DEGRADED: i32 = 2;
+/// Exit code of `explain` and `timings` with `--strict` when warnings were logged
+pub const EXIT_STRICT_WARNINGS: i32 = 3;
+
+/// The output of `explain` and `timings` with `--format json`
+#[derive(Serialize)]
+pub struct PromptReport {
+    /// The version of starship
+    pub version: &'static str,
+    /// The time it took to compute all modules, in microseconds
+    pub total_us: u64,
+    pub modules: Vec<ModuleReport>,
+    /// The modules shortened to their `max_length`, in prompt order
+    pub truncated: Vec<String>,
+    /// How long each phase of the startup took, only reported by `timings`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup: Option<StartupReport>,
+}
+
+/// A module of the prompt in a `PromptReport`
+#[derive(Serialize)]
+pub struct ModuleReport {
+    /// The name of the module, e.g. `git_branch` or `custom.foo`
+    pub module: String,
+    /// The output of the module without any styling
+    pub text_plain: String,
+    /// The time it took to compute the module, in microseconds
+    pub duration_us: u64,
+    /// Whether the module has any output in the prompt
+    pub produced_output: bool,
+}
+
+impl ModuleReport {
+    fn new(module: &Module) -> Self {
+        Self {
+            module: module.get_name().to_owned(),
+            text_plain: module.get_segments().join(""),
+            duration_us: module.duration.as_micros() as u64,
+            produced_output: !module.is_empty(),
+        }
+    }
+}
+
+/// The output of `explain-config` and `module --describe`
+#[derive(Serialize)]
+pub struct ModuleDescription {
+    pub module: &'static str,
+    pub description: &'static str,
+    /// The variables the module provides to its format strings
+    pub variables: &'static [&'static str],
+    pub options: Vec<ModuleOption>,
+}
+
+/// A config key of a module in a `ModuleDescription`
+#[derive(Serialize)]
+pub struct ModuleOption {
+    pub key: &'static str,
+    /// The TOML type of the value, e.g. `array of string`
+    #[serde(rename = "type")]
+    pub value_type: String,
+    /// The first paragraph of the doc comment of the config field
+    pub description: &'static str,
+    /// The default value, or `None` for keys that are unset by default
+    pub default: Option<toml::Value>,
+}
+
+impl ModuleDescription {
+    /// Describes one of `ALL_MODULES`, using the defaults of its config
+    pub fn new(module_name: &str) -> Option<Self> {
+        let module = *ALL_MODULES.iter().find(|module| **module == module_name)?;
+        let defaults = config::DEFAULT_CONFIG.get(module);
+
+        let options = FullConfig::field_config_docs(module)
+            .unwrap_or_default()
+            .iter()
+            .map(|doc| ModuleOption {
+                key: doc.key,
+                value_type: toml_type(doc.rust_type),
+                description: doc.description,
+                default: defaults.and_then(|config| config.get(doc.key)).cloned(),
+            })
+            .collect();
+
+        Some(Self {
+            module,
+            description: modules::description(module),
+            variables: modules::variables(module),
+            options,
+        })
+    }
+}
+
+/// The TOML type read by a config field of the Rust type `rust_type`, as stringified by the
+/// `ModuleConfig` derive macro
+fn toml_type(rust_type: &str) -> String {
+    let rust_type: String = rust_type.split_whitespace().collect();
+    let generic = |prefix: &str| {
+        rust_type
+            .strip_prefix(prefix)
+            .and_then(|inner| inner.strip_suffix('>'))
+    };
+
+    if let Some(inner) = generic("Option<") {
+        toml_type(inner)
+    } else if let Some(inner) = generic("Vec<") {
+        format!("array of {}", toml_type(inner))
+    } else if let Some(inner) = generic("VecOr<") {
+        let inner = toml_type(inner);
+        format!("{} or array of {}", inner, inner)
+    } else {
+        match rust_type.as_str() {
+            "&'astr" | "String" => "string",
+            "bool" => "boolean",
+            "i64" | "u64" | "usize" => "integer",
+            "f64" => "float",
+            // Maps and the structs of nested config tables
+            _ => "table",
+        }
+        .to_owned()
+    }
+}
+
+/// Renders the text output of `explain-config` for a module
+fn explain_module(description: &ModuleDescription) -> String {
+    let mut text = format!("{} - {}\n", description.module, description.description);
+
+    text.push_str("\nVariables:\n");
+    for variable in description.variables {
+        let _ = writeln!(text, "  ${}", variable);
+    }
+
+    text.push_str("\nOptions:\n");
+    for option in &description.options {
+        match &option.default {
+            Some(default) => {
+                let _ = writeln!(
+                    text,
+                    "  {}: {} = {}",
+                    option.key, option.value_type, default
+                );
+            }
+            None => {
+                let _ = writeln!(text, "  {}: {} (unset)", option.key, option.value_type);
+            }
+        }
+        let _ = writeln!(text, "      {}", option.description);
+    }
+
+    text
+}
+
+/// Computes the modules of the prompt for `explain` and `timings`
+fn prompt_report(context: &Context) -> PromptReport {
+    let start = Instant::now();
+    let modules = compute_modules(context);
+    let total_us = start.elapsed().as_micros() as u64;
+    let truncated = modules
+        .iter()
+        .filter(|module| module.truncated)
+        .map(|module| module.get_name().to_owned())
+        .collect();
+
+    PromptReport {
+        version: shadow::PKG_VERSION,
+        total_us,
+        modules: modules.iter().map(ModuleReport::new).collect(),
+        truncated,
+        startup: None,
+    }
+}
+
+/// The category of errors `--strict=<category>` fails on, `None` for all warnings
+fn strict_category(args: &ArgMatches) -> Option<ErrorCategory> {
+    args.value_of("strict")
+        .and_then(|category| category.parse().ok())
+}
+
+/// The number of problems `--strict` fails on: the errors of `category`, or all warnings
+fn strict_warning_count(context: &Context, strict: bool, category: Option<ErrorCategory>) -> usize {
+    if !strict {
+        return 0;
+    }
+    // The modules that aren't in the prompt are checked as well
+    context.config.validate();
+    match category {
+        Some(category) => logger::error_count(category),
+        None => logger::warning_count(),
+    }
+}
+
+/// The exit code of `explain` and `timings`, given the number of warnings logged
+pub fn exit_code(context: &Context, strict: bool, warnings: usize) -> i32 {
+    if context.config_degraded {
+        EXIT_CONFIG_DEGRADED
+    } else if strict && warnings > 0 {
+        EXIT_STRICT_WARNINGS
+    } else {
+        EXIT_SUCCESS
+    }
+}
 
 pub struct Grapheme<'a>(&'a str);
 
@@ -53,85 +269,409 @@ fn test_grapheme_aware_width() {
     assert_eq!(11, "normal text".width_graphemes());
 }
 
-pub fn prompt(args: ArgMatches) {
-    let context = Context::new(args);
+pub fn prompt(args: ArgMatches<'static>, timings: StartupTimings) {
+    // The cache only holds the left prompt of the session
+    let use_cache = !args.is_present("no_cache") && !args.is_present("right");
+    let profile_startup = args.is_present("profile_startup");
+    let mut context = Context::new_with_timings(args, timings);
+    let mut timings = std::mem::take(&mut context.startup_timings);
+    crate::cache::gc_if_due(&context);
+    let update_check = if context.right {
+        None
+    } else {
+        crate::update_check::pending(&context)
+    };
+
+    let prompt = get_cached_prompt(context, use_cache);
+    timings.mark(Phase::Render);
+
     let stdout = io::stdout();
     let mut handle = stdout.lock();
-    write!(handle, "{}", get_prompt(context)).unwrap();
+    write!(handle, "{}", prompt).unwrap();
+    if profile_startup {
+        handle.flush().unwrap();
+        eprint!("{}", format_startup_timings(&timings));
+    }
+
+    // Only started once the prompt is out, so that it never delays it
+    if let Some(update_check) = update_check {
+        handle.flush().unwrap();
+        update_check.spawn();
+    }
+}
+
+/// The breakdown of `prompt --profile-startup`, one phase per line followed by the total
+fn format_startup_timings(timings: &StartupTimings) -> String {
+    let format_ms = |duration: Duration| format!("{:.3}ms", duration.as_secs_f64() * 1000.0);
+    let mut lines: Vec<(&str, String)> = timings
+        .phases()
+        .into_iter()
+        .map(|(phase, duration)| (phase.as_str(), format_ms(duration)))
+        .collect();
+    lines.push(("total", format_ms(timings.total())));
+
+    let name_width = lines.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let duration_width = lines.iter().map(|(_, d)| d.len()).max().unwrap_or(0);
+    let mut out = String::from("\n Startup phases:\n");
+    for (name, duration) in lines {
+        writeln!(
+            out,
+            " {:<name_width$}  -  {:>duration_width$}",
+            name,
+            duration,
+            name_width = name_width,
+            duration_width = duration_width
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Returns the prompt of the previous render if nothing it depends on has changed since,
+/// otherwise renders the prompt and caches it for the next one.
+pub(crate) fn get_cached_prompt(context: Context<'static>, use_cache: bool) -> String {
+    let cache = PromptCache::new(&context);
+    if use_cache {
+        if let Some(prompt) = cache.as_ref().and_then(PromptCache::load) {
+            return prompt;
+        }
+    }
+
+    let (prompt, timed_out) = get_prompt_within_timeout(context, render_prompt);
+    // A prompt missing modules shouldn't be reused once they could finish
+    if let Some(cache) = cache.filter(|_| timed_out.is_empty()) {
+        cache.store(&prompt);
+    }
+    prompt
+}
+
+/// The segments of the modules that finished before the `prompt_timeout_ms` deadline, by the
+/// name of the variable they were rendered for
+type FinishedModules = HashMap<String, Vec<Segment>>;
+
+pub fn get_prompt(context: Context<'static>) -> String {
+    get_prompt_within_timeout(context, render_prompt).0
+}
+
+/// The segments of the prompt, for `prompt --watch` to repaint only what changed
+pub(crate) fn get_prompt_segments(context: Context<'static>) -> Vec<Segment> {
+    get_prompt_within_timeout(context, render_segments).0
+}
+
+/// Renders the prompt with `render`, leaving out the modules that didn't finish within
+/// `prompt_timeout_ms`. Returns the names of those modules along with the prompt.
+fn get_prompt_within_timeout<T>(
+    context: Context<'static>,
+    render: fn(&Context, Option<&FinishedModules>) -> T,
+) -> (T, Vec<String>) {
+    let prompt_timeout = context.config.get_root_config().prompt_timeout_ms;
+    if prompt_timeout == 0 {
+        return (render(&context, None), Vec::new());
+    }
+
+    let clock = context.prompt_clock();
+    let deadline = clock.now() + Duration::from_millis(prompt_timeout);
+    // The modules that miss the deadline keep running after the prompt is rendered, so they
+    // share the context with it
+    let context = Arc::new(context);
+    let (finished, timed_out) = handle_modules_until(&context, deadline, clock.as_ref());
+    if !timed_out.is_empty() {
+        log::warn!(
+            "The prompt took longer than `prompt_timeout_ms` ({}ms), leaving out: {}",
+            prompt_timeout,
+            timed_out.join(", ")
+        );
+    }
+
+    (render(&context, Some(&finished)), timed_out)
+}
+
+/// The modules to render for the prompt format with the variables `module_list`, in the order
+/// they appear in it
+fn prompt_modules(context: &Context, module_list: &BTreeSet<String>) -> Vec<String> {
+    let mut modules: Vec<String> = module_list
+        .iter()
+        .filter(|module| *module != "all" && !context.is_module_disabled_in_config(module))
+        .cloned()
+        .collect();
+    if module_list.contains("all") {
+        for module in context.config.get_root_config().prompt_order() {
+            if !modules.iter().any(|pending| pending == module) {
+                modules.push(module.to_string());
+            }
+        }
+    }
+    modules
+}
+
+/// Removes `config_error` from `modules`, returning whether it was there. It counts the errors
+/// of the other modules, so it's rendered after them.
+fn take_config_error(modules: &mut Vec<String>) -> bool {
+    let len = modules.len();
+    modules.retain(|module| module != "config_error");
+    modules.len() != len
+}
+
+/// Renders the modules of the prompt format with the variables `module_list`
+fn handle_modules(context: &Context, module_list: &BTreeSet<String>) -> FinishedModules {
+    let powerline = context.config.get_root_config().separators.is_powerline();
+    let render = |module: &str| -> Vec<Segment> {
+        handle_module(module, context, module_list)
+            .into_iter()
+            .flat_map(|module| module_segments(module, powerline))
+            .collect()
+    };
+
+    let mut modules = prompt_modules(context, module_list);
+    let config_error = take_config_error(&mut modules);
+    let mut finished: FinishedModules = modules
+        .par_iter()
+        .map(|module| (module.clone(), render(module)))
+        .collect();
+    if config_error {
+        finished.insert("config_error".to_string(), render("config_error"));
+    }
+    finished
+}
+
+/// Renders the modules of the prompt format, each on its own thread, and returns the ones that
+/// finished before `deadline` of `clock` along with the names of the ones that didn't
+fn handle_modules_until(
+    context: &Arc<Context<'static>>,
+    deadline: Instant,
+    clock: &dyn Clock,
+) -> (FinishedModules, Vec<String>) {
+    let module_list = match prompt_variables(context) {
+        Some(module_list) => Arc::new(module_list),
+        None => return Default::default(),
+    };
+    let powerline = context.config.get_root_config().separators.is_powerline();
+    let mut pending = prompt_modules(context, &module_list);
+    let config_error = take_config_error(&mut pending);
+
+    let (sender, receiver) = mpsc::channel();
+    for name in &pending {
+        let sender = sender.clone();
+        let module = name.clone();
+        let module_list = Arc::clone(&module_list);
+        let context = Arc::clone(context);
+        let spawned = thread::Builder::new()
+            .name(format!("module {}", name))
+            .spawn(move || {
+                let segments = handle_module(&module, &context, &module_list)
+                    .into_iter()
+                    .flat_map(|module| module_segments(module, powerline))
+                    .collect::<Vec<Segment>>();
+                // The prompt may have been printed without this module already
+                let _ = sender.send((module, segments));
+            });
+        if let Err(error) = spawned {
+            log::warn!("Unable to start a thread for module `{}`: {}", name, error);
+        }
+    }
+    drop(sender);
+
+    let mut finished = FinishedModules::new();
+    while finished.len() < pending.len() {
+        let timeout = deadline.saturating_duration_since(clock.now());
+        match receiver.recv_timeout(timeout) {
+            Ok((module, segments)) => {
+                finished.insert(module, segments);
+            }
+            Err(_) => break,
+        }
+    }
+
+    if config_error {
+        let segments = handle_module("config_error", context, &module_list)
+            .into_iter()
+            .flat_map(|module| module_segments(module, powerline))
+            .collect();
+        finished.insert("config_error".to_string(), segments);
+    }
+
+    let timed_out = pending
+        .into_iter()
+        .filter(|module| !finished.contains_key(module))
+        .collect();
+    (finished, timed_out)
 }
 
-pub fn get_prompt(context: Context) -> String {
+/// Renders the prompt, using the segments of `finished` modules instead of rendering them if
+/// the prompt has a deadline
+fn render_prompt(context: &Context, finished: Option<&FinishedModules>) -> String {
     let config = context.config.get_root_config();
     let mut buf = String::new();
 
-    match std::env::var_os("TERM") {
-        Some(term) if term == "dumb" => {
-            log::error!("Under a 'dumb' terminal (TERM=dumb).");
-            buf.push_str("Starship disabled due to TERM=dumb > ");
-            return buf;
+    if is_dumb_terminal() {
+        if !context.right {
+            buf.push_str(DUMB_TERMINAL_PROMPT);
         }
-        _ => {}
+        return buf;
     }
 
     // A workaround for a fish bug (see #739,#279). Applying it to all shells
     // breaks things (see #808,#824,#834). Should only be printed in fish.
-    if let Shell::Fish = context.shell {
+    if context.shell == Shell::Fish && !context.right {
         buf.push_str("\x1b[J"); // An ASCII control code to clear screen
     }
 
-    let formatter = if let Ok(formatter) = StringFormatter::new(config.format) {
-        formatter
+    // Creates a root module and prints it.
+    let mut root_module = Module::new("Starship Root", "The root module", None);
+    match prompt_segments(context, finished) {
+        Some(segments) => root_module.set_segments(segments),
+        None => {
+            buf.push('>');
+            return buf;
+        }
+    }
+
+    if config.add_newline && !context.right {
+        writeln!(buf).unwrap();
+    }
+    write!(buf, "{}", root_module).unwrap();
+
+    shell_escape::escape_prompt(buf, context.shell)
+}
+
+/// Renders the prompt as segments, including the newline of `add_newline`, for `prompt --watch`
+/// to repaint only what changed
+fn render_segments(context: &Context, finished: Option<&FinishedModules>) -> Vec<Segment> {
+    if is_dumb_terminal() {
+        return vec![Segment::new(None, DUMB_TERMINAL_PROMPT)];
+    }
+
+    let mut segments = Vec::new();
+    if context.config.get_root_config().add_newline {
+        segments.push(Segment::new(None, "\n"));
+    }
+    match prompt_segments(context, finished) {
+        Some(prompt) => segments.extend(prompt),
+        None => segments.push(Segment::new(None, ">")),
+    }
+    segments
+}
+
+/// The prompt shown under a 'dumb' terminal
+const DUMB_TERMINAL_PROMPT: &str = "Starship disabled due to TERM=dumb > ";
+
+fn is_dumb_terminal() -> bool {
+    match std::env::var_os("TERM") {
+        Some(term) if term == "dumb" => {
+            log::error!("Under a 'dumb' terminal (TERM=dumb).");
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The variables of the prompt format, along with the ones of `right_format` when the left
+/// prompt leaves room for the right one, or `None` if the prompt format can't be parsed
+fn prompt_variables(context: &Context) -> Option<BTreeSet<String>> {
+    let mut variables = StringFormatter::new(context.prompt_format())
+        .ok()?
+        .get_variables();
+    let right_format = context.config.get_root_config().right_format;
+    if !context.right && !right_format.is_empty() {
+        if let Ok(formatter) = StringFormatter::new(right_format) {
+            variables.extend(formatter.get_variables());
+        }
+    }
+    Some(variables)
+}
+
+/// The segments of the modules of the prompt format, with the separators between them, or
+/// `None` if the format can't be parsed
+fn prompt_segments(context: &Context, finished: Option<&FinishedModules>) -> Option<Vec<Segment>> {
+    let config = context.config.get_root_config();
+    let module_list = if let Some(module_list) = prompt_variables(context) {
+        module_list
     } else {
         log::error!("Error parsing `format`");
-        buf.push('>');
-        return buf;
+        return None;
+    };
+    let rendered;
+    let finished = match finished {
+        Some(finished) => finished,
+        None => {
+            rendered = handle_modules(context, &module_list);
+            &rendered
+        }
+    };
+
+    let segments = format_segments(context, context.prompt_format(), finished)?;
+    let segments = resolve_overflow(segments, config.overflow_symbol);
+    if context.right {
+        // The right prompt is a single line next to the left one, which it doesn't fill
+        return Some(segments);
+    }
+    let right = right_prompt_width(context, finished).map(|width| {
+        let line = RightFormatLine::parse(config.right_format_line).unwrap_or_else(|| {
+            log::warn!(
+                "Unknown `right_format_line` '{}', expected 'first' or 'last'",
+                config.right_format_line
+            );
+            RightFormatLine::Last
+        });
+        (line, width)
+    });
+    Some(resolve_lines(segments, context.terminal_width(), right))
+}
+
+/// The width of the right prompt the left one leaves room for, or `None` without one
+fn right_prompt_width(context: &Context, finished: &FinishedModules) -> Option<usize> {
+    let right_format = context.config.get_root_config().right_format;
+    if right_format.is_empty() {
+        return None;
+    }
+    let segments = format_segments(context, right_format, finished)?;
+    Some(
+        segments
+            .iter()
+            .map(|segment| segment.value.width_graphemes())
+            .sum(),
+    )
+}
+
+/// The segments of the modules of `format` rendered in `finished`, with the separators
+/// between them, or `None` if the format can't be parsed
+fn format_segments(
+    context: &Context,
+    format: &str,
+    finished: &FinishedModules,
+) -> Option<Vec<Segment>> {
+    let config = context.config.get_root_config();
+    let formatter = StringFormatter::new(format).ok()?;
+    let segments_for = |module: &str| -> Vec<Segment> {
+        match finished.get(module) {
+            Some(segments) => segments.clone(),
+            // Without a prompt character the prompt would be hard to tell from output
+            None if module == "character" => vec![Segment::new(None, "> ")],
+            None => Vec::new(),
+        }
     };
-    let modules = formatter.get_variables();
     let formatter = formatter.map_variables_to_segments(|module| {
         // Make $all display all modules
         if module == "all" {
-            Some(Ok(PROMPT_ORDER
+            Some(Ok(config
+                .prompt_order()
                 .par_iter()
-                .flat_map(|module| {
-                    handle_module(module, &context, &modules)
-                        .into_iter()
-                        .flat_map(|module| module.segments)
-                        .collect::<Vec<Segment>>()
-                })
+                .flat_map(|module| segments_for(module))
                 .collect::<Vec<_>>()))
         } else if context.is_module_disabled_in_config(&module) {
             None
         } else {
             // Get segments from module
-            Some(Ok(handle_module(module, &context, &modules)
-                .into_iter()
-                .flat_map(|module| module.segments)
-                .collect::<Vec<Segment>>()))
+            Some(Ok(segments_for(module)))
         }
     });
 
-    // Creates a root module and prints it.
-    let mut root_module = Module::new("Starship Root", "The root module", None);
-    root_module.set_segments(
+    Some(resolve_joints(
         formatter
             .parse(None)
             .expect("Unexpected error returned in root format variables"),
-    );
-
-    let module_strings = root_module.ansi_strings_for_shell(context.shell);
-    if config.add_newline {
-        writeln!(buf).unwrap();
-    }
-    write!(buf, "{}", ANSIStrings(&module_strings)).unwrap();
-
-    // escape \n and ! characters for tcsh
-    if let Shell::Tcsh = context.shell {
-        buf = buf.replace('!', "\\!");
-        // space is required before newline
-        buf = buf.replace('\n', " \\n");
-    }
-
-    buf
+        &config.separators,
+    ))
 }
 
 pub fn module(module_name: &str, args: ArgMatches) {
@@ -144,55 +684,298 @@ pub fn get_module(module_name: &str, context: Context) -> Option<String> {
     modules::handle(module_name, &context).map(|m| m.to_string())
 }
 
-pub fn timings(args: ArgMatches) {
+/// Prints the output of the prompt's modules for status bars, see `segment_records`
+pub fn segments(args: ArgMatches) {
+    let modules: Option<Vec<String>> = args
+        .values_of("modules")
+        .map(|modules| modules.map(String::from).collect());
     let context = Context::new(args);
+    print!("{}", segment_records(&context, modules.as_deref()));
+}
 
-    struct ModuleTiming {
-        name: String,
-        name_len: usize,
-        value: String,
-        duration: Duration,
-        duration_len: usize,
+/// Modules that only frame the prompt in a shell, left out of `segment_records`
+const SHELL_ONLY_MODULES: &[&str] = &["character", "line_break"];
+
+/// The output of the modules of the prompt, or of `modules` in that order, as one
+/// `module<TAB>text<TAB>style` line per run of text with the same style. The text is plain, with
+/// tabs and newlines replaced by spaces, and the style is a style string like `bold fg:purple`.
+fn segment_records(context: &Context, modules: Option<&[String]>) -> String {
+    let modules = match modules {
+        Some(modules) => {
+            let module_list: BTreeSet<String> = modules.iter().cloned().collect();
+            modules
+                .iter()
+                .filter(|module| {
+                    let known = ALL_MODULES.contains(&module.as_str())
+                        || *module == "custom"
+                        || module.starts_with("custom.")
+                        || context.get_module_alias(module).is_some();
+                    if !known {
+                        log::warn!("Unknown module '{}' in `--modules`", module);
+                    }
+                    known
+                })
+                .flat_map(|module| handle_module(module, context, &module_list))
+                .collect()
+        }
+        None => compute_modules(context),
+    };
+
+    let mut records = String::new();
+    for module in modules {
+        let name = module.get_name();
+        if SHELL_ONLY_MODULES.contains(&name.as_str()) {
+            continue;
+        }
+
+        let mut runs: Vec<(Option<Style>, String)> = Vec::new();
+        for segment in &module.segments {
+            if segment.value.is_empty() {
+                continue;
+            }
+            match runs.last_mut() {
+                Some((style, text)) if *style == segment.style => text.push_str(&segment.value),
+                _ => runs.push((segment.style, segment.value.clone())),
+            }
+        }
+
+        for (style, text) in runs {
+            let text = text.replace(['\t', '\n'], " ");
+            let style = style.map(|style| config::style_to_string(&style));
+            writeln!(records, "{}\t{}\t{}", name, text, style.unwrap_or_default()).unwrap();
+        }
+    }
+    records
+}
+
+/// Prints the description, variables and options of a module, returning false if it is unknown
+pub fn describe(module_name: &str, args: ArgMatches) -> bool {
+    let description = match ModuleDescription::new(module_name) {
+        Some(description) => description,
+        None => {
+            eprintln!("Unknown module: {}", module_name);
+            if let Some(module) = config::did_you_mean(module_name, ALL_MODULES) {
+                eprintln!("Did you mean '{}'?", module);
+            }
+            return false;
+        }
+    };
+
+    if args.value_of("output_format") == Some("json") {
+        println!("{}", serde_json::to_string_pretty(&description).unwrap());
+    } else {
+        print!("{}", explain_module(&description));
+    }
+
+    true
+}
+
+pub fn timings(args: ArgMatches, timings: StartupTimings) -> i32 {
+    let strict = args.is_present("strict");
+    let strict_category = strict_category(&args);
+    let porcelain = args.is_present("porcelain");
+    let json = args.value_of("output_format") == Some("json");
+    let mut context = Context::new_with_timings(args, timings);
+
+    if json && !porcelain {
+        let mut report = prompt_report(&context);
+        context.startup_timings.mark(Phase::Render);
+        report.startup = Some(context.startup_timings.report());
+        report
+            .modules
+            .sort_by_key(|module| std::cmp::Reverse(module.duration_us));
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return exit_code(
+            &context,
+            strict,
+            strict_warning_count(&context, strict, strict_category),
+        );
     }
 
-    let mut modules = compute_modules(&context)
+    let modules = compute_modules(&context)
         .iter()
-        .filter(|module| !module.is_empty() || module.duration.as_millis() > 0)
         .map(|module| ModuleTiming {
-            name: String::from(module.get_name().as_str()),
-            name_len: module.get_name().width_graphemes(),
-            value: ansi_term::ANSIStrings(&module.ansi_strings())
-                .to_string()
-                .replace('\n', "\\n"),
+            name: module.get_name().clone(),
+            value: module.to_string().replace('\n', "\\n"),
             duration: module.duration,
-            duration_len: format_duration(&module.duration).width_graphemes(),
+            produced_output: !module.is_empty(),
         })
         .collect::<Vec<ModuleTiming>>();
 
-    modules.sort_by(|a, b| b.duration.cmp(&a.duration));
+    print!("{}", format_timings(&modules, porcelain, use_color()));
+    exit_code(
+        &context,
+        strict,
+        strict_warning_count(&context, strict, strict_category),
+    )
+}
+
+/// How long a module of the prompt took, for `timings`
+struct ModuleTiming {
+    name: String,
+    value: String,
+    duration: Duration,
+    produced_output: bool,
+}
+
+/// The output of `timings`. With `porcelain`, every module is listed in prompt order as
+/// `name\tmicros\tproduced_output`, a format that doesn't change between releases. Otherwise
+/// the modules that took at least 1ms or produced output are listed from the slowest.
+fn format_timings(modules: &[ModuleTiming], porcelain: bool, color: bool) -> String {
+    if porcelain {
+        return modules
+            .iter()
+            .map(|module| {
+                format!(
+                    "{}\t{}\t{}\n",
+                    module.name,
+                    module.duration.as_micros(),
+                    module.produced_output
+                )
+            })
+            .collect();
+    }
 
-    let max_name_width = modules.iter().map(|i| i.name_len).max().unwrap_or(0);
-    let max_duration_width = modules.iter().map(|i| i.duration_len).max().unwrap_or(0);
+    let mut modules: Vec<&ModuleTiming> = modules
+        .iter()
+        .filter(|module| module.produced_output || module.duration.as_millis() > 0)
+        .collect();
+    modules.sort_by(|a, b| b.duration.cmp(&a.duration));
 
-    println!("\n Here are the timings of modules in your prompt (>=1ms or output):");
+    let rows: Vec<Vec<String>> = modules
+        .iter()
+        .map(|module| {
+            vec![
+                module.name.clone(),
+                format_duration(&module.duration),
+                format!(" {}", module.value),
+            ]
+        })
+        .collect();
+    let columns = [
+        Column::left(Color::Green.bold()),
+        Column::right(Color::Yellow.normal()),
+        Column::left(Style::new()),
+    ];
 
     // for now we do not expect a wrap around at the end... famous last words
-    // Overall a line looks like this: " {module name}  -  {duration}  -  {module value}".
-    for timing in &modules {
-        println!(
-            " {}{}  -  {}{}  -   {}",
-            timing.name,
-            " ".repeat(max_name_width - (timing.name_len)),
-            " ".repeat(max_duration_width - (timing.duration_len)),
-            format_duration(&timing.duration),
-            timing.value
-        );
+    // Overall a line looks like this: " {module name}  -  {duration}  -   {module value}".
+    format!(
+        "\n Here are the timings of modules in your prompt (>=1ms or output):\n{}",
+        format_columns(&rows, &columns, color)
+    )
+}
+
+/// The output of `module --list`. With `porcelain`, only the names, one per line, a format that
+/// doesn't change between releases. Otherwise the names along with their descriptions.
+pub fn format_module_list(names: &[&str], porcelain: bool, color: bool) -> String {
+    if porcelain {
+        return names.iter().map(|name| format!("{}\n", name)).collect();
+    }
+
+    let rows: Vec<Vec<String>> = names
+        .iter()
+        .map(|name| vec![name.to_string(), modules::description(name).to_owned()])
+        .collect();
+    let columns = [
+        Column::left(Color::Green.bold()),
+        Column::left(Style::new()),
+    ];
+
+    format!(
+        "\n Supported modules:\n{}",
+        format_columns(&rows, &columns, color)
+    )
+}
+
+/// Whether the human-facing listings are colored, which `NO_COLOR` turns off
+/// (see https://no-color.org)
+pub fn use_color() -> bool {
+    !matches!(std::env::var_os("NO_COLOR"), Some(value) if !value.is_empty())
+}
+
+/// A column of `format_columns`
+struct Column {
+    style: Style,
+    right_aligned: bool,
+}
+
+impl Column {
+    fn left(style: Style) -> Self {
+        Column {
+            style,
+            right_aligned: false,
+        }
+    }
+
+    fn right(style: Style) -> Self {
+        Column {
+            style,
+            right_aligned: true,
+        }
+    }
+}
+
+/// Lays out `rows` as indented lines of columns separated by `  -  `. Every cell but the last of
+/// a row is padded to the widest of its column by display width, so that emoji and other wide
+/// characters keep the columns aligned. With `color` the cells are painted with the style of
+/// their column.
+fn format_columns(rows: &[Vec<String>], columns: &[Column], color: bool) -> String {
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|index| {
+            rows.iter()
+                .filter_map(|row| row.get(index))
+                .map(|cell| cell.width_graphemes())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(columns.iter().zip(&widths))
+            .enumerate()
+            .map(|(index, (cell, (column, width)))| {
+                let padding = if index + 1 == row.len() && !column.right_aligned {
+                    String::new()
+                } else {
+                    " ".repeat(width - cell.width_graphemes())
+                };
+                let cell = if color {
+                    column.style.paint(cell.as_str()).to_string()
+                } else {
+                    cell.clone()
+                };
+                if column.right_aligned {
+                    format!("{}{}", padding, cell)
+                } else {
+                    format!("{}{}", cell, padding)
+                }
+            })
+            .collect();
+        writeln!(out, " {}", cells.join("  -  ")).unwrap();
     }
+    out
 }
 
-pub fn explain(args: ArgMatches) {
+pub fn explain(args: ArgMatches) -> i32 {
+    let strict = args.is_present("strict");
+    let strict_category = strict_category(&args);
+    let json = args.value_of("output_format") == Some("json");
     let context = Context::new(args);
 
+    if json {
+        let report = prompt_report(&context);
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return exit_code(
+            &context,
+            strict,
+            strict_warning_count(&context, strict, strict_category),
+        );
+    }
+
     struct ModuleInfo {
         value: String,
         value_len: usize,
@@ -210,7 +993,7 @@ pub fn explain(args: ArgMatches) {
         .map(|module| {
             let value = module.get_segments().join("");
             ModuleInfo {
-                value: ansi_term::ANSIStrings(&module.ansi_strings()).to_string(),
+                value: module.to_string(),
                 value_len: value.width_graphemes()
                     + format_duration(&module.duration).width_graphemes(),
                 desc: module.get_description().to_owned(),
@@ -283,32 +1066,258 @@ pub fn explain(args: ArgMatches) {
             );
         };
     }
+
+    exit_code(
+        &context,
+        strict,
+        strict_warning_count(&context, strict, strict_category),
+    )
 }
 
-fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
-    let mut prompt_order: Vec<Module<'a>> = Vec::new();
+/// The segments of a module, followed by a joint when separators are inserted between modules
+fn module_segments(module: Module, powerline: bool) -> Vec<Segment> {
+    let mut segments = module.segments;
+    if module.truncated {
+        segments.push(Segment::overflow());
+    }
+    if powerline && segments.iter().any(|segment| !segment.value.is_empty()) {
+        segments.push(Segment::joint());
+    }
+    segments
+}
 
-    let config = context.config.get_root_config();
-    let formatter = if let Ok(formatter) = StringFormatter::new(config.format) {
-        formatter
-    } else {
-        log::error!("Error parsing `format`");
-        return Vec::new();
-    };
-    let modules = formatter.get_variables();
+/// Replaces the joints between modules with separators.
+///
+/// Joints at the start or end of the prompt or of a line are dropped, and consecutive joints
+/// are merged into a single separator.
+fn resolve_joints(segments: Vec<Segment>, config: &SeparatorsConfig) -> Vec<Segment> {
+    let mut resolved: Vec<Segment> = Vec::with_capacity(segments.len());
+    let mut pending_joint = false;
 
+    for segment in segments {
+        if segment.kind == SegmentKind::Joint {
+            pending_joint = true;
+            continue;
+        }
+        if segment.value.is_empty() {
+            resolved.push(segment);
+            continue;
+        }
+
+        if pending_joint {
+            let prev = resolved.iter().rev().find(|prev| !prev.value.is_empty());
+            if let Some(prev) = prev.filter(|prev| !prev.value.ends_with('\n')) {
+                resolved.extend(separator(prev.background(), segment.background(), config));
+            }
+            pending_joint = false;
+        }
+        resolved.push(segment);
+    }
+
+    resolved
+}
+
+/// Replaces the markers of modules shortened to their `max_length` with `symbol` at the end of
+/// their line, once per line. The line ends before the next segment with a newline.
+fn resolve_overflow(segments: Vec<Segment>, symbol: &str) -> Vec<Segment> {
+    let mut resolved: Vec<Segment> = Vec::with_capacity(segments.len());
+    let mut pending_overflow = false;
+
+    for segment in segments {
+        if segment.kind == SegmentKind::Overflow {
+            pending_overflow = true;
+            continue;
+        }
+        if pending_overflow && segment.value.contains('\n') {
+            resolved.push(Segment::new(None, symbol));
+            pending_overflow = false;
+        }
+        resolved.push(segment);
+    }
+    if pending_overflow {
+        resolved.push(Segment::new(None, symbol));
+    }
+
+    resolved
+}
+
+/// The line of a multi-line prompt the shell shows the right prompt on, see
+/// `right_format_line`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum RightFormatLine {
+    First,
+    Last,
+}
+
+impl RightFormatLine {
+    fn parse(line: &str) -> Option<Self> {
+        match line {
+            "first" => Some(RightFormatLine::First),
+            "last" => Some(RightFormatLine::Last),
+            _ => None,
+        }
+    }
+}
+
+/// Repeats the symbols of the `fill` modules to make each line of the prompt `width` columns
+/// wide, leaving room on one of them for the right prompt of `right`, a line and a width.
+///
+/// The segments are split into lines first, and every line is budgeted on its own: what the
+/// rest of the line leaves of `width` is split evenly between its fills. When the right prompt
+/// doesn't fit next to its line, it has the lowest priority and is dropped, as shells hide it
+/// then, so that the fills take its room. Without a known width, each symbol is shown once.
+fn resolve_lines(
+    segments: Vec<Segment>,
+    width: Option<usize>,
+    right: Option<(RightFormatLine, usize)>,
+) -> Vec<Segment> {
+    let width = match width {
+        Some(width) => width,
+        None => return segments,
+    };
+    let mut lines = split_lines(segments);
+    let right_index = right.map(|(line, _)| match line {
+        RightFormatLine::First => 0,
+        RightFormatLine::Last => lines.len() - 1,
+    });
+
+    for (index, line) in lines.iter_mut().enumerate() {
+        let used: usize = line
+            .iter()
+            .filter(|segment| segment.kind != SegmentKind::Fill)
+            .map(|segment| segment.value.width_graphemes())
+            .sum();
+        let reserved = match right {
+            Some((_, right_width)) if right_index == Some(index) && used + right_width <= width => {
+                right_width
+            }
+            _ => 0,
+        };
+        expand_fills(line, width.saturating_sub(used + reserved));
+    }
+
+    lines.concat()
+}
+
+/// Splits the segments into the lines of the prompt, each ending with the segment of its
+/// newline. A segment with newlines inside is split into parts with its style.
+fn split_lines(segments: Vec<Segment>) -> Vec<Vec<Segment>> {
+    let mut lines = vec![Vec::new()];
+
+    for segment in segments {
+        if !segment.value.contains('\n') {
+            lines.last_mut().unwrap().push(segment);
+            continue;
+        }
+        for part in segment.value.split_inclusive('\n') {
+            let mut part_segment = segment.clone();
+            part_segment.value = part.to_owned();
+            lines.last_mut().unwrap().push(part_segment);
+            if part.ends_with('\n') {
+                lines.push(Vec::new());
+            }
+        }
+    }
+
+    lines
+}
+
+/// Splits `columns` between the fills of the `line`, repeating their symbols. Columns that
+/// can't be split evenly go to the first fills.
+fn expand_fills(line: &mut [Segment], columns: usize) {
+    let fills: Vec<usize> = line
+        .iter()
+        .enumerate()
+        .filter(|(_, segment)| segment.kind == SegmentKind::Fill)
+        .map(|(index, _)| index)
+        .collect();
+    if fills.is_empty() {
+        return;
+    }
+    let (share, rest) = (columns / fills.len(), columns % fills.len());
+
+    for (n, &index) in fills.iter().enumerate() {
+        let columns = if n < rest { share + 1 } else { share };
+        let symbol = &line[index].value;
+        let mut filled = String::new();
+        let mut filled_width = 0;
+        for grapheme in symbol.graphemes(true).cycle() {
+            let grapheme_width = grapheme.width_graphemes();
+            if grapheme_width == 0 || filled_width + grapheme_width > columns {
+                break;
+            }
+            filled.push_str(grapheme);
+            filled_width += grapheme_width;
+        }
+        // Wide symbols may leave a column that is padded instead
+        filled.push_str(&" ".repeat(columns - filled_width));
+        line[index].value = filled;
+    }
+}
+
+/// The separator between a segment with `prev_bg` and one with `next_bg`.
+///
+/// The separator takes the background of the previous segment as its foreground and the one
+/// of the next segment as its background. A separator leading into a module with a background
+/// from one without uses `right_separator`, and one between modules without any background is
+/// left unstyled.
+fn separator(
+    prev_bg: Option<Color>,
+    next_bg: Option<Color>,
+    config: &SeparatorsConfig,
+) -> Option<Segment> {
+    let (symbol, style) = match (prev_bg, next_bg) {
+        (Some(prev_bg), Some(next_bg)) => (
+            config.left_separator,
+            Some(Style::new().fg(prev_bg).on(next_bg)),
+        ),
+        (Some(prev_bg), None) => (config.left_separator, Some(Style::new().fg(prev_bg))),
+        (None, Some(next_bg)) => (config.right_separator, Some(Style::new().fg(next_bg))),
+        (None, None) => (config.left_separator, None),
+    };
+
+    if symbol.is_empty() {
+        None
+    } else {
+        Some(Segment::new(style, symbol))
+    }
+}
+
+fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
+    let mut prompt_order: Vec<Module<'a>> = Vec::new();
+
+    let config = context.config.get_root_config();
+    let formatter = if let Ok(formatter) = StringFormatter::new(context.prompt_format()) {
+        formatter
+    } else {
+        log::error!("Error parsing `format`");
+        return Vec::new();
+    };
+    let modules = formatter.get_variables();
+
+    let mut names = Vec::new();
     for module in &modules {
         // Manually add all modules if `$all` is encountered
         if module == "all" {
-            for module in PROMPT_ORDER.iter() {
-                let modules = handle_module(module, &context, &modules);
-                prompt_order.extend(modules.into_iter());
-            }
+            names.extend(config.prompt_order().into_iter().map(str::to_string));
+        } else {
+            names.push(module.clone());
+        }
+    }
+
+    // `config_error` counts the errors of the other modules, so it's rendered after them
+    let mut config_error = None;
+    for module in &names {
+        if module == "config_error" {
+            config_error.get_or_insert(prompt_order.len());
         } else {
-            let modules = handle_module(module, &context, &modules);
-            prompt_order.extend(modules.into_iter());
+            prompt_order.extend(handle_module(module, &context, &modules));
         }
     }
+    if let Some(index) = config_error {
+        let modules = handle_module("config_error", &context, &modules);
+        prompt_order.splice(index..index, modules);
+    }
 
     prompt_order
 }
@@ -317,6 +1326,17 @@ fn handle_module<'a>(
     module: &str,
     context: &'a Context,
     module_list: &BTreeSet<String>,
+) -> Vec<Module<'a>> {
+    logger::counting_errors(&context.errors, || {
+        render_module(module, context, module_list)
+    })
+}
+
+/// Renders `module` of the prompt format with the variables `module_list`, see `handle_module`
+fn render_module<'a>(
+    module: &str,
+    context: &'a Context,
+    module_list: &BTreeSet<String>,
 ) -> Vec<Module<'a>> {
     struct DebugCustomModules<'tmp>(&'tmp toml::value::Table);
 
@@ -336,13 +1356,24 @@ fn handle_module<'a>(
     } else if module == "custom" {
         // Write out all custom modules, except for those that are explicitly set
         if let Some(custom_modules) = context.config.get_custom_modules() {
-            let custom_modules = custom_modules.iter().filter_map(|(custom_module, config)| {
-                if should_add_implicit_custom_module(custom_module, config, &module_list) {
-                    modules::custom::module(custom_module, &context)
-                } else {
-                    None
-                }
+            // The config file order, unless overridden with `order`
+            let mut custom_modules: Vec<_> = custom_modules.iter().collect();
+            custom_modules.sort_by_key(|(_, config)| {
+                config
+                    .get("order")
+                    .and_then(toml::Value::as_integer)
+                    .unwrap_or(0)
             });
+            let custom_modules =
+                custom_modules
+                    .into_iter()
+                    .filter_map(|(custom_module, config)| {
+                        if should_add_implicit_custom_module(custom_module, config, &module_list) {
+                            modules::custom::module(custom_module, &context)
+                        } else {
+                            None
+                        }
+                    });
             modules.extend(custom_modules);
         }
     } else if let Some(module) = module.strip_prefix("custom.") {
@@ -362,6 +1393,14 @@ fn handle_module<'a>(
                     ),
             },
         }
+    } else if let Some(alias) = context.get_module_alias(module) {
+        // Write out the module of an alias with the config of the alias, unless it's disabled
+        let disabled = alias.config.get("disabled").and_then(toml::Value::as_bool);
+        if disabled != Some(true) {
+            modules.extend(
+                context.with_module_alias(module, || modules::handle(&alias.module, context)),
+            );
+        }
     } else {
         log::debug!(
             "Expected top level format to contain value from {:?}. Instead received {}",
@@ -403,3 +1442,1055 @@ pub fn format_duration(duration: &Duration) -> String {
         format!("{:?}ms", &milis)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use crate::formatter::StyleVariableHolder;
+    use crate::modules::PROMPT_ORDER;
+    use crate::test::{fixture_repo, FixtureProvider};
+    use std::path::PathBuf;
+
+    fn module_names(config: toml::Value) -> io::Result<Vec<String>> {
+        let dir = tempfile::tempdir()?;
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            dir.path().into(),
+            dir.path().into(),
+        );
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+
+        let names = compute_modules(&context)
+            .iter()
+            .map(|module| module.get_name().to_owned())
+            .collect();
+        dir.close()?;
+        Ok(names)
+    }
+
+    #[test]
+    fn default_order_reorders_all() -> io::Result<()> {
+        let names = module_names(toml::toml! {
+            format = "$all"
+            default_order = ["character", "directory"]
+        })?;
+
+        assert_eq!(names[..2], ["character", "directory"]);
+        Ok(())
+    }
+
+    #[test]
+    fn default_order_skips_unknown_modules() -> io::Result<()> {
+        let names = module_names(toml::toml! {
+            format = "$all"
+            default_order = ["not_a_module", "character"]
+        })?;
+
+        assert_eq!(names[0], "character");
+        assert!(names.contains(&String::from("directory")));
+        Ok(())
+    }
+
+    #[test]
+    fn default_order_appends_missing_modules() {
+        let config = StarshipConfig {
+            config: Some(toml::toml! {
+                default_order = ["time", "username", "time"]
+            }),
+        };
+        let order = config.get_root_config().prompt_order();
+
+        assert_eq!(order[..3], ["time", "username", "hostname"]);
+        assert_eq!(order.len(), PROMPT_ORDER.len());
+    }
+
+    #[test]
+    fn default_prompt_does_not_access_network() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        // terraform is the only module running a command that accesses the network
+        std::fs::create_dir(repo_dir.path().join(".terraform"))?;
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            repo_dir.path().into(),
+            repo_dir.path().into(),
+        );
+        context.config = StarshipConfig { config: None };
+
+        let modules = compute_modules(&context);
+        assert!(modules
+            .iter()
+            .any(|module| module.get_name() == "terraform"));
+        assert!(context.network_log.lock().unwrap().is_empty());
+        repo_dir.close()
+    }
+
+    #[test]
+    fn offline_denies_network_access() {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            PathBuf::new(),
+            PathBuf::new(),
+        );
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                offline = true
+            }),
+        };
+
+        assert!(!context.allow_network("test"));
+        assert!(context.network_log.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn prompt_for_path_ignores_process_dir() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        std::process::Command::new("git")
+            .args(["checkout", "-q", "-b", "path-fixture"])
+            .current_dir(repo_dir.path())
+            .output()?;
+
+        // The process runs in another repository, which `PWD` points at too
+        let process_dir = fixture_repo(FixtureProvider::Git)?;
+        let args = clap::App::new("starship")
+            .arg(clap::Arg::with_name("path").long("path").takes_value(true))
+            .get_matches_from(vec![
+                "starship",
+                "--path",
+                repo_dir.path().to_str().unwrap(),
+            ]);
+        let (path, logical_path) = Context::prompt_paths(
+            &args,
+            Some(process_dir.path().to_path_buf()),
+            Some(process_dir.path().to_path_buf()),
+        );
+        let context = context_with_config(
+            toml::toml! {
+                format = "$directory$git_branch"
+                [directory]
+                style = "none"
+                format = "[$path]($style) "
+                [git_branch]
+                format = "on $branch"
+            },
+            path,
+        );
+
+        assert_eq!(logical_path, repo_dir.path());
+        let repo_name = repo_dir.path().file_name().unwrap().to_string_lossy();
+        assert_eq!(
+            get_prompt(context),
+            format!("\n{} on path-fixture", repo_name)
+        );
+
+        process_dir.close()?;
+        repo_dir.close()
+    }
+
+    fn context_with_config(config: toml::Value, path: PathBuf) -> Context<'static> {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            path.clone(),
+            path,
+        );
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+        context
+    }
+
+    fn slow_module_config() -> toml::Value {
+        toml::toml! {
+            format = "${custom.fast}${custom.slow}$character"
+            add_newline = false
+            prompt_timeout_ms = 10_000
+
+            [custom.fast]
+            command = "echo fast"
+            when = "true"
+            shell = ["sh"]
+            format = "$output "
+
+            [custom.slow]
+            command = "sleep 5; echo slow"
+            when = "true"
+            shell = ["sh"]
+            format = "$output "
+
+            [character]
+            format = "$symbol"
+            success_symbol = "[>](none)"
+        }
+    }
+
+    /// A clock that stands still for its first `reads` reads, then jumps past every deadline.
+    /// The prompt reads it once for the deadline and once before waiting for each module, so
+    /// the deadline passes once `reads - 1` modules finished.
+    struct ExpiringClock {
+        start: Instant,
+        reads: usize,
+        read: std::sync::Mutex<usize>,
+    }
+
+    impl ExpiringClock {
+        fn new(reads: usize) -> Self {
+            Self {
+                start: Instant::now(),
+                reads,
+                read: Default::default(),
+            }
+        }
+    }
+
+    impl Clock for ExpiringClock {
+        fn now(&self) -> Instant {
+            let mut read = self.read.lock().unwrap();
+            *read += 1;
+            if *read <= self.reads {
+                self.start
+            } else {
+                self.start + Duration::from_secs(24 * 60 * 60)
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn prompt_timeout_leaves_out_slow_modules() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = context_with_config(slow_module_config(), dir.path().to_path_buf());
+        // The deadline passes once `custom.fast` and `character` finished
+        context.mock_prompt_clock = Some(Arc::new(ExpiringClock::new(3)));
+
+        let actual = get_prompt(context);
+
+        assert_eq!(actual, "fast >");
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn prompt_timeout_reports_slow_modules() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_with_config(slow_module_config(), dir.path().to_path_buf());
+        let context = Arc::new(context);
+        let clock = ExpiringClock::new(3);
+
+        let deadline = clock.now() + Duration::from_secs(10);
+        let (finished, timed_out) = handle_modules_until(&context, deadline, &clock);
+
+        assert_eq!(timed_out, vec!["custom.slow"]);
+        assert!(finished.contains_key("custom.fast"));
+        assert!(finished.contains_key("character"));
+        dir.close()
+    }
+
+    #[test]
+    fn prompt_timeout_character_fallback() {
+        let context = context_with_config(
+            toml::toml! {
+                format = "$directory$character"
+                add_newline = false
+            },
+            PathBuf::new(),
+        );
+
+        assert_eq!(render_prompt(&context, Some(&FinishedModules::new())), "> ");
+    }
+
+    fn prompt_with_config(config: toml::Value) -> String {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            PathBuf::new(),
+            PathBuf::new(),
+        );
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+
+        get_prompt(context)
+    }
+
+    fn prompt_at_width(width: usize) -> String {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            PathBuf::new(),
+            PathBuf::new(),
+        );
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                format = "wide $character"
+                compact_format = "narrow $character"
+                compact_width_threshold = 80
+                add_newline = false
+                [character]
+                format = "chr"
+            }),
+        };
+        context
+            .properties
+            .insert("terminal_width", width.to_string());
+
+        get_prompt(context)
+    }
+
+    #[test]
+    fn compact_format_at_or_below_threshold() {
+        assert_eq!(prompt_at_width(40), "narrow chr");
+        assert_eq!(prompt_at_width(80), "narrow chr");
+    }
+
+    #[test]
+    fn format_above_threshold() {
+        assert_eq!(prompt_at_width(81), "wide chr");
+    }
+
+    #[test]
+    fn config_error_in_default_prompt() {
+        let actual = prompt_with_config(toml::toml! {
+            [aws]
+            show_duration = true
+        });
+
+        assert!(actual.contains("⚠ 1"));
+    }
+
+    #[test]
+    fn config_error_counts_errors_of_the_prompt() {
+        let actual = prompt_with_config(toml::toml! {
+            format = "$config_error$aws$custom$character"
+            add_newline = false
+            add_newlines = false
+            [aws]
+            show_duration = true
+            [character]
+            format = "chr"
+            [custom.foo]
+            command = "echo foo"
+            whn = "true"
+            [git_branch]
+            symbl = "unused"
+        });
+
+        // `git_branch` isn't in the prompt
+        assert_eq!(format!("{} chr", Color::Red.bold().paint("⚠ 3")), actual);
+    }
+
+    #[test]
+    fn config_error_custom_symbol() {
+        let actual = prompt_with_config(toml::toml! {
+            format = "$config_error$character"
+            add_newline = false
+            [character]
+            format = "chr"
+            formt = "chr"
+            [config_error]
+            symbol = "!! "
+            format = "[$symbol]($style)"
+        });
+
+        assert_eq!(format!("{}chr", Color::Red.bold().paint("!! ")), actual);
+    }
+
+    #[test]
+    fn config_error_removed_from_format() {
+        let actual = prompt_with_config(toml::toml! {
+            format = "$character"
+            add_newline = false
+            [aws]
+            show_duration = true
+            [character]
+            format = "chr"
+        });
+
+        assert_eq!("chr", actual);
+    }
+
+    #[test]
+    fn powerline_separator_between_backgrounds() {
+        let actual = prompt_with_config(toml::toml! {
+            format = "$directory$character"
+            add_newline = false
+            [separators]
+            mode = "powerline"
+            [directory]
+            format = "[dir](bg:red)"
+            [character]
+            format = "[chr](bg:blue)"
+        });
+
+        let expected = "\u{1b}[41mdir\u{1b}[0m\u{1b}[44;31m\u{e0b0}\u{1b}[0m\u{1b}[44mchr\u{1b}[0m";
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn powerline_separator_without_background() {
+        let actual = prompt_with_config(toml::toml! {
+            format = "$directory$character"
+            add_newline = false
+            [separators]
+            mode = "powerline"
+            left_separator = "|"
+            [directory]
+            format = "dir"
+            [character]
+            format = "chr"
+        });
+
+        assert_eq!("dir|chr", actual);
+    }
+
+    #[test]
+    fn powerline_single_module() {
+        let actual = prompt_with_config(toml::toml! {
+            format = "$directory"
+            add_newline = false
+            [separators]
+            mode = "powerline"
+            [directory]
+            format = "[dir](bg:red)"
+        });
+
+        let expected = format!("{}", Style::new().on(Color::Red).paint("dir"));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn plain_mode_adds_no_separators() {
+        let actual = prompt_with_config(toml::toml! {
+            format = "$directory$character"
+            add_newline = false
+            [separators]
+            left_separator = "|"
+            [directory]
+            format = "dir"
+            [character]
+            format = "chr"
+        });
+
+        assert_eq!("dirchr", actual);
+    }
+
+    #[test]
+    fn json_report() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            repo_dir.path().into(),
+            repo_dir.path().into(),
+        );
+        context.config = StarshipConfig { config: None };
+
+        let json = serde_json::to_string(&prompt_report(&context))?;
+        let report: serde_json::Value = serde_json::from_str(&json)?;
+
+        assert_eq!(report["version"], shadow::PKG_VERSION);
+        assert!(report["total_us"].is_u64());
+        let git_branch = report["modules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|module| module["module"] == "git_branch")
+            .unwrap();
+        assert!(git_branch["text_plain"]
+            .as_str()
+            .unwrap()
+            .ends_with("master "));
+        assert!(git_branch["text_plain"]
+            .as_str()
+            .unwrap()
+            .starts_with("on "));
+        assert!(git_branch["duration_us"].is_u64());
+        assert_eq!(git_branch["produced_output"], true);
+        repo_dir.close()
+    }
+
+    fn segments_config() -> toml::Value {
+        toml::toml! {
+            format = "$directory$git_branch$line_break$character"
+            [directory]
+            format = "[$path]($style) "
+            [git_branch]
+            format = "on [$symbol$branch]($style)"
+            symbol = "⎇ "
+            style = "bold 208"
+        }
+    }
+
+    #[test]
+    fn segment_records_of_filtered_modules() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let context = context_with_config(segments_config(), repo_dir.path().into());
+        let repo_name = repo_dir.path().file_name().unwrap().to_string_lossy();
+
+        let modules: Vec<String> = ["git_branch", "character", "line_break", "directory"]
+            .iter()
+            .map(|module| module.to_string())
+            .collect();
+        assert_eq!(
+            segment_records(&context, Some(&modules)),
+            format!(
+                "git_branch\ton \t\n\
+                 git_branch\t⎇ master\tbold fg:208\n\
+                 directory\t{}\tbold fg:cyan\n\
+                 directory\t \t\n",
+                repo_name
+            )
+        );
+        repo_dir.close()
+    }
+
+    #[test]
+    fn segment_records_of_prompt() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let context = context_with_config(segments_config(), repo_dir.path().into());
+        let repo_name = repo_dir.path().file_name().unwrap().to_string_lossy();
+
+        let records = segment_records(&context, None);
+        assert_eq!(
+            records,
+            format!(
+                "directory\t{}\tbold fg:cyan\n\
+                 directory\t \t\n\
+                 git_branch\ton \t\n\
+                 git_branch\t⎇ master\tbold fg:208\n",
+                repo_name
+            )
+        );
+        assert!(!records.contains('\x1b'));
+        repo_dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn segment_records_are_single_lines() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_with_config(
+            toml::toml! {
+                format = "${custom.multiline}"
+                [custom.multiline]
+                command = "printf 'a\\tb'"
+                when = "true"
+                shell = ["sh"]
+                format = "$output\n"
+            },
+            dir.path().to_path_buf(),
+        );
+
+        assert_eq!(segment_records(&context, None), "multiline\ta b \t\n");
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn custom_modules_in_config_order() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_with_config(
+            toml::toml! {
+                add_newline = false
+                format = "${custom}"
+                [custom.zeta]
+                command = "echo zeta"
+                when = "true"
+                shell = ["sh"]
+                format = "$output "
+                [custom.alpha]
+                command = "echo alpha"
+                when = "true"
+                shell = ["sh"]
+                format = "$output "
+                [custom.mu]
+                command = "echo mu"
+                when = "true"
+                shell = ["sh"]
+                format = "$output "
+            },
+            dir.path().to_path_buf(),
+        );
+
+        assert_eq!(get_prompt(context), "zeta alpha mu ");
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn custom_modules_sorted_by_order() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_with_config(
+            toml::toml! {
+                add_newline = false
+                format = "${custom}"
+                [custom.zeta]
+                command = "echo zeta"
+                when = "true"
+                shell = ["sh"]
+                format = "$output "
+                order = 10
+                [custom.alpha]
+                command = "echo alpha"
+                when = "true"
+                shell = ["sh"]
+                format = "$output "
+                [custom.mu]
+                command = "echo mu"
+                when = "true"
+                shell = ["sh"]
+                format = "$output "
+                order = -1
+            },
+            dir.path().to_path_buf(),
+        );
+
+        assert_eq!(get_prompt(context), "mu alpha zeta ");
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn single_custom_module_in_format() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_with_config(
+            toml::toml! {
+                add_newline = false
+                format = "${custom.mu}|${custom}"
+                [custom.zeta]
+                command = "echo zeta"
+                when = "true"
+                shell = ["sh"]
+                format = "$output "
+                [custom.mu]
+                command = "echo mu"
+                when = "true"
+                shell = ["sh"]
+                format = "$output "
+            },
+            dir.path().to_path_buf(),
+        );
+
+        assert_eq!(get_prompt(context), "mu |zeta ");
+        dir.close()
+    }
+
+    #[cfg(not(windows))]
+    fn overflow_config(max_length: i64) -> toml::Value {
+        toml::toml! {
+            add_newline = false
+            overflow_symbol = "»"
+            format = "${custom.long}$line_break${custom.short}"
+            [custom.long]
+            command = "echo a long output"
+            when = "true"
+            shell = ["sh"]
+            format = "$output"
+            max_length = max_length
+            [custom.short]
+            command = "echo short"
+            when = "true"
+            shell = ["sh"]
+            format = "$output"
+            max_length = max_length
+        }
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn overflow_symbol_ends_truncated_line() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_with_config(overflow_config(6), dir.path().to_path_buf());
+
+        assert_eq!(render_prompt(&context, None), "a lon…»\nshort");
+        let report = serde_json::to_value(prompt_report(&context))?;
+        assert_eq!(report["truncated"], serde_json::json!(["long"]));
+
+        let mut config = overflow_config(6);
+        config.as_table_mut().unwrap().remove("overflow_symbol");
+        let context = context_with_config(config, dir.path().to_path_buf());
+        assert_eq!(render_prompt(&context, None), "a lon…\nshort");
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn no_overflow_symbol_when_nothing_truncated() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_with_config(overflow_config(40), dir.path().to_path_buf());
+
+        assert_eq!(render_prompt(&context, None), "a long output\nshort");
+        let report = serde_json::to_value(prompt_report(&context))?;
+        assert_eq!(report["truncated"], serde_json::json!([]));
+        dir.close()
+    }
+
+    #[test]
+    fn overflow_symbol_once_per_line() {
+        let segments = vec![
+            Segment::new(None, "a"),
+            Segment::overflow(),
+            Segment::new(None, "b"),
+            Segment::overflow(),
+            Segment::new(None, "\n"),
+            Segment::new(None, "c"),
+            Segment::overflow(),
+        ];
+        let values: Vec<String> = resolve_overflow(segments, "»")
+            .into_iter()
+            .map(|segment| segment.value)
+            .collect();
+
+        assert_eq!(values, ["a", "b", "»", "\n", "c", "»"]);
+    }
+
+    fn prompt_with_fills(width: usize) -> String {
+        prompt_with_right_format("", "last", width)
+    }
+
+    /// A two-line prompt with fills on both lines, next to the right prompt `right_format`
+    fn prompt_with_right_format(right_format: &str, line: &str, width: usize) -> String {
+        let mut context = context_with_config(
+            toml::toml! {
+                add_newline = false
+                format = "left${fill}right$line_break${fill}mid${fill}> "
+                right_format = right_format
+                right_format_line = line
+                [fill]
+                symbol = "-"
+                style = ""
+            },
+            PathBuf::new(),
+        );
+        context
+            .properties
+            .insert("terminal_width", width.to_string());
+
+        render_prompt(&context, None)
+    }
+
+    #[test]
+    fn fill_expands_each_line_to_the_width() {
+        assert_eq!(prompt_with_fills(12), "left---right\n----mid---> ");
+        assert_eq!(prompt_with_fills(9), "leftright\n--mid--> ");
+    }
+
+    #[test]
+    fn fill_shows_symbol_once_without_width() {
+        let segments = vec![
+            Segment::new(None, "left"),
+            Segment::fill(None, "-"),
+            Segment::new(None, "right\n"),
+            Segment::fill(None, "-"),
+        ];
+        let values: Vec<String> = resolve_lines(segments, None, Some((RightFormatLine::Last, 5)))
+            .into_iter()
+            .map(|segment| segment.value)
+            .collect();
+
+        assert_eq!(values, ["left", "-", "right\n", "-"]);
+    }
+
+    #[test]
+    fn fill_leaves_room_for_the_right_prompt_on_its_line() {
+        assert_eq!(
+            prompt_with_right_format("RIGHT", "last", 16),
+            "left-------right\n---mid---> "
+        );
+        assert_eq!(
+            prompt_with_right_format("RIGHT", "first", 16),
+            "left--right\n------mid-----> "
+        );
+    }
+
+    #[test]
+    fn right_prompt_that_does_not_fit_is_dropped_first() {
+        // The first line has 3 columns left, too few for the right prompt
+        assert_eq!(
+            prompt_with_right_format("RIGHT", "first", 12),
+            "left---right\n----mid---> "
+        );
+        assert_eq!(
+            prompt_with_right_format("RIGHT", "last", 12),
+            "left---right\n-mid-> "
+        );
+    }
+
+    #[test]
+    fn right_prompt_renders_right_format() {
+        let mut context = context_with_config(
+            toml::toml! {
+                format = "left$line_break> "
+                right_format = "${fill}right"
+            },
+            PathBuf::new(),
+        );
+        context.right = true;
+        context
+            .properties
+            .insert("terminal_width", "20".to_string());
+
+        // Without a blank line before it, and a single line that isn't filled
+        let prompt = render_prompt(&context, None);
+        assert!(prompt.ends_with("right"), "{:?}", prompt);
+        assert!(!prompt.contains('\n'), "{:?}", prompt);
+    }
+
+    #[test]
+    fn fill_repeats_wide_symbols_and_pads_the_rest() {
+        let segments = vec![
+            Segment::new(None, "ab\ncd"),
+            Segment::fill(None, "=-"),
+            Segment::new(None, "e"),
+            Segment::fill(None, "日"),
+        ];
+        let values: Vec<String> = resolve_lines(segments, Some(9), None)
+            .into_iter()
+            .map(|segment| segment.value)
+            .collect();
+
+        assert_eq!(values, ["ab\n", "cd", "=-=", "e", "日 "]);
+    }
+
+    #[test]
+    fn module_alias_renders_module_with_its_config() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let context = context_with_config(
+            toml::toml! {
+                format = "$gb_short|$git_branch"
+                [git_branch]
+                format = "$branch"
+                truncation_length = 4
+                truncation_symbol = ""
+                [module_aliases.gb_short]
+                module = "git_branch"
+                config = { truncation_length = 2 }
+            },
+            repo_dir.path().to_path_buf(),
+        );
+
+        let module_list = BTreeSet::new();
+        let render = |module: &str| {
+            handle_module(module, &context, &module_list)
+                .iter()
+                .map(Module::to_string)
+                .collect::<String>()
+        };
+        assert_eq!(render("gb_short"), "ma");
+        assert_eq!(render("git_branch"), "mast");
+        assert_eq!(render_prompt(&context, None), "\nma|mast");
+
+        // Both share the repo of the context, read without running `git`
+        let commands = context.cmd_log.lock().unwrap().clone();
+        assert!(commands.is_empty());
+        repo_dir.close()
+    }
+
+    #[test]
+    fn exit_codes() {
+        let mut context = Context::new_with_shell_and_path(
+            ArgMatches::default(),
+            Shell::Unknown,
+            PathBuf::new(),
+            PathBuf::new(),
+        );
+        context.config_degraded = false;
+
+        assert_eq!(exit_code(&context, false, 0), EXIT_SUCCESS);
+        assert_eq!(exit_code(&context, false, 2), EXIT_SUCCESS);
+        assert_eq!(exit_code(&context, true, 0), EXIT_SUCCESS);
+        assert_eq!(exit_code(&context, true, 2), EXIT_STRICT_WARNINGS);
+
+        // A broken config file takes precedence over warnings
+        context.config_degraded = true;
+        assert_eq!(exit_code(&context, false, 0), EXIT_CONFIG_DEGRADED);
+        assert_eq!(exit_code(&context, true, 2), EXIT_CONFIG_DEGRADED);
+    }
+
+    #[test]
+    fn describe_covers_default_formats() {
+        let defaults = toml::Value::try_from(FullConfig::default()).unwrap();
+
+        for module in ALL_MODULES {
+            let description = ModuleDescription::new(module).unwrap();
+            // `line_break` has no config
+            let config = match defaults.get(module).and_then(|config| config.as_table()) {
+                Some(config) => config,
+                None => continue,
+            };
+
+            let formats = config
+                .iter()
+                .filter(|(key, _)| *key == "format" || key.ends_with("_format"))
+                // `version_format` has variables of its own, e.g. `$major`
+                .filter(|(key, _)| *key != "version_format")
+                .filter_map(|(_, value)| value.as_str());
+
+            for format in formats {
+                let formatter = StringFormatter::new(format).unwrap();
+                let variables = formatter
+                    .get_variables()
+                    .into_iter()
+                    .chain(formatter.get_style_variables());
+                for variable in variables {
+                    assert!(
+                        description.variables.contains(&variable.as_str()),
+                        "${} of {} is not described",
+                        variable,
+                        module
+                    );
+                }
+            }
+
+            for option in &description.options {
+                assert!(config.contains_key(option.key) || option.default.is_none());
+            }
+        }
+
+        let git_status = ModuleDescription::new("git_status").unwrap();
+        let format = git_status.options.iter().find(|o| o.key == "format");
+        assert!(format.unwrap().default.is_some());
+        assert!(ModuleDescription::new("no_such_module").is_none());
+    }
+
+    #[test]
+    fn explain_config_of_git_commit() {
+        let description = ModuleDescription::new("git_commit").unwrap();
+        let text = explain_module(&description);
+
+        assert!(text.starts_with("git_commit - The active commit"));
+        assert!(text.contains("  $hash\n"));
+        assert!(text.contains(
+            "  commit_hash_length: integer = 7\n      The length of the displayed git commit hash.\n"
+        ));
+        assert!(text.contains("  only_detached: boolean = true\n"));
+
+        let json = serde_json::to_value(&description).unwrap();
+        let option = &json["options"][0];
+        assert_eq!(option["key"], "commit_hash_length");
+        assert_eq!(option["type"], "integer");
+        assert_eq!(option["default"], 7);
+    }
+
+    #[test]
+    fn every_module_option_is_described() {
+        for module in ALL_MODULES {
+            let description = ModuleDescription::new(module).unwrap();
+            assert!(
+                !description.description.is_empty(),
+                "{} has no description",
+                module
+            );
+            for option in &description.options {
+                assert!(
+                    !option.description.is_empty(),
+                    "Option `{}` of {} has no doc comment",
+                    option.key,
+                    module
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn toml_types() {
+        assert_eq!(toml_type("& 'a str"), "string");
+        assert_eq!(toml_type("Option < & 'a str >"), "string");
+        assert_eq!(toml_type("Vec < & 'a str >"), "array of string");
+        assert_eq!(toml_type("VecOr < & 'a str >"), "string or array of string");
+        assert_eq!(
+            toml_type("Vec < GitBranchStyleRule < 'a > >"),
+            "array of table"
+        );
+        assert_eq!(toml_type("IndexMap < String, & 'a str >"), "table");
+        assert_eq!(toml_type("u64"), "integer");
+        assert_eq!(toml_type("bool"), "boolean");
+    }
+
+    fn timing(name: &str, micros: u64, produced_output: bool) -> ModuleTiming {
+        ModuleTiming {
+            name: name.to_owned(),
+            value: if produced_output {
+                "x ".to_owned()
+            } else {
+                String::new()
+            },
+            duration: Duration::from_micros(micros),
+            produced_output,
+        }
+    }
+
+    #[test]
+    fn module_list_porcelain() {
+        let actual = format_module_list(&["aws", "git_branch", "character"], true, true);
+        assert_eq!(actual, "aws\ngit_branch\ncharacter\n");
+    }
+
+    #[test]
+    fn module_list_aligns_descriptions() {
+        let actual = format_module_list(&["aws", "new_version_available"], false, false);
+        let expected = concat!(
+            "\n Supported modules:\n",
+            " aws                    -  The current AWS region and profile\n",
+            " new_version_available  -  A newer starship release, when `update_check` is enabled\n",
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn module_list_colors_names() {
+        let actual = format_module_list(&["aws"], false, true);
+        assert!(actual.contains(&format!(
+            " {}  -  The current AWS",
+            Color::Green.bold().paint("aws")
+        )));
+    }
+
+    #[test]
+    fn timings_porcelain() {
+        let modules = [
+            timing("directory", 1530, true),
+            timing("hostname", 12, false),
+            timing("git_status", 25_000, true),
+        ];
+        let actual = format_timings(&modules, true, true);
+        assert_eq!(
+            actual,
+            "directory\t1530\ttrue\nhostname\t12\tfalse\ngit_status\t25000\ttrue\n"
+        );
+    }
+
+    #[test]
+    fn timings_aligns_columns() {
+        let modules = [
+            timing("directory", 1530, true),
+            timing("hostname", 12, false),
+            timing("git_status", 25_000, true),
+            timing("c", 200, true),
+        ];
+        let actual = format_timings(&modules, false, false);
+        let expected = concat!(
+            "\n Here are the timings of modules in your prompt (>=1ms or output):\n",
+            " git_status  -  25ms  -   x \n",
+            " directory   -   1ms  -   x \n",
+            " c           -  <1ms  -   x \n",
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn columns_align_by_display_width() {
+        let rows = vec![
+            vec!["🚀".to_owned(), "wide".to_owned()],
+            vec!["abc".to_owned(), "narrow".to_owned()],
+        ];
+        let columns = [Column::left(Style::new()), Column::left(Style::new())];
+        let actual = format_columns(&rows, &columns, false);
+        assert_eq!(actual, " 🚀   -  wide\n abc  -  narrow\n");
+    }
+}
@@ -0,0 +1,218 @@
+use ansi_term::Style;
+use std::fmt::Write;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::print::UnicodeWidthGraphemes;
+use crate::segment::{paint_segments, Segment};
+
+/// A grapheme of the prompt along with its style
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cell {
+    style: Style,
+    grapheme: String,
+}
+
+/// A line of the prompt, as it was painted on the terminal
+pub type Line = Vec<Cell>;
+
+/// Splits the segments of a prompt into its lines. A prompt always has at least one line.
+pub fn lines(segments: &[Segment]) -> Vec<Line> {
+    let mut lines = vec![Line::new()];
+    for segment in segments {
+        let style = segment.style.unwrap_or_default();
+        for (index, part) in segment.value.split('\n').enumerate() {
+            if index > 0 {
+                lines.push(Line::new());
+            }
+            let line = lines.last_mut().expect("a prompt always has a line");
+            line.extend(part.graphemes(true).map(|grapheme| Cell {
+                style,
+                grapheme: grapheme.to_owned(),
+            }));
+        }
+    }
+    lines
+}
+
+/// The control sequences that turn the painted `old` prompt into `new`, rewriting each line
+/// only from the first cell that changed and clearing what's left of the old line after it.
+///
+/// The cursor is expected at the end of `old` and is left at the end of `new`, like after
+/// printing the prompt. Lines wider than the terminal aren't supported, as they wrap.
+pub fn repaint(old: &[Line], new: &[Line]) -> String {
+    let mut cursor = Cursor {
+        row: old.len().saturating_sub(1),
+        column: old.last().map(|line| width(line)),
+    };
+    let mut out = String::new();
+
+    for row in 0..old.len().max(new.len()) {
+        let old_line = old.get(row).map(Vec::as_slice).unwrap_or_default();
+        let new_line = new.get(row).map(Vec::as_slice).unwrap_or_default();
+        let start = match first_difference(old_line, new_line) {
+            Some(start) => start,
+            None => continue,
+        };
+
+        cursor.move_to_row(&mut out, row);
+        cursor.move_to_column(&mut out, width(&new_line[..start]));
+        let suffix: Vec<Segment> = new_line[start..]
+            .iter()
+            .map(|cell| Segment::new(Some(cell.style), cell.grapheme.as_str()))
+            .collect();
+        out.push_str(&paint_segments(&suffix));
+        if width(old_line) > width(new_line) {
+            out.push_str("\x1b[K");
+        }
+        cursor.column = Some(width(new_line));
+    }
+
+    cursor.move_to_row(&mut out, new.len().saturating_sub(1));
+    cursor.move_to_column(&mut out, new.last().map(|line| width(line)).unwrap_or(0));
+    out
+}
+
+/// Where the cursor is, relative to the first line of the prompt
+struct Cursor {
+    row: usize,
+    /// `None` after moving down a line, which may or may not return to the first column
+    column: Option<usize>,
+}
+
+impl Cursor {
+    fn move_to_row(&mut self, out: &mut String, row: usize) {
+        if row < self.row {
+            write!(out, "\x1b[{}A", self.row - row).unwrap();
+        } else if row > self.row {
+            // Unlike moving the cursor down, newlines scroll at the bottom of the screen
+            out.push_str(&"\n".repeat(row - self.row));
+            self.column = None;
+        }
+        self.row = row;
+    }
+
+    fn move_to_column(&mut self, out: &mut String, column: usize) {
+        if self.column == Some(column) {
+            return;
+        }
+        out.push('\r');
+        if column > 0 {
+            write!(out, "\x1b[{}C", column).unwrap();
+        }
+        self.column = Some(column);
+    }
+}
+
+/// The index of the first cell that differs between the lines, or `None` if they're the same
+fn first_difference(old: &[Cell], new: &[Cell]) -> Option<usize> {
+    if old == new {
+        return None;
+    }
+    let common = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    Some(common)
+}
+
+fn width(cells: &[Cell]) -> usize {
+    cells
+        .iter()
+        .map(|cell| cell.grapheme.width_graphemes())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ansi_term::Color;
+
+    fn line(segments: &[(Option<Style>, &str)]) -> Vec<Line> {
+        let segments: Vec<Segment> = segments
+            .iter()
+            .map(|(style, value)| Segment::new(*style, *value))
+            .collect();
+        lines(&segments)
+    }
+
+    fn plain(value: &str) -> Vec<Line> {
+        line(&[(None, value)])
+    }
+
+    #[test]
+    fn unchanged_prompt() {
+        assert_eq!(repaint(&plain("a\nbc"), &plain("a\nbc")), "");
+    }
+
+    #[test]
+    fn change_at_start_of_line() {
+        assert_eq!(repaint(&plain("abc"), &plain("xbc")), "\rxbc");
+    }
+
+    #[test]
+    fn change_in_middle_of_line() {
+        assert_eq!(repaint(&plain("abcde"), &plain("abXde")), "\r\x1b[2CXde");
+    }
+
+    #[test]
+    fn change_at_end_of_line() {
+        assert_eq!(repaint(&plain("abc"), &plain("abd")), "\r\x1b[2Cd");
+        assert_eq!(repaint(&plain("abc"), &plain("abcd")), "d");
+    }
+
+    #[test]
+    fn shorter_line_cleared() {
+        assert_eq!(repaint(&plain("abcd"), &plain("abX")), "\r\x1b[2CX\x1b[K");
+        assert_eq!(repaint(&plain("abcd"), &plain("ab")), "\r\x1b[2C\x1b[K");
+    }
+
+    #[test]
+    fn style_change() {
+        let old = line(&[(None, "on "), (Some(Color::Red.normal()), "main")]);
+        let new = line(&[(None, "on "), (Some(Color::Green.normal()), "main")]);
+        assert_eq!(
+            repaint(&old, &new),
+            format!("\r\x1b[3C{}", Color::Green.paint("main"))
+        );
+    }
+
+    #[test]
+    fn wide_graphemes() {
+        assert_eq!(repaint(&plain("👩‍👩‍👦‍👦 a"), &plain("👩‍👩‍👦‍👦 b")), "\r\x1b[3Cb");
+    }
+
+    #[test]
+    fn change_on_first_line() {
+        assert_eq!(
+            repaint(&plain("a\nb"), &plain("x\nb")),
+            "\x1b[1A\rx\n\r\x1b[1C"
+        );
+    }
+
+    #[test]
+    fn change_on_every_line() {
+        assert_eq!(
+            repaint(&plain("\nab\ncd"), &plain("\naX\nYd")),
+            "\x1b[1A\r\x1b[1CX\n\rYd"
+        );
+    }
+
+    #[test]
+    fn line_added() {
+        assert_eq!(repaint(&plain("a"), &plain("a\nb")), "\n\rb");
+    }
+
+    #[test]
+    fn line_removed() {
+        assert_eq!(
+            repaint(&plain("a\nb"), &plain("a")),
+            "\r\x1b[K\x1b[1A\r\x1b[1C"
+        );
+    }
+
+    #[test]
+    fn first_paint() {
+        assert_eq!(repaint(&[Line::new()], &plain("a\nb")), "a\n\rb");
+    }
+}
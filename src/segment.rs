@@ -1,5 +1,24 @@
-use ansi_term::{ANSIString, Style};
-use std::fmt;
+use crate::print::UnicodeWidthGraphemes;
+use ansi_term::{ANSIString, Color, Style};
+use std::fmt::{self, Write};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The escape code that resets all text attributes
+const RESET: &str = "\x1b[0m";
+
+/// What a segment stands for once the whole prompt is assembled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentKind {
+    /// Text that is shown as it is.
+    Text,
+    /// Only marks the joint between two modules, where a separator is inserted.
+    Joint,
+    /// Only marks a module shortened to its `max_length`, where the `overflow_symbol` is
+    /// appended to the line.
+    Overflow,
+    /// Text that is repeated to fill the rest of its line.
+    Fill,
+}
 
 /// A segment is a single configurable element in a module. This will usually
 /// contain a data point to provide context for the prompt's user
@@ -11,6 +30,9 @@ pub struct Segment {
 
     /// The string value of the current segment.
     pub value: String,
+
+    /// What the segment stands for once the whole prompt is assembled.
+    pub kind: SegmentKind,
 }
 
 impl Segment {
@@ -22,9 +44,45 @@ impl Segment {
         Self {
             style,
             value: value.into(),
+            kind: SegmentKind::Text,
         }
     }
 
+    /// Creates a joint between two modules.
+    pub fn joint() -> Self {
+        Self {
+            style: None,
+            value: String::new(),
+            kind: SegmentKind::Joint,
+        }
+    }
+
+    /// Marks a module shortened to its `max_length`.
+    pub fn overflow() -> Self {
+        Self {
+            style: None,
+            value: String::new(),
+            kind: SegmentKind::Overflow,
+        }
+    }
+
+    /// Creates a segment that repeats `symbol` to fill the rest of its line.
+    pub fn fill<T>(style: Option<Style>, symbol: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            style,
+            value: symbol.into(),
+            kind: SegmentKind::Fill,
+        }
+    }
+
+    /// The background color of the segment, if it has one
+    pub fn background(&self) -> Option<Color> {
+        self.style.and_then(|style| style.background)
+    }
+
     // Returns the ANSIString of the segment value, not including its prefix and suffix
     pub fn ansi_string(&self) -> ANSIString {
         match self.style {
@@ -39,3 +97,311 @@ impl fmt::Display for Segment {
         write!(f, "{}", self.ansi_string())
     }
 }
+
+/// Paints segments one after the other. Whenever the style changes, the previous one is reset
+/// and the next one is written in full, so that no attribute of a segment carries over to the
+/// next one, even in terminals that handle partial changes differently. Consecutive segments
+/// with the same style share their escape codes, and the end is reset.
+pub fn paint_segments<'a>(segments: impl IntoIterator<Item = &'a Segment>) -> String {
+    let mut out = String::new();
+    let mut current = Style::new();
+    for segment in segments {
+        if segment.value.is_empty() {
+            continue;
+        }
+        let style = segment.style.unwrap_or_default();
+        if style != current {
+            if !current.is_plain() {
+                out.push_str(RESET);
+            }
+            write!(out, "{}", style.prefix()).unwrap();
+            current = style;
+        }
+        out.push_str(&segment.value);
+    }
+    if !current.is_plain() {
+        out.push_str(RESET);
+    }
+    out
+}
+
+/// Where text is cut to fit `max_length`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Truncation {
+    /// Keep the beginning
+    End,
+    /// Keep the beginning and the end
+    Middle,
+    /// Keep the end
+    Start,
+}
+
+impl Truncation {
+    pub fn parse(strategy: &str) -> Option<Self> {
+        match strategy {
+            "end" => Some(Truncation::End),
+            "middle" => Some(Truncation::Middle),
+            "start" => Some(Truncation::Start),
+            _ => None,
+        }
+    }
+}
+
+/// Shortens segments to at most `max_width` columns, replacing what's cut with `symbol`.
+/// Segments are cut between graphemes, so their styles are kept intact, and `symbol` takes
+/// the style of the text it follows, or of the text after it when it's at the start.
+pub fn truncate_segments(
+    segments: Vec<Segment>,
+    max_width: usize,
+    truncation: Truncation,
+    symbol: &str,
+) -> Vec<Segment> {
+    let width: usize = segments.iter().map(|s| s.value.width_graphemes()).sum();
+    if width <= max_width {
+        return segments;
+    }
+    let budget = max_width.saturating_sub(symbol.width_graphemes());
+
+    let (head, tail) = match truncation {
+        Truncation::End => (take_columns(&segments, budget, false), Vec::new()),
+        Truncation::Middle => (
+            take_columns(&segments, budget - budget / 2, false),
+            take_columns(&segments, budget / 2, true),
+        ),
+        Truncation::Start => (Vec::new(), take_columns(&segments, budget, true)),
+    };
+    let style = head
+        .last()
+        .or_else(|| tail.first())
+        .and_then(|segment| segment.style);
+
+    let mut truncated = head;
+    truncated.push(Segment::new(style, symbol));
+    truncated.extend(tail);
+    truncated
+}
+
+/// The segments of the first `columns` columns, or of the last ones if `from_end`
+fn take_columns(segments: &[Segment], columns: usize, from_end: bool) -> Vec<Segment> {
+    let mut taken = Vec::new();
+    let mut remaining = columns;
+    let ordered: Box<dyn Iterator<Item = &Segment>> = if from_end {
+        Box::new(segments.iter().rev())
+    } else {
+        Box::new(segments.iter())
+    };
+
+    for segment in ordered {
+        let graphemes: Vec<&str> = segment.value.graphemes(true).collect();
+        let mut kept = Vec::new();
+        let ordered: Box<dyn Iterator<Item = &&str>> = if from_end {
+            Box::new(graphemes.iter().rev())
+        } else {
+            Box::new(graphemes.iter())
+        };
+        for grapheme in ordered {
+            let width = grapheme.width_graphemes();
+            if width > remaining {
+                remaining = 0;
+                break;
+            }
+            remaining -= width;
+            kept.push(*grapheme);
+        }
+        if from_end {
+            kept.reverse();
+        }
+        if !kept.is_empty() {
+            taken.push(Segment::new(segment.style, kept.concat()));
+        }
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    if from_end {
+        taken.reverse();
+    }
+    taken
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paint(segments: &[(Option<Style>, &str)]) -> String {
+        let segments: Vec<Segment> = segments
+            .iter()
+            .map(|(style, value)| Segment::new(*style, *value))
+            .collect();
+        paint_segments(&segments)
+    }
+
+    #[test]
+    fn plain_segments() {
+        assert_eq!(paint(&[(None, "a"), (Some(Style::new()), "b")]), "ab");
+    }
+
+    #[test]
+    fn bold_to_plain() {
+        assert_eq!(
+            paint(&[(Some(Style::new().bold()), "a"), (None, "b")]),
+            "\x1b[1ma\x1b[0mb"
+        );
+    }
+
+    #[test]
+    fn bold_to_not_bold_with_same_color() {
+        assert_eq!(
+            paint(&[
+                (Some(Color::Red.bold()), "a"),
+                (Some(Color::Red.normal()), "b")
+            ]),
+            "\x1b[1;31ma\x1b[0m\x1b[31mb\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn colored_to_default() {
+        assert_eq!(
+            paint(&[(Some(Color::Green.normal()), "a"), (None, "b"), (None, "c")]),
+            "\x1b[32ma\x1b[0mbc"
+        );
+    }
+
+    #[test]
+    fn background_to_no_background() {
+        assert_eq!(
+            paint(&[
+                (Some(Color::Blue.on(Color::White)), "a"),
+                (Some(Color::Blue.normal()), "b")
+            ]),
+            "\x1b[47;34ma\x1b[0m\x1b[34mb\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn plain_to_styled() {
+        assert_eq!(
+            paint(&[(None, "a"), (Some(Color::Cyan.normal()), "b")]),
+            "a\x1b[36mb\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn empty_segments_keep_the_style() {
+        assert_eq!(
+            paint(&[
+                (Some(Color::Red.normal()), "a"),
+                (None, ""),
+                (Some(Color::Red.normal()), "b")
+            ]),
+            "\x1b[31mab\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn same_styles_coalesced() {
+        let style = Some(Color::Yellow.bold());
+        let segments = vec![(style, "ab"); 1000];
+        let painted = paint(&segments);
+        assert_eq!(painted, format!("\x1b[1;33m{}\x1b[0m", "ab".repeat(1000)));
+
+        // Each change of style costs one reset and the next prefix, no more
+        let alternating: Vec<(Option<Style>, &str)> = (0..1000)
+            .map(|i| {
+                if i % 2 == 0 {
+                    (style, "ab")
+                } else {
+                    (None, "ab")
+                }
+            })
+            .collect();
+        let painted = paint(&alternating);
+        assert_eq!(
+            painted.len(),
+            2000 + 500 * "\x1b[1;33m".len() + 500 * RESET.len()
+        );
+    }
+
+    /// A module output of three differently styled segments, 22 columns wide
+    fn kubernetes_output() -> Vec<Segment> {
+        vec![
+            Segment::new(Some(Color::Cyan.bold()), "☸ "),
+            Segment::new(Some(Color::Blue.normal()), "arn:aws:eks:cluster"),
+            Segment::new(None, "!"),
+        ]
+    }
+
+    fn describe(segments: &[Segment]) -> Vec<(Option<Style>, &str)> {
+        segments
+            .iter()
+            .map(|segment| (segment.style, segment.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn short_output_is_not_truncated() {
+        let truncated = truncate_segments(kubernetes_output(), 22, Truncation::Middle, "…");
+        assert_eq!(describe(&truncated), describe(&kubernetes_output()));
+    }
+
+    #[test]
+    fn truncate_end() {
+        let truncated = truncate_segments(kubernetes_output(), 10, Truncation::End, "…");
+        assert_eq!(
+            describe(&truncated),
+            vec![
+                (Some(Color::Cyan.bold()), "☸ "),
+                (Some(Color::Blue.normal()), "arn:aws"),
+                (Some(Color::Blue.normal()), "…"),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncate_middle() {
+        let truncated = truncate_segments(kubernetes_output(), 9, Truncation::Middle, "…");
+        assert_eq!(
+            describe(&truncated),
+            vec![
+                (Some(Color::Cyan.bold()), "☸ "),
+                (Some(Color::Blue.normal()), "ar"),
+                (Some(Color::Blue.normal()), "…"),
+                (Some(Color::Blue.normal()), "ter"),
+                (None, "!"),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncate_start() {
+        let truncated = truncate_segments(kubernetes_output(), 8, Truncation::Start, "...");
+        assert_eq!(
+            describe(&truncated),
+            vec![
+                (Some(Color::Blue.normal()), "..."),
+                (Some(Color::Blue.normal()), "ster"),
+                (None, "!"),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncate_between_wide_graphemes() {
+        let segments = vec![Segment::new(Some(Color::Red.normal()), "日本語のテキスト")];
+        let truncated = truncate_segments(segments, 6, Truncation::End, "…");
+        // Half of a wide grapheme doesn't fit in the column left
+        assert_eq!(
+            describe(&truncated),
+            vec![
+                (Some(Color::Red.normal()), "日本"),
+                (Some(Color::Red.normal()), "…"),
+            ]
+        );
+        assert_eq!(
+            paint_segments(&truncated),
+            format!("{}", Color::Red.paint("日本…"))
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use crate::context::Shell;
+
+/// Escapes a rendered prompt for the shell that prints it. Everything starship prints as a
+/// prompt goes through here, so that text from modules, such as a directory named `100%` or a
+/// branch named `$(rm -rf)`, is shown as is by every shell.
+pub fn escape_prompt(prompt: String, shell: Shell) -> String {
+    let prompt = wrap_colorseq_for_shell(prompt, shell);
+    match shell {
+        // tcsh expands history with `!` in the prompt, and only reads it from a single line,
+        // where a space is required before the escaped newline
+        Shell::Tcsh => prompt.replace('!', "\\!").replace('\n', " \\n"),
+        _ => prompt,
+    }
+}
+
+/// Escapes the characters that `shell` expands in a prompt
+fn escape_text(text: String, shell: Shell) -> String {
+    match shell {
+        // Bash might interepret baskslashes, backticks and $
+        // see #658 for more details
+        Shell::Bash => text
+            .replace('\\', r"\\")
+            .replace('$', r"\$")
+            .replace('`', r"\`"),
+        // % is an escape in zsh, see PROMPT in `man zshmisc`
+        Shell::Zsh => text.replace('%', "%%"),
+        _ => text,
+    }
+}
+
+/// Escapes the text of the prompt for `shell` and wraps its ANSI color escape sequences in the
+/// shell-appropriate wrappers.
+fn wrap_colorseq_for_shell(ansi: String, shell: Shell) -> String {
+    let ansi = escape_text(ansi, shell);
+
+    const ESCAPE_BEGIN: char = '\u{1b}';
+    const ESCAPE_END: char = 'm';
+    wrap_seq_for_shell(ansi, shell, ESCAPE_BEGIN, ESCAPE_END)
+}
+
+/// Many shells cannot deal with raw unprintable characters and miscompute the cursor position,
+/// leading to strange visual bugs like duplicated/missing chars. This function wraps a specified
+/// sequence in shell-specific escapes to avoid these problems.
+fn wrap_seq_for_shell(ansi: String, shell: Shell, escape_begin: char, escape_end: char) -> String {
+    const BASH_BEG: &str = "\u{5c}\u{5b}"; // \[
+    const BASH_END: &str = "\u{5c}\u{5d}"; // \]
+    const ZSH_BEG: &str = "\u{25}\u{7b}"; // %{
+    const ZSH_END: &str = "\u{25}\u{7d}"; // %}
+    const TCSH_BEG: &str = "\u{25}\u{7b}"; // %{
+    const TCSH_END: &str = "\u{25}\u{7d}"; // %}
+
+    // ANSI escape codes cannot be nested, so we can keep track of whether we're
+    // in an escape or not with a single boolean variable
+    let mut escaped = false;
+    let final_string: String = ansi
+        .chars()
+        .map(|x| {
+            if x == escape_begin && !escaped {
+                escaped = true;
+                match shell {
+                    Shell::Bash => format!("{}{}", BASH_BEG, escape_begin),
+                    Shell::Zsh => format!("{}{}", ZSH_BEG, escape_begin),
+                    Shell::Tcsh => format!("{}{}", TCSH_BEG, escape_begin),
+                    _ => x.to_string(),
+                }
+            } else if x == escape_end && escaped {
+                escaped = false;
+                match shell {
+                    Shell::Bash => format!("{}{}", escape_end, BASH_END),
+                    Shell::Zsh => format!("{}{}", escape_end, ZSH_END),
+                    Shell::Tcsh => format!("{}{}", escape_end, TCSH_END),
+                    _ => x.to_string(),
+                }
+            } else {
+                x.to_string()
+            }
+        })
+        .collect();
+    final_string
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use crate::context::Context;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Set to write the golden files from the current output instead of comparing with them
+    const UPDATE_GOLDEN_ENV: &str = "STARSHIP_UPDATE_GOLDEN";
+
+    /// A prompt with every character that a shell might expand: `%`, `$`, `` ` ``, `\`, `{}`,
+    /// `!`, a newline and a raw escape sequence, in styled text
+    const RISKY_PROMPT: &str = r#"
+        format = """[100% \\$HOME `pwd` C:\\\\ {x}](bold red) [\u001b\\[5mhi!](green)\n[>](blue) """
+    "#;
+
+    fn golden_path(shell: Shell) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/test/fixtures/shell_escape")
+            .join(format!("{}.txt", shell.as_str()))
+    }
+
+    #[test]
+    fn prompt_matches_golden_file_of_each_shell() {
+        for shell in Shell::ALL {
+            let mut context = Context::new_with_shell_and_path(
+                clap::ArgMatches::default(),
+                shell,
+                PathBuf::new(),
+                PathBuf::new(),
+            );
+            context.config = StarshipConfig {
+                config: Some(toml::from_str(RISKY_PROMPT).unwrap()),
+            };
+            let actual = crate::print::get_prompt(context);
+
+            let path = golden_path(shell);
+            if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+                fs::write(&path, &actual).unwrap();
+                continue;
+            }
+            let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+                panic!(
+                    "Missing golden file {:?}, run the tests with {}=1 to create it",
+                    path, UPDATE_GOLDEN_ENV
+                )
+            });
+            assert_eq!(actual, expected, "prompt for {}", shell.as_str());
+        }
+    }
+
+    #[test]
+    fn zsh_percent_is_doubled() {
+        assert_eq!(
+            escape_prompt("100%".to_owned(), Shell::Zsh),
+            "100%%".to_owned()
+        );
+        // The wrappers of the escape sequences are left alone
+        assert_eq!(
+            escape_prompt("\x1b[31m5%\x1b[0m".to_owned(), Shell::Zsh),
+            "%{\x1b[31m%}5%%%{\x1b[0m%}".to_owned()
+        );
+        assert_eq!(escape_prompt("100%".to_owned(), Shell::Fish), "100%");
+    }
+
+    #[test]
+    fn tcsh_history_and_newlines() {
+        assert_eq!(
+            escape_prompt("hi!\n>".to_owned(), Shell::Tcsh),
+            "hi\\! \\n>".to_owned()
+        );
+    }
+
+    #[test]
+    fn test_color_sequence_wrappers() {
+        let test0 = "\x1b2mhellomynamekeyes\x1b2m"; // BEGIN: \x1b     END: m
+        let test1 = "\x1b]330;mlol\x1b]0m"; // BEGIN: \x1b     END: m
+        let test2 = "\u{1b}J"; // BEGIN: \x1b     END: J
+        let test3 = "OH NO"; // BEGIN: O    END: O
+        let test4 = "herpaderp";
+        let test5 = "";
+
+        let zresult0 = wrap_seq_for_shell(test0.to_string(), Shell::Zsh, '\x1b', 'm');
+        let zresult1 = wrap_seq_for_shell(test1.to_string(), Shell::Zsh, '\x1b', 'm');
+        let zresult2 = wrap_seq_for_shell(test2.to_string(), Shell::Zsh, '\x1b', 'J');
+        let zresult3 = wrap_seq_for_shell(test3.to_string(), Shell::Zsh, 'O', 'O');
+        let zresult4 = wrap_seq_for_shell(test4.to_string(), Shell::Zsh, '\x1b', 'm');
+        let zresult5 = wrap_seq_for_shell(test5.to_string(), Shell::Zsh, '\x1b', 'm');
+
+        assert_eq!(&zresult0, "%{\x1b2m%}hellomynamekeyes%{\x1b2m%}");
+        assert_eq!(&zresult1, "%{\x1b]330;m%}lol%{\x1b]0m%}");
+        assert_eq!(&zresult2, "%{\x1bJ%}");
+        assert_eq!(&zresult3, "%{OH NO%}");
+        assert_eq!(&zresult4, "herpaderp");
+        assert_eq!(&zresult5, "");
+
+        let bresult0 = wrap_seq_for_shell(test0.to_string(), Shell::Bash, '\x1b', 'm');
+        let bresult1 = wrap_seq_for_shell(test1.to_string(), Shell::Bash, '\x1b', 'm');
+        let bresult2 = wrap_seq_for_shell(test2.to_string(), Shell::Bash, '\x1b', 'J');
+        let bresult3 = wrap_seq_for_shell(test3.to_string(), Shell::Bash, 'O', 'O');
+        let bresult4 = wrap_seq_for_shell(test4.to_string(), Shell::Bash, '\x1b', 'm');
+        let bresult5 = wrap_seq_for_shell(test5.to_string(), Shell::Bash, '\x1b', 'm');
+
+        assert_eq!(&bresult0, "\\[\x1b2m\\]hellomynamekeyes\\[\x1b2m\\]");
+        assert_eq!(&bresult1, "\\[\x1b]330;m\\]lol\\[\x1b]0m\\]");
+        assert_eq!(&bresult2, "\\[\x1bJ\\]");
+        assert_eq!(&bresult3, "\\[OH NO\\]");
+        assert_eq!(&bresult4, "herpaderp");
+        assert_eq!(&bresult5, "");
+    }
+
+    #[test]
+    fn test_bash_escape() {
+        let test = "$(echo a)";
+        assert_eq!(
+            wrap_colorseq_for_shell(test.to_owned(), Shell::Bash),
+            r"\$(echo a)"
+        );
+        assert_eq!(
+            wrap_colorseq_for_shell(test.to_owned(), Shell::PowerShell),
+            test
+        );
+
+        let test = r"\$(echo a)";
+        assert_eq!(
+            wrap_colorseq_for_shell(test.to_owned(), Shell::Bash),
+            r"\\\$(echo a)"
+        );
+        assert_eq!(
+            wrap_colorseq_for_shell(test.to_owned(), Shell::PowerShell),
+            test
+        );
+
+        let test = r"`echo a`";
+        assert_eq!(
+            wrap_colorseq_for_shell(test.to_owned(), Shell::Bash),
+            r"\`echo a\`"
+        );
+        assert_eq!(
+            wrap_colorseq_for_shell(test.to_owned(), Shell::PowerShell),
+            test
+        );
+    }
+}
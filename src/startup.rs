@@ -0,0 +1,161 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// The phases starship goes through before a prompt is printed, in order
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    /// From the start of the process until the logger is initialized
+    Logger,
+    /// Parsing the command line arguments
+    Arguments,
+    /// Reading and parsing the config file
+    Config,
+    /// Building the rest of the context, e.g. finding the current directory
+    Context,
+    /// Computing the modules and rendering the prompt
+    Render,
+}
+
+impl Phase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phase::Logger => "logger",
+            Phase::Arguments => "arguments",
+            Phase::Config => "config",
+            Phase::Context => "context",
+            Phase::Render => "render",
+        }
+    }
+}
+
+/// The time at which each phase of the startup ended, for `prompt --profile-startup` and
+/// `timings`
+#[derive(Clone, Debug)]
+pub struct StartupTimings {
+    start: Instant,
+    phases: Vec<(Phase, Instant)>,
+}
+
+impl Default for StartupTimings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StartupTimings {
+    /// Starts timing, which should be done as early as possible in `main`
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Records that `phase` has ended now
+    pub fn mark(&mut self, phase: Phase) {
+        self.phases.push((phase, Instant::now()));
+    }
+
+    /// How long each phase took, in the order they ended
+    pub fn phases(&self) -> Vec<(Phase, Duration)> {
+        let mut previous = self.start;
+        self.phases
+            .iter()
+            .map(|&(phase, end)| {
+                let duration = end.saturating_duration_since(previous);
+                previous = end;
+                (phase, duration)
+            })
+            .collect()
+    }
+
+    /// The time from the start until the last phase ended
+    pub fn total(&self) -> Duration {
+        self.phases
+            .last()
+            .map(|(_, end)| end.saturating_duration_since(self.start))
+            .unwrap_or_default()
+    }
+
+    pub fn report(&self) -> StartupReport {
+        StartupReport {
+            total_us: self.total().as_micros() as u64,
+            phases: self
+                .phases()
+                .into_iter()
+                .map(|(phase, duration)| PhaseReport {
+                    phase: phase.as_str(),
+                    duration_us: duration.as_micros() as u64,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The startup phases in the output of `timings` with `--format json`
+#[derive(Serialize)]
+pub struct StartupReport {
+    /// The time from the start of the process until the prompt was rendered, in microseconds
+    pub total_us: u64,
+    pub phases: Vec<PhaseReport>,
+}
+
+/// A phase of the startup in a `StartupReport`
+#[derive(Serialize)]
+pub struct PhaseReport {
+    pub phase: &'static str,
+    /// The time the phase took, in microseconds
+    pub duration_us: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
+    use clap::ArgMatches;
+
+    #[test]
+    fn phases_add_up_to_total() {
+        let mut timings = StartupTimings::new();
+        timings.mark(Phase::Logger);
+        timings.mark(Phase::Arguments);
+        let context = Context::new_with_timings(ArgMatches::default(), timings);
+        let mut timings = context.startup_timings.clone();
+        std::thread::sleep(Duration::from_millis(5));
+        timings.mark(Phase::Render);
+
+        let ends: Vec<Instant> = timings.phases.iter().map(|(_, end)| *end).collect();
+        assert!(ends.windows(2).all(|ends| ends[0] <= ends[1]));
+        assert!(timings.start <= ends[0]);
+
+        let phases = timings.phases();
+        let names: Vec<Phase> = phases.iter().map(|(phase, _)| *phase).collect();
+        assert_eq!(
+            names,
+            vec![
+                Phase::Logger,
+                Phase::Arguments,
+                Phase::Config,
+                Phase::Context,
+                Phase::Render
+            ]
+        );
+        assert!(phases[4].1 >= Duration::from_millis(5));
+
+        let sum: Duration = phases.iter().map(|(_, duration)| *duration).sum();
+        let total = timings.total();
+        assert!(total >= Duration::from_millis(5));
+        assert!(total.max(sum) - total.min(sum) < Duration::from_millis(1));
+
+        let report = timings.report();
+        let sum_us: u64 = report.phases.iter().map(|phase| phase.duration_us).sum();
+        assert!(report.total_us.max(sum_us) - report.total_us.min(sum_us) < 1000);
+    }
+
+    #[test]
+    fn no_phases() {
+        let timings = StartupTimings::new();
+        assert!(timings.phases().is_empty());
+        assert_eq!(timings.total(), Duration::default());
+    }
+}
@@ -1,11 +1,14 @@
 use crate::context::{Context, Shell};
+use crate::fs_budget::Clock;
 use crate::logger::StarshipLogger;
 use crate::{config::StarshipConfig, utils::CommandOutput};
-use log::{Level, LevelFilter};
+use log::{Level, LevelFilter, Log, Metadata, Record};
 use once_cell::sync::Lazy;
+use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 
 static FIXTURE_DIR: Lazy<PathBuf> =
@@ -14,6 +17,29 @@ static FIXTURE_DIR: Lazy<PathBuf> =
 static GIT_FIXTURE: Lazy<PathBuf> = Lazy::new(|| FIXTURE_DIR.join("git-repo.bundle"));
 static HG_FIXTURE: Lazy<PathBuf> = Lazy::new(|| FIXTURE_DIR.join("hg-repo.bundle"));
 
+/// Loggers that receive the records of every test as well, see `capture_logs`
+static CAPTURES: Lazy<Mutex<Vec<Arc<StarshipLogger>>>> = Lazy::new(Default::default);
+
+/// The logger of the tests, passing records on to the loggers of `capture_logs`
+struct TestLogger(StarshipLogger);
+
+impl Log for TestLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.0.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.log(record);
+        for logger in CAPTURES.lock().unwrap().iter() {
+            logger.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.0.flush();
+    }
+}
+
 static LOGGER: Lazy<()> = Lazy::new(|| {
     let mut logger = StarshipLogger::default();
 
@@ -26,9 +52,29 @@ static LOGGER: Lazy<()> = Lazy::new(|| {
     logger.set_log_level(Level::Trace);
     logger.set_log_file_path(nul);
 
-    log::set_boxed_logger(Box::new(logger)).unwrap();
+    log::set_boxed_logger(Box::new(TestLogger(logger))).unwrap();
 });
 
+/// Stops passing records to a logger of `capture_logs` when dropped
+pub struct LogCapture(Arc<StarshipLogger>);
+
+impl Drop for LogCapture {
+    fn drop(&mut self) {
+        CAPTURES
+            .lock()
+            .unwrap()
+            .retain(|logger| !Arc::ptr_eq(logger, &self.0));
+    }
+}
+
+/// Passes what is logged to `logger` as well, to check how it handles the records of a real
+/// render. Tests run in parallel, so it also receives the records of other tests.
+pub fn capture_logs(logger: Arc<StarshipLogger>) -> LogCapture {
+    Lazy::force(&LOGGER);
+    CAPTURES.lock().unwrap().push(logger.clone());
+    LogCapture(logger)
+}
+
 /// Render a specific starship module by name
 pub struct ModuleRenderer<'a> {
     name: &'a str,
@@ -77,6 +123,15 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    /// Loads the config of the underlying context from the file at `path`
+    pub fn config_file<T: AsRef<Path>>(mut self, path: T) -> Self {
+        let (config, degraded) =
+            StarshipConfig::initialize_from_path(&path.as_ref().to_string_lossy());
+        self.context.config = config;
+        self.context.config_degraded = degraded;
+        self
+    }
+
     /// Adds the variable to the env_mocks of the underlying context
     pub fn env<V: Into<String>>(mut self, key: &'a str, val: V) -> Self {
         self.context.env.insert(key, val.into());
@@ -121,6 +176,38 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    /// Sets the clock the filesystem checks of the context are timed with
+    pub fn fs_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.context.mock_fs_clock = Some(clock);
+        self
+    }
+
+    /// Sets the root directory the system files of `Context::system_path` are in
+    pub fn root_dir<T>(mut self, root_dir: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        self.context.mock_root_dir = Some(root_dir.into());
+        self
+    }
+
+    pub fn root_user(mut self, root_user: bool) -> Self {
+        self.context.mock_root_user = Some(root_user);
+        self
+    }
+
+    pub fn readonly_dir(mut self, readonly_dir: bool) -> Self {
+        self.context.mock_readonly_dir = Some(readonly_dir);
+        self
+    }
+
+    /// Sets whether the current directory is owned by the current user, see
+    /// `Context::is_trusted_dir`
+    pub fn owned_dir(mut self, owned_dir: bool) -> Self {
+        self.context.mock_owned_dir = Some(owned_dir);
+        self
+    }
+
     /// Renders the module returning its output
     pub fn collect(self) -> Option<String> {
         let ret = crate::print::get_module(self.name, self.context);
@@ -130,13 +217,90 @@ impl<'a> ModuleRenderer<'a> {
         // module returns None in the tests...
         ret.filter(|s| !s.is_empty())
     }
+
+    /// Renders the module returning its output and the commands it executed
+    pub fn collect_with_commands(self) -> (Option<String>, Vec<String>) {
+        let ret = crate::modules::handle(self.name, &self.context).map(|m| m.to_string());
+        let commands = self.context.cmd_log.into_inner().unwrap();
+        (ret.filter(|s| !s.is_empty()), commands)
+    }
+
+    /// Renders the module returning its output and what it accessed the network for, see
+    /// `Context::allow_network`
+    pub fn collect_with_network_access(self) -> (Option<String>, Vec<String>) {
+        let ret = crate::modules::handle(self.name, &self.context).map(|m| m.to_string());
+        let network_access = self.context.network_log.into_inner().unwrap();
+        (ret.filter(|s| !s.is_empty()), network_access)
+    }
 }
 
 pub enum FixtureProvider {
     Git,
     Hg,
+    /// A directory with hand-written `.hg` metadata, not requiring `hg` to be installed
+    HgMetadata,
+    /// A Cargo workspace with a single member crate in `crates/app`
+    CargoWorkspace,
+    /// An npm/pnpm workspace with a single package in `packages/web`
+    NpmWorkspace,
+    /// A home directory containing a kubeconfig in `.kube/config`
+    Kubeconfig,
+    /// The git fixture with the given number of committed files modified in the working tree
+    DirtyGit(usize),
+    /// A home directory whose files are checked out from the git fixture cloned bare into
+    /// `.dotfiles`, as in the dotfiles setups that run `git --git-dir=~/.dotfiles --work-tree=~`
+    Dotfiles,
+    /// A bare repository created with `git init --bare`, holding the git fixture's `master`
+    BareGit,
 }
 
+const HG_METADATA_FIXTURE: &[(&str, &str)] = &[
+    (
+        ".hg/requires",
+        "dotencode\nfncache\ngeneraldelta\nrevlogv1\nstore\n",
+    ),
+    (".hg/branch", "fixture-branch\n"),
+];
+
+const CARGO_WORKSPACE_FIXTURE: &[(&str, &str)] = &[
+    ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n"),
+    (
+        "crates/app/Cargo.toml",
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2018\"\n",
+    ),
+    ("crates/app/src/main.rs", "fn main() {}\n"),
+];
+
+const NPM_WORKSPACE_FIXTURE: &[(&str, &str)] = &[
+    (
+        "package.json",
+        r#"{ "name": "monorepo", "version": "1.0.0", "private": true, "workspaces": ["packages/*"] }"#,
+    ),
+    ("pnpm-workspace.yaml", "packages:\n  - 'packages/*'\n"),
+    (
+        "packages/web/package.json",
+        r#"{ "name": "web", "version": "0.2.0" }"#,
+    ),
+    ("packages/web/index.js", "module.exports = {};\n"),
+];
+
+const KUBECONFIG_FIXTURE: &[(&str, &str)] = &[(
+    ".kube/config",
+    "apiVersion: v1
+clusters: []
+contexts:
+  - context:
+      cluster: test_cluster
+      user: test_user
+      namespace: test_namespace
+    name: test_context
+current-context: test_context
+kind: Config
+preferences: {}
+users: []
+",
+)];
+
 pub fn fixture_repo(provider: FixtureProvider) -> io::Result<TempDir> {
     match provider {
         FixtureProvider::Git => {
@@ -178,5 +342,203 @@ pub fn fixture_repo(provider: FixtureProvider) -> io::Result<TempDir> {
 
             Ok(path)
         }
+        FixtureProvider::HgMetadata => write_fixture(HG_METADATA_FIXTURE),
+        FixtureProvider::CargoWorkspace => write_fixture(CARGO_WORKSPACE_FIXTURE),
+        FixtureProvider::NpmWorkspace => write_fixture(NPM_WORKSPACE_FIXTURE),
+        FixtureProvider::Kubeconfig => write_fixture(KUBECONFIG_FIXTURE),
+        FixtureProvider::DirtyGit(modified) => {
+            let path = fixture_repo(FixtureProvider::Git)?;
+
+            let names: Vec<String> = (0..modified).map(|i| format!("file_{}", i)).collect();
+            for name in &names {
+                fs::write(path.path().join(name), "")?;
+            }
+
+            Command::new("git")
+                .args(["add", "--all"])
+                .current_dir(path.path())
+                .output()?;
+
+            Command::new("git")
+                .args(["commit", "-m", "Add files", "--no-gpg-sign"])
+                .current_dir(path.path())
+                .output()?;
+
+            for name in &names {
+                fs::write(path.path().join(name), "modified")?;
+            }
+
+            Ok(path)
+        }
+        FixtureProvider::Dotfiles => {
+            let path = tempfile::tempdir()?;
+
+            Command::new("git")
+                .current_dir(path.path())
+                .args(["clone", "--bare", "-b", "master"])
+                .arg(GIT_FIXTURE.as_os_str())
+                .arg(".dotfiles")
+                .output()?;
+
+            // The home directory has much more than dotfiles in it
+            Command::new("git")
+                .args(["config", "status.showUntrackedFiles", "no"])
+                .current_dir(path.path().join(".dotfiles"))
+                .output()?;
+
+            Command::new("git")
+                .args([
+                    "--git-dir=.dotfiles",
+                    "--work-tree=.",
+                    "checkout",
+                    "-f",
+                    "master",
+                ])
+                .current_dir(path.path())
+                .output()?;
+
+            Ok(path)
+        }
+        FixtureProvider::BareGit => {
+            let path = tempfile::tempdir()?;
+
+            Command::new("git")
+                .args(["init", "--bare"])
+                .current_dir(path.path())
+                .output()?;
+
+            Command::new("git")
+                .args(["fetch", "--quiet"])
+                .arg(GIT_FIXTURE.as_os_str())
+                .arg("master:master")
+                .current_dir(path.path())
+                .output()?;
+
+            Command::new("git")
+                .args(["symbolic-ref", "HEAD", "refs/heads/master"])
+                .current_dir(path.path())
+                .output()?;
+
+            Ok(path)
+        }
+    }
+}
+
+/// Writes the given `(relative path, contents)` pairs into a new tempdir
+fn write_fixture(files: &[(&str, &str)]) -> io::Result<TempDir> {
+    let path = tempfile::tempdir()?;
+
+    for (file, contents) in files {
+        let file = path.path().join(file);
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file, contents)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ansi_term::Color;
+
+    #[test]
+    fn hg_metadata_fixture() -> io::Result<()> {
+        let dir = fixture_repo(FixtureProvider::HgMetadata)?;
+        assert!(dir.path().join(".hg/requires").is_file());
+
+        let actual = ModuleRenderer::new("hg_branch")
+            .path(dir.path())
+            .config(toml::toml! {
+                [hg_branch]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "on {} ",
+            Color::Purple.bold().paint("\u{e0a0} fixture-branch")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn cargo_workspace_fixture() -> io::Result<()> {
+        let dir = fixture_repo(FixtureProvider::CargoWorkspace)?;
+        assert!(dir.path().join("Cargo.toml").is_file());
+        assert!(dir.path().join("crates/app/src/main.rs").is_file());
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path().join("crates/app"))
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.1.0")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn npm_workspace_fixture() -> io::Result<()> {
+        let dir = fixture_repo(FixtureProvider::NpmWorkspace)?;
+        assert!(dir.path().join("pnpm-workspace.yaml").is_file());
+        assert!(dir.path().join("packages/web/package.json").is_file());
+
+        let actual = ModuleRenderer::new("package")
+            .path(dir.path().join("packages/web"))
+            .collect();
+        let expected = Some(format!(
+            "is {} ",
+            Color::Fixed(208).bold().paint("📦 v0.2.0")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn kubeconfig_fixture() -> io::Result<()> {
+        let dir = fixture_repo(FixtureProvider::Kubeconfig)?;
+        let kubeconfig = dir.path().join(".kube/config");
+        assert!(kubeconfig.is_file());
+
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", kubeconfig.to_string_lossy())
+            .config(toml::toml! {
+                [kubernetes]
+                disabled = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} in ",
+            Color::Cyan.bold().paint("☸ test_context (test_namespace)")
+        ));
+
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn dirty_git_fixture() -> io::Result<()> {
+        let dir = fixture_repo(FixtureProvider::DirtyGit(3))?;
+        assert!(dir.path().join("file_2").is_file());
+
+        let actual = ModuleRenderer::new("git_status")
+            .path(dir.path())
+            .config(toml::toml! {
+                [git_status]
+                modified = "!$count"
+            })
+            .collect();
+        let expected = Some(format!("{} ", Color::Red.bold().paint("[!3⇡]")));
+
+        assert_eq!(expected, actual);
+        dir.close()
     }
 }
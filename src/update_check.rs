@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::cache::cache_dir;
+use crate::configs::StarshipRootConfig;
+use crate::context::Context;
+use crate::network;
+use crate::shadow;
+use crate::utils;
+
+/// The file of the cache directory with the result of the last update check. It is not a
+/// session file, so `starship cache gc` leaves it alone.
+const STATE_FILE: &str = "latest-release.json";
+
+/// The result of the last update check
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct UpdateCheckState {
+    /// When the last check started, in seconds since the Unix epoch
+    checked_at: u64,
+    /// The latest release found by the last successful check
+    latest_version: Option<String>,
+}
+
+/// An update check that is due, to be started once the prompt has been printed
+pub struct PendingUpdateCheck {
+    url: String,
+    state_path: PathBuf,
+}
+
+impl PendingUpdateCheck {
+    /// Starts `starship update-check` in the background, without waiting for it
+    pub fn spawn(self) {
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe,
+            Err(error) => {
+                log::debug!("Unable to find the starship executable: {}", error);
+                return;
+            }
+        };
+
+        let spawned = Command::new(exe)
+            .arg("update-check")
+            .arg("--url")
+            .arg(&self.url)
+            .arg("--state-file")
+            .arg(&self.state_path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Err(error) = spawned {
+            log::debug!("Unable to start the update check: {}", error);
+        }
+    }
+}
+
+/// Returns the update check to start after this prompt, if `update_check` is enabled, the
+/// network may be accessed and the last check is older than `update_check_interval`.
+///
+/// The check is recorded as started right away, so that prompts printed meanwhile don't start
+/// another one.
+pub fn pending(context: &Context) -> Option<PendingUpdateCheck> {
+    pending_at(context, SystemTime::now())
+}
+
+fn pending_at(context: &Context, now: SystemTime) -> Option<PendingUpdateCheck> {
+    let config = context.config.get_root_config();
+    if !config.update_check {
+        return None;
+    }
+
+    let state_path = cache_dir(context)?.join(STATE_FILE);
+    let state = load_state(&state_path);
+    let interval = Duration::from_secs(config.update_check_interval.saturating_mul(24 * 60 * 60));
+    if !is_due(state.as_ref(), now, interval) || !context.allow_network("update check") {
+        return None;
+    }
+
+    let started = UpdateCheckState {
+        checked_at: seconds_since_epoch(now),
+        latest_version: state.and_then(|state| state.latest_version),
+    };
+    store_state(&state_path, &started)?;
+
+    Some(PendingUpdateCheck {
+        url: config.update_check_url.to_owned(),
+        state_path,
+    })
+}
+
+/// Looks up the latest release at `url` and stores it in `state_path`, for
+/// `starship update-check`. Failures leave the previous result in place.
+pub fn run(config: &StarshipRootConfig, url: &str, state_path: &Path) {
+    let latest_version =
+        match network::get(config, "update check", url).and_then(|body| parse_release(&body)) {
+            Some(version) => version,
+            None => {
+                log::debug!("Unable to look up the latest release at {}", url);
+                return;
+            }
+        };
+
+    store_state(
+        state_path,
+        &UpdateCheckState {
+            checked_at: seconds_since_epoch(SystemTime::now()),
+            latest_version: Some(latest_version),
+        },
+    );
+}
+
+/// Returns the latest release from the last update check if it is newer than this starship.
+/// Never accesses the network.
+pub fn newer_version(context: &Context) -> Option<String> {
+    if !context.config.get_root_config().update_check {
+        return None;
+    }
+
+    let state = load_state(&cache_dir(context)?.join(STATE_FILE))?;
+    state
+        .latest_version
+        .filter(|latest| is_newer(latest, shadow::PKG_VERSION))
+}
+
+/// Returns whether a check is due: when there was none, the last one is at least `interval`
+/// old, or it is in the future, which means the clock changed.
+fn is_due(state: Option<&UpdateCheckState>, now: SystemTime, interval: Duration) -> bool {
+    let state = match state {
+        Some(state) => state,
+        None => return true,
+    };
+    let checked_at = UNIX_EPOCH + Duration::from_secs(state.checked_at);
+    match now.duration_since(checked_at) {
+        Ok(age) => age >= interval,
+        Err(_) => true,
+    }
+}
+
+/// Compares two versions as semver, ignoring a leading `v`. Versions that can't be parsed are
+/// never newer.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parse = |version: &str| semver::Version::parse(version.trim().trim_start_matches('v'));
+    match (parse(latest), parse(current)) {
+        (Ok(latest), Ok(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Reads the version of a release from the `tag_name` of a GitHub release or from a plain tag
+fn parse_release(body: &str) -> Option<String> {
+    let tag = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(release) => release.get("tag_name")?.as_str()?.to_owned(),
+        Err(_) => body.trim().to_owned(),
+    };
+    let version = tag.trim_start_matches('v');
+    semver::Version::parse(version).ok()?;
+    Some(version.to_owned())
+}
+
+fn load_state(path: &Path) -> Option<UpdateCheckState> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn store_state(path: &Path, state: &UpdateCheckState) -> Option<()> {
+    let contents = serde_json::to_string(state).ok()?;
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| utils::write_file_atomic(path, contents));
+    match written {
+        Ok(()) => Some(()),
+        Err(error) => {
+            log::debug!("Unable to write {:?}: {}", path, error);
+            None
+        }
+    }
+}
+
+fn seconds_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use crate::configs::UPDATE_CHECK_URL;
+    use crate::context::Shell;
+    use std::io;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn context_for<'a>(cache: &Path, config: toml::Value) -> Context<'a> {
+        let mut context = Context::new_with_shell_and_path(
+            clap::ArgMatches::default(),
+            Shell::Unknown,
+            cache.to_path_buf(),
+            cache.to_path_buf(),
+        );
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+        context
+            .env
+            .insert("STARSHIP_CACHE", cache.to_string_lossy().to_string());
+        context
+    }
+
+    fn state_at(checked_at: SystemTime, latest_version: &str) -> UpdateCheckState {
+        UpdateCheckState {
+            checked_at: seconds_since_epoch(checked_at),
+            latest_version: Some(latest_version.to_owned()),
+        }
+    }
+
+    #[test]
+    fn compares_versions_as_semver() {
+        assert!(is_newer("1.0.0", "0.54.0"));
+        assert!(is_newer("v0.54.1", "0.54.0"));
+        assert!(is_newer("0.100.0", "0.54.0"));
+        assert!(is_newer("0.55.0-beta.1", "0.54.0"));
+        assert!(!is_newer("0.55.0-beta.1", "0.55.0"));
+        assert!(!is_newer("0.54.0", "0.54.0"));
+        assert!(!is_newer("0.9.0", "0.54.0"));
+        assert!(!is_newer("latest", "0.54.0"));
+    }
+
+    #[test]
+    fn parses_github_releases_and_plain_tags() {
+        assert_eq!(
+            parse_release(r#"{"tag_name": "v1.2.3", "name": "v1.2.3"}"#),
+            Some("1.2.3".to_owned())
+        );
+        assert_eq!(parse_release("v1.2.3\n"), Some("1.2.3".to_owned()));
+        assert_eq!(parse_release(r#"{"message": "Not Found"}"#), None);
+        assert_eq!(parse_release("<html>rate limited</html>"), None);
+    }
+
+    #[test]
+    fn due_once_the_interval_passed() {
+        let now = UNIX_EPOCH + 1000 * DAY;
+        let interval = 7 * DAY;
+
+        assert!(is_due(None, now, interval));
+        assert!(!is_due(Some(&state_at(now, "1.0.0")), now, interval));
+        assert!(!is_due(
+            Some(&state_at(now - 6 * DAY, "1.0.0")),
+            now,
+            interval
+        ));
+        assert!(is_due(
+            Some(&state_at(now - 7 * DAY, "1.0.0")),
+            now,
+            interval
+        ));
+        assert!(is_due(Some(&state_at(now + DAY, "1.0.0")), now, interval));
+    }
+
+    #[test]
+    fn pending_records_the_start_of_the_check() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_for(dir.path(), toml::toml! { update_check = true });
+        let path = dir.path().join(STATE_FILE);
+        let last_week = UNIX_EPOCH + 1000 * DAY;
+        store_state(&path, &state_at(last_week, "1.0.0"));
+        let now = last_week + 8 * DAY;
+
+        let pending = pending_at(&context, now).expect("check should be due");
+        assert_eq!(pending.url, UPDATE_CHECK_URL);
+        assert_eq!(pending.state_path, path);
+        // The previous result is kept until the new check finishes
+        assert_eq!(load_state(&path), Some(state_at(now, "1.0.0")));
+        assert_eq!(*context.network_log.lock().unwrap(), ["update check"]);
+
+        // Prompts printed meanwhile don't start another check
+        assert!(pending_at(&context, now + Duration::from_secs(1)).is_none());
+        dir.close()
+    }
+
+    #[test]
+    fn pending_respects_the_interval() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let context = context_for(
+            dir.path(),
+            toml::toml! {
+                update_check = true
+                update_check_interval = 30
+            },
+        );
+        let last_check = UNIX_EPOCH + 1000 * DAY;
+        store_state(&dir.path().join(STATE_FILE), &state_at(last_check, "1.0.0"));
+
+        assert!(pending_at(&context, last_check + 29 * DAY).is_none());
+        assert!(pending_at(&context, last_check + 30 * DAY).is_some());
+        dir.close()
+    }
+
+    #[test]
+    fn huge_interval_never_checks_again() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let interval = i64::MAX;
+        let context = context_for(
+            dir.path(),
+            toml::toml! {
+                update_check = true
+                update_check_interval = interval
+            },
+        );
+        let last_check = UNIX_EPOCH + 1000 * DAY;
+        store_state(&dir.path().join(STATE_FILE), &state_at(last_check, "1.0.0"));
+
+        assert!(pending_at(&context, last_check + 100_000 * DAY).is_none());
+        dir.close()
+    }
+
+    #[test]
+    fn no_check_unless_enabled_or_when_offline() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let now = SystemTime::now();
+
+        let context = context_for(dir.path(), toml::Value::Table(Default::default()));
+        assert!(pending_at(&context, now).is_none());
+
+        let context = context_for(
+            dir.path(),
+            toml::toml! {
+                update_check = true
+                offline = true
+            },
+        );
+        assert!(pending_at(&context, now).is_none());
+        assert!(context.network_log.lock().unwrap().is_empty());
+        assert!(!dir.path().join(STATE_FILE).exists());
+        dir.close()
+    }
+
+    #[test]
+    fn newer_version_reads_the_last_result() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(STATE_FILE);
+        let context = context_for(dir.path(), toml::toml! { update_check = true });
+
+        assert_eq!(newer_version(&context), None);
+
+        store_state(&path, &state_at(UNIX_EPOCH, "999.0.0"));
+        assert_eq!(newer_version(&context), Some("999.0.0".to_owned()));
+
+        store_state(&path, &state_at(UNIX_EPOCH, shadow::PKG_VERSION));
+        assert_eq!(newer_version(&context), None);
+
+        let context = context_for(dir.path(), toml::Value::Table(Default::default()));
+        store_state(&path, &state_at(UNIX_EPOCH, "999.0.0"));
+        assert_eq!(newer_version(&context), None);
+        dir.close()
+    }
+}
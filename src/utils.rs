@@ -1,18 +1,114 @@
+use once_cell::sync::Lazy;
 use process_control::{ChildExt, Timeout};
+use regex::{Captures, Regex};
 use std::fmt::Debug;
-use std::fs::read_to_string;
-use std::io::Result;
-use std::path::Path;
+use std::fs::{self, read_to_string, File};
+use std::io::{Error, ErrorKind, Result, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::time::{Duration, Instant};
 
-use crate::context::Shell;
+use crate::context::Context;
+use crate::error::StarshipError;
+
+/// Expands a path of the config, with the home directory and environment variables of the
+/// context. See `expand_path_with`.
+pub fn expand_path(path: &str, context: &Context) -> PathBuf {
+    expand_path_with(path, context.get_home(), |name| context.get_env(name))
+}
+
+/// Expands a path of the config:
+///
+/// - a leading `~` is the home directory, and on unix `~user` is the home directory of `user`
+/// - `${VAR}` and `%VAR%` are the value of the environment variable `VAR`. An unset variable
+///   expands to an empty string, with a warning.
+///
+/// `expand_path` should be used instead where there's a context, this is for the paths used
+/// while the config is loaded.
+pub fn expand_path_with(
+    path: &str,
+    home: Option<PathBuf>,
+    get_env: impl Fn(&str) -> Option<String>,
+) -> PathBuf {
+    let (prefix, rest) = split_tilde_prefix(path, home);
+    let rest = expand_env_vars(rest, get_env);
+    match prefix {
+        Some(prefix) => PathBuf::from(format!("{}{}", prefix.to_string_lossy(), rest)),
+        None => PathBuf::from(rest),
+    }
+}
+
+/// Splits `path` into the home directory its leading `~` or `~user` stands for, if any, and
+/// the rest of it
+fn split_tilde_prefix(path: &str, home: Option<PathBuf>) -> (Option<PathBuf>, &str) {
+    let after_tilde = match path.strip_prefix('~') {
+        Some(after_tilde) => after_tilde,
+        None => return (None, path),
+    };
+    let end = after_tilde
+        .find(std::path::is_separator)
+        .unwrap_or(after_tilde.len());
+    let (user, rest) = after_tilde.split_at(end);
+
+    let prefix = if user.is_empty() {
+        home
+    } else {
+        user_home(user)
+    };
+    match prefix {
+        Some(prefix) => (Some(prefix), rest),
+        None => {
+            log::warn!(
+                "Unable to find the home directory of `~{}` in {}",
+                user,
+                path
+            );
+            (None, path)
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn user_home(user: &str) -> Option<PathBuf> {
+    nix::unistd::User::from_name(user)
+        .ok()?
+        .map(|user| user.dir)
+}
+
+#[cfg(windows)]
+fn user_home(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+/// Replaces `${VAR}` and `%VAR%` with the value of the environment variable `VAR`
+fn expand_env_vars(text: &str, get_env: impl Fn(&str) -> Option<String>) -> String {
+    static ENV_VAR: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}|%([A-Za-z_][A-Za-z0-9_]*)%").unwrap()
+    });
+    ENV_VAR
+        .replace_all(text, |captures: &Captures| {
+            let name = captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .unwrap()
+                .as_str();
+            get_env(name).unwrap_or_else(|| {
+                log::warn!(
+                    "The environment variable `{}` in {} is not set, expanding it to nothing",
+                    name,
+                    text
+                );
+                String::new()
+            })
+        })
+        .into_owned()
+}
 
 /// Return the string contents of a file
 pub fn read_file<P: AsRef<Path> + Debug>(file_name: P) -> Result<String> {
     log::trace!("Trying to read from {:?}", file_name);
 
-    let result = read_to_string(file_name);
+    let result = read_to_string(file_name).map(strip_bom);
 
     if result.is_err() {
         log::debug!("Error reading file: {:?}", result);
@@ -23,31 +119,183 @@ pub fn read_file<P: AsRef<Path> + Debug>(file_name: P) -> Result<String> {
     result
 }
 
-#[derive(Debug, Clone)]
+/// Removes the byte order mark some editors on Windows write at the start of UTF-8 files
+fn strip_bom(contents: String) -> String {
+    match contents.strip_prefix('\u{feff}') {
+        Some(contents) => contents.to_owned(),
+        None => contents,
+    }
+}
+
+/// Return the string contents of a file that another process (like git) may be rewriting
+/// concurrently. Empty and missing files, which git leaves for a moment when it renames a new
+/// version into place, and transient errors are read again a few times before giving up.
+/// Failures are only logged at trace level since they are expected while git is working, so
+/// files that may not exist at all should be checked for first.
+pub fn read_file_with_retry<P: AsRef<Path> + Debug>(file_name: P) -> Result<String> {
+    retry_read(|| read_to_string(&file_name), &file_name)
+}
+
+fn retry_read<F, P>(mut read: F, file_name: P) -> Result<String>
+where
+    F: FnMut() -> Result<String>,
+    P: Debug,
+{
+    const BACKOFF_MS: &[u64] = &[1, 2];
+
+    let mut result = read();
+    for delay in BACKOFF_MS {
+        match &result {
+            Ok(contents) if !contents.trim().is_empty() => break,
+            Err(error) if !is_transient(error) => break,
+            _ => {}
+        }
+
+        log::trace!("Retrying read of {:?}: {:?}", file_name, result);
+        std::thread::sleep(Duration::from_millis(*delay));
+        result = read();
+    }
+
+    match result {
+        Ok(contents) if contents.trim().is_empty() => {
+            log::trace!("File {:?} is still empty, giving up", file_name);
+            Err(Error::new(ErrorKind::UnexpectedEof, "file is empty"))
+        }
+        Err(error) => {
+            log::trace!("Error reading file {:?}: {:?}", file_name, error);
+            Err(error)
+        }
+        ok => ok,
+    }
+}
+
+/// Whether reading a file failed in a way that may not happen again right away
+fn is_transient(error: &Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::NotFound | ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut
+    )
+}
+
+/// Replaces the contents of a file without ever leaving it partially written, even if
+/// starship crashes or another prompt writes the same file concurrently.
+///
+/// The contents are written and synced to a temporary file next to `path`, which is then
+/// renamed over it. Symlinks are followed, so that the file they point to is replaced.
+pub fn write_file_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
+    let path = fs::canonicalize(&path).unwrap_or_else(|_| path.as_ref().to_path_buf());
+    let tmp_path = atomic_tmp_path(&path);
+
+    let result = write_and_sync(&path, &tmp_path, contents.as_ref())
+        // On Windows `rename` replaces an existing file as well (`MOVEFILE_REPLACE_EXISTING`)
+        .and_then(|_| fs::rename(&tmp_path, &path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+
+    #[cfg(unix)]
+    if result.is_ok() {
+        // Make the rename itself durable, which is best effort
+        if let Some(dir) = path.parent().and_then(|dir| File::open(dir).ok()) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    result
+}
+
+/// The temporary file `write_file_atomic` writes to before renaming it to `path`
+pub fn atomic_tmp_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()))
+}
+
+fn write_and_sync(path: &Path, tmp_path: &Path, contents: &[u8]) -> Result<()> {
+    // A temporary file left behind by a crash is simply overwritten
+    let mut file = File::create(tmp_path)?;
+    if let Ok(metadata) = fs::metadata(path) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct CommandOutput {
     pub stdout: String,
     pub stderr: String,
+    /// The exit code of the command, `None` if it was terminated by a signal
+    pub status: Option<i32>,
 }
 
-impl PartialEq for CommandOutput {
-    fn eq(&self, other: &Self) -> bool {
-        self.stdout == other.stdout && self.stderr == other.stderr
+impl CommandOutput {
+    /// Whether the command exited with a zero exit code
+    pub fn success(&self) -> bool {
+        self.status == Some(0)
     }
 }
 
-/// Execute a command and return the output on stdout and stderr if successful
+/// Translates a path on a Windows drive mounted in WSL, like `/mnt/c/Users`, to its Windows
+/// form with forward slashes, like `C:/Users`. Returns `None` for other paths.
+pub fn wslpath(path: &Path) -> Option<String> {
+    let mut components = path.strip_prefix("/mnt").ok()?.components();
+    let drive = components.next()?.as_os_str().to_str()?;
+    if drive.len() != 1 || !drive.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let rest: Vec<&str> = components
+        .map(|component| component.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    Some(format!(
+        "{}:/{}",
+        drive.to_ascii_uppercase(),
+        rest.join("/")
+    ))
+}
+
+/// Environment variables set for a command, where an empty value unsets the variable
+pub type EnvOverlay<'a> = [(&'a str, &'a str)];
+
+/// Sets the variables of `env` on `command`, unsetting those with an empty value
+pub fn apply_env(command: &mut Command, env: &EnvOverlay) {
+    for (key, value) in env {
+        if value.is_empty() {
+            command.env_remove(key);
+        } else {
+            command.env(key, value);
+        }
+    }
+}
+
+/// Execute a command and return its output and exit code. Commands exiting with a non-zero
+/// exit code return `None`, unless `allow_nonzero_exit` is set.
 #[cfg(not(test))]
-pub fn exec_cmd(cmd: &str, args: &[&str], time_limit: Duration) -> Option<CommandOutput> {
-    internal_exec_cmd(&cmd, &args, time_limit)
+pub fn exec_cmd(
+    cmd: &str,
+    args: &[&str],
+    env: &EnvOverlay,
+    current_dir: Option<&Path>,
+    time_limit: Duration,
+    allow_nonzero_exit: bool,
+) -> Option<CommandOutput> {
+    internal_exec_cmd(cmd, args, env, current_dir, time_limit, allow_nonzero_exit)
 }
 
 #[cfg(test)]
-pub fn exec_cmd(cmd: &str, args: &[&str], time_limit: Duration) -> Option<CommandOutput> {
+pub fn exec_cmd(
+    cmd: &str,
+    args: &[&str],
+    env: &EnvOverlay,
+    current_dir: Option<&Path>,
+    time_limit: Duration,
+    allow_nonzero_exit: bool,
+) -> Option<CommandOutput> {
     let command = match args.len() {
         0 => String::from(cmd),
         _ => format!("{} {}", cmd, args.join(" ")),
     };
-    match command.as_str() {
+    let output = match command.as_str() {
         "crystal --version" => Some(CommandOutput {
             stdout: String::from(
                 "\
@@ -57,20 +305,29 @@ LLVM: 10.0.0
 Default target: x86_64-apple-macosx\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "dart --version" => Some(CommandOutput {
             stdout: String::default(),
             stderr: String::from(
                 "Dart VM version: 2.8.4 (stable) (Wed Jun 3 12:26:04 2020 +0200) on \"macos_x64\"",
             ),
+            status: Some(0),
         }),
         "deno -V" => Some(CommandOutput {
             stdout: String::from("deno 1.8.3\n"),
-            stderr: String::default()
+            stderr: String::default(),
+            status: Some(0),
         }),
         "dummy_command" => Some(CommandOutput {
             stdout: String::from("stdout ok!\n"),
             stderr: String::from("stderr ok!\n"),
+            status: Some(0),
+        }),
+        "dummy_failing_command" => Some(CommandOutput {
+            stdout: String::from("stdout ok!\n"),
+            stderr: String::from("stderr failed!\n"),
+            status: Some(1),
         }),
         "elixir --version" => Some(CommandOutput {
             stdout: String::from(
@@ -80,46 +337,57 @@ Erlang/OTP 22 [erts-10.6.4] [source] [64-bit] [smp:8:8] [ds:8:8:10] [async-threa
 Elixir 1.10 (compiled with Erlang/OTP 22)\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "elm --version" => Some(CommandOutput {
             stdout: String::from("0.19.1\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "go version" => Some(CommandOutput {
             stdout: String::from("go version go1.12.1 linux/amd64\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "helm version --short --client" => Some(CommandOutput {
             stdout: String::from("v3.1.1+gafe7058\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         s if s.ends_with("java -Xinternalversion") => Some(CommandOutput {
             stdout: String::from("OpenJDK 64-Bit Server VM (13.0.2+8) for bsd-amd64 JRE (13.0.2+8), built on Feb  6 2020 02:07:52 by \"brew\" with clang 4.2.1 Compatible Apple LLVM 11.0.0 (clang-1100.0.33.17)"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "scalac -version" => Some(CommandOutput {
             stdout: String::from("Scala compiler version 2.13.5 -- Copyright 2002-2020, LAMP/EPFL and Lightbend, Inc."),
             stderr: String::default(),
+            status: Some(0),
         }),
         "julia --version" => Some(CommandOutput {
             stdout: String::from("julia version 1.4.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "kotlin -version" => Some(CommandOutput {
             stdout: String::from("Kotlin version 1.4.21-release-411 (JRE 14.0.1+7)\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "kotlinc -version" => Some(CommandOutput {
             stdout: String::from("info: kotlinc-jvm 1.4.21 (JRE 14.0.1+7)\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "lua -v" => Some(CommandOutput{
             stdout: String::from("Lua 5.4.0  Copyright (C) 1994-2020 Lua.org, PUC-Rio\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "luajit -v" => Some(CommandOutput{
             stdout: String::from("LuaJIT 2.0.5 -- Copyright (C) 2005-2017 Mike Pall. http://luajit.org/\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "nim --version" => Some(CommandOutput {
             stdout: String::from(
@@ -131,49 +399,60 @@ git hash: 7e83adff84be5d0c401a213eccb61e321a3fb1ff
 active boot switches: -d:release\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "node --version" => Some(CommandOutput {
             stdout: String::from("v12.0.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "ocaml -vnum" => Some(CommandOutput {
             stdout: String::from("4.10.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "opam switch show --safe" => Some(CommandOutput {
             stdout: String::from("default\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "esy ocaml -vnum" => Some(CommandOutput {
             stdout: String::from("4.08.1\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "perl -e printf q#%vd#,$^V;" => Some(CommandOutput {
             stdout: String::from("5.26.1"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "php -nr echo PHP_MAJOR_VERSION.\".\".PHP_MINOR_VERSION.\".\".PHP_RELEASE_VERSION;" => {
             Some(CommandOutput {
                 stdout: String::from("7.3.8"),
                 stderr: String::default(),
+                status: Some(0),
             })
         }
         "purs --version" => Some(CommandOutput {
             stdout: String::from("0.13.5\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "pyenv version-name" => Some(CommandOutput {
             stdout: String::from("system\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "python --version" => None,
         "python2 --version" => Some(CommandOutput {
             stdout: String::default(),
             stderr: String::from("Python 2.7.17\n"),
+            status: Some(0),
         }),
         "python3 --version" => Some(CommandOutput {
             stdout: String::from("Python 3.8.0\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "R --version" => Some(CommandOutput {
             stdout: String::default(),
@@ -188,14 +467,17 @@ GNU General Public License versions 2 or 3.
 For more information about these matters see
 https://www.gnu.org/licenses/."#
             ),
+            status: Some(0),
         }),
         "red --version" => Some(CommandOutput {
             stdout: String::from("0.6.4\n"),
-            stderr: String::default()
+            stderr: String::default(),
+            status: Some(0),
         }),
         "ruby -v" => Some(CommandOutput {
             stdout: String::from("ruby 2.5.1p57 (2018-03-29 revision 63029) [x86_64-linux-gnu]\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "swift --version" => Some(CommandOutput {
             stdout: String::from(
@@ -204,18 +486,47 @@ Apple Swift version 5.2.2 (swiftlang-1103.0.32.6 clang-1103.0.32.51)
 Target: x86_64-apple-darwin19.4.0\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "vagrant --version" => Some(CommandOutput {
             stdout: String::from("Vagrant 2.2.10\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "v version" => Some(CommandOutput {
             stdout: String::from("V 0.2 30c0659"),
-            stderr: String::default()
+            stderr: String::default(),
+            status: Some(0),
         }),
         "zig version" => Some(CommandOutput {
             stdout: String::from("0.6.0\n"),
             stderr: String::default(),
+            status: Some(0),
+        }),
+        "cobc -version" => Some(CommandOutput {
+            stdout: String::from(
+                "\
+cobc (GnuCOBOL) 3.1.2.0
+Copyright (C) 2020 Free Software Foundation, Inc.
+License GPLv3+: GNU GPL version 3 or later <https://gnu.org/licenses/gpl.html>\n",
+            ),
+            stderr: String::default(),
+            status: Some(0),
+        }),
+        "fennel --version" => Some(CommandOutput {
+            stdout: String::from("Fennel 1.2.1 on PUC Lua 5.4\n"),
+            stderr: String::default(),
+            status: Some(0),
+        }),
+        "gleam --version" => Some(CommandOutput {
+            stdout: String::from("gleam 0.22.0\n"),
+            stderr: String::default(),
+            status: Some(0),
+        }),
+        "haxe --version" => Some(CommandOutput {
+            stdout: String::from("4.3.1\n"),
+            stderr: String::default(),
+            status: Some(0),
         }),
         "cmake --version" => Some(CommandOutput {
             stdout: String::from(
@@ -225,91 +536,51 @@ cmake version 3.17.3
 CMake suite maintained and supported by Kitware (kitware.com/cmake).\n",
             ),
             stderr: String::default(),
+            status: Some(0),
         }),
         "dotnet --version" => Some(CommandOutput {
             stdout: String::from("3.1.103"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "dotnet --list-sdks" => Some(CommandOutput {
             stdout: String::from("3.1.103 [/usr/share/dotnet/sdk]"),
             stderr: String::default(),
+            status: Some(0),
         }),
         "terraform version" => Some(CommandOutput {
             stdout: String::from("Terraform v0.12.14\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         s if s.starts_with("erl -noshell -eval") => Some(CommandOutput {
             stdout: String::from("22.1.3\n"),
             stderr: String::default(),
+            status: Some(0),
         }),
         // If we don't have a mocked command fall back to executing the command
-        _ => internal_exec_cmd(&cmd, &args, time_limit),
-    }
-}
-
-/// Wraps ANSI color escape sequences in the shell-appropriate wrappers.
-pub fn wrap_colorseq_for_shell(mut ansi: String, shell: Shell) -> String {
-    // Bash might interepret baskslashes, backticks and $
-    // see #658 for more details
-    if shell == Shell::Bash {
-        ansi = ansi.replace('\\', r"\\");
-        ansi = ansi.replace('$', r"\$");
-        ansi = ansi.replace('`', r"\`");
-    }
+        _ => {
+            return internal_exec_cmd(cmd, args, env, current_dir, time_limit, allow_nonzero_exit)
+        }
+    };
 
-    const ESCAPE_BEGIN: char = '\u{1b}';
-    const ESCAPE_END: char = 'm';
-    wrap_seq_for_shell(ansi, shell, ESCAPE_BEGIN, ESCAPE_END)
+    output.filter(|output| allow_nonzero_exit || output.success())
 }
 
-/// Many shells cannot deal with raw unprintable characters and miscompute the cursor position,
-/// leading to strange visual bugs like duplicated/missing chars. This function wraps a specified
-/// sequence in shell-specific escapes to avoid these problems.
-pub fn wrap_seq_for_shell(
-    ansi: String,
-    shell: Shell,
-    escape_begin: char,
-    escape_end: char,
-) -> String {
-    const BASH_BEG: &str = "\u{5c}\u{5b}"; // \[
-    const BASH_END: &str = "\u{5c}\u{5d}"; // \]
-    const ZSH_BEG: &str = "\u{25}\u{7b}"; // %{
-    const ZSH_END: &str = "\u{25}\u{7d}"; // %}
-    const TCSH_BEG: &str = "\u{25}\u{7b}"; // %{
-    const TCSH_END: &str = "\u{25}\u{7d}"; // %}
-
-    // ANSI escape codes cannot be nested, so we can keep track of whether we're
-    // in an escape or not with a single boolean variable
-    let mut escaped = false;
-    let final_string: String = ansi
-        .chars()
-        .map(|x| {
-            if x == escape_begin && !escaped {
-                escaped = true;
-                match shell {
-                    Shell::Bash => format!("{}{}", BASH_BEG, escape_begin),
-                    Shell::Zsh => format!("{}{}", ZSH_BEG, escape_begin),
-                    Shell::Tcsh => format!("{}{}", TCSH_BEG, escape_begin),
-                    _ => x.to_string(),
-                }
-            } else if x == escape_end && escaped {
-                escaped = false;
-                match shell {
-                    Shell::Bash => format!("{}{}", escape_end, BASH_END),
-                    Shell::Zsh => format!("{}{}", escape_end, ZSH_END),
-                    Shell::Tcsh => format!("{}{}", escape_end, TCSH_END),
-                    _ => x.to_string(),
-                }
-            } else {
-                x.to_string()
-            }
-        })
-        .collect();
-    final_string
-}
-
-fn internal_exec_cmd(cmd: &str, args: &[&str], time_limit: Duration) -> Option<CommandOutput> {
-    log::trace!("Executing command {:?} with args {:?}", cmd, args);
+fn internal_exec_cmd(
+    cmd: &str,
+    args: &[&str],
+    env: &EnvOverlay,
+    current_dir: Option<&Path>,
+    time_limit: Duration,
+    allow_nonzero_exit: bool,
+) -> Option<CommandOutput> {
+    log::trace!(
+        "Executing command {:?} with args {:?} and env {:?}",
+        cmd,
+        args,
+        env
+    );
 
     let full_path = match which::which(cmd) {
         Ok(full_path) => {
@@ -324,7 +595,13 @@ fn internal_exec_cmd(cmd: &str, args: &[&str], time_limit: Duration) -> Option<C
 
     let start = Instant::now();
 
-    let process = match Command::new(full_path)
+    let mut command = Command::new(full_path);
+    apply_env(&mut command, env);
+    if let Some(current_dir) = current_dir {
+        command.current_dir(current_dir);
+    }
+
+    let process = match command
         .args(args)
         .stderr(Stdio::piped())
         .stdout(Stdio::piped())
@@ -363,18 +640,23 @@ fn internal_exec_cmd(cmd: &str, args: &[&str], time_limit: Duration) -> Option<C
                 start.elapsed()
             );
 
-            if !output.status.success() {
+            if !output.status.success() && !allow_nonzero_exit {
                 return None;
             }
 
             Some(CommandOutput {
                 stdout: stdout_string,
                 stderr: stderr_string,
+                // Wraps like `std::process::ExitStatus::code` for Windows exit codes above
+                // `i32::MAX`
+                status: output.status.code().map(|code| code as i32),
             })
         }
         Ok(None) => {
-            log::warn!("Executing command {:?} timed out.", cmd);
-            log::warn!("You can set command_timeout in your config to a higher value to allow longer-running commands to keep executing.");
+            StarshipError::CommandTimeout {
+                command: cmd.to_owned(),
+            }
+            .log();
             None
         }
         Err(error) => {
@@ -417,6 +699,104 @@ fn render_time_component((component, suffix): (&u128, &&str)) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::context::Shell;
+    use crate::test::ModuleRenderer;
+
+    #[test]
+    fn retry_read_recovers_from_transient_error() {
+        let mut reads = vec![
+            Err(Error::from(ErrorKind::Interrupted)),
+            Ok(String::from("ref: refs/heads/main\n")),
+        ]
+        .into_iter();
+
+        let result = retry_read(|| reads.next().unwrap(), "HEAD");
+        assert_eq!(result.unwrap(), "ref: refs/heads/main\n");
+        assert!(reads.next().is_none());
+    }
+
+    #[test]
+    fn retry_read_gives_up_on_missing_file() {
+        let mut attempts = 0;
+
+        let result = retry_read(
+            || {
+                attempts += 1;
+                Err(Error::from(ErrorKind::NotFound))
+            },
+            "sequencer/todo",
+        );
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn retry_read_sees_file_swapped_in_between_reads() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let head = dir.path().join("HEAD");
+        let mut attempts = 0;
+
+        // git writes `HEAD.lock` and renames it over `HEAD`, which is missing for a moment
+        let result = retry_read(
+            || {
+                attempts += 1;
+                let contents = read_to_string(&head);
+                if attempts == 1 {
+                    let lock = dir.path().join("HEAD.lock");
+                    fs::write(&lock, "ref: refs/heads/main\n")?;
+                    fs::rename(&lock, &head)?;
+                }
+                contents
+            },
+            &head,
+        );
+        assert_eq!(result?, "ref: refs/heads/main\n");
+        assert_eq!(attempts, 2);
+        dir.close()
+    }
+
+    #[test]
+    fn retry_read_recovers_from_empty_file() {
+        let mut reads = vec![
+            Ok(String::new()),
+            Ok(String::new()),
+            Ok(String::from("3\n")),
+        ]
+        .into_iter();
+
+        let result = retry_read(|| reads.next().unwrap(), "rebase-merge/msgnum");
+        assert_eq!(result.unwrap(), "3\n");
+    }
+
+    #[test]
+    fn retry_read_gives_up_on_empty_file() {
+        let mut attempts = 0;
+
+        let result = retry_read(
+            || {
+                attempts += 1;
+                Ok(String::new())
+            },
+            "HEAD",
+        );
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn retry_read_does_not_retry_other_errors() {
+        let mut attempts = 0;
+
+        let result = retry_read(
+            || {
+                attempts += 1;
+                Err(Error::from(ErrorKind::PermissionDenied))
+            },
+            "HEAD",
+        );
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert_eq!(attempts, 1);
+    }
 
     #[test]
     fn test_500ms() {
@@ -439,12 +819,72 @@ mod tests {
         assert_eq!(render_time(86_400_000_u128, true), "1d")
     }
 
+    #[test]
+    fn write_file_atomic_replaces_contents() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("starship.toml");
+        fs::write(&path, "add_newline = true\n")?;
+
+        write_file_atomic(&path, "add_newline = false\n")?;
+        assert_eq!(read_to_string(&path)?, "add_newline = false\n");
+        assert!(!atomic_tmp_path(&path).exists());
+        dir.close()
+    }
+
+    #[test]
+    fn write_file_atomic_overwrites_partial_write() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("prompt_key.json");
+        // A write interrupted by a crash
+        fs::write(atomic_tmp_path(&path), "{\"prompt\": \"trunc")?;
+
+        write_file_atomic(&path, "{\"prompt\": \"full\"}")?;
+        assert_eq!(read_to_string(&path)?, "{\"prompt\": \"full\"}");
+        assert!(!atomic_tmp_path(&path).exists());
+        dir.close()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_file_atomic_follows_symlinks() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let target = dir.path().join("dotfiles.toml");
+        let link = dir.path().join("starship.toml");
+        fs::write(&target, "")?;
+        std::os::unix::fs::symlink(&target, &link)?;
+
+        write_file_atomic(&link, "format = \"$all\"\n")?;
+        assert!(fs::symlink_metadata(&link)?.file_type().is_symlink());
+        assert_eq!(read_to_string(&target)?, "format = \"$all\"\n");
+        dir.close()
+    }
+
+    #[test]
+    fn wslpath_translates_drive_mounts() {
+        assert_eq!(
+            wslpath(Path::new("/mnt/c/Users/astronaut")),
+            Some(String::from("C:/Users/astronaut"))
+        );
+        assert_eq!(wslpath(Path::new("/mnt/d")), Some(String::from("D:/")));
+        assert_eq!(wslpath(Path::new("/mnt/wsl/shared")), None);
+        assert_eq!(wslpath(Path::new("/mnt")), None);
+        assert_eq!(wslpath(Path::new("/home/astronaut")), None);
+    }
+
     #[test]
     fn exec_mocked_command() {
-        let result = exec_cmd("dummy_command", &[], Duration::from_millis(500));
+        let result = exec_cmd(
+            "dummy_command",
+            &[],
+            &[],
+            None,
+            Duration::from_millis(500),
+            false,
+        );
         let expected = Some(CommandOutput {
             stdout: String::from("stdout ok!\n"),
             stderr: String::from("stderr ok!\n"),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -456,10 +896,31 @@ mod tests {
     #[test]
     #[cfg(not(windows))]
     fn exec_no_output() {
-        let result = internal_exec_cmd("true", &[], Duration::from_millis(500));
+        let result = internal_exec_cmd("true", &[], &[], None, Duration::from_millis(500), false);
         let expected = Some(CommandOutput {
             stdout: String::from(""),
             stderr: String::from(""),
+            status: Some(0),
+        });
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn exec_with_env_overlay() {
+        let result = internal_exec_cmd(
+            "/bin/sh",
+            &["-c", "echo \"$STARSHIP_TEST_VAR ${HOME:-unset}\""],
+            &[("STARSHIP_TEST_VAR", "set"), ("HOME", "")],
+            None,
+            Duration::from_millis(500),
+            false,
+        );
+        let expected = Some(CommandOutput {
+            stdout: String::from("set unset\n"),
+            stderr: String::from(""),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -468,11 +929,18 @@ mod tests {
     #[test]
     #[cfg(not(windows))]
     fn exec_with_output_stdout() {
-        let result =
-            internal_exec_cmd("/bin/sh", &["-c", "echo hello"], Duration::from_millis(500));
+        let result = internal_exec_cmd(
+            "/bin/sh",
+            &["-c", "echo hello"],
+            &[],
+            None,
+            Duration::from_millis(500),
+            false,
+        );
         let expected = Some(CommandOutput {
             stdout: String::from("hello\n"),
             stderr: String::from(""),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -484,11 +952,15 @@ mod tests {
         let result = internal_exec_cmd(
             "/bin/sh",
             &["-c", "echo hello >&2"],
+            &[],
+            None,
             Duration::from_millis(500),
+            false,
         );
         let expected = Some(CommandOutput {
             stdout: String::from(""),
             stderr: String::from("hello\n"),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -500,11 +972,15 @@ mod tests {
         let result = internal_exec_cmd(
             "/bin/sh",
             &["-c", "echo hello; echo world >&2"],
+            &[],
+            None,
             Duration::from_millis(500),
+            false,
         );
         let expected = Some(CommandOutput {
             stdout: String::from("hello\n"),
             stderr: String::from("world\n"),
+            status: Some(0),
         });
 
         assert_eq!(result, expected)
@@ -513,89 +989,311 @@ mod tests {
     #[test]
     #[cfg(not(windows))]
     fn exec_with_non_zero_exit_code() {
-        let result = internal_exec_cmd("false", &[], Duration::from_millis(500));
+        let result = internal_exec_cmd("false", &[], &[], None, Duration::from_millis(500), false);
         let expected = None;
 
         assert_eq!(result, expected)
     }
 
+    #[test]
+    #[cfg(not(windows))]
+    fn exec_with_non_zero_exit_code_allowed() {
+        let result = internal_exec_cmd(
+            "/bin/sh",
+            &["-c", "echo version 1.2.3; echo usage >&2; exit 3"],
+            &[],
+            None,
+            Duration::from_millis(500),
+            true,
+        );
+        let expected = Some(CommandOutput {
+            stdout: String::from("version 1.2.3\n"),
+            stderr: String::from("usage\n"),
+            status: Some(3),
+        });
+
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn exec_mocked_command_with_non_zero_exit_code() {
+        let result = exec_cmd(
+            "dummy_failing_command",
+            &[],
+            &[],
+            None,
+            Duration::from_millis(500),
+            false,
+        );
+        assert_eq!(result, None);
+
+        let result = exec_cmd(
+            "dummy_failing_command",
+            &[],
+            &[],
+            None,
+            Duration::from_millis(500),
+            true,
+        );
+        let expected = Some(CommandOutput {
+            stdout: String::from("stdout ok!\n"),
+            stderr: String::from("stderr failed!\n"),
+            status: Some(1),
+        });
+        assert_eq!(result, expected)
+    }
+
     #[test]
     #[cfg(not(windows))]
     fn exec_slow_command() {
-        let result = internal_exec_cmd("sleep", &["500"], Duration::from_millis(500));
+        let result = internal_exec_cmd(
+            "sleep",
+            &["500"],
+            &[],
+            None,
+            Duration::from_millis(500),
+            false,
+        );
         let expected = None;
 
         assert_eq!(result, expected)
     }
 
     #[test]
-    fn test_color_sequence_wrappers() {
-        let test0 = "\x1b2mhellomynamekeyes\x1b2m"; // BEGIN: \x1b     END: m
-        let test1 = "\x1b]330;mlol\x1b]0m"; // BEGIN: \x1b     END: m
-        let test2 = "\u{1b}J"; // BEGIN: \x1b     END: J
-        let test3 = "OH NO"; // BEGIN: O    END: O
-        let test4 = "herpaderp";
-        let test5 = "";
+    #[cfg(not(windows))]
+    fn exec_slow_command_is_killed_at_time_limit() {
+        let timeouts = crate::logger::error_count(crate::error::ErrorCategory::Command);
+        let start = Instant::now();
+        let result = internal_exec_cmd(
+            "sleep",
+            &["30"],
+            &[],
+            None,
+            Duration::from_millis(100),
+            false,
+        );
 
-        let zresult0 = wrap_seq_for_shell(test0.to_string(), Shell::Zsh, '\x1b', 'm');
-        let zresult1 = wrap_seq_for_shell(test1.to_string(), Shell::Zsh, '\x1b', 'm');
-        let zresult2 = wrap_seq_for_shell(test2.to_string(), Shell::Zsh, '\x1b', 'J');
-        let zresult3 = wrap_seq_for_shell(test3.to_string(), Shell::Zsh, 'O', 'O');
-        let zresult4 = wrap_seq_for_shell(test4.to_string(), Shell::Zsh, '\x1b', 'm');
-        let zresult5 = wrap_seq_for_shell(test5.to_string(), Shell::Zsh, '\x1b', 'm');
+        assert_eq!(result, None);
+        assert!(start.elapsed() < Duration::from_secs(10));
+        // Counted for `--strict=command`
+        assert!(crate::logger::error_count(crate::error::ErrorCategory::Command) > timeouts);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn exec_fast_command_within_time_limit() {
+        let result = internal_exec_cmd(
+            "sleep",
+            &["0"],
+            &[],
+            None,
+            Duration::from_millis(500),
+            false,
+        );
+        let expected = Some(CommandOutput {
+            stdout: String::default(),
+            stderr: String::default(),
+            status: Some(0),
+        });
 
-        assert_eq!(&zresult0, "%{\x1b2m%}hellomynamekeyes%{\x1b2m%}");
-        assert_eq!(&zresult1, "%{\x1b]330;m%}lol%{\x1b]0m%}");
-        assert_eq!(&zresult2, "%{\x1bJ%}");
-        assert_eq!(&zresult3, "%{OH NO%}");
-        assert_eq!(&zresult4, "herpaderp");
-        assert_eq!(&zresult5, "");
+        assert_eq!(result, expected)
+    }
 
-        let bresult0 = wrap_seq_for_shell(test0.to_string(), Shell::Bash, '\x1b', 'm');
-        let bresult1 = wrap_seq_for_shell(test1.to_string(), Shell::Bash, '\x1b', 'm');
-        let bresult2 = wrap_seq_for_shell(test2.to_string(), Shell::Bash, '\x1b', 'J');
-        let bresult3 = wrap_seq_for_shell(test3.to_string(), Shell::Bash, 'O', 'O');
-        let bresult4 = wrap_seq_for_shell(test4.to_string(), Shell::Bash, '\x1b', 'm');
-        let bresult5 = wrap_seq_for_shell(test5.to_string(), Shell::Bash, '\x1b', 'm');
+    #[test]
+    fn read_file_strips_bom() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("file");
+        std::fs::write(&path, "\u{feff}contents\r\n")?;
+        assert_eq!(read_file(&path)?, "contents\r\n");
+        dir.close()
+    }
 
-        assert_eq!(&bresult0, "\\[\x1b2m\\]hellomynamekeyes\\[\x1b2m\\]");
-        assert_eq!(&bresult1, "\\[\x1b]330;m\\]lol\\[\x1b]0m\\]");
-        assert_eq!(&bresult2, "\\[\x1bJ\\]");
-        assert_eq!(&bresult3, "\\[OH NO\\]");
-        assert_eq!(&bresult4, "herpaderp");
-        assert_eq!(&bresult5, "");
+    /// Encodes `contents` like editors on Windows may save them, with a byte order mark and
+    /// CRLF line endings
+    fn windows_encoded(contents: &str) -> String {
+        format!("\u{feff}{}", contents.replace('\n', "\r\n"))
     }
 
+    /// Renders `module` in a directory with `files`, encoded with `encode`. `{dir}` in the
+    /// values of `env` is replaced with the directory.
+    fn render_with_files(
+        module: &str,
+        files: &[(&str, &str)],
+        env: &[(&'static str, &str)],
+        config: &toml::Value,
+        encode: fn(&str) -> String,
+    ) -> Result<Option<String>> {
+        let dir = tempfile::tempdir()?;
+        for (path, contents) in files {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, encode(contents))?;
+        }
+
+        let mut renderer = ModuleRenderer::new(module)
+            .path(dir.path())
+            .config(config.clone());
+        for (key, value) in env {
+            let value = value.replace("{dir}", &dir.path().to_string_lossy());
+            renderer = renderer.env(key, value);
+        }
+        let actual = renderer.collect();
+        dir.close()?;
+        Ok(actual)
+    }
+
+    /// A module, the files and environment it renders with, and its config
+    type Fixture<'a> = (
+        &'a str,
+        Vec<(&'a str, &'a str)>,
+        Vec<(&'static str, &'a str)>,
+        toml::Value,
+    );
+
     #[test]
-    fn test_bash_escape() {
-        let test = "$(echo a)";
-        assert_eq!(
-            wrap_colorseq_for_shell(test.to_owned(), Shell::Bash),
-            r"\$(echo a)"
-        );
+    fn files_with_bom_and_crlf_render_the_same() -> Result<()> {
+        let kubeconfig = "apiVersion: v1\ncurrent-context: test_context\ncontexts:\n  - \
+            context:\n      namespace: test_namespace\n    name: test_context\n";
+        let cases: Vec<Fixture> = vec![
+            (
+                "package",
+                vec![(
+                    "Cargo.toml",
+                    "[package]\nname = \"test\"\nversion = \"1.2.3\"\n",
+                )],
+                vec![],
+                toml::toml! { [package] disabled = false },
+            ),
+            (
+                "package",
+                vec![(
+                    "package.json",
+                    "{\n  \"name\": \"test\",\n  \"version\": \"1.2.3\"\n}\n",
+                )],
+                vec![],
+                toml::toml! { [package] disabled = false },
+            ),
+            (
+                "nodejs",
+                vec![
+                    ("index.js", ""),
+                    (".tool-versions", "# runtimes\nnodejs 18.16.0\n"),
+                ],
+                vec![],
+                toml::toml! { [nodejs] disabled = false },
+            ),
+            (
+                "terraform",
+                vec![("main.tf", ""), (".terraform/environment", "development\n")],
+                vec![],
+                toml::toml! {
+                    [terraform]
+                    format = "$workspace"
+                },
+            ),
+            (
+                "kubernetes",
+                vec![("kubeconfig", kubeconfig)],
+                vec![("KUBECONFIG", "{dir}/kubeconfig")],
+                toml::toml! { [kubernetes] disabled = false },
+            ),
+            (
+                "gcloud",
+                vec![
+                    ("active_config", "default\n"),
+                    (
+                        "configurations/config_default",
+                        "[core]\naccount = foo@example.com\n",
+                    ),
+                ],
+                vec![("CLOUDSDK_CONFIG", "{dir}")],
+                toml::toml! { [gcloud] disabled = false },
+            ),
+            (
+                "hg_branch",
+                vec![
+                    (".hg/branch", "feature\n"),
+                    (".hg/bookmarks.current", "topic\n"),
+                ],
+                vec![],
+                toml::toml! { [hg_branch] disabled = false },
+            ),
+        ];
+
+        for (module, files, env, config) in cases {
+            let clean = render_with_files(module, &files, &env, &config, str::to_owned)?;
+            let windows = render_with_files(module, &files, &env, &config, windows_encoded)?;
+            assert!(clean.is_some(), "{} rendered nothing", module);
+            assert_eq!(clean, windows, "{} with {:?}", module, files);
+        }
+        Ok(())
+    }
+
+    fn expand(path: &str) -> PathBuf {
+        let env = |name: &str| match name {
+            "PROJECTS" => Some("/srv/projects".to_owned()),
+            "NAME" => Some("starship".to_owned()),
+            _ => None,
+        };
+        expand_path_with(path, Some(PathBuf::from("/home/test")), env)
+    }
+
+    #[test]
+    fn expand_path_home() {
+        assert_eq!(expand("~"), PathBuf::from("/home/test"));
+        assert_eq!(expand("~/code"), PathBuf::from("/home/test/code"));
+        assert_eq!(expand("/code/~"), PathBuf::from("/code/~"));
+        assert_eq!(expand("code"), PathBuf::from("code"));
         assert_eq!(
-            wrap_colorseq_for_shell(test.to_owned(), Shell::PowerShell),
-            test
+            expand_path_with("~/code", None, |_| None),
+            PathBuf::from("~/code")
         );
+    }
 
-        let test = r"\$(echo a)";
+    #[test]
+    #[cfg(not(windows))]
+    fn expand_path_user_home() {
+        let root_home = nix::unistd::User::from_name("root")
+            .unwrap()
+            .map(|user| user.dir);
+        if let Some(root_home) = root_home {
+            assert_eq!(expand("~root"), root_home);
+            assert_eq!(expand("~root/code"), root_home.join("code"));
+        }
         assert_eq!(
-            wrap_colorseq_for_shell(test.to_owned(), Shell::Bash),
-            r"\\\$(echo a)"
+            expand("~no_such_starship_user/code"),
+            PathBuf::from("~no_such_starship_user/code")
         );
+    }
+
+    #[test]
+    fn expand_path_env_vars() {
+        assert_eq!(expand("${PROJECTS}/a"), PathBuf::from("/srv/projects/a"));
+        assert_eq!(expand("%PROJECTS%/a"), PathBuf::from("/srv/projects/a"));
         assert_eq!(
-            wrap_colorseq_for_shell(test.to_owned(), Shell::PowerShell),
-            test
+            expand("~/${NAME}/%NAME%"),
+            PathBuf::from("/home/test/starship/starship")
         );
+        // Unset variables expand to nothing
+        assert_eq!(expand("/a/${UNSET}/b"), PathBuf::from("/a//b"));
+        assert_eq!(expand("/a/%UNSET%b"), PathBuf::from("/a/b"));
+        // Only the braced and percent forms are variables
+        assert_eq!(expand("/a/$NAME/100%"), PathBuf::from("/a/$NAME/100%"));
+    }
 
-        let test = r"`echo a`";
-        assert_eq!(
-            wrap_colorseq_for_shell(test.to_owned(), Shell::Bash),
-            r"\`echo a\`"
+    #[test]
+    fn expand_path_with_context() {
+        let mut context = Context::new_with_shell_and_path(
+            Default::default(),
+            Shell::Unknown,
+            PathBuf::new(),
+            PathBuf::new(),
         );
+        context.env.insert("HOME", "/home/context".to_owned());
+        context.env.insert("PROJECTS", "/srv/projects".to_owned());
         assert_eq!(
-            wrap_colorseq_for_shell(test.to_owned(), Shell::PowerShell),
-            test
+            expand_path("~/${PROJECTS}", &context),
+            PathBuf::from("/home/context/srv/projects")
         );
     }
 }
@@ -0,0 +1,413 @@
+use clap::ArgMatches;
+use notify::{DebouncedEvent, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::cache;
+use crate::context::Context;
+use crate::print;
+use crate::repaint::{self, Line};
+
+/// How long to wait for more changes before rendering the prompt again
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(100);
+/// How often the paths are checked for changes when polling
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How often to check whether starship was interrupted while waiting for changes
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Clears the screen and moves the cursor to its top left corner
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// How the renders of `prompt --watch` are told apart
+pub enum Separator {
+    /// Written after each render
+    Delimiter(String),
+    /// The screen is cleared before the first render, and the lines of the prompt that
+    /// changed are repainted in place after that
+    Clear,
+}
+
+pub struct WatchOptions {
+    pub separator: Separator,
+    pub use_cache: bool,
+    /// Check the paths for changes periodically instead of using filesystem events
+    pub poll: bool,
+}
+
+/// The watcher of the paths, which stops watching when dropped
+#[allow(dead_code)]
+enum PathWatcher {
+    Events(RecommendedWatcher),
+    Polling(PollWatcher),
+}
+
+/// Prints the prompt and prints it again whenever it changes, until interrupted
+pub fn watch(args: ArgMatches<'static>) {
+    let separator = if args.is_present("clear") {
+        Separator::Clear
+    } else {
+        Separator::Delimiter(args.value_of("delimiter").unwrap_or("\n").to_owned())
+    };
+    let options = WatchOptions {
+        separator,
+        use_cache: !args.is_present("no_cache"),
+        poll: args.is_present("poll"),
+    };
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler_interrupted = Arc::clone(&interrupted);
+    if let Err(error) =
+        ctrlc::set_handler(move || handler_interrupted.store(true, Ordering::SeqCst))
+    {
+        log::warn!("Unable to handle interrupts: {}", error);
+    }
+
+    let stdout = io::stdout();
+    let result = watch_prompt(
+        || Context::new(args.clone()),
+        &options,
+        stdout.lock(),
+        &interrupted,
+    );
+    if let Err(error) = result {
+        // The reading end of the pipe went away, e.g. the status bar exited
+        log::debug!("Stopped watching the prompt: {}", error);
+    }
+}
+
+/// Renders the prompt for a new context from `new_context` whenever the directory or the git
+/// repository it's in changes, writing the renders that differ from the previous one to `out`.
+/// Returns once `interrupted` is set.
+pub fn watch_prompt<F, W>(
+    mut new_context: F,
+    options: &WatchOptions,
+    mut out: W,
+    interrupted: &AtomicBool,
+) -> io::Result<()>
+where
+    F: FnMut() -> Context<'static>,
+    W: Write,
+{
+    let context = new_context();
+    let (sender, receiver) = mpsc::channel();
+    let _watcher = start_watcher(watched_paths(&context), sender, options.poll);
+
+    let mut context = Some(context);
+    let mut last_prompt = None;
+    let mut painted: Option<Vec<Line>> = None;
+    loop {
+        let context = context.take().unwrap_or_else(&mut new_context);
+        match &options.separator {
+            Separator::Delimiter(delimiter) => {
+                let prompt = print::get_cached_prompt(context, options.use_cache);
+                if last_prompt.as_ref() != Some(&prompt) {
+                    write!(out, "{}{}", prompt, delimiter)?;
+                    last_prompt = Some(prompt);
+                }
+            }
+            Separator::Clear => {
+                let lines = repaint::lines(&print::get_prompt_segments(context));
+                match &painted {
+                    Some(painted) if *painted == lines => (),
+                    Some(painted) => write!(out, "{}", repaint::repaint(painted, &lines))?,
+                    None => {
+                        let first_paint = repaint::repaint(&[Line::new()], &lines);
+                        write!(out, "{}{}", CLEAR_SCREEN, first_paint)?
+                    }
+                }
+                painted = Some(lines);
+            }
+        }
+        out.flush()?;
+
+        if !wait_for_change(&receiver, interrupted) {
+            return Ok(());
+        }
+    }
+}
+
+/// The paths whose changes can change the prompt: the directory itself, and the files and
+/// refs of its git repository
+fn watched_paths(context: &Context) -> Vec<(PathBuf, RecursiveMode)> {
+    let mut paths = vec![(context.current_dir.clone(), RecursiveMode::NonRecursive)];
+    if let Some(git_dir) = cache::find_git_dir(&context.current_dir) {
+        let refs = git_dir.join("refs");
+        if refs.is_dir() {
+            paths.push((refs, RecursiveMode::Recursive));
+        }
+        paths.push((git_dir, RecursiveMode::NonRecursive));
+    }
+    paths
+}
+
+/// Watches `paths` with filesystem events, falling back to polling them if that fails or if
+/// `poll` is set
+fn start_watcher(
+    paths: Vec<(PathBuf, RecursiveMode)>,
+    sender: Sender<DebouncedEvent>,
+    poll: bool,
+) -> Option<PathWatcher> {
+    if !poll {
+        match RecommendedWatcher::new(sender.clone(), DEBOUNCE_DELAY)
+            .and_then(|watcher| watch_paths(watcher, &paths))
+        {
+            Ok(watcher) => return Some(PathWatcher::Events(watcher)),
+            Err(error) => log::debug!("Unable to watch for changes, polling instead: {}", error),
+        }
+    }
+
+    match PollWatcher::new(sender, POLL_INTERVAL).and_then(|watcher| watch_paths(watcher, &paths)) {
+        Ok(watcher) => Some(PathWatcher::Polling(watcher)),
+        Err(error) => {
+            log::warn!("Unable to watch for changes: {}", error);
+            None
+        }
+    }
+}
+
+fn watch_paths<T: Watcher>(
+    mut watcher: T,
+    paths: &[(PathBuf, RecursiveMode)],
+) -> notify::Result<T> {
+    for (path, mode) in paths {
+        watcher.watch(path, *mode)?;
+    }
+    Ok(watcher)
+}
+
+/// Waits until one of the watched paths changed, returning `false` if starship was
+/// interrupted instead
+fn wait_for_change(receiver: &Receiver<DebouncedEvent>, interrupted: &AtomicBool) -> bool {
+    while !interrupted.load(Ordering::SeqCst) {
+        match receiver.recv_timeout(INTERRUPT_CHECK_INTERVAL) {
+            Ok(event) if is_change(&event) => {
+                // Render once for all the changes that happened in the meantime
+                while receiver.try_recv().is_ok() {}
+                return true;
+            }
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+            // Nothing is watched, so only an interrupt can end the wait
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(INTERRUPT_CHECK_INTERVAL),
+        }
+    }
+    false
+}
+
+fn is_change(event: &DebouncedEvent) -> bool {
+    !matches!(
+        event,
+        DebouncedEvent::NoticeWrite(_)
+            | DebouncedEvent::NoticeRemove(_)
+            | DebouncedEvent::Error(..)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StarshipConfig;
+    use crate::context::Shell;
+    use crate::test::{fixture_repo, FixtureProvider};
+    use std::fs::{self, File};
+    use std::path::Path;
+    use std::process::Command;
+    use std::time::Instant;
+
+    /// Writes into a channel, standing in for the pipe `prompt --watch` prints to
+    struct Pipe(Sender<Vec<u8>>);
+
+    impl Write for Pipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let _ = self.0.send(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A running `prompt --watch`, stopped when dropped
+    struct Watching {
+        renders: Receiver<Vec<u8>>,
+        output: String,
+        interrupted: Arc<AtomicBool>,
+        thread: Option<thread::JoinHandle<io::Result<()>>>,
+    }
+
+    impl Watching {
+        fn start(path: &Path, separator: Separator) -> Self {
+            let config = toml::toml! {
+                format = "${custom.marker}$git_commit"
+                add_newline = false
+
+                [custom.marker]
+                files = ["marker"]
+                format = "marker "
+
+                [git_commit]
+                only_detached = false
+                format = "$hash"
+            };
+            let path = path.to_owned();
+            let options = WatchOptions {
+                separator,
+                use_cache: true,
+                poll: true,
+            };
+
+            let (sender, renders) = mpsc::channel();
+            let interrupted = Arc::new(AtomicBool::new(false));
+            let thread_interrupted = Arc::clone(&interrupted);
+            let thread = thread::spawn(move || {
+                let new_context = || {
+                    let mut context = Context::new_with_shell_and_path(
+                        ArgMatches::default(),
+                        Shell::Unknown,
+                        path.clone(),
+                        path.clone(),
+                    );
+                    context.config = StarshipConfig {
+                        config: Some(config.clone()),
+                    };
+                    context
+                };
+                watch_prompt(new_context, &options, Pipe(sender), &thread_interrupted)
+            });
+
+            Self {
+                renders,
+                output: String::new(),
+                interrupted,
+                thread: Some(thread),
+            }
+        }
+
+        /// Waits until the output printed so far satisfies `done`
+        fn wait_for(&mut self, done: impl Fn(&str) -> bool) -> &str {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while !done(&self.output) {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match self.renders.recv_timeout(timeout) {
+                    Ok(bytes) => self.output.push_str(&String::from_utf8_lossy(&bytes)),
+                    Err(_) => panic!("Timed out waiting for a render, got {:?}", self.output),
+                }
+            }
+            &self.output
+        }
+
+        fn stop(mut self) -> io::Result<()> {
+            self.interrupted.store(true, Ordering::SeqCst);
+            self.thread.take().unwrap().join().unwrap()
+        }
+    }
+
+    impl Drop for Watching {
+        fn drop(&mut self) {
+            self.interrupted.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn head_hash(repo_dir: &Path) -> io::Result<String> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--short=7", "HEAD"])
+            .current_dir(repo_dir)
+            .output()?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    #[test]
+    fn renders_again_on_new_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut watching = Watching::start(dir.path(), Separator::Delimiter("\n".to_owned()));
+        watching.wait_for(|output| output == "\n");
+
+        File::create(dir.path().join("marker"))?.sync_all()?;
+        assert_eq!(
+            watching.wait_for(|output| output.matches('\n').count() == 2),
+            "\nmarker \n"
+        );
+
+        watching.stop()?;
+        dir.close()
+    }
+
+    #[test]
+    fn renders_again_on_commit() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let first_hash = head_hash(repo_dir.path())?;
+        let mut watching = Watching::start(repo_dir.path(), Separator::Delimiter("---".to_owned()));
+        watching.wait_for(|output| output.ends_with("---"));
+
+        // Polling only notices modification times that differ by a second or more
+        thread::sleep(Duration::from_millis(1100));
+        Command::new("git")
+            .args([
+                "commit",
+                "--allow-empty",
+                "-m",
+                "Empty commit",
+                "--no-gpg-sign",
+            ])
+            .current_dir(repo_dir.path())
+            .output()?;
+        let second_hash = head_hash(repo_dir.path())?;
+        assert_ne!(first_hash, second_hash);
+
+        let output = watching.wait_for(|output| output.matches("---").count() == 2);
+        assert_eq!(output, format!("{}---{}---", first_hash, second_hash));
+
+        watching.stop()?;
+        repo_dir.close()
+    }
+
+    #[test]
+    fn unchanged_prompt_not_printed_again() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut watching = Watching::start(dir.path(), Separator::Clear);
+        watching.wait_for(|output| output == CLEAR_SCREEN);
+
+        // Changes the directory, but not the prompt
+        File::create(dir.path().join("unrelated"))?.sync_all()?;
+        thread::sleep(POLL_INTERVAL * 3);
+        File::create(dir.path().join("marker"))?.sync_all()?;
+
+        let output = watching.wait_for(|output| output.contains("marker"));
+        assert_eq!(output, format!("{}marker ", CLEAR_SCREEN));
+
+        watching.stop()?;
+        dir.close()
+    }
+
+    #[test]
+    fn repaints_in_place() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("marker"))?.sync_all()?;
+        let mut watching = Watching::start(dir.path(), Separator::Clear);
+        watching.wait_for(|output| output.ends_with("marker "));
+
+        fs::remove_file(dir.path().join("marker"))?;
+        let output = watching.wait_for(|output| output.ends_with('K'));
+        assert_eq!(output, format!("{}marker \r\x1b[K", CLEAR_SCREEN));
+
+        watching.stop()?;
+        dir.close()
+    }
+
+    #[test]
+    fn stops_when_interrupted() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut watching = Watching::start(dir.path(), Separator::Clear);
+        watching.wait_for(|output| !output.is_empty());
+
+        let start = Instant::now();
+        watching.stop()?;
+        assert!(start.elapsed() < Duration::from_secs(1));
+        dir.close()
+    }
+}
@@ -14,14 +14,20 @@ fn impl_module_config(dinput: DeriveInput) -> proc_macro::TokenStream {
 
     let mut from_config = quote! {};
     let mut load_config = quote! {};
+    let mut config_keys = quote! {};
+    let mut config_docs = quote! {};
 
     if let syn::Data::Struct(data) = dinput.data {
         if let syn::Fields::Named(fields_named) = data.fields {
             let mut load_tokens = quote! {};
             let mut fields = quote! {};
+            let mut field_keys_tokens = quote! {};
+            let mut docs_tokens = quote! {};
+            let mut field_docs_tokens = quote! {};
 
             for field in fields_named.named.iter() {
                 let ident = field.ident.as_ref().unwrap();
+                let ty = &field.ty;
 
                 let new_load_tokens = quote! {
                     stringify!(#ident) => self.#ident.load_config(v),
@@ -40,37 +46,82 @@ fn impl_module_config(dinput: DeriveInput) -> proc_macro::TokenStream {
                     #fields
                     #new_field
                 };
+
+                field_keys_tokens = quote! {
+                    #field_keys_tokens
+                    stringify!(#ident) => Some(<#ty as ModuleConfig<'a>>::config_keys()),
+                };
+
+                let description = doc_summary(&field.attrs);
+                docs_tokens = quote! {
+                    #docs_tokens
+                    crate::config::ConfigKeyDoc {
+                        key: stringify!(#ident),
+                        rust_type: stringify!(#ty),
+                        description: #description,
+                    },
+                };
+
+                field_docs_tokens = quote! {
+                    #field_docs_tokens
+                    stringify!(#ident) => Some(<#ty as ModuleConfig<'a>>::config_docs()),
+                };
             }
 
+            // The config of a module, which can always be disabled, also accepts the options
+            // `Module` reads itself, like the `env` of its commands. The tables nested in it
+            // don't.
+            let is_module = fields_named
+                .named
+                .iter()
+                .any(|field| matches!(&field.ident, Some(ident) if ident == "disabled"));
+            let common_tokens = if is_module {
+                quote! {
+                    common if crate::module::COMMON_OPTIONS.contains(&common) => (),
+                }
+            } else {
+                quote! {}
+            };
+
             load_config = quote! {
                 fn load_config(&mut self, config: &'a toml::Value) {
                     if let toml::Value::Table(config) = config {
                         config.iter().for_each(|(k, v)| {
                             match k.as_str() {
                                 #load_tokens
+                                #common_tokens
                                 unknown => {
-                                    ::log::warn!("Unknown config key '{}'", unknown);
-
-                                    let did_you_mean = ::std::array::IntoIter::new([#fields])
-                                    .filter_map(|field| {
-                                        let score = ::strsim::jaro_winkler(unknown, field);
-                                        (score > 0.8).then(|| (score, field))
-                                    })
-                                    .max_by(
-                                        |(score_a, _field_a), (score_b, _field_b)| {
-                                            score_a.partial_cmp(score_b).unwrap_or(::std::cmp::Ordering::Equal)
-                                        },
-                                    );
-
-                                    if let Some((_score, field)) = did_you_mean {
-                                        ::log::warn!("Did you mean '{}'?", field);
-                                    }
+                                    crate::config::warn_unknown_key(unknown, &[#fields]);
                                 },
                             }
                         });
                     }
                 }
             };
+            config_keys = quote! {
+                fn config_keys() -> &'static [&'static str] {
+                    &[#fields]
+                }
+
+                fn field_config_keys(key: &str) -> Option<&'static [&'static str]> {
+                    match key {
+                        #field_keys_tokens
+                        _ => None,
+                    }
+                }
+            };
+            config_docs = quote! {
+                fn config_docs() -> &'static [crate::config::ConfigKeyDoc] {
+                    &[#docs_tokens]
+                }
+
+                fn field_config_docs(key: &str) -> Option<&'static [crate::config::ConfigKeyDoc]> {
+                    match key {
+                        #field_docs_tokens
+                        _ => None,
+                    }
+                }
+            };
             from_config = quote! {
                 fn from_config(config: &'a toml::Value) -> Option<Self> {
                     let mut out = Self::default();
@@ -85,6 +136,33 @@ fn impl_module_config(dinput: DeriveInput) -> proc_macro::TokenStream {
         impl<'a> ModuleConfig<'a> for #struct_ident #ty_generics #where_clause {
             #from_config
             #load_config
+            #config_keys
+            #config_docs
         }
     })
 }
+
+/// The first paragraph of the doc comment of a field, joined into one line
+fn doc_summary(attrs: &[syn::Attribute]) -> String {
+    let lines = attrs.iter().filter_map(|attr| match attr.parse_meta() {
+        Ok(syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            lit: syn::Lit::Str(doc),
+            ..
+        })) if path.is_ident("doc") => Some(doc.value()),
+        _ => None,
+    });
+
+    let mut summary = Vec::new();
+    for line in lines {
+        let line = line.trim().to_owned();
+        if line.is_empty() {
+            if summary.is_empty() {
+                continue;
+            }
+            break;
+        }
+        summary.push(line);
+    }
+    summary.join(" ")
+}